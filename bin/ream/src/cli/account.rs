@@ -0,0 +1,306 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::Address;
+use clap::{Parser, Subcommand};
+use ream_consensus::constants::MAX_EFFECTIVE_BALANCE;
+use ream_validator::{
+    deposit::{
+        bls_withdrawal_credentials, check_deposit_amount, eth1_withdrawal_credentials,
+        sign_deposit, LaunchpadDeposit,
+    },
+    error::ValidatorError,
+    key_derivation::{derive_path, mnemonic_to_seed, signing_key_path, withdrawal_key_path},
+    keystore::{KdfParams, Keystore},
+};
+use serde::Serialize;
+
+use crate::config::{
+    network::{NetworkSpec, NetworkSpecError},
+    Network,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccountError {
+    #[error("{path}: {error}")]
+    Io { path: PathBuf, error: io::Error },
+    #[error(transparent)]
+    Validator(#[from] ValidatorError),
+    #[error(transparent)]
+    Network(#[from] NetworkSpecError),
+    #[error("{0} already exists, refusing to overwrite it")]
+    Exists(PathBuf),
+    #[error("--first-index {first_index} plus --count {count} is past the last validator index")]
+    IndexOverflow { first_index: u32, count: u32 },
+    #[error("failed to write: {0}")]
+    Output(#[from] io::Error),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccountSubcommand {
+    /// Derive validator keys from a mnemonic, writing their keystores and deposit data
+    New(NewAccountCommand),
+}
+
+#[derive(Debug, Parser)]
+pub struct NewAccountCommand {
+    /// Network the deposits are made on
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Directory of the chain config of a custom network
+    #[arg(long, value_name = "DIR")]
+    pub custom_config_dir: Option<PathBuf>,
+
+    /// File holding the BIP-39 mnemonic, read from standard input if not set
+    #[arg(long, value_name = "PATH")]
+    pub mnemonic_file: Option<PathBuf>,
+
+    /// File with the password the keystores are encrypted with
+    #[arg(long, value_name = "PATH")]
+    pub keystore_password_file: PathBuf,
+
+    /// Number of validators to create
+    #[arg(long, default_value_t = 1)]
+    pub count: u32,
+
+    /// EIP-2334 index of the first validator, to add validators to those already created
+    #[arg(long, default_value_t = 0, value_name = "INDEX")]
+    pub first_index: u32,
+
+    /// Address the validators withdraw to, their withdrawal key is used otherwise
+    #[arg(long, value_name = "ADDRESS")]
+    pub withdrawal_address: Option<Address>,
+
+    /// Gwei deposited for each validator
+    #[arg(long, default_value_t = MAX_EFFECTIVE_BALANCE, value_name = "GWEI")]
+    pub amount: u64,
+
+    /// Directory the keystores and deposit data are written to
+    #[arg(long, default_value = "validator_keys", value_name = "DIR")]
+    pub output_dir: PathBuf,
+}
+
+impl NewAccountCommand {
+    /// Creates the accounts with the mnemonic of `--mnemonic-file` or else of `input`,
+    /// reporting the written files to `out`.
+    pub fn execute(&self, input: impl BufRead, out: &mut impl Write) -> Result<(), AccountError> {
+        let mnemonic = match &self.mnemonic_file {
+            Some(path) => read_to_string(path)?,
+            None => {
+                writeln!(out, "Enter the mnemonic:")?;
+                input.lines().next().transpose()?.unwrap_or_default()
+            }
+        };
+        let password = read_to_string(&self.keystore_password_file)?;
+        let password = password.trim_end_matches(['\r', '\n']);
+        self.create(&mnemonic, password, KdfParams::scrypt, out)
+    }
+
+    /// Writes the keystore of every validator to `<output dir>/keystores`, where the validator
+    /// client can load them from, and their deposits to `<output dir>/deposit_data-*.json` for
+    /// the launchpad.
+    pub fn create(
+        &self,
+        mnemonic: &str,
+        password: &str,
+        kdf: impl Fn() -> KdfParams,
+        out: &mut impl Write,
+    ) -> Result<(), AccountError> {
+        let seed = mnemonic_to_seed(mnemonic, "")?;
+        check_deposit_amount(self.amount)?;
+        let Some(last_index) = self.first_index.checked_add(self.count) else {
+            return Err(AccountError::IndexOverflow {
+                first_index: self.first_index,
+                count: self.count,
+            });
+        };
+        let keystores_dir = self.output_dir.join("keystores");
+        fs::create_dir_all(&keystores_dir).map_err(|error| AccountError::Io {
+            path: keystores_dir.clone(),
+            error,
+        })?;
+        let spec = NetworkSpec::load(self.network, self.custom_config_dir.as_deref())?;
+        let fork_version = spec.fork_schedule.genesis_fork().version;
+
+        let mut deposits = vec![];
+        for index in self.first_index..last_index {
+            let path = signing_key_path(index);
+            let key = derive_path(&seed, &path)?;
+            let withdrawal_credentials = match self.withdrawal_address {
+                Some(address) => eth1_withdrawal_credentials(address),
+                None => bls_withdrawal_credentials(
+                    &derive_path(&seed, &withdrawal_key_path(index))?.public_key(),
+                ),
+            };
+
+            let keystore = Keystore::encrypt(&key, password, &path, kdf())?;
+            let keystore_path =
+                keystores_dir.join(format!("keystore-{}.json", path.replace('/', "_")));
+            write_new(&keystore_path, &keystore)?;
+            writeln!(out, "Validator {index}: {}", keystore_path.display())?;
+
+            let deposit = sign_deposit(&key, withdrawal_credentials, self.amount, fork_version);
+            deposits.push(LaunchpadDeposit::new(&deposit, fork_version, &spec.name));
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock before 1970")
+            .as_secs();
+        let deposits_path = self
+            .output_dir
+            .join(format!("deposit_data-{timestamp}.json"));
+        write_new(&deposits_path, &deposits)?;
+        writeln!(out, "Deposit data: {}", deposits_path.display())?;
+        Ok(())
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String, AccountError> {
+    fs::read_to_string(path).map_err(|error| AccountError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// Writes `value` as JSON. Files are never overwritten, they may hold the only copy of a
+/// validator's key.
+fn write_new(path: &Path, value: &impl Serialize) -> Result<(), AccountError> {
+    if path.exists() {
+        return Err(AccountError::Exists(path.to_path_buf()));
+    }
+    let contents = serde_json::to_string_pretty(value).map_err(io::Error::from)?;
+    fs::write(path, contents).map_err(|error| AccountError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+    use ream_validator::{key_loader::KeystoreLocations, keystore::Pbkdf2Params};
+
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                            abandon abandon abandon about";
+
+    fn cheap_kdf() -> KdfParams {
+        KdfParams::Pbkdf2(Pbkdf2Params {
+            dklen: 32,
+            c: 16,
+            prf: "hmac-sha256".to_string(),
+            salt: vec![1; 32],
+        })
+    }
+
+    #[test]
+    fn test_new_accounts() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("accounts");
+        let password_file = dir.join("password.txt");
+        let command = NewAccountCommand::parse_from([
+            "new",
+            "--network",
+            "holesky",
+            "--keystore-password-file",
+            password_file.to_str().unwrap(),
+            "--count",
+            "2",
+            "--withdrawal-address",
+            "0x0101010101010101010101010101010101010101",
+            "--output-dir",
+            dir.to_str().unwrap(),
+        ]);
+        let mut out = vec![];
+        command
+            .create(MNEMONIC, "password", cheap_kdf, &mut out)
+            .unwrap();
+
+        let seed = mnemonic_to_seed(MNEMONIC, "").unwrap();
+        let locations = KeystoreLocations {
+            validators_dir: dir.join("keystores"),
+            secrets_dir: None,
+            password_file: Some(password_file.clone()),
+        };
+        fs::write(&password_file, "password\n").unwrap();
+        let paths = locations.keystore_paths().unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[1].ends_with("keystore-m_12381_3600_1_0_0.json"));
+        let key = locations.load(&paths[1]).unwrap();
+        assert_eq!(
+            key.to_bytes(),
+            derive_path(&seed, "m/12381/3600/1/0/0").unwrap().to_bytes()
+        );
+
+        let deposits_path = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_str().unwrap().contains("deposit_data-"))
+            .unwrap();
+        let deposits: Vec<LaunchpadDeposit> =
+            serde_json::from_str(&fs::read_to_string(deposits_path).unwrap()).unwrap();
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[1].pubkey, hex::encode(key.public_key()));
+        assert_eq!(deposits[1].network_name, "holesky");
+        assert_eq!(deposits[1].fork_version, "01017000");
+        assert!(deposits[1].withdrawal_credentials.starts_with("01"));
+        assert!(String::from_utf8(out).unwrap().contains("Deposit data: "));
+
+        // The keystores are kept when created again.
+        assert!(matches!(
+            command.create(MNEMONIC, "password", cheap_kdf, &mut vec![]),
+            Err(AccountError::Exists(_))
+        ));
+    }
+
+    #[test]
+    fn test_refused_accounts() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("accounts");
+        let command = |args: &[&str]| {
+            let mut argv = vec![
+                "new",
+                "--keystore-password-file",
+                "password.txt",
+                "--output-dir",
+                dir.to_str().unwrap(),
+            ];
+            argv.extend(args);
+            NewAccountCommand::parse_from(argv)
+        };
+
+        assert!(matches!(
+            command(&["--amount", "999999999"]).create(
+                MNEMONIC,
+                "password",
+                cheap_kdf,
+                &mut vec![]
+            ),
+            Err(AccountError::Validator(ValidatorError::DepositAmount(
+                999_999_999
+            )))
+        ));
+        assert!(matches!(
+            command(&["--first-index", "4294967295", "--count", "2"]).create(
+                MNEMONIC,
+                "password",
+                cheap_kdf,
+                &mut vec![]
+            ),
+            Err(AccountError::IndexOverflow { .. })
+        ));
+        let flipped = MNEMONIC.replace("about", "above");
+        assert!(matches!(
+            command(&[]).create(&flipped, "password", cheap_kdf, &mut vec![]),
+            Err(AccountError::Validator(ValidatorError::Mnemonic(_)))
+        ));
+        assert!(!dir.exists());
+    }
+}
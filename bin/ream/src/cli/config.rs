@@ -0,0 +1,11 @@
+use clap::Subcommand;
+
+use super::NodeCommand;
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigSubcommand {
+    /// Print the options `ream node` would run with, after merging the config file and the
+    /// command line, along with where each value comes from
+    #[command(name = "dump")]
+    Dump(NodeCommand),
+}
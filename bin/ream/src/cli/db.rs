@@ -0,0 +1,335 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use alloy_primitives::B256;
+use clap::{Parser, Subcommand};
+use ream_storage::{
+    blob_store::BlobStore,
+    database::Column,
+    era::EraError,
+    error::StoreError,
+    hot_cold::{HotColdStore, StoreConfig},
+    pruning::PruningMode,
+    redb_store::RedbStore,
+    store::Store,
+    tables::{BlocksByRoot, StateSummaries},
+    verify::{repair_chain, verify_chain},
+};
+
+use crate::config::{datadir::DataDir, Network};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("no database at {0}, is the node initialized?")]
+    NoDatabase(PathBuf),
+    #[error("nothing with root {0} in the database")]
+    NotFound(B256),
+    #[error("found {0} inconsistencies, run with --repair to fix them")]
+    Inconsistent(usize),
+    #[error("{0} inconsistencies are left after repairing")]
+    RepairFailed(usize),
+    #[error("{path}: {error}")]
+    Era { path: PathBuf, error: EraError },
+    #[error("{path} is for another chain, genesis validators root {found} instead of {expected}")]
+    WrongChain {
+        path: PathBuf,
+        expected: B256,
+        found: B256,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub struct DbCommand {
+    /// Network of the default data directory
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Data directory of the node [default: ~/.ream/<network>]
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: DbSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbSubcommand {
+    /// Show the entries and size of every table and the anchor and split slots
+    Stats,
+
+    /// Delete finalized history the given mode doesn't keep
+    Prune {
+        /// `prune:<retention epochs>`
+        #[arg(long, value_name = "MODE")]
+        pruning: PruningMode,
+    },
+
+    /// Reclaim space freed by deleted entries
+    Compact,
+
+    /// Show what the database holds for a block or state root
+    Inspect { root: B256 },
+
+    /// Check the canonical chain, its slot index and the stored snapshots of both databases,
+    /// for reassurance after a crash or disk failure
+    Verify {
+        /// Fix what was found, rebuilding the slot index from the newest stored block when
+        /// it's broken
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+impl DbCommand {
+    pub fn datadir(&self) -> DataDir {
+        DataDir::resolve(self.datadir.as_deref(), self.network)
+    }
+
+    /// Opens the databases of the data directory and runs the subcommand, writing its report
+    /// to `out`. The node must not be running.
+    pub fn execute(&self, out: &mut impl Write) -> Result<(), DbError> {
+        let pruning = match &self.command {
+            DbSubcommand::Prune { pruning } => *pruning,
+            _ => PruningMode::Archive,
+        };
+        let store = open_store(
+            &self.datadir(),
+            StoreConfig {
+                pruning,
+                ..StoreConfig::default()
+            },
+        )?;
+
+        match &self.command {
+            DbSubcommand::Stats => {
+                for (name, db) in [("hot", store.hot()), ("cold", store.cold())] {
+                    writeln!(out, "{name} database")?;
+                    for column in Column::ALL {
+                        let stats = db.column_stats(column)?;
+                        writeln!(
+                            out,
+                            "  {:<20} {:>10} entries {:>14} bytes",
+                            column.name(),
+                            stats.entries,
+                            stats.bytes
+                        )?;
+                    }
+                }
+                match store.anchor_slot()? {
+                    Some(slot) => writeln!(out, "anchor slot {slot}")?,
+                    None => writeln!(out, "anchor slot none, the database has no blocks")?,
+                }
+                writeln!(out, "split slot {}", store.split().slot)?;
+            }
+            DbSubcommand::Prune { pruning } => {
+                store.prune_cold()?;
+                writeln!(
+                    out,
+                    "Pruned finalized history with {pruning}, anchor slot is now {:?}",
+                    store.anchor_slot()?
+                )?;
+            }
+            DbSubcommand::Compact => {
+                store.compact()?;
+                writeln!(out, "Compacted the hot and cold databases")?;
+            }
+            DbSubcommand::Inspect { root } => inspect(&store, *root, out)?,
+            DbSubcommand::Verify { repair } => verify(&store, *repair, out)?,
+        }
+        Ok(())
+    }
+}
+
+/// Opens the hot and cold databases of `datadir`, which must already exist.
+pub fn open_store(datadir: &DataDir, config: StoreConfig) -> Result<HotColdStore, DbError> {
+    let mut stores = vec![];
+    for path in [datadir.hot_db(), datadir.cold_db()] {
+        if !path.exists() {
+            return Err(DbError::NoDatabase(path));
+        }
+        stores.push(Store::new(Arc::new(RedbStore::open(&path)?)));
+    }
+    let cold = stores.pop().expect("two stores were opened");
+    let hot = stores.pop().expect("two stores were opened");
+    Ok(HotColdStore::open(hot, cold, config)?)
+}
+
+/// Opens the hot and cold databases of `datadir`, creating them for a new node.
+pub fn open_or_create_store(
+    datadir: &DataDir,
+    config: StoreConfig,
+) -> Result<HotColdStore, DbError> {
+    datadir.create()?;
+    let hot = Store::new(Arc::new(RedbStore::open(datadir.hot_db())?));
+    let cold = Store::new(Arc::new(RedbStore::open(datadir.cold_db())?));
+    Ok(HotColdStore::open(hot, cold, config)?)
+}
+
+fn inspect(store: &HotColdStore, root: B256, out: &mut impl Write) -> Result<(), DbError> {
+    let mut found = false;
+    for (name, db) in [("hot", store.hot()), ("cold", store.cold())] {
+        if let Some(block) = db.get::<BlocksByRoot>(&root)? {
+            let block = block.message;
+            writeln!(out, "block in the {name} database")?;
+            writeln!(out, "  slot            {}", block.slot)?;
+            writeln!(out, "  proposer index  {}", block.proposer_index)?;
+            writeln!(out, "  parent root     {}", block.parent_root)?;
+            writeln!(out, "  state root      {}", block.state_root)?;
+            let blobs = BlobStore::new(db.clone(), false).get_blob_sidecars(root)?;
+            writeln!(out, "  blob sidecars   {}", blobs.len())?;
+            found = true;
+        }
+        if let Some(summary) = db.get::<StateSummaries>(&root)? {
+            writeln!(out, "state in the {name} database")?;
+            writeln!(out, "  slot               {}", summary.slot)?;
+            writeln!(out, "  latest block root  {}", summary.latest_block_root)?;
+            writeln!(out, "  stored in full     {}", summary.snapshot)?;
+            found = true;
+        }
+    }
+
+    if found {
+        Ok(())
+    } else {
+        Err(DbError::NotFound(root))
+    }
+}
+
+fn verify(store: &HotColdStore, repair: bool, out: &mut impl Write) -> Result<(), DbError> {
+    let mut found = 0;
+    let mut left = 0;
+    for (name, db) in [("hot", store.hot()), ("cold", store.cold())] {
+        let inconsistencies = verify_chain(db)?;
+        writeln!(
+            out,
+            "{name} database: {} inconsistencies",
+            inconsistencies.len()
+        )?;
+        for inconsistency in &inconsistencies {
+            writeln!(out, "  {inconsistency}")?;
+        }
+        found += inconsistencies.len();
+        if repair && !inconsistencies.is_empty() {
+            repair_chain(db, &inconsistencies)?;
+            let remaining = verify_chain(db)?.len();
+            writeln!(out, "  repaired, {remaining} left")?;
+            left += remaining;
+        }
+    }
+
+    match (found, repair) {
+        (0, _) => Ok(()),
+        (found, false) => Err(DbError::Inconsistent(found)),
+        (_, true) if left > 0 => Err(DbError::RepairFailed(left)),
+        (_, true) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::beacon_block::{BeaconBlock, SignedBeaconBlock};
+    use ream_storage::tables::BlockRootsBySlot;
+
+    use super::*;
+
+    #[test]
+    fn test_stats_and_inspect() {
+        let temp = tempfile::tempdir().unwrap();
+        let datadir = temp.path().join("datadir");
+        std::fs::create_dir_all(datadir.join("db")).unwrap();
+        let command = |command| DbCommand {
+            network: Network::Mainnet,
+            datadir: Some(datadir.clone()),
+            command,
+        };
+
+        assert!(matches!(
+            command(DbSubcommand::Stats).execute(&mut vec![]),
+            Err(DbError::NoDatabase(_))
+        ));
+
+        let root = {
+            let hot = RedbStore::open(DataDir::new(&datadir).hot_db()).unwrap();
+            RedbStore::open(DataDir::new(&datadir).cold_db()).unwrap();
+            let block = SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot: 7,
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            };
+            ream_storage::block_store::BlockStore::new(Store::new(Arc::new(hot)))
+                .put_block(&block)
+                .unwrap()
+        };
+
+        let mut out = vec![];
+        command(DbSubcommand::Stats).execute(&mut out).unwrap();
+        let stats = String::from_utf8(out).unwrap();
+        assert!(stats.contains("anchor slot none"));
+        assert!(stats.contains("split slot 0"));
+
+        let mut out = vec![];
+        command(DbSubcommand::Inspect { root })
+            .execute(&mut out)
+            .unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.starts_with("block in the hot database"));
+        assert!(report.contains("slot            7"));
+
+        assert!(matches!(
+            command(DbSubcommand::Inspect { root: B256::ZERO }).execute(&mut vec![]),
+            Err(DbError::NotFound(_))
+        ));
+
+        command(DbSubcommand::Verify { repair: false })
+            .execute(&mut vec![])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify() {
+        let temp = tempfile::tempdir().unwrap();
+        let datadir = temp.path().join("datadir");
+        std::fs::create_dir_all(datadir.join("db")).unwrap();
+        let command = |repair| DbCommand {
+            network: Network::Mainnet,
+            datadir: Some(datadir.clone()),
+            command: DbSubcommand::Verify { repair },
+        };
+
+        {
+            let hot = Store::new(Arc::new(
+                RedbStore::open(DataDir::new(&datadir).hot_db()).unwrap(),
+            ));
+            RedbStore::open(DataDir::new(&datadir).cold_db()).unwrap();
+            let blocks = ream_storage::block_store::BlockStore::new(hot.clone());
+            let genesis = blocks.put_block(&SignedBeaconBlock::default()).unwrap();
+            blocks.set_canonical_head(genesis).unwrap();
+            // The index points at a block that was never written.
+            hot.put::<BlockRootsBySlot>(&1, &B256::repeat_byte(1))
+                .unwrap();
+        }
+
+        let mut out = vec![];
+        assert!(matches!(
+            command(false).execute(&mut out),
+            Err(DbError::Inconsistent(1))
+        ));
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("hot database: 1 inconsistencies"));
+        assert!(report.contains("cold database: 0 inconsistencies"));
+
+        let mut out = vec![];
+        command(true).execute(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("repaired, 0 left"));
+        command(false).execute(&mut vec![]).unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    ffi::OsStr,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::B256;
+use clap::{Parser, Subcommand, ValueEnum};
+use ream_consensus::{
+    beacon_block::{BeaconBlock, SignedBeaconBlock},
+    beacon_state::BeaconState,
+    blob_sidecar::BlobSidecar,
+    fork_schedule::ForkName,
+};
+use serde::Serialize;
+use ssz::Decode;
+use tree_hash::TreeHash;
+
+/// Fork of the consensus types the client implements, the only one objects decode as.
+pub const SUPPORTED_FORK: ForkName = ForkName::Deneb;
+
+/// First chunk of the framed snappy format, the block format used by the spec tests has none.
+const SNAPPY_STREAM_IDENTIFIER: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DebugError {
+    #[error("failed to read {path}: {error}")]
+    Io { path: PathBuf, error: io::Error },
+    #[error("invalid snappy compression in {path}: {error}")]
+    Snappy { path: PathBuf, error: io::Error },
+    #[error("{path} isn't a {kind}: {error}")]
+    Decode {
+        path: PathBuf,
+        kind: SszType,
+        error: String,
+    },
+    #[error("{0} isn't any known type")]
+    UnknownType(PathBuf),
+    #[error("{path} could be any of {}, pass --type", types.join(", "))]
+    AmbiguousType { path: PathBuf, types: Vec<String> },
+    #[error("objects can only be decoded as {supported}, not {0}", supported = SUPPORTED_FORK)]
+    UnsupportedFork(ForkName),
+    #[error(transparent)]
+    Output(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DebugSubcommand {
+    /// Decode an SSZ or ssz_snappy file and print it with its hash tree root
+    #[command(name = "ssz")]
+    Ssz(SszCommand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SszType {
+    State,
+    Block,
+    SignedBlock,
+    BlobSidecar,
+}
+
+impl SszType {
+    pub const ALL: [SszType; 4] = [
+        SszType::State,
+        SszType::Block,
+        SszType::SignedBlock,
+        SszType::BlobSidecar,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SszType::State => "state",
+            SszType::Block => "block",
+            SszType::SignedBlock => "signed-block",
+            SszType::BlobSidecar => "blob-sidecar",
+        }
+    }
+
+    fn decodes(&self, bytes: &[u8]) -> bool {
+        match self {
+            SszType::State => BeaconState::from_ssz_bytes(bytes).is_ok(),
+            SszType::Block => BeaconBlock::from_ssz_bytes(bytes).is_ok(),
+            SszType::SignedBlock => SignedBeaconBlock::from_ssz_bytes(bytes).is_ok(),
+            SszType::BlobSidecar => BlobSidecar::from_ssz_bytes(bytes).is_ok(),
+        }
+    }
+}
+
+impl fmt::Display for SszType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Parser)]
+pub struct SszCommand {
+    /// Type of the object, guessed from the bytes when left out
+    #[arg(long = "type", value_name = "TYPE")]
+    pub kind: Option<SszType>,
+
+    /// Fork of the object
+    #[arg(long, value_parser = parse_fork, default_value_t = SUPPORTED_FORK)]
+    pub fork: ForkName,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+
+    /// `.ssz` file, or `.ssz_snappy` in the framed or block format
+    pub file: PathBuf,
+}
+
+/// What gets printed, the object along with what it was decoded as.
+#[derive(Debug, Serialize)]
+struct Decoded<T> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    fork: ForkName,
+    hash_tree_root: B256,
+    data: T,
+}
+
+impl SszCommand {
+    /// Decodes the file and writes it to `out`.
+    pub fn execute(&self, out: &mut impl Write) -> Result<(), DebugError> {
+        if self.fork != SUPPORTED_FORK {
+            return Err(DebugError::UnsupportedFork(self.fork));
+        }
+        let bytes = read_ssz(&self.file)?;
+        let kind = match self.kind {
+            Some(kind) => kind,
+            None => detect_type(&self.file, &bytes)?,
+        };
+        match kind {
+            SszType::State => self.write::<BeaconState>(kind, &bytes, out),
+            SszType::Block => self.write::<BeaconBlock>(kind, &bytes, out),
+            SszType::SignedBlock => self.write::<SignedBeaconBlock>(kind, &bytes, out),
+            SszType::BlobSidecar => self.write::<BlobSidecar>(kind, &bytes, out),
+        }
+    }
+
+    fn write<T: Decode + TreeHash + Serialize>(
+        &self,
+        kind: SszType,
+        bytes: &[u8],
+        out: &mut impl Write,
+    ) -> Result<(), DebugError> {
+        let data = T::from_ssz_bytes(bytes).map_err(|error| DebugError::Decode {
+            path: self.file.clone(),
+            kind,
+            error: format!("{error:?}"),
+        })?;
+        let decoded = Decoded {
+            kind: kind.name(),
+            fork: self.fork,
+            hash_tree_root: data.tree_hash_root(),
+            data,
+        };
+        match self.format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, &decoded)?;
+                writeln!(out)?;
+            }
+            OutputFormat::Yaml => serde_yaml::to_writer(out, &decoded)?,
+        }
+        Ok(())
+    }
+}
+
+fn parse_fork(name: &str) -> Result<ForkName, String> {
+    [
+        ForkName::Phase0,
+        ForkName::Altair,
+        ForkName::Bellatrix,
+        ForkName::Capella,
+        ForkName::Deneb,
+        ForkName::Electra,
+    ]
+    .into_iter()
+    .find(|fork| fork.to_string() == name.to_lowercase())
+    .ok_or_else(|| format!("unknown fork {name}"))
+}
+
+/// Contents of the file at `path`, decompressed if it's `.ssz_snappy`.
+fn read_ssz(path: &Path) -> Result<Vec<u8>, DebugError> {
+    let bytes = fs::read(path).map_err(|error| DebugError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    if path.extension().and_then(OsStr::to_str) != Some("ssz_snappy") {
+        return Ok(bytes);
+    }
+    let decompressed = if bytes.starts_with(SNAPPY_STREAM_IDENTIFIER) {
+        let mut decompressed = vec![];
+        snap::read::FrameDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decompressed)
+            .map(|_| decompressed)
+    } else {
+        snap::raw::Decoder::new()
+            .decompress_vec(&bytes)
+            .map_err(io::Error::from)
+    };
+    decompressed.map_err(|error| DebugError::Snappy {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// The only type `bytes` decode as.
+fn detect_type(path: &Path, bytes: &[u8]) -> Result<SszType, DebugError> {
+    let types: Vec<SszType> = SszType::ALL
+        .into_iter()
+        .filter(|kind| kind.decodes(bytes))
+        .collect();
+    match types.as_slice() {
+        [kind] => Ok(*kind),
+        [] => Err(DebugError::UnknownType(path.to_path_buf())),
+        _ => Err(DebugError::AmbiguousType {
+            path: path.to_path_buf(),
+            types: types.iter().map(ToString::to_string).collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use ssz::Encode;
+
+    use super::*;
+
+    fn execute(command: SszCommand) -> Result<String, DebugError> {
+        let mut out = vec![];
+        command.execute(&mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn command(file: PathBuf) -> SszCommand {
+        SszCommand {
+            kind: None,
+            fork: SUPPORTED_FORK,
+            format: OutputFormat::Json,
+            file,
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("ssz");
+        fs::create_dir_all(&dir).unwrap();
+        let block = SignedBeaconBlock {
+            message: BeaconBlock {
+                slot: 12,
+                ..BeaconBlock::default()
+            },
+            ..SignedBeaconBlock::default()
+        };
+        let path = dir.join("block.ssz_snappy");
+        let compressed = snap::raw::Encoder::new()
+            .compress_vec(&block.as_ssz_bytes())
+            .unwrap();
+        fs::write(&path, compressed).unwrap();
+
+        let output: Value = serde_json::from_str(&execute(command(path.clone())).unwrap()).unwrap();
+        assert_eq!(output["type"], "signed-block");
+        assert_eq!(output["fork"], "deneb");
+        assert_eq!(output["data"]["message"]["slot"], "12");
+        assert_eq!(output["hash_tree_root"], block.tree_hash_root().to_string());
+
+        let yaml = execute(SszCommand {
+            format: OutputFormat::Yaml,
+            ..command(path.clone())
+        })
+        .unwrap();
+        assert!(yaml.starts_with("type: signed-block\n"));
+
+        let err = execute(SszCommand {
+            kind: Some(SszType::BlobSidecar),
+            ..command(path.clone())
+        })
+        .unwrap_err();
+        assert!(matches!(err, DebugError::Decode { .. }));
+        let err = execute(SszCommand {
+            fork: ForkName::Electra,
+            ..command(path)
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DebugError::UnsupportedFork(ForkName::Electra)
+        ));
+
+        let path = dir.join("garbage.ssz");
+        fs::write(&path, [1, 2, 3]).unwrap();
+        assert!(matches!(
+            execute(command(path)),
+            Err(DebugError::UnknownType(_))
+        ));
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Parser;
+use ream_consensus::{constants::SECONDS_PER_SLOT, fork_schedule::ForkName};
+use ream_execution::{
+    auth::JwtSecret, capabilities::check_compatibility, client::EngineClient, error::ExecutionError,
+};
+use tokio::{
+    net::{TcpListener, TcpStream, UdpSocket},
+    time::timeout,
+};
+use url::Url;
+
+use crate::config::{datadir::DataDir, Network};
+
+/// Largest clock difference with peers the p2p spec tolerates on gossip.
+pub const MAXIMUM_GOSSIP_CLOCK_DISPARITY: Duration = Duration::from_millis(500);
+
+/// Clock offset past which the node loses part of the tolerance, worth fixing before it grows.
+const CLOCK_DRIFT_WARNING: Duration = Duration::from_millis(250);
+
+/// Seconds between the NTP era and the Unix epoch.
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, thiserror::Error)]
+pub enum DoctorError {
+    #[error(transparent)]
+    Output(#[from] io::Error),
+    #[error("{0} checks failed")]
+    Failed(usize),
+}
+
+#[derive(Debug, Parser)]
+pub struct DoctorCommand {
+    /// Network of the node, whose default data directory is checked
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Data directory of the node [default: ~/.ream/<network>]
+    #[arg(long, value_name = "DIR")]
+    pub datadir: Option<PathBuf>,
+
+    /// Free space the data directory needs, in GiB
+    #[arg(long, default_value_t = 100, value_name = "GIB")]
+    pub min_free_disk: u64,
+
+    /// TCP and UDP port of libp2p and discovery
+    #[arg(long, default_value_t = 9000)]
+    pub port: u16,
+
+    #[arg(long, default_value_t = 9001)]
+    pub quic_port: u16,
+
+    /// Public address of the node, to check the TCP port is forwarded to this host
+    #[arg(long)]
+    pub public_ip: Option<IpAddr>,
+
+    /// Authenticated engine API endpoint of the execution layer
+    #[arg(long, value_name = "URL", requires = "execution_jwt")]
+    pub execution_endpoint: Option<Url>,
+
+    /// File holding the hex encoded JWT secret shared with the execution layer
+    #[arg(long, value_name = "PATH", requires = "execution_endpoint")]
+    pub execution_jwt: Option<PathBuf>,
+
+    /// NTP server the clock is compared to
+    #[arg(long, default_value = "pool.ntp.org:123", value_name = "HOST:PORT")]
+    pub ntp_server: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Ok => "ok",
+            Severity::Warning => "warn",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Outcome of one check, with what to do about it unless it passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(check: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(check: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+impl DoctorCommand {
+    /// Runs every check and writes the findings to `out`, failing if any check found an error.
+    pub async fn execute(&self, out: &mut impl Write) -> Result<(), DoctorError> {
+        let datadir = DataDir::resolve(self.datadir.as_deref(), self.network);
+        let mut findings = check_datadir(&datadir, self.min_free_disk * 1024 * 1024 * 1024);
+        findings.extend(check_ports(self.port, self.quic_port, self.public_ip).await);
+        findings.push(match (&self.execution_endpoint, &self.execution_jwt) {
+            (Some(endpoint), Some(jwt)) => check_engine(endpoint, jwt).await,
+            _ => Finding::warning(
+                "engine",
+                "no execution layer configured, the node can't verify payloads",
+                "pass --execution-endpoint and --execution-jwt as given to `ream node`",
+            ),
+        });
+        findings.push(check_clock(&self.ntp_server).await);
+
+        for finding in &findings {
+            writeln!(
+                out,
+                "{:<7} {:<8} {}",
+                format!("[{}]", finding.severity),
+                finding.check,
+                finding.message
+            )?;
+            if let Some(fix) = &finding.fix {
+                writeln!(out, "{:16} fix: {fix}", "")?;
+            }
+        }
+        let count = |severity| {
+            findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+        let (warnings, errors) = (count(Severity::Warning), count(Severity::Error));
+        writeln!(out, "{warnings} warnings, {errors} errors")?;
+        if errors > 0 {
+            return Err(DoctorError::Failed(errors));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the data directory can be written, keeps its secrets private and has
+/// `min_free_bytes` left.
+pub fn check_datadir(datadir: &DataDir, min_free_bytes: u64) -> Vec<Finding> {
+    let root = datadir.root();
+    let mut findings = vec![];
+    if root.is_dir() {
+        let probe = root.join(".ream-doctor");
+        match fs::write(&probe, b"").and_then(|_| fs::remove_file(&probe)) {
+            Ok(()) => findings.push(Finding::ok(
+                "datadir",
+                format!("{} is writable", root.display()),
+            )),
+            Err(err) => findings.push(Finding::error(
+                "datadir",
+                format!("{} isn't writable: {err}", root.display()),
+                format!(
+                    "give the user running ream ownership of it: `chown -R $USER {}`",
+                    root.display()
+                ),
+            )),
+        }
+    } else {
+        findings.push(Finding::warning(
+            "datadir",
+            format!("{} doesn't exist yet", root.display()),
+            "run `ream init` or start the node once to create it",
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        for (path, mode) in [(datadir.keys_dir(), 0o700), (datadir.node_key(), 0o600)] {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.permissions().mode() & 0o077 != 0 {
+                findings.push(Finding::warning(
+                    "datadir",
+                    format!("{} can be read by other users", path.display()),
+                    format!("`chmod {mode:o} {}`", path.display()),
+                ));
+            }
+        }
+    }
+
+    // The directory may not exist yet, the disk it will be created on counts.
+    let Some(existing) = root.ancestors().find(|path| path.exists()) else {
+        return findings;
+    };
+    let gib = |bytes: u64| bytes / (1024 * 1024 * 1024);
+    findings.push(match fs2::available_space(existing) {
+        Ok(free) if free < min_free_bytes => Finding::error(
+            "disk",
+            format!(
+                "{} GiB free under {}, the node needs {} GiB",
+                gib(free),
+                existing.display(),
+                gib(min_free_bytes)
+            ),
+            "free up space or pass --datadir on a larger disk",
+        ),
+        Ok(free) => Finding::ok("disk", format!("{} GiB free", gib(free))),
+        Err(err) => Finding::warning(
+            "disk",
+            format!(
+                "failed to read the free space of {}: {err}",
+                existing.display()
+            ),
+            "check the disk has room for the chain with `df -h`",
+        ),
+    });
+    findings
+}
+
+/// Whether the p2p ports are free on this host, and the TCP port reachable at `public_ip`.
+///
+/// Reaching the UDP ports from outside needs a peer answering, discovery reports it once the
+/// node runs.
+pub async fn check_ports(port: u16, quic_port: u16, public_ip: Option<IpAddr>) -> Vec<Finding> {
+    let any = |port| SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port);
+    let taken = |port: u16, protocol: &str, err: io::Error| {
+        let fix = if err.kind() == io::ErrorKind::AddrInUse {
+            format!(
+                "stop the program using it, e.g. a running node, or pick another port than \
+                 {port}/{protocol}"
+            )
+        } else {
+            "ports below 1024 need root, pick a higher one".to_string()
+        };
+        Finding::error(
+            "ports",
+            format!("can't listen on {port}/{protocol}: {err}"),
+            fix,
+        )
+    };
+
+    let mut findings = vec![];
+    let listener = match TcpListener::bind(any(port)).await {
+        Ok(listener) => Some(listener),
+        Err(err) => {
+            findings.push(taken(port, "tcp", err));
+            None
+        }
+    };
+    for (port, protocol) in [(port, "udp"), (quic_port, "udp (quic)")] {
+        if let Err(err) = UdpSocket::bind(any(port)).await {
+            findings.push(taken(port, protocol, err));
+        }
+    }
+    if findings.is_empty() {
+        findings.push(Finding::ok(
+            "ports",
+            format!("{port}/tcp, {port}/udp and {quic_port}/udp are free"),
+        ));
+    }
+
+    if let (Some(_listener), Some(ip)) = (listener, public_ip) {
+        let address = SocketAddr::new(ip, port);
+        findings.push(
+            match timeout(NETWORK_TIMEOUT, TcpStream::connect(address)).await {
+                Ok(Ok(_)) => Finding::ok("ports", format!("{address} reaches this host")),
+                Ok(Err(err)) => Finding::warning(
+                    "ports",
+                    format!("{address} doesn't reach this host: {err}"),
+                    forward_fix(port, quic_port),
+                ),
+                Err(_) => Finding::warning(
+                    "ports",
+                    format!("{address} doesn't reach this host: timed out"),
+                    forward_fix(port, quic_port),
+                ),
+            },
+        );
+    }
+    findings
+}
+
+fn forward_fix(port: u16, quic_port: u16) -> String {
+    format!(
+        "forward {port}/tcp, {port}/udp and {quic_port}/udp to this host on the router and \
+         firewall, some routers don't loop back so check from outside too"
+    )
+}
+
+/// Whether the execution layer accepts the JWT secret and serves the engine API methods the
+/// node calls.
+pub async fn check_engine(endpoint: &Url, jwt: &Path) -> Finding {
+    let secret = match JwtSecret::from_file(jwt) {
+        Ok(secret) => secret,
+        Err(err) => {
+            return Finding::error(
+                "engine",
+                err.to_string(),
+                format!(
+                    "write a new secret with `openssl rand -hex 32 > {}` and give the execution \
+                     layer the same file",
+                    jwt.display()
+                ),
+            )
+        }
+    };
+    let engine = EngineClient::new(endpoint.clone(), secret);
+    match check_compatibility(&engine, ForkName::Deneb).await {
+        Ok(info) => Finding::ok("engine", format!("{endpoint} runs {}", info.client_version)),
+        Err(err) if err.is_connection_error() => Finding::error(
+            "engine",
+            err.to_string(),
+            "start the execution layer and check its authenticated RPC listens on this address",
+        ),
+        Err(ExecutionError::Http(err)) if err.status().is_some_and(|status| status == 401) => {
+            Finding::error(
+                "engine",
+                format!("{endpoint} rejected the JWT"),
+                format!(
+                    "give the execution layer the secret of {}, and keep both clocks within a \
+                     minute",
+                    jwt.display()
+                ),
+            )
+        }
+        Err(err @ ExecutionError::MissingMethods { .. }) => {
+            Finding::error("engine", err.to_string(), "upgrade the execution layer")
+        }
+        Err(err) => Finding::error(
+            "engine",
+            err.to_string(),
+            "check the endpoint is the authenticated engine API, not the public JSON-RPC",
+        ),
+    }
+}
+
+/// How far the local clock is from `ntp_server`, against the tolerance of the p2p spec.
+pub async fn check_clock(ntp_server: &str) -> Finding {
+    let offset = match timeout(NETWORK_TIMEOUT, ntp_offset(ntp_server)).await {
+        Ok(Ok(offset)) => offset,
+        Ok(Err(err)) => {
+            return Finding::warning(
+                "clock",
+                format!("failed to query {ntp_server}: {err}"),
+                "allow outgoing UDP to port 123 or pass another --ntp-server",
+            )
+        }
+        Err(_) => {
+            return Finding::warning(
+                "clock",
+                format!("{ntp_server} didn't answer"),
+                "allow outgoing UDP to port 123 or pass another --ntp-server",
+            )
+        }
+    };
+    let drift = Duration::from_secs_f64(offset.abs());
+    let message = format!(
+        "clock is {} ms {} {ntp_server}",
+        drift.as_millis(),
+        if offset > 0.0 { "behind" } else { "ahead of" }
+    );
+    let fix = "enable time synchronization, e.g. `timedatectl set-ntp true` or chrony";
+    if drift > MAXIMUM_GOSSIP_CLOCK_DISPARITY {
+        Finding::error(
+            "clock",
+            format!(
+                "{message}, peers reject gossip past {} ms and duties of {} second slots run late",
+                MAXIMUM_GOSSIP_CLOCK_DISPARITY.as_millis(),
+                SECONDS_PER_SLOT
+            ),
+            fix,
+        )
+    } else if drift > CLOCK_DRIFT_WARNING {
+        Finding::warning("clock", message, fix)
+    } else {
+        Finding::ok("clock", message)
+    }
+}
+
+/// Seconds to add to the local clock to match `server`, from an SNTP request.
+async fn ntp_offset(server: &str) -> io::Result<f64> {
+    let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)).await?;
+    socket.connect(server).await?;
+    let mut request = [0; 48];
+    // No leap second warning, version 4, client mode.
+    request[0] = 0x23;
+    let sent = unix_seconds(SystemTime::now());
+    socket.send(&request).await?;
+    let mut response = [0; 48];
+    let len = socket.recv(&mut response).await?;
+    let received = unix_seconds(SystemTime::now());
+    clock_offset(sent, received, &response[..len])
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid NTP response"))
+}
+
+/// The offset of RFC 4330 between a request sent at `sent` and answered with `response` at
+/// `received`, `None` unless `response` is a valid server reply.
+pub fn clock_offset(sent: f64, received: f64, response: &[u8]) -> Option<f64> {
+    let server_mode = response.len() == 48 && response[0] & 0x07 == 4;
+    // A stratum of 0 is a kiss-o'-death, telling the client to back off.
+    if !server_mode || response[1] == 0 {
+        return None;
+    }
+    let timestamp = |offset: usize| {
+        let seconds = u32::from_be_bytes(response[offset..offset + 4].try_into().ok()?);
+        let fraction = u32::from_be_bytes(response[offset + 4..offset + 8].try_into().ok()?);
+        Some(f64::from(seconds) + f64::from(fraction) / 2f64.powi(32) - NTP_UNIX_OFFSET)
+    };
+    let (server_received, server_sent) = (timestamp(32)?, timestamp(40)?);
+    Some(((server_received - sent) + (server_sent - received)) / 2.0)
+}
+
+fn unix_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_datadir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("datadir");
+        let datadir = DataDir::new(&dir);
+        let findings = check_datadir(&datadir, 0);
+        assert_eq!(findings[0].severity, Severity::Warning);
+        assert_eq!(findings.last().unwrap().check, "disk");
+
+        datadir.create().unwrap();
+        let findings = check_datadir(&datadir, u64::MAX);
+        assert_eq!(
+            findings[0],
+            Finding::ok("datadir", format!("{} is writable", dir.display()))
+        );
+        assert_eq!(findings.last().unwrap().severity, Severity::Error);
+    }
+
+    #[tokio::test]
+    async fn test_check_ports() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let findings = check_ports(port, 0, None).await;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+        drop(listener);
+
+        let findings = check_ports(port, 0, Some(Ipv4Addr::LOCALHOST.into())).await;
+        assert!(findings
+            .iter()
+            .all(|finding| finding.severity == Severity::Ok));
+    }
+
+    #[test]
+    fn test_clock_offset() {
+        let mut response = [0; 48];
+        response[0] = 0x24;
+        response[1] = 2;
+        // The server answers at 1.5 s past the Unix epoch.
+        let server_time = (NTP_UNIX_OFFSET as u32 + 1).to_be_bytes();
+        for offset in [32, 40] {
+            response[offset..offset + 4].copy_from_slice(&server_time);
+            response[offset + 4..offset + 8].copy_from_slice(&(1u32 << 31).to_be_bytes());
+        }
+        assert_eq!(clock_offset(0.25, 0.75, &response), Some(1.0));
+
+        response[1] = 0;
+        assert_eq!(clock_offset(0.25, 0.75, &response), None);
+    }
+}
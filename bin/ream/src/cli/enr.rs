@@ -0,0 +1,409 @@
+use std::{
+    io::{self, Write},
+    net::IpAddr,
+    path::PathBuf,
+};
+
+use alloy_primitives::{hex, B256};
+use alloy_rlp::Bytes;
+use clap::{Args, Parser, Subcommand};
+use ream_consensus::{
+    constants::FAR_FUTURE_EPOCH,
+    fork::ENRForkID,
+    subnet::{
+        attnets_from_subnets, decode_enr_bitfield, encode_enr_bitfield, subnets_from_attnets,
+        subnets_from_syncnets, syncnets_from_subnets, AttestationSubnets, SubnetId,
+        SyncCommitteeSubnets, SyncSubnetId,
+    },
+};
+use ream_discv5::{
+    node_key::{load_node_key, load_or_generate_node_key},
+    CombinedKey, Enr, EnrPublicKey,
+};
+use ssz::{Decode, Encode};
+
+use crate::config::{
+    datadir::DataDir,
+    network::{NetworkSpec, NetworkSpecError},
+    Network,
+};
+
+/// Keys of the ENR fields of the consensus layer.
+pub const ETH2_ENR_KEY: &str = "eth2";
+pub const ATTNETS_ENR_KEY: &str = "attnets";
+pub const SYNCNETS_ENR_KEY: &str = "syncnets";
+pub const QUIC_ENR_KEY: &str = "quic";
+pub const QUIC6_ENR_KEY: &str = "quic6";
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnrError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid ENR: {0}")]
+    Invalid(String),
+    #[error("failed to read the node key {path}: {error}")]
+    NodeKey { path: PathBuf, error: io::Error },
+    #[error(transparent)]
+    Network(#[from] NetworkSpecError),
+    #[error("failed to build the ENR: {0}")]
+    Build(String),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EnrSubcommand {
+    /// Print the addresses and consensus fields of an ENR
+    #[command(name = "decode")]
+    Decode {
+        /// `enr:` followed by the base64 record
+        enr: String,
+    },
+
+    /// Sign a new ENR with a node key
+    #[command(name = "new")]
+    New(NewEnrCommand),
+
+    /// Print the ENR of the node of a data directory, signed with its node key
+    #[command(name = "show")]
+    Show(ShowEnrCommand),
+}
+
+#[derive(Debug, Parser)]
+pub struct NewEnrCommand {
+    /// Raw secp256k1 key, generated there if the file doesn't exist
+    #[arg(long, value_name = "FILE")]
+    pub node_key: PathBuf,
+
+    #[command(flatten)]
+    pub fields: EnrFields,
+}
+
+#[derive(Debug, Parser)]
+pub struct ShowEnrCommand {
+    /// Data directory of the node [default: ~/.ream/<network>]
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub fields: EnrFields,
+}
+
+/// What goes into a built ENR.
+#[derive(Debug, Args)]
+pub struct EnrFields {
+    /// Network whose fork the `eth2` field advertises
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Directory of the chain config of a custom network
+    #[arg(long, value_name = "DIR")]
+    pub custom_config_dir: Option<PathBuf>,
+
+    /// Genesis validators root of the fork digest, needed for the `eth2` field of a custom
+    /// network
+    #[arg(long)]
+    pub genesis_validators_root: Option<B256>,
+
+    /// Epoch of the advertised fork [default: the epoch of the last scheduled fork]
+    #[arg(long)]
+    pub epoch: Option<u64>,
+
+    /// Public address, IPv4 or IPv6
+    #[arg(long)]
+    pub ip: Option<IpAddr>,
+
+    #[arg(long)]
+    pub tcp_port: Option<u16>,
+
+    #[arg(long)]
+    pub udp_port: Option<u16>,
+
+    #[arg(long)]
+    pub quic_port: Option<u16>,
+
+    /// Attestation subnets the node is subscribed to
+    #[arg(long, value_delimiter = ',', value_parser = parse_subnet::<SubnetId>)]
+    pub attnets: Vec<SubnetId>,
+
+    /// Sync committee subnets the node is subscribed to
+    #[arg(long, value_delimiter = ',', value_parser = parse_subnet::<SyncSubnetId>)]
+    pub syncnets: Vec<SyncSubnetId>,
+
+    /// Sequence number, to be raised for peers to replace an older record
+    #[arg(long, default_value_t = 1)]
+    pub seq: u64,
+}
+
+fn parse_subnet<T: TryFrom<u64, Error = String>>(id: &str) -> Result<T, String> {
+    id.parse::<u64>().map_err(|err| err.to_string())?.try_into()
+}
+
+impl EnrSubcommand {
+    pub fn execute(&self, out: &mut impl Write) -> Result<(), EnrError> {
+        match self {
+            EnrSubcommand::Decode { enr } => {
+                let enr = enr.parse::<Enr>().map_err(EnrError::Invalid)?;
+                describe_enr(&enr, out)
+            }
+            EnrSubcommand::New(command) => {
+                let key = load_or_generate_node_key(&command.node_key).map_err(|error| {
+                    EnrError::NodeKey {
+                        path: command.node_key.clone(),
+                        error,
+                    }
+                })?;
+                writeln!(out, "{}", command.fields.build(&key)?)?;
+                Ok(())
+            }
+            EnrSubcommand::Show(command) => {
+                let path =
+                    DataDir::resolve(command.datadir.as_deref(), command.fields.network).node_key();
+                // Showing the ENR of a node that never ran mustn't give it a key.
+                let key = load_node_key(&path).map_err(|error| EnrError::NodeKey {
+                    path: path.clone(),
+                    error,
+                })?;
+                writeln!(out, "{}", command.fields.build(&key)?)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl EnrFields {
+    /// The ENR of these fields signed with `key`. The `eth2` field is left out when the
+    /// genesis validators root of the network isn't known.
+    pub fn build(&self, key: &CombinedKey) -> Result<Enr, EnrError> {
+        let spec = NetworkSpec::load(self.network, self.custom_config_dir.as_deref())?;
+        let mut builder = Enr::builder();
+        builder.seq(self.seq);
+        match self.ip {
+            Some(IpAddr::V4(ip)) => {
+                builder.ip4(ip);
+                if let Some(port) = self.tcp_port {
+                    builder.tcp4(port);
+                }
+                if let Some(port) = self.udp_port {
+                    builder.udp4(port);
+                }
+                if let Some(port) = self.quic_port {
+                    builder.add_value(QUIC_ENR_KEY, &port);
+                }
+            }
+            Some(IpAddr::V6(ip)) => {
+                builder.ip6(ip);
+                if let Some(port) = self.tcp_port {
+                    builder.tcp6(port);
+                }
+                if let Some(port) = self.udp_port {
+                    builder.udp6(port);
+                }
+                if let Some(port) = self.quic_port {
+                    builder.add_value(QUIC6_ENR_KEY, &port);
+                }
+            }
+            None => {}
+        }
+
+        if let Some(genesis_validators_root) = self
+            .genesis_validators_root
+            .or(spec.genesis_validators_root)
+        {
+            let epoch = self.epoch.unwrap_or_else(|| {
+                spec.fork_schedule
+                    .forks()
+                    .iter()
+                    .map(|fork| fork.epoch)
+                    .filter(|epoch| *epoch != FAR_FUTURE_EPOCH)
+                    .max()
+                    .unwrap_or_default()
+            });
+            let enr_fork_id = spec
+                .fork_schedule
+                .enr_fork_id(epoch, genesis_validators_root);
+            builder.add_value(ETH2_ENR_KEY, &Bytes::from(enr_fork_id.as_ssz_bytes()));
+        }
+        let attnets = attnets_from_subnets(self.attnets.iter().copied());
+        builder.add_value(ATTNETS_ENR_KEY, &Bytes::from(encode_enr_bitfield(&attnets)));
+        let syncnets = syncnets_from_subnets(self.syncnets.iter().copied());
+        builder.add_value(
+            SYNCNETS_ENR_KEY,
+            &Bytes::from(encode_enr_bitfield(&syncnets)),
+        );
+        builder
+            .build(key)
+            .map_err(|err| EnrError::Build(err.to_string()))
+    }
+}
+
+/// Writes the fields of `enr` one per line, naming the network and fork of its `eth2` field
+/// when it's one of a public network.
+pub fn describe_enr(enr: &Enr, out: &mut impl Write) -> Result<(), EnrError> {
+    writeln!(out, "node id    0x{}", hex::encode(enr.node_id().raw()))?;
+    writeln!(out, "seq        {}", enr.seq())?;
+    writeln!(
+        out,
+        "public key 0x{}",
+        hex::encode(enr.public_key().encode())
+    )?;
+    if let Some(ip) = enr.ip4() {
+        writeln!(out, "ip         {ip}")?;
+    }
+    if let Some(ip) = enr.ip6() {
+        writeln!(out, "ip6        {ip}")?;
+    }
+    for (name, port) in [
+        ("tcp", enr.tcp4()),
+        ("udp", enr.udp4()),
+        ("quic", decodable::<u16>(enr, QUIC_ENR_KEY)?),
+        ("tcp6", enr.tcp6()),
+        ("udp6", enr.udp6()),
+        ("quic6", decodable::<u16>(enr, QUIC6_ENR_KEY)?),
+    ] {
+        if let Some(port) = port {
+            writeln!(out, "{name:<10} {port}")?;
+        }
+    }
+
+    if let Some(bytes) = decodable::<Bytes>(enr, ETH2_ENR_KEY)? {
+        let enr_fork_id = ENRForkID::from_ssz_bytes(&bytes)
+            .map_err(|err| EnrError::Invalid(format!("eth2 field: {err:?}")))?;
+        let fork = known_fork(&enr_fork_id).map_or_else(String::new, |(network, fork)| {
+            format!(" ({network} {fork})")
+        });
+        writeln!(
+            out,
+            "eth2       fork digest {}{fork}",
+            enr_fork_id.fork_digest
+        )?;
+        if enr_fork_id.next_fork_epoch == FAR_FUTURE_EPOCH {
+            writeln!(out, "           no next fork scheduled")?;
+        } else {
+            writeln!(
+                out,
+                "           next fork {} at epoch {}",
+                enr_fork_id.next_fork_version, enr_fork_id.next_fork_epoch
+            )?;
+        }
+    }
+    if let Some(bytes) = decodable::<Bytes>(enr, ATTNETS_ENR_KEY)? {
+        let attnets: AttestationSubnets = decode_enr_bitfield(&bytes)
+            .map_err(|err| EnrError::Invalid(format!("attnets field: {err:?}")))?;
+        writeln!(out, "attnets    {}", join(subnets_from_attnets(&attnets)))?;
+    }
+    if let Some(bytes) = decodable::<Bytes>(enr, SYNCNETS_ENR_KEY)? {
+        let syncnets: SyncCommitteeSubnets = decode_enr_bitfield(&bytes)
+            .map_err(|err| EnrError::Invalid(format!("syncnets field: {err:?}")))?;
+        writeln!(out, "syncnets   {}", join(subnets_from_syncnets(&syncnets)))?;
+    }
+    Ok(())
+}
+
+fn decodable<T: alloy_rlp::Decodable>(enr: &Enr, key: &str) -> Result<Option<T>, EnrError> {
+    enr.get_decodable::<T>(key)
+        .transpose()
+        .map_err(|err| EnrError::Invalid(format!("{key} field: {err}")))
+}
+
+fn join<T: ToString>(subnets: impl Iterator<Item = T>) -> String {
+    let subnets: Vec<String> = subnets.map(|subnet| subnet.to_string()).collect();
+    if subnets.is_empty() {
+        "none".to_string()
+    } else {
+        subnets.join(", ")
+    }
+}
+
+/// The public network and fork whose digest `enr_fork_id` carries.
+fn known_fork(enr_fork_id: &ENRForkID) -> Option<(String, String)> {
+    [
+        NetworkSpec::mainnet(),
+        NetworkSpec::holesky(),
+        NetworkSpec::sepolia(),
+    ]
+    .into_iter()
+    .find_map(|spec| {
+        let genesis_validators_root = spec.genesis_validators_root?;
+        spec.fork_schedule
+            .fork_digests(genesis_validators_root)
+            .into_iter()
+            .find(|entry| entry.fork_digest == enr_fork_id.fork_digest)
+            .map(|entry| (spec.name.clone(), entry.name.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_discv5::EnrKey;
+
+    use super::*;
+
+    fn fields(network: Network) -> EnrFields {
+        EnrFields {
+            network,
+            custom_config_dir: None,
+            genesis_validators_root: None,
+            epoch: None,
+            ip: Some("203.0.113.7".parse().unwrap()),
+            tcp_port: Some(9000),
+            udp_port: Some(9000),
+            quic_port: Some(9001),
+            attnets: vec![SubnetId::new(1).unwrap(), SubnetId::new(5).unwrap()],
+            syncnets: vec![],
+            seq: 3,
+        }
+    }
+
+    #[test]
+    fn test_new_and_decode() {
+        let enr = fields(Network::Mainnet)
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        let mut out = vec![];
+        EnrSubcommand::Decode {
+            enr: enr.to_base64(),
+        }
+        .execute(&mut out)
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("seq        3\n"));
+        assert!(out.contains("ip         203.0.113.7\n"));
+        assert!(out.contains("tcp        9000\n"));
+        assert!(out.contains("quic       9001\n"));
+        assert!(out.contains("(mainnet electra)\n"));
+        assert!(out.contains("attnets    1, 5\n"));
+        assert!(out.contains("syncnets   none\n"));
+
+        assert!(matches!(
+            EnrSubcommand::Decode {
+                enr: "enr:-invalid".to_string()
+            }
+            .execute(&mut vec![]),
+            Err(EnrError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_show() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("datadir");
+        let show = || {
+            EnrSubcommand::Show(ShowEnrCommand {
+                datadir: Some(dir.clone()),
+                fields: fields(Network::Holesky),
+            })
+            .execute(&mut vec![])
+        };
+        assert!(matches!(show(), Err(EnrError::NodeKey { .. })));
+
+        let key = load_or_generate_node_key(&DataDir::new(&dir).node_key()).unwrap();
+        let mut out = vec![];
+        EnrSubcommand::Show(ShowEnrCommand {
+            datadir: Some(dir.clone()),
+            fields: fields(Network::Holesky),
+        })
+        .execute(&mut out)
+        .unwrap();
+        let enr: Enr = String::from_utf8(out).unwrap().trim().parse().unwrap();
+        assert_eq!(enr.public_key().encode(), key.public().encode());
+    }
+}
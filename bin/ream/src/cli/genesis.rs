@@ -0,0 +1,202 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::B256;
+use clap::Parser;
+use ream_consensus::{deposit::DepositData, execution_payload_header::ExecutionPayloadHeader};
+use ssz::Encode;
+use tree_hash::TreeHash;
+
+use crate::{
+    config::network::{NetworkSpec, NetworkSpecError},
+    genesis::{build_genesis_state, interop_deposits, GenesisError, INTEROP_ETH1_BLOCK_HASH},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GenesisCommandError {
+    #[error(transparent)]
+    Output(#[from] io::Error),
+    #[error("{path}: {error}")]
+    File { path: PathBuf, error: io::Error },
+    #[error("invalid deposits {path}: {error}")]
+    Deposits {
+        path: PathBuf,
+        error: serde_yaml::Error,
+    },
+    #[error("invalid execution payload header {path}: {error}")]
+    ExecutionPayloadHeader {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    #[error(transparent)]
+    Network(#[from] NetworkSpecError),
+    #[error(transparent)]
+    Genesis(#[from] GenesisError),
+}
+
+#[derive(Debug, Parser)]
+pub struct GenesisCommand {
+    /// Directory of the chain config of the devnet, whose forks up to deneb must be at epoch 0
+    #[arg(long, value_name = "DIR")]
+    pub custom_config_dir: PathBuf,
+
+    /// Number of validators with the deterministic interop keys, each depositing 32 ETH
+    #[arg(
+        long,
+        required_unless_present = "deposits",
+        conflicts_with = "deposits"
+    )]
+    pub validator_count: Option<u64>,
+
+    /// YAML list of deposit data, each with `pubkey`, `withdrawal_credentials`, `amount` and
+    /// `signature`
+    #[arg(long, value_name = "FILE")]
+    pub deposits: Option<PathBuf>,
+
+    /// Unix time of genesis [default: now]
+    #[arg(long)]
+    pub genesis_time: Option<u64>,
+
+    /// Block hash the genesis state starts the eth1 data and randao mixes from
+    #[arg(long, default_value_t = INTEROP_ETH1_BLOCK_HASH)]
+    pub eth1_block_hash: B256,
+
+    /// JSON execution payload header of the genesis block of the execution layer, the default
+    /// header leaves the chain before the merge
+    #[arg(long, value_name = "FILE")]
+    pub execution_payload_header: Option<PathBuf>,
+
+    /// Where the SSZ encoded state is written
+    #[arg(short, long, default_value = "genesis.ssz")]
+    pub output: PathBuf,
+}
+
+impl GenesisCommand {
+    /// Builds the genesis state and writes it to `--output`, reporting its roots to `out`.
+    pub fn execute(&self, out: &mut impl Write) -> Result<(), GenesisCommandError> {
+        let spec = NetworkSpec::from_dir(&self.custom_config_dir)?;
+        let deposits = match (&self.deposits, self.validator_count) {
+            (Some(path), _) => {
+                serde_yaml::from_str::<Vec<DepositData>>(&read(path)?).map_err(|error| {
+                    GenesisCommandError::Deposits {
+                        path: path.clone(),
+                        error,
+                    }
+                })?
+            }
+            (None, count) => interop_deposits(
+                count.unwrap_or_default(),
+                spec.fork_schedule.genesis_fork().version,
+            ),
+        };
+        let execution_payload_header =
+            match &self.execution_payload_header {
+                Some(path) => serde_json::from_str::<ExecutionPayloadHeader>(&read(path)?)
+                    .map_err(|error| GenesisCommandError::ExecutionPayloadHeader {
+                        path: path.clone(),
+                        error,
+                    })?,
+                None => ExecutionPayloadHeader::default(),
+            };
+        let genesis_time = self.genesis_time.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+
+        let state = build_genesis_state(
+            &spec.fork_schedule,
+            genesis_time,
+            self.eth1_block_hash,
+            &deposits,
+            execution_payload_header,
+        )?;
+        fs::write(&self.output, state.as_ssz_bytes()).map_err(|error| {
+            GenesisCommandError::File {
+                path: self.output.clone(),
+                error,
+            }
+        })?;
+        writeln!(
+            out,
+            "Wrote {} with {} validators starting at {genesis_time}",
+            self.output.display(),
+            state.validators.len()
+        )?;
+        writeln!(
+            out,
+            "genesis validators root {}",
+            state.genesis_validators_root
+        )?;
+        writeln!(out, "genesis state root {}", state.tree_hash_root())?;
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> Result<String, GenesisCommandError> {
+    fs::read_to_string(path).map_err(|error| GenesisCommandError::File {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::aliases::B32;
+    use ream_consensus::beacon_state::BeaconState;
+    use ssz::Decode;
+
+    use super::*;
+
+    #[test]
+    fn test_genesis() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("testnet");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("config.yaml"),
+            "GENESIS_FORK_VERSION: 0x10000038\n\
+             ALTAIR_FORK_VERSION: 0x20000038\n\
+             ALTAIR_FORK_EPOCH: 0\n\
+             BELLATRIX_FORK_VERSION: 0x30000038\n\
+             BELLATRIX_FORK_EPOCH: 0\n\
+             CAPELLA_FORK_VERSION: 0x40000038\n\
+             CAPELLA_FORK_EPOCH: 0\n\
+             DENEB_FORK_VERSION: 0x50000038\n\
+             DENEB_FORK_EPOCH: 0\n\
+             DEPOSIT_CONTRACT_ADDRESS: 0x4242424242424242424242424242424242424242\n",
+        )
+        .unwrap();
+        let command = |validator_count, deposits| GenesisCommand {
+            custom_config_dir: dir.clone(),
+            validator_count,
+            deposits,
+            genesis_time: Some(1_700_000_000),
+            eth1_block_hash: INTEROP_ETH1_BLOCK_HASH,
+            execution_payload_header: None,
+            output: dir.join("genesis.ssz"),
+        };
+
+        let mut out = vec![];
+        command(Some(2), None).execute(&mut out).unwrap();
+        let interop = fs::read(dir.join("genesis.ssz")).unwrap();
+        let state = BeaconState::from_ssz_bytes(&interop).unwrap();
+        assert_eq!(state.validators.len(), 2);
+        assert_eq!(state.genesis_time, 1_700_000_000);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains(&format!("genesis state root {}", state.tree_hash_root())));
+
+        // The same deposits read from a file give the same state.
+        let deposits = dir.join("deposits.yaml");
+        let yaml =
+            serde_yaml::to_string(&interop_deposits(2, B32::from([0x10, 0, 0, 0x38]))).unwrap();
+        fs::write(&deposits, yaml).unwrap();
+        command(None, Some(deposits)).execute(&mut vec![]).unwrap();
+        assert_eq!(fs::read(dir.join("genesis.ssz")).unwrap(), interop);
+    }
+}
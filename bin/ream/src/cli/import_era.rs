@@ -0,0 +1,69 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use clap::Parser;
+use ream_storage::{
+    era::{import_era, Era},
+    hot_cold::StoreConfig,
+};
+
+use super::db::{open_store, DbError};
+use crate::config::{datadir::DataDir, Network};
+
+#[derive(Debug, Parser)]
+pub struct ImportEraCommand {
+    /// Network of the default data directory
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Data directory of the node [default: ~/.ream/<network>]
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+
+    /// Directory with the `.era` files to import
+    pub dir: PathBuf,
+}
+
+impl ImportEraCommand {
+    /// Imports every era file of `dir` in order of era number into the cold database, checking
+    /// each block against the block roots of its era state. The node must not be running.
+    pub fn execute(&self, out: &mut impl Write) -> Result<(), DbError> {
+        let datadir = DataDir::resolve(self.datadir.as_deref(), self.network);
+        let store = open_store(&datadir, StoreConfig::default())?;
+
+        // Era numbers are zero padded, so names sort by era.
+        let mut paths = fs::read_dir(&self.dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| path.extension().is_some_and(|extension| extension == "era"));
+        paths.sort();
+
+        let mut genesis_validators_root = None;
+        for path in paths {
+            let era = Era::read(&fs::read(&path)?).map_err(|error| DbError::Era {
+                path: path.clone(),
+                error,
+            })?;
+            let found = era.state.genesis_validators_root;
+            let expected = *genesis_validators_root.get_or_insert(found);
+            if found != expected {
+                return Err(DbError::WrongChain {
+                    path,
+                    expected,
+                    found,
+                });
+            }
+            import_era(&store, &era).map_err(|error| DbError::Era {
+                path: path.clone(),
+                error,
+            })?;
+            writeln!(
+                out,
+                "Imported era {} with {} blocks from {}",
+                era.number,
+                era.blocks.len(),
+                path.display()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use clap::{Parser, ValueEnum};
+
+use super::validation::did_you_mean;
+use crate::config::{datadir::default_datadir, Network, ReamConfig, DEFAULT_EXECUTION_ENDPOINT};
+
+#[derive(Debug, thiserror::Error)]
+pub enum InitError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("{0} already exists, pass --force to overwrite it")]
+    ConfigExists(PathBuf),
+    #[error("failed to serialize config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[derive(Debug, Parser)]
+pub struct InitCommand {
+    /// Network to join
+    #[arg(long)]
+    pub network: Option<Network>,
+
+    /// Directory for the config, node key and database [default: ~/.ream/<network>]
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+
+    /// Beacon node URL to checkpoint sync from
+    #[arg(long)]
+    pub checkpoint_sync_url: Option<String>,
+
+    /// Engine API endpoint of the execution client
+    #[arg(long)]
+    pub execution_endpoint: Option<String>,
+
+    /// Path to the JWT secret shared with the execution client
+    #[arg(long)]
+    pub execution_jwt_secret: Option<PathBuf>,
+
+    /// Don't prompt, use the defaults for anything not passed as a flag
+    #[arg(short = 'y', long)]
+    pub non_interactive: bool,
+
+    /// Overwrite an existing config file
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl InitCommand {
+    /// Asks for every setting not passed as a flag, then writes the config file and generates a
+    /// node key in the data directory. Returns the written config.
+    pub fn execute(&self) -> Result<ReamConfig, InitError> {
+        let stdin = io::stdin();
+        let mut prompter = Prompter::new(stdin.lock(), io::stdout(), !self.non_interactive);
+        let config = self.build_config(&mut prompter)?;
+
+        let config_path = config.config_path();
+        if config_path.exists() && !self.force {
+            return Err(InitError::ConfigExists(config_path));
+        }
+        let datadir = config.data_dir();
+        datadir.create()?;
+        fs::write(&config_path, toml::to_string(&config)?)?;
+        ream_discv5::node_key::load_or_generate_node_key(&datadir.node_key())?;
+
+        Ok(config)
+    }
+
+    pub fn build_config<R: BufRead, W: Write>(
+        &self,
+        prompter: &mut Prompter<R, W>,
+    ) -> io::Result<ReamConfig> {
+        let network = match self.network {
+            Some(network) => network,
+            None => prompter.ask_network()?,
+        };
+        let datadir = match &self.datadir {
+            Some(datadir) => datadir.clone(),
+            None => prompter
+                .ask(
+                    "Data directory",
+                    &default_datadir(network).display().to_string(),
+                )?
+                .into(),
+        };
+        let checkpoint_sync_url = match &self.checkpoint_sync_url {
+            Some(url) => Some(url.clone()),
+            None => prompter.ask_optional("Checkpoint sync URL (empty to sync from genesis)")?,
+        };
+        let execution_endpoint = match &self.execution_endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => prompter.ask("Execution engine endpoint", DEFAULT_EXECUTION_ENDPOINT)?,
+        };
+        let execution_jwt_secret = match &self.execution_jwt_secret {
+            Some(path) => Some(path.clone()),
+            None => prompter
+                .ask_optional("Path to the execution JWT secret")?
+                .map(PathBuf::from),
+        };
+
+        Ok(ReamConfig {
+            network,
+            datadir,
+            checkpoint_sync_url,
+            execution_endpoint,
+            execution_jwt_secret,
+            ..ReamConfig::default()
+        })
+    }
+}
+
+/// Line based prompts with defaults. When not interactive every question is answered with its
+/// default without reading input.
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+    interactive: bool,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W, interactive: bool) -> Self {
+        Self {
+            input,
+            output,
+            interactive,
+        }
+    }
+
+    fn read_answer(&mut self, question: &str) -> io::Result<Option<String>> {
+        if !self.interactive {
+            return Ok(None);
+        }
+        write!(self.output, "{question}: ")?;
+        self.output.flush()?;
+
+        let mut line = String::new();
+        self.input.read_line(&mut line)?;
+        let answer = line.trim();
+        Ok((!answer.is_empty()).then(|| answer.to_string()))
+    }
+
+    pub fn ask(&mut self, question: &str, default: &str) -> io::Result<String> {
+        Ok(self
+            .read_answer(&format!("{question} [{default}]"))?
+            .unwrap_or_else(|| default.to_string()))
+    }
+
+    pub fn ask_optional(&mut self, question: &str) -> io::Result<Option<String>> {
+        self.read_answer(question)
+    }
+
+    /// Asks until a known network is entered. Gives up on end of input rather than looping.
+    pub fn ask_network(&mut self) -> io::Result<Network> {
+        let names: Vec<String> = Network::value_variants()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let question = format!("Network ({})", names.join(", "));
+
+        loop {
+            let answer = self.ask(&question, &Network::default().to_string())?;
+            if let Ok(network) = Network::from_str(&answer, true) {
+                return Ok(network);
+            }
+
+            write!(self.output, "unknown network `{answer}`")?;
+            match did_you_mean(&answer, names.iter().map(String::as_str)) {
+                Some(suggestion) => writeln!(self.output, ", did you mean `{suggestion}`?")?,
+                None => writeln!(self.output)?,
+            }
+            if self.input.fill_buf()?.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "no network selected",
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> InitCommand {
+        InitCommand::parse_from(["init"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_flags_skip_prompts() {
+        let cmd = command(&[
+            "--network",
+            "sepolia",
+            "--datadir",
+            "/tmp/ream",
+            "--execution-jwt-secret",
+            "/tmp/jwt.hex",
+            "-y",
+        ]);
+        let mut prompter = Prompter::new(&b""[..], vec![], false);
+
+        let config = cmd.build_config(&mut prompter).unwrap();
+        assert_eq!(
+            config,
+            ReamConfig {
+                network: Network::Sepolia,
+                datadir: "/tmp/ream".into(),
+                checkpoint_sync_url: None,
+                execution_endpoint: DEFAULT_EXECUTION_ENDPOINT.to_string(),
+                execution_jwt_secret: Some("/tmp/jwt.hex".into()),
+                ..ReamConfig::default()
+            }
+        );
+        assert!(prompter.output.is_empty());
+    }
+
+    #[test]
+    fn test_interactive_answers() {
+        let cmd = command(&[]);
+        let input = b"holeski\nholesky\n/data/ream\nhttps://checkpoint.example\n\n\n";
+        let mut prompter = Prompter::new(&input[..], vec![], true);
+
+        let config = cmd.build_config(&mut prompter).unwrap();
+        assert_eq!(config.network, Network::Holesky);
+        assert_eq!(config.datadir, PathBuf::from("/data/ream"));
+        assert_eq!(
+            config.checkpoint_sync_url.as_deref(),
+            Some("https://checkpoint.example")
+        );
+        assert_eq!(config.execution_endpoint, DEFAULT_EXECUTION_ENDPOINT);
+        assert_eq!(config.execution_jwt_secret, None);
+
+        let output = String::from_utf8(prompter.output).unwrap();
+        assert!(output.contains("unknown network `holeski`, did you mean `holesky`?"));
+    }
+}
@@ -1,4 +1,55 @@
-use clap::{Parser, Subcommand};
+pub mod account;
+pub mod config;
+pub mod db;
+pub mod debug;
+pub mod doctor;
+pub mod enr;
+pub mod genesis;
+pub mod import_era;
+pub mod init;
+pub mod validation;
+pub mod validator;
+
+use std::{ffi::OsString, net::IpAddr, path::PathBuf, time::Duration};
+
+use account::AccountSubcommand;
+use alloy_primitives::Address;
+use clap::{
+    error::ErrorKind, ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
+use config::ConfigSubcommand;
+use db::DbCommand;
+use debug::DebugSubcommand;
+use doctor::DoctorCommand;
+use enr::EnrSubcommand;
+use genesis::GenesisCommand;
+use import_era::ImportEraCommand;
+use init::InitCommand;
+use ream_discv5::{dns_discovery::EnrTreeLink, Enr};
+use ream_execution::{auth::JwtSecret, client::EngineClient, error::ExecutionError};
+use ream_executor::runtimes::ExecutorConfig;
+use ream_metrics::server::MetricsServerConfig;
+use ream_rpc::{
+    config::{CorsOrigin, HttpServerConfig},
+    id::ValidatorId,
+};
+use ream_storage::{hot_cold::StoreConfig, pruning::PruningMode};
+use tracing_subscriber::filter::Directive;
+use url::Url;
+use validation::ValidationErrors;
+use validator::ValidatorCommand;
+
+use crate::{
+    client_stats::{MonitoringConfig, DEFAULT_MONITORING_INTERVAL},
+    config::{
+        datadir::DataDir,
+        file::{ConfigFile, EffectiveConfig},
+        network::{read_bootnodes, NetworkSpec, NetworkSpecError},
+        Network,
+    },
+    logging::{LogConfig, LogFileConfig, LogFormat, Rotation},
+    telemetry::TracingConfig,
+};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -7,22 +58,504 @@ pub struct Cli {
     pub command: Commands,
 }
 
+impl Cli {
+    /// Parses the arguments of the process like [`Parser::parse`], see
+    /// [`Cli::try_parse_with_config_from`].
+    pub fn parse_with_config() -> Self {
+        Self::try_parse_with_config_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Parses `args`, reading the node options left out from the `--config` file. Options of
+    /// the command line win over those of the file.
+    pub fn try_parse_with_config_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+        let mut command = Self::command();
+        let matches = command.try_get_matches_from_mut(&args)?;
+        let Some((depth, node)) = node_matches(&matches) else {
+            return Self::from_arg_matches(&matches);
+        };
+        let node_command = command
+            .find_subcommand("node")
+            .expect("the node subcommand exists")
+            .clone();
+
+        let mut from_file = vec![];
+        if let Some(path) = node.get_one::<PathBuf>("config") {
+            let file_args = ConfigFile::load(path)
+                .and_then(|file| file.args(&node_command, node))
+                .map_err(|err| command.error(ErrorKind::InvalidValue, err))?;
+            // The node options follow the names of the subcommands taking them.
+            let at = depth + 1;
+            args.splice(at..at, file_args.args.into_iter().map(OsString::from));
+            from_file = file_args.ids;
+        }
+
+        let matches = command.try_get_matches_from_mut(args)?;
+        let mut cli = Self::from_arg_matches(&matches)?;
+        if let (
+            Commands::Node(cmd) | Commands::Config(ConfigSubcommand::Dump(cmd)),
+            Some((_, node)),
+        ) = (&mut cli.command, node_matches(&matches))
+        {
+            cmd.effective_config = EffectiveConfig::new(&node_command, node, &from_file);
+        }
+        Ok(cli)
+    }
+}
+
+/// The matches of the node options and how deep their subcommand is, `None` for commands not
+/// taking them.
+fn node_matches(matches: &ArgMatches) -> Option<(usize, &ArgMatches)> {
+    match matches.subcommand()? {
+        ("node", node) => Some((1, node)),
+        ("config", config) => match config.subcommand()? {
+            ("dump", dump) => Some((2, dump)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Start the node
     #[command(name = "node")]
     Node(NodeCommand),
+
+    /// Create a config file and node key for a new node
+    #[command(name = "init")]
+    Init(InitCommand),
+
+    /// Inspect and maintain the database of a stopped node
+    #[command(name = "db")]
+    Db(DbCommand),
+
+    /// Import finalized history from era files
+    #[command(name = "import-era")]
+    ImportEra(ImportEraCommand),
+
+    /// Run a validator client against a beacon node
+    #[command(name = "validator")]
+    Validator(Box<ValidatorCommand>),
+
+    /// Create validator keys and their deposits
+    #[command(name = "account", subcommand)]
+    Account(AccountSubcommand),
+
+    /// Inspect the node configuration
+    #[command(name = "config", subcommand)]
+    Config(ConfigSubcommand),
+
+    /// Build the genesis state of a devnet
+    #[command(name = "genesis")]
+    Genesis(GenesisCommand),
+
+    /// Tools for debugging interop issues and test fixtures
+    #[command(name = "debug", subcommand)]
+    Debug(DebugSubcommand),
+
+    /// Decode, create and show node records, for debugging discovery
+    #[command(name = "enr", subcommand)]
+    Enr(EnrSubcommand),
+
+    /// Check the host is ready to run a node and tell how to fix what isn't
+    #[command(name = "doctor")]
+    Doctor(DoctorCommand),
 }
 
 #[derive(Debug, Parser)]
 pub struct NodeCommand {
-    /// Verbosity level
+    /// TOML file of node options keyed by flag name, e.g. `http_port = 5052`, overridden by the
+    /// flags given on the command line
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Network to join
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Data directory of the databases, node key and logs [default: ~/.ream/<network>]
+    #[arg(long, value_name = "DIR")]
+    pub datadir: Option<PathBuf>,
+
+    /// Directory of the chain config of a custom network: `config.yaml`, and optionally
+    /// `deploy_block.txt` and `bootstrap_nodes.txt`
+    #[arg(long, value_name = "DIR")]
+    pub custom_config_dir: Option<PathBuf>,
+
+    /// Verbosity level, from 1 for errors only to 5 for traces
     #[arg(short, long, default_value_t = 3)]
     pub verbosity: u8,
+
+    /// Log levels of single modules applied over the verbosity, comma separated, e.g.
+    /// `ream_discv5=debug,ream_rpc=warn`
+    #[arg(long, value_delimiter = ',', value_name = "DIRECTIVES")]
+    pub log_filter: Vec<String>,
+
+    /// Format of the logs
+    #[arg(long, default_value = "plain", value_name = "FORMAT")]
+    pub log_format: LogFormat,
+
+    /// File to write the logs to as well as the terminal
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Size past which the log file rotates, in MiB, 0 for no limit
+    #[arg(long, default_value_t = 100, value_name = "MIB")]
+    pub log_max_size: u64,
+
+    /// How often the log file rotates whatever its size
+    #[arg(long, default_value = "daily", value_name = "PERIOD")]
+    pub log_rotation: Rotation,
+
+    /// Rotated log files to keep
+    #[arg(long, default_value_t = 5, value_name = "COUNT")]
+    pub log_max_files: usize,
+
+    /// OTLP gRPC endpoint of a collector such as Jaeger or Tempo to export the tracing spans
+    /// to, e.g. `http://localhost:4317`
+    #[arg(long, value_name = "URL")]
+    pub tracing_endpoint: Option<Url>,
+
+    /// Share of traces exported, from 0 to 1
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        value_name = "RATIO",
+        requires = "tracing_endpoint"
+    )]
+    pub tracing_sample_ratio: f64,
+
+    /// ENRs to start discovery from instead of the bootnodes of the network, comma separated,
+    /// each one either an ENR or a file listing one per line
+    #[arg(long, value_delimiter = ',', value_name = "ENRS")]
+    pub bootnodes: Vec<String>,
+
+    /// EIP-1459 ENR tree to discover peers from when discv5 finds too few
+    #[arg(long = "discovery-dns", value_name = "ENRTREE")]
+    pub discovery_dns: Vec<String>,
+
+    /// Finalized history to keep: `archive` or `prune:<retention epochs>`
+    #[arg(long, default_value = "archive", value_name = "MODE")]
+    pub pruning: PruningMode,
+
+    /// Delete blob sidecars once they leave the data availability window, set to false to keep
+    /// them forever
+    #[arg(long, default_value_t = true, action = ArgAction::Set, value_name = "BOOL")]
+    pub prune_blobs: bool,
+
+    /// Memory budget for recently used states, in MiB
+    #[arg(long, default_value_t = 1024, value_name = "MIB")]
+    pub state_cache_size: usize,
+
+    /// Worker threads of the runtime networking and most other tasks run on, one per core by
+    /// default
+    #[arg(long, value_name = "COUNT")]
+    pub worker_threads: Option<usize>,
+
+    /// Threads for blocking work like state transitions and BLS verification, one per core by
+    /// default
+    #[arg(long, value_name = "COUNT")]
+    pub blocking_threads: Option<usize>,
+
+    /// Worker threads of the runtime kept for slot-critical duties, one by default
+    #[arg(long, value_name = "COUNT")]
+    pub duty_threads: Option<usize>,
+
+    /// Serve the beacon node HTTP API
+    #[arg(long)]
+    pub http: bool,
+
+    /// Address the HTTP API listens on
+    #[arg(long, default_value = "127.0.0.1", value_name = "ADDRESS")]
+    pub http_address: IpAddr,
+
+    /// Port the HTTP API listens on
+    #[arg(long, default_value_t = 5052, value_name = "PORT")]
+    pub http_port: u16,
+
+    /// Origins browsers may call the HTTP API from, comma separated, `*` for any
+    #[arg(long, value_delimiter = ',', value_name = "ORIGINS")]
+    pub http_allow_origins: Vec<CorsOrigin>,
+
+    /// Largest request body the HTTP API accepts, in MiB
+    #[arg(long, default_value_t = 16, value_name = "MIB")]
+    pub http_max_body_size: usize,
+
+    /// Requests the HTTP API handles at once, further requests wait
+    #[arg(long, default_value_t = 64, value_name = "COUNT")]
+    pub http_max_concurrent_requests: usize,
+
+    /// Seconds the HTTP API has to answer a request before it times out
+    #[arg(long, default_value_t = 30, value_name = "SECONDS")]
+    pub http_timeout: u64,
+
+    /// Serve Prometheus metrics
+    #[arg(long)]
+    pub metrics: bool,
+
+    /// Address the metrics listen on
+    #[arg(long, default_value = "127.0.0.1", value_name = "ADDRESS")]
+    pub metrics_address: IpAddr,
+
+    /// Port the metrics listen on
+    #[arg(long, default_value_t = 5054, value_name = "PORT")]
+    pub metrics_port: u16,
+
+    /// Validators whose proposals, attestations, sync committee messages and balances are
+    /// reported in the logs and metrics, comma separated indices or public keys
+    #[arg(long, value_delimiter = ',', value_name = "VALIDATORS")]
+    pub validator_monitor: Vec<ValidatorId>,
+
+    /// URL to POST the stats of the node to in the client-stats format, e.g. the one
+    /// beaconcha.in gives for its mobile app
+    #[arg(long, value_name = "URL")]
+    pub monitoring_endpoint: Option<Url>,
+
+    /// Seconds between two reports to the monitoring endpoint
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MONITORING_INTERVAL,
+        value_name = "SECONDS",
+        requires = "monitoring_endpoint"
+    )]
+    pub monitoring_interval: u64,
+
+    /// Authenticated engine API endpoints of the execution layers, comma separated, the first
+    /// one preferred and the others used while it's unreachable, forkchoice updates go to all
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "URLS",
+        requires = "execution_jwt"
+    )]
+    pub execution_endpoint: Vec<Url>,
+
+    /// Files holding the hex encoded JWT secrets shared with the execution layers, comma
+    /// separated, either one for all endpoints or one per endpoint in the same order
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "PATHS",
+        requires = "execution_endpoint"
+    )]
+    pub execution_jwt: Vec<PathBuf>,
+
+    /// Address the execution fees of proposed blocks are paid to unless the validator client
+    /// prepared another one, they are burnt without one
+    #[arg(long, value_name = "ADDRESS")]
+    pub suggested_fee_recipient: Option<Address>,
+
+    /// The value of every option and where it came from, filled by
+    /// [`Cli::try_parse_with_config_from`].
+    #[arg(skip)]
+    pub effective_config: EffectiveConfig,
+}
+
+impl NodeCommand {
+    /// Checks the arguments for semantic problems clap can't catch on its own, reporting all of
+    /// them at once.
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if !(1..=5).contains(&self.verbosity) {
+            errors.push(
+                "--verbosity",
+                format!("must be between 1 and 5, got {}", self.verbosity),
+            );
+        }
+
+        for directive in &self.log_filter {
+            if let Err(err) = directive.parse::<Directive>() {
+                errors.push(
+                    "--log-filter",
+                    format!("invalid directive `{directive}`: {err}"),
+                );
+            }
+        }
+
+        for enr in self
+            .bootnodes
+            .iter()
+            .filter(|source| source.starts_with("enr:"))
+        {
+            if let Err(err) = enr.parse::<Enr>() {
+                errors.push("--bootnodes", format!("invalid ENR {enr}: {err}"));
+            }
+        }
+
+        for link in &self.discovery_dns {
+            if let Err(err) = link.parse::<EnrTreeLink>() {
+                errors.push("--discovery-dns", err.to_string());
+            }
+        }
+
+        match (self.network, &self.custom_config_dir) {
+            (Network::Custom, None) => errors.push("--network", "custom needs --custom-config-dir"),
+            (network, Some(_)) if network != Network::Custom => {
+                errors.push("--custom-config-dir", "only read with --network custom")
+            }
+            _ => {}
+        }
+
+        if !(0.0..=1.0).contains(&self.tracing_sample_ratio) {
+            errors.push(
+                "--tracing-sample-ratio",
+                format!("must be between 0 and 1, got {}", self.tracing_sample_ratio),
+            );
+        }
+
+        if self.http_max_concurrent_requests == 0 {
+            errors.push("--http-max-concurrent-requests", "must be at least 1");
+        }
+
+        if self.http_timeout == 0 {
+            errors.push("--http-timeout", "must be at least 1");
+        }
+
+        if self.monitoring_interval == 0 {
+            errors.push("--monitoring-interval", "must be at least 1");
+        }
+
+        errors.into_result()
+    }
+
+    pub fn log_config(&self) -> LogConfig {
+        LogConfig {
+            verbosity: self.verbosity,
+            format: self.log_format,
+            directives: self.log_filter.clone(),
+            file: self.log_file.clone().map(|path| LogFileConfig {
+                path,
+                max_size: self.log_max_size * 1024 * 1024,
+                rotation: self.log_rotation,
+                max_files: self.log_max_files,
+            }),
+            tracing: self.tracing_endpoint.clone().map(|endpoint| TracingConfig {
+                endpoint,
+                sample_ratio: self.tracing_sample_ratio,
+            }),
+        }
+    }
+
+    pub fn datadir(&self) -> DataDir {
+        DataDir::resolve(self.datadir.as_deref(), self.network)
+    }
+
+    pub fn store_config(&self) -> StoreConfig {
+        StoreConfig {
+            pruning: self.pruning,
+            state_cache_bytes: self.state_cache_size * 1024 * 1024,
+            ..StoreConfig::default()
+        }
+    }
+
+    /// Chain settings of `--network`.
+    pub fn network_spec(&self) -> Result<NetworkSpec, NetworkSpecError> {
+        NetworkSpec::load(self.network, self.custom_config_dir.as_deref())
+    }
+
+    /// ENRs to start discovery from, those of `--bootnodes` or else the bootnodes of `spec`.
+    pub fn bootnodes(&self, spec: &NetworkSpec) -> Result<Vec<Enr>, NetworkSpecError> {
+        let records = if self.bootnodes.is_empty() {
+            spec.bootnodes.clone()
+        } else {
+            read_bootnodes(&self.bootnodes)?
+        };
+        records
+            .into_iter()
+            .map(|enr| {
+                enr.parse()
+                    .map_err(|message| NetworkSpecError::InvalidBootnode { enr, message })
+            })
+            .collect()
+    }
+
+    /// Client of the execution layers, `None` unless `--execution-endpoint` is set. Fails when
+    /// a JWT secret can't be read or there are neither one nor as many as endpoints.
+    pub fn engine_client(&self) -> Result<Option<EngineClient>, ExecutionError> {
+        if self.execution_endpoint.is_empty() {
+            return Ok(None);
+        }
+        let secrets = match self.execution_jwt.as_slice() {
+            [jwt] => vec![JwtSecret::from_file(jwt)?; self.execution_endpoint.len()],
+            jwts if jwts.len() == self.execution_endpoint.len() => jwts
+                .iter()
+                .map(|jwt| JwtSecret::from_file(jwt))
+                .collect::<Result<_, _>>()?,
+            jwts => {
+                return Err(ExecutionError::JwtSecret(format!(
+                    "{} secrets for {} execution endpoints, expected one or one each",
+                    jwts.len(),
+                    self.execution_endpoint.len()
+                )))
+            }
+        };
+        Ok(Some(EngineClient::with_endpoints(
+            self.execution_endpoint
+                .iter()
+                .cloned()
+                .zip(secrets)
+                .collect(),
+        )))
+    }
+
+    /// Threads of the runtimes the node's tasks run on.
+    pub fn executor_config(&self) -> ExecutorConfig {
+        ExecutorConfig {
+            worker_threads: self.worker_threads,
+            blocking_threads: self.blocking_threads,
+            duty_threads: self.duty_threads,
+        }
+    }
+
+    /// Settings of the metrics server, `None` unless `--metrics` is set.
+    pub fn metrics_config(&self) -> Option<MetricsServerConfig> {
+        metrics_config(self.metrics, self.metrics_address, self.metrics_port)
+    }
+
+    /// Where and how often the client stats are sent, `None` unless `--monitoring-endpoint` is
+    /// set.
+    pub fn monitoring_config(&self) -> Option<MonitoringConfig> {
+        monitoring_config(self.monitoring_endpoint.as_ref(), self.monitoring_interval)
+    }
+
+    /// Settings of the HTTP API, `None` unless `--http` is set.
+    pub fn http_config(&self) -> Option<HttpServerConfig> {
+        self.http.then(|| HttpServerConfig {
+            address: self.http_address,
+            port: self.http_port,
+            allow_origins: self.http_allow_origins.clone(),
+            max_body_size: self.http_max_body_size * 1024 * 1024,
+            max_concurrent_requests: self.http_max_concurrent_requests,
+            request_timeout: Duration::from_secs(self.http_timeout),
+        })
+    }
+}
+
+/// Settings of the metrics server of the node or the validator client, `None` unless
+/// `--metrics` is set.
+pub fn metrics_config(enabled: bool, address: IpAddr, port: u16) -> Option<MetricsServerConfig> {
+    enabled.then_some(MetricsServerConfig { address, port })
+}
+
+/// Where and how often the node or the validator client send their client stats, `None`
+/// without an endpoint.
+pub fn monitoring_config(endpoint: Option<&Url>, interval: u64) -> Option<MonitoringConfig> {
+    endpoint.map(|endpoint| MonitoringConfig {
+        endpoint: endpoint.clone(),
+        interval: Duration::from_secs(interval),
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use ream_consensus::fork_schedule::ForkSchedule;
+
     use super::*;
 
     #[test]
@@ -33,6 +566,582 @@ mod tests {
             Commands::Node(cmd) => {
                 assert_eq!(cmd.verbosity, 2);
             }
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_pruning() {
+        let cli = Cli::parse_from(["program", "node"]);
+        let pruned = Cli::parse_from(["program", "node", "--pruning", "prune:100"]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(cmd.pruning, PruningMode::Archive),
+            _ => panic!("expected the node command"),
+        }
+        match pruned.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.pruning,
+                PruningMode::Prune {
+                    retention_epochs: 100
+                }
+            ),
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_prune_blobs() {
+        let cli = Cli::parse_from(["program", "node"]);
+        let archive = Cli::parse_from(["program", "node", "--prune-blobs=false"]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert!(cmd.prune_blobs),
+            _ => panic!("expected the node command"),
+        }
+        match archive.command {
+            Commands::Node(cmd) => assert!(!cmd.prune_blobs),
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_http_config() {
+        let cli = Cli::parse_from(["program", "node", "--http", "--http-port", "5053"]);
+        let disabled = Cli::parse_from(["program", "node"]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.http_config(),
+                Some(HttpServerConfig {
+                    port: 5053,
+                    ..HttpServerConfig::default()
+                })
+            ),
+            _ => panic!("expected the node command"),
+        }
+        match disabled.command {
+            Commands::Node(cmd) => assert_eq!(cmd.http_config(), None),
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_executor_config() {
+        let cli = Cli::parse_from(["program", "node", "--blocking-threads", "4"]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.executor_config(),
+                ExecutorConfig {
+                    blocking_threads: Some(4),
+                    ..ExecutorConfig::default()
+                }
+            ),
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_metrics_config() {
+        let cli = Cli::parse_from(["program", "node", "--metrics", "--metrics-port", "9100"]);
+        let disabled = Cli::parse_from(["program", "node", "--metrics-port", "9100"]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.metrics_config(),
+                Some(MetricsServerConfig {
+                    port: 9100,
+                    ..MetricsServerConfig::default()
+                })
+            ),
+            _ => panic!("expected the node command"),
+        }
+        match disabled.command {
+            Commands::Node(cmd) => assert_eq!(cmd.metrics_config(), None),
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_validator_monitor() {
+        let pubkey = format!("0x{}", "ab".repeat(48));
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--validator-monitor",
+            &format!("3,{pubkey}"),
+        ]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.validator_monitor,
+                vec![
+                    ValidatorId::Index(3),
+                    ValidatorId::Pubkey(pubkey.parse().unwrap())
+                ]
+            ),
+            _ => panic!("expected the node command"),
+        }
+        assert!(Cli::try_parse_from(["program", "node", "--validator-monitor", "0x12"]).is_err());
+    }
+
+    #[test]
+    fn test_monitoring_config() {
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--monitoring-endpoint",
+            "https://beaconcha.in/api/v1/client/metrics?apikey=key",
+            "--monitoring-interval",
+            "30",
+        ]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.monitoring_config(),
+                Some(MonitoringConfig {
+                    endpoint: "https://beaconcha.in/api/v1/client/metrics?apikey=key"
+                        .parse()
+                        .unwrap(),
+                    interval: Duration::from_secs(30),
+                })
+            ),
+            _ => panic!("expected the node command"),
+        }
+        assert!(Cli::try_parse_from(["program", "node", "--monitoring-interval", "30"]).is_err());
+    }
+
+    #[test]
+    fn test_tracing_config() {
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--tracing-endpoint",
+            "http://localhost:4317",
+            "--tracing-sample-ratio",
+            "0.1",
+        ]);
+        let invalid = Cli::parse_from([
+            "program",
+            "node",
+            "--tracing-endpoint",
+            "http://localhost:4317",
+            "--tracing-sample-ratio",
+            "2",
+        ]);
+
+        match cli.command {
+            Commands::Node(cmd) => assert_eq!(
+                cmd.log_config().tracing,
+                Some(TracingConfig {
+                    endpoint: "http://localhost:4317".parse().unwrap(),
+                    sample_ratio: 0.1,
+                })
+            ),
+            _ => panic!("expected the node command"),
+        }
+        match invalid.command {
+            Commands::Node(cmd) => {
+                let errors = cmd.validate().unwrap_err();
+                assert_eq!(errors.errors()[0].arg, "--tracing-sample-ratio");
+            }
+            _ => panic!("expected the node command"),
+        }
+        assert!(Cli::try_parse_from(["program", "node", "--tracing-sample-ratio", "0.5"]).is_err());
+    }
+
+    #[test]
+    fn test_http_hardening_flags() {
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--http",
+            "--http-allow-origins",
+            "http://localhost:3000,*",
+            "--http-max-body-size",
+            "4",
+            "--http-timeout",
+            "5",
+        ]);
+
+        match cli.command {
+            Commands::Node(cmd) => {
+                let config = cmd.http_config().unwrap();
+                assert_eq!(
+                    config.allow_origins,
+                    vec!["http://localhost:3000".parse().unwrap(), CorsOrigin::Any]
+                );
+                assert_eq!(config.max_body_size, 4 * 1024 * 1024);
+                assert_eq!(config.request_timeout, Duration::from_secs(5));
+            }
+            _ => panic!("expected the node command"),
+        }
+        assert!(
+            Cli::try_parse_from(["program", "node", "--http-allow-origins", "localhost"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_execution_flags() {
+        let cli = Cli::parse_from(["program", "node"]);
+        match cli.command {
+            Commands::Node(cmd) => assert!(cmd.engine_client().unwrap().is_none()),
+            _ => panic!("expected the node command"),
+        }
+
+        // The endpoint is useless without the secret and the other way around.
+        assert!(Cli::try_parse_from([
+            "program",
+            "node",
+            "--execution-endpoint",
+            "http://localhost:8551"
+        ])
+        .is_err());
+        assert!(
+            Cli::try_parse_from(["program", "node", "--execution-jwt", "/tmp/jwt.hex"]).is_err()
+        );
+
+        // One secret serves every endpoint, otherwise each needs its own.
+        let temp = tempfile::tempdir().unwrap();
+        let jwt = temp.path().join("jwt.hex");
+        std::fs::write(&jwt, "0x".to_string() + &"01".repeat(32)).unwrap();
+        let jwt = jwt.to_str().unwrap();
+        let endpoints = "http://localhost:8551,http://localhost:9551";
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--execution-endpoint",
+            endpoints,
+            "--execution-jwt",
+            jwt,
+        ]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                let engine = cmd.engine_client().unwrap().unwrap();
+                assert_eq!(engine.endpoints().count(), 2);
+                assert_eq!(engine.endpoint().port(), Some(8551));
+            }
+            _ => panic!("expected the node command"),
+        }
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--execution-endpoint",
+            endpoints,
+            "--execution-jwt",
+            &[jwt; 3].join(","),
+        ]);
+        match cli.command {
+            Commands::Node(cmd) => assert!(matches!(
+                cmd.engine_client(),
+                Err(ExecutionError::JwtSecret(_))
+            )),
+            _ => panic!("expected the node command"),
+        }
+
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--suggested-fee-recipient",
+            "0x0101010101010101010101010101010101010101",
+        ]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                assert_eq!(cmd.suggested_fee_recipient, Some(Address::repeat_byte(1)))
+            }
+            _ => panic!("expected the node command"),
+        }
+        assert!(
+            Cli::try_parse_from(["program", "node", "--suggested-fee-recipient", "0x01"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_validator_command() {
+        let cli = Cli::parse_from(["program", "validator", "--secret-keys", "keys.txt"]);
+        match cli.command {
+            Commands::Validator(cmd) => {
+                assert_eq!(cmd.beacon_node_url.as_str(), "http://127.0.0.1:5052/");
+                assert_eq!(cmd.secret_keys, Some(PathBuf::from("keys.txt")));
+                assert_eq!(cmd.keystore_locations(), None);
+                assert_eq!(cmd.graffiti, None);
+                assert_eq!(cmd.proposer_config, None);
+            }
+            _ => panic!("expected the validator command"),
+        }
+        assert!(Cli::try_parse_from(["program", "validator"]).is_err());
+
+        let cli = Cli::parse_from([
+            "program",
+            "validator",
+            "--secret-keys",
+            "keys.txt",
+            "--suggested-fee-recipient",
+            "0x0101010101010101010101010101010101010101",
+            "--gas-limit",
+            "36000000",
+        ]);
+        match cli.command {
+            Commands::Validator(cmd) => {
+                let config = cmd.load_proposer_config().unwrap();
+                let pubkey = Default::default();
+                assert_eq!(config.fee_recipient(&pubkey), Some(Address::repeat_byte(1)));
+                assert_eq!(config.gas_limit(&pubkey), 36_000_000);
+            }
+            _ => panic!("expected the validator command"),
+        }
+
+        let cli = Cli::parse_from([
+            "program",
+            "validator",
+            "--validators-dir",
+            "validators",
+            "--password-file",
+            "password.txt",
+            "--graffiti",
+            "ream",
+        ]);
+        match cli.command {
+            Commands::Validator(cmd) => {
+                let locations = cmd.keystore_locations().unwrap();
+                assert_eq!(locations.validators_dir, PathBuf::from("validators"));
+                assert_eq!(locations.secrets_dir, None);
+                assert_eq!(locations.password_file, Some(PathBuf::from("password.txt")));
+                assert_eq!(cmd.graffiti.unwrap().to_string(), "ream");
+            }
+            _ => panic!("expected the validator command"),
+        }
+        assert!(Cli::try_parse_from([
+            "program",
+            "validator",
+            "--secret-keys",
+            "keys.txt",
+            "--password-file",
+            "password.txt"
+        ])
+        .is_err());
+
+        // A remote signer can hold every key.
+        assert!(Cli::try_parse_from([
+            "program",
+            "validator",
+            "--web3signer-url",
+            "http://localhost:9000"
+        ])
+        .is_ok());
+        assert!(Cli::try_parse_from([
+            "program",
+            "validator",
+            "--secret-keys",
+            "keys.txt",
+            "--web3signer-ca-cert",
+            "ca.pem"
+        ])
+        .is_err());
+
+        let cli = Cli::parse_from([
+            "program",
+            "validator",
+            "--secret-keys",
+            "keys.txt",
+            "--datadir",
+            "/tmp/ream",
+            "--keymanager",
+        ]);
+        match cli.command {
+            Commands::Validator(cmd) => {
+                assert_eq!(cmd.keymanager_port, 5062);
+                assert_eq!(
+                    cmd.keymanager_token_path(),
+                    PathBuf::from("/tmp/ream/keys/api-token.txt")
+                );
+            }
+            _ => panic!("expected the validator command"),
+        }
+        assert!(Cli::try_parse_from([
+            "program",
+            "validator",
+            "--secret-keys",
+            "keys.txt",
+            "--keymanager-port",
+            "7500"
+        ])
+        .is_err());
+
+        // Moving the slashing protection history doesn't need keys.
+        let cli = Cli::parse_from([
+            "program",
+            "validator",
+            "--datadir",
+            "/tmp/ream",
+            "slashing-protection",
+            "export",
+            "interchange.json",
+        ]);
+        match cli.command {
+            Commands::Validator(cmd) => {
+                assert_eq!(
+                    cmd.slashing_protection_db_path(),
+                    PathBuf::from("/tmp/ream/keys/slashing_protection.redb")
+                );
+                assert!(matches!(
+                    cmd.command,
+                    Some(validator::ValidatorSubcommand::SlashingProtection(
+                        validator::SlashingProtectionCommand::Export { .. }
+                    ))
+                ));
+            }
+            _ => panic!("expected the validator command"),
+        }
+    }
+
+    #[test]
+    fn test_network_flags() {
+        let cli = Cli::parse_from(["program", "node", "--network", "sepolia"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                assert!(cmd.validate().is_ok());
+                let spec = cmd.network_spec().unwrap();
+                assert_eq!(spec.fork_schedule, ForkSchedule::sepolia());
+            }
+            _ => panic!("expected the node command"),
+        }
+
+        let cli = Cli::parse_from(["program", "node", "--network", "custom"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                let errors = cmd.validate().unwrap_err();
+                assert_eq!(errors.errors()[0].arg, "--network");
+            }
+            _ => panic!("expected the node command"),
+        }
+        let cli = Cli::parse_from(["program", "node", "--custom-config-dir", "devnet"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                let errors = cmd.validate().unwrap_err();
+                assert_eq!(errors.errors()[0].arg, "--custom-config-dir");
+            }
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_config_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("ream.toml");
+        std::fs::write(
+            &path,
+            "network = \"sepolia\"\nhttp = true\nhttp_port = 6000\n",
+        )
+        .unwrap();
+        let config = path.to_str().unwrap();
+
+        let cli = Cli::try_parse_with_config_from([
+            "program",
+            "node",
+            "--config",
+            config,
+            "--http-port",
+            "7000",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Node(cmd) => {
+                assert_eq!(cmd.network, Network::Sepolia);
+                assert!(cmd.http);
+                assert_eq!(cmd.http_port, 7000);
+            }
+            _ => panic!("expected the node command"),
+        }
+
+        let cli =
+            Cli::try_parse_with_config_from(["program", "config", "dump", "--config", config])
+                .unwrap();
+        match cli.command {
+            Commands::Config(ConfigSubcommand::Dump(cmd)) => {
+                let dump = cmd.effective_config.to_string();
+                assert!(dump.contains("network = \"sepolia\" # config file\n"));
+                assert!(dump.contains("http_port = 6000 # config file\n"));
+                assert!(dump.contains("http_address = \"127.0.0.1\" # default\n"));
+            }
+            _ => panic!("expected the config dump command"),
+        }
+
+        std::fs::write(&path, "http_prot = 6000\n").unwrap();
+        let err =
+            Cli::try_parse_with_config_from(["program", "node", "--config", config]).unwrap_err();
+        assert!(err.to_string().contains("did you mean `http_port`?"));
+    }
+
+    #[test]
+    fn test_bootnodes() {
+        let spec = NetworkSpec::holesky();
+        let cli = Cli::parse_from(["program", "node", "--network", "holesky"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                assert_eq!(cmd.bootnodes(&spec).unwrap().len(), spec.bootnodes.len());
+            }
+            _ => panic!("expected the node command"),
+        }
+
+        let enrs = NetworkSpec::sepolia().bootnodes[..2].join(",");
+        let cli = Cli::parse_from(["program", "node", "--bootnodes", &enrs]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                assert!(cmd.validate().is_ok());
+                assert_eq!(cmd.bootnodes(&spec).unwrap().len(), 2);
+            }
+            _ => panic!("expected the node command"),
+        }
+
+        let cli = Cli::parse_from(["program", "node", "--bootnodes", "enr:-invalid"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                let errors = cmd.validate().unwrap_err();
+                assert_eq!(errors.errors()[0].arg, "--bootnodes");
+            }
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_node_datadir() {
+        let cli = Cli::parse_from(["program", "node", "--datadir", "/data/ream"]);
+        match cli.command {
+            Commands::Node(cmd) => {
+                let datadir = cmd.datadir();
+                assert_eq!(datadir.hot_db(), PathBuf::from("/data/ream/db/hot.redb"));
+                assert_eq!(
+                    datadir.node_key(),
+                    PathBuf::from("/data/ream/network/node_key")
+                );
+            }
+            _ => panic!("expected the node command"),
+        }
+    }
+
+    #[test]
+    fn test_node_command_validation() {
+        let cli = Cli::parse_from([
+            "program",
+            "node",
+            "--verbosity",
+            "9",
+            "--log-filter",
+            "ream_rpc=loud",
+            "--discovery-dns",
+            "enrtree://invalid@nodes.example.org",
+        ]);
+
+        match cli.command {
+            Commands::Node(cmd) => {
+                let errors = cmd.validate().unwrap_err();
+                assert_eq!(errors.errors()[0].arg, "--verbosity");
+                assert_eq!(errors.errors()[1].arg, "--log-filter");
+                assert_eq!(errors.errors()[2].arg, "--discovery-dns");
+            }
+            _ => panic!("expected the node command"),
         }
     }
 }
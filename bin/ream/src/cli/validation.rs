@@ -0,0 +1,112 @@
+use std::fmt;
+
+/// Minimum similarity for a candidate to be suggested, the same threshold clap uses for its own
+/// subcommand and flag suggestions.
+const SUGGESTION_THRESHOLD: f64 = 0.7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The flag the problem was found in, e.g. `--verbosity`.
+    pub arg: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.arg, self.message)
+    }
+}
+
+/// Every problem found in the configuration, so they can be reported in one go instead of
+/// failing on the first.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, arg: &'static str, message: impl Into<String>) {
+        self.0.push(ValidationError {
+            arg,
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.0.len();
+        writeln!(
+            f,
+            "found {count} configuration error{}:",
+            if count == 1 { "" } else { "s" }
+        )?;
+        for error in &self.0 {
+            writeln!(f, "  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Returns the candidate most similar to `input`, if any is similar enough to be a likely typo.
+pub fn did_you_mean<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|candidate| (strsim::jaro(input, candidate), candidate))
+        .filter(|(similarity, _)| *similarity > SUGGESTION_THRESHOLD)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_did_you_mean() {
+        let networks = ["mainnet", "holesky", "sepolia"];
+
+        assert_eq!(did_you_mean("mainet", networks), Some("mainnet"));
+        assert_eq!(did_you_mean("holeski", networks), Some("holesky"));
+        assert_eq!(did_you_mean("goerli", networks), None);
+    }
+
+    #[test]
+    fn test_errors_are_aggregated() {
+        let mut errors = ValidationErrors::new();
+        errors.push("--verbosity", "must be between 1 and 5, got 9");
+        errors.push("--http-timeout", "must be at least 1");
+
+        let report = errors.clone().into_result().unwrap_err().to_string();
+        assert_eq!(
+            report,
+            "found 2 configuration errors:\n  --verbosity: must be between 1 and 5, got 9\n  \
+             --http-timeout: must be at least 1\n"
+        );
+        assert_eq!(errors.errors().len(), 2);
+        assert!(ValidationErrors::new().into_result().is_ok());
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use alloy_primitives::Address;
+use clap::{ArgGroup, Parser, Subcommand};
+use ream_metrics::server::{start_metrics_server, MetricsServerConfig};
+use ream_validator::{
+    beacon_node::BeaconNodeClient,
+    error::ValidatorError,
+    graffiti::Graffiti,
+    key_loader::{decrypt_keystores, watch_keystores, KeystoreLocations},
+    keymanager::{start_keymanager_server, ApiToken, KeymanagerContext, DEFAULT_KEYMANAGER_PORT},
+    proposer_config::{
+        watch_proposer_config, BuilderSettings, ProposerConfig, ProposerSettings, DEFAULT_GAS_LIMIT,
+    },
+    service::{wait_for_genesis, ValidatorClient},
+    slashing_protection::{interchange::Interchange, SlashingDatabase, SlashingProtectionError},
+    validator_store::{parse_secret_keys, Signer, ValidatorStore},
+    web3signer::Web3SignerClient,
+};
+use tracing::{error, info};
+use url::Url;
+
+use crate::{
+    client_stats::{self, MonitoringConfig, ValidatorStats, DEFAULT_MONITORING_INTERVAL},
+    config::{
+        datadir::DataDir,
+        network::{NetworkSpec, NetworkSpecError},
+        Network,
+    },
+};
+
+/// Port of the validator client's metrics, next to the beacon node's so both can run on a host.
+pub const DEFAULT_VALIDATOR_METRICS_PORT: u16 = 5064;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidatorCommandError {
+    #[error("failed to read {path}: {error}")]
+    Io { path: PathBuf, error: io::Error },
+    #[error(transparent)]
+    Validator(#[from] ValidatorError),
+    #[error("no validator keys found")]
+    NoKeys,
+    #[error("failed to load {path}: {error}")]
+    Keystore {
+        path: PathBuf,
+        error: ValidatorError,
+    },
+    #[error("failed to write: {0}")]
+    Output(#[from] io::Error),
+    #[error(transparent)]
+    SlashingProtection(#[from] SlashingProtectionError),
+    #[error(transparent)]
+    Network(#[from] NetworkSpecError),
+    #[error("the beacon node isn't on {0}")]
+    WrongNetwork(String),
+    #[error("invalid interchange file {path}: {error}")]
+    Interchange {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    group(
+        ArgGroup::new("keys")
+            .args(["secret_keys", "validators_dir", "web3signer_url"])
+            .required(true)
+            .multiple(true)
+    ),
+    subcommand_negates_reqs = true
+)]
+pub struct ValidatorCommand {
+    /// Network of the beacon node, and of the default data directory
+    #[arg(long, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// Directory of the chain config of a custom network
+    #[arg(long, value_name = "DIR")]
+    pub custom_config_dir: Option<PathBuf>,
+
+    /// Data directory holding the slashing protection database [default: ~/.ream/<network>]
+    #[arg(long)]
+    pub datadir: Option<PathBuf>,
+
+    /// HTTP API of the beacon node to follow, such as that of `ream node --http`
+    #[arg(long, default_value = "http://127.0.0.1:5052", value_name = "URL")]
+    pub beacon_node_url: Url,
+
+    /// File of hex encoded validator secret keys, one per line
+    #[arg(long, value_name = "PATH")]
+    pub secret_keys: Option<PathBuf>,
+
+    /// Directory of EIP-2335 keystores, checked for new ones while running
+    #[arg(long, value_name = "DIR")]
+    pub validators_dir: Option<PathBuf>,
+
+    /// Directory of keystore passwords, each in a file named after the `0x` prefixed public key
+    #[arg(long, value_name = "DIR", requires = "validators_dir")]
+    pub secrets_dir: Option<PathBuf>,
+
+    /// File with the password of the keystores without their own
+    #[arg(long, value_name = "PATH", requires = "validators_dir")]
+    pub password_file: Option<PathBuf>,
+
+    /// Web3Signer to sign with the keys it holds, which never leave it
+    #[arg(long, value_name = "URL")]
+    pub web3signer_url: Option<Url>,
+
+    /// PEM certificate to trust for the Web3Signer's HTTPS on top of the system ones
+    #[arg(long, value_name = "PATH", requires = "web3signer_url")]
+    pub web3signer_ca_cert: Option<PathBuf>,
+
+    /// Text put in the proposed blocks, up to 32 bytes
+    #[arg(long, value_name = "TEXT")]
+    pub graffiti: Option<Graffiti>,
+
+    /// Address the execution fees of the proposed blocks are paid to, unless the proposer
+    /// config sets another one
+    #[arg(long, value_name = "ADDRESS")]
+    pub suggested_fee_recipient: Option<Address>,
+
+    /// Gas limit asked of builders, unless the proposer config sets another one
+    #[arg(long, value_name = "GAS", default_value_t = DEFAULT_GAS_LIMIT)]
+    pub gas_limit: u64,
+
+    /// JSON file of fee recipients and gas limits by validator pubkey, reloaded while running
+    #[arg(long, value_name = "PATH")]
+    pub proposer_config: Option<PathBuf>,
+
+    /// Serve the keymanager API, to manage the keys and proposer settings while running
+    #[arg(long)]
+    pub keymanager: bool,
+
+    /// Address of the keymanager API, which anyone holding the token can take keys out of
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST), requires = "keymanager")]
+    pub keymanager_address: IpAddr,
+
+    #[arg(long, default_value_t = DEFAULT_KEYMANAGER_PORT, requires = "keymanager")]
+    pub keymanager_port: u16,
+
+    /// File of the bearer token of the keymanager API, a random one is written if missing
+    /// [default: <datadir>/keys/api-token.txt]
+    #[arg(long, value_name = "PATH", requires = "keymanager")]
+    pub keymanager_token_file: Option<PathBuf>,
+
+    /// Serve Prometheus metrics
+    #[arg(long)]
+    pub metrics: bool,
+
+    /// Address the metrics listen on
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST), value_name = "ADDRESS")]
+    pub metrics_address: IpAddr,
+
+    /// Port the metrics listen on
+    #[arg(long, default_value_t = DEFAULT_VALIDATOR_METRICS_PORT, value_name = "PORT")]
+    pub metrics_port: u16,
+
+    /// URL to POST the stats of the validator client to in the client-stats format, e.g. the
+    /// one beaconcha.in gives for its mobile app
+    #[arg(long, value_name = "URL")]
+    pub monitoring_endpoint: Option<Url>,
+
+    /// Seconds between two reports to the monitoring endpoint
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MONITORING_INTERVAL,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "monitoring_endpoint"
+    )]
+    pub monitoring_interval: u64,
+
+    #[command(subcommand)]
+    pub command: Option<ValidatorSubcommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ValidatorSubcommand {
+    /// Move the slashing protection history in the EIP-3076 interchange format, the validator
+    /// client must not be running
+    #[command(name = "slashing-protection", subcommand)]
+    SlashingProtection(SlashingProtectionCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SlashingProtectionCommand {
+    /// Add the history of an interchange file to the database
+    Import { file: PathBuf },
+
+    /// Write the history of the database to an interchange file
+    Export { file: PathBuf },
+}
+
+impl ValidatorCommand {
+    pub fn slashing_protection_db_path(&self) -> PathBuf {
+        self.datadir().slashing_protection_db()
+    }
+
+    pub fn keymanager_token_path(&self) -> PathBuf {
+        self.keymanager_token_file
+            .clone()
+            .unwrap_or_else(|| self.datadir().keymanager_token())
+    }
+
+    /// Settings of the metrics server, `None` unless `--metrics` is set.
+    pub fn metrics_config(&self) -> Option<MetricsServerConfig> {
+        super::metrics_config(self.metrics, self.metrics_address, self.metrics_port)
+    }
+
+    /// Where and how often the client stats are sent, `None` unless `--monitoring-endpoint` is
+    /// set.
+    pub fn monitoring_config(&self) -> Option<MonitoringConfig> {
+        super::monitoring_config(self.monitoring_endpoint.as_ref(), self.monitoring_interval)
+    }
+
+    pub fn datadir(&self) -> DataDir {
+        DataDir::resolve(self.datadir.as_deref(), self.network)
+    }
+
+    /// Opens the slashing protection database, creating it if the validator client never ran.
+    pub fn open_slashing_protection(&self) -> Result<SlashingDatabase, ValidatorCommandError> {
+        self.datadir().create()?;
+        Ok(SlashingDatabase::open(self.slashing_protection_db_path())?)
+    }
+
+    /// Imports or exports the slashing protection history, reporting what was done to `out`.
+    pub fn execute_slashing_protection(
+        &self,
+        command: &SlashingProtectionCommand,
+        out: &mut impl Write,
+    ) -> Result<(), ValidatorCommandError> {
+        let db = self.open_slashing_protection()?;
+        match command {
+            SlashingProtectionCommand::Import { file } => {
+                let reader = File::open(file).map_err(|error| ValidatorCommandError::Io {
+                    path: file.clone(),
+                    error,
+                })?;
+                let interchange: Interchange = serde_json::from_reader(BufReader::new(reader))
+                    .map_err(|error| ValidatorCommandError::Interchange {
+                        path: file.clone(),
+                        error,
+                    })?;
+                db.import_interchange(&interchange)?;
+                writeln!(
+                    out,
+                    "Imported the history of {} validators from {}",
+                    interchange.data.len(),
+                    file.display()
+                )?;
+            }
+            SlashingProtectionCommand::Export { file } => {
+                let interchange = db.export_interchange()?;
+                let mut writer = BufWriter::new(File::create(file)?);
+                serde_json::to_writer_pretty(&mut writer, &interchange).map_err(io::Error::from)?;
+                writer.flush()?;
+                writeln!(
+                    out,
+                    "Exported the history of {} validators to {}",
+                    interchange.data.len(),
+                    file.display()
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn keystore_locations(&self) -> Option<KeystoreLocations> {
+        self.validators_dir
+            .clone()
+            .map(|validators_dir| KeystoreLocations {
+                validators_dir,
+                secrets_dir: self.secrets_dir.clone(),
+                password_file: self.password_file.clone(),
+            })
+    }
+
+    /// Settings of the proposers left out of the proposer config.
+    pub fn default_proposer_settings(&self) -> ProposerSettings {
+        ProposerSettings {
+            fee_recipient: self.suggested_fee_recipient,
+            builder: Some(BuilderSettings {
+                gas_limit: self.gas_limit,
+            }),
+        }
+    }
+
+    pub fn load_proposer_config(&self) -> Result<ProposerConfig, ValidatorCommandError> {
+        let defaults = self.default_proposer_settings();
+        Ok(match &self.proposer_config {
+            Some(path) => ProposerConfig::load(path, &defaults)?,
+            None => ProposerConfig::from_defaults(defaults),
+        })
+    }
+
+    /// Client of the Web3Signer, `None` unless `--web3signer-url` is set.
+    pub fn web3signer(&self) -> Result<Option<Web3SignerClient>, ValidatorCommandError> {
+        let Some(url) = &self.web3signer_url else {
+            return Ok(None);
+        };
+        let ca_certificate = match &self.web3signer_ca_cert {
+            Some(path) => Some(fs::read(path).map_err(|error| ValidatorCommandError::Io {
+                path: path.clone(),
+                error,
+            })?),
+            None => None,
+        };
+        Ok(Some(Web3SignerClient::new(
+            url.clone(),
+            ca_certificate.as_deref(),
+        )?))
+    }
+
+    /// Loads the keys and performs their duties until the process is stopped. Every keystore
+    /// must decrypt at startup, those added later are loaded as they appear. Keys of the
+    /// Web3Signer are those it holds at startup.
+    pub async fn execute(&self) -> Result<(), ValidatorCommandError> {
+        let mut keys = vec![];
+        if let Some(path) = &self.secret_keys {
+            let contents = fs::read_to_string(path).map_err(|error| ValidatorCommandError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            keys.extend(parse_secret_keys(&contents)?);
+        }
+        let locations = self.keystore_locations();
+        let mut loaded = HashSet::new();
+        if let Some(locations) = &locations {
+            let paths = locations.keystore_paths()?;
+            for (path, result) in paths.iter().zip(decrypt_keystores(locations, &paths)) {
+                keys.push(result.map_err(|error| ValidatorCommandError::Keystore {
+                    path: path.clone(),
+                    error,
+                })?);
+            }
+            loaded.extend(paths);
+        }
+        let mut remote_keys = vec![];
+        let web3signer = self.web3signer()?;
+        if let Some(client) = &web3signer {
+            remote_keys = client.public_keys().await?;
+            info!(
+                "Signing with {} keys of the Web3Signer at {}",
+                remote_keys.len(),
+                client.endpoint()
+            );
+        }
+        if keys.is_empty() && remote_keys.is_empty() && locations.is_none() {
+            return Err(ValidatorCommandError::NoKeys);
+        }
+        let proposer_config = Arc::new(RwLock::new(self.load_proposer_config()?));
+        let spec = NetworkSpec::load(self.network, self.custom_config_dir.as_deref())?;
+
+        let beacon_node = BeaconNodeClient::new(self.beacon_node_url.clone());
+        let genesis = wait_for_genesis(&beacon_node).await;
+        // Signing for another chain than the beacon node's would only produce invalid messages.
+        if let Some(expected) = spec.genesis_validators_root {
+            if genesis.genesis_validators_root != expected {
+                return Err(ValidatorCommandError::WrongNetwork(spec.name));
+            }
+        }
+        let store = Arc::new(
+            ValidatorStore::new(keys, spec.fork_schedule, genesis.genesis_validators_root)
+                .with_slashing_protection(self.open_slashing_protection()?)?,
+        );
+        if let Some(client) = web3signer {
+            for pubkey in remote_keys {
+                store.add_signer(pubkey, Signer::Web3Signer(client.clone()));
+            }
+        }
+        if self.keymanager {
+            let token = ApiToken::load_or_create(&self.keymanager_token_path())?;
+            let context = KeymanagerContext {
+                store: store.clone(),
+                locations: locations.clone(),
+                proposer_config: proposer_config.clone(),
+            };
+            let address = SocketAddr::new(self.keymanager_address, self.keymanager_port);
+            tokio::spawn(async move {
+                if let Err(err) = start_keymanager_server(address, context, token).await {
+                    error!("Keymanager API stopped: {err}");
+                }
+            });
+        }
+        if let Some(config) = self.metrics_config() {
+            tokio::spawn(async move {
+                if let Err(err) =
+                    start_metrics_server(&config, || {}, std::future::pending::<()>()).await
+                {
+                    error!("Metrics server stopped: {err}");
+                }
+            });
+        }
+        if let Some(config) = self.monitoring_config() {
+            let beacon_node = BeaconNodeClient::new(self.beacon_node_url.clone());
+            let store = store.clone();
+            tokio::spawn(async move {
+                client_stats::report(config, || ValidatorStats::collect(&beacon_node, &store)).await
+            });
+        }
+        if let Some(locations) = locations {
+            tokio::spawn(watch_keystores(locations, store.clone(), loaded));
+        }
+        if let Some(path) = &self.proposer_config {
+            tokio::spawn(watch_proposer_config(
+                path.clone(),
+                self.default_proposer_settings(),
+                proposer_config.clone(),
+            ));
+        }
+        let graffiti = self.graffiti.unwrap_or_default();
+        ValidatorClient::new(
+            beacon_node,
+            store,
+            genesis.genesis_time,
+            graffiti.into(),
+            proposer_config,
+        )
+        .run()
+        .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+
+    use super::*;
+
+    #[test]
+    fn test_slashing_protection_import_export() {
+        let temp = tempfile::tempdir().unwrap();
+        let datadir = temp.path().join("datadir");
+        fs::create_dir_all(&datadir).unwrap();
+        let command = ValidatorCommand::parse_from([
+            "validator",
+            "--datadir",
+            datadir.to_str().unwrap(),
+            "slashing-protection",
+            "export",
+            "unused.json",
+        ]);
+
+        let import = datadir.join("import.json");
+        fs::write(
+            &import,
+            r#"{
+                "metadata": {
+                    "interchange_format_version": "5",
+                    "genesis_validators_root": "0x0404040404040404040404040404040404040404040404040404040404040404"
+                },
+                "data": [{
+                    "pubkey": "0xb845089a1457f811bfc000588fbb4e713669be8ce060ea6be3c6ece09afc3794106c91ca73acda5e5457122d58723bed",
+                    "signed_blocks": [{ "slot": "81952" }],
+                    "signed_attestations": [{ "source_epoch": "2290", "target_epoch": "3007" }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        let mut out = vec![];
+        command
+            .execute_slashing_protection(
+                &SlashingProtectionCommand::Import {
+                    file: import.clone(),
+                },
+                &mut out,
+            )
+            .unwrap();
+
+        let export = datadir.join("export.json");
+        command
+            .execute_slashing_protection(
+                &SlashingProtectionCommand::Export {
+                    file: export.clone(),
+                },
+                &mut out,
+            )
+            .unwrap();
+        let interchange: Interchange =
+            serde_json::from_reader(File::open(&export).unwrap()).unwrap();
+        assert_eq!(
+            interchange.metadata.genesis_validators_root,
+            B256::repeat_byte(4)
+        );
+        assert_eq!(interchange.data[0].signed_blocks[0].slot, 81952);
+        assert_eq!(
+            interchange.data[0].signed_attestations[0].target_epoch,
+            3007
+        );
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Imported the history of 1 validators"));
+
+        fs::write(&import, "{}").unwrap();
+        assert!(matches!(
+            command.execute_slashing_protection(
+                &SlashingProtectionCommand::Import { file: import },
+                &mut vec![],
+            ),
+            Err(ValidatorCommandError::Interchange { .. })
+        ));
+    }
+}
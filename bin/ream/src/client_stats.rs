@@ -0,0 +1,248 @@
+//! Stats of the node and validator client in the client-stats format of beaconcha.in, POSTed
+//! periodically to a monitoring service like the other clients do.
+
+use std::{
+    fs,
+    future::Future,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ream_metrics::metric_value;
+use ream_rpc::{context::ApiContext, handlers::validator::ValidatorStatus};
+use ream_validator::{beacon_node::BeaconNodeClient, validator_store::ValidatorStore};
+use serde::Serialize;
+use tracing::{debug, warn};
+use url::Url;
+
+pub const DEFAULT_MONITORING_INTERVAL: u64 = 60;
+/// Version of the client-stats format.
+const FORMAT_VERSION: u64 = 1;
+const CLIENT_NAME: &str = "ream";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitoringConfig {
+    pub endpoint: Url,
+    pub interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Process {
+    #[serde(rename = "beaconnode")]
+    BeaconNode,
+    #[serde(rename = "validator")]
+    Validator,
+}
+
+/// Fields every process reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessStats {
+    pub version: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub process: Process,
+    pub cpu_process_seconds_total: u64,
+    pub memory_process_bytes: u64,
+    pub client_name: &'static str,
+    pub client_version: &'static str,
+    pub client_build: u64,
+}
+
+impl ProcessStats {
+    /// Stats of this process now, CPU and memory read from the process metrics, zero where
+    /// those aren't collected.
+    pub fn now(process: Process) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self {
+            version: FORMAT_VERSION,
+            timestamp,
+            process,
+            cpu_process_seconds_total: metric_value("process_cpu_seconds_total").unwrap_or(0.0)
+                as u64,
+            memory_process_bytes: metric_value("process_resident_memory_bytes").unwrap_or(0.0)
+                as u64,
+            client_name: CLIENT_NAME,
+            client_version: env!("CARGO_PKG_VERSION"),
+            client_build: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BeaconNodeStats {
+    #[serde(flatten)]
+    pub process: ProcessStats,
+    pub disk_beaconchain_bytes_total: u64,
+    pub network_libp2p_bytes_total_receive: u64,
+    pub network_libp2p_bytes_total_transmit: u64,
+    pub network_peers_connected: u64,
+    pub sync_eth1_connected: bool,
+    pub sync_eth2_synced: bool,
+    pub sync_beacon_head_slot: u64,
+    pub sync_eth1_fallback_configured: bool,
+    pub sync_eth1_fallback_connected: bool,
+    pub slasher_active: bool,
+}
+
+impl BeaconNodeStats {
+    /// Reads the stats of the node from `context`, the database size from `db_dir`. There's no
+    /// libp2p service yet, so the network counts are zero.
+    pub fn collect(context: &ApiContext, db_dir: &Path, execution_configured: bool) -> Self {
+        let chain = context.chain_info();
+        Self {
+            process: ProcessStats::now(Process::BeaconNode),
+            disk_beaconchain_bytes_total: dir_size(db_dir),
+            network_libp2p_bytes_total_receive: 0,
+            network_libp2p_bytes_total_transmit: 0,
+            network_peers_connected: 0,
+            sync_eth1_connected: execution_configured && !chain.el_offline,
+            sync_eth2_synced: !chain.is_syncing,
+            sync_beacon_head_slot: chain.head_slot,
+            sync_eth1_fallback_configured: false,
+            sync_eth1_fallback_connected: false,
+            slasher_active: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidatorStats {
+    #[serde(flatten)]
+    pub process: ProcessStats,
+    pub validator_total: u64,
+    pub validator_active: u64,
+    pub sync_eth2_fallback_configured: bool,
+    pub sync_eth2_fallback_connected: bool,
+}
+
+impl ValidatorStats {
+    /// Counts the keys of `store` and those the head state of the beacon node has active, none
+    /// when the beacon node can't be reached.
+    pub async fn collect(beacon_node: &BeaconNodeClient, store: &ValidatorStore) -> Self {
+        let active = match beacon_node.validators(&store.pubkeys()).await {
+            Ok(validators) => validators
+                .iter()
+                .filter(|validator| {
+                    matches!(
+                        validator.status,
+                        ValidatorStatus::ActiveOngoing
+                            | ValidatorStatus::ActiveExiting
+                            | ValidatorStatus::ActiveSlashed
+                    )
+                })
+                .count(),
+            Err(err) => {
+                debug!("Failed to read the validator statuses for the client stats: {err}");
+                0
+            }
+        };
+        Self {
+            process: ProcessStats::now(Process::Validator),
+            validator_total: store.len() as u64,
+            validator_active: active as u64,
+            sync_eth2_fallback_configured: false,
+            sync_eth2_fallback_connected: false,
+        }
+    }
+}
+
+/// POSTs the stats `collect` returns to the endpoint every interval, failures are logged and
+/// the next report goes out as usual.
+pub async fn report<T, F>(config: MonitoringConfig, mut collect: impl FnMut() -> F)
+where
+    T: Serialize,
+    F: Future<Output = T>,
+{
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        send(&client, &config.endpoint, collect().await).await;
+    }
+}
+
+/// POSTs `stats` to `endpoint`, failures are logged.
+pub async fn send<T: Serialize>(client: &reqwest::Client, endpoint: &Url, stats: T) {
+    let result = client
+        .post(endpoint.clone())
+        .timeout(REQUEST_TIMEOUT)
+        .json(&[stats])
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    match result {
+        Ok(_) => debug!("Sent client stats to {endpoint}"),
+        Err(err) => warn!("Failed to send client stats: {err}"),
+    }
+}
+
+/// Bytes of the files under `path`, 0 for what can't be read.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_validator_stats() {
+        let stats = ValidatorStats {
+            process: ProcessStats {
+                timestamp: 1_700_000_000_000,
+                cpu_process_seconds_total: 12,
+                memory_process_bytes: 1024,
+                ..ProcessStats::now(Process::Validator)
+            },
+            validator_total: 3,
+            validator_active: 2,
+            sync_eth2_fallback_configured: false,
+            sync_eth2_fallback_connected: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            json!({
+                "version": 1,
+                "timestamp": 1_700_000_000_000u64,
+                "process": "validator",
+                "cpu_process_seconds_total": 12,
+                "memory_process_bytes": 1024,
+                "client_name": "ream",
+                "client_version": env!("CARGO_PKG_VERSION"),
+                "client_build": 0,
+                "validator_total": 3,
+                "validator_active": 2,
+                "sync_eth2_fallback_configured": false,
+                "sync_eth2_fallback_connected": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dir_size() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("stats");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a"), [0; 10]).unwrap();
+        fs::write(dir.join("nested/b"), [0; 5]).unwrap();
+
+        assert_eq!(dir_size(&dir), 15);
+        assert_eq!(dir_size(&dir.join("missing")), 0);
+    }
+}
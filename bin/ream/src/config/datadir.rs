@@ -0,0 +1,176 @@
+//! Layout of the data directory of a network:
+//!
+//! ```text
+//! <datadir>/
+//!   config.toml
+//!   db/hot.redb, db/cold.redb
+//!   network/node_key
+//!   keys/slashing_protection.redb, keys/api-token.txt
+//!   logs/
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::Network;
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+pub const DB_DIR: &str = "db";
+pub const NETWORK_DIR: &str = "network";
+pub const KEYS_DIR: &str = "keys";
+pub const LOGS_DIR: &str = "logs";
+
+/// The data directory, whose paths every component takes from here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDir(PathBuf);
+
+impl DataDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self(root.into())
+    }
+
+    /// `datadir` if set, the default data directory of `network` otherwise.
+    pub fn resolve(datadir: Option<&Path>, network: Network) -> Self {
+        Self::new(datadir.map_or_else(|| default_datadir(network), Path::to_path_buf))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.0.join(CONFIG_FILE_NAME)
+    }
+
+    pub fn db_dir(&self) -> PathBuf {
+        self.0.join(DB_DIR)
+    }
+
+    pub fn hot_db(&self) -> PathBuf {
+        self.db_dir().join("hot.redb")
+    }
+
+    pub fn cold_db(&self) -> PathBuf {
+        self.db_dir().join("cold.redb")
+    }
+
+    pub fn network_dir(&self) -> PathBuf {
+        self.0.join(NETWORK_DIR)
+    }
+
+    pub fn node_key(&self) -> PathBuf {
+        self.network_dir().join("node_key")
+    }
+
+    /// Holds what the validator client must keep secret or must never lose.
+    pub fn keys_dir(&self) -> PathBuf {
+        self.0.join(KEYS_DIR)
+    }
+
+    pub fn slashing_protection_db(&self) -> PathBuf {
+        self.keys_dir().join("slashing_protection.redb")
+    }
+
+    pub fn keymanager_token(&self) -> PathBuf {
+        self.keys_dir().join("api-token.txt")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.0.join(LOGS_DIR)
+    }
+
+    /// Creates the directories of the layout, the keys directory readable by the user only.
+    pub fn create(&self) -> io::Result<()> {
+        for dir in [self.db_dir(), self.network_dir(), self.logs_dir()] {
+            fs::create_dir_all(dir)?;
+        }
+        let keys_dir = self.keys_dir();
+        fs::create_dir_all(&keys_dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(&keys_dir, fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(())
+    }
+}
+
+/// The data directory of `network` in the usual place of the OS: `~/.ream/<network>` on Linux,
+/// `~/Library/Application Support/ream/<network>` on macOS and `%APPDATA%\ream\<network>` on
+/// Windows. Relative to the working directory if the home directory is unknown.
+pub fn default_datadir(network: Network) -> PathBuf {
+    base_dir().join(network.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn base_dir() -> PathBuf {
+    home_dir().join("Library/Application Support/ream")
+}
+
+#[cfg(windows)]
+fn base_dir() -> PathBuf {
+    std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(home_dir)
+        .join("ream")
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn base_dir() -> PathBuf {
+    home_dir().join(".ream")
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let datadir = DataDir::resolve(Some(Path::new("/data/ream")), Network::Holesky);
+        assert_eq!(datadir.hot_db(), PathBuf::from("/data/ream/db/hot.redb"));
+        assert_eq!(
+            datadir.node_key(),
+            PathBuf::from("/data/ream/network/node_key")
+        );
+        assert_eq!(
+            datadir.slashing_protection_db(),
+            PathBuf::from("/data/ream/keys/slashing_protection.redb")
+        );
+        assert!(DataDir::resolve(None, Network::Holesky)
+            .root()
+            .ends_with("holesky"));
+    }
+
+    #[test]
+    fn test_create() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("datadir");
+        let datadir = DataDir::new(&root);
+        datadir.create().unwrap();
+        // Creating it again keeps what's there.
+        fs::write(datadir.node_key(), "key").unwrap();
+        datadir.create().unwrap();
+        assert!(datadir.node_key().exists());
+        assert!(datadir.logs_dir().is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(datadir.keys_dir())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+    }
+}
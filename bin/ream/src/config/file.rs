@@ -0,0 +1,309 @@
+//! Node options read from the TOML file passed with `--config`, keyed by their flag names.
+//!
+//! ```toml
+//! network = "holesky"
+//! http = true
+//! http_port = 5052
+//! discovery_dns = ["enrtree://..."]
+//! ```
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command};
+use toml::{Table, Value};
+
+use crate::cli::validation::did_you_mean;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    #[error("failed to read {}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+    #[error("invalid config {}: {error}", path.display())]
+    Parse {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    #[error("{}: unknown option `{key}`{}", path.display(), suggestion_hint(suggestion))]
+    UnknownOption {
+        path: PathBuf,
+        key: String,
+        suggestion: Option<String>,
+    },
+    #[error("{}: invalid `{key}`, {reason}", path.display())]
+    InvalidValue {
+        path: PathBuf,
+        key: String,
+        reason: &'static str,
+    },
+}
+
+fn suggestion_hint(suggestion: &Option<String>) -> String {
+    suggestion.as_ref().map_or_else(String::new, |suggestion| {
+        format!(", did you mean `{suggestion}`?")
+    })
+}
+
+/// The options of a config file, turned into command line arguments so clap parses and checks
+/// them the same way as the flags.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    path: PathBuf,
+    options: Table,
+}
+
+/// Arguments of the options of a config file that the command line left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileArgs {
+    /// Ids of the options taken from the file.
+    pub ids: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, ConfigFileError> {
+        let contents = fs::read_to_string(path).map_err(|error| ConfigFileError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Self::parse(path, &contents)
+    }
+
+    pub fn parse(path: &Path, contents: &str) -> Result<Self, ConfigFileError> {
+        let options = toml::from_str(contents).map_err(|error| ConfigFileError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            options,
+        })
+    }
+
+    /// The options of the file as arguments of `command`, leaving out those `matches` got from
+    /// the command line or the environment since they take precedence. Keys are the flag names,
+    /// with either dashes or underscores.
+    pub fn args(
+        &self,
+        command: &Command,
+        matches: &ArgMatches,
+    ) -> Result<FileArgs, ConfigFileError> {
+        let mut file_args = FileArgs::default();
+        for (key, value) in &self.options {
+            let id = key.replace('-', "_");
+            let Some(arg) = options(command).find(|arg| arg.get_id() == &id) else {
+                return Err(ConfigFileError::UnknownOption {
+                    path: self.path.clone(),
+                    key: key.clone(),
+                    suggestion: did_you_mean(
+                        &id,
+                        options(command).map(|arg| arg.get_id().as_str()),
+                    )
+                    .map(str::to_string),
+                });
+            };
+            let source = matches.value_source(&id);
+            if source.is_some_and(|source| source != ValueSource::DefaultValue) {
+                continue;
+            }
+
+            let invalid = |reason| ConfigFileError::InvalidValue {
+                path: self.path.clone(),
+                key: key.clone(),
+                reason,
+            };
+            let long = arg.get_long().expect("options have a long flag");
+            let values = match value {
+                Value::Array(values) if matches!(arg.get_action(), ArgAction::Append) => {
+                    values.iter().collect()
+                }
+                Value::Array(_) => return Err(invalid("expected a single value")),
+                value => vec![value],
+            };
+            for value in values {
+                match (arg.get_action(), value) {
+                    (ArgAction::SetTrue, Value::Boolean(true)) => {
+                        file_args.args.push(format!("--{long}"))
+                    }
+                    (ArgAction::SetTrue, Value::Boolean(false)) => {}
+                    (ArgAction::SetTrue, _) => return Err(invalid("expected true or false")),
+                    (_, Value::String(value)) => file_args.args.push(format!("--{long}={value}")),
+                    (_, Value::Integer(_) | Value::Float(_) | Value::Boolean(_)) => {
+                        file_args.args.push(format!("--{long}={value}"))
+                    }
+                    _ => return Err(invalid("expected a string, number or boolean")),
+                }
+            }
+            file_args.ids.push(id);
+        }
+        Ok(file_args)
+    }
+}
+
+/// The options of `command` that can be set from a file.
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| {
+        arg.get_long().is_some()
+            && arg.get_id() != "config"
+            && !matches!(
+                arg.get_action(),
+                ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+            )
+    })
+}
+
+/// Where the value of an option came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionSource {
+    Default,
+    ConfigFile,
+    CommandLine,
+    Environment,
+}
+
+impl fmt::Display for OptionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OptionSource::Default => "default",
+            OptionSource::ConfigFile => "config file",
+            OptionSource::CommandLine => "command line",
+            OptionSource::Environment => "environment",
+        })
+    }
+}
+
+/// The value every set option of a command ended up with, printed as a config file annotated
+/// with the source of each value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectiveConfig {
+    pub options: Vec<(String, Value, OptionSource)>,
+}
+
+impl EffectiveConfig {
+    /// The options of `command` as parsed into `matches`, `from_file` being the ids of those
+    /// taken from the config file.
+    pub fn new(command: &Command, matches: &ArgMatches, from_file: &[String]) -> Self {
+        let mut effective = Self::default();
+        for arg in options(command) {
+            let id = arg.get_id().as_str();
+            let Some(raw) = matches.get_raw(id) else {
+                continue;
+            };
+            let mut values: Vec<Value> = raw
+                .map(|value| toml_value(&value.to_string_lossy()))
+                .collect();
+            let value = if matches!(arg.get_action(), ArgAction::Append) {
+                Value::Array(values)
+            } else {
+                match values.pop() {
+                    Some(value) => value,
+                    None => continue,
+                }
+            };
+            let source = if from_file.iter().any(|file_id| file_id == id) {
+                OptionSource::ConfigFile
+            } else {
+                match matches.value_source(id) {
+                    Some(ValueSource::CommandLine) => OptionSource::CommandLine,
+                    Some(ValueSource::EnvVariable) => OptionSource::Environment,
+                    _ => OptionSource::Default,
+                }
+            };
+            effective.options.push((id.to_string(), value, source));
+        }
+        effective
+    }
+}
+
+impl fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, value, source) in &self.options {
+            writeln!(f, "{id} = {value} # {source}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Booleans and integers are written bare, anything else quoted.
+fn toml_value(value: &str) -> Value {
+    if let Ok(value) = value.parse::<bool>() {
+        Value::Boolean(value)
+    } else if let Ok(value) = value.parse::<i64>() {
+        Value::Integer(value)
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+    use crate::{cli::NodeCommand, config::Network};
+
+    fn parse(args: &[&str]) -> ArgMatches {
+        NodeCommand::command().get_matches_from(["node"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_file_args() {
+        let file = ConfigFile::parse(
+            Path::new("ream.toml"),
+            r#"
+                network = "holesky"
+                http = true
+                http-port = 6000
+                prune_blobs = false
+                discovery_dns = ["enrtree://a@nodes.example.org", "enrtree://b@nodes.example.org"]
+            "#,
+        )
+        .unwrap();
+        let command = NodeCommand::command();
+        let file_args = file
+            .args(&command, &parse(&["--http-port", "7000"]))
+            .unwrap();
+        // The command line wins over the file.
+        assert!(!file_args.ids.contains(&"http_port".to_string()));
+
+        let mut args = vec!["node".to_string()];
+        args.extend(file_args.args);
+        args.extend(["--http-port".to_string(), "7000".to_string()]);
+        let matches = command.clone().get_matches_from(&args);
+        let node = NodeCommand::from_arg_matches(&matches).unwrap();
+        assert_eq!(node.network, Network::Holesky);
+        assert!(node.http);
+        assert_eq!(node.http_port, 7000);
+        assert!(!node.prune_blobs);
+        assert_eq!(node.discovery_dns.len(), 2);
+
+        let effective = EffectiveConfig::new(&command, &matches, &file_args.ids).to_string();
+        assert!(effective.contains("network = \"holesky\" # config file\n"));
+        assert!(effective.contains("http_port = 7000 # command line\n"));
+        assert!(effective.contains("verbosity = 3 # default\n"));
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let command = NodeCommand::command();
+        let file = ConfigFile::parse(Path::new("ream.toml"), "htp_port = 1").unwrap();
+        let err = file.args(&command, &parse(&[])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ream.toml: unknown option `htp_port`, did you mean `http_port`?"
+        );
+
+        let file = ConfigFile::parse(Path::new("ream.toml"), "http = \"yes\"").unwrap();
+        assert!(matches!(
+            file.args(&command, &parse(&[])),
+            Err(ConfigFileError::InvalidValue { .. })
+        ));
+        let file = ConfigFile::parse(Path::new("ream.toml"), "http_port = [1, 2]").unwrap();
+        assert!(matches!(
+            file.args(&command, &parse(&[])),
+            Err(ConfigFileError::InvalidValue { .. })
+        ));
+    }
+}
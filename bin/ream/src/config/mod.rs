@@ -0,0 +1,80 @@
+pub mod datadir;
+pub mod file;
+pub mod network;
+pub mod reload;
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::Address;
+use clap::ValueEnum;
+use datadir::DataDir;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_EXECUTION_ENDPOINT: &str = "http://localhost:8551";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Holesky,
+    Sepolia,
+    /// A devnet described by `--custom-config-dir`.
+    Custom,
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Network::Mainnet => "mainnet",
+            Network::Holesky => "holesky",
+            Network::Sepolia => "sepolia",
+            Network::Custom => "custom",
+        })
+    }
+}
+
+/// Node settings written by `ream init`.
+///
+/// Settings after `execution_jwt_secret` can be changed while the node is running, see
+/// [`reload`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReamConfig {
+    pub network: Network,
+    pub datadir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_sync_url: Option<String>,
+    pub execution_endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_jwt_secret: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graffiti: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_fee_recipient: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbosity: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_peers: Option<usize>,
+    #[serde(default)]
+    pub builder_enabled: bool,
+}
+
+impl ReamConfig {
+    pub fn data_dir(&self) -> DataDir {
+        DataDir::new(&self.datadir)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.data_dir().config_file()
+    }
+}
+
+pub fn read_config(path: &Path) -> Result<ReamConfig, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    toml::from_str(&contents).map_err(|err| format!("invalid config {}: {err}", path.display()))
+}
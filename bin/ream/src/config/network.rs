@@ -0,0 +1,377 @@
+//! Chain settings of the supported networks, bundled for the public ones and read from a
+//! config directory for devnets.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::{address, aliases::B32, b256, Address, B256};
+use ream_consensus::{
+    constants::FAR_FUTURE_EPOCH,
+    fork_schedule::{ForkName, ForkSchedule, ScheduledFork},
+};
+use ream_execution::eth1::{MAINNET_DEPOSIT_CONTRACT, MAINNET_DEPOSIT_CONTRACT_DEPLOY_BLOCK};
+
+use super::Network;
+
+/// Files of a custom config directory, in the layout of the `eth-clients` network repositories.
+pub const CUSTOM_CONFIG_FILE: &str = "config.yaml";
+pub const CUSTOM_DEPLOY_BLOCK_FILE: &str = "deploy_block.txt";
+pub const CUSTOM_BOOTNODES_FILE: &str = "bootstrap_nodes.txt";
+
+/// Bootnodes of the public networks, as published in their `eth-clients` repositories.
+const MAINNET_BOOTNODES: &str = include_str!("bootnodes/mainnet.txt");
+const HOLESKY_BOOTNODES: &str = include_str!("bootnodes/holesky.txt");
+const SEPOLIA_BOOTNODES: &str = include_str!("bootnodes/sepolia.txt");
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkSpecError {
+    #[error("--network custom needs --custom-config-dir")]
+    MissingConfigDir,
+    #[error("--custom-config-dir is only read with --network custom")]
+    UnusedConfigDir,
+    #[error("failed to read {path}: {error}")]
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    #[error("{path}: {message}")]
+    Invalid { path: PathBuf, message: String },
+    #[error("invalid bootnode {enr}: {message}")]
+    InvalidBootnode { enr: String, message: String },
+}
+
+/// What the node needs to know of the chain it follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkSpec {
+    pub network: Network,
+    /// `CONFIG_NAME` of the chain config, the network name otherwise.
+    pub name: String,
+    pub fork_schedule: ForkSchedule,
+    /// Known for the public networks, learnt from the genesis state otherwise.
+    pub genesis_validators_root: Option<B256>,
+    /// Root of the genesis state, to check one obtained elsewhere against.
+    pub genesis_state_root: Option<B256>,
+    pub deposit_contract: Address,
+    pub deposit_contract_deploy_block: u64,
+    /// ENRs to start discovery from.
+    pub bootnodes: Vec<String>,
+}
+
+impl NetworkSpec {
+    /// The spec of `network`, read from `custom_config_dir` for a custom network.
+    pub fn load(
+        network: Network,
+        custom_config_dir: Option<&Path>,
+    ) -> Result<Self, NetworkSpecError> {
+        match (network, custom_config_dir) {
+            (Network::Custom, Some(dir)) => Self::from_dir(dir),
+            (Network::Custom, None) => Err(NetworkSpecError::MissingConfigDir),
+            (_, Some(_)) => Err(NetworkSpecError::UnusedConfigDir),
+            (Network::Mainnet, None) => Ok(Self::mainnet()),
+            (Network::Holesky, None) => Ok(Self::holesky()),
+            (Network::Sepolia, None) => Ok(Self::sepolia()),
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self {
+            network: Network::Mainnet,
+            name: Network::Mainnet.to_string(),
+            fork_schedule: ForkSchedule::mainnet(),
+            genesis_validators_root: Some(b256!(
+                "4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            )),
+            genesis_state_root: Some(b256!(
+                "7e76880eb67bbdc86250aa578958e9d0675e64e714337855204fb5abaaf82c2b"
+            )),
+            deposit_contract: MAINNET_DEPOSIT_CONTRACT,
+            deposit_contract_deploy_block: MAINNET_DEPOSIT_CONTRACT_DEPLOY_BLOCK,
+            bootnodes: parse_bootnodes(MAINNET_BOOTNODES),
+        }
+    }
+
+    pub fn holesky() -> Self {
+        Self {
+            network: Network::Holesky,
+            name: Network::Holesky.to_string(),
+            fork_schedule: ForkSchedule::holesky(),
+            genesis_validators_root: Some(b256!(
+                "9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"
+            )),
+            genesis_state_root: Some(b256!(
+                "0ea3f6f9515823b59c863454675fefcd1d8b4f2dbe454db166206a41fda060a0"
+            )),
+            deposit_contract: address!("4242424242424242424242424242424242424242"),
+            deposit_contract_deploy_block: 0,
+            bootnodes: parse_bootnodes(HOLESKY_BOOTNODES),
+        }
+    }
+
+    pub fn sepolia() -> Self {
+        Self {
+            network: Network::Sepolia,
+            name: Network::Sepolia.to_string(),
+            fork_schedule: ForkSchedule::sepolia(),
+            genesis_validators_root: Some(b256!(
+                "d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"
+            )),
+            genesis_state_root: Some(b256!(
+                "fb9afe32150fa39f4b346be2519a67e2a4f5efcd50a1dc192c3f6b3d013d2798"
+            )),
+            deposit_contract: address!("7f02C3E3c98b133055B8B348B2Ac625669Ed295D"),
+            deposit_contract_deploy_block: 1_273_020,
+            bootnodes: parse_bootnodes(SEPOLIA_BOOTNODES),
+        }
+    }
+
+    /// Reads `config.yaml`, `deploy_block.txt` and `bootstrap_nodes.txt` of `dir`, the last two
+    /// being optional.
+    pub fn from_dir(dir: &Path) -> Result<Self, NetworkSpecError> {
+        let path = dir.join(CUSTOM_CONFIG_FILE);
+        let config = ChainConfig::parse(&read(&path)?);
+        let invalid = |message: String| NetworkSpecError::Invalid {
+            path: path.clone(),
+            message,
+        };
+
+        let mut forks = vec![];
+        for (name, prefix) in [
+            (ForkName::Phase0, "GENESIS"),
+            (ForkName::Altair, "ALTAIR"),
+            (ForkName::Bellatrix, "BELLATRIX"),
+            (ForkName::Capella, "CAPELLA"),
+            (ForkName::Deneb, "DENEB"),
+            (ForkName::Electra, "ELECTRA"),
+        ] {
+            let Some(version) = config.get(&format!("{prefix}_FORK_VERSION")) else {
+                break;
+            };
+            let epoch = match name {
+                ForkName::Phase0 => 0,
+                _ => config
+                    .get(&format!("{prefix}_FORK_EPOCH"))
+                    .map_or(Ok(FAR_FUTURE_EPOCH), |epoch| epoch.parse())
+                    .map_err(|err| invalid(format!("{prefix}_FORK_EPOCH: {err}")))?,
+            };
+            forks.push(ScheduledFork {
+                name,
+                version: version
+                    .parse::<B32>()
+                    .map_err(|err| invalid(format!("{prefix}_FORK_VERSION: {err}")))?,
+                epoch,
+            });
+        }
+        if forks.is_empty() {
+            return Err(invalid("no GENESIS_FORK_VERSION".to_string()));
+        }
+        if forks.windows(2).any(|pair| pair[0].epoch > pair[1].epoch) {
+            return Err(invalid("fork epochs must be ascending".to_string()));
+        }
+        let deposit_contract = config
+            .get("DEPOSIT_CONTRACT_ADDRESS")
+            .ok_or_else(|| invalid("no DEPOSIT_CONTRACT_ADDRESS".to_string()))?
+            .parse()
+            .map_err(|err| invalid(format!("DEPOSIT_CONTRACT_ADDRESS: {err}")))?;
+
+        let deploy_block_path = dir.join(CUSTOM_DEPLOY_BLOCK_FILE);
+        let deposit_contract_deploy_block = match read_optional(&deploy_block_path)? {
+            Some(block) => {
+                block
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|err| NetworkSpecError::Invalid {
+                        path: deploy_block_path,
+                        message: err.to_string(),
+                    })?
+            }
+            None => 0,
+        };
+        let bootnodes = read_optional(&dir.join(CUSTOM_BOOTNODES_FILE))?
+            .map(|contents| parse_bootnodes(&contents))
+            .unwrap_or_default();
+
+        Ok(Self {
+            network: Network::Custom,
+            name: config
+                .get("CONFIG_NAME")
+                .map_or_else(|| Network::Custom.to_string(), str::to_string),
+            fork_schedule: ForkSchedule::new(forks),
+            genesis_validators_root: None,
+            genesis_state_root: None,
+            deposit_contract,
+            deposit_contract_deploy_block,
+            bootnodes,
+        })
+    }
+}
+
+/// The flat `KEY: value` entries of a chain config, which is all `config.yaml` holds.
+struct ChainConfig(HashMap<String, String>);
+
+impl ChainConfig {
+    fn parse(contents: &str) -> Self {
+        Self(
+            contents
+                .lines()
+                .filter_map(|line| {
+                    let line = line.split('#').next()?;
+                    let (key, value) = line.split_once(':')?;
+                    let value = value.trim().trim_matches(['"', '\'']);
+                    (!value.is_empty()).then(|| (key.trim().to_string(), value.to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+}
+
+/// ENRs of a file listing one per line, as bare lines or YAML list items, comments left out.
+pub fn parse_bootnodes(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .map(|line| line.trim_start_matches("- ").trim_matches(['"', '\'']))
+        .filter(|line| line.starts_with("enr:"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The ENRs of `sources`, each one either an ENR or a file listing them as
+/// [`parse_bootnodes`] reads.
+pub fn read_bootnodes(sources: &[String]) -> Result<Vec<String>, NetworkSpecError> {
+    let mut bootnodes = vec![];
+    for source in sources {
+        if source.starts_with("enr:") {
+            bootnodes.push(source.clone());
+        } else {
+            bootnodes.extend(parse_bootnodes(&read(Path::new(source))?));
+        }
+    }
+    Ok(bootnodes)
+}
+
+fn read(path: &Path) -> Result<String, NetworkSpecError> {
+    fs::read_to_string(path).map_err(|error| NetworkSpecError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, NetworkSpecError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    read(path).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_discv5::Enr;
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_networks() {
+        let spec = NetworkSpec::load(Network::Holesky, None).unwrap();
+        assert_eq!(spec.fork_schedule, ForkSchedule::holesky());
+        assert_eq!(spec.name, "holesky");
+        assert_eq!(
+            NetworkSpec::load(Network::Mainnet, None)
+                .unwrap()
+                .deposit_contract,
+            MAINNET_DEPOSIT_CONTRACT
+        );
+        assert!(matches!(
+            NetworkSpec::load(Network::Custom, None),
+            Err(NetworkSpecError::MissingConfigDir)
+        ));
+        assert!(matches!(
+            NetworkSpec::load(Network::Sepolia, Some(Path::new("devnet"))),
+            Err(NetworkSpecError::UnusedConfigDir)
+        ));
+    }
+
+    #[test]
+    fn test_bundled_bootnodes() {
+        for spec in [
+            NetworkSpec::mainnet(),
+            NetworkSpec::holesky(),
+            NetworkSpec::sepolia(),
+        ] {
+            assert!(!spec.bootnodes.is_empty());
+            for enr in &spec.bootnodes {
+                assert!(enr.parse::<Enr>().is_ok(), "{}: {enr}", spec.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_bootnodes() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("bootnodes.txt");
+        fs::write(&path, "# devnet\nenr:-second\nenr:-third\n").unwrap();
+        let sources = ["enr:-first".to_string(), path.display().to_string()];
+        assert_eq!(
+            read_bootnodes(&sources).unwrap(),
+            ["enr:-first", "enr:-second", "enr:-third"]
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            read_bootnodes(&sources),
+            Err(NetworkSpecError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_custom_network() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("network");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(CUSTOM_CONFIG_FILE),
+            "# Extends the mainnet preset\n\
+             PRESET_BASE: 'mainnet'\n\
+             CONFIG_NAME: 'kurtosis'\n\
+             GENESIS_FORK_VERSION: 0x10000038\n\
+             ALTAIR_FORK_VERSION: 0x20000038\n\
+             ALTAIR_FORK_EPOCH: 0\n\
+             BELLATRIX_FORK_VERSION: 0x30000038\n\
+             BELLATRIX_FORK_EPOCH: 10 # a comment\n\
+             CAPELLA_FORK_VERSION: 0x40000038\n\
+             CAPELLA_FORK_EPOCH: 18446744073709551615\n\
+             DEPOSIT_CONTRACT_ADDRESS: 0x4242424242424242424242424242424242424242\n",
+        )
+        .unwrap();
+        fs::write(dir.join(CUSTOM_DEPLOY_BLOCK_FILE), "5\n").unwrap();
+        fs::write(
+            dir.join(CUSTOM_BOOTNODES_FILE),
+            "# bootnodes\nenr:-first\n- enr:-second\n",
+        )
+        .unwrap();
+
+        let spec = NetworkSpec::load(Network::Custom, Some(&dir)).unwrap();
+        assert_eq!(spec.name, "kurtosis");
+        // Capella isn't scheduled.
+        assert_eq!(spec.fork_schedule.forks().len(), 3);
+        assert_eq!(
+            spec.fork_schedule.fork_at_epoch(10).version,
+            B32::new([0x30, 0x00, 0x00, 0x38])
+        );
+        assert_eq!(spec.deposit_contract, Address::repeat_byte(0x42));
+        assert_eq!(spec.deposit_contract_deploy_block, 5);
+        assert_eq!(spec.bootnodes, ["enr:-first", "enr:-second"]);
+        assert_eq!(spec.genesis_validators_root, None);
+
+        fs::write(dir.join(CUSTOM_CONFIG_FILE), "PRESET_BASE: 'mainnet'\n").unwrap();
+        assert!(matches!(
+            NetworkSpec::from_dir(&dir),
+            Err(NetworkSpecError::Invalid { .. })
+        ));
+    }
+}
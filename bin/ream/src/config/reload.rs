@@ -0,0 +1,153 @@
+//! Reloading the config file while the node is running.
+//!
+//! Only settings that are read on every use can change at runtime. Everything else is reported
+//! as requiring a restart and keeps its current value.
+
+use std::{fmt, path::PathBuf};
+
+use tokio::sync::{broadcast, watch};
+use tracing::{info, warn};
+
+use super::{read_config, ReamConfig};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Settings that changed and were applied.
+    pub applied: Vec<&'static str>,
+    /// Settings that changed but only take effect after a restart.
+    pub requires_restart: Vec<&'static str>,
+}
+
+impl ReloadReport {
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.requires_restart.is_empty()
+    }
+}
+
+impl fmt::Display for ReloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no settings changed");
+        }
+        if !self.applied.is_empty() {
+            write!(f, "applied {}", self.applied.join(", "))?;
+        }
+        if !self.requires_restart.is_empty() {
+            if !self.applied.is_empty() {
+                f.write_str("; ")?;
+            }
+            write!(
+                f,
+                "restart required for {}",
+                self.requires_restart.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies the hot-reloadable settings of `new` into `current` and reports what changed.
+pub fn apply_reload(current: &mut ReamConfig, new: ReamConfig) -> ReloadReport {
+    let mut report = ReloadReport::default();
+
+    macro_rules! reload {
+        ($($field:ident),*) => {
+            $(
+                if current.$field != new.$field {
+                    current.$field = new.$field;
+                    report.applied.push(stringify!($field));
+                }
+            )*
+        };
+    }
+    macro_rules! restart {
+        ($($field:ident),*) => {
+            $(
+                if current.$field != new.$field {
+                    report.requires_restart.push(stringify!($field));
+                }
+            )*
+        };
+    }
+
+    reload!(
+        graffiti,
+        suggested_fee_recipient,
+        verbosity,
+        target_peers,
+        builder_enabled
+    );
+    restart!(
+        network,
+        datadir,
+        checkpoint_sync_url,
+        execution_endpoint,
+        execution_jwt_secret
+    );
+
+    report
+}
+
+/// Re-reads the config file at `path` on every SIGHUP and publishes the hot-reloadable
+/// settings to `config`, until `shutdown` fires. An invalid file is logged and ignored.
+#[cfg(unix)]
+pub async fn reload_on_sighup(
+    path: PathBuf,
+    config: watch::Sender<ReamConfig>,
+    mut shutdown: broadcast::Receiver<()>,
+) -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    loop {
+        tokio::select! {
+            _ = shutdown.recv() => return Ok(()),
+            _ = hangup.recv() => {}
+        }
+
+        match read_config(&path) {
+            Ok(new) => {
+                let mut report = ReloadReport::default();
+                config.send_if_modified(|current| {
+                    report = apply_reload(current, new);
+                    !report.applied.is_empty()
+                });
+                info!(%report, "Reloaded {}", path.display());
+            }
+            Err(err) => warn!(%err, "Config reload failed, keeping the current settings"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Network;
+
+    #[test]
+    fn test_apply_reload() {
+        let mut current = ReamConfig {
+            graffiti: Some("ream".to_string()),
+            ..ReamConfig::default()
+        };
+        let new = ReamConfig {
+            network: Network::Holesky,
+            graffiti: Some("hello".to_string()),
+            target_peers: Some(100),
+            ..ReamConfig::default()
+        };
+
+        let report = apply_reload(&mut current, new);
+        assert_eq!(report.applied, vec!["graffiti", "target_peers"]);
+        assert_eq!(report.requires_restart, vec!["network"]);
+        assert_eq!(
+            report.to_string(),
+            "applied graffiti, target_peers; restart required for network"
+        );
+        assert_eq!(current.graffiti.as_deref(), Some("hello"));
+        assert_eq!(current.network, Network::Mainnet);
+
+        let unchanged = current.clone();
+        assert!(apply_reload(&mut current, unchanged).is_empty());
+    }
+}
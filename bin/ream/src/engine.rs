@@ -0,0 +1,130 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use ream_consensus::constants::SECONDS_PER_SLOT;
+use ream_execution::{
+    forkchoice::{ForkchoiceOutcome, ForkchoiceUpdater},
+    health::EngineState,
+};
+use ream_rpc::context::ChainInfo;
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// Mirrors the reachability of the execution layer into the chain info the API serves, until
+/// the engine client is dropped.
+pub async fn track_engine_state(
+    mut state: watch::Receiver<EngineState>,
+    chain: Arc<RwLock<ChainInfo>>,
+) {
+    loop {
+        let offline = *state.borrow_and_update() == EngineState::Offline;
+        chain.write().expect("chain info lock poisoned").el_offline = offline;
+        if state.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Tells the execution layer the head, justified and finalized blocks of `chain` every slot,
+/// `updater` only calling it when they changed, and sends the update queued while it was
+/// offline as soon as it's back. Runs until the engine client is dropped.
+pub async fn update_forkchoice(
+    mut updater: ForkchoiceUpdater,
+    mut state: watch::Receiver<EngineState>,
+    chain: Arc<RwLock<ChainInfo>>,
+) {
+    let mut slots = tokio::time::interval(Duration::from_secs(SECONDS_PER_SLOT));
+    loop {
+        let result = tokio::select! {
+            _ = slots.tick() => {
+                let (head_root, justified_checkpoint, finalized_checkpoint) = {
+                    let chain = chain.read().expect("chain info lock poisoned");
+                    (
+                        chain.head_root,
+                        chain.justified_checkpoint,
+                        chain.finalized_checkpoint,
+                    )
+                };
+                updater
+                    .update(head_root, justified_checkpoint, finalized_checkpoint)
+                    .await
+            }
+            changed = state.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                if *state.borrow_and_update() != EngineState::Online {
+                    continue;
+                }
+                updater.flush().await
+            }
+        };
+        match result {
+            Ok(ForkchoiceOutcome::Unchanged) => {}
+            Ok(outcome) => debug!(?outcome, "Updated the forkchoice of the execution layer"),
+            Err(err) => warn!("Failed to update the forkchoice of the execution layer: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use ream_consensus::checkpoint::Checkpoint;
+    use ream_execution::{auth::JwtSecret, client::EngineClient};
+    use ream_fork_choice::{
+        proto_array::{Block, ExecutionStatus},
+        proto_array_fork_choice::ProtoArrayForkChoice,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_engine_state() {
+        let (sender, receiver) = watch::channel(EngineState::Offline);
+        let chain = Arc::new(RwLock::new(ChainInfo::default()));
+        let tracker = tokio::spawn(track_engine_state(receiver, chain.clone()));
+        let el_offline = || chain.read().unwrap().el_offline;
+
+        tokio::task::yield_now().await;
+        assert!(el_offline());
+
+        sender.send(EngineState::Online).unwrap();
+        drop(sender);
+        tracker.await.unwrap();
+        assert!(!el_offline());
+    }
+
+    #[tokio::test]
+    async fn test_update_forkchoice_stops_with_the_engine() {
+        let engine = Arc::new(EngineClient::new(
+            "http://127.0.0.1:1".parse().unwrap(),
+            JwtSecret::new(B256::ZERO),
+        ));
+        let anchor = Block {
+            slot: 0,
+            root: B256::repeat_byte(1),
+            parent_root: B256::ZERO,
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Irrelevant(false),
+        };
+        let fork_choice =
+            ProtoArrayForkChoice::new(anchor, Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        let updater = ForkchoiceUpdater::new(engine, Arc::new(RwLock::new(fork_choice)));
+        let (sender, receiver) = watch::channel(EngineState::Offline);
+        let chain = Arc::new(RwLock::new(ChainInfo {
+            head_root: B256::repeat_byte(1),
+            ..ChainInfo::default()
+        }));
+
+        let task = tokio::spawn(update_forkchoice(updater, receiver, chain));
+        // The head has no payload before the merge, so nothing is sent to the engine.
+        tokio::task::yield_now().await;
+        drop(sender);
+        task.await.unwrap();
+    }
+}
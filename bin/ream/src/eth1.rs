@@ -0,0 +1,30 @@
+use std::sync::{Arc, RwLock};
+
+use ream_consensus::{beacon_state::BeaconState, deposit::Deposit, eth1_data::Eth1Data};
+use ream_execution::eth1::deposit_cache::DepositCache;
+use ream_rpc::eth1::Eth1Provider;
+
+/// Serves block production the eth1 data votes and deposits of the deposit cache filled by the
+/// eth1 service.
+pub struct DepositCacheProvider(pub Arc<RwLock<DepositCache>>);
+
+impl Eth1Provider for DepositCacheProvider {
+    fn eth1_data_vote(&self, state: &BeaconState) -> Eth1Data {
+        self.0
+            .read()
+            .expect("deposit cache lock poisoned")
+            .get_eth1_vote(state)
+    }
+
+    fn deposits(
+        &self,
+        eth1_deposit_index: u64,
+        eth1_data: &Eth1Data,
+    ) -> Result<Vec<Deposit>, String> {
+        self.0
+            .read()
+            .expect("deposit cache lock poisoned")
+            .get_deposits(eth1_deposit_index, eth1_data)
+            .map_err(|err| err.to_string())
+    }
+}
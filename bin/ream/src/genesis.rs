@@ -0,0 +1,296 @@
+//! Genesis states of devnets, built from deposits like `initialize_beacon_state_from_eth1` of
+//! the consensus specs.
+
+use std::collections::HashMap;
+
+use alloy_primitives::{aliases::B32, hex, B256, U256};
+use ream_bls::{aggregate_public_keys, BLSError, PrivateKey};
+use ream_consensus::{
+    beacon_block_body::BeaconBlockBody,
+    beacon_block_header::BeaconBlockHeader,
+    beacon_state::BeaconState,
+    committee::compute_shuffled_index,
+    constants::{
+        DOMAIN_DEPOSIT, DOMAIN_SYNC_COMMITTEE, EFFECTIVE_BALANCE_INCREMENT,
+        EPOCHS_PER_HISTORICAL_VECTOR, FAR_FUTURE_EPOCH, GENESIS_EPOCH, MAX_EFFECTIVE_BALANCE,
+        SYNC_COMMITTEE_SIZE,
+    },
+    deposit::{DepositData, DepositMessage},
+    eth1_data::Eth1Data,
+    execution_payload_header::ExecutionPayloadHeader,
+    fork::Fork,
+    fork_schedule::{ForkName, ForkSchedule},
+    misc::{compute_domain, compute_signing_root},
+    sync_committee::SyncCommittee,
+    validator::Validator,
+};
+use ream_validator::deposit::{bls_withdrawal_credentials, sign_deposit};
+use ssz_types::{typenum::U4294967296, VariableList};
+use tree_hash::TreeHash;
+
+use crate::cli::debug::SUPPORTED_FORK;
+
+/// Order of the BLS12-381 scalar field, interop keys are reduced modulo it.
+const CURVE_ORDER: [u8; 32] =
+    hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+
+/// Block hash the interop genesis of other clients uses by default.
+pub const INTEROP_ETH1_BLOCK_HASH: B256 = B256::repeat_byte(0x42);
+
+#[derive(Debug, thiserror::Error)]
+pub enum GenesisError {
+    #[error(
+        "genesis needs {supported} at epoch 0 but the chain config starts with {0}",
+        supported = SUPPORTED_FORK
+    )]
+    UnsupportedFork(ForkName),
+    #[error("no valid deposit, genesis needs at least one active validator")]
+    NoValidators,
+    #[error(transparent)]
+    Bls(#[from] BLSError),
+}
+
+/// The secret key of interop validator `index`, the little endian SHA-256 of the index
+/// reduced modulo the curve order.
+pub fn interop_secret_key(index: u64) -> PrivateKey {
+    let mut preimage = [0; 32];
+    preimage[..8].copy_from_slice(&index.to_le_bytes());
+    let hash: [u8; 32] = ethereum_hashing::hash(&preimage)
+        .try_into()
+        .expect("SHA-256 is 32 bytes");
+    let key = U256::from_le_bytes(hash) % U256::from_be_bytes(CURVE_ORDER);
+    PrivateKey::from_bytes(&key.to_be_bytes::<32>()).expect("reduced keys are valid")
+}
+
+/// Deposits of the full balance of the first `count` interop validators, withdrawing to their
+/// own key.
+pub fn interop_deposits(count: u64, genesis_fork_version: B32) -> Vec<DepositData> {
+    (0..count)
+        .map(|index| {
+            let key = interop_secret_key(index);
+            let credentials = bls_withdrawal_credentials(&key.public_key());
+            sign_deposit(
+                &key,
+                credentials,
+                MAX_EFFECTIVE_BALANCE,
+                genesis_fork_version,
+            )
+        })
+        .collect()
+}
+
+/// The state at `genesis_time` of a chain starting at [`SUPPORTED_FORK`], with a validator for
+/// each pubkey of `deposits`. Deposits with an invalid proof of possession are left out, like
+/// the deposit contract would.
+pub fn build_genesis_state(
+    fork_schedule: &ForkSchedule,
+    genesis_time: u64,
+    eth1_block_hash: B256,
+    deposits: &[DepositData],
+    execution_payload_header: ExecutionPayloadHeader,
+) -> Result<BeaconState, GenesisError> {
+    let fork = fork_schedule.fork_at_epoch(GENESIS_EPOCH);
+    if fork.name != SUPPORTED_FORK {
+        return Err(GenesisError::UnsupportedFork(fork.name));
+    }
+    let deposit_domain = compute_domain(
+        DOMAIN_DEPOSIT,
+        fork_schedule.genesis_fork().version,
+        B256::ZERO,
+    );
+
+    let mut validators: Vec<Validator> = vec![];
+    let mut balances: Vec<u64> = vec![];
+    let mut indices = HashMap::new();
+    for deposit in deposits {
+        if let Some(&index) = indices.get(&deposit.pubkey) {
+            balances[index] += deposit.amount;
+            continue;
+        }
+        let message = DepositMessage {
+            pubkey: deposit.pubkey,
+            withdrawal_credentials: deposit.withdrawal_credentials,
+            amount: deposit.amount,
+        };
+        let signing_root = compute_signing_root(&message, deposit_domain);
+        if !ream_bls::verify(&deposit.pubkey, signing_root.as_slice(), &deposit.signature)
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        validators.push(Validator {
+            pubkey: deposit.pubkey,
+            withdrawal_credentials: deposit.withdrawal_credentials,
+            effective_balance: 0,
+            slashed: false,
+            activation_eligibility_epoch: FAR_FUTURE_EPOCH,
+            activation_epoch: FAR_FUTURE_EPOCH,
+            exit_epoch: FAR_FUTURE_EPOCH,
+            withdrawable_epoch: FAR_FUTURE_EPOCH,
+        });
+        indices.insert(deposit.pubkey, validators.len() - 1);
+        balances.push(deposit.amount);
+    }
+    for (validator, balance) in validators.iter_mut().zip(&balances) {
+        validator.effective_balance =
+            (balance - balance % EFFECTIVE_BALANCE_INCREMENT).min(MAX_EFFECTIVE_BALANCE);
+        if validator.effective_balance == MAX_EFFECTIVE_BALANCE {
+            validator.activation_eligibility_epoch = GENESIS_EPOCH;
+            validator.activation_epoch = GENESIS_EPOCH;
+        }
+    }
+
+    let deposit_root =
+        VariableList::<DepositData, U4294967296>::from(deposits.to_vec()).tree_hash_root();
+    let validator_count = validators.len();
+    let mut state = BeaconState {
+        genesis_time,
+        fork: Fork {
+            previous_version: fork.version,
+            current_version: fork.version,
+            epoch: GENESIS_EPOCH,
+        },
+        latest_block_header: BeaconBlockHeader {
+            body_root: BeaconBlockBody::default().tree_hash_root(),
+            ..BeaconBlockHeader::default()
+        },
+        eth1_data: Eth1Data {
+            deposit_root,
+            deposit_count: deposits.len() as u64,
+            block_hash: eth1_block_hash,
+        },
+        eth1_deposit_index: deposits.len() as u64,
+        validators: validators.into(),
+        balances: balances.into(),
+        randao_mixes: vec![eth1_block_hash; EPOCHS_PER_HISTORICAL_VECTOR as usize].into(),
+        previous_epoch_participation: vec![0; validator_count].into(),
+        current_epoch_participation: vec![0; validator_count].into(),
+        inactivity_scores: vec![0; validator_count].into(),
+        latest_execution_payload_header: execution_payload_header,
+        ..BeaconState::default()
+    };
+    state.genesis_validators_root = state.validators.tree_hash_root();
+    let sync_committee = next_sync_committee(&state)?;
+    state.current_sync_committee = sync_committee.clone();
+    state.next_sync_committee = sync_committee;
+    Ok(state)
+}
+
+/// `get_next_sync_committee` of the consensus specs, members are sampled from the active
+/// validators weighted by effective balance.
+fn next_sync_committee(state: &BeaconState) -> Result<SyncCommittee, GenesisError> {
+    let epoch = GENESIS_EPOCH + 1;
+    let active = state.get_active_validator_indices(epoch);
+    let count = active.len() as u64;
+    if count == 0 {
+        return Err(GenesisError::NoValidators);
+    }
+    let seed = state.get_seed(epoch, DOMAIN_SYNC_COMMITTEE);
+
+    let mut pubkeys = vec![];
+    let mut index = 0;
+    while pubkeys.len() < SYNC_COMMITTEE_SIZE as usize {
+        let candidate = active[compute_shuffled_index(index % count, count, seed) as usize];
+        let mut preimage = seed.to_vec();
+        preimage.extend_from_slice(&(index / 32).to_le_bytes());
+        let random_byte = u64::from(ethereum_hashing::hash(&preimage)[(index % 32) as usize]);
+        let validator = &state.validators[candidate as usize];
+        if validator.effective_balance * u64::from(u8::MAX) >= MAX_EFFECTIVE_BALANCE * random_byte {
+            pubkeys.push(validator.pubkey);
+        }
+        index += 1;
+    }
+    Ok(SyncCommittee {
+        aggregate_pubkey: aggregate_public_keys(&pubkeys)?,
+        pubkeys: pubkeys.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::fork_schedule::ScheduledFork;
+
+    use super::*;
+
+    fn devnet_schedule() -> ForkSchedule {
+        ForkSchedule::new(
+            [
+                (ForkName::Phase0, [0x10, 0, 0, 0x38]),
+                (ForkName::Altair, [0x20, 0, 0, 0x38]),
+                (ForkName::Bellatrix, [0x30, 0, 0, 0x38]),
+                (ForkName::Capella, [0x40, 0, 0, 0x38]),
+                (ForkName::Deneb, [0x50, 0, 0, 0x38]),
+            ]
+            .map(|(name, version)| ScheduledFork {
+                name,
+                version: B32::from(version),
+                epoch: 0,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_interop_secret_key() {
+        assert_eq!(
+            interop_secret_key(0).public_key().to_string(),
+            concat!(
+                "0xa99a76ed7796f7be22d5b7e85deeb7c5677e88e511e0b337",
+                "618f8c4eb61349b4bf2d153f649f7b53359fe8b94a38e44c"
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_genesis_state() {
+        let schedule = devnet_schedule();
+        let mut deposits = interop_deposits(4, schedule.genesis_fork().version);
+        // A top up of the first validator, and a deposit without a valid signature.
+        deposits.push(DepositData {
+            amount: EFFECTIVE_BALANCE_INCREMENT,
+            ..deposits[0].clone()
+        });
+        deposits.push(DepositData {
+            pubkey: interop_secret_key(4).public_key(),
+            ..deposits[1].clone()
+        });
+
+        let state = build_genesis_state(
+            &schedule,
+            1_700_000_000,
+            INTEROP_ETH1_BLOCK_HASH,
+            &deposits,
+            ExecutionPayloadHeader::default(),
+        )
+        .unwrap();
+        assert_eq!(state.validators.len(), 4);
+        assert_eq!(
+            state.balances[0],
+            MAX_EFFECTIVE_BALANCE + EFFECTIVE_BALANCE_INCREMENT
+        );
+        assert_eq!(state.validators[0].effective_balance, MAX_EFFECTIVE_BALANCE);
+        assert_eq!(state.get_active_validator_indices(GENESIS_EPOCH).len(), 4);
+        assert_eq!(state.eth1_data.deposit_count, 6);
+        assert_eq!(state.fork.current_version, B32::from([0x50, 0, 0, 0x38]));
+        assert_eq!(
+            state.genesis_validators_root,
+            state.validators.tree_hash_root()
+        );
+        assert!(state
+            .current_sync_committee
+            .pubkeys
+            .iter()
+            .all(|pubkey| state.validators.iter().any(|v| v.pubkey == *pubkey)));
+
+        let schedule = ForkSchedule::mainnet();
+        assert!(matches!(
+            build_genesis_state(
+                &schedule,
+                0,
+                B256::ZERO,
+                &deposits,
+                ExecutionPayloadHeader::default()
+            ),
+            Err(GenesisError::UnsupportedFork(ForkName::Phase0))
+        ));
+    }
+}
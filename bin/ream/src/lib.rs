@@ -1 +1,12 @@
 pub mod cli;
+pub mod client_stats;
+pub mod config;
+pub mod engine;
+pub mod eth1;
+pub mod genesis;
+pub mod logging;
+pub mod metrics;
+pub mod payload;
+pub mod shutdown;
+pub mod telemetry;
+pub mod validator_monitor;
@@ -0,0 +1,284 @@
+//! Logging of the node, to the terminal and optionally to a file rotated by size and time.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use opentelemetry::trace::{TraceError, TracerProvider as _};
+use opentelemetry_sdk::trace::TracerProvider;
+use tracing::{level_filters::LevelFilter, Subscriber};
+use tracing_subscriber::{
+    filter::{Directive, EnvFilter, ParseError},
+    fmt::{format::FmtSpan, MakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::{SubscriberInitExt, TryInitError},
+    Layer,
+};
+
+use crate::telemetry::{self, TracingConfig, SERVICE_NAME};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoggingError {
+    #[error("failed to open log file {path}: {error}")]
+    File { path: PathBuf, error: io::Error },
+    #[error("invalid log filter: {0}")]
+    Filter(#[from] ParseError),
+    #[error("failed to install the logger: {0}")]
+    Init(#[from] TryInitError),
+    #[error("failed to start the span exporter: {0}")]
+    Tracing(#[from] TraceError),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Plain,
+    /// One JSON object per line, for log collectors
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Rotation {
+    Never,
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl Rotation {
+    /// Index of the period `time` falls in, the file rotates when it changes.
+    fn period(self, time: SystemTime) -> u64 {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        match self {
+            Rotation::Never => 0,
+            Rotation::Hourly => secs / 3600,
+            Rotation::Daily => secs / 86400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogConfig {
+    /// 1 for errors only up to 5 for traces.
+    pub verbosity: u8,
+    pub format: LogFormat,
+    /// Directives such as `ream_discv5=debug`, applied over the verbosity.
+    pub directives: Vec<String>,
+    pub file: Option<LogFileConfig>,
+    /// Collector the spans are exported to as well.
+    pub tracing: Option<TracingConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    pub path: PathBuf,
+    /// Size in bytes past which the file rotates, 0 for no limit.
+    pub max_size: u64,
+    pub rotation: Rotation,
+    /// Rotated files kept next to the current one.
+    pub max_files: usize,
+}
+
+pub fn verbosity_level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 | 1 => LevelFilter::ERROR,
+        2 => LevelFilter::WARN,
+        3 => LevelFilter::INFO,
+        4 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// The level of `verbosity` refined by `directives`, later directives winning over earlier
+/// ones for the same target.
+pub fn log_filter<'a>(
+    verbosity: u8,
+    directives: impl IntoIterator<Item = &'a str>,
+) -> Result<EnvFilter, ParseError> {
+    let mut filter = EnvFilter::default().add_directive(verbosity_level(verbosity).into());
+    for directive in directives
+        .into_iter()
+        .filter(|directive| !directive.is_empty())
+    {
+        filter = filter.add_directive(directive.parse::<Directive>()?);
+    }
+    Ok(filter)
+}
+
+/// Installs the global logger of `config`. The directives of `RUST_LOG` apply last, to the
+/// exported spans too.
+///
+/// Returns the span exporter, to shut down before the runtime it runs on.
+pub fn init(config: &LogConfig) -> Result<Option<TracerProvider>, LoggingError> {
+    let rust_log = std::env::var("RUST_LOG").unwrap_or_default();
+    let filter = log_filter(
+        config.verbosity,
+        config
+            .directives
+            .iter()
+            .map(String::as_str)
+            .chain(rust_log.split(',')),
+    )?;
+    let file = config.file.clone().map(RollingFile::open).transpose()?;
+    let provider = config
+        .tracing
+        .as_ref()
+        .map(telemetry::tracer_provider)
+        .transpose()?;
+    let otlp = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(config.format, io::stdout, true))
+        .with(file.map(|file| fmt_layer(config.format, Mutex::new(file), false)))
+        .with(otlp)
+        .try_init()?;
+    Ok(provider)
+}
+
+/// Logs closing spans with how long they were busy and idle, so that the stages of a block can
+/// be timed from the logs.
+fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .with_span_events(FmtSpan::CLOSE);
+    match format {
+        LogFormat::Plain => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// A log file rotated once it would grow past its size limit or a new period starts. Rotated
+/// files are named after it, `.1` being the newest, and the oldest are deleted.
+#[derive(Debug)]
+pub struct RollingFile {
+    config: LogFileConfig,
+    file: File,
+    size: u64,
+    period: u64,
+}
+
+impl RollingFile {
+    pub fn open(config: LogFileConfig) -> Result<Self, LoggingError> {
+        let file_error = |error| LoggingError::File {
+            path: config.path.clone(),
+            error,
+        };
+        if let Some(dir) = config.path.parent() {
+            fs::create_dir_all(dir).map_err(file_error)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)
+            .map_err(file_error)?;
+        let metadata = file.metadata().map_err(file_error)?;
+        // A file left from an earlier period rotates on the first write.
+        let modified = metadata.modified().unwrap_or_else(|_| SystemTime::now());
+        Ok(Self {
+            period: config.rotation.period(modified),
+            size: metadata.len(),
+            file,
+            config,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let path = &self.config.path;
+        for index in (1..self.config.max_files).rev() {
+            let rotated = rotated_path(path, index);
+            if rotated.exists() {
+                fs::rename(rotated, rotated_path(path, index + 1))?;
+            }
+        }
+        if self.config.max_files > 0 {
+            fs::rename(path, rotated_path(path, 1))?;
+        }
+        self.file = File::create(path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let period = self.config.rotation.period(SystemTime::now());
+        let full = self.config.max_size > 0 && self.size + buf.len() as u64 > self.config.max_size;
+        if self.size > 0 && (full || period != self.period) {
+            self.rotate()?;
+        }
+        self.period = period;
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `<path>.<index>`
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    name.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter() {
+        let filter = log_filter(2, []).unwrap();
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::WARN));
+        let filter = log_filter(3, ["ream_discv5=trace", ""]).unwrap();
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::TRACE));
+        assert!(log_filter(3, ["ream_discv5=loud"]).is_err());
+    }
+
+    #[test]
+    fn test_rolling_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("logs");
+        let path = dir.join("ream.log");
+        let mut file = RollingFile::open(LogFileConfig {
+            path: path.clone(),
+            max_size: 10,
+            rotation: Rotation::Never,
+            max_files: 2,
+        })
+        .unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "second\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+    }
+}
@@ -1,13 +1,332 @@
-use clap::Parser;
+use std::{sync::Arc, time::Instant};
 
-use ream::cli::{Cli, Commands};
+use ream::{
+    cli::{
+        account::AccountSubcommand, config::ConfigSubcommand, db::open_or_create_store,
+        debug::DebugSubcommand, validator::ValidatorSubcommand, Cli, Commands,
+    },
+    client_stats::{self, BeaconNodeStats},
+    engine::{track_engine_state, update_forkchoice},
+    eth1::DepositCacheProvider,
+    logging, metrics,
+    payload::serve_payload_requests,
+    shutdown,
+    validator_monitor::{self, ValidatorMonitor},
+};
+use ream_consensus::fork_schedule::ForkName;
+use ream_discv5::node_key::load_or_generate_node_key;
+use ream_execution::{
+    capabilities::check_compatibility, eth1::service::Eth1Service, forkchoice::ForkchoiceUpdater,
+    payload_builder::PayloadBuilder,
+};
+use ream_executor::runtimes::Runtimes;
+use ream_metrics::server::start_metrics_server;
+use ream_rpc::{context::ApiContext, server::start_http_server};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = Cli::parse_with_config();
 
     match cli.command {
         Commands::Node(cmd) => {
-            println!("Starting node with verbosity {}", cmd.verbosity);
+            if let Err(err) = cmd.validate() {
+                eprint!("{err}");
+                std::process::exit(1);
+            }
+            let runtimes =
+                Runtimes::new(&cmd.executor_config()).expect("failed to start the runtimes");
+            let runtime = runtimes.networking();
+            let executor = runtimes.executor();
+            let tracer_provider = {
+                // The span exporter sends its batches from a task of the runtime.
+                let _runtime = runtime.enter();
+                match logging::init(&cmd.log_config()) {
+                    Ok(provider) => provider,
+                    Err(err) => {
+                        eprintln!("{err}");
+                        std::process::exit(1);
+                    }
+                }
+            };
+
+            let spec = match cmd.network_spec() {
+                Ok(spec) => spec,
+                Err(err) => {
+                    error!("{err}");
+                    std::process::exit(1);
+                }
+            };
+            let bootnodes = match cmd.bootnodes(&spec) {
+                Ok(bootnodes) => bootnodes,
+                Err(err) => {
+                    error!("{err}");
+                    std::process::exit(1);
+                }
+            };
+            if bootnodes.is_empty() {
+                warn!(
+                    "No bootnodes for {}, pass --bootnodes to find peers",
+                    spec.name
+                );
+            } else {
+                info!(
+                    "Loaded {} bootnodes, discovery isn't run yet",
+                    bootnodes.len()
+                );
+            }
+            let datadir = cmd.datadir();
+            info!(
+                "Starting node on {} with verbosity {} in {}",
+                spec.name,
+                cmd.verbosity,
+                datadir.root().display()
+            );
+            let store = match open_or_create_store(&datadir, cmd.store_config()) {
+                Ok(store) => Arc::new(store),
+                Err(err) => {
+                    error!("{err}");
+                    std::process::exit(1);
+                }
+            };
+            if let Err(err) = load_or_generate_node_key(&datadir.node_key()) {
+                error!("Failed to load {}: {err}", datadir.node_key().display());
+                std::process::exit(1);
+            }
+
+            let engine = match cmd.engine_client() {
+                Ok(engine) => engine.map(Arc::new),
+                Err(err) => {
+                    error!("{err}");
+                    std::process::exit(1);
+                }
+            };
+            if let Some(engine) = engine.clone() {
+                // The engine client speaks the Deneb methods, an execution layer lacking them
+                // would fail every import. One that's down may still come up.
+                match runtime.block_on(check_compatibility(&engine, ForkName::Deneb)) {
+                    Ok(info) => info!("Execution layer runs {}", info.client_version),
+                    Err(err) if err.is_connection_error() => {
+                        warn!("Execution layer unreachable, retrying in the background: {err}")
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        std::process::exit(1);
+                    }
+                }
+                executor.spawn("engine_monitor", async move { engine.monitor().await });
+            }
+            let mut context = ApiContext::new(store, spec.fork_schedule.clone());
+            match shutdown::restore_operation_pool(&context) {
+                Ok(true) => info!("Restored the operation pool of the last shutdown"),
+                Ok(false) => {}
+                Err(err) => warn!("Starting with an empty operation pool: {err}"),
+            }
+            match shutdown::restore_fork_choice(&mut context) {
+                Ok(true) => info!("Restored the fork choice of the last run"),
+                Ok(false) => {}
+                Err(err) => warn!("Starting without fork choice: {err}"),
+            }
+
+            if let Some(engine) = engine {
+                if cmd.suggested_fee_recipient.is_none() {
+                    warn!(
+                        "No --suggested-fee-recipient set, fees of validators without a \
+                         prepared fee recipient will be burnt"
+                    );
+                }
+                executor.spawn(
+                    "engine_state",
+                    track_engine_state(engine.subscribe(), context.chain.clone()),
+                );
+                if let Some(fork_choice) = context.fork_choice.clone() {
+                    executor.spawn_duty(
+                        "forkchoice_updater",
+                        update_forkchoice(
+                            ForkchoiceUpdater::new(engine.clone(), fork_choice),
+                            engine.subscribe(),
+                            context.chain.clone(),
+                        ),
+                    );
+                }
+                let eth1 = Eth1Service::new(
+                    engine.clone(),
+                    spec.deposit_contract,
+                    spec.deposit_contract_deploy_block,
+                );
+                context.eth1 = Some(Arc::new(DepositCacheProvider(eth1.cache())));
+                executor.spawn("eth1", eth1.run());
+                // Proposals can't wait for a busy runtime, payloads are built on the duties one.
+                let (requester, requests) = mpsc::channel(8);
+                context.payload_requester = Some(requester);
+                executor.spawn_duty(
+                    "payload_builder",
+                    serve_payload_requests(
+                        Arc::new(PayloadBuilder::new(engine)),
+                        context.proposer_preparations.clone(),
+                        cmd.suggested_fee_recipient.unwrap_or_default(),
+                        requests,
+                    ),
+                );
+            }
+
+            if !cmd.validator_monitor.is_empty() {
+                let monitor = ValidatorMonitor::new(&cmd.validator_monitor);
+                info!("Monitoring {} validators", cmd.validator_monitor.len());
+                executor.spawn(
+                    "validator_monitor",
+                    validator_monitor::run(context.clone(), monitor),
+                );
+            }
+
+            if let Some(config) = cmd.monitoring_config() {
+                let context = context.clone();
+                let db_dir = datadir.db_dir();
+                let execution_configured = !cmd.execution_endpoint.is_empty();
+                let client = reqwest::Client::new();
+                executor.spawn_interval("client_stats", config.interval, move || {
+                    let stats = BeaconNodeStats::collect(&context, &db_dir, execution_configured);
+                    let client = client.clone();
+                    let endpoint = config.endpoint.clone();
+                    async move { client_stats::send(&client, &endpoint, stats).await }
+                });
+            }
+
+            {
+                let context = context.clone();
+                let blocking = executor.clone();
+                executor.spawn_interval_at(
+                    "persist_state",
+                    Instant::now() + shutdown::PERSIST_INTERVAL,
+                    shutdown::PERSIST_INTERVAL,
+                    move || {
+                        shutdown::persist_state_in_background(blocking.clone(), context.clone())
+                    },
+                );
+            }
+
+            if let Some(config) = cmd.http_config() {
+                let context = context.clone();
+                executor.spawn_critical("http_api", |shutdown| async move {
+                    start_http_server(&config, context, shutdown.cancelled_owned()).await
+                });
+            }
+
+            if let Some(config) = cmd.metrics_config() {
+                let context = context.clone();
+                executor.spawn_graceful("metrics_server", |shutdown| async move {
+                    let on_scrape = move || metrics::update(&context);
+                    if let Err(err) =
+                        start_metrics_server(&config, on_scrape, shutdown.cancelled_owned()).await
+                    {
+                        error!("Metrics server failed: {err}");
+                    }
+                });
+            }
+
+            let failed = runtime.block_on(async {
+                // A failed critical task shuts the node down like a signal does, so that state
+                // is still persisted, but the node then exits with an error.
+                tokio::select! {
+                    signal = shutdown::wait_for_signal() => {
+                        info!("Received {signal}, shutting down");
+                    }
+                    _ = executor.shutdown_token().cancelled_owned() => {}
+                }
+
+                // There's no gossip to stop nor peers to send Goodbye to until the node runs
+                // the network service, state is persisted first so that nothing arriving
+                // meanwhile is lost.
+                match shutdown::persist_state(&context) {
+                    Ok(()) => info!("Persisted the operation pool and fork choice"),
+                    Err(err) => {
+                        error!("Failed to persist the operation pool and fork choice: {err}")
+                    }
+                }
+                let report = executor.shutdown(shutdown::SHUTDOWN_TIMEOUT).await;
+                if !report.timed_out.is_empty() {
+                    warn!(
+                        "Tasks didn't stop in time and were aborted: {}",
+                        report.timed_out.join(", ")
+                    );
+                }
+                executor.failure().is_some()
+            });
+            if let Some(provider) = tracer_provider {
+                if let Err(err) = provider.shutdown() {
+                    warn!("Failed to export the last spans: {err}");
+                }
+            }
+            runtimes.shutdown_timeout(shutdown::SHUTDOWN_TIMEOUT);
+            if failed {
+                std::process::exit(1);
+            }
+            info!("Shutdown complete");
+        }
+        Commands::Init(cmd) => match cmd.execute() {
+            Ok(config) => println!("Wrote {}", config.config_path().display()),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        },
+        Commands::Db(cmd) => {
+            if let Err(err) = cmd.execute(&mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::ImportEra(cmd) => {
+            if let Err(err) = cmd.execute(&mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Validator(cmd) => {
+            if let Some(ValidatorSubcommand::SlashingProtection(command)) = &cmd.command {
+                if let Err(err) = cmd.execute_slashing_protection(command, &mut std::io::stdout()) {
+                    eprintln!("{err}");
+                    std::process::exit(1);
+                }
+                return;
+            }
+            let runtime = tokio::runtime::Runtime::new().expect("failed to start the runtime");
+            if let Err(err) = runtime.block_on(cmd.execute()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Config(ConfigSubcommand::Dump(cmd)) => print!("{}", cmd.effective_config),
+        Commands::Genesis(cmd) => {
+            if let Err(err) = cmd.execute(&mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Debug(DebugSubcommand::Ssz(cmd)) => {
+            if let Err(err) = cmd.execute(&mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Enr(cmd) => {
+            if let Err(err) = cmd.execute(&mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Doctor(cmd) => {
+            let runtime = tokio::runtime::Runtime::new().expect("failed to start the runtime");
+            if let Err(err) = runtime.block_on(cmd.execute(&mut std::io::stdout())) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Account(AccountSubcommand::New(cmd)) => {
+            if let Err(err) = cmd.execute(std::io::stdin().lock(), &mut std::io::stdout()) {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
         }
     }
 }
@@ -0,0 +1,59 @@
+//! Metrics of the node read from its shared state on each scrape, named like those of other
+//! consensus clients so that the usual dashboards work.
+
+use ream_metrics::{int_gauge, set_gauge, IntGauge, Metric};
+use ream_rpc::context::ApiContext;
+
+pub static HEAD_SLOT: Metric<IntGauge> = int_gauge!("beacon_head_slot", "Slot of the head block");
+pub static FINALIZED_EPOCH: Metric<IntGauge> = int_gauge!(
+    "beacon_finalized_epoch",
+    "Epoch of the finalized checkpoint"
+);
+pub static JUSTIFIED_EPOCH: Metric<IntGauge> = int_gauge!(
+    "beacon_current_justified_epoch",
+    "Epoch of the current justified checkpoint"
+);
+pub static IS_SYNCING: Metric<IntGauge> = int_gauge!(
+    "ream_sync_is_syncing",
+    "1 while the node syncs towards the head of the network"
+);
+pub static HEAD_OPTIMISTIC: Metric<IntGauge> = int_gauge!(
+    "ream_head_optimistic",
+    "1 while the payload of the head block isn't verified by the execution layer"
+);
+pub static EXECUTION_LAYER_OFFLINE: Metric<IntGauge> = int_gauge!(
+    "ream_execution_layer_offline",
+    "1 while the execution layer is unreachable"
+);
+pub static KNOWN_PEERS: Metric<IntGauge> = int_gauge!(
+    "ream_discovery_known_peers",
+    "Node records discovery knows of"
+);
+pub static OPERATION_POOL_ATTESTATIONS: Metric<IntGauge> = int_gauge!(
+    "ream_operation_pool_attestations",
+    "Aggregated attestations waiting for inclusion in a block"
+);
+
+/// Reads the gauges of the node from `context`.
+pub fn update(context: &ApiContext) {
+    let chain = context.chain_info();
+    set_gauge(&HEAD_SLOT, chain.head_slot as i64);
+    set_gauge(&FINALIZED_EPOCH, chain.finalized_checkpoint.epoch as i64);
+    set_gauge(&JUSTIFIED_EPOCH, chain.justified_checkpoint.epoch as i64);
+    set_gauge(&IS_SYNCING, chain.is_syncing.into());
+    set_gauge(&HEAD_OPTIMISTIC, chain.head_optimistic.into());
+    set_gauge(&EXECUTION_LAYER_OFFLINE, chain.el_offline.into());
+
+    let peers = context
+        .peers
+        .read()
+        .expect("peer table lock poisoned")
+        .len();
+    set_gauge(&KNOWN_PEERS, peers as i64);
+    let attestations = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned")
+        .num_attestations();
+    set_gauge(&OPERATION_POOL_ATTESTATIONS, attestations as i64);
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use alloy_primitives::Address;
+use ream_execution::{
+    payload_builder::PayloadBuilder,
+    types::{ForkchoiceStateV1, GetPayloadV3Response, HexBlob, PayloadAttributesV3, WithdrawalV1},
+};
+use ream_rpc::{
+    payload::{BlobsBundle, BuiltPayload, PayloadRequest},
+    preparation::ProposerPreparations,
+};
+use tokio::sync::mpsc;
+
+/// Answers the payload requests of block production with payloads built by the execution
+/// layer, paying the fees to the fee recipient the proposer's validator client prepared, or to
+/// `default_fee_recipient`. Returns once the HTTP API is gone.
+pub async fn serve_payload_requests(
+    builder: Arc<PayloadBuilder>,
+    preparations: Arc<ProposerPreparations>,
+    default_fee_recipient: Address,
+    mut requests: mpsc::Receiver<PayloadRequest>,
+) {
+    while let Some(request) = requests.recv().await {
+        let builder = builder.clone();
+        let fee_recipient = preparations
+            .fee_recipient(request.proposer_index)
+            .unwrap_or(default_fee_recipient);
+        tokio::spawn(async move {
+            let attributes = PayloadAttributesV3 {
+                timestamp: request.timestamp,
+                prev_randao: request.prev_randao,
+                suggested_fee_recipient: fee_recipient,
+                withdrawals: request.withdrawals.iter().map(WithdrawalV1::from).collect(),
+                parent_beacon_block_root: request.parent_beacon_block_root,
+            };
+            // The node doesn't follow a chain yet, its safe and finalized blocks are unknown.
+            let forkchoice_state = ForkchoiceStateV1 {
+                head_block_hash: request.parent_hash,
+                ..ForkchoiceStateV1::default()
+            };
+            let result = builder
+                .get_payload(request.slot, forkchoice_state, attributes)
+                .await
+                .map(built_payload)
+                .map_err(|err| err.to_string());
+            // Block production may have given up waiting.
+            let _ = request.result.send(result);
+        });
+    }
+}
+
+fn built_payload(response: GetPayloadV3Response) -> BuiltPayload {
+    let bundle = response.blobs_bundle;
+    BuiltPayload {
+        payload: response.execution_payload.into(),
+        block_value: response.block_value,
+        blobs_bundle: BlobsBundle {
+            commitments: bundle.commitments,
+            proofs: bundle.proofs,
+            blobs: bundle.blobs.into_iter().map(|HexBlob(blob)| blob).collect(),
+        },
+    }
+}
@@ -0,0 +1,186 @@
+//! Ordered shutdown of the node on SIGINT or SIGTERM, the state worth keeping is written to the
+//! database before the services it comes from stop, and every epoch in case of a crash.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use ream_consensus::constants::{SECONDS_PER_SLOT, SLOTS_PER_EPOCH};
+use ream_executor::executor::ReamExecutor;
+use ream_fork_choice::{
+    persisted_fork_choice::{PersistedForkChoice, PersistedForkChoiceError},
+    proto_array_fork_choice::ProtoArrayForkChoice,
+};
+use ream_operation_pool::persisted_operation_pool::PersistedOperationPool;
+use ream_rpc::context::ApiContext;
+use ream_storage::error::StoreError;
+use tracing::{debug, warn};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RestoreError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("invalid persisted fork choice: {0}")]
+    ForkChoice(#[from] PersistedForkChoiceError),
+}
+
+/// How long tasks still running once the services stopped get before they're cancelled.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the state worth keeping is also written while the node runs, so that a crash
+/// loses at most an epoch of it.
+pub const PERSIST_INTERVAL: Duration = Duration::from_secs(SLOTS_PER_EPOCH * SECONDS_PER_SLOT);
+
+/// Waits for SIGINT, or SIGTERM on unix, returning the name of the signal received.
+pub async fn wait_for_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "SIGINT"
+    }
+}
+
+/// Writes the operation pool and fork choice of `context` to its store, in one batch.
+pub fn persist_state(context: &ApiContext) -> Result<(), StoreError> {
+    let operation_pool = PersistedOperationPool::from(
+        &*context
+            .operation_pool
+            .read()
+            .expect("operation pool lock poisoned"),
+    );
+    let fork_choice = context.fork_choice.as_ref().map(|fork_choice| {
+        PersistedForkChoice::from(&*fork_choice.read().expect("fork choice lock poisoned"))
+    });
+    context
+        .store
+        .persist_on_shutdown(&operation_pool, fork_choice.as_ref())
+}
+
+/// Runs [`persist_state`] on the blocking pool of `executor`, logging the outcome.
+pub async fn persist_state_in_background(executor: ReamExecutor, context: ApiContext) {
+    match executor
+        .spawn_blocking("persist_state", move || persist_state(&context))
+        .await
+    {
+        Ok(Ok(())) => debug!("Persisted the operation pool and fork choice"),
+        Ok(Err(err)) => warn!("Failed to persist the operation pool and fork choice: {err}"),
+        // The executor reported the panic.
+        Err(_) => {}
+    }
+}
+
+/// Fills the operation pool of `context` with the operations persisted by the last shutdown,
+/// returning whether there were any.
+pub fn restore_operation_pool(context: &ApiContext) -> Result<bool, StoreError> {
+    let Some(persisted) = context.store.get_operation_pool()? else {
+        return Ok(false);
+    };
+    *context
+        .operation_pool
+        .write()
+        .expect("operation pool lock poisoned") = persisted.into();
+    Ok(true)
+}
+
+/// Sets the fork choice of `context` to the one persisted by the last shutdown or block
+/// import, returning whether there was one.
+pub fn restore_fork_choice(context: &mut ApiContext) -> Result<bool, RestoreError> {
+    let Some(persisted) = context.store.get_fork_choice()? else {
+        return Ok(false);
+    };
+    let fork_choice = ProtoArrayForkChoice::try_from(persisted)?;
+    context.fork_choice = Some(Arc::new(RwLock::new(fork_choice)));
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use ream_consensus::{
+        checkpoint::Checkpoint,
+        fork_schedule::ForkSchedule,
+        voluntary_exit::{SignedVoluntaryExit, VoluntaryExit},
+    };
+    use ream_fork_choice::proto_array::{Block, ExecutionStatus};
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_persist_and_restore() {
+        let store = Arc::new(
+            HotColdStore::open(
+                Store::new(Arc::new(MemoryStore::new())),
+                Store::new(Arc::new(MemoryStore::new())),
+                StoreConfig::default(),
+            )
+            .unwrap(),
+        );
+        let mut context = ApiContext::new(store.clone(), ForkSchedule::mainnet());
+        assert!(!restore_operation_pool(&context).unwrap());
+        assert!(!restore_fork_choice(&mut context).unwrap());
+        let anchor = Block {
+            slot: 0,
+            root: B256::repeat_byte(1),
+            parent_root: B256::ZERO,
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Irrelevant(false),
+        };
+        let fork_choice =
+            ProtoArrayForkChoice::new(anchor, Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        context.fork_choice = Some(Arc::new(RwLock::new(fork_choice)));
+
+        context
+            .operation_pool
+            .write()
+            .unwrap()
+            .insert_voluntary_exit(SignedVoluntaryExit {
+                message: VoluntaryExit {
+                    validator_index: 9,
+                    ..VoluntaryExit::default()
+                },
+                ..SignedVoluntaryExit::default()
+            });
+        persist_state(&context).unwrap();
+
+        let mut restarted = ApiContext::new(store, ForkSchedule::mainnet());
+        assert!(restore_operation_pool(&restarted).unwrap());
+        assert!(restore_fork_choice(&mut restarted).unwrap());
+        assert!(restarted
+            .fork_choice
+            .as_ref()
+            .unwrap()
+            .read()
+            .unwrap()
+            .contains_block(&B256::repeat_byte(1)));
+        let pool = restarted.operation_pool.read().unwrap();
+        assert_eq!(
+            pool.voluntary_exits()
+                .map(|exit| exit.message.validator_index)
+                .collect::<Vec<_>>(),
+            [9]
+        );
+    }
+}
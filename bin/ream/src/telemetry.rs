@@ -0,0 +1,37 @@
+//! Export of the tracing spans over OTLP to a collector such as Jaeger or Tempo, to follow a
+//! block or request across the nodes of a devnet.
+
+use opentelemetry::{trace::TraceError, KeyValue};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    runtime,
+    trace::{Sampler, TracerProvider},
+    Resource,
+};
+use url::Url;
+
+pub const SERVICE_NAME: &str = "ream";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracingConfig {
+    /// gRPC endpoint of the collector, e.g. `http://localhost:4317`.
+    pub endpoint: Url,
+    /// Share of traces exported, from 0 to 1. Spans follow the decision of their parent.
+    pub sample_ratio: f64,
+}
+
+/// Exports the spans sampled by `config` in batches from the current tokio runtime, which must
+/// outlive it. [`TracerProvider::shutdown`] flushes the last batch.
+pub fn tracer_provider(config: &TracingConfig) -> Result<TracerProvider, TraceError> {
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(config.endpoint.as_str())
+        .build()?;
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio,
+        ))))
+        .with_resource(Resource::new([KeyValue::new("service.name", SERVICE_NAME)]))
+        .build())
+}
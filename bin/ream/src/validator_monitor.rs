@@ -0,0 +1,571 @@
+//! Performance of the validators watched with `--validator-monitor`, read from the blocks the
+//! node imports: proposals, attestation inclusion, sync committee participation and balance
+//! changes at the start of each epoch.
+
+use std::collections::{BTreeSet, HashMap};
+
+use futures::StreamExt;
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock,
+    beacon_state::BeaconState,
+    bls::BLSPubkey,
+    committee::{compute_epoch_proposers, CommitteeAssignment, CommitteeCache},
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch},
+};
+use ream_metrics::{
+    histogram_vec, inc_counter_vec, int_counter_vec, int_gauge_vec, observe_vec, set_gauge_vec,
+    HistogramVec, IntCounterVec, IntGaugeVec, Metric,
+};
+use ream_rpc::{
+    context::ApiContext,
+    events::{BeaconEvent, EventTopic},
+    id::ValidatorId,
+};
+use tracing::{debug, info, warn};
+
+pub static BLOCKS: Metric<IntCounterVec> = int_counter_vec!(
+    "validator_monitor_beacon_blocks_total",
+    "Block proposals of a watched validator by result",
+    ["validator", "result"]
+);
+pub static ATTESTATIONS: Metric<IntCounterVec> = int_counter_vec!(
+    "validator_monitor_attestations_total",
+    "Attestations of a watched validator by result",
+    ["validator", "result"]
+);
+pub static INCLUSION_DISTANCE: Metric<HistogramVec> = histogram_vec!(
+    "validator_monitor_attestation_inclusion_distance",
+    "Slots between an attestation of a watched validator and the block that included it",
+    [1.0, 2.0, 3.0, 4.0, 8.0, 16.0, 32.0],
+    ["validator"]
+);
+pub static SYNC_COMMITTEE: Metric<IntCounterVec> = int_counter_vec!(
+    "validator_monitor_sync_committee_messages_total",
+    "Sync committee messages of a watched validator by result",
+    ["validator", "result"]
+);
+pub static BALANCE: Metric<IntGaugeVec> = int_gauge_vec!(
+    "validator_monitor_balance_gwei",
+    "Balance of a watched validator at the start of the epoch",
+    ["validator"]
+);
+
+/// What a watched validator was seen doing, or failing to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Observation {
+    Proposed {
+        validator_index: u64,
+        slot: u64,
+    },
+    ProposalMissed {
+        validator_index: u64,
+        slot: u64,
+    },
+    AttestationIncluded {
+        validator_index: u64,
+        attestation_slot: u64,
+        block_slot: u64,
+    },
+    /// Not included by the end of the epoch after its own.
+    AttestationMissed {
+        validator_index: u64,
+        attestation_slot: u64,
+    },
+    /// Whether the sync aggregate of the block at `slot` includes the validator's message.
+    SyncCommittee {
+        validator_index: u64,
+        slot: u64,
+        participated: bool,
+    },
+    Balance {
+        validator_index: u64,
+        epoch: u64,
+        balance: u64,
+        /// Since the previous epoch, 0 for the first one seen.
+        change: i64,
+    },
+}
+
+impl Observation {
+    pub fn validator_index(&self) -> u64 {
+        match *self {
+            Observation::Proposed {
+                validator_index, ..
+            }
+            | Observation::ProposalMissed {
+                validator_index, ..
+            }
+            | Observation::AttestationIncluded {
+                validator_index, ..
+            }
+            | Observation::AttestationMissed {
+                validator_index, ..
+            }
+            | Observation::SyncCommittee {
+                validator_index, ..
+            }
+            | Observation::Balance {
+                validator_index, ..
+            } => validator_index,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AttestationDuty {
+    validator_index: u64,
+    assignment: CommitteeAssignment,
+}
+
+/// Tracks the duties of the watched validators and checks them against the imported blocks,
+/// which must be processed in slot order.
+#[derive(Debug, Default)]
+pub struct ValidatorMonitor {
+    indices: BTreeSet<u64>,
+    /// Watched public keys without a validator yet, checked again every epoch.
+    unresolved: Vec<BLSPubkey>,
+    epoch: Option<u64>,
+    /// Slots the watched validators propose at in the current epoch.
+    proposals: Vec<(u64, u64)>,
+    attestations: Vec<AttestationDuty>,
+    balances: HashMap<u64, u64>,
+}
+
+impl ValidatorMonitor {
+    pub fn new(watched: &[ValidatorId]) -> Self {
+        let mut monitor = Self::default();
+        for validator in watched {
+            match validator {
+                ValidatorId::Index(index) => {
+                    monitor.indices.insert(*index);
+                }
+                ValidatorId::Pubkey(pubkey) => monitor.unresolved.push(*pubkey),
+            }
+        }
+        monitor
+    }
+
+    /// Indices of the watched validators known so far.
+    pub fn indices(&self) -> &BTreeSet<u64> {
+        &self.indices
+    }
+
+    /// What `block`, imported with `post_state`, tells about the watched validators.
+    pub fn process_block(
+        &mut self,
+        block: &SignedBeaconBlock,
+        post_state: &BeaconState,
+    ) -> Vec<Observation> {
+        let block = &block.message;
+        let mut observations = vec![];
+        let epoch = compute_epoch_at_slot(block.slot);
+        if self.epoch.map_or(true, |current| epoch > current) {
+            self.start_epoch(epoch, block.slot, post_state, &mut observations);
+        }
+
+        // Blocks come in slot order, so duties before this block's slot were missed.
+        self.proposals.retain(|&(slot, validator_index)| {
+            if slot > block.slot {
+                return true;
+            }
+            if slot < block.slot || block.proposer_index != validator_index {
+                observations.push(Observation::ProposalMissed {
+                    validator_index,
+                    slot,
+                });
+            }
+            false
+        });
+        if self.indices.contains(&block.proposer_index) {
+            observations.push(Observation::Proposed {
+                validator_index: block.proposer_index,
+                slot: block.slot,
+            });
+        }
+
+        for attestation in block.body.attestations.iter() {
+            let data = &attestation.data;
+            self.attestations.retain(|duty| {
+                let included = duty.assignment.slot == data.slot
+                    && duty.assignment.committee_index == data.index
+                    && attestation
+                        .aggregation_bits
+                        .get(duty.assignment.position as usize)
+                        .unwrap_or(false);
+                if included {
+                    observations.push(Observation::AttestationIncluded {
+                        validator_index: duty.validator_index,
+                        attestation_slot: data.slot,
+                        block_slot: block.slot,
+                    });
+                }
+                !included
+            });
+        }
+
+        let committee = &post_state.current_sync_committee.pubkeys;
+        let bits = &block.body.sync_aggregate.sync_committee_bits;
+        for &validator_index in &self.indices {
+            let Some(validator) = post_state.validators.get(validator_index as usize) else {
+                continue;
+            };
+            let mut positions = committee
+                .iter()
+                .enumerate()
+                .filter(|(_, pubkey)| **pubkey == validator.pubkey)
+                .map(|(position, _)| position)
+                .peekable();
+            if positions.peek().is_none() {
+                continue;
+            }
+            observations.push(Observation::SyncCommittee {
+                validator_index,
+                slot: block.slot,
+                participated: positions.any(|position| bits.get(position).unwrap_or(false)),
+            });
+        }
+
+        observations
+    }
+
+    /// Closes the duties of earlier epochs, summarizes balances and records the duties of
+    /// `epoch` from `slot` on.
+    fn start_epoch(
+        &mut self,
+        epoch: u64,
+        slot: u64,
+        state: &BeaconState,
+        observations: &mut Vec<Observation>,
+    ) {
+        // Duties before the first block seen may have been fulfilled in blocks never seen.
+        let first_slot = match self.epoch {
+            Some(_) => compute_start_slot_at_epoch(epoch),
+            None => slot,
+        };
+        self.epoch = Some(epoch);
+
+        // Attestations can be included until the end of the epoch after their own.
+        self.attestations.retain(|duty| {
+            let open = compute_epoch_at_slot(duty.assignment.slot) + 2 > epoch;
+            if !open {
+                observations.push(Observation::AttestationMissed {
+                    validator_index: duty.validator_index,
+                    attestation_slot: duty.assignment.slot,
+                });
+            }
+            open
+        });
+        for (slot, validator_index) in self.proposals.drain(..) {
+            observations.push(Observation::ProposalMissed {
+                validator_index,
+                slot,
+            });
+        }
+
+        if !self.unresolved.is_empty() {
+            for (index, validator) in state.validators.iter().enumerate() {
+                if self.unresolved.contains(&validator.pubkey) {
+                    self.indices.insert(index as u64);
+                }
+            }
+            let validators = &state.validators;
+            self.unresolved.retain(|pubkey| {
+                !validators
+                    .iter()
+                    .any(|validator| validator.pubkey == *pubkey)
+            });
+        }
+
+        for &validator_index in &self.indices {
+            let Some(&balance) = state.balances.get(validator_index as usize) else {
+                continue;
+            };
+            let previous = self.balances.insert(validator_index, balance);
+            observations.push(Observation::Balance {
+                validator_index,
+                epoch,
+                balance,
+                change: previous.map_or(0, |previous| balance as i64 - previous as i64),
+            });
+        }
+
+        match compute_epoch_proposers(state) {
+            Ok(proposers) => {
+                let start_slot = compute_start_slot_at_epoch(epoch);
+                self.proposals = (start_slot..)
+                    .zip(proposers)
+                    .filter(|(slot, proposer)| {
+                        *slot >= first_slot && self.indices.contains(proposer)
+                    })
+                    .collect();
+            }
+            Err(err) => {
+                warn!("Validator monitor can't compute the proposers of epoch {epoch}: {err}")
+            }
+        }
+        match CommitteeCache::new(state, epoch) {
+            Ok(committees) => {
+                self.attestations
+                    .extend(self.indices.iter().filter_map(|&validator_index| {
+                        let assignment = committees.get_assignment(validator_index)?;
+                        (assignment.slot >= first_slot).then_some(AttestationDuty {
+                            validator_index,
+                            assignment,
+                        })
+                    }))
+            }
+            Err(err) => {
+                warn!("Validator monitor can't compute the committees of epoch {epoch}: {err}")
+            }
+        }
+    }
+}
+
+/// Logs `observation` and counts it in the metrics of its validator.
+pub fn report(observation: &Observation) {
+    let validator = observation.validator_index().to_string();
+    let validator = validator.as_str();
+    match *observation {
+        Observation::Proposed {
+            validator_index,
+            slot,
+        } => {
+            info!("Monitored validator {validator_index} proposed the block of slot {slot}");
+            inc_counter_vec(&BLOCKS, &[validator, "proposed"]);
+        }
+        Observation::ProposalMissed {
+            validator_index,
+            slot,
+        } => {
+            warn!("Monitored validator {validator_index} missed its proposal at slot {slot}");
+            inc_counter_vec(&BLOCKS, &[validator, "missed"]);
+        }
+        Observation::AttestationIncluded {
+            validator_index,
+            attestation_slot,
+            block_slot,
+        } => {
+            let distance = block_slot.saturating_sub(attestation_slot);
+            info!(
+                "Attestation of monitored validator {validator_index} at slot {attestation_slot} \
+                 included in slot {block_slot} with distance {distance}"
+            );
+            inc_counter_vec(&ATTESTATIONS, &[validator, "included"]);
+            observe_vec(&INCLUSION_DISTANCE, &[validator], distance as f64);
+        }
+        Observation::AttestationMissed {
+            validator_index,
+            attestation_slot,
+        } => {
+            warn!(
+                "Attestation of monitored validator {validator_index} at slot \
+                 {attestation_slot} was not included"
+            );
+            inc_counter_vec(&ATTESTATIONS, &[validator, "missed"]);
+        }
+        Observation::SyncCommittee {
+            validator_index,
+            slot,
+            participated,
+        } => {
+            let result = if participated {
+                debug!(
+                    "Sync committee message of monitored validator {validator_index} included \
+                     in slot {slot}"
+                );
+                "included"
+            } else {
+                warn!(
+                    "Sync committee message of monitored validator {validator_index} missing \
+                     from slot {slot}"
+                );
+                "missed"
+            };
+            inc_counter_vec(&SYNC_COMMITTEE, &[validator, result]);
+        }
+        Observation::Balance {
+            validator_index,
+            epoch,
+            balance,
+            change,
+        } => {
+            info!(
+                "Monitored validator {validator_index} has {balance} Gwei at epoch {epoch}, \
+                 {change:+} since the previous one"
+            );
+            set_gauge_vec(&BALANCE, &[validator], balance as i64);
+        }
+    }
+}
+
+/// Follows the blocks the node imports through its event stream, taking one of its
+/// subscriptions, and reports on the watched validators until the node stops.
+pub async fn run(context: ApiContext, mut monitor: ValidatorMonitor) {
+    let Some(subscription) = context.events.subscribe() else {
+        warn!("Validator monitor can't subscribe to block events, too many subscribers");
+        return;
+    };
+    let mut blocks = Box::pin(subscription.into_stream(vec![EventTopic::Block]));
+    while let Some(event) = blocks.next().await {
+        let BeaconEvent::Block(event) = event else {
+            continue;
+        };
+        let block = match context.store.get_block(&event.block) {
+            Ok(Some(block)) => block,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!(
+                    "Validator monitor failed to load block {}: {err}",
+                    event.block
+                );
+                continue;
+            }
+        };
+        let state = match context
+            .store
+            .get_state(&block.message.state_root, context.replayer.as_ref())
+        {
+            Ok(Some(state)) => state,
+            Ok(None) => continue,
+            Err(err) => {
+                debug!("Validator monitor skipped block {}: {err}", event.block);
+                continue;
+            }
+        };
+        for observation in monitor.process_block(&block, &state) {
+            report(&observation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+    use ream_consensus::{
+        attestation::Attestation,
+        attestation_data::AttestationData,
+        bls::BLSSignature,
+        constants::{FAR_FUTURE_EPOCH, MAX_EFFECTIVE_BALANCE, SLOTS_PER_EPOCH},
+        validator::Validator,
+    };
+    use ssz_types::{BitList, VariableList};
+
+    use super::*;
+
+    fn pubkey(index: u64) -> BLSPubkey {
+        FixedBytes::repeat_byte(index as u8 + 1)
+    }
+
+    fn state(slot: u64) -> BeaconState {
+        let validators = (0..64)
+            .map(|index| Validator {
+                pubkey: pubkey(index),
+                effective_balance: MAX_EFFECTIVE_BALANCE,
+                exit_epoch: FAR_FUTURE_EPOCH,
+                ..Validator::default()
+            })
+            .collect();
+        let mut state = BeaconState {
+            slot,
+            validators: VariableList::new(validators).unwrap(),
+            balances: VariableList::new(vec![MAX_EFFECTIVE_BALANCE; 64]).unwrap(),
+            ..BeaconState::default()
+        };
+        state.current_sync_committee.pubkeys[0] = pubkey(5);
+        state
+    }
+
+    fn block(slot: u64, proposer_index: u64) -> SignedBeaconBlock {
+        let mut block = SignedBeaconBlock::default();
+        block.message.slot = slot;
+        block.message.proposer_index = proposer_index;
+        block
+    }
+
+    #[test]
+    fn test_process_block() {
+        let mut monitor =
+            ValidatorMonitor::new(&[ValidatorId::Index(3), ValidatorId::Pubkey(pubkey(5))]);
+        let epoch = 3;
+        let start_slot = epoch * SLOTS_PER_EPOCH;
+        let last_slot = start_slot + SLOTS_PER_EPOCH - 1;
+        let assignment = CommitteeCache::new(&state(start_slot), epoch)
+            .unwrap()
+            .get_assignment(3)
+            .unwrap();
+
+        let observations = monitor.process_block(&block(start_slot, 3), &state(start_slot));
+        assert_eq!(monitor.indices(), &BTreeSet::from([3, 5]));
+        assert!(observations.contains(&Observation::Proposed {
+            validator_index: 3,
+            slot: start_slot,
+        }));
+        assert!(observations.contains(&Observation::Balance {
+            validator_index: 5,
+            epoch,
+            balance: MAX_EFFECTIVE_BALANCE,
+            change: 0,
+        }));
+
+        let mut aggregation_bits =
+            BitList::with_capacity(assignment.committee_length as usize).unwrap();
+        aggregation_bits
+            .set(assignment.position as usize, true)
+            .unwrap();
+        let mut including = block(last_slot, 0);
+        including.message.body.attestations = VariableList::new(vec![Attestation {
+            aggregation_bits,
+            data: AttestationData {
+                slot: assignment.slot,
+                index: assignment.committee_index,
+                ..AttestationData::default()
+            },
+            signature: BLSSignature::default(),
+        }])
+        .unwrap();
+        including
+            .message
+            .body
+            .sync_aggregate
+            .sync_committee_bits
+            .set(0, true)
+            .unwrap();
+        let observations = monitor.process_block(&including, &state(last_slot));
+        assert!(observations.contains(&Observation::AttestationIncluded {
+            validator_index: 3,
+            attestation_slot: assignment.slot,
+            block_slot: last_slot,
+        }));
+        assert!(observations.contains(&Observation::SyncCommittee {
+            validator_index: 5,
+            slot: last_slot,
+            participated: true,
+        }));
+
+        let mut next_state = state(start_slot + 2 * SLOTS_PER_EPOCH);
+        next_state.balances[3] += 1_000;
+        let observations =
+            monitor.process_block(&block(start_slot + 2 * SLOTS_PER_EPOCH, 0), &next_state);
+        assert!(observations.contains(&Observation::Balance {
+            validator_index: 3,
+            epoch: epoch + 2,
+            balance: MAX_EFFECTIVE_BALANCE + 1_000,
+            change: 1_000,
+        }));
+        // Validator 5 attested in epoch 3 without being included.
+        assert!(observations.iter().any(|observation| matches!(
+            observation,
+            Observation::AttestationMissed {
+                validator_index: 5,
+                ..
+            }
+        )));
+        assert!(!observations.iter().any(|observation| matches!(
+            observation,
+            Observation::AttestationMissed {
+                validator_index: 3,
+                ..
+            }
+        )));
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BLSError {
+    #[error("invalid private key")]
+    InvalidPrivateKey,
+    #[error("invalid public key")]
+    InvalidPublicKey,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("cannot aggregate an empty set")]
+    EmptyAggregate,
+}
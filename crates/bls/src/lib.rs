@@ -0,0 +1,10 @@
+pub mod error;
+pub mod private_key;
+pub mod signature;
+
+pub use error::BLSError;
+pub use private_key::PrivateKey;
+pub use signature::{aggregate_public_keys, aggregate_signatures, fast_aggregate_verify, verify};
+
+/// Domain separation tag of the proof of possession ciphersuite used by Ethereum.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
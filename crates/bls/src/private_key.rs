@@ -0,0 +1,36 @@
+use alloy_primitives::FixedBytes;
+use blst::min_pk::SecretKey;
+
+use crate::{error::BLSError, DST};
+
+/// A BLS12-381 secret key. Doesn't implement `Debug` so it can't end up in logs by accident.
+#[derive(Clone)]
+pub struct PrivateKey(SecretKey);
+
+impl PrivateKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BLSError> {
+        SecretKey::from_bytes(bytes)
+            .map(Self)
+            .map_err(|_| BLSError::InvalidPrivateKey)
+    }
+
+    /// Derives a key from at least 32 bytes of input keying material, the `KeyGen` of the BLS
+    /// signature draft.
+    pub fn key_gen(ikm: &[u8]) -> Result<Self, BLSError> {
+        SecretKey::key_gen(ikm, &[])
+            .map(Self)
+            .map_err(|_| BLSError::InvalidPrivateKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn public_key(&self) -> FixedBytes<48> {
+        FixedBytes::from(self.0.sk_to_pk().to_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> FixedBytes<96> {
+        FixedBytes::from(self.0.sign(message, DST, &[]).to_bytes())
+    }
+}
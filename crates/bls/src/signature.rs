@@ -0,0 +1,132 @@
+use alloy_primitives::FixedBytes;
+use blst::{
+    min_pk::{AggregatePublicKey, AggregateSignature, PublicKey, Signature},
+    BLST_ERROR,
+};
+
+use crate::{error::BLSError, DST};
+
+fn parse_public_key(bytes: &FixedBytes<48>) -> Result<PublicKey, BLSError> {
+    PublicKey::key_validate(bytes.as_slice()).map_err(|_| BLSError::InvalidPublicKey)
+}
+
+fn parse_signature(bytes: &FixedBytes<96>) -> Result<Signature, BLSError> {
+    Signature::from_bytes(bytes.as_slice()).map_err(|_| BLSError::InvalidSignature)
+}
+
+/// Whether `signature` is a valid signature of `message` by `pubkey`.
+pub fn verify(
+    pubkey: &FixedBytes<48>,
+    message: &[u8],
+    signature: &FixedBytes<96>,
+) -> Result<bool, BLSError> {
+    let result = parse_signature(signature)?.verify(
+        true,
+        message,
+        DST,
+        &[],
+        &parse_public_key(pubkey)?,
+        false,
+    );
+    Ok(result == BLST_ERROR::BLST_SUCCESS)
+}
+
+/// Whether `signature` is a valid aggregate of every one of `pubkeys` signing `message`.
+pub fn fast_aggregate_verify(
+    pubkeys: &[FixedBytes<48>],
+    message: &[u8],
+    signature: &FixedBytes<96>,
+) -> Result<bool, BLSError> {
+    if pubkeys.is_empty() {
+        return Ok(false);
+    }
+    let pubkeys = pubkeys
+        .iter()
+        .map(parse_public_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    let pubkeys: Vec<&PublicKey> = pubkeys.iter().collect();
+
+    let result = parse_signature(signature)?.fast_aggregate_verify(true, message, DST, &pubkeys);
+    Ok(result == BLST_ERROR::BLST_SUCCESS)
+}
+
+pub fn aggregate_signatures(signatures: &[FixedBytes<96>]) -> Result<FixedBytes<96>, BLSError> {
+    if signatures.is_empty() {
+        return Err(BLSError::EmptyAggregate);
+    }
+    let signatures = signatures
+        .iter()
+        .map(parse_signature)
+        .collect::<Result<Vec<_>, _>>()?;
+    let signatures: Vec<&Signature> = signatures.iter().collect();
+
+    let aggregate =
+        AggregateSignature::aggregate(&signatures, true).map_err(|_| BLSError::InvalidSignature)?;
+    Ok(FixedBytes::from(aggregate.to_signature().to_bytes()))
+}
+
+/// The public key of the aggregate of signatures by every one of `pubkeys`, like the
+/// `eth_aggregate_pubkeys` of the consensus specs.
+pub fn aggregate_public_keys(pubkeys: &[FixedBytes<48>]) -> Result<FixedBytes<48>, BLSError> {
+    if pubkeys.is_empty() {
+        return Err(BLSError::EmptyAggregate);
+    }
+    let pubkeys = pubkeys
+        .iter()
+        .map(parse_public_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    let pubkeys: Vec<&PublicKey> = pubkeys.iter().collect();
+
+    let aggregate =
+        AggregatePublicKey::aggregate(&pubkeys, false).map_err(|_| BLSError::InvalidPublicKey)?;
+    Ok(FixedBytes::from(aggregate.to_public_key().to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+
+    fn private_key(seed: u8) -> PrivateKey {
+        PrivateKey::key_gen(&[seed; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = private_key(1);
+        let signature = key.sign(b"message");
+
+        assert_eq!(verify(&key.public_key(), b"message", &signature), Ok(true));
+        assert_eq!(verify(&key.public_key(), b"other", &signature), Ok(false));
+        assert_eq!(
+            verify(&private_key(2).public_key(), b"message", &signature),
+            Ok(false)
+        );
+        assert_eq!(
+            verify(&FixedBytes::ZERO, b"message", &signature),
+            Err(BLSError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let keys: Vec<PrivateKey> = (1..=3).map(private_key).collect();
+        let pubkeys: Vec<FixedBytes<48>> = keys.iter().map(PrivateKey::public_key).collect();
+        let signatures: Vec<FixedBytes<96>> = keys.iter().map(|key| key.sign(b"message")).collect();
+
+        let aggregate = aggregate_signatures(&signatures).unwrap();
+        assert_eq!(
+            fast_aggregate_verify(&pubkeys, b"message", &aggregate),
+            Ok(true)
+        );
+        assert_eq!(
+            fast_aggregate_verify(&pubkeys[..2], b"message", &aggregate),
+            Ok(false)
+        );
+        assert_eq!(aggregate_signatures(&[]), Err(BLSError::EmptyAggregate));
+
+        let aggregate_pubkey = aggregate_public_keys(&pubkeys).unwrap();
+        assert_eq!(verify(&aggregate_pubkey, b"message", &aggregate), Ok(true));
+        assert_eq!(aggregate_public_keys(&[]), Err(BLSError::EmptyAggregate));
+    }
+}
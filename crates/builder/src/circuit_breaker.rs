@@ -0,0 +1,73 @@
+use ream_consensus::constants::SLOTS_PER_EPOCH;
+
+pub const DEFAULT_MAX_CONSECUTIVE_MISSED_SLOTS: u64 = 3;
+pub const DEFAULT_MAX_MISSED_SLOTS_PER_EPOCH: u64 = 8;
+
+/// Stops using the builder while the chain is missing blocks, as a builder or relay fault
+/// would otherwise make every proposer relying on it miss its slot too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Slots in a row without a block right before the proposal that trip the breaker.
+    pub max_consecutive_missed_slots: u64,
+    /// Slots without a block in the last epoch that trip the breaker.
+    pub max_missed_slots_per_epoch: u64,
+}
+
+impl CircuitBreaker {
+    /// Whether the block of `slot` must use the local payload, given the slots of the
+    /// canonical blocks before it, of which those of the last epoch matter.
+    pub fn is_tripped(&self, slot: u64, canonical_block_slots: &[u64]) -> bool {
+        let epoch_start = slot.saturating_sub(SLOTS_PER_EPOCH);
+        let recent_blocks = canonical_block_slots
+            .iter()
+            .filter(|block_slot| (epoch_start..slot).contains(*block_slot))
+            .count() as u64;
+        let missed_in_epoch = (slot - epoch_start).saturating_sub(recent_blocks);
+
+        let last_block_slot = canonical_block_slots
+            .iter()
+            .filter(|block_slot| **block_slot < slot)
+            .max();
+        let missed_in_a_row = match last_block_slot {
+            Some(last_block_slot) => slot - last_block_slot - 1,
+            None => slot - epoch_start,
+        };
+
+        missed_in_a_row >= self.max_consecutive_missed_slots
+            || missed_in_epoch >= self.max_missed_slots_per_epoch
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            max_consecutive_missed_slots: DEFAULT_MAX_CONSECUTIVE_MISSED_SLOTS,
+            max_missed_slots_per_epoch: DEFAULT_MAX_MISSED_SLOTS_PER_EPOCH,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::default();
+        let slot = 3 * SLOTS_PER_EPOCH;
+        let all: Vec<u64> = (0..slot).collect();
+        assert!(!breaker.is_tripped(slot, &all));
+
+        // Three empty slots right before the proposal.
+        assert!(breaker.is_tripped(slot, &all[..all.len() - 3]));
+        assert!(!breaker.is_tripped(slot, &all[..all.len() - 2]));
+
+        // Eight empty slots spread over the last epoch.
+        let sparse: Vec<u64> = all
+            .iter()
+            .copied()
+            .filter(|block_slot| !(block_slot % 4 == 0 && *block_slot >= slot - SLOTS_PER_EPOCH))
+            .collect();
+        assert!(breaker.is_tripped(slot, &sparse));
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    blinded_beacon_block::SignedBlindedBeaconBlock, bls::BLSPubkey, fork_schedule::ForkName,
+};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize};
+use url::Url;
+
+use crate::{
+    error::BuilderError,
+    types::{
+        ExecutionPayloadAndBlobsBundle, SignedBuilderBid, SignedValidatorRegistrationV1,
+        VersionedResponse,
+    },
+};
+
+/// Timeouts of each builder API call, short enough for the proposal to fall back to the local
+/// payload in time.
+pub const STATUS_TIMEOUT: Duration = Duration::from_secs(1);
+pub const REGISTER_VALIDATORS_TIMEOUT: Duration = Duration::from_secs(3);
+pub const GET_HEADER_TIMEOUT: Duration = Duration::from_secs(1);
+pub const SUBMIT_BLINDED_BLOCK_TIMEOUT: Duration = Duration::from_secs(3);
+
+const CONSENSUS_VERSION_HEADER: &str = "Eth-Consensus-Version";
+
+#[derive(Debug, Deserialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+/// Client of the builder API of a relay or mev-boost.
+#[derive(Debug, Clone)]
+pub struct BuilderClient {
+    http: reqwest::Client,
+    endpoint: Url,
+}
+
+impl BuilderClient {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint,
+        }
+    }
+
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// `GET /eth/v1/builder/status`, fails unless the builder is ready to serve bids.
+    pub async fn status(&self) -> Result<(), BuilderError> {
+        self.send(
+            self.http
+                .get(self.url("/eth/v1/builder/status"))
+                .timeout(STATUS_TIMEOUT),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `POST /eth/v1/builder/validators`, tells the builder the fee recipients and gas limits
+    /// of the node's validators.
+    pub async fn register_validators(
+        &self,
+        registrations: &[SignedValidatorRegistrationV1],
+    ) -> Result<(), BuilderError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v1/builder/validators"))
+                .json(registrations)
+                .timeout(REGISTER_VALIDATORS_TIMEOUT),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `GET /eth/v1/builder/header/{slot}/{parent_hash}/{pubkey}`, the best bid for the payload
+    /// of `slot`, `None` when the builder has none.
+    pub async fn get_header(
+        &self,
+        slot: u64,
+        parent_hash: B256,
+        pubkey: BLSPubkey,
+    ) -> Result<Option<SignedBuilderBid>, BuilderError> {
+        let response = self
+            .send(
+                self.http
+                    .get(self.url(&format!(
+                        "/eth/v1/builder/header/{slot}/{parent_hash}/{pubkey}"
+                    )))
+                    .timeout(GET_HEADER_TIMEOUT),
+            )
+            .await?;
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+        Ok(Some(Self::decode::<SignedBuilderBid>(response).await?))
+    }
+
+    /// `POST /eth/v1/builder/blinded_blocks`, hands the signed blinded block to the builder in
+    /// exchange for the payload it commits to.
+    pub async fn submit_blinded_block(
+        &self,
+        block: &SignedBlindedBeaconBlock,
+    ) -> Result<ExecutionPayloadAndBlobsBundle, BuilderError> {
+        let response = self
+            .send(
+                self.http
+                    .post(self.url("/eth/v1/builder/blinded_blocks"))
+                    .header(CONSENSUS_VERSION_HEADER, ForkName::Deneb.to_string())
+                    .json(block)
+                    .timeout(SUBMIT_BLINDED_BLOCK_TIMEOUT),
+            )
+            .await?;
+        Self::decode(response).await
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        url.set_path(path);
+        url
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, BuilderError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ErrorMessage>(&body)
+            .map(|error| error.message)
+            .unwrap_or(body);
+        Err(BuilderError::Relay {
+            status: status.as_u16(),
+            message,
+        })
+    }
+
+    async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, BuilderError> {
+        let body = response.bytes().await?;
+        serde_json::from_slice::<VersionedResponse<T>>(&body)
+            .map(|response| response.data)
+            .map_err(|err| BuilderError::InvalidResponse(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode as AxumStatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use ream_consensus::execution_payload::ExecutionPayload;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::types::{BlobsBundle, BuilderBid};
+
+    /// A relay bidding on slot 1 only, which reveals the default payload.
+    async fn mock_relay() -> Url {
+        let get_header = |Path((slot, _, _)): Path<(u64, String, String)>| async move {
+            if slot != 1 {
+                return Err(AxumStatusCode::NO_CONTENT);
+            }
+            let bid = SignedBuilderBid {
+                message: BuilderBid {
+                    header: ExecutionPayload::default().to_execution_payload_header(),
+                    blob_kzg_commitments: Default::default(),
+                    value: U256::from(5),
+                    pubkey: BLSPubkey::repeat_byte(1),
+                },
+                signature: Default::default(),
+            };
+            Ok(Json(json!({ "version": "deneb", "data": bid })))
+        };
+        let submit = |headers: HeaderMap, Json(_): Json<Value>| async move {
+            assert_eq!(headers[CONSENSUS_VERSION_HEADER], "deneb");
+            let revealed = ExecutionPayloadAndBlobsBundle {
+                execution_payload: ExecutionPayload::default(),
+                blobs_bundle: BlobsBundle::default(),
+            };
+            Json(json!({ "version": "deneb", "data": revealed }))
+        };
+        let register = |Json(_): Json<Value>| async move {
+            (
+                AxumStatusCode::BAD_REQUEST,
+                Json(json!({ "code": 400, "message": "unknown validator" })),
+            )
+        };
+        let router = Router::new()
+            .route(
+                "/eth/v1/builder/header/{slot}/{parent_hash}/{pubkey}",
+                get(get_header),
+            )
+            .route("/eth/v1/builder/blinded_blocks", post(submit))
+            .route("/eth/v1/builder/validators", post(register))
+            .route("/eth/v1/builder/status", get(|| async {}));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_builder_client() {
+        let client = BuilderClient::new(mock_relay().await);
+        client.status().await.unwrap();
+
+        let bid = client
+            .get_header(1, B256::ZERO, BLSPubkey::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bid.message.value, U256::from(5));
+        assert_eq!(
+            client
+                .get_header(2, B256::ZERO, BLSPubkey::ZERO)
+                .await
+                .unwrap(),
+            None
+        );
+
+        let revealed = client
+            .submit_blinded_block(&SignedBlindedBeaconBlock::default())
+            .await
+            .unwrap();
+        assert_eq!(revealed.execution_payload, ExecutionPayload::default());
+
+        let err = client.register_validators(&[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            BuilderError::Relay { status: 400, message } if message == "unknown validator"
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(Debug, thiserror::Error)]
+pub enum BuilderError {
+    #[error("builder unreachable: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("builder returned {status}: {message}")]
+    Relay { status: u16, message: String },
+    #[error("invalid response from the builder: {0}")]
+    InvalidResponse(String),
+    #[error("invalid builder bid: {0}")]
+    InvalidBid(String),
+}
@@ -0,0 +1,5 @@
+pub mod circuit_breaker;
+pub mod client;
+pub mod error;
+pub mod selection;
+pub mod types;
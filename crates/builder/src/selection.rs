@@ -0,0 +1,82 @@
+use alloy_primitives::{aliases::B32, B256, U256};
+
+use crate::{error::BuilderError, types::SignedBuilderBid};
+
+/// Builder boost factor under which the builder and local payloads compete on value alone.
+pub const DEFAULT_BUILDER_BOOST_FACTOR: u64 = 100;
+
+/// Where the payload of a proposed block comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadSource {
+    Local,
+    Builder,
+}
+
+/// Picks the builder's payload when its value, scaled by `builder_boost_factor` percent, is
+/// above the value of the local payload. A factor of 0 always picks the local payload.
+pub fn choose_payload_source(
+    local_value: U256,
+    builder_value: U256,
+    builder_boost_factor: u64,
+) -> PayloadSource {
+    let boosted_value = builder_value.saturating_mul(U256::from(builder_boost_factor))
+        / U256::from(DEFAULT_BUILDER_BOOST_FACTOR);
+    if boosted_value > local_value {
+        PayloadSource::Builder
+    } else {
+        PayloadSource::Local
+    }
+}
+
+/// Checks a bid is for a payload on `parent_hash`, pays something and is signed by its
+/// builder.
+pub fn validate_bid(
+    bid: &SignedBuilderBid,
+    parent_hash: B256,
+    genesis_fork_version: B32,
+) -> Result<(), BuilderError> {
+    let header = &bid.message.header;
+    if header.parent_hash != parent_hash {
+        return Err(BuilderError::InvalidBid(format!(
+            "payload builds on {} instead of {parent_hash}",
+            header.parent_hash
+        )));
+    }
+    if bid.message.value.is_zero() {
+        return Err(BuilderError::InvalidBid("bid is worth nothing".to_string()));
+    }
+    if !bid.verify_signature(genesis_fork_version) {
+        return Err(BuilderError::InvalidBid(format!(
+            "invalid signature by builder {}",
+            bid.message.pubkey
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_payload_source() {
+        let value = U256::from;
+        assert_eq!(
+            choose_payload_source(value(100), value(101), DEFAULT_BUILDER_BOOST_FACTOR),
+            PayloadSource::Builder
+        );
+        assert_eq!(
+            choose_payload_source(value(100), value(100), DEFAULT_BUILDER_BOOST_FACTOR),
+            PayloadSource::Local
+        );
+        // Halving the builder's value makes the local payload win.
+        assert_eq!(
+            choose_payload_source(value(100), value(150), 50),
+            PayloadSource::Local
+        );
+        assert_eq!(
+            choose_payload_source(value(0), value(1_000), 0),
+            PayloadSource::Local
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Containers of the builder API specification.
+
+use alloy_primitives::{aliases::B32, Address, B256, U256};
+use ream_consensus::{
+    beacon_block_body::KZGCommitment,
+    blob_sidecar::{Blob, KZGProof},
+    bls::{BLSPubkey, BLSSignature},
+    constants::DOMAIN_APPLICATION_BUILDER,
+    execution_payload::ExecutionPayload,
+    execution_payload_header::ExecutionPayloadHeader,
+    misc::{compute_domain, compute_signing_root},
+};
+use serde::{Deserialize, Serialize};
+use ssz_types::{typenum::U4096, VariableList};
+use tree_hash_derive::TreeHash;
+
+/// Domain of builder API signatures, the same on every fork as builders don't follow forks.
+pub fn compute_builder_domain(genesis_fork_version: B32) -> B256 {
+    compute_domain(DOMAIN_APPLICATION_BUILDER, genesis_fork_version, B256::ZERO)
+}
+
+/// Asks builders to pay `fee_recipient` when building for `pubkey`, in blocks up to
+/// `gas_limit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TreeHash)]
+pub struct ValidatorRegistrationV1 {
+    pub fee_recipient: Address,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub timestamp: u64,
+    pub pubkey: BLSPubkey,
+}
+
+impl ValidatorRegistrationV1 {
+    pub fn signing_root(&self, genesis_fork_version: B32) -> B256 {
+        compute_signing_root(self, compute_builder_domain(genesis_fork_version))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedValidatorRegistrationV1 {
+    pub message: ValidatorRegistrationV1,
+    pub signature: BLSSignature,
+}
+
+impl SignedValidatorRegistrationV1 {
+    /// Whether the registration is signed by the validator it registers.
+    pub fn verify_signature(&self, genesis_fork_version: B32) -> bool {
+        ream_bls::verify(
+            &self.message.pubkey,
+            self.message.signing_root(genesis_fork_version).as_slice(),
+            &self.signature,
+        )
+        .unwrap_or(false)
+    }
+}
+
+/// A builder's offer of a payload worth `value` wei to the proposer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TreeHash)]
+pub struct BuilderBid {
+    pub header: ExecutionPayloadHeader,
+    pub blob_kzg_commitments: VariableList<KZGCommitment, U4096>,
+    #[serde(with = "serde_utils::quoted_u256")]
+    pub value: U256,
+    pub pubkey: BLSPubkey,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBuilderBid {
+    pub message: BuilderBid,
+    pub signature: BLSSignature,
+}
+
+impl SignedBuilderBid {
+    /// Whether the bid is signed by the builder it names.
+    pub fn verify_signature(&self, genesis_fork_version: B32) -> bool {
+        let signing_root =
+            compute_signing_root(&self.message, compute_builder_domain(genesis_fork_version));
+        ream_bls::verify(
+            &self.message.pubkey,
+            signing_root.as_slice(),
+            &self.signature,
+        )
+        .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HexBlob(#[serde(with = "ssz_types::serde_utils::hex_fixed_vec")] pub Blob);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobsBundle {
+    pub commitments: Vec<KZGCommitment>,
+    pub proofs: Vec<KZGProof>,
+    pub blobs: Vec<HexBlob>,
+}
+
+/// What a builder reveals once the proposer signed the blinded block of its bid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionPayloadAndBlobsBundle {
+    pub execution_payload: ExecutionPayload,
+    pub blobs_bundle: BlobsBundle,
+}
+
+/// `{"version": ..., "data": ...}`, the envelope of builder API responses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedResponse<T> {
+    pub version: String,
+    pub data: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_bls::PrivateKey;
+
+    use super::*;
+
+    #[test]
+    fn test_bid_signature() {
+        let key = PrivateKey::key_gen(&[7; 32]).unwrap();
+        let genesis_fork_version = B32::ZERO;
+        let message = BuilderBid {
+            header: ExecutionPayloadHeader::default(),
+            blob_kzg_commitments: VariableList::default(),
+            value: U256::from(1_000_000_000_u64),
+            pubkey: key.public_key(),
+        };
+        let signing_root =
+            compute_signing_root(&message, compute_builder_domain(genesis_fork_version));
+        let bid = SignedBuilderBid {
+            signature: key.sign(signing_root.as_slice()),
+            message,
+        };
+        assert!(bid.verify_signature(genesis_fork_version));
+        assert!(!bid.verify_signature(B32::repeat_byte(1)));
+
+        let json = serde_json::to_value(&bid).unwrap();
+        assert_eq!(json["message"]["value"], "1000000000");
+    }
+}
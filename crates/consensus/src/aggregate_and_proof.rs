@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    attestation::Attestation, bls::BLSSignature, constants::TARGET_AGGREGATORS_PER_COMMITTEE,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash)]
+pub struct AggregateAndProof {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub aggregator_index: u64,
+    pub aggregate: Attestation,
+    pub selection_proof: BLSSignature,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash)]
+pub struct SignedAggregateAndProof {
+    pub message: AggregateAndProof,
+    pub signature: BLSSignature,
+}
+
+/// Whether the validator that produced `slot_signature` has to aggregate for a committee of
+/// `committee_len` validators, about `TARGET_AGGREGATORS_PER_COMMITTEE` members of each
+/// committee are selected.
+pub fn is_aggregator(committee_len: u64, slot_signature: &BLSSignature) -> bool {
+    let modulo = (committee_len / TARGET_AGGREGATORS_PER_COMMITTEE).max(1);
+    let hash = ethereum_hashing::hash(slot_signature.as_slice());
+    let value = u64::from_le_bytes(hash[..8].try_into().expect("sha256 is 32 bytes"));
+    value % modulo == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_committees_always_aggregate() {
+        let signature = BLSSignature::repeat_byte(7);
+        assert!(is_aggregator(1, &signature));
+        assert!(is_aggregator(
+            TARGET_AGGREGATORS_PER_COMMITTEE * 2 - 1,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_aggregator_ratio() {
+        let selected = (0..=255u8)
+            .filter(|byte| is_aggregator(128, &BLSSignature::repeat_byte(*byte)))
+            .count();
+        // One in eight for a committee of 128, allow for the randomness of the hash.
+        assert!((16..=48).contains(&selected), "{selected} selected");
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum::U2048, BitList, VariableList};
+use tree_hash_derive::TreeHash;
+
+use crate::{attestation_data::AttestationData, bls::BLSSignature};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash)]
+pub struct Attestation {
+    pub aggregation_bits: BitList<U2048>,
+    pub data: AttestationData,
+    pub signature: BLSSignature,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct IndexedAttestation {
+    #[serde(with = "ssz_types::serde_utils::quoted_u64_var_list")]
+    pub attesting_indices: VariableList<u64, U2048>,
+    pub data: AttestationData,
+    pub signature: BLSSignature,
+}
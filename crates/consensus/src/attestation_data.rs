@@ -0,0 +1,38 @@
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+use crate::checkpoint::Checkpoint;
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    TreeHash,
+)]
+pub struct AttestationData {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    /// LMD GHOST vote
+    pub beacon_block_root: B256,
+    /// FFG vote
+    pub source: Checkpoint,
+    pub target: Checkpoint,
+}
+
+/// Double vote or surround vote.
+pub fn is_slashable_attestation_data(data_1: &AttestationData, data_2: &AttestationData) -> bool {
+    (data_1 != data_2 && data_1.target.epoch == data_2.target.epoch)
+        || (data_1.source.epoch < data_2.source.epoch && data_2.target.epoch < data_1.target.epoch)
+}
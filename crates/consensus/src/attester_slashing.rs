@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+use crate::attestation::IndexedAttestation;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct AttesterSlashing {
+    pub attestation_1: IndexedAttestation,
+    pub attestation_2: IndexedAttestation,
+}
+
+impl AttesterSlashing {
+    /// Validators that signed both attestations, the ones this slashing applies to, in
+    /// ascending order.
+    pub fn slashable_indices(&self) -> Vec<u64> {
+        let mut indices: Vec<u64> = self
+            .attestation_1
+            .attesting_indices
+            .iter()
+            .filter(|index| self.attestation_2.attesting_indices.contains(index))
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
@@ -0,0 +1,72 @@
+use alloy_primitives::{FixedBytes, B256};
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U128, U16, U2, U4, U4096},
+    FixedVector, VariableList,
+};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    attestation::Attestation,
+    attester_slashing::AttesterSlashing,
+    bls::BLSSignature,
+    bls_to_execution_change::SignedBLSToExecutionChange,
+    constants::EXECUTION_PAYLOAD_GINDEX,
+    deposit::Deposit,
+    eth1_data::Eth1Data,
+    execution_payload::ExecutionPayload,
+    misc::{compute_merkle_branch, floorlog2, get_subtree_index},
+    proposer_slashing::ProposerSlashing,
+    sync_aggregate::SyncAggregate,
+    voluntary_exit::SignedVoluntaryExit,
+};
+
+pub type KZGCommitment = FixedBytes<48>;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BeaconBlockBody {
+    pub randao_reveal: BLSSignature,
+    /// Eth1 data vote
+    pub eth1_data: Eth1Data,
+    /// Arbitrary data
+    pub graffiti: B256,
+    pub proposer_slashings: VariableList<ProposerSlashing, U16>,
+    pub attester_slashings: VariableList<AttesterSlashing, U2>,
+    pub attestations: VariableList<Attestation, U128>,
+    pub deposits: VariableList<Deposit, U16>,
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, U16>,
+    pub sync_aggregate: SyncAggregate,
+    pub execution_payload: ExecutionPayload,
+    pub bls_to_execution_changes: VariableList<SignedBLSToExecutionChange, U16>,
+    pub blob_kzg_commitments: VariableList<KZGCommitment, U4096>,
+}
+
+impl BeaconBlockBody {
+    /// Branch proving ``execution_payload`` against the body root.
+    pub fn execution_payload_branch(&self) -> FixedVector<B256, U4> {
+        let field_roots = vec![
+            self.randao_reveal.tree_hash_root(),
+            self.eth1_data.tree_hash_root(),
+            self.graffiti.tree_hash_root(),
+            self.proposer_slashings.tree_hash_root(),
+            self.attester_slashings.tree_hash_root(),
+            self.attestations.tree_hash_root(),
+            self.deposits.tree_hash_root(),
+            self.voluntary_exits.tree_hash_root(),
+            self.sync_aggregate.tree_hash_root(),
+            self.execution_payload.tree_hash_root(),
+            self.bls_to_execution_changes.tree_hash_root(),
+            self.blob_kzg_commitments.tree_hash_root(),
+        ];
+        FixedVector::new(compute_merkle_branch(
+            &field_roots,
+            floorlog2(EXECUTION_PAYLOAD_GINDEX),
+            get_subtree_index(EXECUTION_PAYLOAD_GINDEX),
+        ))
+        .expect("body fields are at depth 4")
+    }
+}
@@ -0,0 +1,323 @@
+use alloy_primitives::{aliases::B32, Address, B256};
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U1099511627776, U16777216, U2048, U4, U5, U6, U65536, U8192},
+    BitVector, FixedVector, VariableList,
+};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    beacon_block_header::BeaconBlockHeader,
+    checkpoint::Checkpoint,
+    constants::{
+        CURRENT_SYNC_COMMITTEE_GINDEX, EPOCHS_PER_HISTORICAL_VECTOR, FINALIZED_ROOT_GINDEX,
+        MAX_COMMITTEES_PER_SLOT, MAX_EFFECTIVE_BALANCE, MAX_VALIDATORS_PER_WITHDRAWALS_SWEEP,
+        MAX_WITHDRAWALS_PER_PAYLOAD, MIN_SEED_LOOKAHEAD, NEXT_SYNC_COMMITTEE_GINDEX,
+        SLOTS_PER_EPOCH, TARGET_COMMITTEE_SIZE,
+    },
+    eth1_data::Eth1Data,
+    execution_payload_header::ExecutionPayloadHeader,
+    fork::Fork,
+    historical_summary::HistoricalSummary,
+    misc::{compute_epoch_at_slot, compute_merkle_branch, floorlog2, get_subtree_index},
+    sync_committee::SyncCommittee,
+    validator::Validator,
+    withdrawal::Withdrawal,
+};
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BeaconState {
+    // Versioning
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub genesis_time: u64,
+    pub genesis_validators_root: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub fork: Fork,
+
+    // History
+    pub latest_block_header: BeaconBlockHeader,
+    pub block_roots: FixedVector<B256, U8192>,
+    pub state_roots: FixedVector<B256, U8192>,
+    /// Frozen in Capella, replaced by historical_summaries
+    pub historical_roots: VariableList<B256, U16777216>,
+
+    // Eth1
+    pub eth1_data: Eth1Data,
+    pub eth1_data_votes: VariableList<Eth1Data, U2048>,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub eth1_deposit_index: u64,
+
+    // Registry
+    pub validators: VariableList<Validator, U1099511627776>,
+    #[serde(with = "ssz_types::serde_utils::quoted_u64_var_list")]
+    pub balances: VariableList<u64, U1099511627776>,
+
+    // Randomness
+    pub randao_mixes: FixedVector<B256, U65536>,
+
+    // Slashings
+    /// Per-epoch sums of slashed effective balances
+    #[serde(with = "ssz_types::serde_utils::quoted_u64_fixed_vec")]
+    pub slashings: FixedVector<u64, U8192>,
+
+    // Participation
+    pub previous_epoch_participation: VariableList<u8, U1099511627776>,
+    pub current_epoch_participation: VariableList<u8, U1099511627776>,
+
+    // Finality
+    /// Bit set for every recent justified epoch
+    pub justification_bits: BitVector<U4>,
+    pub previous_justified_checkpoint: Checkpoint,
+    pub current_justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+
+    // Inactivity
+    #[serde(with = "ssz_types::serde_utils::quoted_u64_var_list")]
+    pub inactivity_scores: VariableList<u64, U1099511627776>,
+
+    // Sync
+    pub current_sync_committee: SyncCommittee,
+    pub next_sync_committee: SyncCommittee,
+
+    // Execution
+    pub latest_execution_payload_header: ExecutionPayloadHeader,
+
+    // Withdrawals
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub next_withdrawal_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub next_withdrawal_validator_index: u64,
+
+    // Deep history valid from Capella onwards
+    pub historical_summaries: VariableList<HistoricalSummary, U16777216>,
+}
+
+impl BeaconState {
+    /// Root of the latest block applied to this state, whose root is `state_root`.
+    ///
+    /// The state root of `latest_block_header` is only filled in by the next slot processing,
+    /// until then it is zero and `state_root` is used instead.
+    pub fn latest_block_root(&self, state_root: B256) -> B256 {
+        let mut header = self.latest_block_header.clone();
+        if header.state_root == B256::ZERO {
+            header.state_root = state_root;
+        }
+        header.tree_hash_root()
+    }
+
+    /// Roots of the fields, the leaves of the hash tree of the state.
+    fn field_roots(&self) -> Vec<B256> {
+        vec![
+            self.genesis_time.tree_hash_root(),
+            self.genesis_validators_root.tree_hash_root(),
+            self.slot.tree_hash_root(),
+            self.fork.tree_hash_root(),
+            self.latest_block_header.tree_hash_root(),
+            self.block_roots.tree_hash_root(),
+            self.state_roots.tree_hash_root(),
+            self.historical_roots.tree_hash_root(),
+            self.eth1_data.tree_hash_root(),
+            self.eth1_data_votes.tree_hash_root(),
+            self.eth1_deposit_index.tree_hash_root(),
+            self.validators.tree_hash_root(),
+            self.balances.tree_hash_root(),
+            self.randao_mixes.tree_hash_root(),
+            self.slashings.tree_hash_root(),
+            self.previous_epoch_participation.tree_hash_root(),
+            self.current_epoch_participation.tree_hash_root(),
+            self.justification_bits.tree_hash_root(),
+            self.previous_justified_checkpoint.tree_hash_root(),
+            self.current_justified_checkpoint.tree_hash_root(),
+            self.finalized_checkpoint.tree_hash_root(),
+            self.inactivity_scores.tree_hash_root(),
+            self.current_sync_committee.tree_hash_root(),
+            self.next_sync_committee.tree_hash_root(),
+            self.latest_execution_payload_header.tree_hash_root(),
+            self.next_withdrawal_index.tree_hash_root(),
+            self.next_withdrawal_validator_index.tree_hash_root(),
+            self.historical_summaries.tree_hash_root(),
+        ]
+    }
+
+    /// Branch proving ``finalized_checkpoint.root`` against the state root.
+    pub fn finalized_root_branch(&self) -> FixedVector<B256, U6> {
+        // The root is the second field of the checkpoint, next to the epoch.
+        let mut branch = vec![self.finalized_checkpoint.epoch.tree_hash_root()];
+        branch.extend(compute_merkle_branch(
+            &self.field_roots(),
+            floorlog2(FINALIZED_ROOT_GINDEX) - 1,
+            get_subtree_index(FINALIZED_ROOT_GINDEX) / 2,
+        ));
+        FixedVector::new(branch).expect("finalized root branch has the depth of its gindex")
+    }
+
+    /// Branch proving ``current_sync_committee`` against the state root.
+    pub fn current_sync_committee_branch(&self) -> FixedVector<B256, U5> {
+        self.field_branch(CURRENT_SYNC_COMMITTEE_GINDEX)
+    }
+
+    /// Branch proving ``next_sync_committee`` against the state root.
+    pub fn next_sync_committee_branch(&self) -> FixedVector<B256, U5> {
+        self.field_branch(NEXT_SYNC_COMMITTEE_GINDEX)
+    }
+
+    fn field_branch(&self, gindex: u64) -> FixedVector<B256, U5> {
+        FixedVector::new(compute_merkle_branch(
+            &self.field_roots(),
+            floorlog2(gindex),
+            get_subtree_index(gindex),
+        ))
+        .expect("state fields are at depth 5")
+    }
+
+    /// Return the sequence of active validator indices at ``epoch``.
+    pub fn get_active_validator_indices(&self, epoch: u64) -> Vec<u64> {
+        self.validators
+            .iter()
+            .enumerate()
+            .filter(|(_, validator)| validator.is_active_validator(epoch))
+            .map(|(index, _)| index as u64)
+            .collect()
+    }
+
+    /// Return the randao mix at a recent ``epoch``.
+    pub fn get_randao_mix(&self, epoch: u64) -> B256 {
+        self.randao_mixes[(epoch % EPOCHS_PER_HISTORICAL_VECTOR) as usize]
+    }
+
+    /// Return the seed at ``epoch``.
+    pub fn get_seed(&self, epoch: u64, domain_type: B32) -> B256 {
+        let mix =
+            self.get_randao_mix(epoch + EPOCHS_PER_HISTORICAL_VECTOR - MIN_SEED_LOOKAHEAD - 1);
+        let mut preimage = [0; 44];
+        preimage[..4].copy_from_slice(domain_type.as_slice());
+        preimage[4..12].copy_from_slice(&epoch.to_le_bytes());
+        preimage[12..].copy_from_slice(mix.as_slice());
+        B256::from_slice(&ethereum_hashing::hash(&preimage))
+    }
+
+    /// Return the number of committees in each slot for the given ``epoch``.
+    pub fn get_committee_count_per_slot(&self, epoch: u64) -> u64 {
+        let active_validators = self.get_active_validator_indices(epoch).len() as u64;
+        (active_validators / SLOTS_PER_EPOCH / TARGET_COMMITTEE_SIZE)
+            .clamp(1, MAX_COMMITTEES_PER_SLOT)
+    }
+
+    /// Return the withdrawals the execution payload of the next block has to contain, sweeping
+    /// validators from ``next_withdrawal_validator_index`` on.
+    pub fn get_expected_withdrawals(&self) -> Vec<Withdrawal> {
+        let epoch = compute_epoch_at_slot(self.slot);
+        let mut withdrawal_index = self.next_withdrawal_index;
+        let mut validator_index = self.next_withdrawal_validator_index;
+        let mut withdrawals = vec![];
+        let bound = (self.validators.len() as u64).min(MAX_VALIDATORS_PER_WITHDRAWALS_SWEEP);
+        for _ in 0..bound {
+            let validator = &self.validators[validator_index as usize];
+            let balance = self.balances[validator_index as usize];
+            let amount = if validator.is_fully_withdrawable_validator(balance, epoch) {
+                balance
+            } else if validator.is_partially_withdrawable_validator(balance) {
+                balance - MAX_EFFECTIVE_BALANCE
+            } else {
+                0
+            };
+            if amount > 0 {
+                withdrawals.push(Withdrawal {
+                    index: withdrawal_index,
+                    validator_index,
+                    address: Address::from_slice(&validator.withdrawal_credentials[12..]),
+                    amount,
+                });
+                withdrawal_index += 1;
+            }
+            if withdrawals.len() == MAX_WITHDRAWALS_PER_PAYLOAD {
+                break;
+            }
+            validator_index = (validator_index + 1) % self.validators.len() as u64;
+        }
+        withdrawals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz::{Decode, Encode};
+
+    use super::*;
+    use crate::constants::ETH1_ADDRESS_WITHDRAWAL_PREFIX;
+
+    #[test]
+    fn test_latest_block_root() {
+        let mut state = BeaconState {
+            slot: 3,
+            latest_block_header: BeaconBlockHeader {
+                slot: 3,
+                ..BeaconBlockHeader::default()
+            },
+            ..BeaconState::default()
+        };
+        let state_root = state.tree_hash_root();
+        let block_root = state.latest_block_root(state_root);
+
+        state.latest_block_header.state_root = state_root;
+        assert_eq!(state.latest_block_root(B256::ZERO), block_root);
+        assert_eq!(
+            BeaconState::from_ssz_bytes(&state.as_ssz_bytes()).unwrap(),
+            state
+        );
+    }
+
+    #[test]
+    fn test_expected_withdrawals() {
+        let mut state = BeaconState {
+            slot: 10 * SLOTS_PER_EPOCH,
+            next_withdrawal_index: 5,
+            next_withdrawal_validator_index: 2,
+            ..BeaconState::default()
+        };
+        let mut eth1_credentials = B256::repeat_byte(0xaa);
+        eth1_credentials[0] = ETH1_ADDRESS_WITHDRAWAL_PREFIX;
+        for (withdrawable_epoch, balance, credentials) in [
+            (10, 31_000_000_000, eth1_credentials),
+            (u64::MAX, 33_000_000_000, eth1_credentials),
+            (u64::MAX, 33_000_000_000, B256::ZERO),
+            (u64::MAX, 32_000_000_000, eth1_credentials),
+        ] {
+            state
+                .validators
+                .push(Validator {
+                    withdrawal_credentials: credentials,
+                    effective_balance: MAX_EFFECTIVE_BALANCE,
+                    withdrawable_epoch,
+                    ..Validator::default()
+                })
+                .unwrap();
+            state.balances.push(balance).unwrap();
+        }
+
+        let withdrawals = state.get_expected_withdrawals();
+        let address = Address::repeat_byte(0xaa);
+        assert_eq!(
+            withdrawals,
+            vec![
+                Withdrawal {
+                    index: 5,
+                    validator_index: 0,
+                    address,
+                    amount: 31_000_000_000,
+                },
+                Withdrawal {
+                    index: 6,
+                    validator_index: 1,
+                    address,
+                    amount: 1_000_000_000,
+                },
+            ]
+        );
+    }
+}
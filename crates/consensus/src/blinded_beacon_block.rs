@@ -0,0 +1,177 @@
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U128, U16, U2, U4096},
+    VariableList,
+};
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    attestation::Attestation,
+    attester_slashing::AttesterSlashing,
+    beacon_block::{BeaconBlock, SignedBeaconBlock},
+    beacon_block_body::{BeaconBlockBody, KZGCommitment},
+    bls::BLSSignature,
+    bls_to_execution_change::SignedBLSToExecutionChange,
+    deposit::Deposit,
+    eth1_data::Eth1Data,
+    execution_payload::ExecutionPayload,
+    execution_payload_header::ExecutionPayloadHeader,
+    proposer_slashing::ProposerSlashing,
+    sync_aggregate::SyncAggregate,
+    voluntary_exit::SignedVoluntaryExit,
+};
+
+/// A block body carrying only the header of its execution payload, as proposed through a
+/// builder. It has the hash tree root of the full body.
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BlindedBeaconBlockBody {
+    pub randao_reveal: BLSSignature,
+    pub eth1_data: Eth1Data,
+    pub graffiti: B256,
+    pub proposer_slashings: VariableList<ProposerSlashing, U16>,
+    pub attester_slashings: VariableList<AttesterSlashing, U2>,
+    pub attestations: VariableList<Attestation, U128>,
+    pub deposits: VariableList<Deposit, U16>,
+    pub voluntary_exits: VariableList<SignedVoluntaryExit, U16>,
+    pub sync_aggregate: SyncAggregate,
+    pub execution_payload_header: ExecutionPayloadHeader,
+    pub bls_to_execution_changes: VariableList<SignedBLSToExecutionChange, U16>,
+    pub blob_kzg_commitments: VariableList<KZGCommitment, U4096>,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BlindedBeaconBlock {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub proposer_index: u64,
+    pub parent_root: B256,
+    pub state_root: B256,
+    pub body: BlindedBeaconBlockBody,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct SignedBlindedBeaconBlock {
+    pub message: BlindedBeaconBlock,
+    pub signature: BLSSignature,
+}
+
+impl BeaconBlock {
+    /// The block with its execution payload replaced by the payload's header.
+    pub fn to_blinded(&self) -> BlindedBeaconBlock {
+        let body = &self.body;
+        BlindedBeaconBlock {
+            slot: self.slot,
+            proposer_index: self.proposer_index,
+            parent_root: self.parent_root,
+            state_root: self.state_root,
+            body: BlindedBeaconBlockBody {
+                randao_reveal: body.randao_reveal,
+                eth1_data: body.eth1_data.clone(),
+                graffiti: body.graffiti,
+                proposer_slashings: body.proposer_slashings.clone(),
+                attester_slashings: body.attester_slashings.clone(),
+                attestations: body.attestations.clone(),
+                deposits: body.deposits.clone(),
+                voluntary_exits: body.voluntary_exits.clone(),
+                sync_aggregate: body.sync_aggregate.clone(),
+                execution_payload_header: body.execution_payload.to_execution_payload_header(),
+                bls_to_execution_changes: body.bls_to_execution_changes.clone(),
+                blob_kzg_commitments: body.blob_kzg_commitments.clone(),
+            },
+        }
+    }
+}
+
+impl SignedBlindedBeaconBlock {
+    /// The full block with `payload` in place of its header, `None` if `payload` isn't the one
+    /// the header commits to. The signature stays valid as the block root doesn't change.
+    pub fn into_full(self, payload: ExecutionPayload) -> Option<SignedBeaconBlock> {
+        let BlindedBeaconBlock {
+            slot,
+            proposer_index,
+            parent_root,
+            state_root,
+            body,
+        } = self.message;
+        if body.execution_payload_header != payload.to_execution_payload_header() {
+            return None;
+        }
+        Some(SignedBeaconBlock {
+            message: BeaconBlock {
+                slot,
+                proposer_index,
+                parent_root,
+                state_root,
+                body: BeaconBlockBody {
+                    randao_reveal: body.randao_reveal,
+                    eth1_data: body.eth1_data,
+                    graffiti: body.graffiti,
+                    proposer_slashings: body.proposer_slashings,
+                    attester_slashings: body.attester_slashings,
+                    attestations: body.attestations,
+                    deposits: body.deposits,
+                    voluntary_exits: body.voluntary_exits,
+                    sync_aggregate: body.sync_aggregate,
+                    execution_payload: payload,
+                    bls_to_execution_changes: body.bls_to_execution_changes,
+                    blob_kzg_commitments: body.blob_kzg_commitments,
+                },
+            },
+            signature: self.signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, U256};
+    use tree_hash::TreeHash;
+
+    use super::*;
+    use crate::{execution_payload::Transaction, withdrawal::Withdrawal};
+
+    #[test]
+    fn test_blinded_block_round_trip() {
+        let payload = ExecutionPayload {
+            block_number: 7,
+            base_fee_per_gas: U256::from(9),
+            transactions: VariableList::from(vec![Transaction::from(vec![0x02, 0xf8])]),
+            withdrawals: vec![Withdrawal {
+                index: 1,
+                validator_index: 2,
+                address: Address::repeat_byte(3),
+                amount: 4,
+            }]
+            .into(),
+            ..ExecutionPayload::default()
+        };
+        let block = BeaconBlock {
+            slot: 5,
+            body: BeaconBlockBody {
+                execution_payload: payload.clone(),
+                ..BeaconBlockBody::default()
+            },
+            ..BeaconBlock::default()
+        };
+        let blinded = block.to_blinded();
+        assert_eq!(blinded.tree_hash_root(), block.tree_hash_root());
+
+        let signed = SignedBlindedBeaconBlock {
+            message: blinded,
+            signature: BLSSignature::repeat_byte(1),
+        };
+        assert_eq!(signed.clone().into_full(ExecutionPayload::default()), None);
+        let full = signed.into_full(payload).unwrap();
+        assert_eq!(full.message, block);
+        assert_eq!(full.signature, BLSSignature::repeat_byte(1));
+    }
+}
@@ -0,0 +1,45 @@
+use alloy_primitives::{FixedBytes, B256};
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U131072, U17},
+    FixedVector,
+};
+use tree_hash_derive::TreeHash;
+
+use crate::{beacon_block_body::KZGCommitment, beacon_block_header::SignedBeaconBlockHeader};
+
+pub type Blob = FixedVector<u8, U131072>;
+pub type KZGProof = FixedBytes<48>;
+
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The hash a blob is referenced by in the execution layer.
+pub fn kzg_commitment_to_versioned_hash(kzg_commitment: &KZGCommitment) -> B256 {
+    let mut versioned_hash = B256::from_slice(&ethereum_hashing::hash(kzg_commitment.as_slice()));
+    versioned_hash[0] = VERSIONED_HASH_VERSION_KZG;
+    versioned_hash
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BlobSidecar {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "ssz_types::serde_utils::hex_fixed_vec")]
+    pub blob: Blob,
+    pub kzg_commitment: KZGCommitment,
+    pub kzg_proof: KZGProof,
+    pub signed_block_header: SignedBeaconBlockHeader,
+    pub kzg_commitment_inclusion_proof: FixedVector<B256, U17>,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BlobIdentifier {
+    pub block_root: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+}
@@ -0,0 +1,11 @@
+use alloy_primitives::FixedBytes;
+
+pub type BLSPubkey = FixedBytes<48>;
+pub type BLSSignature = FixedBytes<96>;
+
+/// The compressed G2 point at infinity, the aggregate of no signatures.
+pub const G2_POINT_AT_INFINITY: BLSSignature = {
+    let mut bytes = [0; 96];
+    bytes[0] = 0xc0;
+    FixedBytes(bytes)
+};
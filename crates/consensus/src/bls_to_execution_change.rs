@@ -0,0 +1,24 @@
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::{BLSPubkey, BLSSignature};
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct BLSToExecutionChange {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    pub from_bls_pubkey: BLSPubkey,
+    pub to_execution_address: Address,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct SignedBLSToExecutionChange {
+    pub message: BLSToExecutionChange,
+    pub signature: BLSSignature,
+}
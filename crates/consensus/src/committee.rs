@@ -0,0 +1,307 @@
+use std::ops::Range;
+
+use alloy_primitives::B256;
+
+use crate::{
+    beacon_state::BeaconState,
+    constants::{
+        DOMAIN_BEACON_ATTESTER, DOMAIN_BEACON_PROPOSER, EPOCHS_PER_HISTORICAL_VECTOR,
+        MAX_EFFECTIVE_BALANCE, MIN_SEED_LOOKAHEAD, SHUFFLE_ROUND_COUNT, SLOTS_PER_EPOCH,
+    },
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CommitteeError {
+    #[error("epoch {epoch} can't be computed from a state at epoch {state_epoch}")]
+    EpochOutOfRange { epoch: u64, state_epoch: u64 },
+    #[error("no active validators at epoch {0}")]
+    NoActiveValidators(u64),
+}
+
+fn shuffle_pivot(seed: B256, round: u8, index_count: u64) -> u64 {
+    let mut preimage = [0; 33];
+    preimage[..32].copy_from_slice(seed.as_slice());
+    preimage[32] = round;
+    let hash = ethereum_hashing::hash(&preimage);
+    u64::from_le_bytes(hash[..8].try_into().expect("sha256 is 32 bytes")) % index_count
+}
+
+fn shuffle_source(seed: B256, round: u8, position: u64) -> Vec<u8> {
+    let mut preimage = [0; 37];
+    preimage[..32].copy_from_slice(seed.as_slice());
+    preimage[32] = round;
+    preimage[33..].copy_from_slice(&((position / 256) as u32).to_le_bytes());
+    ethereum_hashing::hash(&preimage)
+}
+
+fn shuffle_bit(source: &[u8], position: u64) -> bool {
+    (source[((position % 256) / 8) as usize] >> (position % 8)) & 1 == 1
+}
+
+/// Return the shuffled index corresponding to ``seed`` (and ``index_count``).
+pub fn compute_shuffled_index(mut index: u64, index_count: u64, seed: B256) -> u64 {
+    assert!(index < index_count, "index {index} out of {index_count}");
+    for round in 0..SHUFFLE_ROUND_COUNT {
+        let pivot = shuffle_pivot(seed, round, index_count);
+        let flip = (pivot + index_count - index) % index_count;
+        let position = index.max(flip);
+        if shuffle_bit(&shuffle_source(seed, round, position), position) {
+            index = flip;
+        }
+    }
+    index
+}
+
+/// [`compute_shuffled_index`] of every index below `index_count` at once, hashing each source
+/// block once per round instead of once per index.
+pub fn compute_shuffled_indices(index_count: u64, seed: B256) -> Vec<u64> {
+    let mut indices: Vec<u64> = (0..index_count).collect();
+    if index_count == 0 {
+        return indices;
+    }
+    for round in 0..SHUFFLE_ROUND_COUNT {
+        let pivot = shuffle_pivot(seed, round, index_count);
+        let sources: Vec<Vec<u8>> = (0..index_count.div_ceil(256))
+            .map(|block| shuffle_source(seed, round, block * 256))
+            .collect();
+        for index in indices.iter_mut() {
+            let flip = (pivot + index_count - *index) % index_count;
+            let position = (*index).max(flip);
+            if shuffle_bit(&sources[(position / 256) as usize], position) {
+                *index = flip;
+            }
+        }
+    }
+    indices
+}
+
+/// Return from ``indices`` a random index sampled by effective balance.
+pub fn compute_proposer_index(state: &BeaconState, indices: &[u64], seed: B256) -> Option<u64> {
+    if indices.is_empty() {
+        return None;
+    }
+    let total = indices.len() as u64;
+    let mut preimage = [0; 40];
+    preimage[..32].copy_from_slice(seed.as_slice());
+    for i in 0.. {
+        let candidate = indices[compute_shuffled_index(i % total, total, seed) as usize];
+        preimage[32..].copy_from_slice(&(i / 32).to_le_bytes());
+        let random_byte = ethereum_hashing::hash(&preimage)[(i % 32) as usize];
+        let effective_balance = state.validators[candidate as usize].effective_balance;
+        if effective_balance * u8::MAX as u64 >= MAX_EFFECTIVE_BALANCE * random_byte as u64 {
+            return Some(candidate);
+        }
+    }
+    unreachable!("the sampling loop only ends by returning")
+}
+
+/// Proposer of every slot of the epoch `state` is in, in slot order.
+///
+/// Proposers depend on effective balances, which only settle at the start of the epoch, so
+/// they can't be computed ahead from an earlier state.
+pub fn compute_epoch_proposers(state: &BeaconState) -> Result<Vec<u64>, CommitteeError> {
+    let epoch = compute_epoch_at_slot(state.slot);
+    let indices = state.get_active_validator_indices(epoch);
+    let epoch_seed = state.get_seed(epoch, DOMAIN_BEACON_PROPOSER);
+    let mut preimage = [0; 40];
+    preimage[..32].copy_from_slice(epoch_seed.as_slice());
+    (compute_start_slot_at_epoch(epoch)..compute_start_slot_at_epoch(epoch + 1))
+        .map(|slot| {
+            preimage[32..].copy_from_slice(&slot.to_le_bytes());
+            let seed = B256::from_slice(&ethereum_hashing::hash(&preimage));
+            compute_proposer_index(state, &indices, seed)
+                .ok_or(CommitteeError::NoActiveValidators(epoch))
+        })
+        .collect()
+}
+
+/// Where a validator attests in an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitteeAssignment {
+    pub slot: u64,
+    pub committee_index: u64,
+    pub committee_length: u64,
+    /// Position of the validator in its committee.
+    pub position: u64,
+}
+
+/// The shuffled beacon committees of one epoch.
+///
+/// The shuffling only depends on the randao mix of `epoch - MIN_SEED_LOOKAHEAD - 1` and on the
+/// active validators, so any state that agrees on those yields the same committees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitteeCache {
+    epoch: u64,
+    committees_per_slot: u64,
+    /// Active validator indices in committee order.
+    shuffling: Vec<u64>,
+    /// Position of each validator in `shuffling`, by validator index, `u32::MAX` if inactive.
+    positions: Vec<u32>,
+}
+
+impl CommitteeCache {
+    /// Computes the committees of `epoch`, which must be at most `MIN_SEED_LOOKAHEAD` epochs
+    /// ahead of `state` and have its randao mix still in the state.
+    pub fn new(state: &BeaconState, epoch: u64) -> Result<Self, CommitteeError> {
+        let state_epoch = compute_epoch_at_slot(state.slot);
+        if epoch > state_epoch + MIN_SEED_LOOKAHEAD
+            || epoch + EPOCHS_PER_HISTORICAL_VECTOR <= state_epoch + MIN_SEED_LOOKAHEAD + 1
+        {
+            return Err(CommitteeError::EpochOutOfRange { epoch, state_epoch });
+        }
+
+        let indices = state.get_active_validator_indices(epoch);
+        if indices.is_empty() {
+            return Err(CommitteeError::NoActiveValidators(epoch));
+        }
+        let seed = state.get_seed(epoch, DOMAIN_BEACON_ATTESTER);
+        let shuffling: Vec<u64> = compute_shuffled_indices(indices.len() as u64, seed)
+            .into_iter()
+            .map(|index| indices[index as usize])
+            .collect();
+        let mut positions = vec![u32::MAX; state.validators.len()];
+        for (position, validator_index) in shuffling.iter().enumerate() {
+            positions[*validator_index as usize] = position as u32;
+        }
+        Ok(Self {
+            epoch,
+            committees_per_slot: state.get_committee_count_per_slot(epoch),
+            shuffling,
+            positions,
+        })
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn committees_per_slot(&self) -> u64 {
+        self.committees_per_slot
+    }
+
+    pub fn active_validator_count(&self) -> usize {
+        self.shuffling.len()
+    }
+
+    fn committee_count(&self) -> usize {
+        (self.committees_per_slot * SLOTS_PER_EPOCH) as usize
+    }
+
+    /// Range of `shuffling` holding the `index`th committee of the epoch.
+    fn committee_range(&self, index: usize) -> Range<usize> {
+        let len = self.shuffling.len();
+        len * index / self.committee_count()..len * (index + 1) / self.committee_count()
+    }
+
+    /// Return the beacon committee at ``slot`` for ``index``, `None` outside of the epoch.
+    pub fn get_beacon_committee(&self, slot: u64, committee_index: u64) -> Option<&[u64]> {
+        if compute_epoch_at_slot(slot) != self.epoch || committee_index >= self.committees_per_slot
+        {
+            return None;
+        }
+        let index = (slot % SLOTS_PER_EPOCH) * self.committees_per_slot + committee_index;
+        Some(&self.shuffling[self.committee_range(index as usize)])
+    }
+
+    /// Where `validator_index` attests this epoch, `None` if it isn't active.
+    pub fn get_assignment(&self, validator_index: u64) -> Option<CommitteeAssignment> {
+        let position = *self.positions.get(validator_index as usize)?;
+        if position == u32::MAX {
+            return None;
+        }
+        let position = position as usize;
+        // Committees differ in length by at most one, so the estimate is off by at most one.
+        let mut index = position * self.committee_count() / self.shuffling.len();
+        while self.committee_range(index).start > position {
+            index -= 1;
+        }
+        while self.committee_range(index).end <= position {
+            index += 1;
+        }
+        let range = self.committee_range(index);
+        Some(CommitteeAssignment {
+            slot: compute_start_slot_at_epoch(self.epoch) + index as u64 / self.committees_per_slot,
+            committee_index: index as u64 % self.committees_per_slot,
+            committee_length: range.len() as u64,
+            position: (position - range.start) as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssz_types::VariableList;
+
+    use super::*;
+    use crate::{
+        constants::{FAR_FUTURE_EPOCH, TARGET_COMMITTEE_SIZE},
+        validator::Validator,
+    };
+
+    fn state_with_validators(count: usize) -> BeaconState {
+        let validator = Validator {
+            effective_balance: MAX_EFFECTIVE_BALANCE,
+            exit_epoch: FAR_FUTURE_EPOCH,
+            ..Validator::default()
+        };
+        BeaconState {
+            slot: 3 * SLOTS_PER_EPOCH + 5,
+            validators: VariableList::new(vec![validator; count]).unwrap(),
+            ..BeaconState::default()
+        }
+    }
+
+    #[test]
+    fn test_committees_cover_active_validators() {
+        let seed = B256::repeat_byte(0x42);
+        let shuffled = compute_shuffled_indices(300, seed);
+        for (index, shuffled_index) in shuffled.iter().enumerate() {
+            assert_eq!(
+                compute_shuffled_index(index as u64, 300, seed),
+                *shuffled_index
+            );
+        }
+
+        let count = (2 * SLOTS_PER_EPOCH * TARGET_COMMITTEE_SIZE + 7) as usize;
+        let mut state = state_with_validators(count);
+        state.validators[0].exit_epoch = 2;
+        let cache = CommitteeCache::new(&state, 4).unwrap();
+        assert_eq!(cache.committees_per_slot(), 2);
+        assert_eq!(cache.active_validator_count(), count - 1);
+        assert_eq!(cache.get_assignment(0), None);
+
+        let mut seen = vec![false; count];
+        for slot in compute_start_slot_at_epoch(4)..compute_start_slot_at_epoch(5) {
+            for committee_index in 0..2 {
+                let committee = cache.get_beacon_committee(slot, committee_index).unwrap();
+                for (position, validator_index) in committee.iter().enumerate() {
+                    assert!(!seen[*validator_index as usize]);
+                    seen[*validator_index as usize] = true;
+                    let assignment = cache.get_assignment(*validator_index).unwrap();
+                    assert_eq!(
+                        (
+                            assignment.slot,
+                            assignment.committee_index,
+                            assignment.position
+                        ),
+                        (slot, committee_index, position as u64)
+                    );
+                }
+            }
+        }
+        assert_eq!(seen.iter().filter(|seen| **seen).count(), count - 1);
+        assert_eq!(cache.get_beacon_committee(0, 0), None);
+
+        assert_eq!(
+            CommitteeCache::new(&state, 5),
+            Err(CommitteeError::EpochOutOfRange {
+                epoch: 5,
+                state_epoch: 3
+            })
+        );
+        let proposers = compute_epoch_proposers(&state).unwrap();
+        assert_eq!(proposers.len(), SLOTS_PER_EPOCH as usize);
+        assert!(proposers.iter().all(|proposer| *proposer != 0));
+    }
+}
@@ -0,0 +1,65 @@
+use alloy_primitives::aliases::B32;
+
+pub const SECONDS_PER_SLOT: u64 = 12;
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SLOTS_PER_HISTORICAL_ROOT: u64 = 8192;
+pub const SYNC_COMMITTEE_SIZE: u64 = 512;
+
+pub const MAX_VALIDATORS_PER_COMMITTEE: u64 = 2048;
+pub const MAX_COMMITTEES_PER_SLOT: u64 = 64;
+pub const TARGET_COMMITTEE_SIZE: u64 = 128;
+pub const SHUFFLE_ROUND_COUNT: u8 = 90;
+pub const EPOCHS_PER_HISTORICAL_VECTOR: u64 = 65536;
+pub const MIN_SEED_LOOKAHEAD: u64 = 1;
+pub const MAX_EFFECTIVE_BALANCE: u64 = 32_000_000_000;
+pub const EFFECTIVE_BALANCE_INCREMENT: u64 = 1_000_000_000;
+pub const MIN_DEPOSIT_AMOUNT: u64 = 1_000_000_000;
+
+pub const MAX_PROPOSER_SLASHINGS: usize = 16;
+pub const MAX_ATTESTER_SLASHINGS: usize = 2;
+pub const MAX_ATTESTATIONS: usize = 128;
+pub const MAX_VOLUNTARY_EXITS: usize = 16;
+pub const MAX_BLS_TO_EXECUTION_CHANGES: usize = 16;
+pub const MAX_DEPOSITS: u64 = 16;
+
+pub const DEPOSIT_CONTRACT_TREE_DEPTH: u64 = 32;
+pub const EPOCHS_PER_ETH1_VOTING_PERIOD: u64 = 64;
+pub const ETH1_FOLLOW_DISTANCE: u64 = 2048;
+pub const SECONDS_PER_ETH1_BLOCK: u64 = 14;
+
+pub const MAX_BLOBS_PER_BLOCK: u64 = 6;
+pub const MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS: u64 = 4096;
+
+pub const TARGET_AGGREGATORS_PER_COMMITTEE: u64 = 16;
+pub const ATTESTATION_SUBNET_COUNT: u64 = 64;
+pub const SYNC_COMMITTEE_SUBNET_COUNT: u64 = 4;
+
+pub const EXECUTION_PAYLOAD_GINDEX: u64 = 25;
+pub const FINALIZED_ROOT_GINDEX: u64 = 105;
+pub const CURRENT_SYNC_COMMITTEE_GINDEX: u64 = 54;
+pub const NEXT_SYNC_COMMITTEE_GINDEX: u64 = 55;
+pub const MIN_SYNC_COMMITTEE_PARTICIPANTS: u64 = 1;
+
+pub const GENESIS_SLOT: u64 = 0;
+pub const GENESIS_EPOCH: u64 = 0;
+pub const FAR_FUTURE_EPOCH: u64 = u64::MAX;
+pub const SHARD_COMMITTEE_PERIOD: u64 = 256;
+
+pub const BLS_WITHDRAWAL_PREFIX: u8 = 0x00;
+pub const ETH1_ADDRESS_WITHDRAWAL_PREFIX: u8 = 0x01;
+pub const MAX_WITHDRAWALS_PER_PAYLOAD: usize = 16;
+pub const MAX_VALIDATORS_PER_WITHDRAWALS_SWEEP: u64 = 16384;
+
+pub const DOMAIN_BEACON_PROPOSER: B32 = B32::new([0x00, 0x00, 0x00, 0x00]);
+pub const DOMAIN_BEACON_ATTESTER: B32 = B32::new([0x01, 0x00, 0x00, 0x00]);
+pub const DOMAIN_RANDAO: B32 = B32::new([0x02, 0x00, 0x00, 0x00]);
+pub const DOMAIN_DEPOSIT: B32 = B32::new([0x03, 0x00, 0x00, 0x00]);
+pub const DOMAIN_VOLUNTARY_EXIT: B32 = B32::new([0x04, 0x00, 0x00, 0x00]);
+pub const DOMAIN_SELECTION_PROOF: B32 = B32::new([0x05, 0x00, 0x00, 0x00]);
+pub const DOMAIN_AGGREGATE_AND_PROOF: B32 = B32::new([0x06, 0x00, 0x00, 0x00]);
+pub const DOMAIN_SYNC_COMMITTEE: B32 = B32::new([0x07, 0x00, 0x00, 0x00]);
+pub const DOMAIN_SYNC_COMMITTEE_SELECTION_PROOF: B32 = B32::new([0x08, 0x00, 0x00, 0x00]);
+pub const DOMAIN_CONTRIBUTION_AND_PROOF: B32 = B32::new([0x09, 0x00, 0x00, 0x00]);
+pub const DOMAIN_BLS_TO_EXECUTION_CHANGE: B32 = B32::new([0x0a, 0x00, 0x00, 0x00]);
+pub const DOMAIN_APPLICATION_BUILDER: B32 = B32::new([0x00, 0x00, 0x00, 0x01]);
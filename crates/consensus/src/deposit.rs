@@ -0,0 +1,38 @@
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum::U33, FixedVector};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::{BLSPubkey, BLSSignature};
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct DepositData {
+    pub pubkey: BLSPubkey,
+    pub withdrawal_credentials: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub amount: u64,
+    pub signature: BLSSignature,
+}
+
+/// What the signature of a [`DepositData`] signs, the data without its signature.
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct DepositMessage {
+    pub pubkey: BLSPubkey,
+    pub withdrawal_credentials: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub amount: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct Deposit {
+    /// Merkle path to deposit root
+    pub proof: FixedVector<B256, U33>,
+    pub data: DepositData,
+}
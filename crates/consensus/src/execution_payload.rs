@@ -0,0 +1,71 @@
+use alloy_primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{self, U1048576, U1073741824},
+    FixedVector, VariableList,
+};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use crate::{execution_payload_header::ExecutionPayloadHeader, withdrawal::Withdrawal};
+
+pub type Transaction = VariableList<u8, U1073741824>;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct ExecutionPayload {
+    pub parent_hash: B256,
+    pub fee_recipient: Address,
+    pub state_root: B256,
+    pub receipts_root: B256,
+    #[serde(with = "ssz_types::serde_utils::hex_fixed_vec")]
+    pub logs_bloom: FixedVector<u8, typenum::U256>,
+    pub prev_randao: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub block_number: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_used: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub timestamp: u64,
+    #[serde(with = "ssz_types::serde_utils::hex_var_list")]
+    pub extra_data: VariableList<u8, typenum::U32>,
+    #[serde(with = "serde_utils::quoted_u256")]
+    pub base_fee_per_gas: U256,
+    pub block_hash: B256,
+    #[serde(with = "ssz_types::serde_utils::list_of_hex_var_list")]
+    pub transactions: VariableList<Transaction, U1048576>,
+    pub withdrawals: VariableList<Withdrawal, typenum::U16>,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub blob_gas_used: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub excess_blob_gas: u64,
+}
+
+impl ExecutionPayload {
+    /// The header committing to this payload, with the same hash tree root.
+    pub fn to_execution_payload_header(&self) -> ExecutionPayloadHeader {
+        ExecutionPayloadHeader {
+            parent_hash: self.parent_hash,
+            fee_recipient: self.fee_recipient,
+            state_root: self.state_root,
+            receipts_root: self.receipts_root,
+            logs_bloom: self.logs_bloom.clone(),
+            prev_randao: self.prev_randao,
+            block_number: self.block_number,
+            gas_limit: self.gas_limit,
+            gas_used: self.gas_used,
+            timestamp: self.timestamp,
+            extra_data: self.extra_data.clone(),
+            base_fee_per_gas: self.base_fee_per_gas,
+            block_hash: self.block_hash,
+            transactions_root: self.transactions.tree_hash_root(),
+            withdrawals_root: self.withdrawals.tree_hash_root(),
+            blob_gas_used: self.blob_gas_used,
+            excess_blob_gas: self.excess_blob_gas,
+        }
+    }
+}
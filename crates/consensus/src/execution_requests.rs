@@ -0,0 +1,147 @@
+use alloy_primitives::{Address, Bytes, B256};
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U16, U2, U8192},
+    VariableList,
+};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::{BLSPubkey, BLSSignature};
+
+pub const DEPOSIT_REQUEST_TYPE: u8 = 0x00;
+pub const WITHDRAWAL_REQUEST_TYPE: u8 = 0x01;
+pub const CONSOLIDATION_REQUEST_TYPE: u8 = 0x02;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct DepositRequest {
+    pub pubkey: BLSPubkey,
+    pub withdrawal_credentials: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub amount: u64,
+    pub signature: BLSSignature,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct WithdrawalRequest {
+    pub source_address: Address,
+    pub validator_pubkey: BLSPubkey,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub amount: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct ConsolidationRequest {
+    pub source_address: Address,
+    pub source_pubkey: BLSPubkey,
+    pub target_pubkey: BLSPubkey,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct ExecutionRequests {
+    pub deposits: VariableList<DepositRequest, U8192>,
+    pub withdrawals: VariableList<WithdrawalRequest, U16>,
+    pub consolidations: VariableList<ConsolidationRequest, U2>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("execution requests hash {computed} doesn't match the payload's commitment {expected}")]
+pub struct RequestsHashMismatch {
+    pub expected: B256,
+    pub computed: B256,
+}
+
+impl ExecutionRequests {
+    /// Encodes the requests the way they are passed to `engine_newPayloadV4`: one entry per
+    /// non-empty request type, the type byte followed by the SSZ list.
+    pub fn get_execution_requests_list(&self) -> Vec<Bytes> {
+        [
+            (DEPOSIT_REQUEST_TYPE, self.deposits.as_ssz_bytes()),
+            (WITHDRAWAL_REQUEST_TYPE, self.withdrawals.as_ssz_bytes()),
+            (
+                CONSOLIDATION_REQUEST_TYPE,
+                self.consolidations.as_ssz_bytes(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, request_data)| !request_data.is_empty())
+        .map(|(request_type, request_data)| {
+            let mut request = Vec::with_capacity(request_data.len() + 1);
+            request.push(request_type);
+            request.extend(request_data);
+            request.into()
+        })
+        .collect()
+    }
+
+    /// The EIP-7685 commitment the execution layer computes over these requests,
+    /// `sha256(sha256(request_0) ++ sha256(request_1) ++ ...)`.
+    pub fn requests_hash(&self) -> B256 {
+        let mut hashes = vec![];
+        for request in self.get_execution_requests_list() {
+            hashes.extend(ethereum_hashing::hash(&request));
+        }
+        B256::from_slice(&ethereum_hashing::hash(&hashes))
+    }
+
+    /// Rejects a payload whose `executionRequestsHash` isn't the commitment to the requests in
+    /// the block body.
+    pub fn verify_requests_hash(&self, expected: B256) -> Result<(), RequestsHashMismatch> {
+        let computed = self.requests_hash();
+        if computed != expected {
+            return Err(RequestsHashMismatch { expected, computed });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn test_requests_hash() {
+        let mut requests = ExecutionRequests::default();
+        // sha256 of nothing, the commitment for a block without requests.
+        let empty_hash = b256!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(requests.requests_hash(), empty_hash);
+        assert!(requests.get_execution_requests_list().is_empty());
+
+        requests
+            .withdrawals
+            .push(WithdrawalRequest {
+                source_address: Address::repeat_byte(1),
+                validator_pubkey: BLSPubkey::repeat_byte(2),
+                amount: 7,
+            })
+            .unwrap();
+        let list = requests.get_execution_requests_list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0][0], WITHDRAWAL_REQUEST_TYPE);
+        assert_eq!(list[0].len(), 1 + 20 + 48 + 8);
+
+        let request_hash = ethereum_hashing::hash(&list[0]);
+        let expected = B256::from_slice(&ethereum_hashing::hash(&request_hash));
+        assert_eq!(requests.verify_requests_hash(expected), Ok(()));
+        assert_eq!(
+            requests.verify_requests_hash(empty_hash),
+            Err(RequestsHashMismatch {
+                expected: empty_hash,
+                computed: expected,
+            })
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use alloy_primitives::{aliases::B32, B256};
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct Fork {
+    pub previous_version: B32,
+    pub current_version: B32,
+    /// Epoch of latest fork
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct ForkData {
+    pub current_version: B32,
+    pub genesis_validators_root: B256,
+}
+
+/// The `eth2` ENR field.
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct ENRForkID {
+    pub fork_digest: B32,
+    pub next_fork_version: B32,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub next_fork_epoch: u64,
+}
@@ -0,0 +1,393 @@
+use std::fmt;
+
+use alloy_primitives::{aliases::B32, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{FAR_FUTURE_EPOCH, GENESIS_EPOCH},
+    fork::{ENRForkID, Fork},
+    misc::{compute_domain, compute_fork_digest},
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForkName {
+    Phase0,
+    Altair,
+    Bellatrix,
+    Capella,
+    Deneb,
+    Electra,
+}
+
+impl fmt::Display for ForkName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ForkName::Phase0 => "phase0",
+            ForkName::Altair => "altair",
+            ForkName::Bellatrix => "bellatrix",
+            ForkName::Capella => "capella",
+            ForkName::Deneb => "deneb",
+            ForkName::Electra => "electra",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ScheduledFork {
+    pub name: ForkName,
+    pub version: B32,
+    pub epoch: u64,
+}
+
+/// Fork digest a network uses from `epoch` on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ForkDigestEntry {
+    pub name: ForkName,
+    pub epoch: u64,
+    pub fork_digest: B32,
+}
+
+/// Forks of a network ordered by activation epoch.
+///
+/// Single source of truth for fork versions, used to compute signature domains, the `eth2` ENR
+/// field and gossip topic fork digests.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ForkSchedule {
+    forks: Vec<ScheduledFork>,
+}
+
+impl ForkSchedule {
+    /// Builds a schedule from `forks`, dropping forks scheduled at `FAR_FUTURE_EPOCH`.
+    ///
+    /// Panics if the schedule doesn't start at genesis or epochs are not ascending, as that is a
+    /// bug in the network configuration.
+    pub fn new(forks: impl IntoIterator<Item = ScheduledFork>) -> Self {
+        let forks: Vec<ScheduledFork> = forks
+            .into_iter()
+            .filter(|fork| fork.epoch != FAR_FUTURE_EPOCH)
+            .collect();
+        assert!(
+            forks
+                .first()
+                .is_some_and(|fork| fork.epoch == GENESIS_EPOCH),
+            "fork schedule must start at genesis"
+        );
+        assert!(
+            forks.windows(2).all(|pair| pair[0].epoch <= pair[1].epoch),
+            "fork schedule epochs must be ascending"
+        );
+        Self { forks }
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new([
+            ScheduledFork {
+                name: ForkName::Phase0,
+                version: B32::new([0x00, 0x00, 0x00, 0x00]),
+                epoch: 0,
+            },
+            ScheduledFork {
+                name: ForkName::Altair,
+                version: B32::new([0x01, 0x00, 0x00, 0x00]),
+                epoch: 74240,
+            },
+            ScheduledFork {
+                name: ForkName::Bellatrix,
+                version: B32::new([0x02, 0x00, 0x00, 0x00]),
+                epoch: 144896,
+            },
+            ScheduledFork {
+                name: ForkName::Capella,
+                version: B32::new([0x03, 0x00, 0x00, 0x00]),
+                epoch: 194048,
+            },
+            ScheduledFork {
+                name: ForkName::Deneb,
+                version: B32::new([0x04, 0x00, 0x00, 0x00]),
+                epoch: 269568,
+            },
+            ScheduledFork {
+                name: ForkName::Electra,
+                version: B32::new([0x05, 0x00, 0x00, 0x00]),
+                epoch: 364032,
+            },
+        ])
+    }
+
+    pub fn holesky() -> Self {
+        Self::from_versions([
+            (ForkName::Phase0, [0x01, 0x01, 0x70, 0x00], 0),
+            (ForkName::Altair, [0x02, 0x01, 0x70, 0x00], 0),
+            (ForkName::Bellatrix, [0x03, 0x01, 0x70, 0x00], 0),
+            (ForkName::Capella, [0x04, 0x01, 0x70, 0x00], 256),
+            (ForkName::Deneb, [0x05, 0x01, 0x70, 0x00], 29696),
+            (ForkName::Electra, [0x06, 0x01, 0x70, 0x00], 115968),
+        ])
+    }
+
+    pub fn sepolia() -> Self {
+        Self::from_versions([
+            (ForkName::Phase0, [0x90, 0x00, 0x00, 0x69], 0),
+            (ForkName::Altair, [0x90, 0x00, 0x00, 0x70], 50),
+            (ForkName::Bellatrix, [0x90, 0x00, 0x00, 0x71], 100),
+            (ForkName::Capella, [0x90, 0x00, 0x00, 0x72], 56832),
+            (ForkName::Deneb, [0x90, 0x00, 0x00, 0x73], 132608),
+            (ForkName::Electra, [0x90, 0x00, 0x00, 0x74], 222464),
+        ])
+    }
+
+    fn from_versions<const N: usize>(forks: [(ForkName, [u8; 4], u64); N]) -> Self {
+        Self::new(
+            forks
+                .into_iter()
+                .map(|(name, version, epoch)| ScheduledFork {
+                    name,
+                    version: B32::new(version),
+                    epoch,
+                }),
+        )
+    }
+
+    pub fn forks(&self) -> &[ScheduledFork] {
+        &self.forks
+    }
+
+    pub fn genesis_fork(&self) -> &ScheduledFork {
+        &self.forks[0]
+    }
+
+    fn position_at_epoch(&self, epoch: u64) -> usize {
+        self.forks
+            .iter()
+            .rposition(|fork| fork.epoch <= epoch)
+            .unwrap_or(0)
+    }
+
+    /// The fork active at `epoch`.
+    pub fn fork_at_epoch(&self, epoch: u64) -> &ScheduledFork {
+        &self.forks[self.position_at_epoch(epoch)]
+    }
+
+    /// The first fork activating after `epoch`, if any is scheduled.
+    pub fn next_fork(&self, epoch: u64) -> Option<&ScheduledFork> {
+        self.forks.get(self.position_at_epoch(epoch) + 1)
+    }
+
+    /// The fork called `name`, if it is scheduled.
+    pub fn fork_by_name(&self, name: ForkName) -> Option<&ScheduledFork> {
+        self.forks.iter().find(|fork| fork.name == name)
+    }
+
+    /// The `Fork` container as it appears in a `BeaconState` at `epoch`.
+    pub fn fork(&self, epoch: u64) -> Fork {
+        let position = self.position_at_epoch(epoch);
+        let current = &self.forks[position];
+        let previous = &self.forks[position.saturating_sub(1)];
+        Fork {
+            previous_version: previous.version,
+            current_version: current.version,
+            epoch: current.epoch,
+        }
+    }
+
+    pub fn fork_digest_at_epoch(&self, epoch: u64, genesis_validators_root: B256) -> B32 {
+        compute_fork_digest(self.fork_at_epoch(epoch).version, genesis_validators_root)
+    }
+
+    /// Return the signature domain (fork version concatenated with domain type) of a message
+    /// signed at `epoch`.
+    pub fn get_domain(&self, domain_type: B32, epoch: u64, genesis_validators_root: B256) -> B256 {
+        compute_domain(
+            domain_type,
+            self.fork_at_epoch(epoch).version,
+            genesis_validators_root,
+        )
+    }
+
+    /// Every fork digest of the network in activation order, for subscribing to the gossip
+    /// topics of an upcoming fork ahead of time.
+    pub fn fork_digests(&self, genesis_validators_root: B256) -> Vec<ForkDigestEntry> {
+        self.forks
+            .iter()
+            .map(|fork| ForkDigestEntry {
+                name: fork.name,
+                epoch: fork.epoch,
+                fork_digest: compute_fork_digest(fork.version, genesis_validators_root),
+            })
+            .collect()
+    }
+
+    /// The value of the `eth2` ENR field at `epoch`.
+    pub fn enr_fork_id(&self, epoch: u64, genesis_validators_root: B256) -> ENRForkID {
+        let current = self.fork_at_epoch(epoch);
+        let (next_fork_version, next_fork_epoch) = match self.next_fork(epoch) {
+            Some(next) => (next.version, next.epoch),
+            None => (current.version, FAR_FUTURE_EPOCH),
+        };
+        ENRForkID {
+            fork_digest: compute_fork_digest(current.version, genesis_validators_root),
+            next_fork_version,
+            next_fork_epoch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{b256, fixed_bytes};
+
+    use super::*;
+    use crate::constants::DOMAIN_BEACON_PROPOSER;
+
+    const MAINNET_GENESIS_VALIDATORS_ROOT: B256 =
+        b256!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95");
+
+    #[test]
+    fn test_fork_at_epoch() {
+        let schedule = ForkSchedule::mainnet();
+
+        assert_eq!(schedule.fork_at_epoch(0).name, ForkName::Phase0);
+        assert_eq!(schedule.fork_at_epoch(74239).name, ForkName::Phase0);
+        assert_eq!(schedule.fork_at_epoch(74240).name, ForkName::Altair);
+        assert_eq!(schedule.fork_at_epoch(269568).name, ForkName::Deneb);
+        assert_eq!(schedule.fork_at_epoch(u64::MAX).name, ForkName::Electra);
+    }
+
+    #[test]
+    fn test_next_fork() {
+        let schedule = ForkSchedule::mainnet();
+
+        assert_eq!(schedule.next_fork(0).unwrap().name, ForkName::Altair);
+        assert_eq!(schedule.next_fork(194048).unwrap().name, ForkName::Deneb);
+        assert!(schedule.next_fork(364032).is_none());
+    }
+
+    #[test]
+    fn test_fork_container() {
+        let schedule = ForkSchedule::mainnet();
+
+        let fork = schedule.fork(200000);
+        assert_eq!(fork.previous_version, B32::new([0x02, 0x00, 0x00, 0x00]));
+        assert_eq!(fork.current_version, B32::new([0x03, 0x00, 0x00, 0x00]));
+        assert_eq!(fork.epoch, 194048);
+
+        let genesis_fork = schedule.fork(0);
+        assert_eq!(genesis_fork.previous_version, genesis_fork.current_version);
+    }
+
+    #[test]
+    fn test_enr_fork_id_at_last_fork() {
+        let schedule = ForkSchedule::mainnet();
+
+        let enr_fork_id = schedule.enr_fork_id(400000, B256::ZERO);
+        assert_eq!(enr_fork_id.next_fork_epoch, FAR_FUTURE_EPOCH);
+        assert_eq!(
+            enr_fork_id.next_fork_version,
+            schedule.fork_at_epoch(400000).version
+        );
+    }
+
+    #[test]
+    fn test_testnet_schedules() {
+        let holesky = ForkSchedule::holesky();
+        assert_eq!(
+            holesky.genesis_fork().version,
+            B32::new([0x01, 0x01, 0x70, 0x00])
+        );
+        // Holesky started in Bellatrix.
+        assert_eq!(holesky.fork_at_epoch(0).name, ForkName::Bellatrix);
+
+        let sepolia = ForkSchedule::sepolia();
+        assert_eq!(sepolia.fork_at_epoch(132608).name, ForkName::Deneb);
+        assert_eq!(
+            sepolia.fork_by_name(ForkName::Electra).unwrap().version,
+            B32::new([0x90, 0x00, 0x00, 0x74])
+        );
+    }
+
+    #[test]
+    fn test_unscheduled_forks_are_dropped() {
+        let schedule = ForkSchedule::new([
+            ScheduledFork {
+                name: ForkName::Phase0,
+                version: B32::new([0x00, 0x00, 0x00, 0x01]),
+                epoch: 0,
+            },
+            ScheduledFork {
+                name: ForkName::Altair,
+                version: B32::new([0x01, 0x00, 0x00, 0x01]),
+                epoch: FAR_FUTURE_EPOCH,
+            },
+        ]);
+
+        assert_eq!(schedule.forks().len(), 1);
+        assert!(schedule.next_fork(0).is_none());
+    }
+
+    #[test]
+    fn test_mainnet_fork_digests() {
+        let digests = ForkSchedule::mainnet().fork_digests(MAINNET_GENESIS_VALIDATORS_ROOT);
+
+        let expected = [
+            (ForkName::Phase0, 0, fixed_bytes!("b5303f2a")),
+            (ForkName::Altair, 74240, fixed_bytes!("afcaaba0")),
+            (ForkName::Bellatrix, 144896, fixed_bytes!("4a26c58b")),
+            (ForkName::Capella, 194048, fixed_bytes!("bba4da96")),
+            (ForkName::Deneb, 269568, fixed_bytes!("6a95a1a9")),
+        ];
+        for (entry, (name, epoch, fork_digest)) in digests.iter().zip(expected) {
+            assert_eq!(
+                *entry,
+                ForkDigestEntry {
+                    name,
+                    epoch,
+                    fork_digest,
+                }
+            );
+        }
+        assert_eq!(digests.len(), ForkSchedule::mainnet().forks().len());
+    }
+
+    #[test]
+    fn test_fork_digests_are_unique() {
+        let schedule = ForkSchedule::mainnet();
+
+        for genesis_validators_root in [B256::ZERO, MAINNET_GENESIS_VALIDATORS_ROOT] {
+            let digests = schedule.fork_digests(genesis_validators_root);
+            for (index, entry) in digests.iter().enumerate() {
+                assert!(digests[index + 1..]
+                    .iter()
+                    .all(|other| other.fork_digest != entry.fork_digest));
+                assert_eq!(
+                    schedule.fork_digest_at_epoch(entry.epoch, genesis_validators_root),
+                    entry.fork_digest
+                );
+            }
+        }
+        assert_ne!(
+            schedule.fork_digests(B256::ZERO),
+            schedule.fork_digests(MAINNET_GENESIS_VALIDATORS_ROOT)
+        );
+    }
+
+    #[test]
+    fn test_get_domain_uses_fork_at_epoch() {
+        let schedule = ForkSchedule::mainnet();
+
+        for fork in schedule.forks() {
+            let domain = schedule.get_domain(
+                DOMAIN_BEACON_PROPOSER,
+                fork.epoch,
+                MAINNET_GENESIS_VALIDATORS_ROOT,
+            );
+            let fork_digest =
+                schedule.fork_digest_at_epoch(fork.epoch, MAINNET_GENESIS_VALIDATORS_ROOT);
+            // Domain type followed by the first 28 bytes of the fork data root, which start
+            // with the fork digest.
+            assert_eq!(domain[..4], DOMAIN_BEACON_PROPOSER[..]);
+            assert_eq!(domain[4..8], fork_digest[..]);
+        }
+    }
+}
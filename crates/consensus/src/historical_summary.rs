@@ -0,0 +1,12 @@
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct HistoricalSummary {
+    pub block_summary_root: B256,
+    pub state_summary_root: B256,
+}
@@ -0,0 +1,32 @@
+pub mod aggregate_and_proof;
+pub mod attestation;
+pub mod attestation_data;
+pub mod attester_slashing;
+pub mod beacon_block;
+pub mod beacon_block_body;
+pub mod beacon_block_header;
+pub mod beacon_state;
+pub mod blinded_beacon_block;
+pub mod blob_sidecar;
+pub mod bls;
+pub mod bls_to_execution_change;
+pub mod checkpoint;
+pub mod committee;
+pub mod constants;
+pub mod deposit;
+pub mod eth1_data;
+pub mod execution_payload;
+pub mod execution_payload_header;
+pub mod execution_requests;
+pub mod fork;
+pub mod fork_schedule;
+pub mod historical_summary;
+pub mod light_client;
+pub mod misc;
+pub mod proposer_slashing;
+pub mod subnet;
+pub mod sync_aggregate;
+pub mod sync_committee;
+pub mod validator;
+pub mod voluntary_exit;
+pub mod withdrawal;
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use super::{
+    has_supermajority, LightClientError, LightClientFinalityUpdate, LightClientOptimisticUpdate,
+    LightClientUpdate,
+};
+use crate::{
+    beacon_block::SignedBeaconBlock, beacon_state::BeaconState,
+    misc::compute_sync_committee_period_at_slot,
+};
+
+/// Light client data served to peers and the API, derived from imported blocks.
+///
+/// Keeps the best [`LightClientUpdate`] per sync committee period (as ranked by
+/// [`LightClientUpdate::is_better_update`]) together with the latest finality and optimistic
+/// updates.
+#[derive(Debug, Default)]
+pub struct LightClientUpdateCache {
+    best_updates: BTreeMap<u64, LightClientUpdate>,
+    latest_finality_update: Option<LightClientFinalityUpdate>,
+    latest_optimistic_update: Option<LightClientOptimisticUpdate>,
+}
+
+impl LightClientUpdateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes an update produced for a newly imported block. Returns `true` if it became the
+    /// best update of its period.
+    pub fn on_update(&mut self, update: LightClientUpdate) -> bool {
+        self.maybe_update_latest(&update);
+
+        let period = compute_sync_committee_period_at_slot(update.attested_header.beacon.slot);
+        match self.best_updates.get(&period) {
+            Some(best) if !update.is_better_update(best) => false,
+            _ => {
+                self.best_updates.insert(period, update);
+                true
+            }
+        }
+    }
+
+    /// Produces the update of a newly imported `block` and processes it, see
+    /// [`LightClientUpdate::new`] for the arguments. Returns whether it became the best update
+    /// of its period.
+    pub fn on_block(
+        &mut self,
+        state: &BeaconState,
+        block: &SignedBeaconBlock,
+        attested_state: &BeaconState,
+        attested_block: &SignedBeaconBlock,
+        finalized_block: Option<&SignedBeaconBlock>,
+    ) -> Result<bool, LightClientError> {
+        let update = LightClientUpdate::new(
+            state,
+            block,
+            attested_state,
+            attested_block,
+            finalized_block,
+        )?;
+        Ok(self.on_update(update))
+    }
+
+    fn maybe_update_latest(&mut self, update: &LightClientUpdate) {
+        let attested_slot = update.attested_header.beacon.slot;
+        let is_newer_optimistic = self
+            .latest_optimistic_update
+            .as_ref()
+            .map_or(true, |latest| {
+                attested_slot > latest.attested_header.beacon.slot
+            });
+        if is_newer_optimistic {
+            self.latest_optimistic_update = Some(update.into());
+        }
+
+        if !update.is_finality_update() {
+            return;
+        }
+        let finalized_slot = update.finalized_header.beacon.slot;
+        let is_supermajority =
+            has_supermajority(update.sync_aggregate.num_active_participants() as u64);
+        let is_newer_finality = match &self.latest_finality_update {
+            None => true,
+            Some(latest) => {
+                let latest_finalized_slot = latest.finalized_header.beacon.slot;
+                finalized_slot > latest_finalized_slot
+                    || (finalized_slot == latest_finalized_slot
+                        && is_supermajority
+                        && !has_supermajority(
+                            latest.sync_aggregate.num_active_participants() as u64
+                        ))
+            }
+        };
+        if is_newer_finality {
+            self.latest_finality_update = Some(update.into());
+        }
+    }
+
+    pub fn best_update(&self, period: u64) -> Option<&LightClientUpdate> {
+        self.best_updates.get(&period)
+    }
+
+    /// Best updates for `count` consecutive periods starting at `start_period`, as served by
+    /// `LightClientUpdatesByRange`. Stops at the first period without an update.
+    pub fn updates_by_range(&self, start_period: u64, count: u64) -> Vec<LightClientUpdate> {
+        (start_period..start_period.saturating_add(count))
+            .map_while(|period| self.best_updates.get(&period).cloned())
+            .collect()
+    }
+
+    pub fn latest_finality_update(&self) -> Option<&LightClientFinalityUpdate> {
+        self.latest_finality_update.as_ref()
+    }
+
+    pub fn latest_optimistic_update(&self) -> Option<&LightClientOptimisticUpdate> {
+        self.latest_optimistic_update.as_ref()
+    }
+
+    /// Drops best updates of periods before `min_period`.
+    pub fn prune(&mut self, min_period: u64) {
+        self.best_updates = self.best_updates.split_off(&min_period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use tree_hash::TreeHash;
+
+    use super::*;
+    use crate::{
+        constants::{EPOCHS_PER_SYNC_COMMITTEE_PERIOD, SLOTS_PER_EPOCH},
+        light_client::tests::{block_with_state, update_with},
+    };
+
+    const PERIOD_SLOTS: u64 = EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+
+    #[test]
+    fn test_keeps_best_update_per_period() {
+        let mut cache = LightClientUpdateCache::new();
+
+        assert!(cache.on_update(update_with(300, 100, false)));
+        assert!(cache.on_update(update_with(400, 200, true)));
+        assert!(!cache.on_update(update_with(350, 300, true)));
+        assert!(cache.on_update(update_with(300, PERIOD_SLOTS + 10, false)));
+
+        assert_eq!(
+            cache.best_update(0).unwrap().attested_header.beacon.slot,
+            200
+        );
+        assert_eq!(
+            cache.best_update(1).unwrap().attested_header.beacon.slot,
+            PERIOD_SLOTS + 10
+        );
+        assert_eq!(cache.updates_by_range(0, 5).len(), 2);
+
+        cache.prune(1);
+        assert!(cache.best_update(0).is_none());
+        assert_eq!(cache.updates_by_range(0, 5).len(), 0);
+    }
+
+    #[test]
+    fn test_tracks_latest_updates() {
+        let mut cache = LightClientUpdateCache::new();
+
+        cache.on_update(update_with(400, 200, true));
+        cache.on_update(update_with(400, 150, true));
+        cache.on_update(update_with(400, 300, false));
+
+        assert_eq!(
+            cache
+                .latest_optimistic_update()
+                .unwrap()
+                .attested_header
+                .beacon
+                .slot,
+            300
+        );
+        assert_eq!(
+            cache
+                .latest_finality_update()
+                .unwrap()
+                .finalized_header
+                .beacon
+                .slot,
+            136
+        );
+    }
+
+    #[test]
+    fn test_on_block() {
+        let mut cache = LightClientUpdateCache::new();
+        let (attested_block, attested_state) = block_with_state(100, B256::ZERO, 0, |_| {});
+        let attested_root = attested_block.message.tree_hash_root();
+        let (block, state) = block_with_state(101, attested_root, 400, |_| {});
+
+        assert_eq!(
+            cache.on_block(&state, &block, &attested_state, &attested_block, None),
+            Ok(true)
+        );
+        assert_eq!(cache.best_update(0).unwrap().signature_slot, 101);
+        assert_eq!(
+            cache
+                .latest_optimistic_update()
+                .unwrap()
+                .attested_header
+                .beacon
+                .slot,
+            100
+        );
+        assert!(cache
+            .on_block(
+                &attested_state,
+                &block,
+                &attested_state,
+                &attested_block,
+                None
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,527 @@
+pub mod cache;
+
+use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{
+    typenum::{U4, U5, U6},
+    FixedVector,
+};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    beacon_block::{BeaconBlock, SignedBeaconBlock},
+    beacon_block_header::BeaconBlockHeader,
+    beacon_state::BeaconState,
+    constants::{GENESIS_SLOT, MIN_SYNC_COMMITTEE_PARTICIPANTS, SYNC_COMMITTEE_SIZE},
+    execution_payload_header::ExecutionPayloadHeader,
+    misc::compute_sync_committee_period_at_slot,
+    sync_aggregate::SyncAggregate,
+    sync_committee::SyncCommittee,
+};
+
+/// Why light client data couldn't be derived from a block and state.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LightClientError {
+    #[error("block at slot {slot} has {participants} sync committee participants")]
+    NotEnoughParticipants { slot: u64, participants: u64 },
+    #[error("state isn't the post-state of block {0}")]
+    StateMismatch(B256),
+    #[error("attested block {attested_root} isn't the parent {parent_root} of the signing block")]
+    NotParent {
+        attested_root: B256,
+        parent_root: B256,
+    },
+    #[error(
+        "finalized block {root} isn't the finalized checkpoint {expected} of the attested state"
+    )]
+    WrongFinalizedBlock { root: B256, expected: B256 },
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct LightClientHeader {
+    pub beacon: BeaconBlockHeader,
+    pub execution: ExecutionPayloadHeader,
+    pub execution_branch: FixedVector<B256, U4>,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct LightClientBootstrap {
+    pub header: LightClientHeader,
+    pub current_sync_committee: SyncCommittee,
+    pub current_sync_committee_branch: FixedVector<B256, U5>,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct LightClientUpdate {
+    /// Header attested to by the sync committee
+    pub attested_header: LightClientHeader,
+    /// Next sync committee corresponding to `attested_header.beacon.state_root`
+    pub next_sync_committee: SyncCommittee,
+    pub next_sync_committee_branch: FixedVector<B256, U5>,
+    /// Finalized header corresponding to `attested_header.beacon.state_root`
+    pub finalized_header: LightClientHeader,
+    pub finality_branch: FixedVector<B256, U6>,
+    /// Sync committee aggregate signature
+    pub sync_aggregate: SyncAggregate,
+    /// Slot at which the aggregate signature was created (untrusted)
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub signature_slot: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct LightClientFinalityUpdate {
+    pub attested_header: LightClientHeader,
+    pub finalized_header: LightClientHeader,
+    pub finality_branch: FixedVector<B256, U6>,
+    pub sync_aggregate: SyncAggregate,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub signature_slot: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct LightClientOptimisticUpdate {
+    pub attested_header: LightClientHeader,
+    pub sync_aggregate: SyncAggregate,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub signature_slot: u64,
+}
+
+impl LightClientHeader {
+    pub fn from_block(block: &BeaconBlock) -> Self {
+        Self {
+            beacon: block.block_header(),
+            execution: block.body.execution_payload.to_execution_payload_header(),
+            execution_branch: block.body.execution_payload_branch(),
+        }
+    }
+}
+
+impl LightClientBootstrap {
+    /// Bootstrap from `block` and its post-state `state`, usually of a finalized block.
+    pub fn new(state: &BeaconState, block: &SignedBeaconBlock) -> Result<Self, LightClientError> {
+        check_post_state(state, &block.message)?;
+        Ok(Self {
+            header: LightClientHeader::from_block(&block.message),
+            current_sync_committee: state.current_sync_committee.clone(),
+            current_sync_committee_branch: state.current_sync_committee_branch(),
+        })
+    }
+}
+
+impl LightClientUpdate {
+    /// Update for a light client from `block`, whose sync aggregate signs its parent
+    /// `attested_block`. `state` and `attested_state` are the post-states of the blocks and
+    /// `finalized_block` the block of the finalized checkpoint of `attested_state`, if known.
+    pub fn new(
+        state: &BeaconState,
+        block: &SignedBeaconBlock,
+        attested_state: &BeaconState,
+        attested_block: &SignedBeaconBlock,
+        finalized_block: Option<&SignedBeaconBlock>,
+    ) -> Result<Self, LightClientError> {
+        let block = &block.message;
+        let participants = block.body.sync_aggregate.num_active_participants() as u64;
+        if participants < MIN_SYNC_COMMITTEE_PARTICIPANTS {
+            return Err(LightClientError::NotEnoughParticipants {
+                slot: block.slot,
+                participants,
+            });
+        }
+        check_post_state(state, block)?;
+        let attested_root = check_post_state(attested_state, &attested_block.message)?;
+        if attested_root != block.parent_root {
+            return Err(LightClientError::NotParent {
+                attested_root,
+                parent_root: block.parent_root,
+            });
+        }
+
+        let mut update = Self {
+            attested_header: LightClientHeader::from_block(&attested_block.message),
+            sync_aggregate: block.body.sync_aggregate.clone(),
+            signature_slot: block.slot,
+            ..Self::default()
+        };
+        // The next sync committee is only known to the signing period's committee when the
+        // attested block is in the same period.
+        if compute_sync_committee_period_at_slot(attested_block.message.slot)
+            == compute_sync_committee_period_at_slot(block.slot)
+        {
+            update.next_sync_committee = attested_state.next_sync_committee.clone();
+            update.next_sync_committee_branch = attested_state.next_sync_committee_branch();
+        }
+        if let Some(finalized_block) = finalized_block {
+            let expected = attested_state.finalized_checkpoint.root;
+            if finalized_block.message.slot != GENESIS_SLOT {
+                update.finalized_header = LightClientHeader::from_block(&finalized_block.message);
+                let root = update.finalized_header.beacon.tree_hash_root();
+                if root != expected {
+                    return Err(LightClientError::WrongFinalizedBlock { root, expected });
+                }
+            } else if expected != B256::ZERO {
+                return Err(LightClientError::WrongFinalizedBlock {
+                    root: finalized_block.message.tree_hash_root(),
+                    expected,
+                });
+            }
+            update.finality_branch = attested_state.finalized_root_branch();
+        }
+        Ok(update)
+    }
+
+    pub fn is_sync_committee_update(&self) -> bool {
+        self.next_sync_committee_branch
+            .iter()
+            .any(|node| !node.is_zero())
+    }
+
+    pub fn is_finality_update(&self) -> bool {
+        self.finality_branch.iter().any(|node| !node.is_zero())
+    }
+
+    fn has_relevant_sync_committee(&self) -> bool {
+        self.is_sync_committee_update()
+            && compute_sync_committee_period_at_slot(self.attested_header.beacon.slot)
+                == compute_sync_committee_period_at_slot(self.signature_slot)
+    }
+
+    fn has_sync_committee_finality(&self) -> bool {
+        compute_sync_committee_period_at_slot(self.finalized_header.beacon.slot)
+            == compute_sync_committee_period_at_slot(self.attested_header.beacon.slot)
+    }
+
+    /// Whether `self` should replace `old` as the best update of a sync committee period.
+    pub fn is_better_update(&self, old: &LightClientUpdate) -> bool {
+        // Compare supermajority (> 2/3) sync committee participation
+        let new_num_active_participants = self.sync_aggregate.num_active_participants() as u64;
+        let old_num_active_participants = old.sync_aggregate.num_active_participants() as u64;
+        let new_has_supermajority = has_supermajority(new_num_active_participants);
+        let old_has_supermajority = has_supermajority(old_num_active_participants);
+        if new_has_supermajority != old_has_supermajority {
+            return new_has_supermajority;
+        }
+        if !new_has_supermajority && new_num_active_participants != old_num_active_participants {
+            return new_num_active_participants > old_num_active_participants;
+        }
+
+        // Compare presence of relevant sync committee
+        let new_has_relevant_sync_committee = self.has_relevant_sync_committee();
+        let old_has_relevant_sync_committee = old.has_relevant_sync_committee();
+        if new_has_relevant_sync_committee != old_has_relevant_sync_committee {
+            return new_has_relevant_sync_committee;
+        }
+
+        // Compare indication of any finality
+        let new_has_finality = self.is_finality_update();
+        let old_has_finality = old.is_finality_update();
+        if new_has_finality != old_has_finality {
+            return new_has_finality;
+        }
+
+        // Compare sync committee finality
+        if new_has_finality {
+            let new_has_sync_committee_finality = self.has_sync_committee_finality();
+            let old_has_sync_committee_finality = old.has_sync_committee_finality();
+            if new_has_sync_committee_finality != old_has_sync_committee_finality {
+                return new_has_sync_committee_finality;
+            }
+        }
+
+        // Tiebreaker 1: Sync committee participation beyond supermajority
+        if new_num_active_participants != old_num_active_participants {
+            return new_num_active_participants > old_num_active_participants;
+        }
+
+        // Tiebreaker 2: Prefer older data (fewer changes to best)
+        if self.attested_header.beacon.slot != old.attested_header.beacon.slot {
+            return self.attested_header.beacon.slot < old.attested_header.beacon.slot;
+        }
+        self.signature_slot < old.signature_slot
+    }
+}
+
+impl From<&LightClientUpdate> for LightClientFinalityUpdate {
+    fn from(update: &LightClientUpdate) -> Self {
+        Self {
+            attested_header: update.attested_header.clone(),
+            finalized_header: update.finalized_header.clone(),
+            finality_branch: update.finality_branch.clone(),
+            sync_aggregate: update.sync_aggregate.clone(),
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+impl From<&LightClientUpdate> for LightClientOptimisticUpdate {
+    fn from(update: &LightClientUpdate) -> Self {
+        Self {
+            attested_header: update.attested_header.clone(),
+            sync_aggregate: update.sync_aggregate.clone(),
+            signature_slot: update.signature_slot,
+        }
+    }
+}
+
+pub fn has_supermajority(num_active_participants: u64) -> bool {
+    num_active_participants * 3 >= SYNC_COMMITTEE_SIZE * 2
+}
+
+/// Checks that `state` is the state right after applying `block`, returning the block root.
+fn check_post_state(state: &BeaconState, block: &BeaconBlock) -> Result<B256, LightClientError> {
+    let root = block.tree_hash_root();
+    if state.slot != state.latest_block_header.slot
+        || state.latest_block_root(state.tree_hash_root()) != root
+    {
+        return Err(LightClientError::StateMismatch(root));
+    }
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checkpoint::Checkpoint, misc::is_valid_merkle_branch};
+
+    /// A block at `slot` on `parent_root` signed by `participants` and its post-state, which
+    /// `update_state` changes first.
+    pub(crate) fn block_with_state(
+        slot: u64,
+        parent_root: B256,
+        participants: usize,
+        update_state: impl FnOnce(&mut BeaconState),
+    ) -> (SignedBeaconBlock, BeaconState) {
+        let mut block = BeaconBlock {
+            slot,
+            parent_root,
+            ..BeaconBlock::default()
+        };
+        for index in 0..participants {
+            block
+                .body
+                .sync_aggregate
+                .sync_committee_bits
+                .set(index, true)
+                .unwrap();
+        }
+        block.body.execution_payload.block_number = slot;
+        let mut state = BeaconState {
+            slot,
+            latest_block_header: block.block_header(),
+            ..BeaconState::default()
+        };
+        update_state(&mut state);
+        block.state_root = state.tree_hash_root();
+        (
+            SignedBeaconBlock {
+                message: block,
+                ..SignedBeaconBlock::default()
+            },
+            state,
+        )
+    }
+
+    pub(crate) fn update_with(
+        participants: usize,
+        attested_slot: u64,
+        finalized: bool,
+    ) -> LightClientUpdate {
+        let mut update = LightClientUpdate {
+            signature_slot: attested_slot + 1,
+            ..Default::default()
+        };
+        update.attested_header.beacon.slot = attested_slot;
+        for index in 0..participants {
+            update
+                .sync_aggregate
+                .sync_committee_bits
+                .set(index, true)
+                .unwrap();
+        }
+        if finalized {
+            update.finalized_header.beacon.slot = attested_slot.saturating_sub(64);
+            update.finality_branch[0] = B256::repeat_byte(1);
+        }
+        update
+    }
+
+    #[test]
+    fn test_supermajority_beats_participation() {
+        let supermajority = update_with(342, 100, false);
+        let minority = update_with(341, 100, true);
+
+        assert!(supermajority.is_better_update(&minority));
+        assert!(!minority.is_better_update(&supermajority));
+    }
+
+    #[test]
+    fn test_finality_beats_no_finality() {
+        let finalized = update_with(400, 100, true);
+        let not_finalized = update_with(512, 100, false);
+
+        assert!(finalized.is_better_update(&not_finalized));
+    }
+
+    #[test]
+    fn test_tiebreak_prefers_older_data() {
+        let older = update_with(400, 100, true);
+        let newer = update_with(400, 120, true);
+
+        assert!(older.is_better_update(&newer));
+        assert!(!newer.is_better_update(&older));
+        assert!(!older.is_better_update(&older));
+    }
+
+    #[test]
+    fn test_derived_updates() {
+        let update = update_with(400, 100, true);
+
+        let finality_update = LightClientFinalityUpdate::from(&update);
+        assert_eq!(finality_update.finalized_header, update.finalized_header);
+        assert_eq!(finality_update.signature_slot, 101);
+
+        let optimistic_update = LightClientOptimisticUpdate::from(&update);
+        assert_eq!(optimistic_update.attested_header, update.attested_header);
+    }
+
+    #[test]
+    fn test_create_update() {
+        let (finalized_block, _) = block_with_state(64, B256::ZERO, 0, |_| {});
+        let finalized_root = finalized_block.message.tree_hash_root();
+        let (attested_block, attested_state) = block_with_state(100, finalized_root, 0, |state| {
+            state.finalized_checkpoint = Checkpoint {
+                epoch: 2,
+                root: finalized_root,
+            };
+            state.next_sync_committee.aggregate_pubkey[0] = 1;
+        });
+        let attested_root = attested_block.message.tree_hash_root();
+        let (block, state) = block_with_state(101, attested_root, 400, |_| {});
+
+        let update = LightClientUpdate::new(
+            &state,
+            &block,
+            &attested_state,
+            &attested_block,
+            Some(&finalized_block),
+        )
+        .unwrap();
+
+        assert_eq!(update.signature_slot, 101);
+        assert_eq!(update.sync_aggregate.num_active_participants(), 400);
+        assert_eq!(
+            update.attested_header.beacon.tree_hash_root(),
+            attested_root
+        );
+        assert_eq!(
+            update.finalized_header.beacon.tree_hash_root(),
+            finalized_root
+        );
+        assert!(is_valid_merkle_branch(
+            update.attested_header.execution.tree_hash_root(),
+            &update.attested_header.execution_branch,
+            4,
+            9,
+            update.attested_header.beacon.body_root,
+        ));
+        assert!(is_valid_merkle_branch(
+            update.next_sync_committee.tree_hash_root(),
+            &update.next_sync_committee_branch,
+            5,
+            23,
+            attested_block.message.state_root,
+        ));
+        assert!(is_valid_merkle_branch(
+            finalized_root,
+            &update.finality_branch,
+            6,
+            41,
+            attested_block.message.state_root,
+        ));
+        assert!(update.is_sync_committee_update());
+        assert!(update.is_finality_update());
+
+        let bootstrap = LightClientBootstrap::new(&attested_state, &attested_block).unwrap();
+        assert!(is_valid_merkle_branch(
+            bootstrap.current_sync_committee.tree_hash_root(),
+            &bootstrap.current_sync_committee_branch,
+            5,
+            22,
+            attested_block.message.state_root,
+        ));
+    }
+
+    #[test]
+    fn test_refused_updates() {
+        let (attested_block, attested_state) = block_with_state(100, B256::ZERO, 0, |_| {});
+        let attested_root = attested_block.message.tree_hash_root();
+        let (unsigned, unsigned_state) = block_with_state(101, attested_root, 0, |_| {});
+        let (orphan, orphan_state) = block_with_state(101, B256::ZERO, 400, |_| {});
+        let (block, state) = block_with_state(101, attested_root, 400, |_| {});
+
+        assert_eq!(
+            LightClientUpdate::new(
+                &unsigned_state,
+                &unsigned,
+                &attested_state,
+                &attested_block,
+                None
+            ),
+            Err(LightClientError::NotEnoughParticipants {
+                slot: 101,
+                participants: 0
+            })
+        );
+        assert_eq!(
+            LightClientUpdate::new(
+                &orphan_state,
+                &orphan,
+                &attested_state,
+                &attested_block,
+                None
+            ),
+            Err(LightClientError::NotParent {
+                attested_root,
+                parent_root: B256::ZERO
+            })
+        );
+        assert_eq!(
+            LightClientUpdate::new(
+                &attested_state,
+                &block,
+                &attested_state,
+                &attested_block,
+                None
+            ),
+            Err(LightClientError::StateMismatch(
+                block.message.tree_hash_root()
+            ))
+        );
+        assert!(matches!(
+            LightClientUpdate::new(
+                &state,
+                &block,
+                &attested_state,
+                &attested_block,
+                Some(&attested_block)
+            ),
+            Err(LightClientError::WrongFinalizedBlock { .. })
+        ));
+
+        // Without a finalized block the update carries no finality.
+        let update =
+            LightClientUpdate::new(&state, &block, &attested_state, &attested_block, None).unwrap();
+        assert!(!update.is_finality_update());
+    }
+}
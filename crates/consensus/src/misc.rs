@@ -0,0 +1,185 @@
+use alloy_primitives::{aliases::B32, B256};
+use ssz_derive::{Decode, Encode};
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+
+use crate::{
+    constants::{EPOCHS_PER_SYNC_COMMITTEE_PERIOD, SLOTS_PER_EPOCH},
+    fork::ForkData,
+};
+
+/// Return the epoch number at ``slot``.
+pub fn compute_epoch_at_slot(slot: u64) -> u64 {
+    slot / SLOTS_PER_EPOCH
+}
+
+/// Return the start slot of ``epoch``.
+pub fn compute_start_slot_at_epoch(epoch: u64) -> u64 {
+    epoch * SLOTS_PER_EPOCH
+}
+
+/// Return the sync committee period at ``epoch``.
+pub fn compute_sync_committee_period(epoch: u64) -> u64 {
+    epoch / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+/// Return the sync committee period at ``slot``.
+pub fn compute_sync_committee_period_at_slot(slot: u64) -> u64 {
+    compute_sync_committee_period(compute_epoch_at_slot(slot))
+}
+
+/// Return the 32-byte fork data root for the ``current_version`` and
+/// ``genesis_validators_root``. This is used primarily in signature domains to avoid collisions
+/// across forks/chains.
+pub fn compute_fork_data_root(current_version: B32, genesis_validators_root: B256) -> B256 {
+    ForkData {
+        current_version,
+        genesis_validators_root,
+    }
+    .tree_hash_root()
+}
+
+/// Return the 4-byte fork digest for the ``current_version`` and ``genesis_validators_root``.
+/// This is a digest primarily used for domain separation on the p2p layer. 4-bytes suffices for
+/// practical separation of forks/chains.
+pub fn compute_fork_digest(current_version: B32, genesis_validators_root: B256) -> B32 {
+    B32::from_slice(&compute_fork_data_root(current_version, genesis_validators_root)[..4])
+}
+
+/// Return the domain for the ``domain_type`` and ``fork_version``.
+pub fn compute_domain(domain_type: B32, fork_version: B32, genesis_validators_root: B256) -> B256 {
+    let fork_data_root = compute_fork_data_root(fork_version, genesis_validators_root);
+    let mut domain = B256::ZERO;
+    domain[..4].copy_from_slice(domain_type.as_slice());
+    domain[4..].copy_from_slice(&fork_data_root[..28]);
+    domain
+}
+
+/// Return the signing root for the corresponding signing data.
+pub fn compute_signing_root<T: TreeHash>(ssz_object: &T, domain: B256) -> B256 {
+    SigningData {
+        object_root: ssz_object.tree_hash_root(),
+        domain,
+    }
+    .tree_hash_root()
+}
+
+/// Check if ``leaf`` at ``index`` verifies against the Merkle ``root`` and ``branch``.
+pub fn is_valid_merkle_branch(
+    leaf: B256,
+    branch: &[B256],
+    depth: u64,
+    index: u64,
+    root: B256,
+) -> bool {
+    let mut value = leaf;
+    for (i, node) in branch.iter().take(depth as usize).enumerate() {
+        value = if (index >> i) & 1 == 1 {
+            B256::from(ethereum_hashing::hash32_concat(
+                node.as_slice(),
+                value.as_slice(),
+            ))
+        } else {
+            B256::from(ethereum_hashing::hash32_concat(
+                value.as_slice(),
+                node.as_slice(),
+            ))
+        };
+    }
+    value == root
+}
+
+/// Branch proving the leaf at ``index`` of the tree of ``depth`` whose first leaves are
+/// ``leaves`` and the others zero, as checked by [`is_valid_merkle_branch`].
+pub fn compute_merkle_branch(leaves: &[B256], depth: u64, index: u64) -> Vec<B256> {
+    let mut layer = leaves.to_vec();
+    layer.resize(1 << depth, B256::ZERO);
+    let mut index = index as usize;
+    let mut branch = Vec::with_capacity(depth as usize);
+    for _ in 0..depth {
+        branch.push(layer[index ^ 1]);
+        layer = layer
+            .chunks(2)
+            .map(|pair| {
+                B256::from(ethereum_hashing::hash32_concat(
+                    pair[0].as_slice(),
+                    pair[1].as_slice(),
+                ))
+            })
+            .collect();
+        index /= 2;
+    }
+    branch
+}
+
+/// Return the depth of the generalized index ``gindex``.
+pub fn floorlog2(gindex: u64) -> u64 {
+    u64::from(63 - gindex.leading_zeros())
+}
+
+/// Return the index of the generalized index ``gindex`` among the nodes at its depth.
+pub fn get_subtree_index(gindex: u64) -> u64 {
+    gindex % (1 << floorlog2(gindex))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Encode, Decode, TreeHash)]
+pub struct SigningData {
+    pub object_root: B256,
+    pub domain: B256,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{b256, fixed_bytes};
+
+    use super::*;
+    use crate::constants::DOMAIN_DEPOSIT;
+
+    #[test]
+    fn test_sync_committee_period_boundaries() {
+        let period_length = EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+
+        assert_eq!(compute_sync_committee_period_at_slot(0), 0);
+        assert_eq!(compute_sync_committee_period_at_slot(period_length - 1), 0);
+        assert_eq!(compute_sync_committee_period_at_slot(period_length), 1);
+        assert_eq!(compute_start_slot_at_epoch(compute_epoch_at_slot(65)), 64);
+    }
+
+    #[test]
+    fn test_compute_fork_digest() {
+        let genesis_validators_root =
+            b256!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95");
+
+        assert_eq!(
+            compute_fork_digest(fixed_bytes!("00000000"), genesis_validators_root),
+            fixed_bytes!("b5303f2a")
+        );
+        assert_eq!(
+            compute_fork_digest(fixed_bytes!("04000000"), genesis_validators_root),
+            fixed_bytes!("6a95a1a9")
+        );
+    }
+
+    #[test]
+    fn test_compute_domain() {
+        // Deposits are signed over the genesis fork version with an empty validators root on
+        // every network, this is the mainnet deposit domain.
+        assert_eq!(
+            compute_domain(DOMAIN_DEPOSIT, fixed_bytes!("00000000"), B256::ZERO),
+            b256!("03000000f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a9")
+        );
+    }
+
+    #[test]
+    fn test_compute_signing_root() {
+        let object_root = B256::repeat_byte(0xab);
+        let domain = compute_domain(DOMAIN_DEPOSIT, fixed_bytes!("00000000"), B256::ZERO);
+
+        // Two field container, the root is the hash of both leaves concatenated.
+        let expected = B256::from(ethereum_hashing::hash32_concat(
+            object_root.as_slice(),
+            domain.as_slice(),
+        ));
+        assert_eq!(compute_signing_root(&object_root, domain), expected);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{collections::BTreeSet, fmt};
+
+use serde::{Deserialize, Serialize};
+use ssz::{Decode, Encode};
+use ssz_types::{
+    typenum::{U4, U64},
+    BitVector,
+};
+
+use crate::{
+    bls::BLSPubkey,
+    constants::{
+        ATTESTATION_SUBNET_COUNT, SLOTS_PER_EPOCH, SYNC_COMMITTEE_SIZE, SYNC_COMMITTEE_SUBNET_COUNT,
+    },
+    sync_committee::SyncCommittee,
+};
+
+/// The `attnets` ENR and metadata field.
+pub type AttestationSubnets = BitVector<U64>;
+
+/// The `syncnets` ENR and metadata field.
+pub type SyncCommitteeSubnets = BitVector<U4>;
+
+/// Index of an attestation subnet, always below `ATTESTATION_SUBNET_COUNT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub struct SubnetId(u64);
+
+impl SubnetId {
+    pub fn new(id: u64) -> Option<Self> {
+        (id < ATTESTATION_SUBNET_COUNT).then_some(Self(id))
+    }
+
+    /// Subnet an attestation for `committee_index` at `slot` is published on.
+    pub fn compute_for_attestation(
+        committees_per_slot: u64,
+        slot: u64,
+        committee_index: u64,
+    ) -> Self {
+        let slots_since_epoch_start = slot % SLOTS_PER_EPOCH;
+        let committees_since_epoch_start = committees_per_slot * slots_since_epoch_start;
+        Self((committees_since_epoch_start + committee_index) % ATTESTATION_SUBNET_COUNT)
+    }
+}
+
+/// Index of a sync committee subnet, always below `SYNC_COMMITTEE_SUBNET_COUNT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u64", into = "u64")]
+pub struct SyncSubnetId(u64);
+
+impl SyncSubnetId {
+    pub fn new(id: u64) -> Option<Self> {
+        (id < SYNC_COMMITTEE_SUBNET_COUNT).then_some(Self(id))
+    }
+
+    /// Subnet of the member at `index` in the sync committee.
+    pub fn from_sync_committee_index(index: u64) -> Option<Self> {
+        Self::new(index / (SYNC_COMMITTEE_SIZE / SYNC_COMMITTEE_SUBNET_COUNT))
+    }
+
+    /// Subnets `pubkey` has to publish sync committee messages on. A validator can appear
+    /// several times in the same committee, so this can be more than one.
+    pub fn compute_for_sync_committee(
+        sync_committee: &SyncCommittee,
+        pubkey: &BLSPubkey,
+    ) -> BTreeSet<Self> {
+        sync_committee
+            .pubkeys
+            .iter()
+            .enumerate()
+            .filter(|(_, member)| *member == pubkey)
+            .filter_map(|(index, _)| Self::from_sync_committee_index(index as u64))
+            .collect()
+    }
+}
+
+macro_rules! impl_subnet_id {
+    ($name:ident, $count:ident) => {
+        impl TryFrom<u64> for $name {
+            type Error = String;
+
+            fn try_from(id: u64) -> Result<Self, Self::Error> {
+                Self::new(id).ok_or_else(|| {
+                    format!(
+                        "{} {id} is out of range, expected less than {}",
+                        stringify!($name),
+                        $count
+                    )
+                })
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+impl_subnet_id!(SubnetId, ATTESTATION_SUBNET_COUNT);
+impl_subnet_id!(SyncSubnetId, SYNC_COMMITTEE_SUBNET_COUNT);
+
+pub fn attnets_from_subnets(subnets: impl IntoIterator<Item = SubnetId>) -> AttestationSubnets {
+    let mut attnets = AttestationSubnets::new();
+    for subnet in subnets {
+        attnets
+            .set(subnet.0 as usize, true)
+            .expect("SubnetId is always below ATTESTATION_SUBNET_COUNT");
+    }
+    attnets
+}
+
+pub fn subnets_from_attnets(attnets: &AttestationSubnets) -> impl Iterator<Item = SubnetId> + '_ {
+    attnets
+        .iter()
+        .enumerate()
+        .filter(|(_, set)| *set)
+        .map(|(index, _)| SubnetId(index as u64))
+}
+
+pub fn syncnets_from_subnets(
+    subnets: impl IntoIterator<Item = SyncSubnetId>,
+) -> SyncCommitteeSubnets {
+    let mut syncnets = SyncCommitteeSubnets::new();
+    for subnet in subnets {
+        syncnets
+            .set(subnet.0 as usize, true)
+            .expect("SyncSubnetId is always below SYNC_COMMITTEE_SUBNET_COUNT");
+    }
+    syncnets
+}
+
+pub fn subnets_from_syncnets(
+    syncnets: &SyncCommitteeSubnets,
+) -> impl Iterator<Item = SyncSubnetId> + '_ {
+    syncnets
+        .iter()
+        .enumerate()
+        .filter(|(_, set)| *set)
+        .map(|(index, _)| SyncSubnetId(index as u64))
+}
+
+/// Encodes a bitfield as the raw bytes stored in the `attnets`/`syncnets` ENR fields.
+pub fn encode_enr_bitfield<T: Encode>(bitfield: &T) -> Vec<u8> {
+    bitfield.as_ssz_bytes()
+}
+
+/// Decodes the raw bytes of an `attnets`/`syncnets` ENR field, rejecting a wrong length or
+/// set padding bits.
+pub fn decode_enr_bitfield<T: Decode>(bytes: &[u8]) -> Result<T, ssz::DecodeError> {
+    T::from_ssz_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+
+    use super::*;
+
+    #[test]
+    fn test_compute_subnet_for_attestation() {
+        assert_eq!(SubnetId::compute_for_attestation(4, 0, 3), SubnetId(3));
+        assert_eq!(SubnetId::compute_for_attestation(4, 33, 2), SubnetId(6));
+        // Wraps around once more committees than subnets have been assigned in the epoch.
+        assert_eq!(SubnetId::compute_for_attestation(64, 31, 5), SubnetId(5));
+        assert_eq!(SubnetId::new(64), None);
+    }
+
+    #[test]
+    fn test_sync_subnets_for_pubkey() {
+        let pubkey = FixedBytes::repeat_byte(1);
+        let mut sync_committee = SyncCommittee::default();
+        sync_committee.pubkeys[3] = pubkey;
+        sync_committee.pubkeys[130] = pubkey;
+        sync_committee.pubkeys[140] = pubkey;
+
+        assert_eq!(
+            SyncSubnetId::compute_for_sync_committee(&sync_committee, &pubkey),
+            BTreeSet::from([SyncSubnetId(0), SyncSubnetId(1)])
+        );
+    }
+
+    #[test]
+    fn test_enr_bitfield_round_trip() {
+        let attnets = attnets_from_subnets([SubnetId(0), SubnetId(9), SubnetId(63)]);
+        let bytes = encode_enr_bitfield(&attnets);
+        assert_eq!(bytes, [0x01, 0x02, 0, 0, 0, 0, 0, 0x80]);
+
+        let decoded: AttestationSubnets = decode_enr_bitfield(&bytes).unwrap();
+        assert_eq!(
+            subnets_from_attnets(&decoded).collect::<Vec<_>>(),
+            [SubnetId(0), SubnetId(9), SubnetId(63)]
+        );
+
+        let syncnets = syncnets_from_subnets([SyncSubnetId(2)]);
+        assert_eq!(encode_enr_bitfield(&syncnets), [0x04]);
+        // Bits past SYNC_COMMITTEE_SUBNET_COUNT must be zero.
+        assert!(decode_enr_bitfield::<SyncCommitteeSubnets>(&[0x10]).is_err());
+    }
+}
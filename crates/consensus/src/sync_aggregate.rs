@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum::U512, BitVector};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::{BLSSignature, G2_POINT_AT_INFINITY};
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct SyncAggregate {
+    pub sync_committee_bits: BitVector<U512>,
+    pub sync_committee_signature: BLSSignature,
+}
+
+impl SyncAggregate {
+    /// Aggregate without participants, included when no sync committee messages were received.
+    pub fn empty() -> Self {
+        Self {
+            sync_committee_bits: BitVector::new(),
+            sync_committee_signature: G2_POINT_AT_INFINITY,
+        }
+    }
+
+    /// Number of sync committee members that took part in the aggregate signature.
+    pub fn num_active_participants(&self) -> usize {
+        self.sync_committee_bits.num_set_bits()
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum::U512, FixedVector};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::BLSPubkey;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct SyncCommittee {
+    pub pubkeys: FixedVector<BLSPubkey, U512>,
+    pub aggregate_pubkey: BLSPubkey,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+use crate::bls::BLSSignature;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct VoluntaryExit {
+    /// Earliest epoch when voluntary exit can be processed
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+}
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct SignedVoluntaryExit {
+    pub message: VoluntaryExit,
+    pub signature: BLSSignature,
+}
@@ -0,0 +1,17 @@
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+#[derive(
+    Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize, Encode, Decode, TreeHash,
+)]
+pub struct Withdrawal {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    pub address: Address,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub amount: u64,
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::B256;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ExecutionError;
+
+/// The secret shared with the execution layer, read from the hex file both clients are
+/// configured with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct JwtSecret(B256);
+
+impl JwtSecret {
+    pub fn new(secret: B256) -> Self {
+        Self(secret)
+    }
+
+    /// Reads a secret file holding 32 hex encoded bytes, `0x` prefix optional.
+    pub fn from_file(path: &Path) -> Result<Self, ExecutionError> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            ExecutionError::JwtSecret(format!("failed to read {}: {err}", path.display()))
+        })?;
+        contents.parse()
+    }
+
+    /// A token for a request made at `now`, the execution layer rejects tokens issued more
+    /// than a minute away from its own clock.
+    pub fn token_at(&self, now: u64) -> Result<String, ExecutionError> {
+        Ok(jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &Claims { iat: now },
+            &EncodingKey::from_secret(self.0.as_slice()),
+        )?)
+    }
+
+    pub fn token(&self) -> Result<String, ExecutionError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.token_at(now)
+    }
+}
+
+impl FromStr for JwtSecret {
+    type Err = ExecutionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.len() != 64 {
+            return Err(ExecutionError::JwtSecret(format!(
+                "expected 32 hex encoded bytes, got {} characters",
+                hex.len()
+            )));
+        }
+        B256::from_str(hex)
+            .map(Self)
+            .map_err(|err| ExecutionError::JwtSecret(err.to_string()))
+    }
+}
+
+/// Keeps the secret out of logs.
+impl std::fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("JwtSecret(..)")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Issued-at time, in seconds since the Unix epoch.
+    pub iat: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{DecodingKey, Validation};
+
+    use super::*;
+
+    const SECRET: &str = "0x7365637265747365637265747365637265747365637265747365637265747365";
+
+    #[test]
+    fn test_parse_secret() {
+        let secret: JwtSecret = format!("{SECRET}\n").parse().unwrap();
+        assert_eq!(secret, SECRET[2..].parse().unwrap());
+        assert!("0x1234".parse::<JwtSecret>().is_err());
+        assert!(SECRET.replace('7', "z").parse::<JwtSecret>().is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let secret: JwtSecret = SECRET.parse().unwrap();
+        let token = secret.token_at(1_700_000_000).unwrap();
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        let claims = jsonwebtoken::decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(secret.0.as_slice()),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+        assert_eq!(claims, Claims { iat: 1_700_000_000 });
+
+        let other = JwtSecret::new(B256::repeat_byte(1));
+        assert!(jsonwebtoken::decode::<Claims>(
+            &token,
+            &DecodingKey::from_secret(other.0.as_slice()),
+            &validation,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use ream_consensus::fork_schedule::ForkName;
+use tracing::info;
+
+use crate::{client::EngineClient, error::ExecutionError};
+
+/// Engine API methods [`EngineClient`] calls, announced to the execution layer.
+pub const SUPPORTED_METHODS: &[&str] = &[
+    "engine_newPayloadV3",
+    "engine_forkchoiceUpdatedV3",
+    "engine_getPayloadV3",
+];
+
+/// Engine API methods the execution layer must serve for the node to follow `fork`.
+pub fn required_methods(fork: ForkName) -> &'static [&'static str] {
+    match fork {
+        ForkName::Phase0 | ForkName::Altair => &[],
+        ForkName::Bellatrix => &[
+            "engine_newPayloadV1",
+            "engine_forkchoiceUpdatedV1",
+            "engine_getPayloadV1",
+        ],
+        ForkName::Capella => &[
+            "engine_newPayloadV2",
+            "engine_forkchoiceUpdatedV2",
+            "engine_getPayloadV2",
+        ],
+        ForkName::Deneb => &[
+            "engine_newPayloadV3",
+            "engine_forkchoiceUpdatedV3",
+            "engine_getPayloadV3",
+        ],
+        ForkName::Electra => &[
+            "engine_newPayloadV4",
+            "engine_forkchoiceUpdatedV3",
+            "engine_getPayloadV4",
+        ],
+    }
+}
+
+/// What the execution layer told about itself when first contacted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionLayerInfo {
+    pub client_version: String,
+    /// Engine API methods it serves.
+    pub capabilities: Vec<String>,
+}
+
+/// Exchanges capabilities with the execution layer and fetches its client version, failing
+/// with [`ExecutionError::MissingMethods`] when it can't serve `fork`.
+///
+/// Meant to run at startup so an incompatible execution layer is caught before it fails a
+/// block import or proposal.
+pub async fn check_compatibility(
+    engine: &EngineClient,
+    fork: ForkName,
+) -> Result<ExecutionLayerInfo, ExecutionError> {
+    let capabilities = engine.exchange_capabilities(SUPPORTED_METHODS).await?;
+    let client_version = engine.client_version().await?;
+    info!(
+        "Execution layer at {} runs {client_version}",
+        engine.endpoint()
+    );
+
+    let missing: Vec<String> = required_methods(fork)
+        .iter()
+        .filter(|method| !capabilities.iter().any(|capability| capability == *method))
+        .map(|method| method.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ExecutionError::MissingMethods {
+            fork,
+            methods: missing,
+        });
+    }
+    Ok(ExecutionLayerInfo {
+        client_version,
+        capabilities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::auth::JwtSecret;
+
+    /// An execution layer serving the Deneb methods only.
+    async fn mock_engine() -> Url {
+        let handler = |Json(request): Json<Value>| async move {
+            let result = match request["method"].as_str().unwrap() {
+                "engine_exchangeCapabilities" => {
+                    assert_eq!(request["params"][0], json!(SUPPORTED_METHODS));
+                    json!(required_methods(ForkName::Deneb))
+                }
+                "web3_clientVersion" => json!("Geth/v1.14.0-stable/linux-amd64/go1.22.2"),
+                method => panic!("unexpected method {method}"),
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(handler)))
+                .await
+                .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_compatibility() {
+        let engine = EngineClient::new(mock_engine().await, JwtSecret::new(B256::ZERO));
+
+        let info = check_compatibility(&engine, ForkName::Deneb).await.unwrap();
+        assert!(info.client_version.starts_with("Geth/"));
+
+        let err = check_compatibility(&engine, ForkName::Electra)
+            .await
+            .unwrap_err();
+        match err {
+            ExecutionError::MissingMethods { fork, methods } => {
+                assert_eq!(fork, ForkName::Electra);
+                assert_eq!(methods, ["engine_newPayloadV4", "engine_getPayloadV4"]);
+            }
+            err => panic!("unexpected error {err}"),
+        }
+    }
+}
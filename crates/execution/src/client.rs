@@ -0,0 +1,583 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use alloy_primitives::{B256, U64};
+use futures::future::join_all;
+use ream_consensus::execution_payload::ExecutionPayload;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::{
+    auth::JwtSecret,
+    error::ExecutionError,
+    health::{Backoff, EngineState, HEALTH_CHECK_INTERVAL},
+    types::{
+        Eth1Block, ExecutionPayloadV3, ForkchoiceStateV1, ForkchoiceUpdatedResponse,
+        GetPayloadV3Response, Log, LogFilter, PayloadAttributesV3, PayloadId, PayloadStatusV1,
+    },
+};
+
+/// Timeouts the engine API specification sets for each method.
+pub const NEW_PAYLOAD_TIMEOUT: Duration = Duration::from_secs(8);
+pub const FORKCHOICE_UPDATED_TIMEOUT: Duration = Duration::from_secs(8);
+pub const GET_PAYLOAD_TIMEOUT: Duration = Duration::from_secs(1);
+pub const EXCHANGE_CAPABILITIES_TIMEOUT: Duration = Duration::from_secs(1);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(1);
+const ETH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize)]
+struct JsonRpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    method: &'a str,
+    params: P,
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    result: Option<Value>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+/// Payload ids remembered with the endpoint building them, only the latest few are fetched.
+const MAX_PAYLOAD_ENDPOINTS: usize = 8;
+
+/// An execution layer the client talks to.
+#[derive(Debug)]
+struct Endpoint {
+    url: Url,
+    secret: JwtSecret,
+    state: watch::Sender<EngineState>,
+}
+
+/// JSON-RPC client of the authenticated engine API of one or more execution layers.
+///
+/// Requests go to the first reachable endpoint in the configured order and fail over to the
+/// next ones when it can't be reached. Forkchoice updates are sent to every endpoint so the
+/// fallbacks follow the head too. Every request carries a freshly issued JWT and updates the
+/// [`EngineState`] of its endpoint, which [`EngineClient::monitor`] keeps current while no
+/// requests are made.
+#[derive(Debug)]
+pub struct EngineClient {
+    http: reqwest::Client,
+    endpoints: Vec<Endpoint>,
+    next_id: AtomicU64,
+    /// Online while any endpoint is.
+    state: watch::Sender<EngineState>,
+    /// Endpoints that returned the latest payload ids, `engine_getPayloadV3` has to ask the
+    /// same one.
+    payload_endpoints: Mutex<VecDeque<(PayloadId, usize)>>,
+}
+
+impl EngineClient {
+    pub fn new(endpoint: Url, secret: JwtSecret) -> Self {
+        Self::with_endpoints(vec![(endpoint, secret)])
+    }
+
+    /// Client of several execution layers, the first one preferred and the others used when
+    /// it's unreachable.
+    ///
+    /// # Panics
+    ///
+    /// When `endpoints` is empty.
+    pub fn with_endpoints(endpoints: Vec<(Url, JwtSecret)>) -> Self {
+        assert!(!endpoints.is_empty(), "no execution layer endpoint");
+        Self {
+            http: reqwest::Client::new(),
+            endpoints: endpoints
+                .into_iter()
+                .map(|(url, secret)| Endpoint {
+                    url,
+                    secret,
+                    state: watch::Sender::new(EngineState::Offline),
+                })
+                .collect(),
+            next_id: AtomicU64::new(1),
+            state: watch::Sender::new(EngineState::Offline),
+            payload_endpoints: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// The preferred endpoint.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoints[0].url
+    }
+
+    pub fn endpoints(&self) -> impl Iterator<Item = &Url> {
+        self.endpoints.iter().map(|endpoint| &endpoint.url)
+    }
+
+    pub fn state(&self) -> EngineState {
+        *self.state.borrow()
+    }
+
+    /// Notifies of every change of the [`EngineState`].
+    pub fn subscribe(&self) -> watch::Receiver<EngineState> {
+        self.state.subscribe()
+    }
+
+    /// `engine_newPayloadV3`, asks the execution layer to validate `payload`.
+    pub async fn new_payload_v3(
+        &self,
+        payload: &ExecutionPayload,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatusV1, ExecutionError> {
+        self.request(
+            "engine_newPayloadV3",
+            (
+                ExecutionPayloadV3::from(payload),
+                versioned_hashes,
+                parent_beacon_block_root,
+            ),
+            NEW_PAYLOAD_TIMEOUT,
+        )
+        .await
+    }
+
+    /// `engine_forkchoiceUpdatedV3`, moves the head of the execution layer and starts building
+    /// a payload when `attributes` are given.
+    pub async fn forkchoice_updated_v3(
+        &self,
+        forkchoice_state: ForkchoiceStateV1,
+        attributes: Option<PayloadAttributesV3>,
+    ) -> Result<ForkchoiceUpdatedResponse, ExecutionError> {
+        let results: Vec<Result<ForkchoiceUpdatedResponse, _>> = self
+            .broadcast(
+                "engine_forkchoiceUpdatedV3",
+                (forkchoice_state, attributes),
+                FORKCHOICE_UPDATED_TIMEOUT,
+            )
+            .await;
+
+        // The answer of the most preferred endpoint counts, an error only when none answered
+        // and preferably one that isn't about reaching it.
+        let mut error: Option<ExecutionError> = None;
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(response) => {
+                    if let Some(payload_id) = response.payload_id {
+                        let mut payload_endpoints = self
+                            .payload_endpoints
+                            .lock()
+                            .expect("payload endpoints lock poisoned");
+                        if payload_endpoints.len() == MAX_PAYLOAD_ENDPOINTS {
+                            payload_endpoints.pop_front();
+                        }
+                        payload_endpoints.push_back((payload_id, index));
+                    }
+                    return Ok(response);
+                }
+                Err(err) => match &error {
+                    Some(error) if !error.is_connection_error() => {}
+                    _ => error = Some(err),
+                },
+            }
+        }
+        Err(error.expect("there is at least one endpoint"))
+    }
+
+    /// `engine_getPayloadV3`, fetches the payload built since the forkchoice update that
+    /// returned `payload_id`.
+    pub async fn get_payload_v3(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<GetPayloadV3Response, ExecutionError> {
+        let endpoint = self
+            .payload_endpoints
+            .lock()
+            .expect("payload endpoints lock poisoned")
+            .iter()
+            .rev()
+            .find(|(id, _)| *id == payload_id)
+            .map(|(_, index)| *index);
+        match endpoint {
+            Some(index) => {
+                self.request_endpoint(
+                    index,
+                    "engine_getPayloadV3",
+                    (payload_id,),
+                    GET_PAYLOAD_TIMEOUT,
+                )
+                .await
+            }
+            None => {
+                self.request("engine_getPayloadV3", (payload_id,), GET_PAYLOAD_TIMEOUT)
+                    .await
+            }
+        }
+    }
+
+    /// `engine_exchangeCapabilities`, announces the engine API methods the client calls and
+    /// returns those the execution layer serves.
+    pub async fn exchange_capabilities(
+        &self,
+        methods: &[&str],
+    ) -> Result<Vec<String>, ExecutionError> {
+        self.request(
+            "engine_exchangeCapabilities",
+            (methods,),
+            EXCHANGE_CAPABILITIES_TIMEOUT,
+        )
+        .await
+    }
+
+    /// `web3_clientVersion`, the name and version of the execution layer client.
+    pub async fn client_version(&self) -> Result<String, ExecutionError> {
+        self.request(
+            "web3_clientVersion",
+            Vec::<Value>::new(),
+            HEALTH_CHECK_TIMEOUT,
+        )
+        .await
+    }
+
+    /// `eth_blockNumber`, the number of the head block of the execution layer.
+    pub async fn block_number(&self) -> Result<u64, ExecutionError> {
+        let number: U64 = self
+            .request("eth_blockNumber", Vec::<Value>::new(), ETH_TIMEOUT)
+            .await?;
+        Ok(number.to())
+    }
+
+    /// `eth_getBlockByNumber` without transactions, `None` for a block the execution layer
+    /// doesn't have.
+    pub async fn block_by_number(&self, number: u64) -> Result<Option<Eth1Block>, ExecutionError> {
+        self.request(
+            "eth_getBlockByNumber",
+            (U64::from(number), false),
+            ETH_TIMEOUT,
+        )
+        .await
+    }
+
+    /// `eth_getLogs`
+    pub async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>, ExecutionError> {
+        self.request("eth_getLogs", (filter,), ETH_TIMEOUT).await
+    }
+
+    /// Checks on every execution layer forever, every [`HEALTH_CHECK_INTERVAL`] while it is
+    /// reachable and with a growing [`Backoff`] while it isn't. Meant to be spawned.
+    pub async fn monitor(&self) {
+        join_all((0..self.endpoints.len()).map(|index| self.monitor_endpoint(index))).await;
+    }
+
+    async fn monitor_endpoint(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        let mut backoff = Backoff::default();
+        loop {
+            // Any request answered, not only the health check, proves the connection works.
+            let delay = match *endpoint.state.borrow() {
+                EngineState::Online => {
+                    backoff.reset();
+                    HEALTH_CHECK_INTERVAL
+                }
+                EngineState::Offline => backoff.next_delay(),
+            };
+            tokio::time::sleep(delay).await;
+            if let Err(err) = self
+                .request_endpoint::<_, Value>(
+                    index,
+                    "eth_syncing",
+                    Vec::<Value>::new(),
+                    HEALTH_CHECK_TIMEOUT,
+                )
+                .await
+            {
+                warn!(
+                    "Health check of the execution layer at {} failed: {err}",
+                    endpoint.url
+                );
+            }
+        }
+    }
+
+    /// Sends the request to the reachable endpoints first, in order, then to the others, until
+    /// one answers.
+    async fn request<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R, ExecutionError> {
+        let (online, offline): (Vec<usize>, Vec<usize>) = (0..self.endpoints.len())
+            .partition(|&index| *self.endpoints[index].state.borrow() == EngineState::Online);
+        let mut error = None;
+        for index in online.into_iter().chain(offline) {
+            match self.request_endpoint(index, method, &params, timeout).await {
+                Err(err) if err.is_connection_error() => error = Some(err),
+                result => return result,
+            }
+        }
+        Err(error.expect("there is at least one endpoint"))
+    }
+
+    /// Sends the request to every endpoint at once, the results in the order of the endpoints.
+    async fn broadcast<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Vec<Result<R, ExecutionError>> {
+        join_all(
+            (0..self.endpoints.len())
+                .map(|index| self.request_endpoint(index, method, &params, timeout)),
+        )
+        .await
+    }
+
+    async fn request_endpoint<P: Serialize, R: DeserializeOwned>(
+        &self,
+        index: usize,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R, ExecutionError> {
+        let result = self
+            .send(&self.endpoints[index], method, params, timeout)
+            .await;
+        match &result {
+            // Signing the token failed, nothing was sent.
+            Err(ExecutionError::Jwt(_)) => {}
+            Err(err) if err.is_connection_error() => self.set_state(index, EngineState::Offline),
+            _ => self.set_state(index, EngineState::Online),
+        }
+        let result = result?;
+        serde_json::from_value(result)
+            .map_err(|err| ExecutionError::InvalidResponse(format!("{method}: {err}")))
+    }
+
+    async fn send<P: Serialize>(
+        &self,
+        endpoint: &Endpoint,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<Value, ExecutionError> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            method,
+            params,
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+        };
+        let response: JsonRpcResponse = self
+            .http
+            .post(endpoint.url.clone())
+            .bearer_auth(endpoint.secret.token()?)
+            .timeout(timeout)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match response {
+            JsonRpcResponse {
+                error: Some(error), ..
+            } => Err(ExecutionError::Rpc {
+                code: error.code,
+                message: error.message,
+            }),
+            JsonRpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            // A `null` result deserializes as a missing one.
+            JsonRpcResponse { result: None, .. } => Ok(Value::Null),
+        }
+    }
+
+    fn set_state(&self, index: usize, state: EngineState) {
+        let endpoint = &self.endpoints[index];
+        let changed = endpoint.state.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            match state {
+                EngineState::Online => info!("Execution layer at {} is online", endpoint.url),
+                EngineState::Offline => warn!("Execution layer at {} is offline", endpoint.url),
+            }
+            *current = state;
+            true
+        });
+        if !changed {
+            return;
+        }
+        let state = if self
+            .endpoints
+            .iter()
+            .any(|endpoint| *endpoint.state.borrow() == EngineState::Online)
+        {
+            EngineState::Online
+        } else {
+            EngineState::Offline
+        };
+        self.state.send_if_modified(|current| {
+            let changed = *current != state;
+            *current = state;
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::types::PayloadStatusKind;
+
+    /// An execution layer answering every forkchoice update with a payload id and rejecting
+    /// everything else.
+    async fn mock_engine() -> Url {
+        counting_mock_engine(Arc::new(AtomicU64::new(0))).await
+    }
+
+    /// [`mock_engine`] counting the requests it gets in `requests`.
+    async fn counting_mock_engine(requests: Arc<AtomicU64>) -> Url {
+        let handler = move |headers: HeaderMap, Json(request): Json<Value>| async move {
+            requests.fetch_add(1, Ordering::Relaxed);
+            assert!(headers["authorization"]
+                .to_str()
+                .unwrap()
+                .starts_with("Bearer "));
+            let id = request["id"].clone();
+            Json(match request["method"].as_str().unwrap() {
+                "engine_forkchoiceUpdatedV3" => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "payloadStatus": {
+                            "status": "VALID",
+                            "latestValidHash": request["params"][0]["headBlockHash"],
+                            "validationError": null
+                        },
+                        "payloadId": "0x0000000000000001"
+                    }
+                }),
+                _ => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": "Method not found" }
+                }),
+            })
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(handler)))
+                .await
+                .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    /// An endpoint nothing listens on.
+    async fn unreachable_endpoint() -> Url {
+        // Nothing listens on a port that was just released.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        endpoint.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_engine_client() {
+        let client = EngineClient::new(mock_engine().await, JwtSecret::new(B256::ZERO));
+        assert_eq!(client.state(), EngineState::Offline);
+
+        let head = B256::repeat_byte(1);
+        let response = client
+            .forkchoice_updated_v3(
+                ForkchoiceStateV1 {
+                    head_block_hash: head,
+                    ..ForkchoiceStateV1::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.payload_status.status, PayloadStatusKind::Valid);
+        assert_eq!(response.payload_status.latest_valid_hash, Some(head));
+        assert!(response.payload_id.is_some());
+        assert_eq!(client.state(), EngineState::Online);
+
+        // An error answer still means the execution layer is reachable.
+        let err = client.get_payload_v3(PayloadId::ZERO).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::Rpc { code: -32601, .. }));
+        assert_eq!(client.state(), EngineState::Online);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_engine() {
+        let client = EngineClient::new(unreachable_endpoint().await, JwtSecret::new(B256::ZERO));
+        let mut state = client.subscribe();
+        let err = client
+            .forkchoice_updated_v3(ForkchoiceStateV1::default(), None)
+            .await
+            .unwrap_err();
+        assert!(err.is_connection_error());
+        assert_eq!(*state.borrow_and_update(), EngineState::Offline);
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let primary = Arc::new(AtomicU64::new(0));
+        let fallback = Arc::new(AtomicU64::new(0));
+        let client = EngineClient::with_endpoints(vec![
+            (unreachable_endpoint().await, JwtSecret::new(B256::ZERO)),
+            (
+                counting_mock_engine(primary.clone()).await,
+                JwtSecret::new(B256::ZERO),
+            ),
+            (
+                counting_mock_engine(fallback.clone()).await,
+                JwtSecret::new(B256::ZERO),
+            ),
+        ]);
+
+        // Every endpoint follows the head, the first one answering is used.
+        let response = client
+            .forkchoice_updated_v3(ForkchoiceStateV1::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(response.payload_status.status, PayloadStatusKind::Valid);
+        assert_eq!(primary.load(Ordering::Relaxed), 1);
+        assert_eq!(fallback.load(Ordering::Relaxed), 1);
+        assert_eq!(*client.endpoints[0].state.borrow(), EngineState::Offline);
+        assert_eq!(client.state(), EngineState::Online);
+
+        // The payload is fetched from the endpoint building it.
+        let err = client
+            .get_payload_v3(response.payload_id.unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ExecutionError::Rpc { code: -32601, .. }));
+        assert_eq!(primary.load(Ordering::Relaxed), 2);
+        assert_eq!(fallback.load(Ordering::Relaxed), 1);
+
+        // Other requests skip the unreachable endpoint.
+        client.client_version().await.unwrap_err();
+        assert_eq!(primary.load(Ordering::Relaxed), 3);
+        assert_eq!(fallback.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,38 @@
+use alloy_primitives::B256;
+use ream_consensus::fork_schedule::ForkName;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExecutionError {
+    #[error("invalid JWT secret: {0}")]
+    JwtSecret(String),
+    #[error("failed to sign the JWT: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("execution layer unreachable: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("execution layer returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("invalid response from the execution layer: {0}")]
+    InvalidResponse(String),
+    #[error("execution layer rejected payload {block_hash}: {reason}")]
+    InvalidPayload { block_hash: B256, reason: String },
+    #[error("execution layer lacks the engine API methods {fork} needs: {}", .methods.join(", "))]
+    MissingMethods {
+        fork: ForkName,
+        methods: Vec<String>,
+    },
+    #[error("deposit cache: {0}")]
+    DepositCache(String),
+    #[error("failed to apply the execution layer's verdict to fork choice: {0}")]
+    ForkChoice(#[from] ream_fork_choice::error::ProtoArrayError),
+}
+
+impl ExecutionError {
+    /// Whether the execution layer could not be reached at all, as opposed to answering with
+    /// an error.
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            ExecutionError::Http(err) => err.is_connect() || err.is_timeout() || err.is_request(),
+            _ => false,
+        }
+    }
+}
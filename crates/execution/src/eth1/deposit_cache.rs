@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use ream_consensus::{
+    beacon_state::BeaconState,
+    constants::{
+        EPOCHS_PER_ETH1_VOTING_PERIOD, ETH1_FOLLOW_DISTANCE, MAX_DEPOSITS, SECONDS_PER_ETH1_BLOCK,
+        SECONDS_PER_SLOT, SLOTS_PER_EPOCH,
+    },
+    deposit::{Deposit, DepositData},
+    eth1_data::Eth1Data,
+};
+use tree_hash::TreeHash;
+
+use super::{deposit_log::DepositLog, deposit_tree::DepositTree};
+use crate::{error::ExecutionError, types::Eth1Block};
+
+/// Execution blocks kept for eth1 data votes, enough to cover the candidate blocks of a voting
+/// period.
+pub const MAX_CACHED_BLOCKS: usize = 2 * ETH1_FOLLOW_DISTANCE as usize;
+
+/// Deposits of the deposit contract and the recent execution blocks, all a proposer needs for
+/// the eth1 data vote and deposits of its block.
+#[derive(Debug, Default)]
+pub struct DepositCache {
+    tree: DepositTree,
+    deposits: Vec<DepositData>,
+    /// Number of the block each deposit was made in, in deposit order.
+    deposit_blocks: Vec<u64>,
+    blocks: VecDeque<Eth1Block>,
+}
+
+impl DepositCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn deposit_count(&self) -> u64 {
+        self.tree.len()
+    }
+
+    /// Adds the next deposit, logs of deposits already known are ignored.
+    pub fn insert_log(&mut self, log: DepositLog) -> Result<(), ExecutionError> {
+        let expected = self.deposit_count();
+        if log.index < expected {
+            return Ok(());
+        }
+        if log.index > expected {
+            return Err(ExecutionError::DepositCache(format!(
+                "got deposit {} while expecting deposit {expected}",
+                log.index
+            )));
+        }
+        self.tree.push(log.data.tree_hash_root());
+        self.deposits.push(log.data);
+        self.deposit_blocks.push(log.block_number);
+        Ok(())
+    }
+
+    /// Adds a block following the cached ones, dropping the oldest beyond
+    /// [`MAX_CACHED_BLOCKS`]. Deposit logs must be inserted up to the block first.
+    pub fn insert_block(&mut self, block: Eth1Block) {
+        if self
+            .blocks
+            .back()
+            .is_some_and(|last| last.number >= block.number)
+        {
+            return;
+        }
+        self.blocks.push_back(block);
+        while self.blocks.len() > MAX_CACHED_BLOCKS {
+            self.blocks.pop_front();
+        }
+    }
+
+    pub fn latest_block(&self) -> Option<&Eth1Block> {
+        self.blocks.back()
+    }
+
+    /// The deposit root and count of the contract as of `block`.
+    pub fn eth1_data(&self, block: &Eth1Block) -> Eth1Data {
+        let deposit_count = self
+            .deposit_blocks
+            .partition_point(|number| *number <= block.number) as u64;
+        Eth1Data {
+            deposit_root: self
+                .tree
+                .root(deposit_count)
+                .expect("deposit count is within the tree"),
+            deposit_count,
+            block_hash: block.hash,
+        }
+    }
+
+    /// The eth1 data a block proposed on `state` should vote for.
+    pub fn get_eth1_vote(&self, state: &BeaconState) -> Eth1Data {
+        let period_start = voting_period_start_time(state);
+        let votes_to_consider: Vec<Eth1Data> = self
+            .blocks
+            .iter()
+            .filter(|block| is_candidate_block(block, period_start))
+            .map(|block| self.eth1_data(block))
+            .filter(|eth1_data| eth1_data.deposit_count >= state.eth1_data.deposit_count)
+            .collect();
+
+        // Valid votes with their counts, in order of first appearance.
+        let mut valid_votes: Vec<(&Eth1Data, usize)> = vec![];
+        for vote in state
+            .eth1_data_votes
+            .iter()
+            .filter(|vote| votes_to_consider.contains(vote))
+        {
+            match valid_votes.iter_mut().find(|(valid, _)| *valid == vote) {
+                Some((_, count)) => *count += 1,
+                None => valid_votes.push((vote, 1)),
+            }
+        }
+        // The most voted, the earliest on ties.
+        let best = valid_votes
+            .into_iter()
+            .reduce(|best, vote| if vote.1 > best.1 { vote } else { best });
+
+        match best {
+            Some((vote, _)) => vote.clone(),
+            None => votes_to_consider
+                .last()
+                .cloned()
+                .unwrap_or_else(|| state.eth1_data.clone()),
+        }
+    }
+
+    /// The deposits a block has to include to bring `eth1_deposit_index` closer to the deposit
+    /// count of `eth1_data`, with proofs against its deposit root.
+    pub fn get_deposits(
+        &self,
+        eth1_deposit_index: u64,
+        eth1_data: &Eth1Data,
+    ) -> Result<Vec<Deposit>, ExecutionError> {
+        let count = eth1_data.deposit_count;
+        let root = self.tree.root(count).ok_or_else(|| {
+            ExecutionError::DepositCache(format!(
+                "{count} deposits voted for while only {} are known",
+                self.deposit_count()
+            ))
+        })?;
+        if root != eth1_data.deposit_root {
+            return Err(ExecutionError::DepositCache(format!(
+                "deposit root {} of {count} deposits doesn't match the voted {}",
+                root, eth1_data.deposit_root
+            )));
+        }
+        let end = count.min(eth1_deposit_index.saturating_add(MAX_DEPOSITS));
+        (eth1_deposit_index..end)
+            .map(|index| {
+                Ok(Deposit {
+                    proof: self.tree.proof(index, count).expect("index below count"),
+                    data: self.deposits[index as usize].clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+fn voting_period_start_time(state: &BeaconState) -> u64 {
+    let slots_per_period = EPOCHS_PER_ETH1_VOTING_PERIOD * SLOTS_PER_EPOCH;
+    let period_start_slot = state.slot - state.slot % slots_per_period;
+    state.genesis_time + period_start_slot * SECONDS_PER_SLOT
+}
+
+fn is_candidate_block(block: &Eth1Block, period_start: u64) -> bool {
+    let follow_time = SECONDS_PER_ETH1_BLOCK * ETH1_FOLLOW_DISTANCE;
+    block.timestamp + follow_time <= period_start
+        && block.timestamp + follow_time * 2 >= period_start
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use ream_consensus::{
+        bls::BLSPubkey, constants::DEPOSIT_CONTRACT_TREE_DEPTH, misc::is_valid_merkle_branch,
+    };
+
+    use super::*;
+
+    const FOLLOW_TIME: u64 = SECONDS_PER_ETH1_BLOCK * ETH1_FOLLOW_DISTANCE;
+
+    fn deposit(index: u64, block_number: u64) -> DepositLog {
+        DepositLog {
+            data: DepositData {
+                pubkey: BLSPubkey::repeat_byte(index as u8),
+                amount: 32_000_000_000,
+                ..DepositData::default()
+            },
+            index,
+            block_number,
+        }
+    }
+
+    fn block(number: u64, timestamp: u64) -> Eth1Block {
+        Eth1Block {
+            hash: B256::repeat_byte(number as u8),
+            number,
+            timestamp,
+        }
+    }
+
+    /// Deposits 0 and 1 in block 1, deposit 2 in block 3, blocks 1 to 4 being candidates of the
+    /// first voting period of a chain started at `2 * FOLLOW_TIME`.
+    fn cache() -> DepositCache {
+        let mut cache = DepositCache::new();
+        for (index, block_number) in [(0, 1), (1, 1), (2, 3)] {
+            cache.insert_log(deposit(index, block_number)).unwrap();
+        }
+        for number in 1..=4 {
+            cache.insert_block(block(number, number * 100));
+        }
+        cache
+    }
+
+    #[test]
+    fn test_insert_log() {
+        let mut cache = cache();
+        cache.insert_log(deposit(1, 1)).unwrap();
+        assert_eq!(cache.deposit_count(), 3);
+        assert!(cache.insert_log(deposit(4, 5)).is_err());
+    }
+
+    #[test]
+    fn test_get_eth1_vote() {
+        let cache = cache();
+        let mut state = BeaconState {
+            genesis_time: FOLLOW_TIME + 300,
+            ..BeaconState::default()
+        };
+        // Without votes, the latest candidate block. Block 4 is too recent.
+        let vote = cache.get_eth1_vote(&state);
+        assert_eq!(vote, cache.eth1_data(&block(3, 300)));
+        assert_eq!(vote.deposit_count, 3);
+
+        // The most voted candidate wins, votes for unknown blocks don't count.
+        let block_1 = cache.eth1_data(&block(1, 100));
+        assert_eq!(block_1.deposit_count, 2);
+        let unknown = Eth1Data {
+            block_hash: B256::repeat_byte(9),
+            ..block_1.clone()
+        };
+        state.eth1_data_votes = vec![
+            unknown.clone(),
+            unknown,
+            block_1.clone(),
+            vote,
+            block_1.clone(),
+        ]
+        .into();
+        assert_eq!(cache.get_eth1_vote(&state), block_1);
+
+        // Candidates behind the deposits of the state don't count either.
+        state.eth1_data.deposit_count = 3;
+        assert_eq!(cache.get_eth1_vote(&state).block_hash, block(3, 300).hash);
+    }
+
+    #[test]
+    fn test_get_deposits() {
+        let cache = cache();
+        let eth1_data = cache.eth1_data(&block(3, 300));
+        let deposits = cache.get_deposits(1, &eth1_data).unwrap();
+        assert_eq!(deposits.len(), 2);
+        for (index, deposit) in (1..).zip(&deposits) {
+            assert!(is_valid_merkle_branch(
+                deposit.data.tree_hash_root(),
+                &deposit.proof,
+                DEPOSIT_CONTRACT_TREE_DEPTH + 1,
+                index,
+                eth1_data.deposit_root,
+            ));
+        }
+
+        let wrong_root = Eth1Data {
+            deposit_root: B256::ZERO,
+            ..eth1_data
+        };
+        assert!(cache.get_deposits(1, &wrong_root).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use alloy_primitives::{b256, B256};
+use ream_consensus::{
+    bls::{BLSPubkey, BLSSignature},
+    deposit::DepositData,
+};
+
+use crate::{error::ExecutionError, types::Log};
+
+/// `keccak256("DepositEvent(bytes,bytes,bytes,bytes,bytes)")`
+pub const DEPOSIT_EVENT_TOPIC: B256 =
+    b256!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5");
+
+/// Lengths of the `pubkey`, `withdrawal_credentials`, `amount`, `signature` and `index` fields
+/// of a `DepositEvent`.
+const FIELD_LENGTHS: [usize; 5] = [48, 32, 8, 96, 8];
+
+/// A deposit as logged by the deposit contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositLog {
+    pub data: DepositData,
+    /// Position of the deposit in the contract's Merkle tree.
+    pub index: u64,
+    pub block_number: u64,
+}
+
+impl DepositLog {
+    /// Decodes the ABI encoded fields of a `DepositEvent`.
+    pub fn from_log(log: &Log) -> Result<Self, ExecutionError> {
+        let data = log.data.as_ref();
+        let mut fields = Vec::with_capacity(FIELD_LENGTHS.len());
+        for (position, length) in FIELD_LENGTHS.into_iter().enumerate() {
+            let offset = read_word(data, position * 32)?;
+            let encoded_length = read_word(data, offset)?;
+            if encoded_length != length {
+                return Err(invalid_log(format!(
+                    "field {position} is {encoded_length} bytes long instead of {length}"
+                )));
+            }
+            let field = data
+                .get(offset.saturating_add(32)..offset.saturating_add(32 + length))
+                .ok_or_else(|| invalid_log("field out of bounds"))?;
+            fields.push(field);
+        }
+
+        Ok(Self {
+            data: DepositData {
+                pubkey: BLSPubkey::from_slice(fields[0]),
+                withdrawal_credentials: B256::from_slice(fields[1]),
+                amount: u64::from_le_bytes(fields[2].try_into().expect("checked length")),
+                signature: BLSSignature::from_slice(fields[3]),
+            },
+            index: u64::from_le_bytes(fields[4].try_into().expect("checked length")),
+            block_number: log.block_number,
+        })
+    }
+}
+
+/// The big endian word at `offset`, as offsets and lengths are encoded.
+fn read_word(data: &[u8], offset: usize) -> Result<usize, ExecutionError> {
+    let word = data
+        .get(offset..offset.saturating_add(32))
+        .ok_or_else(|| invalid_log("word out of bounds"))?;
+    if word[..24].iter().any(|byte| *byte != 0) {
+        return Err(invalid_log("word overflows"));
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into().expect("8 bytes")) as usize)
+}
+
+fn invalid_log(reason: impl std::fmt::Display) -> ExecutionError {
+    ExecutionError::InvalidResponse(format!("invalid deposit log: {reason}"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloy_primitives::Address;
+
+    use super::*;
+
+    /// ABI encodes a `DepositEvent` the way the deposit contract logs it.
+    pub(crate) fn deposit_event(data: &DepositData, index: u64, block_number: u64) -> Log {
+        let fields: [&[u8]; 5] = [
+            data.pubkey.as_slice(),
+            data.withdrawal_credentials.as_slice(),
+            &data.amount.to_le_bytes(),
+            data.signature.as_slice(),
+            &index.to_le_bytes(),
+        ];
+        let mut head = vec![];
+        let mut tail = vec![];
+        for field in fields {
+            let mut offset = [0; 32];
+            offset[24..].copy_from_slice(&((5 * 32 + tail.len()) as u64).to_be_bytes());
+            head.extend_from_slice(&offset);
+
+            let mut length = [0; 32];
+            length[24..].copy_from_slice(&(field.len() as u64).to_be_bytes());
+            tail.extend_from_slice(&length);
+            tail.extend_from_slice(field);
+            tail.resize(tail.len().next_multiple_of(32), 0);
+        }
+        head.extend(tail);
+        Log {
+            address: Address::ZERO,
+            topics: vec![DEPOSIT_EVENT_TOPIC],
+            data: head.into(),
+            block_number,
+        }
+    }
+
+    #[test]
+    fn test_decode_deposit_log() {
+        let data = DepositData {
+            pubkey: BLSPubkey::repeat_byte(1),
+            withdrawal_credentials: B256::repeat_byte(2),
+            amount: 32_000_000_000,
+            signature: BLSSignature::repeat_byte(3),
+        };
+        let log = deposit_event(&data, 7, 100);
+        assert_eq!(log.data.len(), 576);
+        assert_eq!(
+            DepositLog::from_log(&log).unwrap(),
+            DepositLog {
+                data,
+                index: 7,
+                block_number: 100,
+            }
+        );
+
+        let truncated = Log {
+            data: log.data[..500].to_vec().into(),
+            ..log
+        };
+        assert!(DepositLog::from_log(&truncated).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use alloy_primitives::B256;
+use ethereum_hashing::hash32_concat;
+use ream_consensus::constants::DEPOSIT_CONTRACT_TREE_DEPTH;
+use ssz_types::{typenum::U33, FixedVector};
+
+const DEPTH: usize = DEPOSIT_CONTRACT_TREE_DEPTH as usize;
+
+/// The Merkle tree of the deposit contract, able to prove deposits against the root it had at
+/// any earlier deposit count, as the eth1 data of a state lags behind the contract.
+#[derive(Debug, Clone)]
+pub struct DepositTree {
+    /// Complete nodes of each level, the leaves first.
+    levels: Vec<Vec<B256>>,
+    /// Roots of empty subtrees of each height.
+    zero_hashes: Vec<B256>,
+}
+
+impl DepositTree {
+    pub fn new() -> Self {
+        let mut zero_hashes = vec![B256::ZERO];
+        for height in 0..DEPTH {
+            let zero_hash = zero_hashes[height];
+            zero_hashes.push(hash(zero_hash, zero_hash));
+        }
+        Self {
+            levels: vec![vec![]; DEPTH + 1],
+            zero_hashes,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Appends the hash tree root of a `DepositData`.
+    pub fn push(&mut self, leaf: B256) {
+        self.levels[0].push(leaf);
+        let mut index = self.levels[0].len() - 1;
+        for height in 0..DEPTH {
+            if index % 2 == 0 {
+                break;
+            }
+            let parent = hash(self.levels[height][index - 1], self.levels[height][index]);
+            self.levels[height + 1].push(parent);
+            index /= 2;
+        }
+    }
+
+    /// Root of the contract once it held `count` deposits, the deposit count mixed in.
+    pub fn root(&self, count: u64) -> Option<B256> {
+        if count > self.len() {
+            return None;
+        }
+        Some(hash(self.node(DEPTH, 0, count), length_node(count)))
+    }
+
+    /// Branch proving the deposit at `index` against [`DepositTree::root`] of `count`, the
+    /// deposit count being the last node.
+    pub fn proof(&self, index: u64, count: u64) -> Option<FixedVector<B256, U33>> {
+        if index >= count || count > self.len() {
+            return None;
+        }
+        let mut branch: Vec<B256> = (0..DEPTH)
+            .map(|height| self.node(height, (index >> height) ^ 1, count))
+            .collect();
+        branch.push(length_node(count));
+        Some(branch.into())
+    }
+
+    /// Node at `height` and `index` of the tree holding the first `count` leaves.
+    fn node(&self, height: usize, index: u64, count: u64) -> B256 {
+        let start = index << height;
+        let end = (index + 1) << height;
+        if start >= count {
+            self.zero_hashes[height]
+        } else if end <= count {
+            self.levels[height][index as usize]
+        } else {
+            hash(
+                self.node(height - 1, index * 2, count),
+                self.node(height - 1, index * 2 + 1, count),
+            )
+        }
+    }
+}
+
+impl Default for DepositTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hash(left: B256, right: B256) -> B256 {
+    B256::from(hash32_concat(left.as_slice(), right.as_slice()))
+}
+
+fn length_node(count: u64) -> B256 {
+    let mut node = B256::ZERO;
+    node[..8].copy_from_slice(&count.to_le_bytes());
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::misc::is_valid_merkle_branch;
+
+    use super::*;
+
+    #[test]
+    fn test_deposit_tree() {
+        let mut tree = DepositTree::new();
+        // The root of the empty deposit contract.
+        assert_eq!(
+            tree.root(0).unwrap(),
+            alloy_primitives::b256!(
+                "d70a234731285c6804c2a4f56711ddb8c82c99740f207854891028af34e27e5e"
+            )
+        );
+
+        let leaves: Vec<B256> = (1..=5).map(B256::repeat_byte).collect();
+        for leaf in &leaves {
+            tree.push(*leaf);
+        }
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.root(6), None);
+
+        // Proofs hold against the roots of every earlier deposit count.
+        for count in 1..=5 {
+            let root = tree.root(count).unwrap();
+            for index in 0..count {
+                let proof = tree.proof(index, count).unwrap();
+                assert!(is_valid_merkle_branch(
+                    leaves[index as usize],
+                    &proof,
+                    DEPOSIT_CONTRACT_TREE_DEPTH + 1,
+                    index,
+                    root,
+                ));
+            }
+        }
+        assert_eq!(tree.proof(3, 3), None);
+
+        // Roots of earlier counts match trees built with only those deposits.
+        let mut smaller = DepositTree::new();
+        for leaf in &leaves[..3] {
+            smaller.push(*leaf);
+        }
+        assert_eq!(smaller.root(3), tree.root(3));
+    }
+}
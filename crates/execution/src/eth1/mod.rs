@@ -0,0 +1,12 @@
+//! Following the deposit contract on the execution layer, for the deposits and eth1 data votes
+//! of produced blocks.
+
+pub mod deposit_cache;
+pub mod deposit_log;
+pub mod deposit_tree;
+pub mod service;
+
+use alloy_primitives::{address, Address};
+
+pub const MAINNET_DEPOSIT_CONTRACT: Address = address!("00000000219ab540356cBB839Cbe05303d7705Fa");
+pub const MAINNET_DEPOSIT_CONTRACT_DEPLOY_BLOCK: u64 = 11_052_984;
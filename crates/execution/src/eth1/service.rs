@@ -0,0 +1,185 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use alloy_primitives::Address;
+use ream_consensus::constants::{ETH1_FOLLOW_DISTANCE, SECONDS_PER_ETH1_BLOCK};
+use tracing::{debug, warn};
+
+use super::{
+    deposit_cache::{DepositCache, MAX_CACHED_BLOCKS},
+    deposit_log::{DepositLog, DEPOSIT_EVENT_TOPIC},
+};
+use crate::{client::EngineClient, error::ExecutionError, types::LogFilter};
+
+/// Blocks whose logs are fetched by a single `eth_getLogs`, few enough for execution layers to
+/// serve.
+pub const LOG_BATCH_SIZE: u64 = 1000;
+pub const UPDATE_INTERVAL: Duration = Duration::from_secs(SECONDS_PER_ETH1_BLOCK);
+
+/// Follows the deposit contract up to [`ETH1_FOLLOW_DISTANCE`] blocks behind the head of the
+/// execution layer, filling a [`DepositCache`].
+#[derive(Debug)]
+pub struct Eth1Service {
+    engine: Arc<EngineClient>,
+    deposit_contract: Address,
+    /// First block whose deposit logs weren't fetched yet.
+    next_log_block: u64,
+    cache: Arc<RwLock<DepositCache>>,
+}
+
+impl Eth1Service {
+    /// Follows `deposit_contract` from `deploy_block`, where the first deposits can be.
+    pub fn new(engine: Arc<EngineClient>, deposit_contract: Address, deploy_block: u64) -> Self {
+        Self {
+            engine,
+            deposit_contract,
+            next_log_block: deploy_block,
+            cache: Arc::new(RwLock::new(DepositCache::new())),
+        }
+    }
+
+    pub fn cache(&self) -> Arc<RwLock<DepositCache>> {
+        self.cache.clone()
+    }
+
+    /// Fetches the deposits and blocks up to the follow distance, returns the number of the
+    /// latest block followed.
+    pub async fn update(&mut self) -> Result<u64, ExecutionError> {
+        let head = self.engine.block_number().await?;
+        let follow_block = head.saturating_sub(ETH1_FOLLOW_DISTANCE);
+
+        while self.next_log_block <= follow_block {
+            let to_block = follow_block.min(self.next_log_block + LOG_BATCH_SIZE - 1);
+            let logs = self
+                .engine
+                .get_logs(&LogFilter {
+                    address: self.deposit_contract,
+                    from_block: self.next_log_block,
+                    to_block,
+                    topics: vec![DEPOSIT_EVENT_TOPIC],
+                })
+                .await?;
+            let deposits = logs
+                .iter()
+                .map(DepositLog::from_log)
+                .collect::<Result<Vec<_>, _>>()?;
+            {
+                let mut cache = self.cache.write().expect("deposit cache lock poisoned");
+                for deposit in deposits {
+                    cache.insert_log(deposit)?;
+                }
+            }
+            debug!(
+                "Fetched {} deposit logs of blocks {} to {to_block}",
+                logs.len(),
+                self.next_log_block
+            );
+            self.next_log_block = to_block + 1;
+        }
+
+        let latest_block = self
+            .cache
+            .read()
+            .expect("deposit cache lock poisoned")
+            .latest_block()
+            .map(|block| block.number);
+        let earliest_block = follow_block.saturating_sub(MAX_CACHED_BLOCKS as u64 - 1);
+        let first_block =
+            latest_block.map_or(earliest_block, |number| (number + 1).max(earliest_block));
+        for number in first_block..=follow_block {
+            let block = self.engine.block_by_number(number).await?.ok_or_else(|| {
+                ExecutionError::InvalidResponse(format!("block {number} is missing"))
+            })?;
+            self.cache
+                .write()
+                .expect("deposit cache lock poisoned")
+                .insert_block(block);
+        }
+        Ok(follow_block)
+    }
+
+    /// Updates every [`UPDATE_INTERVAL`] forever. Meant to be spawned.
+    pub async fn run(mut self) {
+        loop {
+            match self.update().await {
+                Ok(block) => debug!("Deposit contract followed up to block {block}"),
+                Err(err) => warn!("Failed to follow the deposit contract: {err}"),
+            }
+            tokio::time::sleep(UPDATE_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use axum::{routing::post, Json, Router};
+    use ream_consensus::deposit::DepositData;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::{auth::JwtSecret, eth1::deposit_log::tests::deposit_event, types::Eth1Block};
+
+    const HEAD: u64 = ETH1_FOLLOW_DISTANCE + 10;
+
+    /// An execution layer at block [`HEAD`] with a deposit in every block up to 20.
+    async fn mock_engine() -> Url {
+        async fn handler(Json(request): Json<Value>) -> Json<Value> {
+            let params = &request["params"];
+            let number = |value: &Value| {
+                u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+            };
+            let result = match request["method"].as_str().unwrap() {
+                "eth_blockNumber" => json!(format!("{HEAD:#x}")),
+                "eth_getBlockByNumber" => {
+                    let number = number(&params[0]);
+                    json!(Eth1Block {
+                        hash: B256::with_last_byte(number as u8),
+                        number,
+                        timestamp: number * SECONDS_PER_ETH1_BLOCK,
+                    })
+                }
+                "eth_getLogs" => {
+                    let filter = &params[0];
+                    assert_eq!(filter["topics"], json!([DEPOSIT_EVENT_TOPIC]));
+                    let logs: Vec<_> = (number(&filter["fromBlock"])
+                        ..=number(&filter["toBlock"]).min(20))
+                        .map(|block| deposit_event(&DepositData::default(), block, block))
+                        .collect();
+                    json!(logs)
+                }
+                method => panic!("unexpected method {method}"),
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(handler)))
+                .await
+                .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let engine = Arc::new(EngineClient::new(
+            mock_engine().await,
+            JwtSecret::new(B256::ZERO),
+        ));
+        let mut service = Eth1Service::new(engine, Address::ZERO, 0);
+        assert_eq!(service.update().await.unwrap(), 10);
+
+        let cache = service.cache();
+        let cache = cache.read().unwrap();
+        assert_eq!(cache.deposit_count(), 11);
+        let latest_block = *cache.latest_block().unwrap();
+        assert_eq!(latest_block.number, 10);
+        assert_eq!(cache.eth1_data(&latest_block).deposit_count, 11);
+    }
+}
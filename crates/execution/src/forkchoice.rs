@@ -0,0 +1,307 @@
+use std::sync::{Arc, RwLock};
+
+use alloy_primitives::B256;
+use ream_consensus::checkpoint::Checkpoint;
+use ream_fork_choice::proto_array_fork_choice::ProtoArrayForkChoice;
+use tracing::{debug, warn};
+
+use crate::{
+    client::EngineClient,
+    error::ExecutionError,
+    types::{ForkchoiceStateV1, PayloadStatusKind, PayloadStatusV1},
+};
+
+/// What became of the head after the execution layer was told about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkchoiceOutcome {
+    /// Same forkchoice state as last time, nothing was sent.
+    Unchanged,
+    /// The execution layer follows the head and its payload was marked valid.
+    Valid,
+    /// The execution layer is syncing, the head stays optimistic.
+    Optimistic,
+    /// The head payload was invalid and marked so with its descendants, the head has to be found
+    /// again and sent anew.
+    Invalidated,
+    /// The execution layer is unreachable, the update is sent by [`ForkchoiceUpdater::flush`]
+    /// once it's back unless a newer one replaces it.
+    Queued,
+}
+
+/// Keeps the execution layer's head, safe and finalized blocks in line with fork choice.
+///
+/// Call [`ForkchoiceUpdater::update`] after every head computation, it only talks to the
+/// execution layer when the head, justified or finalized block changed, and
+/// [`ForkchoiceUpdater::flush`] when the execution layer comes back online.
+#[derive(Debug)]
+pub struct ForkchoiceUpdater {
+    engine: Arc<EngineClient>,
+    fork_choice: Arc<RwLock<ProtoArrayForkChoice>>,
+    last_sent: Option<ForkchoiceStateV1>,
+    /// Latest update the execution layer was unreachable for, with its head root.
+    pending: Option<(B256, ForkchoiceStateV1)>,
+}
+
+impl ForkchoiceUpdater {
+    pub fn new(engine: Arc<EngineClient>, fork_choice: Arc<RwLock<ProtoArrayForkChoice>>) -> Self {
+        Self {
+            engine,
+            fork_choice,
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// Sends `engine_forkchoiceUpdatedV3` with the payloads of `head_root` and of the justified
+    /// and finalized checkpoint blocks, the justified one serving as the safe block.
+    pub async fn update(
+        &mut self,
+        head_root: B256,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+    ) -> Result<ForkchoiceOutcome, ExecutionError> {
+        let forkchoice_state = {
+            let fork_choice = self.fork_choice.read().expect("fork choice lock poisoned");
+            let block_hash = |root: &B256| {
+                fork_choice
+                    .execution_status(root)
+                    .map(|status| status.block_hash())
+                    .unwrap_or_default()
+            };
+            ForkchoiceStateV1 {
+                head_block_hash: block_hash(&head_root),
+                safe_block_hash: block_hash(&justified_checkpoint.root),
+                finalized_block_hash: block_hash(&finalized_checkpoint.root),
+            }
+        };
+        // Before the merge there is nothing to tell the execution layer.
+        if forkchoice_state.head_block_hash == B256::ZERO
+            || self.last_sent == Some(forkchoice_state)
+        {
+            return Ok(ForkchoiceOutcome::Unchanged);
+        }
+        self.send(head_root, forkchoice_state).await
+    }
+
+    /// Sends the update queued while the execution layer was unreachable, if any.
+    pub async fn flush(&mut self) -> Result<ForkchoiceOutcome, ExecutionError> {
+        match self.pending.take() {
+            Some((head_root, forkchoice_state)) => self.send(head_root, forkchoice_state).await,
+            None => Ok(ForkchoiceOutcome::Unchanged),
+        }
+    }
+
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    async fn send(
+        &mut self,
+        head_root: B256,
+        forkchoice_state: ForkchoiceStateV1,
+    ) -> Result<ForkchoiceOutcome, ExecutionError> {
+        let response = match self
+            .engine
+            .forkchoice_updated_v3(forkchoice_state, None)
+            .await
+        {
+            Ok(response) => response,
+            Err(err) if err.is_connection_error() => {
+                debug!("Execution layer unreachable, queueing the update to head {head_root}");
+                self.pending = Some((head_root, forkchoice_state));
+                return Ok(ForkchoiceOutcome::Queued);
+            }
+            Err(err) => return Err(err),
+        };
+        self.pending = None;
+        let outcome = self.process_payload_status(head_root, &response.payload_status)?;
+        if outcome != ForkchoiceOutcome::Invalidated {
+            self.last_sent = Some(forkchoice_state);
+        }
+        Ok(outcome)
+    }
+
+    /// Applies the verdict of the execution layer on the payload of `root` to fork choice.
+    pub fn process_payload_status(
+        &self,
+        root: B256,
+        status: &PayloadStatusV1,
+    ) -> Result<ForkchoiceOutcome, ExecutionError> {
+        let mut fork_choice = self.fork_choice.write().expect("fork choice lock poisoned");
+        match status.status {
+            PayloadStatusKind::Valid => {
+                fork_choice.process_execution_payload_validation(&root)?;
+                Ok(ForkchoiceOutcome::Valid)
+            }
+            PayloadStatusKind::Invalid | PayloadStatusKind::InvalidBlockHash => {
+                warn!(
+                    "Execution layer rejected the payload of block {root}: {}",
+                    status
+                        .validation_error
+                        .as_deref()
+                        .unwrap_or("no reason given")
+                );
+                // An invalid block hash says nothing about the ancestors.
+                let latest_valid_hash = match status.status {
+                    PayloadStatusKind::Invalid => status.latest_valid_hash,
+                    _ => None,
+                };
+                fork_choice.process_execution_payload_invalidation(&root, latest_valid_hash)?;
+                Ok(ForkchoiceOutcome::Invalidated)
+            }
+            PayloadStatusKind::Syncing | PayloadStatusKind::Accepted => {
+                debug!("Execution layer is syncing, block {root} stays optimistic");
+                Ok(ForkchoiceOutcome::Optimistic)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+    use ream_fork_choice::proto_array::{Block, ExecutionStatus};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::auth::JwtSecret;
+
+    fn block(slot: u64, tag: u8, parent: u8) -> Block {
+        Block {
+            slot,
+            root: B256::with_last_byte(tag),
+            parent_root: B256::with_last_byte(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Optimistic(B256::repeat_byte(tag)),
+        }
+    }
+
+    /// An execution layer that finds the payload with hash `0x03..` invalid, with `0x02..` as
+    /// the latest valid one.
+    async fn mock_engine() -> Url {
+        let handler = |Json(request): Json<Value>| async move {
+            let head = request["params"][0]["headBlockHash"].clone();
+            let status = if head == json!(B256::repeat_byte(3)) {
+                json!({
+                    "status": "INVALID",
+                    "latestValidHash": B256::repeat_byte(2),
+                    "validationError": "bad state root"
+                })
+            } else {
+                json!({ "status": "VALID", "latestValidHash": head, "validationError": null })
+            };
+            Json(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "payloadStatus": status, "payloadId": null }
+            }))
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(handler)))
+                .await
+                .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_forkchoice_updates() {
+        // 1 <- 2 <- 3 and 1 <- 4.
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        for block in [block(1, 2, 1), block(2, 3, 2), block(1, 4, 1)] {
+            fork_choice.process_block(block).unwrap();
+        }
+        let fork_choice = Arc::new(RwLock::new(fork_choice));
+        let engine = Arc::new(EngineClient::new(
+            mock_engine().await,
+            JwtSecret::new(B256::ZERO),
+        ));
+        let mut updater = ForkchoiceUpdater::new(engine, fork_choice.clone());
+        let genesis = Checkpoint {
+            epoch: 0,
+            root: B256::with_last_byte(1),
+        };
+        let status = |tag| {
+            fork_choice
+                .read()
+                .unwrap()
+                .execution_status(&B256::with_last_byte(tag))
+                .unwrap()
+        };
+
+        assert_eq!(
+            updater
+                .update(B256::with_last_byte(2), genesis, genesis)
+                .await
+                .unwrap(),
+            ForkchoiceOutcome::Valid
+        );
+        assert_eq!(status(2), ExecutionStatus::Valid(B256::repeat_byte(2)));
+        assert_eq!(status(1), ExecutionStatus::Valid(B256::repeat_byte(1)));
+        assert_eq!(
+            updater
+                .update(B256::with_last_byte(2), genesis, genesis)
+                .await
+                .unwrap(),
+            ForkchoiceOutcome::Unchanged
+        );
+
+        assert_eq!(
+            updater
+                .update(B256::with_last_byte(3), genesis, genesis)
+                .await
+                .unwrap(),
+            ForkchoiceOutcome::Invalidated
+        );
+        assert!(status(3).is_invalid());
+        assert_eq!(status(2), ExecutionStatus::Valid(B256::repeat_byte(2)));
+        assert_eq!(
+            fork_choice
+                .write()
+                .unwrap()
+                .find_head(genesis, genesis, &[], B256::ZERO)
+                .unwrap(),
+            B256::with_last_byte(4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queued_update() {
+        // Nothing listens on a port that was just released.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        let engine = Arc::new(EngineClient::new(
+            endpoint.parse().unwrap(),
+            JwtSecret::new(B256::ZERO),
+        ));
+        let mut updater = ForkchoiceUpdater::new(engine, Arc::new(RwLock::new(fork_choice)));
+        let genesis = Checkpoint {
+            epoch: 0,
+            root: B256::with_last_byte(1),
+        };
+
+        assert_eq!(
+            updater
+                .update(B256::with_last_byte(1), genesis, genesis)
+                .await
+                .unwrap(),
+            ForkchoiceOutcome::Queued
+        );
+        assert!(updater.has_pending());
+        // Still unreachable, the update stays queued.
+        assert_eq!(updater.flush().await.unwrap(), ForkchoiceOutcome::Queued);
+        assert!(updater.has_pending());
+    }
+}
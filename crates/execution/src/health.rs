@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+/// How often a reachable execution layer is checked on.
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(12);
+pub const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+pub const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(32);
+
+/// Whether the execution layer answered the last request made to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineState {
+    Online,
+    /// Unreachable, or not contacted yet.
+    Offline,
+}
+
+/// Delays between attempts to reach an unreachable execution layer, doubling from `min` up to
+/// `max` so a node whose execution layer is down for long doesn't keep hammering it.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            next: min,
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.next = self.min;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(MIN_RECONNECT_DELAY, MAX_RECONNECT_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<_> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod auth;
+pub mod capabilities;
+pub mod client;
+pub mod error;
+pub mod eth1;
+pub mod forkchoice;
+pub mod health;
+pub mod new_payload;
+pub mod payload_builder;
+pub mod types;
@@ -0,0 +1,270 @@
+use std::sync::{Arc, RwLock};
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    beacon_block::BeaconBlock, blob_sidecar::kzg_commitment_to_versioned_hash,
+    execution_payload::ExecutionPayload,
+};
+use ream_fork_choice::{
+    proto_array::ExecutionStatus, proto_array_fork_choice::ProtoArrayForkChoice,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    client::EngineClient,
+    error::ExecutionError,
+    types::{PayloadStatusKind, PayloadStatusV1},
+};
+
+/// Everything `engine_newPayloadV3` needs to validate the payload of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPayloadRequest {
+    pub execution_payload: ExecutionPayload,
+    /// Versioned hashes of the blob KZG commitments of the block, in order.
+    pub versioned_hashes: Vec<B256>,
+    pub parent_beacon_block_root: B256,
+}
+
+impl From<&BeaconBlock> for NewPayloadRequest {
+    fn from(block: &BeaconBlock) -> Self {
+        Self {
+            execution_payload: block.body.execution_payload.clone(),
+            versioned_hashes: block
+                .body
+                .blob_kzg_commitments
+                .iter()
+                .map(kzg_commitment_to_versioned_hash)
+                .collect(),
+            parent_beacon_block_root: block.parent_root,
+        }
+    }
+}
+
+/// Has the execution layer validate the payloads of blocks before they are imported.
+#[derive(Debug)]
+pub struct PayloadVerifier {
+    engine: Arc<EngineClient>,
+    fork_choice: Arc<RwLock<ProtoArrayForkChoice>>,
+}
+
+impl PayloadVerifier {
+    pub fn new(engine: Arc<EngineClient>, fork_choice: Arc<RwLock<ProtoArrayForkChoice>>) -> Self {
+        Self {
+            engine,
+            fork_choice,
+        }
+    }
+
+    /// Sends the payload of `block` to `engine_newPayloadV3` and returns the status to import
+    /// the block into fork choice with.
+    ///
+    /// A payload the execution layer rejects fails with [`ExecutionError::InvalidPayload`], the
+    /// block must not be imported. Ancestors the verdict also covers are marked in fork choice.
+    /// Without a reachable execution layer the block is imported optimistically.
+    pub async fn verify(&self, block: &BeaconBlock) -> Result<ExecutionStatus, ExecutionError> {
+        let block_hash = block.body.execution_payload.block_hash;
+        // Before the merge there is nothing to verify.
+        if block_hash == B256::ZERO {
+            return Ok(ExecutionStatus::Irrelevant(false));
+        }
+        if self
+            .fork_choice
+            .read()
+            .expect("fork choice lock poisoned")
+            .execution_status(&block.parent_root)
+            .is_some_and(|status| status.is_invalid())
+        {
+            return Err(ExecutionError::InvalidPayload {
+                block_hash,
+                reason: "parent block has an invalid payload".to_string(),
+            });
+        }
+
+        let request = NewPayloadRequest::from(block);
+        let status = match self
+            .engine
+            .new_payload_v3(
+                &request.execution_payload,
+                request.versioned_hashes,
+                request.parent_beacon_block_root,
+            )
+            .await
+        {
+            Ok(status) => status,
+            // Blocks keep being imported while the execution layer is away, to be verified
+            // through the forkchoice updates once it's back.
+            Err(err) if err.is_connection_error() => {
+                debug!("Execution layer unreachable, payload {block_hash} imported optimistically");
+                return Ok(ExecutionStatus::Optimistic(block_hash));
+            }
+            Err(err) => return Err(err),
+        };
+        self.process_payload_status(block, &status)
+    }
+
+    fn process_payload_status(
+        &self,
+        block: &BeaconBlock,
+        status: &PayloadStatusV1,
+    ) -> Result<ExecutionStatus, ExecutionError> {
+        let block_hash = block.body.execution_payload.block_hash;
+        let mut fork_choice = self.fork_choice.write().expect("fork choice lock poisoned");
+        let parent_status = fork_choice.execution_status(&block.parent_root);
+        match status.status {
+            PayloadStatusKind::Valid => {
+                // A valid payload has valid ancestors.
+                if parent_status.is_some() {
+                    fork_choice.process_execution_payload_validation(&block.parent_root)?;
+                }
+                Ok(ExecutionStatus::Valid(block_hash))
+            }
+            PayloadStatusKind::Syncing | PayloadStatusKind::Accepted => {
+                debug!("Execution layer is syncing, payload {block_hash} imported optimistically");
+                Ok(ExecutionStatus::Optimistic(block_hash))
+            }
+            PayloadStatusKind::Invalid | PayloadStatusKind::InvalidBlockHash => {
+                let reason = status
+                    .validation_error
+                    .clone()
+                    .unwrap_or_else(|| "no reason given".to_string());
+                warn!("Execution layer rejected payload {block_hash}: {reason}");
+                // The ancestors after the latest valid hash are invalid too, an invalid block
+                // hash says nothing about them.
+                if let (PayloadStatusKind::Invalid, Some(latest_valid_hash), Some(parent_status)) =
+                    (status.status, status.latest_valid_hash, parent_status)
+                {
+                    if parent_status.block_hash() != latest_valid_hash {
+                        fork_choice.process_execution_payload_invalidation(
+                            &block.parent_root,
+                            Some(latest_valid_hash),
+                        )?;
+                    }
+                }
+                Err(ExecutionError::InvalidPayload { block_hash, reason })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+    use ream_consensus::{beacon_block_body::KZGCommitment, checkpoint::Checkpoint};
+    use ream_fork_choice::proto_array::Block;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::auth::JwtSecret;
+
+    fn fork_choice_block(slot: u64, tag: u8, parent: u8) -> Block {
+        Block {
+            slot,
+            root: B256::with_last_byte(tag),
+            parent_root: B256::with_last_byte(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Optimistic(B256::repeat_byte(tag)),
+        }
+    }
+
+    fn beacon_block(tag: u8, parent: u8) -> BeaconBlock {
+        let mut block = BeaconBlock {
+            parent_root: B256::with_last_byte(parent),
+            ..BeaconBlock::default()
+        };
+        block.body.execution_payload.block_hash = B256::repeat_byte(tag);
+        block
+            .body
+            .blob_kzg_commitments
+            .push(KZGCommitment::repeat_byte(0xaa))
+            .unwrap();
+        block
+    }
+
+    /// An execution layer that finds the payload with hash `0x04..` invalid with `0x01..` as the
+    /// latest valid one, is syncing for `0x05..` and finds every other payload valid.
+    async fn mock_engine() -> Url {
+        let handler = |Json(request): Json<Value>| async move {
+            assert_eq!(request["method"], "engine_newPayloadV3");
+            let versioned_hash =
+                kzg_commitment_to_versioned_hash(&KZGCommitment::repeat_byte(0xaa));
+            assert_eq!(request["params"][1], json!([versioned_hash]));
+            let block_hash = request["params"][0]["blockHash"].clone();
+            let status = if block_hash == json!(B256::repeat_byte(4)) {
+                json!({
+                    "status": "INVALID",
+                    "latestValidHash": B256::repeat_byte(1),
+                    "validationError": "bad state root"
+                })
+            } else if block_hash == json!(B256::repeat_byte(5)) {
+                json!({ "status": "SYNCING", "latestValidHash": null, "validationError": null })
+            } else {
+                json!({ "status": "VALID", "latestValidHash": block_hash, "validationError": null })
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": status }))
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(handler)))
+                .await
+                .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_payloads() {
+        // 1 <- 2 <- 3, all optimistic.
+        let mut fork_choice = ProtoArrayForkChoice::new(
+            fork_choice_block(0, 1, 0),
+            Checkpoint::default(),
+            Checkpoint::default(),
+        )
+        .unwrap();
+        for block in [fork_choice_block(1, 2, 1), fork_choice_block(2, 3, 2)] {
+            fork_choice.process_block(block).unwrap();
+        }
+        let fork_choice = Arc::new(RwLock::new(fork_choice));
+        let engine = Arc::new(EngineClient::new(
+            mock_engine().await,
+            JwtSecret::new(B256::ZERO),
+        ));
+        let verifier = PayloadVerifier::new(engine, fork_choice.clone());
+        let status = |tag| {
+            fork_choice
+                .read()
+                .unwrap()
+                .execution_status(&B256::with_last_byte(tag))
+                .unwrap()
+        };
+
+        assert_eq!(
+            verifier.verify(&BeaconBlock::default()).await.unwrap(),
+            ExecutionStatus::Irrelevant(false)
+        );
+        assert_eq!(
+            verifier.verify(&beacon_block(5, 3)).await.unwrap(),
+            ExecutionStatus::Optimistic(B256::repeat_byte(5))
+        );
+        assert_eq!(
+            verifier.verify(&beacon_block(6, 1)).await.unwrap(),
+            ExecutionStatus::Valid(B256::repeat_byte(6))
+        );
+        assert!(status(2).is_optimistic());
+        assert_eq!(status(1), ExecutionStatus::Valid(B256::repeat_byte(1)));
+
+        // Everything after the latest valid hash goes down with the invalid payload.
+        let err = verifier.verify(&beacon_block(4, 3)).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::InvalidPayload { .. }));
+        assert!(status(3).is_invalid());
+        assert!(status(2).is_invalid());
+        assert_eq!(status(1), ExecutionStatus::Valid(B256::repeat_byte(1)));
+
+        // Children of invalid blocks are rejected without asking.
+        let err = verifier.verify(&beacon_block(7, 3)).await.unwrap_err();
+        assert!(matches!(err, ExecutionError::InvalidPayload { .. }));
+    }
+}
@@ -0,0 +1,225 @@
+use std::{collections::HashMap, sync::Arc};
+
+use alloy_primitives::B256;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::{
+    client::EngineClient,
+    error::ExecutionError,
+    types::{ForkchoiceStateV1, GetPayloadV3Response, PayloadAttributesV3, PayloadId},
+};
+
+/// A payload the execution layer was asked to build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PreparedPayload {
+    parent_hash: B256,
+    attributes: PayloadAttributesV3,
+    payload_id: PayloadId,
+}
+
+/// Builds the payloads of proposed blocks on the execution layer.
+///
+/// A proposer known ahead of its slot should have its payload prepared with
+/// [`PayloadBuilder::prepare_payload`], giving the execution layer the time to fill it with
+/// transactions. [`PayloadBuilder::get_payload`] fetches it at proposal time, and starts
+/// building on the spot when nothing was prepared for the same parent and attributes.
+#[derive(Debug)]
+pub struct PayloadBuilder {
+    engine: Arc<EngineClient>,
+    /// Payload being built for each upcoming slot.
+    prepared: Mutex<HashMap<u64, PreparedPayload>>,
+}
+
+impl PayloadBuilder {
+    pub fn new(engine: Arc<EngineClient>) -> Self {
+        Self {
+            engine,
+            prepared: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sends `engine_forkchoiceUpdatedV3` with `attributes` to start building the payload of
+    /// `slot` on top of the head of `forkchoice_state`, and remembers its payload id.
+    pub async fn prepare_payload(
+        &self,
+        slot: u64,
+        forkchoice_state: ForkchoiceStateV1,
+        attributes: PayloadAttributesV3,
+    ) -> Result<PayloadId, ExecutionError> {
+        let payload_id = self
+            .start_building(slot, forkchoice_state, attributes.clone())
+            .await?;
+        let mut prepared = self.prepared.lock().await;
+        // Payloads of past slots will never be fetched.
+        prepared.retain(|prepared_slot, _| *prepared_slot >= slot);
+        prepared.insert(
+            slot,
+            PreparedPayload {
+                parent_hash: forkchoice_state.head_block_hash,
+                attributes,
+                payload_id,
+            },
+        );
+        Ok(payload_id)
+    }
+
+    /// Fetches the payload of `slot` with `engine_getPayloadV3`, building it first unless it
+    /// was prepared on the same parent with the same attributes.
+    pub async fn get_payload(
+        &self,
+        slot: u64,
+        forkchoice_state: ForkchoiceStateV1,
+        attributes: PayloadAttributesV3,
+    ) -> Result<GetPayloadV3Response, ExecutionError> {
+        let prepared = self.prepared.lock().await.remove(&slot);
+        let payload_id = match prepared {
+            Some(prepared)
+                if prepared.parent_hash == forkchoice_state.head_block_hash
+                    && prepared.attributes == attributes =>
+            {
+                prepared.payload_id
+            }
+            _ => {
+                self.start_building(slot, forkchoice_state, attributes)
+                    .await?
+            }
+        };
+        self.engine.get_payload_v3(payload_id).await
+    }
+
+    async fn start_building(
+        &self,
+        slot: u64,
+        forkchoice_state: ForkchoiceStateV1,
+        attributes: PayloadAttributesV3,
+    ) -> Result<PayloadId, ExecutionError> {
+        let response = self
+            .engine
+            .forkchoice_updated_v3(forkchoice_state, Some(attributes))
+            .await?;
+        let payload_id = response.payload_id.ok_or_else(|| {
+            ExecutionError::InvalidResponse(format!(
+                "no payload id for slot {slot}, payload status {:?}",
+                response.payload_status.status
+            ))
+        })?;
+        debug!("Building payload {payload_id} for slot {slot}");
+        Ok(payload_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use alloy_primitives::Address;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::{auth::JwtSecret, types::ExecutionPayloadV3};
+
+    /// An execution layer handing out a new payload id for every forkchoice update with
+    /// attributes, counting them, and returning payloads whose block number is their id.
+    async fn mock_engine(updates: Arc<AtomicU64>) -> Url {
+        async fn handler(
+            State(updates): State<Arc<AtomicU64>>,
+            Json(request): Json<Value>,
+        ) -> Json<Value> {
+            let result = match request["method"].as_str().unwrap() {
+                "engine_forkchoiceUpdatedV3" => {
+                    let id = updates.fetch_add(1, Ordering::Relaxed) + 1;
+                    json!({
+                        "payloadStatus": {
+                            "status": "VALID",
+                            "latestValidHash": request["params"][0]["headBlockHash"],
+                            "validationError": null
+                        },
+                        "payloadId": PayloadId::from(id.to_be_bytes())
+                    })
+                }
+                "engine_getPayloadV3" => {
+                    let id = request["params"][0].as_str().unwrap();
+                    let payload = ExecutionPayloadV3 {
+                        block_number: u64::from_str_radix(&id[2..], 16).unwrap(),
+                        ..ExecutionPayloadV3::default()
+                    };
+                    json!({
+                        "executionPayload": payload,
+                        "blockValue": "0x1",
+                        "blobsBundle": { "commitments": [], "proofs": [], "blobs": [] },
+                        "shouldOverrideBuilder": false
+                    })
+                }
+                method => panic!("unexpected method {method}"),
+            };
+            Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                Router::new().route("/", post(handler)).with_state(updates),
+            )
+            .await
+            .unwrap()
+        });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    fn attributes(timestamp: u64) -> PayloadAttributesV3 {
+        PayloadAttributesV3 {
+            timestamp,
+            prev_randao: B256::repeat_byte(1),
+            suggested_fee_recipient: Address::repeat_byte(2),
+            withdrawals: vec![],
+            parent_beacon_block_root: B256::repeat_byte(3),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_payload_building() {
+        let updates = Arc::new(AtomicU64::new(0));
+        let engine = EngineClient::new(
+            mock_engine(updates.clone()).await,
+            JwtSecret::new(B256::ZERO),
+        );
+        let builder = PayloadBuilder::new(Arc::new(engine));
+        let forkchoice_state = ForkchoiceStateV1 {
+            head_block_hash: B256::repeat_byte(4),
+            ..ForkchoiceStateV1::default()
+        };
+
+        // A prepared payload is fetched without another forkchoice update.
+        let payload_id = builder
+            .prepare_payload(1, forkchoice_state, attributes(12))
+            .await
+            .unwrap();
+        let response = builder
+            .get_payload(1, forkchoice_state, attributes(12))
+            .await
+            .unwrap();
+        assert_eq!(
+            PayloadId::from(response.execution_payload.block_number.to_be_bytes()),
+            payload_id
+        );
+        assert_eq!(updates.load(Ordering::Relaxed), 1);
+
+        // Different attributes than prepared start a new payload.
+        builder
+            .prepare_payload(2, forkchoice_state, attributes(24))
+            .await
+            .unwrap();
+        let response = builder
+            .get_payload(2, forkchoice_state, attributes(25))
+            .await
+            .unwrap();
+        assert_eq!(response.execution_payload.block_number, 3);
+        assert_eq!(updates.load(Ordering::Relaxed), 3);
+    }
+}
@@ -0,0 +1,302 @@
+//! Engine API objects in the JSON form the execution layer speaks, with conversions from and
+//! to their consensus counterparts.
+
+use alloy_primitives::{Address, Bytes, B256, B64, U256};
+use ream_consensus::{
+    beacon_block_body::KZGCommitment,
+    blob_sidecar::{Blob, KZGProof},
+    execution_payload::{ExecutionPayload, Transaction},
+    withdrawal::Withdrawal,
+};
+use serde::{Deserialize, Serialize};
+use ssz_types::{
+    typenum::{self, U1048576},
+    FixedVector, VariableList,
+};
+
+/// Identifies a payload being built, returned by `engine_forkchoiceUpdated` and passed to
+/// `engine_getPayload`.
+pub type PayloadId = B64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadStatusKind {
+    Valid,
+    Invalid,
+    Syncing,
+    Accepted,
+    InvalidBlockHash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadStatusV1 {
+    pub status: PayloadStatusKind,
+    /// The most recent valid ancestor of an invalid payload, or the payload itself when valid.
+    pub latest_valid_hash: Option<B256>,
+    pub validation_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceStateV1 {
+    pub head_block_hash: B256,
+    pub safe_block_hash: B256,
+    pub finalized_block_hash: B256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributesV3 {
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub timestamp: u64,
+    pub prev_randao: B256,
+    pub suggested_fee_recipient: Address,
+    pub withdrawals: Vec<WithdrawalV1>,
+    pub parent_beacon_block_root: B256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceUpdatedResponse {
+    pub payload_status: PayloadStatusV1,
+    pub payload_id: Option<PayloadId>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalV1 {
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub index: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub validator_index: u64,
+    pub address: Address,
+    /// In Gwei, like the consensus withdrawal.
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub amount: u64,
+}
+
+impl From<&Withdrawal> for WithdrawalV1 {
+    fn from(withdrawal: &Withdrawal) -> Self {
+        Self {
+            index: withdrawal.index,
+            validator_index: withdrawal.validator_index,
+            address: withdrawal.address,
+            amount: withdrawal.amount,
+        }
+    }
+}
+
+impl From<&WithdrawalV1> for Withdrawal {
+    fn from(withdrawal: &WithdrawalV1) -> Self {
+        Self {
+            index: withdrawal.index,
+            validator_index: withdrawal.validator_index,
+            address: withdrawal.address,
+            amount: withdrawal.amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadV3 {
+    pub parent_hash: B256,
+    pub fee_recipient: Address,
+    pub state_root: B256,
+    pub receipts_root: B256,
+    #[serde(with = "ssz_types::serde_utils::hex_fixed_vec")]
+    pub logs_bloom: FixedVector<u8, typenum::U256>,
+    pub prev_randao: B256,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub block_number: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub gas_limit: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub gas_used: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub timestamp: u64,
+    #[serde(with = "ssz_types::serde_utils::hex_var_list")]
+    pub extra_data: VariableList<u8, typenum::U32>,
+    pub base_fee_per_gas: U256,
+    pub block_hash: B256,
+    #[serde(with = "ssz_types::serde_utils::list_of_hex_var_list")]
+    pub transactions: VariableList<Transaction, U1048576>,
+    pub withdrawals: VariableList<WithdrawalV1, typenum::U16>,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub blob_gas_used: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub excess_blob_gas: u64,
+}
+
+impl From<&ExecutionPayload> for ExecutionPayloadV3 {
+    fn from(payload: &ExecutionPayload) -> Self {
+        Self {
+            parent_hash: payload.parent_hash,
+            fee_recipient: payload.fee_recipient,
+            state_root: payload.state_root,
+            receipts_root: payload.receipts_root,
+            logs_bloom: payload.logs_bloom.clone(),
+            prev_randao: payload.prev_randao,
+            block_number: payload.block_number,
+            gas_limit: payload.gas_limit,
+            gas_used: payload.gas_used,
+            timestamp: payload.timestamp,
+            extra_data: payload.extra_data.clone(),
+            base_fee_per_gas: payload.base_fee_per_gas,
+            block_hash: payload.block_hash,
+            transactions: payload.transactions.clone(),
+            withdrawals: payload
+                .withdrawals
+                .iter()
+                .map(WithdrawalV1::from)
+                .collect::<Vec<_>>()
+                .into(),
+            blob_gas_used: payload.blob_gas_used,
+            excess_blob_gas: payload.excess_blob_gas,
+        }
+    }
+}
+
+impl From<ExecutionPayloadV3> for ExecutionPayload {
+    fn from(payload: ExecutionPayloadV3) -> Self {
+        Self {
+            parent_hash: payload.parent_hash,
+            fee_recipient: payload.fee_recipient,
+            state_root: payload.state_root,
+            receipts_root: payload.receipts_root,
+            logs_bloom: payload.logs_bloom,
+            prev_randao: payload.prev_randao,
+            block_number: payload.block_number,
+            gas_limit: payload.gas_limit,
+            gas_used: payload.gas_used,
+            timestamp: payload.timestamp,
+            extra_data: payload.extra_data,
+            base_fee_per_gas: payload.base_fee_per_gas,
+            block_hash: payload.block_hash,
+            transactions: payload.transactions,
+            withdrawals: payload
+                .withdrawals
+                .iter()
+                .map(Withdrawal::from)
+                .collect::<Vec<_>>()
+                .into(),
+            blob_gas_used: payload.blob_gas_used,
+            excess_blob_gas: payload.excess_blob_gas,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HexBlob(#[serde(with = "ssz_types::serde_utils::hex_fixed_vec")] pub Blob);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobsBundleV1 {
+    pub commitments: Vec<KZGCommitment>,
+    pub proofs: Vec<KZGProof>,
+    pub blobs: Vec<HexBlob>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPayloadV3Response {
+    pub execution_payload: ExecutionPayloadV3,
+    /// Fees paid to the fee recipient, in wei.
+    pub block_value: U256,
+    pub blobs_bundle: BlobsBundleV1,
+    pub should_override_builder: bool,
+}
+
+/// Header fields of an execution block, as returned by `eth_getBlockByNumber`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eth1Block {
+    pub hash: B256,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub number: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub timestamp: u64,
+}
+
+/// Filter of `eth_getLogs`, both block bounds inclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    pub address: Address,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub from_block: u64,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub to_block: u64,
+    pub topics: Vec<B256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    #[serde(with = "serde_utils::u64_hex_be")]
+    pub block_number: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_payload_status() {
+        let status: PayloadStatusV1 = serde_json::from_value(json!({
+            "status": "INVALID_BLOCK_HASH",
+            "latestValidHash": null,
+            "validationError": "invalid block hash"
+        }))
+        .unwrap();
+        assert_eq!(status.status, PayloadStatusKind::InvalidBlockHash);
+        assert_eq!(status.latest_valid_hash, None);
+
+        let response: ForkchoiceUpdatedResponse = serde_json::from_value(json!({
+            "payloadStatus": {
+                "status": "VALID",
+                "latestValidHash": B256::repeat_byte(1),
+                "validationError": null
+            },
+            "payloadId": "0x0000000021f32cc1"
+        }))
+        .unwrap();
+        assert_eq!(response.payload_status.status, PayloadStatusKind::Valid);
+        assert_eq!(
+            response.payload_id,
+            Some(PayloadId::from(0x21f32cc1_u64.to_be_bytes()))
+        );
+    }
+
+    #[test]
+    fn test_execution_payload_round_trip() {
+        let payload = ExecutionPayload {
+            block_number: 16,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: U256::from(7),
+            transactions: VariableList::from(vec![Transaction::from(vec![0x02, 0xf8])]),
+            withdrawals: vec![Withdrawal {
+                index: 1,
+                validator_index: 2,
+                address: Address::repeat_byte(3),
+                amount: 32_000_000_000,
+            }]
+            .into(),
+            ..ExecutionPayload::default()
+        };
+        let json = serde_json::to_value(ExecutionPayloadV3::from(&payload)).unwrap();
+        assert_eq!(json["blockNumber"], "0x10");
+        assert_eq!(json["baseFeePerGas"], "0x7");
+        assert_eq!(json["transactions"], json!(["0x02f8"]));
+        assert_eq!(json["withdrawals"][0]["amount"], "0x773594000");
+        assert_eq!(json["blobGasUsed"], "0x0");
+
+        let decoded: ExecutionPayloadV3 = serde_json::from_value(json).unwrap();
+        assert_eq!(ExecutionPayload::from(decoded), payload);
+    }
+}
@@ -0,0 +1,854 @@
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display},
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Once,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::FutureExt;
+use ream_metrics::{dec_gauge_vec, inc_counter_vec, inc_gauge_vec};
+use tokio::{
+    runtime::Handle,
+    task::{AbortHandle, JoinError, JoinHandle},
+};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{error, warn};
+
+use crate::metrics;
+
+/// Panics kept for [`ReamExecutor::panics`], older ones are only in the logs.
+const MAX_PANICS: usize = 32;
+
+thread_local! {
+    /// Backtrace of the last panic on this thread, taken by the task that catches it.
+    static PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Chains a panic hook keeping the backtrace, which the caught payload lacks.
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            PANIC_BACKTRACE
+                .with(|backtrace| *backtrace.borrow_mut() = Some(Backtrace::force_capture()));
+            previous(info);
+        }));
+    });
+}
+
+/// What became of the tasks when the executor shut down.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Names of the tasks still running when the timeout passed, they were aborted.
+    pub timed_out: Vec<&'static str>,
+}
+
+/// A task spawned with [`ReamExecutor::spawn_cancellable`], resolving to `None` once cancelled.
+pub struct TaskHandle<T> {
+    join: JoinHandle<Option<T>>,
+    cancel: CancellationToken,
+}
+
+impl<T> TaskHandle<T> {
+    /// Stops this task alone, it's dropped at its next await point.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.join.is_finished()
+    }
+}
+
+impl<T> Future for TaskHandle<T> {
+    type Output = Result<Option<T>, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.join).poll(cx)
+    }
+}
+
+/// A panic caught in a task, see [`ReamExecutor::panics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPanic {
+    pub task: &'static str,
+    pub message: String,
+    pub backtrace: String,
+    pub at: Instant,
+}
+
+/// How [`ReamExecutor::spawn_supervised`] restarts a task that panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Restarts before the task is given up on.
+    pub max_restarts: usize,
+    pub backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why a critical task brought the node down, see [`ReamExecutor::spawn_critical`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskFailure {
+    pub task: &'static str,
+    pub reason: String,
+}
+
+impl Display for TaskFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.task, self.reason)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    /// Shutdown started and the task hasn't returned yet.
+    Stopping,
+}
+
+/// A running task, see [`ReamExecutor::tasks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub id: u64,
+    pub name: &'static str,
+    pub spawned_at: Instant,
+    pub state: TaskState,
+}
+
+struct TaskEntry {
+    name: &'static str,
+    spawned_at: Instant,
+    abort: AbortHandle,
+}
+
+struct Inner {
+    handle: Handle,
+    blocking: Handle,
+    duties: Handle,
+    shutdown: CancellationToken,
+    tracker: TaskTracker,
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<u64, TaskEntry>>,
+    failure: Mutex<Option<TaskFailure>>,
+    panics: Mutex<VecDeque<TaskPanic>>,
+}
+
+/// Removes its task from the registry when the task returns or is aborted.
+struct Registration {
+    inner: Arc<Inner>,
+    id: u64,
+    name: &'static str,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.inner
+            .tasks
+            .lock()
+            .expect("tasks lock poisoned")
+            .remove(&self.id);
+        dec_gauge_vec(&metrics::TASKS, &[self.name]);
+    }
+}
+
+/// Spawns the node's tasks on a tokio runtime and stops them together.
+///
+/// Every task has a name, the running ones are listed by [`ReamExecutor::tasks`] and counted
+/// by name in the `ream_executor_tasks` metric so that a wedged subsystem can be told apart.
+///
+/// Tasks spawned with [`ReamExecutor::spawn`] are dropped when shutdown starts, those spawned
+/// with [`ReamExecutor::spawn_graceful`] are told to stop and given the time to clean up.
+/// Those spawned with [`ReamExecutor::spawn_critical`] start the shutdown themselves when they
+/// fail, the node is of no use without them.
+///
+/// Blocking work and slot-critical duties can be given runtimes of their own, see
+/// [`crate::runtimes::Runtimes`].
+///
+/// A panic in any task is caught, logged with the task name and backtrace, counted in the
+/// `ream_executor_task_panics_total` metric and kept for [`ReamExecutor::panics`].
+#[derive(Clone)]
+pub struct ReamExecutor {
+    inner: Arc<Inner>,
+}
+
+impl ReamExecutor {
+    /// An executor running every task on the runtime of `handle`.
+    pub fn new(handle: Handle) -> Self {
+        Self::with_pools(handle.clone(), handle.clone(), handle)
+    }
+
+    /// An executor running tasks on `handle`, blocking work on the blocking pool of the
+    /// runtime of `blocking` and duties on `duties`, see [`crate::runtimes::Runtimes`].
+    pub fn with_pools(handle: Handle, blocking: Handle, duties: Handle) -> Self {
+        install_panic_hook();
+        Self {
+            inner: Arc::new(Inner {
+                handle,
+                blocking,
+                duties,
+                shutdown: CancellationToken::new(),
+                tracker: TaskTracker::new(),
+                next_id: AtomicU64::new(0),
+                tasks: Mutex::new(HashMap::new()),
+                failure: Mutex::new(None),
+                panics: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.inner.handle
+    }
+
+    /// Cancelled when shutdown starts.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.inner.shutdown.clone()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutdown.is_cancelled()
+    }
+
+    /// Spawns `future`, dropped when shutdown starts. Its handle resolves to `None` then.
+    pub fn spawn<F>(&self, name: &'static str, future: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let shutdown = self.inner.shutdown.clone();
+        self.spawn_graceful(name, |_| async move {
+            shutdown.run_until_cancelled(future).await
+        })
+    }
+
+    /// Spawns `future` to start at `at`, dropped when shutdown starts like
+    /// [`ReamExecutor::spawn`], even if it didn't start yet.
+    pub fn spawn_at<F>(
+        &self,
+        name: &'static str,
+        at: Instant,
+        future: F,
+    ) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn(name, async move {
+            tokio::time::sleep_until(at.into()).await;
+            future.await
+        })
+    }
+
+    /// Runs the future `task` builds every `period` from now on, see
+    /// [`ReamExecutor::spawn_interval_at`].
+    pub fn spawn_interval<F>(
+        &self,
+        name: &'static str,
+        period: Duration,
+        task: impl FnMut() -> F + Send + 'static,
+    ) -> JoinHandle<Option<()>>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_interval_at(name, Instant::now(), period, task)
+    }
+
+    /// Runs the future `task` builds at `start` and every `period` after it until shutdown.
+    ///
+    /// Runs start on the grid of `start` however long the previous ones took, e.g. on slot
+    /// boundaries, one after the other. Those that would have started while a run overran are
+    /// skipped rather than caught up with, and a panicking run is reported like a task panic
+    /// without stopping the next ones. Panics if `period` is zero.
+    pub fn spawn_interval_at<F>(
+        &self,
+        name: &'static str,
+        start: Instant,
+        period: Duration,
+        mut task: impl FnMut() -> F + Send + 'static,
+    ) -> JoinHandle<Option<()>>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        assert!(!period.is_zero(), "interval of task {name} is zero");
+        let executor = self.clone();
+        self.spawn(name, async move {
+            let mut next = start;
+            loop {
+                tokio::time::sleep_until(next.into()).await;
+                let _ = executor.catch_panic(name, task()).await;
+                // Computed from `start` rather than the last run, which would add up the delays.
+                let elapsed = Instant::now().saturating_duration_since(start);
+                let ticks = elapsed.as_nanos() / period.as_nanos() + 1;
+                next = start + Duration::from_nanos((ticks * period.as_nanos()) as u64);
+            }
+        })
+    }
+
+    /// Spawns `future` like [`ReamExecutor::spawn`], with a handle that can also cancel it alone.
+    pub fn spawn_cancellable<F>(&self, name: &'static str, future: F) -> TaskHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let cancel = self.inner.shutdown.child_token();
+        let token = cancel.clone();
+        let join = self.spawn_graceful(
+            name,
+            |_| async move { token.run_until_cancelled(future).await },
+        );
+        TaskHandle { join, cancel }
+    }
+
+    /// Spawns the future `task` builds from the shutdown token, which is expected to clean up
+    /// and return once the token is cancelled. [`ReamExecutor::shutdown`] waits for it.
+    pub fn spawn_graceful<F>(
+        &self,
+        name: &'static str,
+        task: impl FnOnce(CancellationToken) -> F,
+    ) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let future = task(self.inner.shutdown.clone());
+        self.spawn_reported(name, &self.inner.handle, future)
+    }
+
+    /// Spawns `future` on the runtime kept for slot-critical duties, dropped when shutdown
+    /// starts like [`ReamExecutor::spawn`].
+    pub fn spawn_duty<F>(&self, name: &'static str, future: F) -> JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let shutdown = self.inner.shutdown.clone();
+        self.spawn_reported(name, &self.inner.duties, async move {
+            shutdown.run_until_cancelled(future).await
+        })
+    }
+
+    /// Runs `task` on the blocking pool, for CPU heavy work like state transitions and BLS
+    /// verification. It can't be stopped, [`ReamExecutor::shutdown`] waits for it.
+    pub fn spawn_blocking<T>(
+        &self,
+        name: &'static str,
+        task: impl FnOnce() -> T + Send + 'static,
+    ) -> JoinHandle<T>
+    where
+        T: Send + 'static,
+    {
+        let executor = self.clone();
+        self.register(name, |registration| {
+            self.inner.tracker.spawn_blocking_on(
+                move || {
+                    let _registration = registration;
+                    match panic::catch_unwind(AssertUnwindSafe(task)) {
+                        Ok(output) => output,
+                        Err(panic) => {
+                            let panic = executor.record_panic(name, &*panic);
+                            panic::resume_unwind(Box::new(panic.message))
+                        }
+                    }
+                },
+                &self.inner.blocking,
+            )
+        })
+    }
+
+    /// Spawns `future` on `handle`, reporting its panics.
+    fn spawn_reported<F>(
+        &self,
+        name: &'static str,
+        handle: &Handle,
+        future: F,
+    ) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let executor = self.clone();
+        self.spawn_task(name, handle, async move {
+            match executor.catch_panic(name, future).await {
+                Ok(output) => output,
+                // Handed on so that the join handle still reports the panic, without running
+                // the panic hook again.
+                Err(panic) => panic::resume_unwind(Box::new(panic.message)),
+            }
+        })
+    }
+
+    fn spawn_task<F>(&self, name: &'static str, handle: &Handle, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.register(name, |registration| {
+            self.inner.tracker.spawn_on(
+                async move {
+                    let _registration = registration;
+                    future.await
+                },
+                handle,
+            )
+        })
+    }
+
+    /// Lists the task `spawn` starts in the registry until it returns or is aborted.
+    fn register<T>(
+        &self,
+        name: &'static str,
+        spawn: impl FnOnce(Registration) -> JoinHandle<T>,
+    ) -> JoinHandle<T> {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let registration = Registration {
+            inner: self.inner.clone(),
+            id,
+            name,
+        };
+        inc_gauge_vec(&metrics::TASKS, &[name]);
+
+        // Registered before the task can run and deregister itself.
+        let mut tasks = self.inner.tasks.lock().expect("tasks lock poisoned");
+        let handle = spawn(registration);
+        tasks.insert(
+            id,
+            TaskEntry {
+                name,
+                spawned_at: Instant::now(),
+                abort: handle.abort_handle(),
+            },
+        );
+        handle
+    }
+
+    /// Spawns a task the node can't run without, like [`ReamExecutor::spawn_graceful`]. If it
+    /// returns an error, panics or returns before shutdown started, the failure is recorded
+    /// and the shutdown token cancelled.
+    pub fn spawn_critical<F, E>(
+        &self,
+        name: &'static str,
+        task: impl FnOnce(CancellationToken) -> F,
+    ) -> JoinHandle<()>
+    where
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        let shutdown = self.inner.shutdown.clone();
+        let future = task(shutdown.clone());
+        let executor = self.clone();
+        self.spawn_task(name, &self.inner.handle, async move {
+            let reason = match executor.catch_panic(name, future).await {
+                Ok(Ok(())) if shutdown.is_cancelled() => return,
+                Ok(Ok(())) => "stopped unexpectedly".to_string(),
+                Ok(Err(err)) => format!("failed: {err}"),
+                Err(panic) => format!("panicked: {}", panic.message),
+            };
+            executor.fail(TaskFailure { task: name, reason });
+        })
+    }
+
+    /// Spawns the future `task` builds, building and running it again after a panic as
+    /// `policy` allows. Dropped when shutdown starts like [`ReamExecutor::spawn`].
+    pub fn spawn_supervised<F>(
+        &self,
+        name: &'static str,
+        policy: RestartPolicy,
+        mut task: impl FnMut() -> F + Send + 'static,
+    ) -> JoinHandle<Option<()>>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let executor = self.clone();
+        self.spawn(name, async move {
+            let mut restarts = 0;
+            while executor.catch_panic(name, task()).await.is_err() {
+                if restarts == policy.max_restarts {
+                    error!("Task {name} panicked {} times, giving up", restarts + 1);
+                    return;
+                }
+                restarts += 1;
+                warn!(
+                    "Restarting task {name} in {:?} ({restarts}/{})",
+                    policy.backoff, policy.max_restarts
+                );
+                tokio::time::sleep(policy.backoff).await;
+            }
+        })
+    }
+
+    /// The latest panics caught in tasks, oldest first.
+    pub fn panics(&self) -> Vec<TaskPanic> {
+        self.inner
+            .panics
+            .lock()
+            .expect("panics lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    async fn catch_panic<F: Future>(
+        &self,
+        name: &'static str,
+        future: F,
+    ) -> Result<F::Output, TaskPanic> {
+        AssertUnwindSafe(future)
+            .catch_unwind()
+            .await
+            .map_err(|panic| self.record_panic(name, &*panic))
+    }
+
+    fn record_panic(&self, name: &'static str, panic: &(dyn Any + Send)) -> TaskPanic {
+        let panic = TaskPanic {
+            task: name,
+            message: panic_message(panic).to_string(),
+            backtrace: PANIC_BACKTRACE
+                .with(|backtrace| backtrace.borrow_mut().take())
+                .map(|backtrace| backtrace.to_string())
+                .unwrap_or_default(),
+            at: Instant::now(),
+        };
+        error!(
+            "Task {name} panicked: {}\n{}",
+            panic.message, panic.backtrace
+        );
+        inc_counter_vec(&metrics::TASK_PANICS, &[name]);
+        let mut panics = self.inner.panics.lock().expect("panics lock poisoned");
+        if panics.len() == MAX_PANICS {
+            panics.pop_front();
+        }
+        panics.push_back(panic.clone());
+        panic
+    }
+
+    /// The first critical task failure, if one brought the node down.
+    pub fn failure(&self) -> Option<TaskFailure> {
+        self.inner
+            .failure
+            .lock()
+            .expect("failure lock poisoned")
+            .clone()
+    }
+
+    fn fail(&self, failure: TaskFailure) {
+        error!("Critical task {failure}, shutting down");
+        self.inner
+            .failure
+            .lock()
+            .expect("failure lock poisoned")
+            .get_or_insert(failure);
+        self.inner.shutdown.cancel();
+    }
+
+    /// The running tasks, oldest first.
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        let state = if self.is_shutting_down() {
+            TaskState::Stopping
+        } else {
+            TaskState::Running
+        };
+        let mut tasks: Vec<TaskInfo> = self
+            .inner
+            .tasks
+            .lock()
+            .expect("tasks lock poisoned")
+            .iter()
+            .map(|(id, task)| TaskInfo {
+                id: *id,
+                name: task.name,
+                spawned_at: task.spawned_at,
+                state,
+            })
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+        tasks
+    }
+
+    /// Cancels the shutdown token and waits up to `timeout` for the tasks to return, aborting
+    /// the ones that don't.
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        self.inner.shutdown.cancel();
+        self.inner.tracker.close();
+        let _ = tokio::time::timeout(timeout, self.inner.tracker.wait()).await;
+
+        let mut report = ShutdownReport::default();
+        for task in self.tasks() {
+            if let Some(entry) = self
+                .inner
+                .tasks
+                .lock()
+                .expect("tasks lock poisoned")
+                .get(&task.id)
+            {
+                entry.abort.abort();
+                report.timed_out.push(task.name);
+            }
+        }
+        report
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let executor = ReamExecutor::new(Handle::current());
+
+        let dropped = executor.spawn("dropped", std::future::pending::<()>());
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let graceful = executor.spawn_graceful("graceful", |shutdown| {
+            let cleaned_up = cleaned_up.clone();
+            async move {
+                shutdown.cancelled().await;
+                cleaned_up.store(true, Ordering::Relaxed);
+                7
+            }
+        });
+        let stuck = executor.spawn_graceful("stuck", |_| std::future::pending::<()>());
+
+        let report = executor.shutdown(Duration::from_millis(50)).await;
+
+        assert!(executor.is_shutting_down());
+        assert_eq!(
+            report,
+            ShutdownReport {
+                timed_out: vec!["stuck"]
+            }
+        );
+        assert_eq!(dropped.await.unwrap(), None);
+        assert_eq!(graceful.await.unwrap(), 7);
+        assert!(cleaned_up.load(Ordering::Relaxed));
+        assert!(stuck.await.unwrap_err().is_cancelled());
+        assert_eq!(executor.tasks(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_task_registry() {
+        let executor = ReamExecutor::new(Handle::current());
+        let (finish, finished) = tokio::sync::oneshot::channel::<()>();
+        let short = executor.spawn("short", finished);
+        executor.spawn("long", std::future::pending::<()>());
+
+        let names: Vec<_> = executor.tasks().iter().map(|task| task.name).collect();
+        assert_eq!(names, ["short", "long"]);
+        assert!(executor
+            .tasks()
+            .iter()
+            .all(|task| task.state == TaskState::Running));
+        let running = |name| {
+            metrics::TASKS
+                .as_ref()
+                .unwrap()
+                .with_label_values(&[name])
+                .get()
+        };
+        assert_eq!(running("long"), 1);
+
+        finish.send(()).unwrap();
+        short.await.unwrap();
+        let names: Vec<_> = executor.tasks().iter().map(|task| task.name).collect();
+        assert_eq!(names, ["long"]);
+        assert_eq!(running("short"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_critical_failure() {
+        let executor = ReamExecutor::new(Handle::current());
+        let healthy = executor.spawn_critical("healthy", |shutdown| async move {
+            shutdown.cancelled().await;
+            Ok::<_, String>(())
+        });
+        executor.spawn_critical("sync", |_| async { Err("database is gone") });
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            executor.shutdown_token().cancelled(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            executor.failure(),
+            Some(TaskFailure {
+                task: "sync",
+                reason: "failed: database is gone".to_string()
+            })
+        );
+        healthy.await.unwrap();
+        // Stopping on shutdown isn't a failure, and only the first failure is kept.
+        assert_eq!(executor.failure().unwrap().task, "sync");
+    }
+
+    #[tokio::test]
+    async fn test_critical_panic() {
+        let executor = ReamExecutor::new(Handle::current());
+        executor
+            .spawn_critical("network", |_| async {
+                if true {
+                    panic!("peer table corrupted");
+                }
+                Ok::<_, String>(())
+            })
+            .await
+            .unwrap();
+
+        assert!(executor.is_shutting_down());
+        assert_eq!(
+            executor.failure().unwrap().to_string(),
+            "network panicked: peer table corrupted"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancellable() {
+        let executor = ReamExecutor::new(Handle::current());
+        let cancelled = executor.spawn_cancellable("cancelled", std::future::pending::<()>());
+        let other = executor.spawn_cancellable("other", std::future::pending::<()>());
+
+        cancelled.cancel();
+        assert_eq!(cancelled.await.unwrap(), None);
+        assert!(!executor.is_shutting_down());
+        assert!(!other.is_finished());
+
+        let report = executor.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(report, ShutdownReport::default());
+        assert_eq!(other.await.unwrap(), None);
+
+        let done = executor.spawn_cancellable("done", async { 3 });
+        assert_eq!(done.await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_panic_reporting() {
+        let executor = ReamExecutor::new(Handle::current());
+        let panicked = executor.spawn("panicking", async {
+            panic!("slot {} out of range", 7);
+        });
+
+        let err = panicked.await.unwrap_err();
+        assert!(err.is_panic());
+        let panics = executor.panics();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].task, "panicking");
+        assert_eq!(panics[0].message, "slot 7 out of range");
+        assert!(!panics[0].backtrace.is_empty());
+        let count = metrics::TASK_PANICS
+            .as_ref()
+            .unwrap()
+            .with_label_values(&["panicking"])
+            .get();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_supervised_restarts() {
+        let executor = ReamExecutor::new(Handle::current());
+        let runs = Arc::new(AtomicU64::new(0));
+        let policy = RestartPolicy {
+            max_restarts: 2,
+            backoff: Duration::from_millis(1),
+        };
+
+        let task = executor.spawn_supervised("flaky", policy, {
+            let runs = runs.clone();
+            move || {
+                let run = runs.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if run == 0 {
+                        panic!("first run");
+                    }
+                }
+            }
+        });
+        assert_eq!(task.await.unwrap(), Some(()));
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+
+        let task = executor.spawn_supervised("broken", policy, || async { panic!("always") });
+        assert_eq!(task.await.unwrap(), Some(()));
+        let broken = executor
+            .panics()
+            .iter()
+            .filter(|panic| panic.task == "broken")
+            .count();
+        assert_eq!(broken, 3);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_at() {
+        let executor = ReamExecutor::new(Handle::current());
+        let at = Instant::now() + Duration::from_millis(30);
+
+        let started = executor.spawn_at("later", at, async { Instant::now() });
+        assert!(started.await.unwrap().unwrap() >= at);
+
+        let never = executor.spawn_at("never", Instant::now() + Duration::from_secs(60), async {});
+        executor.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(never.await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_interval() {
+        let executor = ReamExecutor::new(Handle::current());
+        let period = Duration::from_millis(40);
+        let runs = Arc::new(Mutex::new(vec![]));
+
+        executor.spawn_interval("ticking", period, {
+            let runs = runs.clone();
+            move || {
+                let run = {
+                    let mut runs = runs.lock().unwrap();
+                    runs.push(Instant::now());
+                    runs.len()
+                };
+                async move {
+                    match run {
+                        // Overruns the next tick.
+                        1 => tokio::time::sleep(Duration::from_millis(60)).await,
+                        2 => panic!("second run"),
+                        _ => {}
+                    }
+                }
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        executor.shutdown(Duration::from_secs(1)).await;
+
+        let runs = runs.lock().unwrap();
+        assert!(runs.len() >= 3, "{} runs", runs.len());
+        // The tick missed during the overrun isn't caught up with right away.
+        assert!(runs[1] - runs[0] >= Duration::from_millis(70));
+        assert_eq!(executor.panics()[0].task, "ticking");
+    }
+}
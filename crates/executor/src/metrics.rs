@@ -0,0 +1,12 @@
+use ream_metrics::{int_counter_vec, int_gauge_vec, IntCounterVec, IntGaugeVec, Metric};
+
+pub static TASKS: Metric<IntGaugeVec> = int_gauge_vec!(
+    "ream_executor_tasks",
+    "Running tasks spawned through the executor, by name",
+    ["task"]
+);
+pub static TASK_PANICS: Metric<IntCounterVec> = int_counter_vec!(
+    "ream_executor_task_panics_total",
+    "Panics caught in tasks spawned through the executor, by name",
+    ["task"]
+);
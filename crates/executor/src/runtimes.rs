@@ -0,0 +1,106 @@
+use std::{io, thread, time::Duration};
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::executor::ReamExecutor;
+
+/// Threads of the runtimes the node's tasks are split over, see [`Runtimes`]. Counts below one
+/// are raised to one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutorConfig {
+    /// Worker threads of the networking runtime most tasks run on, one per core by default.
+    pub worker_threads: Option<usize>,
+    /// Threads for blocking work like state transitions and BLS verification, one per core by
+    /// default.
+    pub blocking_threads: Option<usize>,
+    /// Worker threads of the runtime kept for slot-critical duties, one by default.
+    pub duty_threads: Option<usize>,
+}
+
+/// The runtimes of the node, kept apart so that heavy epoch processing on the blocking pool
+/// can't starve gossip heartbeats nor delay a duty due in the current slot.
+pub struct Runtimes {
+    networking: Runtime,
+    blocking: Runtime,
+    duties: Runtime,
+}
+
+impl Runtimes {
+    pub fn new(config: &ExecutorConfig) -> io::Result<Self> {
+        let cores = thread::available_parallelism().map_or(1, usize::from);
+        let networking = Builder::new_multi_thread()
+            .worker_threads(config.worker_threads.unwrap_or(cores).max(1))
+            .thread_name("ream-network")
+            .enable_all()
+            .build()?;
+        // Its single worker only starts the blocking tasks, which get threads of their own.
+        let blocking = Builder::new_multi_thread()
+            .worker_threads(1)
+            .max_blocking_threads(config.blocking_threads.unwrap_or(cores).max(1))
+            .thread_name("ream-blocking")
+            .enable_all()
+            .build()?;
+        let duties = Builder::new_multi_thread()
+            .worker_threads(config.duty_threads.unwrap_or(1).max(1))
+            .thread_name("ream-duties")
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            networking,
+            blocking,
+            duties,
+        })
+    }
+
+    /// The runtime most tasks run on, which the node blocks on.
+    pub fn networking(&self) -> &Runtime {
+        &self.networking
+    }
+
+    /// An executor spawning on these runtimes.
+    pub fn executor(&self) -> ReamExecutor {
+        ReamExecutor::with_pools(
+            self.networking.handle().clone(),
+            self.blocking.handle().clone(),
+            self.duties.handle().clone(),
+        )
+    }
+
+    /// Shuts the runtimes down, waiting up to `timeout` for each.
+    pub fn shutdown_timeout(self, timeout: Duration) {
+        self.duties.shutdown_timeout(timeout);
+        self.blocking.shutdown_timeout(timeout);
+        self.networking.shutdown_timeout(timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pools() {
+        let runtimes = Runtimes::new(&ExecutorConfig {
+            worker_threads: Some(2),
+            blocking_threads: Some(1),
+            duty_threads: Some(0),
+        })
+        .unwrap();
+        let executor = runtimes.executor();
+
+        let thread_name = || thread::current().name().unwrap_or_default().to_string();
+        let (networking, blocking, duty) = runtimes.networking().block_on(async {
+            (
+                executor.spawn("networking", async move { thread_name() }),
+                executor.spawn_blocking("blocking", thread_name),
+                executor.spawn_duty("duty", async move { thread_name() }),
+            )
+        });
+        runtimes.networking().block_on(async {
+            assert_eq!(networking.await.unwrap().unwrap(), "ream-network");
+            assert_eq!(blocking.await.unwrap(), "ream-blocking");
+            assert_eq!(duty.await.unwrap().unwrap(), "ream-duties");
+        });
+        runtimes.shutdown_timeout(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,222 @@
+use alloy_primitives::B256;
+use ream_consensus::{
+    attestation_data::AttestationData,
+    checkpoint::Checkpoint,
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch},
+};
+
+use crate::proto_array::ProtoArray;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InconsistentAttestationData {
+    #[error("head block {0} is unknown to fork choice")]
+    UnknownHead(B256),
+    #[error("head block slot {head_slot} is after attestation slot {slot}")]
+    HeadAfterSlot { head_slot: u64, slot: u64 },
+    #[error("target epoch {target_epoch} is not the epoch of slot {slot}")]
+    TargetEpochMismatch { target_epoch: u64, slot: u64 },
+    #[error("target root {target_root} is not the epoch boundary block {expected:?} of the head")]
+    WrongTargetRoot {
+        target_root: B256,
+        expected: Option<B256>,
+    },
+    #[error("source epoch {source_epoch} is after target epoch {target_epoch}")]
+    SourceAfterTarget {
+        source_epoch: u64,
+        target_epoch: u64,
+    },
+    #[error("source {source_checkpoint:?} doesn't match the justified checkpoint {justified:?}")]
+    SourceNotJustified {
+        source_checkpoint: Checkpoint,
+        justified: Checkpoint,
+    },
+}
+
+/// Checks that attestation data handed to a validator is self-consistent with fork choice
+/// before it gets signed:
+///
+/// - the head vote is a known block no newer than the attestation slot,
+/// - the target is the epoch boundary block of the head's chain for the attestation's epoch,
+/// - the source is the justified checkpoint of the head block or of fork choice.
+///
+/// This catches obviously wrong data from a malfunctioning node, it is not a replacement for
+/// slashing protection.
+pub fn verify_attestation_data(
+    proto_array: &ProtoArray,
+    data: &AttestationData,
+) -> Result<(), InconsistentAttestationData> {
+    let head = proto_array.get_node(&data.beacon_block_root).ok_or(
+        InconsistentAttestationData::UnknownHead(data.beacon_block_root),
+    )?;
+    if head.slot > data.slot {
+        return Err(InconsistentAttestationData::HeadAfterSlot {
+            head_slot: head.slot,
+            slot: data.slot,
+        });
+    }
+
+    verify_attestation_epochs(data)?;
+
+    let expected_target = proto_array
+        .get_ancestor(
+            &data.beacon_block_root,
+            compute_start_slot_at_epoch(data.target.epoch),
+        )
+        .ok();
+    if expected_target != Some(data.target.root) {
+        return Err(InconsistentAttestationData::WrongTargetRoot {
+            target_root: data.target.root,
+            expected: expected_target,
+        });
+    }
+
+    if data.source != head.justified_checkpoint && data.source != proto_array.justified_checkpoint {
+        return Err(InconsistentAttestationData::SourceNotJustified {
+            source_checkpoint: data.source,
+            justified: head.justified_checkpoint,
+        });
+    }
+
+    Ok(())
+}
+
+/// The checks of [`verify_attestation_data`] that need no fork choice, for a validator client
+/// without one: the target is the epoch of the slot and the source isn't after it.
+pub fn verify_attestation_epochs(
+    data: &AttestationData,
+) -> Result<(), InconsistentAttestationData> {
+    if data.target.epoch != compute_epoch_at_slot(data.slot) {
+        return Err(InconsistentAttestationData::TargetEpochMismatch {
+            target_epoch: data.target.epoch,
+            slot: data.slot,
+        });
+    }
+    if data.source.epoch > data.target.epoch {
+        return Err(InconsistentAttestationData::SourceAfterTarget {
+            source_epoch: data.source.epoch,
+            target_epoch: data.target.epoch,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_array::{Block, ExecutionStatus};
+
+    fn root(index: u8) -> B256 {
+        B256::repeat_byte(index)
+    }
+
+    fn justified() -> Checkpoint {
+        Checkpoint {
+            epoch: 0,
+            root: root(1),
+        }
+    }
+
+    /// Chain 1 (slot 0) <- 2 (slot 31) <- 3 (slot 33), plus 4 (slot 32) forking off 2.
+    fn proto_array() -> ProtoArray {
+        let mut proto_array = ProtoArray::new(justified(), Checkpoint::default());
+        for (slot, index, parent) in [(0, 1, 0), (31, 2, 1), (33, 3, 2), (32, 4, 2)] {
+            proto_array
+                .on_block(Block {
+                    slot,
+                    root: root(index),
+                    parent_root: root(parent),
+                    justified_checkpoint: justified(),
+                    finalized_checkpoint: Checkpoint::default(),
+                    execution_status: ExecutionStatus::Irrelevant(false),
+                })
+                .unwrap();
+        }
+        proto_array
+    }
+
+    fn attestation_data(slot: u64, head: u8, target: u8) -> AttestationData {
+        AttestationData {
+            slot,
+            index: 0,
+            beacon_block_root: root(head),
+            source: justified(),
+            target: Checkpoint {
+                epoch: compute_epoch_at_slot(slot),
+                root: root(target),
+            },
+        }
+    }
+
+    #[test]
+    fn test_consistent_data() {
+        let proto_array = proto_array();
+
+        // Slot 32 was skipped on this chain, so the boundary block is the one at slot 31.
+        assert_eq!(
+            verify_attestation_data(&proto_array, &attestation_data(40, 3, 2)),
+            Ok(())
+        );
+        assert_eq!(
+            verify_attestation_data(&proto_array, &attestation_data(40, 4, 4)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_wrong_target() {
+        let proto_array = proto_array();
+
+        assert_eq!(
+            verify_attestation_data(&proto_array, &attestation_data(40, 3, 4)),
+            Err(InconsistentAttestationData::WrongTargetRoot {
+                target_root: root(4),
+                expected: Some(root(2)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_head_after_slot() {
+        let proto_array = proto_array();
+
+        assert_eq!(
+            verify_attestation_data(&proto_array, &attestation_data(32, 3, 2)),
+            Err(InconsistentAttestationData::HeadAfterSlot {
+                head_slot: 33,
+                slot: 32,
+            })
+        );
+    }
+
+    #[test]
+    fn test_wrong_epochs() {
+        let mut data = attestation_data(40, 3, 2);
+        data.target.epoch = 2;
+        assert_eq!(
+            verify_attestation_epochs(&data),
+            Err(InconsistentAttestationData::TargetEpochMismatch {
+                target_epoch: 2,
+                slot: 40,
+            })
+        );
+
+        let mut data = attestation_data(40, 3, 2);
+        data.source.epoch = 2;
+        assert!(matches!(
+            verify_attestation_epochs(&data),
+            Err(InconsistentAttestationData::SourceAfterTarget { .. })
+        ));
+    }
+
+    #[test]
+    fn test_source_not_justified() {
+        let proto_array = proto_array();
+        let mut data = attestation_data(40, 3, 2);
+        data.source.root = root(9);
+
+        assert!(matches!(
+            verify_attestation_data(&proto_array, &data),
+            Err(InconsistentAttestationData::SourceNotJustified { .. })
+        ));
+    }
+}
@@ -0,0 +1 @@
+pub const PROPOSER_SCORE_BOOST: u64 = 40;
@@ -0,0 +1,61 @@
+use alloy_primitives::B256;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProtoArrayError {
+    #[error("unknown block {0}")]
+    UnknownBlock(B256),
+    #[error("parent {parent_root} of block {root} is unknown")]
+    UnknownParent { root: B256, parent_root: B256 },
+    #[error("justified node {0} is unknown")]
+    JustifiedNodeUnknown(B256),
+    #[error("finalized node {0} is unknown")]
+    FinalizedNodeUnknown(B256),
+    #[error("invalid node index {0}")]
+    InvalidNodeIndex(usize),
+    #[error("expected {expected} deltas, got {got}")]
+    InvalidDeltaLen { expected: usize, got: usize },
+    #[error("applying delta {delta} to weight {weight} overflowed")]
+    DeltaOverflow { weight: u64, delta: i64 },
+    #[error("best node {0} is not viable for head")]
+    InvalidBestNode(B256),
+    #[error("ancestor of {root} at slot {slot} was pruned")]
+    AncestorPruned { root: B256, slot: u64 },
+    #[error("node index {0} points before the finalized node after pruning")]
+    IndexUnderflow(usize),
+    #[error("block {root} with invalid payload {block_hash} was reported valid")]
+    InvalidBlockMarkedValid { root: B256, block_hash: B256 },
+}
+
+/// Reasons an attestation is refused by [`on_attestation`], the assertions of the spec's
+/// `validate_on_attestation`.
+///
+/// [`on_attestation`]: crate::proto_array_fork_choice::ProtoArrayForkChoice::on_attestation
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidAttestation {
+    #[error("target epoch {target_epoch} is neither the current epoch {current_epoch} nor the previous one")]
+    TargetEpochOutOfRange {
+        target_epoch: u64,
+        current_epoch: u64,
+    },
+    #[error("target epoch {target_epoch} is not the epoch of slot {slot}")]
+    TargetEpochMismatch { target_epoch: u64, slot: u64 },
+    #[error("target block {0} is unknown")]
+    UnknownTarget(B256),
+    #[error("head block {0} is unknown")]
+    UnknownHead(B256),
+    #[error("head block slot {head_slot} is after attestation slot {slot}")]
+    HeadAfterSlot { head_slot: u64, slot: u64 },
+    #[error("target root {target_root} is not the checkpoint block {expected:?} of the head")]
+    WrongTargetRoot {
+        target_root: B256,
+        expected: Option<B256>,
+    },
+    #[error("attestation for slot {slot} can only count from the next slot, it is {current_slot}")]
+    NotPastSlot { slot: u64, current_slot: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidAttesterSlashing {
+    #[error("the attestations are neither a double vote nor a surround vote")]
+    NotSlashable,
+}
@@ -0,0 +1,7 @@
+pub mod attestation_data_check;
+pub mod constants;
+pub mod error;
+pub mod metrics;
+pub mod persisted_fork_choice;
+pub mod proto_array;
+pub mod proto_array_fork_choice;
@@ -0,0 +1,27 @@
+//! Fork choice metrics, named after the standard beacon node metrics where one exists.
+
+use ream_metrics::{histogram, int_counter, int_gauge, Histogram, IntCounter, IntGauge, Metric};
+
+pub static NODES: Metric<IntGauge> = int_gauge!(
+    "beacon_fork_choice_nodes",
+    "Blocks in the fork choice tree, growing until the next prune after finalization"
+);
+pub static REORGS: Metric<IntCounter> = int_counter!(
+    "beacon_reorgs_total",
+    "Head changes to a block that doesn't descend from the previous head"
+);
+pub static REORG_DEPTH: Metric<Histogram> = histogram!(
+    "beacon_reorg_depth",
+    "Slots between the previous head and the block it shares with the new head",
+    [1.0, 2.0, 3.0, 4.0, 8.0, 16.0, 32.0, 64.0]
+);
+pub static FIND_HEAD_TIME: Metric<Histogram> = histogram!(
+    "beacon_fork_choice_find_head_seconds",
+    "Time to apply pending votes and find the head",
+    [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25]
+);
+pub static PROCESS_BLOCK_TIME: Metric<Histogram> = histogram!(
+    "beacon_fork_choice_process_block_seconds",
+    "Time to insert a block into the fork choice tree",
+    [0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01]
+);
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use alloy_primitives::B256;
+use ream_consensus::checkpoint::Checkpoint;
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+
+use crate::{
+    proto_array::{ExecutionStatus, ProposerBoost, ProtoArray, ProtoNode},
+    proto_array_fork_choice::{ProtoArrayForkChoice, VoteTracker},
+};
+
+/// Version of the layout of [`PersistedForkChoice`], bumped whenever it changes so that a
+/// snapshot of another layout is refused instead of misread.
+pub const PERSISTED_FORK_CHOICE_VERSION: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PersistedForkChoiceError {
+    #[error("invalid SSZ encoding: {0:?}")]
+    Decode(ssz::DecodeError),
+    #[error("snapshot version {0}, expected {PERSISTED_FORK_CHOICE_VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("node {index} references index {reference} which is not before it")]
+    InvalidParent { index: usize, reference: u64 },
+    #[error("node {index} references index {reference} which is out of bounds")]
+    OutOfBounds { index: usize, reference: u64 },
+    #[error("block {0} appears more than once")]
+    DuplicateBlock(B256),
+}
+
+/// SSZ snapshot of [`ProtoArrayForkChoice`], written to the database on shutdown and
+/// periodically so that unfinalized blocks and latest messages survive a restart.
+///
+/// The root to index map is not stored, it is rebuilt from the nodes on load.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PersistedForkChoice {
+    /// [`PERSISTED_FORK_CHOICE_VERSION`] when written, the first byte of the encoding.
+    pub version: u8,
+    pub prune_threshold: u64,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub previous_proposer_boost: ProposerBoost,
+    pub nodes: Vec<PersistedProtoNode>,
+    pub votes: Vec<VoteTracker>,
+    pub balances: Vec<u64>,
+    pub equivocating_indices: Vec<u64>,
+}
+
+/// A [`ProtoNode`] with its indices as `u64`, so that snapshots don't depend on the width of
+/// `usize`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PersistedProtoNode {
+    pub slot: u64,
+    pub root: B256,
+    pub parent: Option<u64>,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub weight: u64,
+    pub best_child: Option<u64>,
+    pub best_descendant: Option<u64>,
+    pub depth: u64,
+    pub skip: Option<u64>,
+    pub execution_status: ExecutionStatus,
+}
+
+impl From<&ProtoNode> for PersistedProtoNode {
+    fn from(node: &ProtoNode) -> Self {
+        let index = |index: Option<usize>| index.map(|index| index as u64);
+        Self {
+            slot: node.slot,
+            root: node.root,
+            parent: index(node.parent),
+            justified_checkpoint: node.justified_checkpoint,
+            finalized_checkpoint: node.finalized_checkpoint,
+            weight: node.weight,
+            best_child: index(node.best_child),
+            best_descendant: index(node.best_descendant),
+            depth: node.depth,
+            skip: index(node.skip),
+            execution_status: node.execution_status,
+        }
+    }
+}
+
+impl From<&ProtoArrayForkChoice> for PersistedForkChoice {
+    fn from(fork_choice: &ProtoArrayForkChoice) -> Self {
+        let proto_array = &fork_choice.proto_array;
+        Self {
+            version: PERSISTED_FORK_CHOICE_VERSION,
+            prune_threshold: proto_array.prune_threshold as u64,
+            justified_checkpoint: proto_array.justified_checkpoint,
+            finalized_checkpoint: proto_array.finalized_checkpoint,
+            previous_proposer_boost: proto_array.previous_proposer_boost,
+            nodes: proto_array
+                .nodes
+                .iter()
+                .map(PersistedProtoNode::from)
+                .collect(),
+            votes: fork_choice.votes.clone(),
+            balances: fork_choice.balances.clone(),
+            equivocating_indices: fork_choice.equivocating_indices.iter().copied().collect(),
+        }
+    }
+}
+
+impl TryFrom<PersistedForkChoice> for ProtoArrayForkChoice {
+    type Error = PersistedForkChoiceError;
+
+    fn try_from(persisted: PersistedForkChoice) -> Result<Self, Self::Error> {
+        if persisted.version != PERSISTED_FORK_CHOICE_VERSION {
+            return Err(PersistedForkChoiceError::UnsupportedVersion(
+                persisted.version,
+            ));
+        }
+        let node_count = persisted.nodes.len() as u64;
+        let mut indices = HashMap::with_capacity(persisted.nodes.len());
+        let mut nodes = Vec::with_capacity(persisted.nodes.len());
+
+        for (index, node) in persisted.nodes.into_iter().enumerate() {
+            for reference in [node.parent, node.skip].into_iter().flatten() {
+                if reference >= index as u64 {
+                    return Err(PersistedForkChoiceError::InvalidParent { index, reference });
+                }
+            }
+            for reference in [node.best_child, node.best_descendant]
+                .into_iter()
+                .flatten()
+            {
+                if reference >= node_count {
+                    return Err(PersistedForkChoiceError::OutOfBounds { index, reference });
+                }
+            }
+            if indices.insert(node.root, index).is_some() {
+                return Err(PersistedForkChoiceError::DuplicateBlock(node.root));
+            }
+            // Every index was checked against the node count, they all fit in a `usize`.
+            let index = |index: Option<u64>| index.map(|index| index as usize);
+            nodes.push(ProtoNode {
+                slot: node.slot,
+                root: node.root,
+                parent: index(node.parent),
+                justified_checkpoint: node.justified_checkpoint,
+                finalized_checkpoint: node.finalized_checkpoint,
+                weight: node.weight,
+                best_child: index(node.best_child),
+                best_descendant: index(node.best_descendant),
+                depth: node.depth,
+                skip: index(node.skip),
+                execution_status: node.execution_status,
+            });
+        }
+
+        Ok(Self {
+            proto_array: ProtoArray {
+                prune_threshold: persisted.prune_threshold as usize,
+                justified_checkpoint: persisted.justified_checkpoint,
+                finalized_checkpoint: persisted.finalized_checkpoint,
+                nodes,
+                indices,
+                previous_proposer_boost: persisted.previous_proposer_boost,
+            },
+            votes: persisted.votes,
+            balances: persisted.balances,
+            equivocating_indices: persisted.equivocating_indices.into_iter().collect(),
+            head: None,
+        })
+    }
+}
+
+impl ProtoArrayForkChoice {
+    pub fn to_persisted_bytes(&self) -> Vec<u8> {
+        PersistedForkChoice::from(self).as_ssz_bytes()
+    }
+
+    /// Decodes a snapshot, refusing one of another version before reading the rest of it.
+    pub fn from_persisted_bytes(bytes: &[u8]) -> Result<Self, PersistedForkChoiceError> {
+        if let Some(&version) = bytes.first() {
+            if version != PERSISTED_FORK_CHOICE_VERSION {
+                return Err(PersistedForkChoiceError::UnsupportedVersion(version));
+            }
+        }
+        PersistedForkChoice::from_ssz_bytes(bytes)
+            .map_err(PersistedForkChoiceError::Decode)?
+            .try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_array::Block;
+
+    fn block(slot: u64, index: u8, parent: u8) -> Block {
+        Block {
+            slot,
+            root: B256::repeat_byte(index),
+            parent_root: B256::repeat_byte(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Optimistic(B256::repeat_byte(index)),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let checkpoint = Checkpoint {
+            epoch: 0,
+            root: B256::repeat_byte(1),
+        };
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), checkpoint, checkpoint).unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(1, 3, 1)).unwrap();
+        fork_choice.process_attestation(0, B256::repeat_byte(2), 1);
+        fork_choice.process_attestation(1, B256::repeat_byte(3), 1);
+        fork_choice
+            .find_head(checkpoint, checkpoint, &[32, 64], B256::repeat_byte(2))
+            .unwrap();
+        fork_choice.process_attestation(0, B256::repeat_byte(3), 2);
+        fork_choice.equivocating_indices.insert(1);
+
+        let restored =
+            ProtoArrayForkChoice::from_persisted_bytes(&fork_choice.to_persisted_bytes()).unwrap();
+
+        assert_eq!(
+            restored,
+            ProtoArrayForkChoice {
+                head: None,
+                ..fork_choice
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_forward_parent() {
+        let mut persisted = PersistedForkChoice::from(
+            &ProtoArrayForkChoice::new(
+                block(0, 1, 0),
+                Checkpoint::default(),
+                Checkpoint::default(),
+            )
+            .unwrap(),
+        );
+        persisted.nodes[0].parent = Some(0);
+
+        assert_eq!(
+            ProtoArrayForkChoice::try_from(persisted),
+            Err(PersistedForkChoiceError::InvalidParent {
+                index: 0,
+                reference: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_version() {
+        let fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        let mut bytes = fork_choice.to_persisted_bytes();
+        assert_eq!(bytes[0], PERSISTED_FORK_CHOICE_VERSION);
+        bytes[0] = PERSISTED_FORK_CHOICE_VERSION + 1;
+        assert_eq!(
+            ProtoArrayForkChoice::from_persisted_bytes(&bytes),
+            Err(PersistedForkChoiceError::UnsupportedVersion(
+                PERSISTED_FORK_CHOICE_VERSION + 1
+            ))
+        );
+
+        let mut persisted = PersistedForkChoice::from(&fork_choice);
+        persisted.version = 0;
+        assert_eq!(
+            ProtoArrayForkChoice::try_from(persisted),
+            Err(PersistedForkChoiceError::UnsupportedVersion(0))
+        );
+    }
+}
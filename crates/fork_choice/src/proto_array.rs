@@ -0,0 +1,862 @@
+use std::collections::HashMap;
+
+use alloy_primitives::B256;
+use ream_consensus::checkpoint::Checkpoint;
+use ssz_derive::{Decode, Encode};
+
+use crate::error::ProtoArrayError;
+
+/// Number of nodes that must be behind the finalized node before pruning is worth the cost of
+/// re-indexing the array.
+pub const DEFAULT_PRUNE_THRESHOLD: usize = 256;
+
+/// What the execution layer said about the payload of a block, with the payload's block hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[ssz(enum_behaviour = "union")]
+pub enum ExecutionStatus {
+    Valid(B256),
+    /// Not verified yet, e.g. imported while the execution layer was syncing.
+    Optimistic(B256),
+    /// Rejected, neither the block nor its descendants may become head.
+    Invalid(B256),
+    /// The block has no payload. The flag is meaningless, SSZ unions need a value.
+    Irrelevant(bool),
+}
+
+impl ExecutionStatus {
+    /// Hash of the execution block, zero for blocks without a payload as the engine API expects.
+    pub fn block_hash(&self) -> B256 {
+        match self {
+            ExecutionStatus::Valid(hash)
+            | ExecutionStatus::Optimistic(hash)
+            | ExecutionStatus::Invalid(hash) => *hash,
+            ExecutionStatus::Irrelevant(_) => B256::ZERO,
+        }
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, ExecutionStatus::Invalid(_))
+    }
+
+    pub fn is_optimistic(&self) -> bool {
+        matches!(self, ExecutionStatus::Optimistic(_))
+    }
+}
+
+/// A block as inserted into fork choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub slot: u64,
+    pub root: B256,
+    pub parent_root: B256,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub execution_status: ExecutionStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoNode {
+    pub slot: u64,
+    pub root: B256,
+    pub parent: Option<usize>,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    /// Sum of the balances of validators voting for this node or any of its descendants.
+    pub weight: u64,
+    pub best_child: Option<usize>,
+    pub best_descendant: Option<usize>,
+    /// Number of ancestors, counted from the anchor block.
+    pub depth: u64,
+    /// Ancestor at depth `skip_depth(depth)`, letting ancestor lookups skip over long chains in
+    /// a logarithmic number of hops. `None` if it was pruned.
+    pub skip: Option<usize>,
+    pub execution_status: ExecutionStatus,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ProposerBoost {
+    pub root: B256,
+    pub score: u64,
+}
+
+/// Block tree stored as a flat array in insertion order, so that parents always come before
+/// their children.
+///
+/// Weight changes are applied as deltas in a single backwards pass which also keeps every node's
+/// best child and best descendant up to date, making head lookup a single index access.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoArray {
+    pub prune_threshold: usize,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub nodes: Vec<ProtoNode>,
+    pub indices: HashMap<B256, usize>,
+    pub previous_proposer_boost: ProposerBoost,
+}
+
+impl ProtoArray {
+    pub fn new(justified_checkpoint: Checkpoint, finalized_checkpoint: Checkpoint) -> Self {
+        Self {
+            prune_threshold: DEFAULT_PRUNE_THRESHOLD,
+            justified_checkpoint,
+            finalized_checkpoint,
+            nodes: vec![],
+            indices: HashMap::new(),
+            previous_proposer_boost: ProposerBoost::default(),
+        }
+    }
+
+    pub fn contains_block(&self, root: &B256) -> bool {
+        self.indices.contains_key(root)
+    }
+
+    pub fn get_node(&self, root: &B256) -> Option<&ProtoNode> {
+        self.indices.get(root).map(|&index| &self.nodes[index])
+    }
+
+    fn node(&self, index: usize) -> Result<&ProtoNode, ProtoArrayError> {
+        self.nodes
+            .get(index)
+            .ok_or(ProtoArrayError::InvalidNodeIndex(index))
+    }
+
+    /// Registers a block. The first block inserted is the anchor and is allowed to have an
+    /// unknown parent, every later block must descend from a known one.
+    pub fn on_block(&mut self, block: Block) -> Result<(), ProtoArrayError> {
+        if self.indices.contains_key(&block.root) {
+            return Ok(());
+        }
+
+        let parent = match self.indices.get(&block.parent_root) {
+            Some(&parent_index) => Some(parent_index),
+            None if self.nodes.is_empty() => None,
+            None => {
+                return Err(ProtoArrayError::UnknownParent {
+                    root: block.root,
+                    parent_root: block.parent_root,
+                })
+            }
+        };
+
+        let depth = match parent {
+            Some(parent_index) => self.node(parent_index)?.depth + 1,
+            None => 0,
+        };
+        let skip =
+            parent.and_then(|parent_index| self.ancestor_at_depth(parent_index, skip_depth(depth)));
+
+        let node_index = self.nodes.len();
+        self.nodes.push(ProtoNode {
+            slot: block.slot,
+            root: block.root,
+            parent,
+            justified_checkpoint: block.justified_checkpoint,
+            finalized_checkpoint: block.finalized_checkpoint,
+            weight: 0,
+            best_child: None,
+            best_descendant: None,
+            depth,
+            skip,
+            execution_status: block.execution_status,
+        });
+        self.indices.insert(block.root, node_index);
+
+        if let Some(parent_index) = parent {
+            self.maybe_update_best_child_and_descendant(parent_index, node_index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies per-node weight `deltas` (indexed like `nodes`), moves the proposer boost to
+    /// `proposer_boost` and refreshes best children and descendants.
+    pub fn apply_score_changes(
+        &mut self,
+        mut deltas: Vec<i64>,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+        proposer_boost: ProposerBoost,
+    ) -> Result<(), ProtoArrayError> {
+        if deltas.len() != self.nodes.len() {
+            return Err(ProtoArrayError::InvalidDeltaLen {
+                expected: self.nodes.len(),
+                got: deltas.len(),
+            });
+        }
+
+        self.justified_checkpoint = justified_checkpoint;
+        self.finalized_checkpoint = finalized_checkpoint;
+
+        for node_index in (0..self.nodes.len()).rev() {
+            let node = &mut self.nodes[node_index];
+
+            let mut delta = deltas[node_index];
+            if self.previous_proposer_boost.root == node.root {
+                delta -= self.previous_proposer_boost.score as i64;
+            }
+            if proposer_boost.root == node.root {
+                delta += proposer_boost.score as i64;
+            }
+
+            node.weight =
+                node.weight
+                    .checked_add_signed(delta)
+                    .ok_or(ProtoArrayError::DeltaOverflow {
+                        weight: node.weight,
+                        delta,
+                    })?;
+
+            if let Some(parent_index) = node.parent {
+                deltas[parent_index] += delta;
+            }
+        }
+        self.previous_proposer_boost = proposer_boost;
+
+        for node_index in (0..self.nodes.len()).rev() {
+            if let Some(parent_index) = self.nodes[node_index].parent {
+                self.maybe_update_best_child_and_descendant(parent_index, node_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the head of the chain descending from `justified_root`.
+    pub fn find_head(&self, justified_root: &B256) -> Result<B256, ProtoArrayError> {
+        let justified_index = *self
+            .indices
+            .get(justified_root)
+            .ok_or(ProtoArrayError::JustifiedNodeUnknown(*justified_root))?;
+        let justified_node = self.node(justified_index)?;
+
+        let best_node = self.node(justified_node.best_descendant.unwrap_or(justified_index))?;
+        if !self.node_is_viable_for_head(best_node) {
+            return Err(ProtoArrayError::InvalidBestNode(best_node.root));
+        }
+
+        Ok(best_node.root)
+    }
+
+    /// Drops every node inserted before the finalized one once there are at least
+    /// `prune_threshold` of them. Conflicting nodes inserted later are kept but lose their
+    /// parent link, they can never become head again as they don't descend from finality.
+    pub fn maybe_prune(&mut self, finalized_root: &B256) -> Result<(), ProtoArrayError> {
+        let finalized_index = *self
+            .indices
+            .get(finalized_root)
+            .ok_or(ProtoArrayError::FinalizedNodeUnknown(*finalized_root))?;
+
+        if finalized_index < self.prune_threshold {
+            return Ok(());
+        }
+
+        for node in &self.nodes[..finalized_index] {
+            self.indices.remove(&node.root);
+        }
+        self.nodes = self.nodes.split_off(finalized_index);
+
+        for index in self.indices.values_mut() {
+            *index -= finalized_index;
+        }
+
+        let shift = |index: usize| {
+            index
+                .checked_sub(finalized_index)
+                .ok_or(ProtoArrayError::IndexUnderflow(index))
+        };
+        for node in self.nodes.iter_mut() {
+            node.parent = node
+                .parent
+                .and_then(|parent| parent.checked_sub(finalized_index));
+            node.skip = node.skip.and_then(|skip| skip.checked_sub(finalized_index));
+            node.best_child = node.best_child.map(shift).transpose()?;
+            node.best_descendant = node.best_descendant.map(shift).transpose()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the root of the block at or before `slot` in the chain of `root`, the spec's
+    /// `get_ancestor`.
+    ///
+    /// Follows skip pointers where they don't overshoot `slot`, so the cost grows with the
+    /// logarithm of the distance rather than the number of blocks in between.
+    pub fn get_ancestor(&self, root: &B256, slot: u64) -> Result<B256, ProtoArrayError> {
+        let mut index = *self
+            .indices
+            .get(root)
+            .ok_or(ProtoArrayError::UnknownBlock(*root))?;
+
+        loop {
+            let node = self.node(index)?;
+            if node.slot <= slot {
+                return Ok(node.root);
+            }
+
+            index = match (node.skip, node.parent) {
+                (Some(skip), _) if self.node(skip)?.slot > slot => skip,
+                (_, Some(parent)) => parent,
+                (_, None) => return Err(ProtoArrayError::AncestorPruned { root: *root, slot }),
+            };
+        }
+    }
+
+    /// Whether `root` is `ancestor_root` or one of its descendants.
+    pub fn is_descendant(&self, ancestor_root: &B256, root: &B256) -> bool {
+        let Some(ancestor) = self.get_node(ancestor_root) else {
+            return false;
+        };
+
+        self.get_ancestor(root, ancestor.slot)
+            .is_ok_and(|ancestor_at_slot| ancestor_at_slot == *ancestor_root)
+    }
+
+    /// The latest block both `a` and `b` descend from, `None` if either is unknown or their
+    /// branches only meet at a pruned block.
+    pub fn common_ancestor(&self, a: &B256, b: &B256) -> Option<B256> {
+        let a = *self.indices.get(a)?;
+        let b = *self.indices.get(b)?;
+        let depth = self.nodes.get(a)?.depth.min(self.nodes.get(b)?.depth);
+        let mut a = self.ancestor_at_depth(a, depth)?;
+        let mut b = self.ancestor_at_depth(b, depth)?;
+
+        while a != b {
+            a = self.nodes.get(a)?.parent?;
+            b = self.nodes.get(b)?.parent?;
+        }
+
+        Some(self.nodes.get(a)?.root)
+    }
+
+    /// Marks the payloads of `root` and its optimistic ancestors valid, a valid payload implies
+    /// valid parents.
+    pub fn propagate_execution_payload_validation(
+        &mut self,
+        root: &B256,
+    ) -> Result<(), ProtoArrayError> {
+        let mut index = Some(
+            *self
+                .indices
+                .get(root)
+                .ok_or(ProtoArrayError::UnknownBlock(*root))?,
+        );
+        while let Some(node_index) = index {
+            let node = &mut self.nodes[node_index];
+            match node.execution_status {
+                ExecutionStatus::Optimistic(hash) => {
+                    node.execution_status = ExecutionStatus::Valid(hash)
+                }
+                ExecutionStatus::Invalid(hash) => {
+                    return Err(ProtoArrayError::InvalidBlockMarkedValid {
+                        root: node.root,
+                        block_hash: hash,
+                    })
+                }
+                // Everything before is valid already or has no payload.
+                ExecutionStatus::Valid(_) | ExecutionStatus::Irrelevant(_) => break,
+            }
+            index = node.parent;
+        }
+        Ok(())
+    }
+
+    /// Marks the payload of `root` invalid after the execution layer rejected it, together with
+    /// every ancestor after the one whose payload is `latest_valid_hash` and every descendant of
+    /// those. Without a known `latest_valid_hash` only `root` and its descendants are marked.
+    ///
+    /// Best children and descendants are refreshed so the invalid chain can't be head anymore.
+    pub fn propagate_execution_payload_invalidation(
+        &mut self,
+        root: &B256,
+        latest_valid_hash: Option<B256>,
+    ) -> Result<(), ProtoArrayError> {
+        let root_index = *self
+            .indices
+            .get(root)
+            .ok_or(ProtoArrayError::UnknownBlock(*root))?;
+
+        let mut invalid = vec![root_index];
+        if let Some(latest_valid_hash) = latest_valid_hash {
+            let mut ancestors = vec![];
+            let mut index = self.nodes[root_index].parent;
+            let mut found = false;
+            while let Some(node_index) = index {
+                let node = &self.nodes[node_index];
+                if node.execution_status.block_hash() == latest_valid_hash {
+                    found = true;
+                    break;
+                }
+                if matches!(node.execution_status, ExecutionStatus::Irrelevant(_)) {
+                    break;
+                }
+                ancestors.push(node_index);
+                index = node.parent;
+            }
+            // A hash outside the chain says nothing about the ancestors.
+            if found {
+                invalid.extend(ancestors);
+            }
+        }
+
+        let mut first_invalid = root_index;
+        for index in invalid {
+            let node = &mut self.nodes[index];
+            node.execution_status = ExecutionStatus::Invalid(node.execution_status.block_hash());
+            first_invalid = first_invalid.min(index);
+        }
+        // Parents come before children, a single forward pass reaches every descendant.
+        for index in first_invalid + 1..self.nodes.len() {
+            let parent_is_invalid = self.nodes[index]
+                .parent
+                .is_some_and(|parent| self.nodes[parent].execution_status.is_invalid());
+            let node = &mut self.nodes[index];
+            if parent_is_invalid {
+                node.execution_status =
+                    ExecutionStatus::Invalid(node.execution_status.block_hash());
+            }
+        }
+
+        for node_index in (0..self.nodes.len()).rev() {
+            if let Some(parent_index) = self.nodes[node_index].parent {
+                self.maybe_update_best_child_and_descendant(parent_index, node_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Index of the ancestor of the node at `index` at `depth`, `None` if it was pruned.
+    fn ancestor_at_depth(&self, mut index: usize, depth: u64) -> Option<usize> {
+        let mut node = self.nodes.get(index)?;
+        if depth > node.depth {
+            return None;
+        }
+
+        while node.depth > depth {
+            let skip = skip_depth(node.depth);
+            let previous_skip = skip_depth(node.depth - 1);
+            // Take the skip pointer unless the parent's one gets closer to `depth` faster.
+            let take_skip = skip == depth
+                || (skip > depth && !(previous_skip + 2 < skip && previous_skip >= depth));
+            index = match (node.skip, node.parent) {
+                (Some(skip_index), _) if take_skip => skip_index,
+                (_, Some(parent)) => parent,
+                (_, None) => return None,
+            };
+            node = self.nodes.get(index)?;
+        }
+
+        Some(index)
+    }
+
+    /// Updates the best child and descendant of `parent_index` after `child_index` changed.
+    fn maybe_update_best_child_and_descendant(
+        &mut self,
+        parent_index: usize,
+        child_index: usize,
+    ) -> Result<(), ProtoArrayError> {
+        let child = self.node(child_index)?;
+        let parent = self.node(parent_index)?;
+
+        let child_leads_to_viable_head = self.node_leads_to_viable_head(child)?;
+        let change_to_none = (None, None);
+        let change_to_child = (
+            Some(child_index),
+            child.best_descendant.or(Some(child_index)),
+        );
+        let no_change = (parent.best_child, parent.best_descendant);
+
+        let (best_child, best_descendant) = match parent.best_child {
+            Some(best_child_index) if best_child_index == child_index => {
+                if child_leads_to_viable_head {
+                    change_to_child
+                } else {
+                    change_to_none
+                }
+            }
+            Some(best_child_index) => {
+                let best_child = self.node(best_child_index)?;
+                let best_child_leads_to_viable_head = self.node_leads_to_viable_head(best_child)?;
+
+                if child_leads_to_viable_head != best_child_leads_to_viable_head {
+                    if child_leads_to_viable_head {
+                        change_to_child
+                    } else {
+                        no_change
+                    }
+                } else if child.weight != best_child.weight {
+                    if child.weight > best_child.weight {
+                        change_to_child
+                    } else {
+                        no_change
+                    }
+                } else if child.root >= best_child.root {
+                    // Tie-break by root, like the spec's `max` over `(weight, root)`
+                    change_to_child
+                } else {
+                    no_change
+                }
+            }
+            None if child_leads_to_viable_head => change_to_child,
+            None => no_change,
+        };
+
+        let parent = &mut self.nodes[parent_index];
+        parent.best_child = best_child;
+        parent.best_descendant = best_descendant;
+
+        Ok(())
+    }
+
+    fn node_leads_to_viable_head(&self, node: &ProtoNode) -> Result<bool, ProtoArrayError> {
+        let best_descendant_is_viable = match node.best_descendant {
+            Some(index) => self.node_is_viable_for_head(self.node(index)?),
+            None => false,
+        };
+        Ok(best_descendant_is_viable || self.node_is_viable_for_head(node))
+    }
+
+    /// Equivalent of the spec's `filter_block_tree` check: a node may only be head if it agrees
+    /// with the store's justified and finalized checkpoints. Blocks with an invalid payload
+    /// never may.
+    pub fn node_is_viable_for_head(&self, node: &ProtoNode) -> bool {
+        if node.execution_status.is_invalid() {
+            return false;
+        }
+        let correct_justified = self.justified_checkpoint.epoch == 0
+            || node.justified_checkpoint == self.justified_checkpoint;
+        let correct_finalized = self.finalized_checkpoint.epoch == 0
+            || node.finalized_checkpoint == self.finalized_checkpoint;
+        correct_justified && correct_finalized
+    }
+}
+
+/// Depth the skip pointer of a node at `depth` points to. Clears the lowest set bits so that
+/// pointers form a skip list, the same scheme Bitcoin Core uses for its block index.
+fn skip_depth(depth: u64) -> u64 {
+    fn clear_lowest_set_bit(n: u64) -> u64 {
+        n & n.saturating_sub(1)
+    }
+
+    if depth < 2 {
+        return 0;
+    }
+    if depth & 1 == 1 {
+        clear_lowest_set_bit(clear_lowest_set_bit(depth - 1)) + 1
+    } else {
+        clear_lowest_set_bit(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(index: u8) -> B256 {
+        B256::repeat_byte(index)
+    }
+
+    fn block(slot: u64, index: u8, parent: u8) -> Block {
+        Block {
+            slot,
+            root: root(index),
+            parent_root: root(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Optimistic(B256::with_last_byte(index)),
+        }
+    }
+
+    #[test]
+    fn test_heaviest_branch_wins() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(1, 3, 1)).unwrap();
+        proto_array.on_block(block(2, 4, 2)).unwrap();
+
+        // Without votes the tie is broken by the higher root.
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(3));
+
+        proto_array
+            .apply_score_changes(
+                vec![0, 0, 0, 10],
+                Checkpoint::default(),
+                Checkpoint::default(),
+                ProposerBoost::default(),
+            )
+            .unwrap();
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(4));
+        assert_eq!(proto_array.get_node(&root(1)).unwrap().weight, 10);
+    }
+
+    #[test]
+    fn test_proposer_boost_is_moved() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(1, 3, 1)).unwrap();
+
+        let boost = ProposerBoost {
+            root: root(2),
+            score: 5,
+        };
+        proto_array
+            .apply_score_changes(
+                vec![0, 0, 3],
+                Checkpoint::default(),
+                Checkpoint::default(),
+                boost,
+            )
+            .unwrap();
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(2));
+
+        proto_array
+            .apply_score_changes(
+                vec![0, 0, 0],
+                Checkpoint::default(),
+                Checkpoint::default(),
+                ProposerBoost::default(),
+            )
+            .unwrap();
+        assert_eq!(proto_array.get_node(&root(2)).unwrap().weight, 0);
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(3));
+    }
+
+    #[test]
+    fn test_non_viable_branch_is_filtered() {
+        let justified = Checkpoint {
+            epoch: 1,
+            root: root(2),
+        };
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array
+            .on_block(Block {
+                justified_checkpoint: justified,
+                ..block(32, 2, 1)
+            })
+            .unwrap();
+        proto_array.on_block(block(33, 3, 1)).unwrap();
+
+        proto_array
+            .apply_score_changes(
+                vec![0, 0, 100],
+                justified,
+                Checkpoint::default(),
+                ProposerBoost::default(),
+            )
+            .unwrap();
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(2));
+    }
+
+    #[test]
+    fn test_unknown_parent_is_rejected() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+
+        assert_eq!(
+            proto_array.on_block(block(1, 2, 9)),
+            Err(ProtoArrayError::UnknownParent {
+                root: root(2),
+                parent_root: root(9),
+            })
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.prune_threshold = 1;
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(1, 3, 1)).unwrap();
+        proto_array.on_block(block(2, 4, 3)).unwrap();
+
+        proto_array.maybe_prune(&root(3)).unwrap();
+
+        assert_eq!(proto_array.nodes.len(), 2);
+        assert!(!proto_array.contains_block(&root(1)));
+        assert!(!proto_array.contains_block(&root(2)));
+        assert_eq!(proto_array.get_node(&root(3)).unwrap().parent, None);
+        assert_eq!(proto_array.get_node(&root(4)).unwrap().parent, Some(0));
+        assert_eq!(proto_array.find_head(&root(3)).unwrap(), root(4));
+    }
+
+    #[test]
+    fn test_get_ancestor() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(2, 2, 1)).unwrap();
+        proto_array.on_block(block(5, 3, 2)).unwrap();
+
+        assert_eq!(proto_array.get_ancestor(&root(3), 5), Ok(root(3)));
+        assert_eq!(proto_array.get_ancestor(&root(3), 4), Ok(root(2)));
+        assert_eq!(proto_array.get_ancestor(&root(3), 1), Ok(root(1)));
+        assert_eq!(
+            proto_array.get_ancestor(&root(9), 1),
+            Err(ProtoArrayError::UnknownBlock(root(9)))
+        );
+    }
+
+    #[test]
+    fn test_get_ancestor_long_chain() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.prune_threshold = 1;
+        let chain_root = |slot: u64| B256::from(alloy_primitives::U256::from(slot + 1));
+        // Every third slot is skipped.
+        let slots: Vec<u64> = (0..3000).filter(|slot| slot % 3 != 2).collect();
+        for (index, &slot) in slots.iter().enumerate() {
+            let parent_root = match index {
+                0 => B256::ZERO,
+                _ => chain_root(slots[index - 1]),
+            };
+            proto_array
+                .on_block(Block {
+                    slot,
+                    root: chain_root(slot),
+                    parent_root,
+                    justified_checkpoint: Checkpoint::default(),
+                    finalized_checkpoint: Checkpoint::default(),
+                    execution_status: ExecutionStatus::Irrelevant(false),
+                })
+                .unwrap();
+        }
+
+        let head = chain_root(*slots.last().unwrap());
+        for slot in [0, 1, 2, 1000, 1001, 1002, 2997, 2998, 2999] {
+            let expected = slots.iter().rev().find(|&&block_slot| block_slot <= slot);
+            assert_eq!(
+                proto_array.get_ancestor(&head, slot),
+                Ok(chain_root(*expected.unwrap()))
+            );
+        }
+
+        proto_array.maybe_prune(&chain_root(1500)).unwrap();
+        assert_eq!(proto_array.get_ancestor(&head, 1501), Ok(chain_root(1501)));
+        assert_eq!(
+            proto_array.get_ancestor(&head, 1499),
+            Err(ProtoArrayError::AncestorPruned {
+                root: head,
+                slot: 1499
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_descendant() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(1, 3, 1)).unwrap();
+        proto_array.on_block(block(2, 4, 3)).unwrap();
+
+        assert!(proto_array.is_descendant(&root(1), &root(4)));
+        assert!(proto_array.is_descendant(&root(3), &root(4)));
+        assert!(proto_array.is_descendant(&root(4), &root(4)));
+        assert!(!proto_array.is_descendant(&root(2), &root(4)));
+        assert!(!proto_array.is_descendant(&root(4), &root(1)));
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(2, 3, 2)).unwrap();
+        proto_array.on_block(block(2, 4, 1)).unwrap();
+        proto_array.on_block(block(3, 5, 4)).unwrap();
+
+        assert_eq!(
+            proto_array.common_ancestor(&root(3), &root(5)),
+            Some(root(1))
+        );
+        assert_eq!(
+            proto_array.common_ancestor(&root(5), &root(4)),
+            Some(root(4))
+        );
+        assert_eq!(
+            proto_array.common_ancestor(&root(3), &root(3)),
+            Some(root(3))
+        );
+        assert_eq!(proto_array.common_ancestor(&root(3), &root(9)), None);
+    }
+
+    #[test]
+    fn test_execution_payload_invalidation() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        // 1 <- 2 <- 3 <- 4 and 1 <- 5.
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(2, 3, 2)).unwrap();
+        proto_array.on_block(block(3, 4, 3)).unwrap();
+        proto_array.on_block(block(1, 5, 1)).unwrap();
+        proto_array
+            .apply_score_changes(
+                vec![0, 0, 0, 10, 0],
+                Checkpoint::default(),
+                Checkpoint::default(),
+                ProposerBoost::default(),
+            )
+            .unwrap();
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(4));
+
+        // The payload of 3 is rejected, 2 is the latest valid one.
+        proto_array
+            .propagate_execution_payload_invalidation(&root(3), Some(B256::with_last_byte(2)))
+            .unwrap();
+        let status = |index| proto_array.get_node(&root(index)).unwrap().execution_status;
+        assert!(status(3).is_invalid());
+        assert!(status(4).is_invalid());
+        assert!(status(2).is_optimistic());
+        // The votes for 4 still count for 2, which is now the leaf of its branch.
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(2));
+
+        // Reported the other way around, 4 as invalid with 2 latest valid also catches 3.
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        for block in [
+            block(0, 1, 0),
+            block(1, 2, 1),
+            block(2, 3, 2),
+            block(3, 4, 3),
+        ] {
+            proto_array.on_block(block).unwrap();
+        }
+        proto_array
+            .propagate_execution_payload_invalidation(&root(4), Some(B256::with_last_byte(2)))
+            .unwrap();
+        assert!(proto_array
+            .get_node(&root(3))
+            .unwrap()
+            .execution_status
+            .is_invalid());
+        assert_eq!(proto_array.find_head(&root(1)).unwrap(), root(2));
+    }
+
+    #[test]
+    fn test_execution_payload_validation() {
+        let mut proto_array = ProtoArray::new(Checkpoint::default(), Checkpoint::default());
+        proto_array.on_block(block(0, 1, 0)).unwrap();
+        proto_array.on_block(block(1, 2, 1)).unwrap();
+        proto_array.on_block(block(2, 3, 2)).unwrap();
+
+        proto_array
+            .propagate_execution_payload_validation(&root(2))
+            .unwrap();
+        let status = |index| proto_array.get_node(&root(index)).unwrap().execution_status;
+        assert_eq!(status(1), ExecutionStatus::Valid(B256::with_last_byte(1)));
+        assert_eq!(status(2), ExecutionStatus::Valid(B256::with_last_byte(2)));
+        assert!(status(3).is_optimistic());
+
+        proto_array
+            .propagate_execution_payload_invalidation(&root(3), None)
+            .unwrap();
+        assert_eq!(
+            proto_array.propagate_execution_payload_validation(&root(3)),
+            Err(ProtoArrayError::InvalidBlockMarkedValid {
+                root: root(3),
+                block_hash: B256::with_last_byte(3),
+            })
+        );
+    }
+}
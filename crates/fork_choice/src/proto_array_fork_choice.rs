@@ -0,0 +1,708 @@
+use std::collections::{BTreeSet, HashMap};
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    attestation::IndexedAttestation,
+    attestation_data::is_slashable_attestation_data,
+    attester_slashing::AttesterSlashing,
+    checkpoint::Checkpoint,
+    constants::SLOTS_PER_EPOCH,
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch},
+};
+use ream_metrics::{inc_counter, observe, set_gauge, start_timer};
+use ssz_derive::{Decode, Encode};
+
+use crate::{
+    constants::PROPOSER_SCORE_BOOST,
+    error::{InvalidAttestation, InvalidAttesterSlashing, ProtoArrayError},
+    metrics,
+    proto_array::{Block, ExecutionStatus, ProposerBoost, ProtoArray},
+};
+
+/// Latest message of a validator, split into the vote already applied to the tree (`current`)
+/// and the vote to apply on the next head computation (`next`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub struct VoteTracker {
+    pub current_root: B256,
+    pub next_root: B256,
+    pub next_epoch: u64,
+}
+
+/// Head of the canonical chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Head {
+    pub root: B256,
+    pub slot: u64,
+}
+
+/// Fork choice backed by a [`ProtoArray`].
+///
+/// Votes and balances are diffed against what was applied on the previous head computation, so
+/// only validators whose vote or balance changed cost anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtoArrayForkChoice {
+    pub(crate) proto_array: ProtoArray,
+    pub(crate) votes: Vec<VoteTracker>,
+    pub(crate) balances: Vec<u64>,
+    /// Validators caught equivocating, their votes no longer count.
+    pub(crate) equivocating_indices: BTreeSet<u64>,
+    /// Head found by the previous head computation, to detect reorgs. Not persisted, a
+    /// restarted node has no previous head to reorg away from.
+    pub(crate) head: Option<B256>,
+}
+
+impl ProtoArrayForkChoice {
+    pub fn new(
+        anchor: Block,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+    ) -> Result<Self, ProtoArrayError> {
+        let mut proto_array = ProtoArray::new(justified_checkpoint, finalized_checkpoint);
+        proto_array.on_block(anchor)?;
+
+        Ok(Self {
+            proto_array,
+            votes: vec![],
+            balances: vec![],
+            equivocating_indices: BTreeSet::new(),
+            head: None,
+        })
+    }
+
+    pub fn proto_array(&self) -> &ProtoArray {
+        &self.proto_array
+    }
+
+    pub fn contains_block(&self, root: &B256) -> bool {
+        self.proto_array.contains_block(root)
+    }
+
+    pub fn process_block(&mut self, block: Block) -> Result<(), ProtoArrayError> {
+        let _timer = start_timer(&metrics::PROCESS_BLOCK_TIME);
+        self.proto_array.on_block(block)?;
+        set_gauge(&metrics::NODES, self.proto_array.nodes.len() as i64);
+        Ok(())
+    }
+
+    /// Records the latest message of `validator_index`. Messages for an older target epoch than
+    /// the one already recorded are ignored, so are messages of equivocating validators.
+    pub fn process_attestation(
+        &mut self,
+        validator_index: usize,
+        block_root: B256,
+        target_epoch: u64,
+    ) {
+        if self
+            .equivocating_indices
+            .contains(&(validator_index as u64))
+        {
+            return;
+        }
+        if validator_index >= self.votes.len() {
+            self.votes
+                .resize(validator_index + 1, VoteTracker::default());
+        }
+
+        let vote = &mut self.votes[validator_index];
+        if target_epoch > vote.next_epoch || *vote == VoteTracker::default() {
+            vote.next_root = block_root;
+            vote.next_epoch = target_epoch;
+        }
+    }
+
+    /// Spec's `on_attestation`: records the attestation as the latest message of each of its
+    /// attesters. Signatures aren't checked, `attestation` must already be verified against
+    /// the state of its target checkpoint.
+    ///
+    /// `is_from_block` skips the check that the target is the current or previous epoch, as
+    /// attestations included in blocks may be older.
+    pub fn on_attestation(
+        &mut self,
+        attestation: &IndexedAttestation,
+        current_slot: u64,
+        is_from_block: bool,
+    ) -> Result<(), InvalidAttestation> {
+        self.validate_on_attestation(attestation, current_slot, is_from_block)?;
+
+        let data = &attestation.data;
+        for &validator_index in attestation.attesting_indices.iter() {
+            self.process_attestation(
+                validator_index as usize,
+                data.beacon_block_root,
+                data.target.epoch,
+            );
+        }
+        Ok(())
+    }
+
+    /// Spec's `validate_on_attestation`.
+    pub fn validate_on_attestation(
+        &self,
+        attestation: &IndexedAttestation,
+        current_slot: u64,
+        is_from_block: bool,
+    ) -> Result<(), InvalidAttestation> {
+        let data = &attestation.data;
+        let target = data.target;
+
+        if !is_from_block {
+            let current_epoch = compute_epoch_at_slot(current_slot);
+            let previous_epoch = current_epoch.saturating_sub(1);
+            if target.epoch != current_epoch && target.epoch != previous_epoch {
+                return Err(InvalidAttestation::TargetEpochOutOfRange {
+                    target_epoch: target.epoch,
+                    current_epoch,
+                });
+            }
+        }
+        if target.epoch != compute_epoch_at_slot(data.slot) {
+            return Err(InvalidAttestation::TargetEpochMismatch {
+                target_epoch: target.epoch,
+                slot: data.slot,
+            });
+        }
+        if !self.proto_array.contains_block(&target.root) {
+            return Err(InvalidAttestation::UnknownTarget(target.root));
+        }
+
+        let head = self
+            .proto_array
+            .get_node(&data.beacon_block_root)
+            .ok_or(InvalidAttestation::UnknownHead(data.beacon_block_root))?;
+        if head.slot > data.slot {
+            return Err(InvalidAttestation::HeadAfterSlot {
+                head_slot: head.slot,
+                slot: data.slot,
+            });
+        }
+
+        let expected_target = self
+            .proto_array
+            .get_ancestor(
+                &data.beacon_block_root,
+                compute_start_slot_at_epoch(target.epoch),
+            )
+            .ok();
+        if expected_target != Some(target.root) {
+            return Err(InvalidAttestation::WrongTargetRoot {
+                target_root: target.root,
+                expected: expected_target,
+            });
+        }
+
+        // Attestations only count for fork choice from the slot after their own.
+        if current_slot <= data.slot {
+            return Err(InvalidAttestation::NotPastSlot {
+                slot: data.slot,
+                current_slot,
+            });
+        }
+        Ok(())
+    }
+
+    /// Spec's `on_attester_slashing`: the validators that signed both attestations are
+    /// equivocating, their weight is removed on the next head computation and they can't vote
+    /// again. Signatures aren't checked, both attestations must already be verified.
+    pub fn on_attester_slashing(
+        &mut self,
+        slashing: &AttesterSlashing,
+    ) -> Result<(), InvalidAttesterSlashing> {
+        if !is_slashable_attestation_data(
+            &slashing.attestation_1.data,
+            &slashing.attestation_2.data,
+        ) {
+            return Err(InvalidAttesterSlashing::NotSlashable);
+        }
+        self.equivocating_indices
+            .extend(slashing.slashable_indices());
+        Ok(())
+    }
+
+    pub fn equivocating_indices(&self) -> &BTreeSet<u64> {
+        &self.equivocating_indices
+    }
+
+    /// Spec's `get_head`: applies pending votes and balance changes, then walks from the
+    /// justified block to the heaviest viable child at every step, ties going to the higher
+    /// root.
+    pub fn get_head(
+        &mut self,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+        justified_state_balances: &[u64],
+        proposer_boost_root: B256,
+    ) -> Result<Head, ProtoArrayError> {
+        let root = self.find_head(
+            justified_checkpoint,
+            finalized_checkpoint,
+            justified_state_balances,
+            proposer_boost_root,
+        )?;
+        self.head().ok_or(ProtoArrayError::UnknownBlock(root))
+    }
+
+    /// Head found by the last head computation, `None` before the first one.
+    pub fn head(&self) -> Option<Head> {
+        let root = self.head?;
+        let node = self.proto_array.get_node(&root)?;
+        Some(Head {
+            root,
+            slot: node.slot,
+        })
+    }
+
+    /// Applies pending votes and balance changes and returns the head.
+    ///
+    /// `justified_state_balances` are the effective balances of the justified state, zero for
+    /// inactive validators.
+    pub fn find_head(
+        &mut self,
+        justified_checkpoint: Checkpoint,
+        finalized_checkpoint: Checkpoint,
+        justified_state_balances: &[u64],
+        proposer_boost_root: B256,
+    ) -> Result<B256, ProtoArrayError> {
+        let _timer = start_timer(&metrics::FIND_HEAD_TIME);
+        let deltas = compute_deltas(
+            &self.proto_array.indices,
+            &mut self.votes,
+            &self.balances,
+            justified_state_balances,
+            &self.equivocating_indices,
+        );
+
+        let proposer_boost = ProposerBoost {
+            root: proposer_boost_root,
+            score: calculate_proposer_score(justified_state_balances),
+        };
+        self.proto_array.apply_score_changes(
+            deltas,
+            justified_checkpoint,
+            finalized_checkpoint,
+            proposer_boost,
+        )?;
+        self.balances = justified_state_balances.to_vec();
+
+        let head = self.proto_array.find_head(&justified_checkpoint.root)?;
+        if let Some(previous_head) = self.head.replace(head) {
+            if let Some(depth) = self.reorg_depth(&previous_head, &head) {
+                inc_counter(&metrics::REORGS);
+                observe(&metrics::REORG_DEPTH, depth as f64);
+            }
+        }
+        Ok(head)
+    }
+
+    /// Slots the chain of `previous_head` is rolled back by when switching to `head`, `None` if
+    /// `head` descends from it.
+    fn reorg_depth(&self, previous_head: &B256, head: &B256) -> Option<u64> {
+        let previous_slot = self.proto_array.get_node(previous_head)?.slot;
+        let ancestor = self.proto_array.common_ancestor(previous_head, head)?;
+        if ancestor == *previous_head {
+            return None;
+        }
+        let ancestor_slot = self.proto_array.get_node(&ancestor)?.slot;
+        Some(previous_slot - ancestor_slot)
+    }
+
+    pub fn maybe_prune(&mut self, finalized_root: &B256) -> Result<(), ProtoArrayError> {
+        self.proto_array.maybe_prune(finalized_root)?;
+        set_gauge(&metrics::NODES, self.proto_array.nodes.len() as i64);
+        Ok(())
+    }
+
+    /// Execution status of the payload of `root`, `None` for unknown blocks.
+    pub fn execution_status(&self, root: &B256) -> Option<ExecutionStatus> {
+        self.proto_array
+            .get_node(root)
+            .map(|node| node.execution_status)
+    }
+
+    pub fn process_execution_payload_validation(
+        &mut self,
+        root: &B256,
+    ) -> Result<(), ProtoArrayError> {
+        self.proto_array
+            .propagate_execution_payload_validation(root)
+    }
+
+    /// Marks `root` invalid, see [`ProtoArray::propagate_execution_payload_invalidation`]. The
+    /// head has to be found again afterwards.
+    pub fn process_execution_payload_invalidation(
+        &mut self,
+        root: &B256,
+        latest_valid_hash: Option<B256>,
+    ) -> Result<(), ProtoArrayError> {
+        self.proto_array
+            .propagate_execution_payload_invalidation(root, latest_valid_hash)
+    }
+}
+
+/// Weight added by proposer boost: `PROPOSER_SCORE_BOOST` percent of the weight of a single
+/// slot's committees.
+pub fn calculate_proposer_score(justified_state_balances: &[u64]) -> u64 {
+    let total_balance: u64 = justified_state_balances.iter().sum();
+    let committee_weight = total_balance / SLOTS_PER_EPOCH;
+    committee_weight * PROPOSER_SCORE_BOOST / 100
+}
+
+/// Returns the weight change of every node in `indices` caused by votes moving and balances
+/// changing since the last call, and marks pending votes as applied. The applied vote of an
+/// equivocating validator is removed once and never replaced.
+pub fn compute_deltas(
+    indices: &HashMap<B256, usize>,
+    votes: &mut [VoteTracker],
+    old_balances: &[u64],
+    new_balances: &[u64],
+    equivocating_indices: &BTreeSet<u64>,
+) -> Vec<i64> {
+    let mut deltas = vec![0i64; indices.len()];
+
+    for (validator_index, vote) in votes.iter_mut().enumerate() {
+        if *vote == VoteTracker::default() {
+            continue;
+        }
+
+        let old_balance = old_balances.get(validator_index).copied().unwrap_or(0);
+        if equivocating_indices.contains(&(validator_index as u64)) {
+            if vote.current_root != B256::ZERO {
+                if let Some(&index) = indices.get(&vote.current_root) {
+                    deltas[index] -= old_balance as i64;
+                }
+                vote.current_root = B256::ZERO;
+            }
+            continue;
+        }
+
+        let new_balance = new_balances.get(validator_index).copied().unwrap_or(0);
+
+        if vote.current_root != vote.next_root || old_balance != new_balance {
+            if let Some(&index) = indices.get(&vote.current_root) {
+                deltas[index] -= old_balance as i64;
+            }
+            if let Some(&index) = indices.get(&vote.next_root) {
+                deltas[index] += new_balance as i64;
+            }
+            vote.current_root = vote.next_root;
+        }
+    }
+
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::attestation_data::AttestationData;
+
+    use super::*;
+
+    fn root(index: u8) -> B256 {
+        B256::repeat_byte(index)
+    }
+
+    fn block(slot: u64, index: u8, parent: u8) -> Block {
+        Block {
+            slot,
+            root: root(index),
+            parent_root: root(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Irrelevant(false),
+        }
+    }
+
+    fn genesis_checkpoint() -> Checkpoint {
+        Checkpoint {
+            epoch: 0,
+            root: root(1),
+        }
+    }
+
+    #[test]
+    fn test_votes_move_head() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(1, 3, 1)).unwrap();
+        let balances = vec![32; 3];
+
+        fork_choice.process_attestation(0, root(2), 1);
+        fork_choice.process_attestation(1, root(2), 1);
+        fork_choice.process_attestation(2, root(3), 1);
+        let head = fork_choice
+            .find_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &balances,
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(head, root(2));
+
+        // Two validators switch, the older vote for an earlier target is ignored.
+        fork_choice.process_attestation(0, root(3), 2);
+        fork_choice.process_attestation(1, root(3), 2);
+        fork_choice.process_attestation(2, root(2), 0);
+        let head = fork_choice
+            .find_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &balances,
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(head, root(3));
+        assert_eq!(
+            fork_choice.proto_array().get_node(&root(3)).unwrap().weight,
+            96
+        );
+        assert_eq!(
+            fork_choice.proto_array().get_node(&root(2)).unwrap().weight,
+            0
+        );
+    }
+
+    fn attestation(indices: Vec<u64>, slot: u64, head: u8, target: u8) -> IndexedAttestation {
+        IndexedAttestation {
+            attesting_indices: indices.into(),
+            data: AttestationData {
+                slot,
+                index: 0,
+                beacon_block_root: root(head),
+                source: genesis_checkpoint(),
+                target: Checkpoint {
+                    epoch: compute_epoch_at_slot(slot),
+                    root: root(target),
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_on_attestation() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(1, 3, 1)).unwrap();
+
+        fork_choice
+            .on_attestation(&attestation(vec![0, 1], 1, 2, 1), 2, false)
+            .unwrap();
+        fork_choice
+            .on_attestation(&attestation(vec![2], 1, 3, 1), 2, false)
+            .unwrap();
+        let head = fork_choice
+            .find_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &[32; 3],
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(head, root(2));
+        assert_eq!(
+            fork_choice.proto_array().get_node(&root(2)).unwrap().weight,
+            64
+        );
+    }
+
+    #[test]
+    fn test_refused_attestations() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 1, 2, 1), 1, false),
+            Err(InvalidAttestation::NotPastSlot {
+                slot: 1,
+                current_slot: 1,
+            })
+        );
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 1, 2, 2), 2, false),
+            Err(InvalidAttestation::WrongTargetRoot {
+                target_root: root(2),
+                expected: Some(root(1)),
+            })
+        );
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 1, 9, 1), 2, false),
+            Err(InvalidAttestation::UnknownHead(root(9)))
+        );
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 0, 2, 1), 2, false),
+            Err(InvalidAttestation::HeadAfterSlot {
+                head_slot: 1,
+                slot: 0,
+            })
+        );
+
+        // Two epochs later the attestation is only accepted from a block.
+        let current_slot = 2 * SLOTS_PER_EPOCH;
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 1, 2, 1), current_slot, false),
+            Err(InvalidAttestation::TargetEpochOutOfRange {
+                target_epoch: 0,
+                current_epoch: 2,
+            })
+        );
+        assert_eq!(
+            fork_choice.on_attestation(&attestation(vec![0], 1, 2, 1), current_slot, true),
+            Ok(())
+        );
+        assert!(fork_choice
+            .votes
+            .iter()
+            .all(|vote| vote.next_root == root(2)));
+    }
+
+    #[test]
+    fn test_on_attester_slashing() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(1, 3, 1)).unwrap();
+        let balances = vec![32; 3];
+
+        fork_choice.process_attestation(0, root(2), 0);
+        fork_choice.process_attestation(1, root(2), 0);
+        fork_choice.process_attestation(2, root(3), 0);
+        let head = fork_choice
+            .find_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &balances,
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(head, root(2));
+
+        // Validators 0 and 1 vote for both blocks of slot 1.
+        let slashing = AttesterSlashing {
+            attestation_1: attestation(vec![0, 1], 1, 2, 1),
+            attestation_2: attestation(vec![0, 1, 2], 1, 3, 1),
+        };
+        assert_eq!(
+            fork_choice.on_attester_slashing(&AttesterSlashing {
+                attestation_1: slashing.attestation_1.clone(),
+                attestation_2: slashing.attestation_1.clone(),
+            }),
+            Err(InvalidAttesterSlashing::NotSlashable)
+        );
+        fork_choice.on_attester_slashing(&slashing).unwrap();
+        assert_eq!(fork_choice.equivocating_indices(), &BTreeSet::from([0, 1]));
+
+        fork_choice.process_attestation(0, root(2), 1);
+        let head = fork_choice
+            .find_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &balances,
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(head, root(3));
+        assert_eq!(
+            fork_choice.proto_array().get_node(&root(2)).unwrap().weight,
+            0
+        );
+        assert_eq!(
+            fork_choice.proto_array().get_node(&root(3)).unwrap().weight,
+            32
+        );
+    }
+
+    #[test]
+    fn test_get_head() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(1, 3, 1)).unwrap();
+        fork_choice.process_block(block(2, 4, 2)).unwrap();
+        assert_eq!(fork_choice.head(), None);
+
+        // Without votes the higher root wins the tie.
+        let head = fork_choice
+            .get_head(genesis_checkpoint(), genesis_checkpoint(), &[], B256::ZERO)
+            .unwrap();
+        assert_eq!(
+            head,
+            Head {
+                root: root(3),
+                slot: 1,
+            }
+        );
+
+        fork_choice.process_attestation(0, root(4), 0);
+        let head = fork_choice
+            .get_head(
+                genesis_checkpoint(),
+                genesis_checkpoint(),
+                &[32],
+                B256::ZERO,
+            )
+            .unwrap();
+        assert_eq!(
+            head,
+            Head {
+                root: root(4),
+                slot: 2,
+            }
+        );
+        assert_eq!(fork_choice.head(), Some(head));
+    }
+
+    #[test]
+    fn test_reorg_depth() {
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), genesis_checkpoint(), genesis_checkpoint())
+                .unwrap();
+        fork_choice.process_block(block(1, 2, 1)).unwrap();
+        fork_choice.process_block(block(2, 3, 2)).unwrap();
+        fork_choice.process_block(block(3, 4, 1)).unwrap();
+
+        assert_eq!(fork_choice.reorg_depth(&root(2), &root(3)), None);
+        assert_eq!(fork_choice.reorg_depth(&root(3), &root(4)), Some(2));
+        assert_eq!(fork_choice.reorg_depth(&root(4), &root(3)), Some(3));
+    }
+
+    #[test]
+    fn test_balance_changes_are_applied() {
+        let mut indices = HashMap::new();
+        indices.insert(root(1), 0);
+        indices.insert(root(2), 1);
+        let mut votes = vec![
+            VoteTracker {
+                current_root: root(1),
+                next_root: root(1),
+                next_epoch: 1,
+            },
+            VoteTracker {
+                current_root: root(1),
+                next_root: root(2),
+                next_epoch: 1,
+            },
+        ];
+
+        let deltas = compute_deltas(&indices, &mut votes, &[10, 10], &[15, 10], &BTreeSet::new());
+
+        assert_eq!(deltas, vec![5 - 10, 10]);
+        assert!(votes.iter().all(|vote| vote.current_root == vote.next_root));
+    }
+
+    #[test]
+    fn test_proposer_score() {
+        let balances = vec![32_000_000_000; 64];
+        assert_eq!(
+            calculate_proposer_score(&balances),
+            2 * 32_000_000_000 * 40 / 100
+        );
+    }
+}
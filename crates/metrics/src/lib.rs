@@ -0,0 +1,9 @@
+pub mod macros;
+pub mod registry;
+pub mod runtime;
+pub mod server;
+
+pub use prometheus::{
+    Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
+pub use registry::*;
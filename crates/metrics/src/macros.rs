@@ -0,0 +1,58 @@
+//! Declarations of [`Metric`](crate::Metric) statics, e.g.
+//!
+//! ```
+//! use ream_metrics::{int_gauge, IntGauge, Metric};
+//!
+//! pub static HEAD_SLOT: Metric<IntGauge> = int_gauge!("beacon_head_slot", "Slot of the head");
+//! ```
+
+#[macro_export]
+macro_rules! int_counter {
+    ($name:expr, $help:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_int_counter($name, $help))
+    };
+}
+
+#[macro_export]
+macro_rules! int_gauge {
+    ($name:expr, $help:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_int_gauge($name, $help))
+    };
+}
+
+/// With buckets in seconds, the default buckets of Prometheus when left out.
+#[macro_export]
+macro_rules! histogram {
+    ($name:expr, $help:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_histogram($name, $help, &[]))
+    };
+    ($name:expr, $help:expr, $buckets:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_histogram($name, $help, &$buckets))
+    };
+}
+
+#[macro_export]
+macro_rules! int_counter_vec {
+    ($name:expr, $help:expr, $labels:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_int_counter_vec($name, $help, &$labels))
+    };
+}
+
+#[macro_export]
+macro_rules! int_gauge_vec {
+    ($name:expr, $help:expr, $labels:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_int_gauge_vec($name, $help, &$labels))
+    };
+}
+
+#[macro_export]
+macro_rules! histogram_vec {
+    ($name:expr, $help:expr, $labels:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| $crate::try_create_histogram_vec($name, $help, &[], &$labels))
+    };
+    ($name:expr, $help:expr, $buckets:expr, $labels:expr $(,)?) => {
+        ::std::sync::LazyLock::new(|| {
+            $crate::try_create_histogram_vec($name, $help, &$buckets, &$labels)
+        })
+    };
+}
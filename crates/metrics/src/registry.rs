@@ -0,0 +1,223 @@
+//! Metrics of the default Prometheus registry, which the metrics server exposes.
+//!
+//! Metrics are created on first use and registering one can fail, e.g. on a name taken twice.
+//! The helpers take the result and do nothing on error, so a broken metric never takes a
+//! subsystem down with it.
+
+use std::sync::LazyLock;
+
+use prometheus::{
+    proto::MetricType, Encoder, Histogram, HistogramOpts, HistogramTimer, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Result, TextEncoder,
+};
+
+/// A metric registered the first time it's used, declared with the macros of
+/// [`crate::macros`].
+pub type Metric<T> = LazyLock<Result<T>>;
+
+pub fn try_create_int_counter(name: &str, help: &str) -> Result<IntCounter> {
+    let counter = IntCounter::new(name, help)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+pub fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge> {
+    let gauge = IntGauge::new(name, help)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
+/// A histogram with `buckets` in seconds, the default buckets of Prometheus when empty.
+pub fn try_create_histogram(name: &str, help: &str, buckets: &[f64]) -> Result<Histogram> {
+    let histogram = Histogram::with_opts(histogram_opts(name, help, buckets))?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+pub fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    labels: &[&str],
+) -> Result<IntCounterVec> {
+    let counter = IntCounterVec::new(Opts::new(name, help), labels)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+pub fn try_create_int_gauge_vec(name: &str, help: &str, labels: &[&str]) -> Result<IntGaugeVec> {
+    let gauge = IntGaugeVec::new(Opts::new(name, help), labels)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
+pub fn try_create_histogram_vec(
+    name: &str,
+    help: &str,
+    buckets: &[f64],
+    labels: &[&str],
+) -> Result<HistogramVec> {
+    let histogram = HistogramVec::new(histogram_opts(name, help, buckets), labels)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+fn histogram_opts(name: &str, help: &str, buckets: &[f64]) -> HistogramOpts {
+    let opts = HistogramOpts::new(name, help);
+    if buckets.is_empty() {
+        opts
+    } else {
+        opts.buckets(buckets.to_vec())
+    }
+}
+
+pub fn inc_counter(counter: &Result<IntCounter>) {
+    if let Ok(counter) = counter {
+        counter.inc();
+    }
+}
+
+pub fn inc_counter_by(counter: &Result<IntCounter>, value: u64) {
+    if let Ok(counter) = counter {
+        counter.inc_by(value);
+    }
+}
+
+pub fn inc_counter_vec(counter: &Result<IntCounterVec>, labels: &[&str]) {
+    if let Ok(counter) = counter {
+        if let Ok(counter) = counter.get_metric_with_label_values(labels) {
+            counter.inc();
+        }
+    }
+}
+
+pub fn inc_counter_vec_by(counter: &Result<IntCounterVec>, labels: &[&str], value: u64) {
+    if let Ok(counter) = counter {
+        if let Ok(counter) = counter.get_metric_with_label_values(labels) {
+            counter.inc_by(value);
+        }
+    }
+}
+
+pub fn set_gauge(gauge: &Result<IntGauge>, value: i64) {
+    if let Ok(gauge) = gauge {
+        gauge.set(value);
+    }
+}
+
+pub fn set_gauge_vec(gauge: &Result<IntGaugeVec>, labels: &[&str], value: i64) {
+    if let Ok(gauge) = gauge {
+        if let Ok(gauge) = gauge.get_metric_with_label_values(labels) {
+            gauge.set(value);
+        }
+    }
+}
+
+pub fn inc_gauge_vec(gauge: &Result<IntGaugeVec>, labels: &[&str]) {
+    if let Ok(gauge) = gauge {
+        if let Ok(gauge) = gauge.get_metric_with_label_values(labels) {
+            gauge.inc();
+        }
+    }
+}
+
+pub fn dec_gauge_vec(gauge: &Result<IntGaugeVec>, labels: &[&str]) {
+    if let Ok(gauge) = gauge {
+        if let Ok(gauge) = gauge.get_metric_with_label_values(labels) {
+            gauge.dec();
+        }
+    }
+}
+
+/// Clears every label set of `gauge`, for gauges rebuilt from scratch on each update.
+pub fn reset_gauge_vec(gauge: &Result<IntGaugeVec>) {
+    if let Ok(gauge) = gauge {
+        gauge.reset();
+    }
+}
+
+pub fn observe(histogram: &Result<Histogram>, value: f64) {
+    if let Ok(histogram) = histogram {
+        histogram.observe(value);
+    }
+}
+
+pub fn observe_vec(histogram: &Result<HistogramVec>, labels: &[&str], value: f64) {
+    if let Ok(histogram) = histogram {
+        if let Ok(histogram) = histogram.get_metric_with_label_values(labels) {
+            histogram.observe(value);
+        }
+    }
+}
+
+/// Observes the seconds until the returned timer drops.
+pub fn start_timer(histogram: &Result<Histogram>) -> Option<HistogramTimer> {
+    histogram.as_ref().ok().map(Histogram::start_timer)
+}
+
+pub fn start_timer_vec(
+    histogram: &Result<HistogramVec>,
+    labels: &[&str],
+) -> Option<HistogramTimer> {
+    histogram
+        .as_ref()
+        .ok()?
+        .get_metric_with_label_values(labels)
+        .ok()
+        .map(|histogram| histogram.start_timer())
+}
+
+/// Every registered metric in the Prometheus text format, the process metrics included on
+/// Linux.
+pub fn gather() -> String {
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("encoding metrics to memory can't fail");
+    String::from_utf8(buffer).expect("the text format is UTF-8")
+}
+
+/// Value of the counter or gauge `name` without labels, e.g. one of the process metrics,
+/// `None` if no such metric is registered.
+pub fn metric_value(name: &str) -> Option<f64> {
+    let family = prometheus::gather()
+        .into_iter()
+        .find(|family| family.get_name() == name)?;
+    let metric = family.get_metric().first()?;
+    match family.get_field_type() {
+        MetricType::COUNTER => Some(metric.get_counter().get_value()),
+        MetricType::GAUGE => Some(metric.get_gauge().get_value()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let counter = try_create_int_counter("test_registry_total", "Test counter");
+        inc_counter(&counter);
+        inc_counter_by(&counter, 2);
+        let gauge = try_create_int_gauge_vec("test_registry_gauge", "Test gauge", &["kind"]);
+        set_gauge_vec(&gauge, &["a"], 7);
+        // A wrong label count is ignored.
+        set_gauge_vec(&gauge, &["a", "b"], 8);
+
+        let text = gather();
+        assert!(text.contains("test_registry_total 3\n"));
+        assert!(text.contains("test_registry_gauge{kind=\"a\"} 7\n"));
+
+        let duplicate = try_create_int_counter("test_registry_total", "Test counter");
+        assert!(duplicate.is_err());
+        inc_counter(&duplicate);
+    }
+
+    #[test]
+    fn test_metric_value() {
+        let gauge = try_create_int_gauge("test_registry_value", "Test gauge");
+        set_gauge(&gauge, 42);
+        assert_eq!(metric_value("test_registry_value"), Some(42.0));
+        assert_eq!(metric_value("test_registry_missing"), None);
+    }
+}
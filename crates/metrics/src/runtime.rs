@@ -0,0 +1,26 @@
+//! Load of the tokio runtime the node's tasks run on.
+
+use tokio::runtime::Handle;
+
+use crate::{int_gauge, set_gauge, IntGauge, Metric};
+
+pub static WORKERS: Metric<IntGauge> = int_gauge!(
+    "ream_executor_workers",
+    "Worker threads of the async runtime"
+);
+pub static ALIVE_TASKS: Metric<IntGauge> = int_gauge!(
+    "ream_executor_alive_tasks",
+    "Tasks spawned on the async runtime that haven't finished"
+);
+pub static GLOBAL_QUEUE_DEPTH: Metric<IntGauge> = int_gauge!(
+    "ream_executor_global_queue_depth",
+    "Tasks waiting in the global queue for a worker, growing when the runtime is saturated"
+);
+
+/// Reads the current load of the runtime of `handle`.
+pub fn update(handle: &Handle) {
+    let metrics = handle.metrics();
+    set_gauge(&WORKERS, metrics.num_workers() as i64);
+    set_gauge(&ALIVE_TASKS, metrics.num_alive_tasks() as i64);
+    set_gauge(&GLOBAL_QUEUE_DEPTH, metrics.global_queue_depth() as i64);
+}
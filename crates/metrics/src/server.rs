@@ -0,0 +1,116 @@
+use std::{
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{http::header::CONTENT_TYPE, routing::get, Router};
+use prometheus::TEXT_FORMAT;
+use tokio::{net::TcpListener, runtime::Handle};
+use tracing::info;
+
+use crate::{gather, runtime};
+
+pub const DEFAULT_METRICS_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+pub const DEFAULT_METRICS_PORT: u16 = 5054;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsServerConfig {
+    pub address: IpAddr,
+    pub port: u16,
+}
+
+impl MetricsServerConfig {
+    pub fn socket_address(&self) -> SocketAddr {
+        SocketAddr::new(self.address, self.port)
+    }
+}
+
+impl Default for MetricsServerConfig {
+    fn default() -> Self {
+        Self {
+            address: DEFAULT_METRICS_ADDRESS,
+            port: DEFAULT_METRICS_PORT,
+        }
+    }
+}
+
+/// Serves `/metrics` on the configured address until `shutdown` completes. `on_scrape` runs
+/// before each scrape, to update the gauges read from the state of the node.
+pub async fn start_metrics_server(
+    config: &MetricsServerConfig,
+    on_scrape: impl Fn() + Send + Sync + 'static,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(config.socket_address()).await?;
+    info!("Metrics listening on {}", listener.local_addr()?);
+    serve(listener, on_scrape, shutdown).await
+}
+
+pub async fn serve(
+    listener: TcpListener,
+    on_scrape: impl Fn() + Send + Sync + 'static,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let on_scrape = Arc::new(on_scrape);
+    let router = Router::new().route(
+        "/metrics",
+        get(move || {
+            on_scrape();
+            runtime::update(&Handle::current());
+            let metrics = gather();
+            async move { ([(CONTENT_TYPE, TEXT_FORMAT)], metrics) }
+        }),
+    );
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::oneshot,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let scrapes = Arc::new(AtomicU64::new(0));
+        let (stop, stopped) = oneshot::channel::<()>();
+        let server = tokio::spawn({
+            let scrapes = scrapes.clone();
+            serve(
+                listener,
+                move || {
+                    scrapes.fetch_add(1, Ordering::Relaxed);
+                },
+                async move {
+                    let _ = stopped.await;
+                },
+            )
+        });
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("ream_executor_workers "));
+        assert_eq!(scrapes.load(Ordering::Relaxed), 1);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,431 @@
+//! EIP-1459 node discovery via DNS.
+//!
+//! A tree is published as TXT records under a domain. The root record is signed by the tree's
+//! key and points to the root hashes of an ENR subtree and a link subtree, every other record
+//! lives at `<hash>.<domain>` where the hash is the base32 encoded first 16 bytes of the keccak256
+//! of its contents.
+
+use std::{collections::VecDeque, future::Future, str::FromStr, time::Duration};
+
+use alloy_primitives::keccak256;
+use data_encoding::{BASE32_NOPAD, BASE64URL_NOPAD};
+use discv5::{
+    enr::k256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey},
+    Enr,
+};
+use hickory_resolver::TokioResolver;
+use ream_metrics::{inc_counter_vec, inc_counter_vec_by, start_timer_vec};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use crate::{
+    metrics::{DISCOVERED_RECORDS, DISCOVERY_QUERIES, DISCOVERY_QUERY_TIME, DNS},
+    peer_table::PeerTable,
+};
+
+const LINK_PREFIX: &str = "enrtree://";
+const ROOT_PREFIX: &str = "enrtree-root:v1";
+const BRANCH_PREFIX: &str = "enrtree-branch:";
+const ENR_PREFIX: &str = "enr:";
+
+/// Upper bound on the records fetched per tree, so a malicious tree can't keep us resolving
+/// forever.
+const MAX_TREE_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DnsDiscoveryError {
+    #[error("invalid enrtree link `{0}`")]
+    InvalidLink(String),
+    #[error("invalid record at {name}: {reason}")]
+    InvalidRecord { name: String, reason: String },
+    #[error("root record of {0} has an invalid signature")]
+    InvalidSignature(String),
+    #[error("record at {0} doesn't match its hash")]
+    HashMismatch(String),
+    #[error("failed to resolve {name}: {message}")]
+    Resolve { name: String, message: String },
+}
+
+/// An `enrtree://<public key>@<domain>` link to a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnrTreeLink {
+    pub public_key: VerifyingKey,
+    pub domain: String,
+}
+
+impl FromStr for EnrTreeLink {
+    type Err = DnsDiscoveryError;
+
+    fn from_str(link: &str) -> Result<Self, Self::Err> {
+        let invalid = || DnsDiscoveryError::InvalidLink(link.to_string());
+        let (public_key, domain) = link
+            .strip_prefix(LINK_PREFIX)
+            .and_then(|rest| rest.split_once('@'))
+            .ok_or_else(invalid)?;
+        if domain.is_empty() {
+            return Err(invalid());
+        }
+        let public_key = BASE32_NOPAD
+            .decode(public_key.as_bytes())
+            .ok()
+            .and_then(|bytes| VerifyingKey::from_sec1_bytes(&bytes).ok())
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            public_key,
+            domain: domain.to_string(),
+        })
+    }
+}
+
+/// The signed `enrtree-root:v1` record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRoot {
+    pub enr_root: String,
+    pub link_root: String,
+    pub seq: u64,
+}
+
+impl TreeRoot {
+    /// Parses the root record at `name` and checks it is signed by `public_key`.
+    pub fn parse_and_verify(
+        name: &str,
+        record: &str,
+        public_key: &VerifyingKey,
+    ) -> Result<Self, DnsDiscoveryError> {
+        let invalid = |reason: &str| DnsDiscoveryError::InvalidRecord {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let (signed, signature) = record
+            .rsplit_once(" sig=")
+            .ok_or_else(|| invalid("missing signature"))?;
+        let mut fields = signed.split(' ');
+        if fields.next() != Some(ROOT_PREFIX) {
+            return Err(invalid("not a root record"));
+        }
+        let mut field = |key: &str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(key))
+                .ok_or_else(|| invalid(&format!("missing `{key}`")))
+        };
+        let enr_root = field("e=")?.to_string();
+        let link_root = field("l=")?.to_string();
+        let seq = field("seq=")?
+            .parse()
+            .map_err(|_| invalid("invalid sequence number"))?;
+
+        // 64 byte signature followed by the recovery id, which isn't needed to verify.
+        let signature = BASE64URL_NOPAD
+            .decode(signature.as_bytes())
+            .ok()
+            .filter(|signature| signature.len() == 65)
+            .and_then(|signature| Signature::from_slice(&signature[..64]).ok())
+            .ok_or_else(|| invalid("malformed signature"))?;
+        public_key
+            .verify_prehash(keccak256(signed.as_bytes()).as_slice(), &signature)
+            .map_err(|_| DnsDiscoveryError::InvalidSignature(name.to_string()))?;
+
+        Ok(Self {
+            enr_root,
+            link_root,
+            seq,
+        })
+    }
+}
+
+/// A record below the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEntry {
+    Branch(Vec<String>),
+    Enr(Enr),
+    Link(EnrTreeLink),
+}
+
+impl TreeEntry {
+    /// Parses the record found at `<hash>.<domain>`, checking it matches `hash`.
+    pub fn parse(name: &str, hash: &str, record: &str) -> Result<Self, DnsDiscoveryError> {
+        if entry_hash(record) != hash {
+            return Err(DnsDiscoveryError::HashMismatch(name.to_string()));
+        }
+        let invalid = |reason: String| DnsDiscoveryError::InvalidRecord {
+            name: name.to_string(),
+            reason,
+        };
+
+        if let Some(children) = record.strip_prefix(BRANCH_PREFIX) {
+            Ok(TreeEntry::Branch(
+                children
+                    .split(',')
+                    .filter(|child| !child.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ))
+        } else if record.starts_with(ENR_PREFIX) {
+            record.parse().map(TreeEntry::Enr).map_err(invalid)
+        } else if record.starts_with(LINK_PREFIX) {
+            record.parse().map(TreeEntry::Link)
+        } else {
+            Err(invalid("unknown record type".to_string()))
+        }
+    }
+}
+
+/// The subdomain label a record is published under.
+pub fn entry_hash(record: &str) -> String {
+    BASE32_NOPAD.encode(&keccak256(record.as_bytes())[..16])
+}
+
+/// Source of TXT records, the system resolver in production.
+pub trait TxtResolver {
+    /// Returns the concatenated strings of the TXT record at `name`.
+    fn lookup_txt(&self, name: &str) -> impl Future<Output = Result<String, String>> + Send;
+}
+
+impl TxtResolver for TokioResolver {
+    async fn lookup_txt(&self, name: &str) -> Result<String, String> {
+        let lookup = self.txt_lookup(name).await.map_err(|err| err.to_string())?;
+        let txt = lookup.iter().next().ok_or("no TXT record")?;
+        Ok(txt
+            .txt_data()
+            .iter()
+            .map(|data| String::from_utf8_lossy(data))
+            .collect())
+    }
+}
+
+/// Resolves every ENR of the tree behind `link`. Links to other trees are not followed.
+pub async fn resolve_tree(
+    resolver: &impl TxtResolver,
+    link: &EnrTreeLink,
+) -> Result<Vec<Enr>, DnsDiscoveryError> {
+    let lookup = |name: String| async move {
+        resolver
+            .lookup_txt(&name)
+            .await
+            .map_err(|message| DnsDiscoveryError::Resolve {
+                name: name.clone(),
+                message,
+            })
+    };
+
+    let root_record = lookup(link.domain.clone()).await?;
+    let root = TreeRoot::parse_and_verify(&link.domain, &root_record, &link.public_key)?;
+
+    let mut enrs = vec![];
+    let mut pending = VecDeque::from([root.enr_root]);
+    let mut visited = 0;
+    while let Some(hash) = pending.pop_front() {
+        visited += 1;
+        if visited > MAX_TREE_ENTRIES {
+            warn!(
+                domain = link.domain,
+                "ENR tree is too large, ignoring the rest"
+            );
+            break;
+        }
+
+        let name = format!("{hash}.{}", link.domain);
+        let record = lookup(name.clone()).await?;
+        match TreeEntry::parse(&name, &hash, &record)? {
+            TreeEntry::Branch(children) => pending.extend(children),
+            TreeEntry::Enr(enr) => enrs.push(enr),
+            TreeEntry::Link(_) => {}
+        }
+    }
+
+    Ok(enrs)
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsDiscoveryConfig {
+    /// Trees are only resolved while fewer peers than this are connected.
+    pub target_peers: usize,
+    pub interval: Duration,
+}
+
+impl Default for DnsDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            target_peers: 64,
+            interval: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Periodically resolves `links` while the node has fewer than `target_peers`, sending ENRs
+/// that are new or updated to `discovered`. Runs until `shutdown` fires or the receiver of
+/// `discovered` is dropped.
+pub async fn run_dns_discovery(
+    resolver: impl TxtResolver,
+    links: Vec<EnrTreeLink>,
+    config: DnsDiscoveryConfig,
+    connected_peers: impl Fn() -> usize,
+    discovered: mpsc::UnboundedSender<Enr>,
+    mut shutdown: broadcast::Receiver<()>,
+) {
+    let mut peer_table = PeerTable::new();
+
+    loop {
+        if connected_peers() < config.target_peers {
+            for link in &links {
+                let timer = start_timer_vec(&DISCOVERY_QUERY_TIME, &[DNS]);
+                let result = tokio::select! {
+                    _ = shutdown.recv() => return,
+                    result = resolve_tree(&resolver, link) => result,
+                };
+                drop(timer);
+
+                match result {
+                    Ok(enrs) => {
+                        inc_counter_vec(&DISCOVERY_QUERIES, &[DNS, "success"]);
+                        let found = enrs.len();
+                        let actionable = peer_table.extend(enrs);
+                        inc_counter_vec_by(&DISCOVERED_RECORDS, &[DNS], actionable.len() as u64);
+                        debug!(
+                            domain = link.domain,
+                            found,
+                            new = actionable.len(),
+                            "Resolved ENR tree"
+                        );
+                        for enr in actionable {
+                            if discovered.send(enr).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        inc_counter_vec(&DISCOVERY_QUERIES, &[DNS, "failure"]);
+                        warn!(domain = link.domain, %err, "Failed to resolve ENR tree");
+                    }
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            _ = tokio::time::sleep(config.interval) => {}
+        }
+    }
+
+    debug!("DNS discovery stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use discv5::enr::{k256::ecdsa::SigningKey, CombinedKey};
+
+    use super::*;
+
+    struct StaticResolver(HashMap<String, String>);
+
+    impl TxtResolver for StaticResolver {
+        async fn lookup_txt(&self, name: &str) -> Result<String, String> {
+            self.0.get(name).cloned().ok_or("NXDOMAIN".to_string())
+        }
+    }
+
+    fn signed_root(signing_key: &SigningKey, enr_root: &str, seq: u64) -> String {
+        let signed = format!("{ROOT_PREFIX} e={enr_root} l={} seq={seq}", entry_hash(""));
+        let (signature, recovery_id) = signing_key
+            .sign_prehash_recoverable(keccak256(signed.as_bytes()).as_slice())
+            .unwrap();
+        let mut signature_bytes = signature.to_bytes().to_vec();
+        signature_bytes.push(recovery_id.to_byte());
+        format!("{signed} sig={}", BASE64URL_NOPAD.encode(&signature_bytes))
+    }
+
+    fn link(signing_key: &SigningKey, domain: &str) -> String {
+        let public_key = signing_key.verifying_key().to_sec1_bytes();
+        format!("{LINK_PREFIX}{}@{domain}", BASE32_NOPAD.encode(&public_key))
+    }
+
+    /// A tree with a branch holding two ENRs.
+    fn tree(signing_key: &SigningKey, domain: &str) -> (HashMap<String, String>, Vec<Enr>) {
+        let enrs: Vec<Enr> = (0..2)
+            .map(|_| Enr::empty(&CombinedKey::generate_secp256k1()).unwrap())
+            .collect();
+        let leaves: Vec<String> = enrs.iter().map(Enr::to_base64).collect();
+        let branch = format!(
+            "{BRANCH_PREFIX}{}",
+            leaves
+                .iter()
+                .map(|leaf| entry_hash(leaf))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        let mut records = HashMap::new();
+        records.insert(
+            domain.to_string(),
+            signed_root(signing_key, &entry_hash(&branch), 1),
+        );
+        for record in leaves.into_iter().chain([branch]) {
+            records.insert(format!("{}.{domain}", entry_hash(&record)), record);
+        }
+        (records, enrs)
+    }
+
+    #[test]
+    fn test_parse_link() {
+        let signing_key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let link: EnrTreeLink = link(&signing_key, "nodes.example.org").parse().unwrap();
+
+        assert_eq!(link.domain, "nodes.example.org");
+        assert_eq!(link.public_key, *signing_key.verifying_key());
+        // The example link from EIP-1459.
+        assert!(
+            "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org"
+                .parse::<EnrTreeLink>()
+                .is_ok()
+        );
+        assert!("enrtree://AKA3AM@".parse::<EnrTreeLink>().is_err());
+        assert!("https://nodes.example.org".parse::<EnrTreeLink>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_tree() {
+        let signing_key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let (records, enrs) = tree(&signing_key, "nodes.example.org");
+        let link: EnrTreeLink = link(&signing_key, "nodes.example.org").parse().unwrap();
+
+        let resolved = resolve_tree(&StaticResolver(records), &link).await.unwrap();
+        assert_eq!(resolved, enrs);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_tampered_trees() {
+        let signing_key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let link: EnrTreeLink = link(&signing_key, "nodes.example.org").parse().unwrap();
+
+        // Signed by a different key.
+        let (records, _) = tree(
+            &SigningKey::from_slice(&[2; 32]).unwrap(),
+            "nodes.example.org",
+        );
+        assert_eq!(
+            resolve_tree(&StaticResolver(records), &link).await,
+            Err(DnsDiscoveryError::InvalidSignature(
+                "nodes.example.org".to_string()
+            ))
+        );
+
+        // A leaf replaced after signing.
+        let (mut records, _) = tree(&signing_key, "nodes.example.org");
+        let leaf = records
+            .values_mut()
+            .find(|record| record.starts_with(ENR_PREFIX))
+            .unwrap();
+        *leaf = Enr::empty(&CombinedKey::generate_secp256k1())
+            .unwrap()
+            .to_base64();
+        assert!(matches!(
+            resolve_tree(&StaticResolver(records), &link).await,
+            Err(DnsDiscoveryError::HashMismatch(_))
+        ));
+    }
+}
@@ -1,14 +1,10 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+pub mod dns_discovery;
+pub mod metrics;
+pub mod node_key;
+pub mod peer_table;
+pub mod random_walk;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use discv5::{
+    enr::{CombinedKey, EnrKey, EnrPublicKey},
+    Enr,
+};
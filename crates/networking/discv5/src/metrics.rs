@@ -0,0 +1,22 @@
+use ream_metrics::{histogram_vec, int_counter_vec, HistogramVec, IntCounterVec, Metric};
+
+/// Labels of the ways records are discovered.
+pub const RANDOM_WALK: &str = "random_walk";
+pub const DNS: &str = "dns";
+
+pub static DISCOVERY_QUERIES: Metric<IntCounterVec> = int_counter_vec!(
+    "ream_discovery_queries_total",
+    "Discovery queries by source and result, `success` or `failure`",
+    ["source", "result"]
+);
+pub static DISCOVERY_QUERY_TIME: Metric<HistogramVec> = histogram_vec!(
+    "ream_discovery_query_seconds",
+    "Duration of discovery queries by source",
+    [1.0, 2.5, 5.0, 10.0, 20.0, 40.0, 80.0],
+    ["source"]
+);
+pub static DISCOVERED_RECORDS: Metric<IntCounterVec> = int_counter_vec!(
+    "ream_discovery_new_records_total",
+    "Node records discovered that were new or newer than the known ones, by source",
+    ["source"]
+);
@@ -0,0 +1,62 @@
+use std::{fs, io, path::Path};
+
+use discv5::enr::CombinedKey;
+
+/// Loads the secp256k1 node key stored as raw bytes at `path`, generating and writing a new
+/// one if the file doesn't exist yet.
+pub fn load_or_generate_node_key(path: &Path) -> io::Result<CombinedKey> {
+    match load_node_key(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let key = CombinedKey::generate_secp256k1();
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_private(path, &key.encode())?;
+            Ok(key)
+        }
+        result => result,
+    }
+}
+
+/// Loads the secp256k1 node key stored as raw bytes at `path`.
+pub fn load_node_key(path: &Path) -> io::Result<CombinedKey> {
+    let mut bytes = fs::read(path)?;
+    CombinedKey::secp256k1_from_bytes(&mut bytes).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid node key at {}: {err}", path.display()),
+        )
+    })
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_key_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("network").join("node_key");
+
+        let generated = load_or_generate_node_key(&path).unwrap();
+        let loaded = load_or_generate_node_key(&path).unwrap();
+        assert_eq!(generated.encode(), loaded.encode());
+    }
+}
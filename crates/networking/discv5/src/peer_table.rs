@@ -0,0 +1,175 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use discv5::{enr::NodeId, Enr};
+
+/// Outcome of recording an ENR in the [`PeerTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrUpdate {
+    /// The node was not known before.
+    New,
+    /// The node was known with a lower sequence number, the previous record is returned.
+    Updated(Enr),
+    /// The stored record is at least as fresh as the observed one, nothing changed.
+    Stale,
+}
+
+/// Latest known ENR per node id.
+///
+/// Discovery queries frequently return the same node several times, sometimes with outdated
+/// records. The table only ever keeps the record with the highest sequence number so dialing
+/// never uses stale addresses.
+#[derive(Debug, Default, Clone)]
+pub struct PeerTable {
+    records: HashMap<NodeId, Enr>,
+}
+
+impl PeerTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an observed ENR, replacing the stored one only if the sequence number is higher.
+    pub fn insert(&mut self, enr: Enr) -> EnrUpdate {
+        match self.records.entry(enr.node_id()) {
+            Entry::Vacant(entry) => {
+                entry.insert(enr);
+                EnrUpdate::New
+            }
+            Entry::Occupied(mut entry) => {
+                if enr.seq() > entry.get().seq() {
+                    EnrUpdate::Updated(entry.insert(enr))
+                } else {
+                    EnrUpdate::Stale
+                }
+            }
+        }
+    }
+
+    /// Records every ENR from a discovery result and returns the ones that are new or newer than
+    /// what was stored, i.e. the records worth acting on.
+    pub fn extend(&mut self, enrs: impl IntoIterator<Item = Enr>) -> Vec<Enr> {
+        enrs.into_iter()
+            .filter_map(|enr| match self.insert(enr.clone()) {
+                EnrUpdate::New | EnrUpdate::Updated(_) => Some(enr),
+                EnrUpdate::Stale => None,
+            })
+            .collect()
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<&Enr> {
+        self.records.get(node_id)
+    }
+
+    pub fn remove(&mut self, node_id: &NodeId) -> Option<Enr> {
+        self.records.remove(node_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Enr> {
+        self.records.values()
+    }
+}
+
+/// Deduplicates ENRs by node id, keeping the record with the highest sequence number. The order
+/// of first appearance is preserved.
+pub fn dedup_enrs(enrs: impl IntoIterator<Item = Enr>) -> Vec<Enr> {
+    let mut positions: HashMap<NodeId, usize> = HashMap::new();
+    let mut deduped: Vec<Enr> = vec![];
+
+    for enr in enrs {
+        match positions.entry(enr.node_id()) {
+            Entry::Vacant(entry) => {
+                entry.insert(deduped.len());
+                deduped.push(enr);
+            }
+            Entry::Occupied(entry) => {
+                let existing = &mut deduped[*entry.get()];
+                if enr.seq() > existing.seq() {
+                    *existing = enr;
+                }
+            }
+        }
+    }
+
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use discv5::enr::CombinedKey;
+
+    use super::*;
+
+    fn enr_with_seq(key: &CombinedKey, seq: u64, port: u16) -> Enr {
+        Enr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp4(port)
+            .seq(seq)
+            .build(key)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dedup_keeps_highest_seq() {
+        let key_a = CombinedKey::generate_secp256k1();
+        let key_b = CombinedKey::generate_secp256k1();
+
+        let deduped = dedup_enrs([
+            enr_with_seq(&key_a, 1, 9000),
+            enr_with_seq(&key_b, 4, 9001),
+            enr_with_seq(&key_a, 3, 9002),
+            enr_with_seq(&key_a, 2, 9003),
+        ]);
+
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].seq(), 3);
+        assert_eq!(deduped[0].udp4(), Some(9002));
+        assert_eq!(deduped[1].seq(), 4);
+    }
+
+    #[test]
+    fn test_peer_table_only_accepts_newer_records() {
+        let key = CombinedKey::generate_secp256k1();
+        let mut table = PeerTable::new();
+
+        assert_eq!(table.insert(enr_with_seq(&key, 2, 9000)), EnrUpdate::New);
+        assert_eq!(table.insert(enr_with_seq(&key, 1, 9001)), EnrUpdate::Stale);
+        assert_eq!(table.insert(enr_with_seq(&key, 2, 9002)), EnrUpdate::Stale);
+
+        match table.insert(enr_with_seq(&key, 5, 9005)) {
+            EnrUpdate::Updated(previous) => assert_eq!(previous.udp4(), Some(9000)),
+            update => panic!("expected an update, got {update:?}"),
+        }
+
+        let stored = table.get(&enr_with_seq(&key, 1, 9000).node_id()).unwrap();
+        assert_eq!(stored.udp4(), Some(9005));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_peer_table_extend_returns_actionable_records() {
+        let key_a = CombinedKey::generate_secp256k1();
+        let key_b = CombinedKey::generate_secp256k1();
+        let mut table = PeerTable::new();
+        table.insert(enr_with_seq(&key_a, 5, 9000));
+
+        let actionable = table.extend([
+            enr_with_seq(&key_a, 4, 9001),
+            enr_with_seq(&key_b, 1, 9002),
+            enr_with_seq(&key_b, 1, 9003),
+        ]);
+
+        assert_eq!(actionable.len(), 1);
+        assert_eq!(actionable[0].udp4(), Some(9002));
+        assert_eq!(table.len(), 2);
+    }
+}
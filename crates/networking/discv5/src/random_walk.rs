@@ -0,0 +1,149 @@
+use std::{sync::Arc, time::Duration};
+
+use discv5::{enr::NodeId, Discv5, Enr};
+use ream_metrics::{inc_counter_vec, inc_counter_vec_by, start_timer_vec};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use crate::{
+    metrics::{DISCOVERED_RECORDS, DISCOVERY_QUERIES, DISCOVERY_QUERY_TIME, RANDOM_WALK},
+    peer_table::PeerTable,
+};
+
+/// Controls how often random-walk discovery queries are issued.
+///
+/// The delay between queries scales with how close the node is to `target_peers`: queries run
+/// every `min_interval` when no peers are connected and back off towards `max_interval` as the
+/// peer count approaches the target. Once the target is reached no queries are issued, the peer
+/// count is only re-checked every `max_interval`.
+#[derive(Debug, Clone)]
+pub struct RandomWalkConfig {
+    pub target_peers: usize,
+    pub min_interval: Duration,
+    pub max_interval: Duration,
+}
+
+impl Default for RandomWalkConfig {
+    fn default() -> Self {
+        Self {
+            target_peers: 64,
+            min_interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RandomWalkConfig {
+    pub fn should_query(&self, connected_peers: usize) -> bool {
+        connected_peers < self.target_peers
+    }
+
+    pub fn query_delay(&self, connected_peers: usize) -> Duration {
+        if !self.should_query(connected_peers) {
+            return self.max_interval;
+        }
+        let fill_ratio = connected_peers as f64 / self.target_peers as f64;
+        self.min_interval
+            + self
+                .max_interval
+                .saturating_sub(self.min_interval)
+                .mul_f64(fill_ratio)
+    }
+}
+
+/// Runs random-walk discovery queries until `shutdown` fires or the receiver of `discovered` is
+/// dropped.
+///
+/// `connected_peers` reports the current peer count and drives the query frequency. Every ENR
+/// that is new, or newer than the previously seen record of the same node, is sent to
+/// `discovered`.
+pub async fn run_random_walk(
+    discv5: Arc<Discv5>,
+    config: RandomWalkConfig,
+    connected_peers: impl Fn() -> usize,
+    discovered: mpsc::UnboundedSender<Enr>,
+    mut shutdown: broadcast::Receiver<()>,
+) {
+    let mut peer_table = PeerTable::new();
+
+    loop {
+        if config.should_query(connected_peers()) {
+            let query = discv5.find_node(NodeId::random());
+            let timer = start_timer_vec(&DISCOVERY_QUERY_TIME, &[RANDOM_WALK]);
+            let result = tokio::select! {
+                _ = shutdown.recv() => break,
+                result = query => result,
+            };
+            drop(timer);
+
+            match result {
+                Ok(enrs) => {
+                    inc_counter_vec(&DISCOVERY_QUERIES, &[RANDOM_WALK, "success"]);
+                    let found = enrs.len();
+                    let actionable = peer_table.extend(enrs);
+                    inc_counter_vec_by(
+                        &DISCOVERED_RECORDS,
+                        &[RANDOM_WALK],
+                        actionable.len() as u64,
+                    );
+                    debug!(found, new = actionable.len(), "Random walk query finished");
+                    for enr in actionable {
+                        if discovered.send(enr).is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) => {
+                    inc_counter_vec(&DISCOVERY_QUERIES, &[RANDOM_WALK, "failure"]);
+                    warn!(?err, "Random walk query failed");
+                }
+            }
+        }
+
+        let delay = config.query_delay(connected_peers());
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+
+    debug!("Random walk discovery stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_delay_scales_with_peer_count() {
+        let config = RandomWalkConfig {
+            target_peers: 50,
+            min_interval: Duration::from_secs(10),
+            max_interval: Duration::from_secs(110),
+        };
+
+        assert_eq!(config.query_delay(0), Duration::from_secs(10));
+        assert_eq!(config.query_delay(25), Duration::from_secs(60));
+        assert_eq!(config.query_delay(50), Duration::from_secs(110));
+        assert_eq!(config.query_delay(80), Duration::from_secs(110));
+    }
+
+    #[test]
+    fn test_idle_when_target_reached() {
+        let config = RandomWalkConfig::default();
+
+        assert!(config.should_query(config.target_peers - 1));
+        assert!(!config.should_query(config.target_peers));
+    }
+
+    #[test]
+    fn test_zero_target_never_queries() {
+        let config = RandomWalkConfig {
+            target_peers: 0,
+            ..Default::default()
+        };
+
+        assert!(!config.should_query(0));
+        assert_eq!(config.query_delay(0), config.max_interval);
+    }
+}
@@ -0,0 +1,280 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use ream_metrics::{inc_counter, inc_counter_vec, start_timer_vec};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug_span, error, Span};
+
+use crate::metrics::{GOSSIP_MESSAGES, GOSSIP_MESSAGES_DROPPED, GOSSIP_PROCESSING_TIME};
+
+/// CPU-bound work done for every received gossip message, e.g. snappy decompression, SSZ
+/// decoding and signature verification.
+pub trait GossipHandler: Send + Sync + 'static {
+    type Message: Send + 'static;
+    type Output: Send + 'static;
+
+    fn process(&self, message: Self::Message) -> Self::Output;
+
+    /// Kind of topic `message` arrived on, e.g. `beacon_block`, labelling its metrics.
+    fn topic(&self, message: &Self::Message) -> &'static str;
+
+    /// How the validation of a message ended, as reported back to gossipsub.
+    fn validation_result(&self, output: &Self::Output) -> ValidationResult;
+
+    /// Span `message` is processed in. Handlers of blocks record the root, slot and peer, and
+    /// hand the span on in their output so that import continues in it.
+    fn span(&self, message: &Self::Message) -> Span {
+        debug_span!("gossip", topic = self.topic(message))
+    }
+}
+
+/// Outcome of validating a gossip message, which decides whether gossipsub forwards it and
+/// penalizes its sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationResult {
+    Accept,
+    Ignore,
+    Reject,
+}
+
+impl ValidationResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationResult::Accept => "accept",
+            ValidationResult::Ignore => "ignore",
+            ValidationResult::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GossipProcessorStats {
+    pub queued: AtomicU64,
+    pub dropped: AtomicU64,
+    pub processed: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SubmitError {
+    #[error("gossip processor queue is full")]
+    QueueFull,
+    #[error("gossip processor has shut down")]
+    Closed,
+}
+
+/// Handle used by the swarm event loop to hand messages to the processor.
+///
+/// Submitting never blocks: when workers fall behind messages are dropped and counted instead of
+/// stalling the event loop, which would otherwise miss keep-alives and heartbeats.
+#[derive(Debug)]
+pub struct GossipProcessorHandle<M> {
+    sender: mpsc::Sender<M>,
+    stats: Arc<GossipProcessorStats>,
+}
+
+impl<M> Clone for GossipProcessorHandle<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<M> GossipProcessorHandle<M> {
+    pub fn try_submit(&self, message: M) -> Result<(), SubmitError> {
+        match self.sender.try_send(message) {
+            Ok(()) => {
+                self.stats.queued.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                inc_counter(&GOSSIP_MESSAGES_DROPPED);
+                Err(SubmitError::QueueFull)
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => Err(SubmitError::Closed),
+        }
+    }
+
+    pub fn stats(&self) -> &GossipProcessorStats {
+        &self.stats
+    }
+}
+
+/// Spawns `workers` tasks that pull messages from a queue bounded to `queue_size`, process them
+/// on the blocking thread pool and forward the results on the returned receiver, which is itself
+/// bounded to `queue_size`.
+///
+/// Workers stop once every [`GossipProcessorHandle`] has been dropped and the queue is drained.
+pub fn spawn_gossip_processor<H: GossipHandler>(
+    handler: H,
+    workers: usize,
+    queue_size: usize,
+) -> (GossipProcessorHandle<H::Message>, mpsc::Receiver<H::Output>) {
+    let (message_sender, message_receiver) = mpsc::channel(queue_size);
+    let (output_sender, output_receiver) = mpsc::channel(queue_size);
+    let message_receiver = Arc::new(Mutex::new(message_receiver));
+    let handler = Arc::new(handler);
+    let stats = Arc::new(GossipProcessorStats::default());
+
+    for _ in 0..workers.max(1) {
+        let message_receiver = message_receiver.clone();
+        let output_sender = output_sender.clone();
+        let handler = handler.clone();
+        let stats = stats.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Some(message) = message_receiver.lock().await.recv().await else {
+                    break;
+                };
+
+                let topic = handler.topic(&message);
+                let span = handler.span(&message);
+                let timer = start_timer_vec(&GOSSIP_PROCESSING_TIME, &[topic]);
+                let worker_handler = handler.clone();
+                let worker_span = span.clone();
+                let output = match tokio::task::spawn_blocking(move || {
+                    worker_span.in_scope(|| worker_handler.process(message))
+                })
+                .await
+                {
+                    Ok(output) => output,
+                    Err(err) => {
+                        error!(parent: &span, ?err, topic, "Gossip processing task failed");
+                        continue;
+                    }
+                };
+                drop(timer);
+                stats.processed.fetch_add(1, Ordering::Relaxed);
+                inc_counter_vec(
+                    &GOSSIP_MESSAGES,
+                    &[topic, handler.validation_result(&output).as_str()],
+                );
+
+                if output_sender.send(output).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    (
+        GossipProcessorHandle {
+            sender: message_sender,
+            stats,
+        },
+        output_receiver,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc as std_mpsc;
+
+    use super::*;
+
+    struct Doubler;
+
+    impl GossipHandler for Doubler {
+        type Message = u64;
+        type Output = u64;
+
+        fn process(&self, message: u64) -> u64 {
+            message * 2
+        }
+
+        fn topic(&self, _message: &u64) -> &'static str {
+            "doubler"
+        }
+
+        fn validation_result(&self, output: &u64) -> ValidationResult {
+            if output % 4 == 0 {
+                ValidationResult::Accept
+            } else {
+                ValidationResult::Reject
+            }
+        }
+    }
+
+    /// Reports every message it starts on to `started`, then waits for `release`.
+    struct Blocked {
+        started: mpsc::UnboundedSender<u64>,
+        release: std::sync::Mutex<std_mpsc::Receiver<()>>,
+    }
+
+    impl GossipHandler for Blocked {
+        type Message = u64;
+        type Output = u64;
+
+        fn process(&self, message: u64) -> u64 {
+            self.started.send(message).unwrap();
+            self.release.lock().unwrap().recv().unwrap();
+            message
+        }
+
+        fn topic(&self, _message: &u64) -> &'static str {
+            "blocked"
+        }
+
+        fn validation_result(&self, _output: &u64) -> ValidationResult {
+            ValidationResult::Ignore
+        }
+    }
+
+    #[tokio::test]
+    async fn test_processes_messages() {
+        let (handle, mut outputs) = spawn_gossip_processor(Doubler, 4, 16);
+
+        for message in 0..10 {
+            handle.try_submit(message).unwrap();
+        }
+        drop(handle);
+
+        let mut results = vec![];
+        while let Some(output) = outputs.recv().await {
+            results.push(output);
+        }
+        results.sort();
+        assert_eq!(
+            results,
+            (0..10).map(|message| message * 2).collect::<Vec<_>>()
+        );
+        let messages = |result| {
+            GOSSIP_MESSAGES
+                .as_ref()
+                .unwrap()
+                .with_label_values(&["doubler", result])
+                .get()
+        };
+        assert_eq!(messages("accept"), 5);
+        assert_eq!(messages("reject"), 5);
+    }
+
+    #[tokio::test]
+    async fn test_drops_when_queue_full() {
+        let (started, mut processing) = mpsc::unbounded_channel();
+        let (release, blocked) = std_mpsc::channel();
+        let handler = Blocked {
+            started,
+            release: std::sync::Mutex::new(blocked),
+        };
+        let (handle, mut outputs) = spawn_gossip_processor(handler, 1, 1);
+
+        // The single worker picks up the first message and blocks on it.
+        handle.try_submit(0).unwrap();
+        assert_eq!(processing.recv().await, Some(0));
+
+        handle.try_submit(1).unwrap();
+        assert_eq!(handle.try_submit(2), Err(SubmitError::QueueFull));
+        assert_eq!(handle.stats().dropped.load(Ordering::Relaxed), 1);
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        assert_eq!(outputs.recv().await, Some(0));
+        assert_eq!(outputs.recv().await, Some(1));
+    }
+}
@@ -0,0 +1,18 @@
+use ream_metrics::{
+    histogram_vec, int_counter, int_counter_vec, HistogramVec, IntCounter, IntCounterVec, Metric,
+};
+
+pub static GOSSIP_MESSAGES: Metric<IntCounterVec> = int_counter_vec!(
+    "ream_gossip_messages_total",
+    "Gossip messages processed by topic and validation result",
+    ["topic", "result"]
+);
+pub static GOSSIP_MESSAGES_DROPPED: Metric<IntCounter> = int_counter!(
+    "ream_gossip_messages_dropped_total",
+    "Gossip messages dropped because the processor queue was full"
+);
+pub static GOSSIP_PROCESSING_TIME: Metric<HistogramVec> = histogram_vec!(
+    "ream_gossip_processing_seconds",
+    "Time spent decoding and validating a gossip message by topic",
+    ["topic"]
+);
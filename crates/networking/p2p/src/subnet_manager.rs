@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use ream_consensus::subnet::SubnetId;
+
+/// Attestation subnets needed for the duties of the node's validators.
+///
+/// A validator publishes its attestation on the subnet of its committee, which only needs peers
+/// on that subnet. An aggregator also has to collect the attestations of its committee, so the
+/// node subscribes to the subnet until the slot of the duty.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AttestationSubnetManager {
+    /// Last slot each subnet needs peers for.
+    peers_needed: BTreeMap<SubnetId, u64>,
+    /// Last slot each subnet has to be subscribed for.
+    subscriptions: BTreeMap<SubnetId, u64>,
+}
+
+impl AttestationSubnetManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the duty of a validator attesting on `subnet` at `slot`.
+    pub fn add_duty(&mut self, subnet: SubnetId, slot: u64, is_aggregator: bool) {
+        let extend = |subnets: &mut BTreeMap<SubnetId, u64>| {
+            let until = subnets.entry(subnet).or_insert(slot);
+            *until = (*until).max(slot);
+        };
+        extend(&mut self.peers_needed);
+        if is_aggregator {
+            extend(&mut self.subscriptions);
+        }
+    }
+
+    /// Subnets the node has to be subscribed to at `current_slot`.
+    pub fn subscribed_subnets(&self, current_slot: u64) -> Vec<SubnetId> {
+        active(&self.subscriptions, current_slot)
+    }
+
+    /// Subnets the node needs peers on at `current_slot`, a superset of the subscribed ones.
+    pub fn subnets_needing_peers(&self, current_slot: u64) -> Vec<SubnetId> {
+        active(&self.peers_needed, current_slot)
+    }
+
+    /// Forgets duties before `current_slot`.
+    pub fn prune(&mut self, current_slot: u64) {
+        self.peers_needed.retain(|_, until| *until >= current_slot);
+        self.subscriptions.retain(|_, until| *until >= current_slot);
+    }
+}
+
+fn active(subnets: &BTreeMap<SubnetId, u64>, current_slot: u64) -> Vec<SubnetId> {
+    subnets
+        .iter()
+        .filter(|(_, until)| **until >= current_slot)
+        .map(|(subnet, _)| *subnet)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregators_subscribe() {
+        let subnet = |id| SubnetId::new(id).unwrap();
+        let mut manager = AttestationSubnetManager::new();
+        manager.add_duty(subnet(3), 10, false);
+        manager.add_duty(subnet(5), 12, true);
+        manager.add_duty(subnet(5), 11, false);
+
+        assert_eq!(manager.subscribed_subnets(10), vec![subnet(5)]);
+        assert_eq!(
+            manager.subnets_needing_peers(10),
+            vec![subnet(3), subnet(5)]
+        );
+        assert_eq!(manager.subnets_needing_peers(11), vec![subnet(5)]);
+
+        manager.prune(13);
+        assert_eq!(manager, AttestationSubnetManager::new());
+    }
+}
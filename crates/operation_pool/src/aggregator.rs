@@ -0,0 +1,153 @@
+use alloy_primitives::B256;
+use ream_bls::PrivateKey;
+use ream_consensus::{
+    aggregate_and_proof::{is_aggregator, AggregateAndProof, SignedAggregateAndProof},
+    attestation_data::AttestationData,
+    bls::BLSSignature,
+    constants::{DOMAIN_AGGREGATE_AND_PROOF, DOMAIN_SELECTION_PROOF},
+    fork_schedule::ForkSchedule,
+    misc::{compute_epoch_at_slot, compute_signing_root},
+};
+
+use crate::naive_aggregation_pool::NaiveAggregationPool;
+
+/// Produces the `SignedAggregateAndProof`s of validators selected to aggregate their
+/// committee's attestations.
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    fork_schedule: ForkSchedule,
+    genesis_validators_root: B256,
+}
+
+impl Aggregator {
+    pub fn new(fork_schedule: ForkSchedule, genesis_validators_root: B256) -> Self {
+        Self {
+            fork_schedule,
+            genesis_validators_root,
+        }
+    }
+
+    /// The selection proof of the validator owning `key` for `slot`.
+    pub fn get_slot_signature(&self, slot: u64, key: &PrivateKey) -> BLSSignature {
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_SELECTION_PROOF,
+            compute_epoch_at_slot(slot),
+            self.genesis_validators_root,
+        );
+        key.sign(compute_signing_root(&slot, domain).as_slice())
+    }
+
+    /// Signs the aggregate of `data` collected so far if the validator owning `key` is selected
+    /// as an aggregator for its committee of `committee_len` validators. `None` if it isn't
+    /// selected or no attestation for `data` was received.
+    pub fn produce_signed_aggregate_and_proof(
+        &self,
+        pool: &NaiveAggregationPool,
+        data: &AttestationData,
+        committee_len: u64,
+        aggregator_index: u64,
+        key: &PrivateKey,
+    ) -> Option<SignedAggregateAndProof> {
+        let selection_proof = self.get_slot_signature(data.slot, key);
+        if !is_aggregator(committee_len, &selection_proof) {
+            return None;
+        }
+
+        let message = AggregateAndProof {
+            aggregator_index,
+            aggregate: pool.get(data)?.clone(),
+            selection_proof,
+        };
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_AGGREGATE_AND_PROOF,
+            compute_epoch_at_slot(data.slot),
+            self.genesis_validators_root,
+        );
+        let signature = key.sign(compute_signing_root(&message, domain).as_slice());
+
+        Some(SignedAggregateAndProof { message, signature })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+    use ream_consensus::{attestation::Attestation, constants::DOMAIN_BEACON_ATTESTER};
+    use ssz_types::BitList;
+
+    use super::*;
+    use crate::naive_aggregation_pool::AggregationError;
+
+    fn signed_attestation(
+        aggregator: &Aggregator,
+        data: AttestationData,
+        position: usize,
+        key: &PrivateKey,
+    ) -> Attestation {
+        let mut aggregation_bits = BitList::with_capacity(4).unwrap();
+        aggregation_bits.set(position, true).unwrap();
+        let domain = aggregator.fork_schedule.get_domain(
+            DOMAIN_BEACON_ATTESTER,
+            data.target.epoch,
+            aggregator.genesis_validators_root,
+        );
+        Attestation {
+            aggregation_bits,
+            data,
+            signature: key.sign(compute_signing_root(&data, domain).as_slice()),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_and_publish() {
+        let aggregator = Aggregator::new(ForkSchedule::mainnet(), B256::repeat_byte(1));
+        let keys: Vec<PrivateKey> = (1..=3)
+            .map(|seed| PrivateKey::key_gen(&[seed; 32]).unwrap())
+            .collect();
+        let data = AttestationData {
+            slot: 10,
+            ..AttestationData::default()
+        };
+
+        let mut pool = NaiveAggregationPool::new();
+        for (position, key) in keys.iter().enumerate() {
+            pool.insert(&signed_attestation(&aggregator, data, position, key))
+                .unwrap();
+        }
+        assert_eq!(
+            pool.insert(&signed_attestation(&aggregator, data, 1, &keys[1])),
+            Err(AggregationError::AlreadyKnown)
+        );
+
+        let aggregate = pool.get(&data).unwrap();
+        assert_eq!(aggregate.aggregation_bits.num_set_bits(), 3);
+        let domain = aggregator.fork_schedule.get_domain(
+            DOMAIN_BEACON_ATTESTER,
+            0,
+            aggregator.genesis_validators_root,
+        );
+        let pubkeys: Vec<FixedBytes<48>> = keys.iter().map(PrivateKey::public_key).collect();
+        assert_eq!(
+            ream_bls::fast_aggregate_verify(
+                &pubkeys,
+                compute_signing_root(&data, domain).as_slice(),
+                &aggregate.signature
+            ),
+            Ok(true)
+        );
+
+        // Committees of fewer than 2 * TARGET_AGGREGATORS_PER_COMMITTEE always aggregate.
+        let signed = aggregator
+            .produce_signed_aggregate_and_proof(&pool, &data, 4, 7, &keys[0])
+            .unwrap();
+        assert_eq!(signed.message.aggregator_index, 7);
+        assert_eq!(&signed.message.aggregate, aggregate);
+        assert_eq!(
+            signed.message.selection_proof,
+            aggregator.get_slot_signature(10, &keys[0])
+        );
+
+        pool.prune(10 + 33);
+        assert!(pool.is_empty());
+    }
+}
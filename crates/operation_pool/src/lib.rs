@@ -0,0 +1,4 @@
+pub mod aggregator;
+pub mod naive_aggregation_pool;
+pub mod operation_pool;
+pub mod persisted_operation_pool;
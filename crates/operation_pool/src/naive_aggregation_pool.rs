@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use alloy_primitives::B256;
+use ream_bls::BLSError;
+use ream_consensus::{
+    attestation::Attestation, attestation_data::AttestationData, constants::SLOTS_PER_EPOCH,
+};
+use tree_hash::TreeHash;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AggregationError {
+    #[error("expected a single aggregation bit, got {0}")]
+    NotUnaggregated(usize),
+    #[error("attestation is already part of the aggregate")]
+    AlreadyKnown,
+    #[error("aggregation bits length {got} doesn't match the aggregate's {expected}")]
+    CommitteeSizeMismatch { expected: usize, got: usize },
+    #[error(transparent)]
+    Bls(#[from] BLSError),
+}
+
+/// Aggregates unaggregated attestations received on the attestation subnets, one aggregate per
+/// `AttestationData`, for aggregators to publish.
+#[derive(Debug, Default)]
+pub struct NaiveAggregationPool {
+    aggregates: HashMap<B256, Attestation>,
+}
+
+impl NaiveAggregationPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges a single validator's attestation into the aggregate for its data. The signature
+    /// is expected to have been verified already.
+    pub fn insert(&mut self, attestation: &Attestation) -> Result<(), AggregationError> {
+        let set_bits = attestation.aggregation_bits.num_set_bits();
+        if set_bits != 1 {
+            return Err(AggregationError::NotUnaggregated(set_bits));
+        }
+
+        let Some(aggregate) = self.aggregates.get_mut(&attestation.data.tree_hash_root()) else {
+            self.aggregates
+                .insert(attestation.data.tree_hash_root(), attestation.clone());
+            return Ok(());
+        };
+
+        if aggregate.aggregation_bits.len() != attestation.aggregation_bits.len() {
+            return Err(AggregationError::CommitteeSizeMismatch {
+                expected: aggregate.aggregation_bits.len(),
+                got: attestation.aggregation_bits.len(),
+            });
+        }
+        if attestation
+            .aggregation_bits
+            .is_subset(&aggregate.aggregation_bits)
+        {
+            return Err(AggregationError::AlreadyKnown);
+        }
+
+        aggregate.signature =
+            ream_bls::aggregate_signatures(&[aggregate.signature, attestation.signature])?;
+        aggregate.aggregation_bits = aggregate
+            .aggregation_bits
+            .union(&attestation.aggregation_bits);
+        Ok(())
+    }
+
+    pub fn get(&self, data: &AttestationData) -> Option<&Attestation> {
+        self.get_by_root(&data.tree_hash_root())
+    }
+
+    /// The aggregate of the `AttestationData` with hash tree root `data_root`.
+    pub fn get_by_root(&self, data_root: &B256) -> Option<&Attestation> {
+        self.aggregates.get(data_root)
+    }
+
+    /// Drops aggregates more than an epoch older than `current_slot`, past the point where they
+    /// can still be usefully published.
+    pub fn prune(&mut self, current_slot: u64) {
+        self.aggregates
+            .retain(|_, aggregate| aggregate.data.slot + SLOTS_PER_EPOCH >= current_slot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.aggregates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aggregates.is_empty()
+    }
+}
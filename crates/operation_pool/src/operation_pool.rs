@@ -0,0 +1,446 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    attestation::Attestation,
+    attester_slashing::AttesterSlashing,
+    bls_to_execution_change::SignedBLSToExecutionChange,
+    constants::{
+        MAX_ATTESTATIONS, MAX_ATTESTER_SLASHINGS, MAX_BLS_TO_EXECUTION_CHANGES,
+        MAX_PROPOSER_SLASHINGS, MAX_VOLUNTARY_EXITS,
+    },
+    misc::compute_epoch_at_slot,
+    proposer_slashing::ProposerSlashing,
+    voluntary_exit::SignedVoluntaryExit,
+};
+use ssz_types::{typenum::U2048, BitList};
+use tree_hash::TreeHash;
+
+/// Operations received from gossip and the API waiting to be included in a block.
+///
+/// The pool doesn't have access to the beacon state, so anything that depends on it (whether a
+/// validator is still slashable, has exited, ...) is answered by the caller through predicates.
+#[derive(Debug, Default)]
+pub struct OperationPool {
+    /// Attestations keyed by the root of their `AttestationData`. No stored attestation's bits
+    /// are a subset of another one's with the same data.
+    attestations: HashMap<B256, Vec<Attestation>>,
+    /// Keyed by proposer index, one slashing per proposer is enough.
+    proposer_slashings: HashMap<u64, ProposerSlashing>,
+    attester_slashings: Vec<AttesterSlashing>,
+    /// Keyed by validator index.
+    voluntary_exits: HashMap<u64, SignedVoluntaryExit>,
+    /// Keyed by validator index.
+    bls_to_execution_changes: HashMap<u64, SignedBLSToExecutionChange>,
+}
+
+impl OperationPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an attestation, returning `false` if an attestation with the same data and a
+    /// superset of its bits is already pooled. Pooled attestations covered by the new one are
+    /// dropped.
+    pub fn insert_attestation(&mut self, attestation: Attestation) -> bool {
+        let attestations = self
+            .attestations
+            .entry(attestation.data.tree_hash_root())
+            .or_default();
+        if attestations.iter().any(|existing| {
+            attestation
+                .aggregation_bits
+                .is_subset(&existing.aggregation_bits)
+        }) {
+            return false;
+        }
+
+        attestations.retain(|existing| {
+            !existing
+                .aggregation_bits
+                .is_subset(&attestation.aggregation_bits)
+        });
+        attestations.push(attestation);
+        true
+    }
+
+    pub fn num_attestations(&self) -> usize {
+        self.attestations.values().map(Vec::len).sum()
+    }
+
+    /// Returns `false` if a slashing for the same proposer is already pooled.
+    pub fn insert_proposer_slashing(&mut self, slashing: ProposerSlashing) -> bool {
+        let proposer_index = slashing.signed_header_1.message.proposer_index;
+        if self.proposer_slashings.contains_key(&proposer_index) {
+            return false;
+        }
+        self.proposer_slashings.insert(proposer_index, slashing);
+        true
+    }
+
+    /// Returns `false` if the slashing doesn't slash anyone not already covered by a pooled
+    /// slashing.
+    pub fn insert_attester_slashing(&mut self, slashing: AttesterSlashing) -> bool {
+        let covered: HashSet<u64> = self
+            .attester_slashings
+            .iter()
+            .flat_map(AttesterSlashing::slashable_indices)
+            .collect();
+        if slashing
+            .slashable_indices()
+            .iter()
+            .all(|index| covered.contains(index))
+        {
+            return false;
+        }
+        self.attester_slashings.push(slashing);
+        true
+    }
+
+    /// Returns `false` if an exit for the same validator is already pooled.
+    pub fn insert_voluntary_exit(&mut self, exit: SignedVoluntaryExit) -> bool {
+        let validator_index = exit.message.validator_index;
+        if self.voluntary_exits.contains_key(&validator_index) {
+            return false;
+        }
+        self.voluntary_exits.insert(validator_index, exit);
+        true
+    }
+
+    /// Returns `false` if a change for the same validator is already pooled.
+    pub fn insert_bls_to_execution_change(&mut self, change: SignedBLSToExecutionChange) -> bool {
+        let validator_index = change.message.validator_index;
+        if self.bls_to_execution_changes.contains_key(&validator_index) {
+            return false;
+        }
+        self.bls_to_execution_changes
+            .insert(validator_index, change);
+        true
+    }
+
+    /// Every pooled attestation, in no particular order.
+    pub fn attestations(&self) -> impl Iterator<Item = &Attestation> {
+        self.attestations.values().flatten()
+    }
+
+    pub fn proposer_slashings(&self) -> impl Iterator<Item = &ProposerSlashing> {
+        self.proposer_slashings.values()
+    }
+
+    pub fn attester_slashings(&self) -> &[AttesterSlashing] {
+        &self.attester_slashings
+    }
+
+    pub fn voluntary_exits(&self) -> impl Iterator<Item = &SignedVoluntaryExit> {
+        self.voluntary_exits.values()
+    }
+
+    pub fn bls_to_execution_changes(&self) -> impl Iterator<Item = &SignedBLSToExecutionChange> {
+        self.bls_to_execution_changes.values()
+    }
+
+    /// Picks up to `MAX_ATTESTATIONS` attestations for a block at `slot`, greedily taking the
+    /// one adding the most not yet included votes each time. `is_valid` is checked against the
+    /// state the block is built on, e.g. that the source is the justified checkpoint.
+    pub fn get_attestations(
+        &self,
+        slot: u64,
+        is_valid: impl Fn(&Attestation) -> bool,
+    ) -> Vec<Attestation> {
+        let current_epoch = compute_epoch_at_slot(slot);
+        let candidates: Vec<(B256, &Attestation)> = self
+            .attestations
+            .iter()
+            .flat_map(|(data_root, attestations)| {
+                attestations
+                    .iter()
+                    .map(move |attestation| (*data_root, attestation))
+            })
+            .filter(|(_, attestation)| {
+                attestation.data.slot < slot
+                    && attestation.data.target.epoch + 1 >= current_epoch
+                    && is_valid(attestation)
+            })
+            .collect();
+
+        let mut covered: HashMap<B256, BitList<U2048>> = HashMap::new();
+        let mut selected = vec![];
+        let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+        while selected.len() < MAX_ATTESTATIONS {
+            let best = remaining
+                .iter()
+                .enumerate()
+                .map(|(position, &candidate)| {
+                    let (data_root, attestation) = candidates[candidate];
+                    let new_votes = match covered.get(&data_root) {
+                        Some(bits) => attestation.aggregation_bits.difference(bits),
+                        None => attestation.aggregation_bits.clone(),
+                    }
+                    .num_set_bits();
+                    (position, new_votes)
+                })
+                .max_by_key(|(_, new_votes)| *new_votes);
+            let Some((position, new_votes)) = best else {
+                break;
+            };
+            if new_votes == 0 {
+                break;
+            }
+
+            let (data_root, attestation) = candidates[remaining.swap_remove(position)];
+            covered
+                .entry(data_root)
+                .and_modify(|bits| *bits = bits.union(&attestation.aggregation_bits))
+                .or_insert_with(|| attestation.aggregation_bits.clone());
+            selected.push(attestation.clone());
+        }
+
+        selected
+    }
+
+    /// Picks slashings for a block. Proposer slashings come first, then attester slashings
+    /// greedily by how many validators they newly slash. `is_slashable` tells whether a
+    /// validator can still be slashed in the state the block is built on.
+    pub fn get_slashings(
+        &self,
+        is_slashable: impl Fn(u64) -> bool,
+    ) -> (Vec<ProposerSlashing>, Vec<AttesterSlashing>) {
+        let proposer_slashings: Vec<ProposerSlashing> = self
+            .proposer_slashings
+            .iter()
+            .filter(|(proposer_index, _)| is_slashable(**proposer_index))
+            .take(MAX_PROPOSER_SLASHINGS)
+            .map(|(_, slashing)| slashing.clone())
+            .collect();
+
+        let mut slashed: HashSet<u64> = proposer_slashings
+            .iter()
+            .map(|slashing| slashing.signed_header_1.message.proposer_index)
+            .collect();
+        let mut candidates: Vec<&AttesterSlashing> = self.attester_slashings.iter().collect();
+        let mut attester_slashings = vec![];
+        while attester_slashings.len() < MAX_ATTESTER_SLASHINGS {
+            let best = candidates
+                .iter()
+                .enumerate()
+                .map(|(position, slashing)| {
+                    let newly_slashed = slashing
+                        .slashable_indices()
+                        .into_iter()
+                        .filter(|index| !slashed.contains(index) && is_slashable(*index))
+                        .count();
+                    (position, newly_slashed)
+                })
+                .max_by_key(|(_, newly_slashed)| *newly_slashed);
+            let Some((position, newly_slashed)) = best else {
+                break;
+            };
+            if newly_slashed == 0 {
+                break;
+            }
+
+            let slashing = candidates.swap_remove(position);
+            slashed.extend(slashing.slashable_indices());
+            attester_slashings.push(slashing.clone());
+        }
+
+        (proposer_slashings, attester_slashings)
+    }
+
+    /// Picks up to `MAX_VOLUNTARY_EXITS` exits that are valid in the state the block is built
+    /// on, skipping validators that are being slashed in the same block.
+    pub fn get_voluntary_exits(
+        &self,
+        slashed: &HashSet<u64>,
+        is_valid: impl Fn(&SignedVoluntaryExit) -> bool,
+    ) -> Vec<SignedVoluntaryExit> {
+        self.voluntary_exits
+            .iter()
+            .filter(|(validator_index, exit)| !slashed.contains(validator_index) && is_valid(exit))
+            .take(MAX_VOLUNTARY_EXITS)
+            .map(|(_, exit)| exit.clone())
+            .collect()
+    }
+
+    pub fn get_bls_to_execution_changes(
+        &self,
+        is_valid: impl Fn(&SignedBLSToExecutionChange) -> bool,
+    ) -> Vec<SignedBLSToExecutionChange> {
+        self.bls_to_execution_changes
+            .values()
+            .filter(|change| is_valid(change))
+            .take(MAX_BLS_TO_EXECUTION_CHANGES)
+            .cloned()
+            .collect()
+    }
+
+    /// Drops attestations too old to be included in a block at `current_epoch`.
+    pub fn prune_attestations(&mut self, current_epoch: u64) {
+        self.attestations.retain(|_, attestations| {
+            attestations.retain(|attestation| attestation.data.target.epoch + 1 >= current_epoch);
+            !attestations.is_empty()
+        });
+    }
+
+    /// Drops slashings that no longer slash anyone, called with the finalized state so that
+    /// slashings included on a fork that gets orphaned are kept.
+    pub fn prune_slashings(&mut self, is_slashable: impl Fn(u64) -> bool) {
+        self.proposer_slashings
+            .retain(|proposer_index, _| is_slashable(*proposer_index));
+        self.attester_slashings
+            .retain(|slashing| slashing.slashable_indices().into_iter().any(&is_slashable));
+    }
+
+    /// Drops exits of validators that already initiated an exit in the finalized state.
+    pub fn prune_voluntary_exits(&mut self, has_exited: impl Fn(u64) -> bool) {
+        self.voluntary_exits
+            .retain(|validator_index, _| !has_exited(*validator_index));
+    }
+
+    /// Drops changes of validators whose withdrawal credentials in the finalized state already
+    /// point to an execution address.
+    pub fn prune_bls_to_execution_changes(&mut self, has_bls_credentials: impl Fn(u64) -> bool) {
+        self.bls_to_execution_changes
+            .retain(|validator_index, _| has_bls_credentials(*validator_index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::{
+        attestation::IndexedAttestation, attestation_data::AttestationData, bls::BLSSignature,
+        checkpoint::Checkpoint,
+    };
+
+    use super::*;
+
+    fn attestation(slot: u64, index: u64, bits: &[usize]) -> Attestation {
+        let mut aggregation_bits = BitList::with_capacity(8).unwrap();
+        for bit in bits {
+            aggregation_bits.set(*bit, true).unwrap();
+        }
+        Attestation {
+            aggregation_bits,
+            data: AttestationData {
+                slot,
+                index,
+                target: Checkpoint {
+                    epoch: compute_epoch_at_slot(slot),
+                    ..Checkpoint::default()
+                },
+                ..AttestationData::default()
+            },
+            signature: BLSSignature::default(),
+        }
+    }
+
+    fn attester_slashing(indices: &[u64]) -> AttesterSlashing {
+        let indexed = IndexedAttestation {
+            attesting_indices: indices.to_vec().into(),
+            ..IndexedAttestation::default()
+        };
+        AttesterSlashing {
+            attestation_1: indexed.clone(),
+            attestation_2: indexed,
+        }
+    }
+
+    #[test]
+    fn test_attestation_dedup() {
+        let mut pool = OperationPool::new();
+
+        assert!(pool.insert_attestation(attestation(1, 0, &[0, 1])));
+        assert!(!pool.insert_attestation(attestation(1, 0, &[1])));
+        assert!(pool.insert_attestation(attestation(1, 0, &[2])));
+        assert!(pool.insert_attestation(attestation(1, 1, &[1])));
+        assert_eq!(pool.num_attestations(), 3);
+
+        // Covers both attestations for committee 0, which are replaced.
+        assert!(pool.insert_attestation(attestation(1, 0, &[0, 1, 2])));
+        assert_eq!(pool.num_attestations(), 2);
+    }
+
+    #[test]
+    fn test_attestation_selection_maximizes_coverage() {
+        let mut pool = OperationPool::new();
+        pool.insert_attestation(attestation(1, 0, &[0, 1, 2, 3]));
+        pool.insert_attestation(attestation(1, 0, &[2, 3, 4]));
+        pool.insert_attestation(attestation(1, 0, &[4, 5]));
+        pool.insert_attestation(attestation(1, 1, &[0]));
+        // Too recent to be included at slot 2.
+        pool.insert_attestation(attestation(2, 0, &[0]));
+
+        let selected = pool.get_attestations(2, |_| true);
+        let selected_bits: Vec<usize> = selected
+            .iter()
+            .map(|attestation| attestation.aggregation_bits.num_set_bits())
+            .collect();
+        // [2, 3, 4] adds nothing new once the other two committee 0 attestations are in.
+        assert_eq!(selected_bits, vec![4, 2, 1]);
+
+        let selected = pool.get_attestations(2, |attestation| attestation.data.index == 1);
+        assert_eq!(selected.len(), 1);
+    }
+
+    #[test]
+    fn test_prune_attestations() {
+        let mut pool = OperationPool::new();
+        pool.insert_attestation(attestation(1, 0, &[0]));
+        pool.insert_attestation(attestation(40, 0, &[0]));
+
+        pool.prune_attestations(1);
+        assert_eq!(pool.num_attestations(), 2);
+        pool.prune_attestations(2);
+        assert_eq!(pool.num_attestations(), 1);
+        assert!(pool.get_attestations(33, |_| true).is_empty());
+        assert_eq!(pool.get_attestations(41, |_| true).len(), 1);
+    }
+
+    #[test]
+    fn test_slashing_selection() {
+        let mut pool = OperationPool::new();
+        assert!(pool.insert_attester_slashing(attester_slashing(&[1, 2])));
+        assert!(pool.insert_attester_slashing(attester_slashing(&[2, 3, 4])));
+        assert!(!pool.insert_attester_slashing(attester_slashing(&[1, 3])));
+        assert!(pool.insert_attester_slashing(attester_slashing(&[5])));
+
+        let mut proposer_slashing = ProposerSlashing::default();
+        proposer_slashing.signed_header_1.message.proposer_index = 3;
+        assert!(pool.insert_proposer_slashing(proposer_slashing.clone()));
+        assert!(!pool.insert_proposer_slashing(proposer_slashing));
+
+        // Validator 4 was already slashed, 3 is covered by the proposer slashing.
+        let (proposer_slashings, attester_slashings) = pool.get_slashings(|index| index != 4);
+        assert_eq!(proposer_slashings.len(), 1);
+        assert_eq!(
+            attester_slashings
+                .iter()
+                .map(AttesterSlashing::slashable_indices)
+                .collect::<Vec<_>>(),
+            vec![vec![1, 2], vec![5]]
+        );
+
+        pool.prune_slashings(|index| index == 5);
+        let (proposer_slashings, attester_slashings) = pool.get_slashings(|_| true);
+        assert!(proposer_slashings.is_empty());
+        assert_eq!(attester_slashings.len(), 1);
+    }
+
+    #[test]
+    fn test_exits_skip_slashed_validators() {
+        let mut pool = OperationPool::new();
+        for validator_index in 0..3 {
+            let mut exit = SignedVoluntaryExit::default();
+            exit.message.validator_index = validator_index;
+            assert!(pool.insert_voluntary_exit(exit.clone()));
+            assert!(!pool.insert_voluntary_exit(exit));
+        }
+
+        let exits = pool.get_voluntary_exits(&HashSet::from([1]), |_| true);
+        assert_eq!(exits.len(), 2);
+        assert!(exits.iter().all(|exit| exit.message.validator_index != 1));
+
+        pool.prune_voluntary_exits(|validator_index| validator_index == 0);
+        assert_eq!(pool.get_voluntary_exits(&HashSet::new(), |_| true).len(), 2);
+    }
+}
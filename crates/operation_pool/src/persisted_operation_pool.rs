@@ -0,0 +1,102 @@
+use ream_consensus::{
+    attestation::Attestation, attester_slashing::AttesterSlashing,
+    bls_to_execution_change::SignedBLSToExecutionChange, proposer_slashing::ProposerSlashing,
+    voluntary_exit::SignedVoluntaryExit,
+};
+use ssz_derive::{Decode, Encode};
+
+use crate::operation_pool::OperationPool;
+
+/// SSZ snapshot of an [`OperationPool`], written to the database on shutdown so that pooled
+/// operations aren't lost to a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct PersistedOperationPool {
+    pub attestations: Vec<Attestation>,
+    pub proposer_slashings: Vec<ProposerSlashing>,
+    pub attester_slashings: Vec<AttesterSlashing>,
+    pub voluntary_exits: Vec<SignedVoluntaryExit>,
+    pub bls_to_execution_changes: Vec<SignedBLSToExecutionChange>,
+}
+
+impl From<&OperationPool> for PersistedOperationPool {
+    fn from(pool: &OperationPool) -> Self {
+        Self {
+            attestations: pool.attestations().cloned().collect(),
+            proposer_slashings: pool.proposer_slashings().cloned().collect(),
+            attester_slashings: pool.attester_slashings().to_vec(),
+            voluntary_exits: pool.voluntary_exits().cloned().collect(),
+            bls_to_execution_changes: pool.bls_to_execution_changes().cloned().collect(),
+        }
+    }
+}
+
+/// Operations are inserted again, so the pool keeps its invariants whatever was stored.
+impl From<PersistedOperationPool> for OperationPool {
+    fn from(persisted: PersistedOperationPool) -> Self {
+        let mut pool = OperationPool::new();
+        for attestation in persisted.attestations {
+            pool.insert_attestation(attestation);
+        }
+        for slashing in persisted.proposer_slashings {
+            pool.insert_proposer_slashing(slashing);
+        }
+        for slashing in persisted.attester_slashings {
+            pool.insert_attester_slashing(slashing);
+        }
+        for exit in persisted.voluntary_exits {
+            pool.insert_voluntary_exit(exit);
+        }
+        for change in persisted.bls_to_execution_changes {
+            pool.insert_bls_to_execution_change(change);
+        }
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::{
+        attestation_data::AttestationData, bls::BLSSignature, voluntary_exit::VoluntaryExit,
+    };
+    use ssz::{Decode, Encode};
+    use ssz_types::BitList;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut pool = OperationPool::new();
+        let mut aggregation_bits = BitList::with_capacity(8).unwrap();
+        aggregation_bits.set(3, true).unwrap();
+        pool.insert_attestation(Attestation {
+            aggregation_bits,
+            data: AttestationData {
+                slot: 7,
+                ..AttestationData::default()
+            },
+            signature: BLSSignature::default(),
+        });
+        pool.insert_proposer_slashing(ProposerSlashing::default());
+        pool.insert_voluntary_exit(SignedVoluntaryExit {
+            message: VoluntaryExit {
+                validator_index: 4,
+                ..VoluntaryExit::default()
+            },
+            ..SignedVoluntaryExit::default()
+        });
+
+        let persisted = PersistedOperationPool::from(&pool);
+        let decoded = PersistedOperationPool::from_ssz_bytes(&persisted.as_ssz_bytes()).unwrap();
+        assert_eq!(decoded, persisted);
+        let restored = OperationPool::from(decoded);
+        assert_eq!(restored.num_attestations(), 1);
+        assert_eq!(restored.proposer_slashings().count(), 1);
+        assert_eq!(
+            restored
+                .voluntary_exits()
+                .map(|exit| exit.message.validator_index)
+                .collect::<Vec<_>>(),
+            [4]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use alloy_primitives::B256;
+use ream_consensus::committee::CommitteeCache;
+
+/// Epochs kept by each [`DutiesCache`] map, enough for the previous, current and next epoch of
+/// a couple of competing heads.
+pub const MAX_CACHED_EPOCHS: usize = 8;
+
+/// Cached values by epoch and dependent root.
+type EpochMap<V> = Mutex<HashMap<(u64, B256), V>>;
+
+/// Committees and proposers computed for duties, keyed by epoch and dependent root.
+///
+/// The dependent root is the last block whose randao reveal feeds the seed, a reorg past it
+/// changes the duties and misses the cache instead of serving stale ones.
+#[derive(Debug, Default)]
+pub struct DutiesCache {
+    committees: EpochMap<Arc<CommitteeCache>>,
+    proposers: EpochMap<Arc<Vec<u64>>>,
+}
+
+impl DutiesCache {
+    pub fn committees<E>(
+        &self,
+        epoch: u64,
+        dependent_root: B256,
+        compute: impl FnOnce() -> Result<CommitteeCache, E>,
+    ) -> Result<Arc<CommitteeCache>, E> {
+        get_or_compute(&self.committees, (epoch, dependent_root), || {
+            compute().map(Arc::new)
+        })
+    }
+
+    /// Proposer of each slot of `epoch`, in slot order.
+    pub fn proposers<E>(
+        &self,
+        epoch: u64,
+        dependent_root: B256,
+        compute: impl FnOnce() -> Result<Vec<u64>, E>,
+    ) -> Result<Arc<Vec<u64>>, E> {
+        get_or_compute(&self.proposers, (epoch, dependent_root), || {
+            compute().map(Arc::new)
+        })
+    }
+}
+
+/// Computes outside the lock, two requests racing for a missing entry both compute it.
+fn get_or_compute<K: Copy + Eq + Hash + Ord, V: Clone, E>(
+    map: &Mutex<HashMap<K, V>>,
+    key: K,
+    compute: impl FnOnce() -> Result<V, E>,
+) -> Result<V, E> {
+    if let Some(value) = map.lock().expect("duties cache lock poisoned").get(&key) {
+        return Ok(value.clone());
+    }
+    let value = compute()?;
+    let mut map = map.lock().expect("duties cache lock poisoned");
+    if map.len() >= MAX_CACHED_EPOCHS {
+        if let Some(oldest) = map.keys().min().copied() {
+            map.remove(&oldest);
+        }
+    }
+    map.insert(key, value.clone());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_oldest_epoch() {
+        let cache = DutiesCache::default();
+        for epoch in 0..=MAX_CACHED_EPOCHS as u64 {
+            cache
+                .proposers(epoch, B256::ZERO, || Ok::<_, ()>(vec![epoch]))
+                .unwrap();
+        }
+        let cached = cache
+            .proposers(1, B256::ZERO, || Err("recomputed"))
+            .unwrap();
+        assert_eq!(*cached, vec![1]);
+        assert_eq!(
+            cache.proposers(0, B256::ZERO, || Err("recomputed")),
+            Err("recomputed")
+        );
+        assert_eq!(
+            cache.proposers(1, B256::repeat_byte(1), || Err("recomputed")),
+            Err("recomputed")
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+    time::Duration,
+};
+
+use axum::http::HeaderValue;
+
+pub const DEFAULT_HTTP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+pub const DEFAULT_HTTP_PORT: u16 = 5052;
+/// Fits a JSON Deneb block with the full six blobs with room to spare.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 64;
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpServerConfig {
+    pub address: IpAddr,
+    pub port: u16,
+    /// Origins browsers may call the API from, cross-origin requests are refused when empty.
+    pub allow_origins: Vec<CorsOrigin>,
+    /// Largest request body accepted, in bytes.
+    pub max_body_size: usize,
+    /// Requests handled at once, further requests wait for one to finish.
+    pub max_concurrent_requests: usize,
+    /// Time a handler has to answer a request in before it fails with 408.
+    pub request_timeout: Duration,
+}
+
+impl HttpServerConfig {
+    pub fn socket_address(&self) -> SocketAddr {
+        SocketAddr::new(self.address, self.port)
+    }
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            address: DEFAULT_HTTP_ADDRESS,
+            port: DEFAULT_HTTP_PORT,
+            allow_origins: vec![],
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+/// An origin allowed to make cross-origin requests, parsed from `*` or e.g.
+/// `http://localhost:3000`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigin {
+    Any,
+    Origin(HeaderValue),
+}
+
+impl FromStr for CorsOrigin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(CorsOrigin::Any);
+        }
+        // Browsers send the origin as scheme://host[:port], anything else never matches.
+        let valid = match s.split_once("://") {
+            Some(("http" | "https", host)) => !host.is_empty() && !host.contains('/'),
+            _ => false,
+        };
+        match HeaderValue::from_str(s) {
+            Ok(origin) if valid => Ok(CorsOrigin::Origin(origin)),
+            _ => Err(format!(
+                "invalid origin {s:?}, expected * or scheme://host[:port]"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cors_origin() {
+        assert_eq!("*".parse(), Ok(CorsOrigin::Any));
+        assert_eq!(
+            "http://localhost:3000".parse(),
+            Ok(CorsOrigin::Origin(HeaderValue::from_static(
+                "http://localhost:3000"
+            )))
+        );
+        assert!("localhost:3000".parse::<CorsOrigin>().is_err());
+        assert!("https://example.org/".parse::<CorsOrigin>().is_err());
+        assert!("ftp://example.org".parse::<CorsOrigin>().is_err());
+    }
+}
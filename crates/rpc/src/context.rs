@@ -0,0 +1,136 @@
+use std::sync::{Arc, RwLock};
+
+use alloy_primitives::{aliases::B32, B256};
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock, beacon_state::BeaconState, checkpoint::Checkpoint,
+    fork_schedule::ForkSchedule,
+};
+use ream_discv5::peer_table::PeerTable;
+use ream_fork_choice::proto_array_fork_choice::ProtoArrayForkChoice;
+use ream_operation_pool::{
+    naive_aggregation_pool::NaiveAggregationPool, operation_pool::OperationPool,
+};
+use ream_p2p::subnet_manager::AttestationSubnetManager;
+use ream_storage::{blob_store::BlobStore, hot_cold::HotColdStore, state_store::StateReplayer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::DutiesCache,
+    eth1::SharedEth1Provider,
+    events::EventBroadcaster,
+    payload::PayloadRequester,
+    preparation::ProposerPreparations,
+    publish::{BlockPublisher, OperationPublisher},
+};
+
+pub type SharedReplayer = Arc<dyn StateReplayer<Error = String> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisInfo {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub genesis_time: u64,
+    pub genesis_validators_root: B256,
+    pub genesis_fork_version: B32,
+}
+
+/// What the API knows about the chain, updated by the node whenever the head or the finalized
+/// checkpoint changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainInfo {
+    /// `None` until the node has a genesis or checkpoint state.
+    pub genesis: Option<GenesisInfo>,
+    pub head_root: B256,
+    pub head_slot: u64,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    /// Whether the node is still syncing towards the head of the network.
+    pub is_syncing: bool,
+    /// Whether the payload of the head block hasn't been verified by the execution client yet.
+    pub head_optimistic: bool,
+    /// Whether the execution client is unreachable, blocks are then imported optimistically.
+    pub el_offline: bool,
+}
+
+/// Handles to the node shared by all handlers.
+#[derive(Clone)]
+pub struct ApiContext {
+    pub store: Arc<HotColdStore>,
+    /// Verified blob sidecars, kept by the node in the hot database.
+    pub blobs: BlobStore,
+    pub chain: Arc<RwLock<ChainInfo>>,
+    pub operation_pool: Arc<RwLock<OperationPool>>,
+    /// Unaggregated attestations submitted through the API, aggregated for aggregators.
+    pub naive_aggregation_pool: Arc<RwLock<NaiveAggregationPool>>,
+    /// Subnets the duties of the node's validators need, read by the network to manage its
+    /// subscriptions and peers.
+    pub attestation_subnets: Arc<RwLock<AttestationSubnetManager>>,
+    pub peers: Arc<RwLock<PeerTable>>,
+    /// `None` until the node has an anchor block to start fork choice from.
+    pub fork_choice: Option<Arc<RwLock<ProtoArrayForkChoice>>>,
+    /// Rebuilds states the store doesn't hold in full.
+    pub replayer: SharedReplayer,
+    /// Where published blocks go, `None` while the node can't import blocks.
+    pub block_publisher: Option<BlockPublisher>,
+    /// Where operations accepted into the pool go to be gossiped, `None` without gossipsub.
+    pub operation_publisher: Option<OperationPublisher>,
+    /// Where block production gets execution payloads, `None` without an execution client.
+    pub payload_requester: Option<PayloadRequester>,
+    /// Fee recipients and builder registrations of the validators of the node's clients.
+    pub proposer_preparations: Arc<ProposerPreparations>,
+    /// Where block production gets its eth1 data vote and deposits, `None` without an
+    /// execution client.
+    pub eth1: Option<SharedEth1Provider>,
+    pub fork_schedule: Arc<ForkSchedule>,
+    /// Fed by block import, fork choice and gossip, streamed to `/eth/v1/events` subscribers.
+    pub events: EventBroadcaster,
+    pub duties: Arc<DutiesCache>,
+}
+
+impl ApiContext {
+    /// A context with an empty chain, pool and peer table in front of `store`, for a network
+    /// following `fork_schedule`.
+    pub fn new(store: Arc<HotColdStore>, fork_schedule: ForkSchedule) -> Self {
+        Self {
+            blobs: BlobStore::new(store.hot().clone(), true),
+            store,
+            chain: Arc::default(),
+            operation_pool: Arc::default(),
+            naive_aggregation_pool: Arc::default(),
+            attestation_subnets: Arc::default(),
+            peers: Arc::default(),
+            fork_choice: None,
+            replayer: Arc::new(NoStateTransition),
+            block_publisher: None,
+            operation_publisher: None,
+            payload_requester: None,
+            proposer_preparations: Arc::default(),
+            eth1: None,
+            fork_schedule: Arc::new(fork_schedule),
+            events: EventBroadcaster::default(),
+            duties: Arc::default(),
+        }
+    }
+
+    pub fn chain_info(&self) -> ChainInfo {
+        self.chain.read().expect("chain info lock poisoned").clone()
+    }
+}
+
+/// Replayer of a node without a state transition, only states stored in full can be served.
+pub struct NoStateTransition;
+
+impl StateReplayer for NoStateTransition {
+    type Error = String;
+
+    fn process_slots(&self, _state: &mut BeaconState, _slot: u64) -> Result<(), String> {
+        Err("the node can't replay states without a state transition".to_string())
+    }
+
+    fn process_block(
+        &self,
+        _state: &mut BeaconState,
+        _block: &SignedBeaconBlock,
+    ) -> Result<(), String> {
+        Err("the node can't replay states without a state transition".to_string())
+    }
+}
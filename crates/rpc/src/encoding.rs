@@ -0,0 +1,116 @@
+use axum::{
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use ream_consensus::fork_schedule::ForkName;
+use serde::de::DeserializeOwned;
+use ssz::Decode;
+
+use crate::{error::ApiError, handlers::block::CONSENSUS_VERSION_HEADER};
+
+pub const SSZ_MEDIA_TYPE: &str = "application/octet-stream";
+pub const JSON_MEDIA_TYPE: &str = "application/json";
+
+/// Media type of a header value without its parameters, e.g. `application/json` of
+/// `application/json; charset=utf-8`.
+fn media_type(value: &str) -> &str {
+    value.split(';').next().unwrap_or_default().trim()
+}
+
+/// Whether the client prefers SSZ over JSON, going by the quality values of its `Accept`
+/// header. JSON wins ties and is the default without the header.
+pub fn accepts_ssz(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+    else {
+        return false;
+    };
+    let mut ssz_quality = 0.0;
+    let mut json_quality = 0.0;
+    for range in accept.split(',') {
+        let quality = range
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|quality| quality.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match media_type(range) {
+            SSZ_MEDIA_TYPE => ssz_quality = quality,
+            JSON_MEDIA_TYPE | "application/*" | "*/*" => {
+                json_quality = f32::max(json_quality, quality)
+            }
+            _ => {}
+        }
+    }
+    ssz_quality > json_quality
+}
+
+/// Whether a request body is SSZ, going by its `Content-Type` header.
+pub fn is_ssz_body(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| media_type(content_type) == SSZ_MEDIA_TYPE)
+}
+
+/// Decodes a request body as SSZ if its content type says so and as JSON otherwise, `name`
+/// names the decoded value in errors.
+pub fn decode_body<T: Decode + DeserializeOwned>(
+    headers: &HeaderMap,
+    body: &[u8],
+    name: &str,
+) -> Result<T, ApiError> {
+    if is_ssz_body(headers) {
+        T::from_ssz_bytes(body)
+            .map_err(|err| ApiError::BadRequest(format!("invalid SSZ {name}: {err:?}")))
+    } else {
+        serde_json::from_slice(body)
+            .map_err(|err| ApiError::BadRequest(format!("invalid JSON {name}: {err}")))
+    }
+}
+
+/// SSZ bytes of fork dependent data, with the fork named in a header since the body can't.
+pub fn ssz_response(version: ForkName, bytes: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE.as_str(), SSZ_MEDIA_TYPE.to_string()),
+            (CONSENSUS_VERSION_HEADER, version.to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_accepts_ssz() {
+        let accepts = |accept: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+            accepts_ssz(&headers)
+        };
+        assert!(!accepts_ssz(&HeaderMap::new()));
+        assert!(accepts("application/octet-stream"));
+        assert!(accepts(
+            "application/octet-stream;q=1.0,application/json;q=0.9"
+        ));
+        assert!(!accepts(
+            "application/json;q=1,application/octet-stream;q=0.9"
+        ));
+        assert!(!accepts("application/octet-stream, */*"));
+        assert!(!accepts("application/json"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream; charset=binary"),
+        );
+        assert!(is_ssz_body(&headers));
+    }
+}
@@ -0,0 +1,88 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ream_consensus::committee::CommitteeError;
+use ream_storage::error::StoreError;
+use serde::{Deserialize, Serialize};
+
+/// Error returned by handlers, rendered as the beacon-APIs error message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Unavailable(String),
+    #[error("{0}")]
+    Internal(String),
+    /// Some items of a submitted list were rejected, rendered as an indexed error message.
+    #[error("{message}")]
+    IndexedErrors {
+        message: String,
+        failures: Vec<IndexedError>,
+    },
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) | ApiError::IndexedErrors { .. } => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(err: StoreError) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
+
+impl From<CommitteeError> for ApiError {
+    fn from(err: CommitteeError) -> Self {
+        match err {
+            CommitteeError::EpochOutOfRange { .. } => ApiError::BadRequest(err.to_string()),
+            CommitteeError::NoActiveValidators(_) => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorMessage {
+    pub code: u16,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stacktraces: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<IndexedError>,
+}
+
+/// Why the item at `index` of a submitted list was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedError {
+    pub index: usize,
+    pub message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        let message = self.to_string();
+        let failures = match self {
+            ApiError::IndexedErrors { failures, .. } => failures,
+            _ => vec![],
+        };
+        let body = ErrorMessage {
+            code: status.as_u16(),
+            message,
+            stacktraces: vec![],
+            failures,
+        };
+        (status, Json(body)).into_response()
+    }
+}
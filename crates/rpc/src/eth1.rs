@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use ream_consensus::{
+    beacon_state::BeaconState,
+    constants::{EPOCHS_PER_ETH1_VOTING_PERIOD, SLOTS_PER_EPOCH},
+    deposit::Deposit,
+    eth1_data::Eth1Data,
+};
+
+/// Source of the eth1 data votes and deposits of produced blocks, backed by the node's view of
+/// the deposit contract.
+pub trait Eth1Provider {
+    /// The eth1 data a block proposed on `state` votes for.
+    fn eth1_data_vote(&self, state: &BeaconState) -> Eth1Data;
+
+    /// The deposits following `eth1_deposit_index` a block has to include, with proofs against
+    /// the deposit root of `eth1_data`.
+    fn deposits(
+        &self,
+        eth1_deposit_index: u64,
+        eth1_data: &Eth1Data,
+    ) -> Result<Vec<Deposit>, String>;
+}
+
+pub type SharedEth1Provider = Arc<dyn Eth1Provider + Send + Sync>;
+
+/// The eth1 data of `state` once a block voting for `vote` is processed, which is the vote when
+/// it reaches a majority of the voting period.
+pub fn eth1_data_after_vote(state: &BeaconState, vote: &Eth1Data) -> Eth1Data {
+    let votes = state
+        .eth1_data_votes
+        .iter()
+        .filter(|existing| *existing == vote)
+        .count() as u64
+        + 1;
+    if votes * 2 > EPOCHS_PER_ETH1_VOTING_PERIOD * SLOTS_PER_EPOCH {
+        vote.clone()
+    } else {
+        state.eth1_data.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+
+    use super::*;
+
+    #[test]
+    fn test_eth1_data_after_vote() {
+        let vote = Eth1Data {
+            deposit_count: 1,
+            block_hash: B256::repeat_byte(1),
+            ..Eth1Data::default()
+        };
+        let half = (EPOCHS_PER_ETH1_VOTING_PERIOD * SLOTS_PER_EPOCH / 2) as usize;
+        let mut state = BeaconState {
+            eth1_data_votes: vec![vote.clone(); half - 1].into(),
+            ..BeaconState::default()
+        };
+        assert_eq!(eth1_data_after_vote(&state, &vote), Eth1Data::default());
+
+        state.eth1_data_votes.push(vote.clone()).unwrap();
+        assert_eq!(eth1_data_after_vote(&state, &vote), vote);
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use alloy_primitives::B256;
+use futures::{stream, Stream};
+use ream_consensus::{
+    attestation::Attestation,
+    beacon_block_body::KZGCommitment,
+    blob_sidecar::{kzg_commitment_to_versioned_hash, BlobSidecar},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::error::ApiError;
+
+/// Events kept for subscribers that fall behind, older events are dropped for them.
+pub const DEFAULT_EVENT_BUFFER: usize = 256;
+pub const DEFAULT_MAX_EVENT_CONNECTIONS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventTopic {
+    Head,
+    Block,
+    Attestation,
+    FinalizedCheckpoint,
+    ChainReorg,
+    BlobSidecar,
+}
+
+impl FromStr for EventTopic {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(EventTopic::Head),
+            "block" => Ok(EventTopic::Block),
+            "attestation" => Ok(EventTopic::Attestation),
+            "finalized_checkpoint" => Ok(EventTopic::FinalizedCheckpoint),
+            "chain_reorg" => Ok(EventTopic::ChainReorg),
+            "blob_sidecar" => Ok(EventTopic::BlobSidecar),
+            _ => Err(ApiError::BadRequest(format!("unknown event topic: {s}"))),
+        }
+    }
+}
+
+impl fmt::Display for EventTopic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EventTopic::Head => "head",
+            EventTopic::Block => "block",
+            EventTopic::Attestation => "attestation",
+            EventTopic::FinalizedCheckpoint => "finalized_checkpoint",
+            EventTopic::ChainReorg => "chain_reorg",
+            EventTopic::BlobSidecar => "blob_sidecar",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeadEvent {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub block: B256,
+    pub state: B256,
+    pub epoch_transition: bool,
+    pub previous_duty_dependent_root: B256,
+    pub current_duty_dependent_root: B256,
+    pub execution_optimistic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockEvent {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub block: B256,
+    pub execution_optimistic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalizedCheckpointEvent {
+    pub block: B256,
+    pub state: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: u64,
+    pub execution_optimistic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainReorgEvent {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub depth: u64,
+    pub old_head_block: B256,
+    pub new_head_block: B256,
+    pub old_head_state: B256,
+    pub new_head_state: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: u64,
+    pub execution_optimistic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobSidecarEvent {
+    pub block_root: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub kzg_commitment: KZGCommitment,
+    pub versioned_hash: B256,
+}
+
+impl BlobSidecarEvent {
+    pub fn new(block_root: B256, sidecar: &BlobSidecar) -> Self {
+        Self {
+            block_root,
+            index: sidecar.index,
+            slot: sidecar.signed_block_header.message.slot,
+            kzg_commitment: sidecar.kzg_commitment,
+            versioned_hash: kzg_commitment_to_versioned_hash(&sidecar.kzg_commitment),
+        }
+    }
+}
+
+/// Something that happened in the node that API clients can subscribe to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconEvent {
+    Head(HeadEvent),
+    Block(BlockEvent),
+    Attestation(Box<Attestation>),
+    FinalizedCheckpoint(FinalizedCheckpointEvent),
+    ChainReorg(ChainReorgEvent),
+    BlobSidecar(BlobSidecarEvent),
+}
+
+impl BeaconEvent {
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            BeaconEvent::Head(_) => EventTopic::Head,
+            BeaconEvent::Block(_) => EventTopic::Block,
+            BeaconEvent::Attestation(_) => EventTopic::Attestation,
+            BeaconEvent::FinalizedCheckpoint(_) => EventTopic::FinalizedCheckpoint,
+            BeaconEvent::ChainReorg(_) => EventTopic::ChainReorg,
+            BeaconEvent::BlobSidecar(_) => EventTopic::BlobSidecar,
+        }
+    }
+
+    /// The JSON `data` of the event.
+    pub fn to_json(&self) -> String {
+        match self {
+            BeaconEvent::Head(event) => serde_json::to_string(event),
+            BeaconEvent::Block(event) => serde_json::to_string(event),
+            BeaconEvent::Attestation(event) => serde_json::to_string(event),
+            BeaconEvent::FinalizedCheckpoint(event) => serde_json::to_string(event),
+            BeaconEvent::ChainReorg(event) => serde_json::to_string(event),
+            BeaconEvent::BlobSidecar(event) => serde_json::to_string(event),
+        }
+        .expect("events serialize to JSON")
+    }
+}
+
+/// Fans events out from block import, fork choice and gossip to API subscribers.
+///
+/// Each subscriber has a bounded buffer, a subscriber that can't keep up misses events rather
+/// than slowing down the node. The number of subscribers is capped as each holds a connection
+/// open.
+#[derive(Debug, Clone)]
+pub struct EventBroadcaster {
+    sender: broadcast::Sender<BeaconEvent>,
+    connections: Arc<AtomicUsize>,
+    max_connections: usize,
+}
+
+impl EventBroadcaster {
+    pub fn new(buffer: usize, max_connections: usize) -> Self {
+        Self {
+            sender: broadcast::channel(buffer).0,
+            connections: Arc::default(),
+            max_connections,
+        }
+    }
+
+    /// Sends `event` to every subscriber, doing nothing if there are none.
+    pub fn send(&self, event: BeaconEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// `None` if the maximum number of subscribers is reached.
+    pub fn subscribe(&self) -> Option<EventSubscription> {
+        self.connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |connections| {
+                (connections < self.max_connections).then_some(connections + 1)
+            })
+            .ok()?;
+        Some(EventSubscription {
+            receiver: self.sender.subscribe(),
+            connections: self.connections.clone(),
+        })
+    }
+
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Acquire)
+    }
+}
+
+impl Default for EventBroadcaster {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER, DEFAULT_MAX_EVENT_CONNECTIONS)
+    }
+}
+
+/// A subscriber's receiver, freeing its connection slot when dropped.
+#[derive(Debug)]
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<BeaconEvent>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl EventSubscription {
+    /// The events of `topics`, ending when the broadcaster is dropped.
+    pub fn into_stream(self, topics: Vec<EventTopic>) -> impl Stream<Item = BeaconEvent> {
+        stream::unfold(self, move |mut subscription| {
+            let topics = topics.clone();
+            async move {
+                loop {
+                    match subscription.receiver.recv().await {
+                        Ok(event) if topics.contains(&event.topic()) => {
+                            return Some((event, subscription))
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!("Event subscriber fell behind and missed {missed} events")
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    fn block_event(slot: u64) -> BeaconEvent {
+        BeaconEvent::Block(BlockEvent {
+            slot,
+            block: B256::with_last_byte(slot as u8),
+            execution_optimistic: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_subscription() {
+        let events = EventBroadcaster::new(2, 1);
+        let subscription = events.subscribe().unwrap();
+        assert!(events.subscribe().is_none());
+
+        let mut stream = Box::pin(subscription.into_stream(vec![EventTopic::Block]));
+        events.send(BeaconEvent::FinalizedCheckpoint(FinalizedCheckpointEvent {
+            block: B256::ZERO,
+            state: B256::ZERO,
+            epoch: 0,
+            execution_optimistic: false,
+        }));
+        events.send(block_event(1));
+        assert_eq!(stream.next().await, Some(block_event(1)));
+
+        // The buffer holds two events, the oldest are dropped for a subscriber that lags.
+        for slot in 2..6 {
+            events.send(block_event(slot));
+        }
+        assert_eq!(stream.next().await, Some(block_event(4)));
+        assert_eq!(stream.next().await, Some(block_event(5)));
+
+        drop(stream);
+        assert_eq!(events.connections(), 0);
+        assert!(events.subscribe().is_some());
+    }
+}
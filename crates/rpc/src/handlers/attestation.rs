@@ -0,0 +1,397 @@
+use alloy_primitives::B256;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ream_consensus::{
+    aggregate_and_proof::SignedAggregateAndProof,
+    attestation::Attestation,
+    attestation_data::AttestationData,
+    beacon_state::BeaconState,
+    checkpoint::Checkpoint,
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch},
+    subnet::SubnetId,
+};
+use ream_fork_choice::attestation_data_check::verify_attestation_data;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    duties::{block_root_at_slot, epoch_committees},
+    pool::{list_result, submit},
+    state::resolve_state,
+};
+use crate::{
+    context::ApiContext, error::ApiError, id::StateId, publish::GossipOperation,
+    response::DataResponse,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct AttestationDataQuery {
+    pub slot: u64,
+    pub committee_index: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct AggregateAttestationQuery {
+    pub attestation_data_root: B256,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconCommitteeSubscription {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committee_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committees_at_slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub is_aggregator: bool,
+}
+
+/// `GET /eth/v1/validator/attestation_data`
+///
+/// Attests to the head, for a slot from the previous epoch of the head up to the next one.
+pub async fn get_attestation_data(
+    State(context): State<ApiContext>,
+    Query(query): Query<AttestationDataQuery>,
+) -> Result<Json<DataResponse<AttestationData>>, ApiError> {
+    if context.chain_info().is_syncing {
+        return Err(ApiError::Unavailable(
+            "the node is syncing, attestation data can't be produced".to_string(),
+        ));
+    }
+    let head = resolve_state(&context, StateId::Head)?;
+    let head_epoch = compute_epoch_at_slot(head.state.slot);
+    let epoch = compute_epoch_at_slot(query.slot);
+    if epoch + 1 < head_epoch || epoch > head_epoch + 1 {
+        return Err(ApiError::BadRequest(format!(
+            "slot {} is too far from the head slot {}",
+            query.slot, head.state.slot
+        )));
+    }
+    let committees = epoch_committees(&context, &head.state, epoch)?;
+    if query.committee_index >= committees.committees_per_slot() {
+        return Err(ApiError::BadRequest(format!(
+            "committee index {} out of {} committees per slot",
+            query.committee_index,
+            committees.committees_per_slot()
+        )));
+    }
+
+    let source = if epoch < head_epoch {
+        head.state.previous_justified_checkpoint
+    } else if epoch == head_epoch {
+        head.state.current_justified_checkpoint
+    } else {
+        justified_after_epoch_transition(&context, &head.state, epoch)?
+    };
+    let data = AttestationData {
+        slot: query.slot,
+        index: query.committee_index,
+        beacon_block_root: block_root_at_slot(&head, query.slot)?,
+        source,
+        target: Checkpoint {
+            epoch,
+            root: block_root_at_slot(&head, compute_start_slot_at_epoch(epoch))?,
+        },
+    };
+    // Validators sign what they are given, data fork choice disagrees with isn't handed out.
+    if let Some(fork_choice) = &context.fork_choice {
+        let fork_choice = fork_choice.read().expect("fork choice lock poisoned");
+        verify_attestation_data(fork_choice.proto_array(), &data).map_err(|err| {
+            ApiError::Unavailable(format!(
+                "attestation data is inconsistent with fork choice: {err}"
+            ))
+        })?;
+    }
+    Ok(Json(DataResponse::new(data)))
+}
+
+/// Current justified checkpoint of `state` once advanced into `epoch`, which the epoch
+/// transition may have justified.
+fn justified_after_epoch_transition(
+    context: &ApiContext,
+    state: &BeaconState,
+    epoch: u64,
+) -> Result<Checkpoint, ApiError> {
+    let mut state = state.clone();
+    let slot = compute_start_slot_at_epoch(epoch);
+    context
+        .replayer
+        .process_slots(&mut state, slot)
+        .map_err(|err| {
+            ApiError::Unavailable(format!(
+                "can't advance the head state to slot {slot}: {err}"
+            ))
+        })?;
+    Ok(state.current_justified_checkpoint)
+}
+
+/// `GET /eth/v1/validator/aggregate_attestation`
+pub async fn get_aggregate_attestation(
+    State(context): State<ApiContext>,
+    Query(query): Query<AggregateAttestationQuery>,
+) -> Result<Json<DataResponse<Attestation>>, ApiError> {
+    let pool = context
+        .naive_aggregation_pool
+        .read()
+        .expect("naive aggregation pool lock poisoned");
+    match pool.get_by_root(&query.attestation_data_root) {
+        Some(aggregate) if aggregate.data.slot == query.slot => {
+            Ok(Json(DataResponse::new(aggregate.clone())))
+        }
+        _ => Err(ApiError::NotFound(format!(
+            "no aggregate for attestation data {} at slot {}",
+            query.attestation_data_root, query.slot
+        ))),
+    }
+}
+
+/// `POST /eth/v1/validator/aggregate_and_proofs`
+pub async fn post_aggregate_and_proofs(
+    State(context): State<ApiContext>,
+    Json(aggregates): Json<Vec<SignedAggregateAndProof>>,
+) -> Result<(), ApiError> {
+    list_result(
+        submit(
+            &context,
+            aggregates,
+            |validator, context, signed| {
+                let aggregate = &signed.message.aggregate;
+                validator
+                    .validate_attestation(aggregate, |root| {
+                        context.store.get_block(root).ok().flatten().is_some()
+                    })
+                    .map_err(|err| err.to_string())?;
+                let data = &aggregate.data;
+                let committees = epoch_committees(context, validator.state(), data.target.epoch)
+                    .map_err(|err| err.to_string())?;
+                let committee = committees
+                    .get_beacon_committee(data.slot, data.index)
+                    .ok_or_else(|| format!("no committee {} at slot {}", data.index, data.slot))?;
+                validator
+                    .validate_aggregate_and_proof(signed, committee)
+                    .map_err(|err| err.to_string())
+            },
+            |pool, signed| pool.insert_attestation(signed.message.aggregate),
+            GossipOperation::AggregateAndProof,
+        )
+        .await?,
+    )
+}
+
+/// `POST /eth/v1/validator/beacon_committee_subscriptions`
+///
+/// Every duty needs peers on the subnet of its committee, aggregators also subscribe to it to
+/// collect the attestations of the committee.
+pub async fn post_beacon_committee_subscriptions(
+    State(context): State<ApiContext>,
+    Json(subscriptions): Json<Vec<BeaconCommitteeSubscription>>,
+) -> Result<(), ApiError> {
+    if let Some(subscription) = subscriptions
+        .iter()
+        .find(|subscription| subscription.committee_index >= subscription.committees_at_slot)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "committee index {} out of {} committees at slot {}",
+            subscription.committee_index, subscription.committees_at_slot, subscription.slot
+        )));
+    }
+
+    let mut manager = context
+        .attestation_subnets
+        .write()
+        .expect("attestation subnets lock poisoned");
+    for subscription in subscriptions {
+        let subnet = SubnetId::compute_for_attestation(
+            subscription.committees_at_slot,
+            subscription.slot,
+            subscription.committee_index,
+        );
+        manager.add_duty(subnet, subscription.slot, subscription.is_aggregator);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_block_header::BeaconBlockHeader,
+        bls::BLSSignature,
+        constants::{FAR_FUTURE_EPOCH, MAX_EFFECTIVE_BALANCE, SLOTS_PER_EPOCH},
+        fork_schedule::ForkSchedule,
+        validator::Validator,
+    };
+    use ream_fork_choice::{
+        proto_array::{Block, ExecutionStatus},
+        proto_array_fork_choice::ProtoArrayForkChoice,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+    use ssz_types::{BitList, VariableList};
+    use tree_hash::TreeHash;
+
+    use super::*;
+    use crate::context::ChainInfo;
+
+    #[tokio::test]
+    async fn test_attestation_data_and_subscriptions() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+
+        let head_slot = 2 * SLOTS_PER_EPOCH + 3;
+        let message = BeaconBlock {
+            slot: head_slot,
+            state_root: B256::repeat_byte(1),
+            ..BeaconBlock::default()
+        };
+        let validator = Validator {
+            effective_balance: MAX_EFFECTIVE_BALANCE,
+            exit_epoch: FAR_FUTURE_EPOCH,
+            ..Validator::default()
+        };
+        let mut state = BeaconState {
+            slot: head_slot,
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            validators: VariableList::new(vec![validator; 64]).unwrap(),
+            current_justified_checkpoint: Checkpoint {
+                epoch: 1,
+                root: B256::repeat_byte(9),
+            },
+            ..BeaconState::default()
+        };
+        for (slot, root) in state.block_roots.iter_mut().enumerate() {
+            *root = B256::with_last_byte(slot as u8);
+        }
+        context
+            .store
+            .hot_states()
+            .put_snapshot(message.state_root, &state)
+            .unwrap();
+        let head_root = context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root,
+            head_slot,
+            ..ChainInfo::default()
+        };
+
+        let query = AttestationDataQuery {
+            slot: head_slot,
+            committee_index: 0,
+        };
+        let Json(data) = get_attestation_data(State(context.clone()), Query(query))
+            .await
+            .unwrap();
+        let start_slot = compute_start_slot_at_epoch(2);
+        assert_eq!(
+            data.data,
+            AttestationData {
+                slot: head_slot,
+                index: 0,
+                beacon_block_root: head_root,
+                source: state.current_justified_checkpoint,
+                target: Checkpoint {
+                    epoch: 2,
+                    root: B256::with_last_byte(start_slot as u8),
+                },
+            }
+        );
+        let out_of_range = AttestationDataQuery {
+            committee_index: 1,
+            ..query
+        };
+        assert!(matches!(
+            get_attestation_data(State(context.clone()), Query(out_of_range)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        // Fork choice doesn't know the head block, so the data isn't handed out.
+        let fork_choice = ProtoArrayForkChoice::new(
+            Block {
+                slot: 0,
+                root: B256::repeat_byte(9),
+                parent_root: B256::ZERO,
+                justified_checkpoint: Checkpoint::default(),
+                finalized_checkpoint: Checkpoint::default(),
+                execution_status: ExecutionStatus::Irrelevant(false),
+            },
+            Checkpoint::default(),
+            Checkpoint::default(),
+        )
+        .unwrap();
+        let mut with_fork_choice = context.clone();
+        with_fork_choice.fork_choice = Some(Arc::new(RwLock::new(fork_choice)));
+        assert!(matches!(
+            get_attestation_data(State(with_fork_choice), Query(query)).await,
+            Err(ApiError::Unavailable(_))
+        ));
+
+        let mut aggregation_bits = BitList::with_capacity(2).unwrap();
+        aggregation_bits.set(1, true).unwrap();
+        let attestation = Attestation {
+            aggregation_bits,
+            data: data.data,
+            signature: BLSSignature::default(),
+        };
+        context
+            .naive_aggregation_pool
+            .write()
+            .unwrap()
+            .insert(&attestation)
+            .unwrap();
+        let query = AggregateAttestationQuery {
+            attestation_data_root: data.data.tree_hash_root(),
+            slot: head_slot,
+        };
+        let Json(aggregate) = get_aggregate_attestation(State(context.clone()), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(aggregate.data, attestation);
+        let wrong_slot = AggregateAttestationQuery {
+            slot: head_slot + 1,
+            ..query
+        };
+        assert!(matches!(
+            get_aggregate_attestation(State(context.clone()), Query(wrong_slot)).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        let subscription = BeaconCommitteeSubscription {
+            validator_index: 5,
+            committee_index: 1,
+            committees_at_slot: 2,
+            slot: head_slot + 1,
+            is_aggregator: true,
+        };
+        post_beacon_committee_subscriptions(State(context.clone()), Json(vec![subscription]))
+            .await
+            .unwrap();
+        let subnet = SubnetId::compute_for_attestation(2, head_slot + 1, 1);
+        let manager = context.attestation_subnets.read().unwrap();
+        assert_eq!(manager.subscribed_subnets(head_slot), vec![subnet]);
+        assert_eq!(manager.subscribed_subnets(head_slot + 2), vec![]);
+    }
+}
@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, RawQuery, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ream_consensus::{blob_sidecar::BlobSidecar, fork_schedule::ForkName};
+use ssz::Encode;
+
+use super::block::resolve_block;
+use crate::{
+    context::ApiContext,
+    encoding::{accepts_ssz, ssz_response},
+    error::ApiError,
+    query::query_values,
+};
+
+/// `GET /eth/v1/beacon/blob_sidecars/{block_id}`, as JSON or SSZ.
+///
+/// Serves the sidecars of every blob of the block, or of those in `indices`. Blobs are only
+/// kept for the data availability window, a block whose blobs were pruned is not found.
+pub async fn get_blob_sidecars(
+    State(context): State<ApiContext>,
+    Path(block_id): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let indices = query_values(query.as_deref(), "indices")
+        .iter()
+        .map(|index| {
+            index
+                .parse::<u64>()
+                .map_err(|_| ApiError::BadRequest(format!("invalid blob index: {index}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let block = resolve_block(&context, block_id.parse()?)?;
+    let commitments = block.block.message.body.blob_kzg_commitments.len() as u64;
+    if let Some(index) = indices.iter().find(|index| **index >= commitments) {
+        return Err(ApiError::BadRequest(format!(
+            "blob index {index} out of the {commitments} blobs of the block"
+        )));
+    }
+
+    let sidecars = context.blobs.get_blob_sidecars(block.root)?;
+    if (sidecars.len() as u64) < commitments {
+        return Err(ApiError::NotFound(format!(
+            "blob sidecars of block {} are not available",
+            block.root
+        )));
+    }
+    let sidecars: Vec<BlobSidecar> = sidecars
+        .into_iter()
+        .filter(|sidecar| indices.is_empty() || indices.contains(&sidecar.index))
+        .collect();
+
+    if accepts_ssz(&headers) {
+        return Ok(ssz_response(ForkName::Deneb, sidecars.as_ssz_bytes()));
+    }
+    Ok(Json(block.versioned_response(sidecars)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::http::{header, HeaderValue};
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_block_body::{BeaconBlockBody, KZGCommitment},
+        beacon_block_header::SignedBeaconBlockHeader,
+        fork_schedule::ForkSchedule,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+    use ssz::Decode;
+
+    use super::*;
+    use crate::context::ChainInfo;
+
+    #[tokio::test]
+    async fn test_get_blob_sidecars() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+
+        let message = BeaconBlock {
+            slot: 1,
+            body: BeaconBlockBody {
+                blob_kzg_commitments: vec![KZGCommitment::repeat_byte(1); 2].into(),
+                ..BeaconBlockBody::default()
+            },
+            ..BeaconBlock::default()
+        };
+        let sidecars: Vec<_> = (0..2)
+            .map(|index| BlobSidecar {
+                index,
+                signed_block_header: SignedBeaconBlockHeader {
+                    message: message.block_header(),
+                    ..SignedBeaconBlockHeader::default()
+                },
+                ..BlobSidecar::default()
+            })
+            .collect();
+        let root = context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        context.store.hot_blocks().set_canonical_head(root).unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root: root,
+            head_slot: 1,
+            ..ChainInfo::default()
+        };
+
+        let get = |query: &str, accept: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+            get_blob_sidecars(
+                State(context.clone()),
+                Path("head".to_string()),
+                RawQuery(Some(query.to_string())),
+                headers,
+            )
+        };
+        // Blobs not stored yet.
+        assert!(matches!(
+            get("", "application/json").await,
+            Err(ApiError::NotFound(_))
+        ));
+        for sidecar in &sidecars {
+            context.blobs.put_blob_sidecar(sidecar).unwrap();
+        }
+
+        let response = get("indices=1", "application/octet-stream").await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            Vec::<BlobSidecar>::from_ssz_bytes(&body).unwrap(),
+            sidecars[1..]
+        );
+
+        let response = get("", "application/json").await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["data"].as_array().unwrap().len(), 2);
+        assert_eq!(json["data"][1]["index"], "1");
+
+        assert!(matches!(
+            get("indices=2", "application/json").await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,512 @@
+use std::sync::Arc;
+
+use alloy_primitives::B256;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock, beacon_block_header::SignedBeaconBlockHeader,
+    fork_schedule::ForkName, misc::compute_start_slot_at_epoch,
+};
+use ream_storage::tables::BlockSummaries;
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+use tokio::sync::oneshot;
+use tracing::{debug, debug_span, Instrument, Span};
+use tree_hash::TreeHash;
+
+use super::state::RootData;
+use crate::{
+    context::ApiContext,
+    encoding::{accepts_ssz, decode_body, ssz_response},
+    error::ApiError,
+    id::BlockId,
+    publish::{BroadcastValidation, PublishBlockRequest, PublishOutcome},
+    response::{BeaconResponse, VersionedResponse},
+};
+
+pub const CONSENSUS_VERSION_HEADER: &str = "Eth-Consensus-Version";
+
+/// A block looked up by [`BlockId`].
+#[derive(Debug, Clone)]
+pub struct ResolvedBlock {
+    pub root: B256,
+    pub block: SignedBeaconBlock,
+    pub canonical: bool,
+    pub execution_optimistic: bool,
+    pub finalized: bool,
+}
+
+impl ResolvedBlock {
+    pub fn response<T>(&self, data: T) -> BeaconResponse<T> {
+        BeaconResponse {
+            execution_optimistic: self.execution_optimistic,
+            finalized: self.finalized,
+            data,
+        }
+    }
+
+    pub fn versioned_response<T>(&self, data: T) -> VersionedResponse<T> {
+        VersionedResponse {
+            version: ForkName::Deneb,
+            execution_optimistic: self.execution_optimistic,
+            finalized: self.finalized,
+            data,
+        }
+    }
+
+    pub fn header(&self) -> BlockHeaderData {
+        BlockHeaderData {
+            root: self.root,
+            canonical: self.canonical,
+            header: SignedBeaconBlockHeader {
+                message: self.block.message.block_header(),
+                signature: self.block.signature,
+            },
+        }
+    }
+}
+
+/// Finds the block `block_id` refers to, slots are looked up on the canonical chain of the
+/// fork choice head.
+pub fn resolve_block(context: &ApiContext, block_id: BlockId) -> Result<ResolvedBlock, ApiError> {
+    let chain = context.chain_info();
+    let root = match block_id {
+        BlockId::Head => chain.head_root,
+        BlockId::Genesis => canonical_root_at_slot(context, 0)?,
+        BlockId::Finalized => chain.finalized_checkpoint.root,
+        BlockId::Justified => chain.justified_checkpoint.root,
+        BlockId::Slot(slot) => canonical_root_at_slot(context, slot)?,
+        BlockId::Root(root) => root,
+    };
+    let block = context
+        .store
+        .get_block(&root)?
+        .ok_or_else(|| ApiError::NotFound(format!("block {block_id} not found")))?;
+    block_with_flags(context, root, block)
+}
+
+fn canonical_root_at_slot(context: &ApiContext, slot: u64) -> Result<B256, ApiError> {
+    let not_found = || ApiError::NotFound(format!("no canonical block at slot {slot}"));
+    let root = context
+        .store
+        .get_block_root_at_slot(slot)?
+        .ok_or_else(not_found)?;
+    // The index points skipped slots at the last block before them.
+    match context.store.get_block(&root)? {
+        Some(block) if block.message.slot == slot => Ok(root),
+        _ => Err(not_found()),
+    }
+}
+
+/// A block is finalized when it's canonical and not after the finalized checkpoint, the rest
+/// inherit the optimistic flag of the head like states do.
+fn block_with_flags(
+    context: &ApiContext,
+    root: B256,
+    block: SignedBeaconBlock,
+) -> Result<ResolvedBlock, ApiError> {
+    let chain = context.chain_info();
+    let slot = block.message.slot;
+    let canonical = context.store.get_block_root_at_slot(slot)? == Some(root);
+    let finalized =
+        canonical && slot <= compute_start_slot_at_epoch(chain.finalized_checkpoint.epoch);
+    Ok(ResolvedBlock {
+        root,
+        block,
+        canonical,
+        execution_optimistic: chain.head_optimistic && !finalized,
+        finalized,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeaderData {
+    pub root: B256,
+    pub canonical: bool,
+    pub header: SignedBeaconBlockHeader,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HeadersQuery {
+    pub slot: Option<u64>,
+    pub parent_root: Option<B256>,
+}
+
+/// `GET /eth/v2/beacon/blocks/{block_id}`, as JSON or SSZ.
+pub async fn get_block(
+    State(context): State<ApiContext>,
+    Path(block_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let block = resolve_block(&context, block_id.parse()?)?;
+    if accepts_ssz(&headers) {
+        return Ok(ssz_response(ForkName::Deneb, block.block.as_ssz_bytes()));
+    }
+    Ok(Json(block.versioned_response(block.block.clone())).into_response())
+}
+
+/// `GET /eth/v1/beacon/blocks/{block_id}/root`
+pub async fn get_block_root(
+    State(context): State<ApiContext>,
+    Path(block_id): Path<String>,
+) -> Result<Json<BeaconResponse<RootData>>, ApiError> {
+    let block = resolve_block(&context, block_id.parse()?)?;
+    Ok(Json(block.response(RootData { root: block.root })))
+}
+
+/// `GET /eth/v1/beacon/headers/{block_id}`
+pub async fn get_block_header(
+    State(context): State<ApiContext>,
+    Path(block_id): Path<String>,
+) -> Result<Json<BeaconResponse<BlockHeaderData>>, ApiError> {
+    let block = resolve_block(&context, block_id.parse()?)?;
+    Ok(Json(block.response(block.header())))
+}
+
+/// `GET /eth/v1/beacon/headers`, the head by default, otherwise the blocks at `slot` and the
+/// children of `parent_root`.
+///
+/// Non-canonical blocks are only found in the hot database, which holds everything that isn't
+/// finalized.
+pub async fn get_block_headers(
+    State(context): State<ApiContext>,
+    Query(query): Query<HeadersQuery>,
+) -> Result<Json<BeaconResponse<Vec<BlockHeaderData>>>, ApiError> {
+    if query.slot.is_none() && query.parent_root.is_none() {
+        let head = resolve_block(&context, BlockId::Head)?;
+        return Ok(Json(head.response(vec![head.header()])));
+    }
+
+    let mut roots = vec![];
+    if let Some(slot) = query.slot {
+        if let Ok(root) = canonical_root_at_slot(&context, slot) {
+            roots.push(root);
+        }
+    }
+    context
+        .store
+        .hot()
+        .iterate::<BlockSummaries>(&B256::ZERO, |root, summary| {
+            if query.slot.map_or(true, |slot| summary.slot == slot)
+                && query
+                    .parent_root
+                    .map_or(true, |parent_root| summary.parent_root == parent_root)
+                && !roots.contains(&root)
+            {
+                roots.push(root);
+            }
+            true
+        })?;
+
+    let mut headers = vec![];
+    for root in roots {
+        let Some(block) = context.store.get_block(&root)? else {
+            continue;
+        };
+        if query
+            .parent_root
+            .is_some_and(|parent_root| block.message.parent_root != parent_root)
+        {
+            continue;
+        }
+        headers.push(block_with_flags(&context, root, block)?);
+    }
+    headers.sort_by_key(|block| (block.block.message.slot, !block.canonical));
+
+    // The response is only as final as its least final header.
+    Ok(Json(BeaconResponse {
+        execution_optimistic: headers.iter().any(|block| block.execution_optimistic),
+        finalized: !headers.is_empty() && headers.iter().all(|block| block.finalized),
+        data: headers.iter().map(ResolvedBlock::header).collect(),
+    }))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct PublishQuery {
+    #[serde(default)]
+    pub broadcast_validation: BroadcastValidation,
+}
+
+/// Checks what can be checked without the block's pre-state and hands the block to the node.
+///
+/// Returns 200 once the block is broadcast and imported and 202 if it was broadcast but failed
+/// to import. Blocks already known are accepted without being published again.
+async fn publish_block(
+    context: &ApiContext,
+    block: SignedBeaconBlock,
+    validation: BroadcastValidation,
+) -> Result<StatusCode, ApiError> {
+    let root = block.message.tree_hash_root();
+    let span = debug_span!("block", %root, slot = block.message.slot, source = "api");
+    submit_block(context, root, block, validation)
+        .instrument(span)
+        .await
+}
+
+/// The checks and hand over of [`publish_block`], in the span that follows the block through
+/// its import.
+async fn submit_block(
+    context: &ApiContext,
+    root: B256,
+    block: SignedBeaconBlock,
+    validation: BroadcastValidation,
+) -> Result<StatusCode, ApiError> {
+    let chain = context.chain_info();
+    let message = &block.message;
+    if context.store.get_block(&root)?.is_some() {
+        return Ok(StatusCode::OK);
+    }
+    let finalized_slot = compute_start_slot_at_epoch(chain.finalized_checkpoint.epoch);
+    if message.slot <= finalized_slot {
+        return Err(ApiError::BadRequest(format!(
+            "block slot {} is not after the finalized slot {finalized_slot}",
+            message.slot
+        )));
+    }
+    if context.store.get_block(&message.parent_root)?.is_none() {
+        return Err(ApiError::BadRequest(format!(
+            "parent block {} is unknown",
+            message.parent_root
+        )));
+    }
+
+    let publisher = context
+        .block_publisher
+        .as_ref()
+        .ok_or_else(|| ApiError::Unavailable("the node can't import blocks yet".to_string()))?;
+    let (result, outcome) = oneshot::channel();
+    let unavailable = || ApiError::Unavailable("block import has shut down".to_string());
+    debug!(?validation, "Publishing block");
+    publisher
+        .send(PublishBlockRequest {
+            block: Arc::new(block),
+            validation,
+            result,
+            span: Span::current(),
+        })
+        .await
+        .map_err(|_| unavailable())?;
+    match outcome.await.map_err(|_| unavailable())? {
+        Ok(PublishOutcome::Imported) => Ok(StatusCode::OK),
+        Ok(PublishOutcome::BroadcastOnly(reason)) => {
+            debug!(%reason, "Block broadcast but not imported");
+            Ok(StatusCode::ACCEPTED)
+        }
+        Err(reason) => {
+            debug!(%reason, "Block rejected");
+            Err(ApiError::BadRequest(reason))
+        }
+    }
+}
+
+/// `POST /eth/v1/beacon/blocks`
+pub async fn post_block(
+    State(context): State<ApiContext>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let block = decode_body(&headers, &body, "block")?;
+    publish_block(&context, block, BroadcastValidation::Gossip).await
+}
+
+/// `POST /eth/v2/beacon/blocks`, which names the fork of the block in a header and lets the
+/// caller choose the validation done before broadcasting.
+pub async fn post_block_v2(
+    State(context): State<ApiContext>,
+    Query(query): Query<PublishQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let version = headers
+        .get(CONSENSUS_VERSION_HEADER)
+        .and_then(|version| version.to_str().ok())
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!("missing {CONSENSUS_VERSION_HEADER} header"))
+        })?;
+    if version != ForkName::Deneb.to_string() {
+        return Err(ApiError::BadRequest(format!(
+            "unsupported consensus version: {version}"
+        )));
+    }
+    let block = decode_body(&headers, &body, "block")?;
+    publish_block(&context, block, query.broadcast_validation).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::{
+        beacon_block::BeaconBlock, checkpoint::Checkpoint, fork_schedule::ForkSchedule,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+    use crate::context::ChainInfo;
+
+    fn put_block(context: &ApiContext, slot: u64, parent_root: B256, tag: u8) -> B256 {
+        context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot,
+                    parent_root,
+                    proposer_index: tag as u64,
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_blocks_and_headers() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+
+        // 0 <- 1 <- 3, with a fork 1 <- 2'.
+        let genesis = put_block(&context, 0, B256::ZERO, 0);
+        let a = put_block(&context, 1, genesis, 1);
+        let fork = put_block(&context, 2, a, 2);
+        let head = put_block(&context, 3, a, 3);
+        context.store.hot_blocks().set_canonical_head(head).unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root: head,
+            head_slot: 3,
+            finalized_checkpoint: Checkpoint {
+                epoch: 0,
+                root: genesis,
+            },
+            ..ChainInfo::default()
+        };
+
+        let block = resolve_block(&context, BlockId::Head).unwrap();
+        assert_eq!(block.root, head);
+        assert!(block.canonical && !block.finalized);
+        let block = resolve_block(&context, BlockId::Genesis).unwrap();
+        assert_eq!(block.root, genesis);
+        assert!(block.finalized);
+        assert!(
+            !resolve_block(&context, BlockId::Root(fork))
+                .unwrap()
+                .canonical
+        );
+        assert!(matches!(
+            resolve_block(&context, BlockId::Slot(2)),
+            Err(ApiError::NotFound(_))
+        ));
+
+        let headers = |slot, parent_root| {
+            get_block_headers(
+                State(context.clone()),
+                Query(HeadersQuery { slot, parent_root }),
+            )
+        };
+        let Json(response) = headers(None, None).await.unwrap();
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].root, head);
+
+        let Json(response) = headers(None, Some(a)).await.unwrap();
+        let roots: Vec<_> = response.data.iter().map(|header| header.root).collect();
+        assert_eq!(roots, [fork, head]);
+        assert!(!response.data[0].canonical);
+
+        let Json(response) = headers(Some(2), None).await.unwrap();
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].root, fork);
+    }
+
+    #[tokio::test]
+    async fn test_publish_block() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let mut context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        let genesis = put_block(&context, 0, B256::ZERO, 0);
+        let block = |slot, parent_root| SignedBeaconBlock {
+            message: BeaconBlock {
+                slot,
+                parent_root,
+                ..BeaconBlock::default()
+            },
+            ..SignedBeaconBlock::default()
+        };
+        let publish = |context: &ApiContext, block: &SignedBeaconBlock| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONSENSUS_VERSION_HEADER, "deneb".parse().unwrap());
+            post_block_v2(
+                State(context.clone()),
+                Query(PublishQuery {
+                    broadcast_validation: BroadcastValidation::Consensus,
+                }),
+                headers,
+                serde_json::to_vec(block).unwrap().into(),
+            )
+        };
+
+        assert!(matches!(
+            publish(&context, &block(1, genesis)).await,
+            Err(ApiError::Unavailable(_))
+        ));
+
+        let (sender, mut requests) = tokio::sync::mpsc::channel(1);
+        context.block_publisher = Some(sender);
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                let PublishBlockRequest {
+                    block,
+                    validation,
+                    result,
+                    span,
+                } = request;
+                let _span = span.entered();
+                assert_eq!(validation, BroadcastValidation::Consensus);
+                let _ = result.send(match block.message.slot {
+                    1 => Ok(PublishOutcome::Imported),
+                    2 => Ok(PublishOutcome::BroadcastOnly("invalid".to_string())),
+                    _ => Err("invalid signature".to_string()),
+                });
+            }
+        });
+
+        assert_eq!(
+            publish(&context, &block(1, genesis)).await,
+            Ok(StatusCode::OK)
+        );
+        assert_eq!(
+            publish(&context, &block(2, genesis)).await,
+            Ok(StatusCode::ACCEPTED)
+        );
+        assert!(matches!(
+            publish(&context, &block(3, genesis)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            publish(&context, &block(1, B256::repeat_byte(1))).await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert_eq!(
+            publish(&context, &block(0, B256::ZERO)).await,
+            Ok(StatusCode::OK),
+            "known blocks are accepted"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+
+use alloy_primitives::B256;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ream_consensus::{checkpoint::Checkpoint, fork_schedule::ForkName};
+use ream_fork_choice::proto_array::{ExecutionStatus, ProtoArray, ProtoNode};
+use serde::{Deserialize, Serialize};
+use ssz::Encode;
+
+use super::state::resolve_state;
+use crate::{
+    context::ApiContext,
+    encoding::{accepts_ssz, ssz_response},
+    error::ApiError,
+    response::{DataResponse, VersionedResponse},
+};
+
+/// `GET /eth/v2/debug/beacon/states/{state_id}`, as JSON or SSZ.
+pub async fn get_debug_state(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let state = resolve_state(&context, state_id.parse()?)?;
+    let version = ForkName::Deneb;
+    if accepts_ssz(&headers) {
+        return Ok(ssz_response(version, state.state.as_ssz_bytes()));
+    }
+    Ok(Json(VersionedResponse {
+        version,
+        execution_optimistic: state.execution_optimistic,
+        finalized: state.finalized,
+        data: state.state.as_ref(),
+    })
+    .into_response())
+}
+
+fn fork_choice_unavailable() -> ApiError {
+    ApiError::Unavailable("fork choice is not initialized".to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHead {
+    pub root: B256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub execution_optimistic: bool,
+}
+
+/// Blocks of the fork choice tree without children.
+fn leaves(proto_array: &ProtoArray) -> impl Iterator<Item = &ProtoNode> {
+    let parents: HashSet<usize> = proto_array
+        .nodes
+        .iter()
+        .filter_map(|node| node.parent)
+        .collect();
+    proto_array
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(move |(index, _)| !parents.contains(index))
+        .map(|(_, node)| node)
+}
+
+/// `GET /eth/v2/debug/beacon/heads`, the tip of every fork fork choice knows about.
+pub async fn get_debug_heads(
+    State(context): State<ApiContext>,
+) -> Result<Json<DataResponse<Vec<ChainHead>>>, ApiError> {
+    let fork_choice = context
+        .fork_choice
+        .as_ref()
+        .ok_or_else(fork_choice_unavailable)?
+        .read()
+        .expect("fork choice lock poisoned");
+    let heads = leaves(fork_choice.proto_array())
+        .map(|node| ChainHead {
+            root: node.root,
+            slot: node.slot,
+            execution_optimistic: node.execution_status.is_optimistic(),
+        })
+        .collect();
+    Ok(Json(DataResponse::new(heads)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeValidity {
+    Valid,
+    Invalid,
+    Optimistic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkChoiceNode {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    pub block_root: B256,
+    pub parent_root: Option<B256>,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub justified_epoch: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub finalized_epoch: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub weight: u64,
+    pub validity: NodeValidity,
+    /// Zero for blocks without a payload.
+    pub execution_block_hash: B256,
+    pub extra_data: ForkChoiceNodeExtra,
+}
+
+/// Proto array internals of a node, for following how fork choice picks the head.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkChoiceNodeExtra {
+    pub best_child: Option<B256>,
+    pub best_descendant: Option<B256>,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub depth: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkChoiceDump {
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub fork_choice_nodes: Vec<ForkChoiceNode>,
+}
+
+/// `GET /eth/v1/debug/fork_choice`, the whole block tree with weights and checkpoints.
+pub async fn get_debug_fork_choice(
+    State(context): State<ApiContext>,
+) -> Result<Json<ForkChoiceDump>, ApiError> {
+    let proto_array = context
+        .fork_choice
+        .as_ref()
+        .ok_or_else(fork_choice_unavailable)?
+        .read()
+        .expect("fork choice lock poisoned")
+        .proto_array()
+        .clone();
+    let root_at = |index: Option<usize>| index.map(|index| proto_array.nodes[index].root);
+
+    let mut nodes = vec![];
+    for node in &proto_array.nodes {
+        nodes.push(ForkChoiceNode {
+            slot: node.slot,
+            block_root: node.root,
+            parent_root: root_at(node.parent),
+            justified_epoch: node.justified_checkpoint.epoch,
+            finalized_epoch: node.finalized_checkpoint.epoch,
+            weight: node.weight,
+            validity: match node.execution_status {
+                ExecutionStatus::Valid(_) | ExecutionStatus::Irrelevant(_) => NodeValidity::Valid,
+                ExecutionStatus::Optimistic(_) => NodeValidity::Optimistic,
+                ExecutionStatus::Invalid(_) => NodeValidity::Invalid,
+            },
+            execution_block_hash: node.execution_status.block_hash(),
+            extra_data: ForkChoiceNodeExtra {
+                best_child: root_at(node.best_child),
+                best_descendant: root_at(node.best_descendant),
+                depth: node.depth,
+            },
+        });
+    }
+
+    Ok(Json(ForkChoiceDump {
+        justified_checkpoint: proto_array.justified_checkpoint,
+        finalized_checkpoint: proto_array.finalized_checkpoint,
+        fork_choice_nodes: nodes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use ream_fork_choice::{proto_array::Block, proto_array_fork_choice::ProtoArrayForkChoice};
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_heads_and_fork_choice_dump() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let mut context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        assert!(matches!(
+            get_debug_heads(State(context.clone())).await,
+            Err(ApiError::Unavailable(_))
+        ));
+
+        // 1 <- 2 <- 3 and 1 <- 4.
+        let root = B256::with_last_byte;
+        let block = |slot, tag: u8, parent: u8| Block {
+            slot,
+            root: root(tag),
+            parent_root: root(parent),
+            justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+            execution_status: ExecutionStatus::Optimistic(root(tag)),
+        };
+        let mut fork_choice =
+            ProtoArrayForkChoice::new(block(0, 1, 0), Checkpoint::default(), Checkpoint::default())
+                .unwrap();
+        for block in [block(1, 2, 1), block(2, 3, 2), block(1, 4, 1)] {
+            fork_choice.process_block(block).unwrap();
+        }
+        context.fork_choice = Some(Arc::new(RwLock::new(fork_choice)));
+
+        let Json(heads) = get_debug_heads(State(context.clone())).await.unwrap();
+        let heads: Vec<_> = heads
+            .data
+            .iter()
+            .map(|head| (head.root, head.slot))
+            .collect();
+        assert_eq!(heads, [(root(3), 2), (root(4), 1)]);
+
+        let Json(dump) = get_debug_fork_choice(State(context)).await.unwrap();
+        assert_eq!(dump.fork_choice_nodes.len(), 4);
+        assert_eq!(dump.fork_choice_nodes[0].parent_root, None);
+        assert_eq!(dump.fork_choice_nodes[2].parent_root, Some(root(2)));
+        assert_eq!(dump.fork_choice_nodes[3].extra_data.depth, 1);
+        assert_eq!(dump.fork_choice_nodes[3].validity, NodeValidity::Optimistic);
+        assert_eq!(dump.fork_choice_nodes[3].execution_block_hash, root(4));
+    }
+}
@@ -0,0 +1,386 @@
+use std::sync::Arc;
+
+use alloy_primitives::B256;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use ream_consensus::{
+    beacon_state::BeaconState,
+    bls::BLSPubkey,
+    committee::{compute_epoch_proposers, CommitteeCache},
+    constants::{MIN_SEED_LOOKAHEAD, SLOTS_PER_HISTORICAL_ROOT},
+    misc::{compute_epoch_at_slot, compute_start_slot_at_epoch, compute_sync_committee_period},
+    validator::Validator,
+};
+use serde::{Deserialize, Serialize};
+use tree_hash::TreeHash;
+
+use super::state::{resolve_state, ResolvedState};
+use crate::{
+    context::ApiContext,
+    error::ApiError,
+    id::StateId,
+    response::{DutiesResponse, OptimisticResponse},
+};
+
+/// Body of the duties requests, the indices of the validators to look up.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ValidatorIndices(#[serde(with = "serde_utils::quoted_u64_vec")] pub Vec<u64>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttesterDuty {
+    pub pubkey: BLSPubkey,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committee_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committee_length: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub committees_at_slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_committee_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposerDuty {
+    pub pubkey: BLSPubkey,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncDuty {
+    pub pubkey: BLSPubkey,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    #[serde(with = "serde_utils::quoted_u64_vec")]
+    pub validator_sync_committee_indices: Vec<u64>,
+}
+
+fn parse_epoch(epoch: &str) -> Result<u64, ApiError> {
+    epoch
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid epoch: {epoch}")))
+}
+
+fn validator(state: &ResolvedState, index: u64) -> Result<&Validator, ApiError> {
+    state
+        .state
+        .validators
+        .get(index as usize)
+        .ok_or_else(|| ApiError::BadRequest(format!("unknown validator index {index}")))
+}
+
+/// Root of the block at `slot` on the chain of `state`, which is the state's own block from its
+/// slot onwards.
+pub fn block_root_at_slot(state: &ResolvedState, slot: u64) -> Result<B256, ApiError> {
+    if slot >= state.state.slot {
+        return Ok(state.state.latest_block_root(state.root));
+    }
+    if slot + SLOTS_PER_HISTORICAL_ROOT < state.state.slot {
+        return Err(ApiError::Internal(format!(
+            "block root of slot {slot} is no longer in the state at slot {}",
+            state.state.slot
+        )));
+    }
+    Ok(state.state.block_roots[(slot % SLOTS_PER_HISTORICAL_ROOT) as usize])
+}
+
+/// Last slot whose block feeds the attester shuffling of `epoch`, the end of `epoch - 2`.
+pub fn attester_dependent_slot(epoch: u64) -> u64 {
+    compute_start_slot_at_epoch(epoch.saturating_sub(1)).saturating_sub(1)
+}
+
+/// Last slot whose block feeds the proposer shuffling of `epoch`, the end of `epoch - 1`.
+pub fn proposer_dependent_slot(epoch: u64) -> u64 {
+    compute_start_slot_at_epoch(epoch).saturating_sub(1)
+}
+
+/// Committees of `epoch`, an epoch up to the one of `state`, through the duties cache.
+pub fn epoch_committees(
+    context: &ApiContext,
+    state: &BeaconState,
+    epoch: u64,
+) -> Result<Arc<CommitteeCache>, ApiError> {
+    let dependent_slot = attester_dependent_slot(epoch);
+    // Only the genesis state is at or before the dependent slot of its own epoch.
+    let dependent_root = if dependent_slot < state.slot {
+        state.block_roots[(dependent_slot % SLOTS_PER_HISTORICAL_ROOT) as usize]
+    } else {
+        state.latest_block_root(state.tree_hash_root())
+    };
+    Ok(context
+        .duties
+        .committees(epoch, dependent_root, || CommitteeCache::new(state, epoch))?)
+}
+
+/// The head if it can compute the duties of `epoch`, which must be at most `lookahead` epochs
+/// ahead of it, otherwise the canonical state at the start of `epoch`.
+fn duties_state(
+    context: &ApiContext,
+    epoch: u64,
+    lookahead: u64,
+) -> Result<ResolvedState, ApiError> {
+    let head = resolve_state(context, StateId::Head)?;
+    let head_epoch = compute_epoch_at_slot(head.state.slot);
+    if epoch > head_epoch + lookahead {
+        return Err(ApiError::BadRequest(format!(
+            "duties of epoch {epoch} aren't known at head epoch {head_epoch}"
+        )));
+    }
+    if epoch + lookahead >= head_epoch {
+        Ok(head)
+    } else {
+        resolve_state(context, StateId::Slot(compute_start_slot_at_epoch(epoch)))
+    }
+}
+
+/// `POST /eth/v1/validator/duties/attester/{epoch}`
+///
+/// Committees are shuffled with the randao mix at the end of `epoch - 2`, so the duties depend
+/// on the last block before `epoch - 1`.
+pub async fn post_attester_duties(
+    State(context): State<ApiContext>,
+    Path(epoch): Path<String>,
+    Json(ValidatorIndices(indices)): Json<ValidatorIndices>,
+) -> Result<Json<DutiesResponse<Vec<AttesterDuty>>>, ApiError> {
+    let epoch = parse_epoch(&epoch)?;
+    let state = duties_state(&context, epoch, MIN_SEED_LOOKAHEAD)?;
+    let dependent_root = block_root_at_slot(&state, attester_dependent_slot(epoch))?;
+    let committees = context.duties.committees(epoch, dependent_root, || {
+        CommitteeCache::new(&state.state, epoch)
+    })?;
+
+    let mut duties = vec![];
+    for index in indices {
+        let validator = validator(&state, index)?;
+        if let Some(assignment) = committees.get_assignment(index) {
+            duties.push(AttesterDuty {
+                pubkey: validator.pubkey,
+                validator_index: index,
+                committee_index: assignment.committee_index,
+                committee_length: assignment.committee_length,
+                committees_at_slot: committees.committees_per_slot(),
+                validator_committee_index: assignment.position,
+                slot: assignment.slot,
+            });
+        }
+    }
+    Ok(Json(DutiesResponse {
+        dependent_root,
+        execution_optimistic: state.execution_optimistic,
+        data: duties,
+    }))
+}
+
+/// `GET /eth/v1/validator/duties/proposer/{epoch}`
+///
+/// Proposers are sampled by effective balance, so they're only known once `epoch` started and
+/// depend on the last block before it.
+pub async fn get_proposer_duties(
+    State(context): State<ApiContext>,
+    Path(epoch): Path<String>,
+) -> Result<Json<DutiesResponse<Vec<ProposerDuty>>>, ApiError> {
+    let epoch = parse_epoch(&epoch)?;
+    let state = duties_state(&context, epoch, 0)?;
+    let start_slot = compute_start_slot_at_epoch(epoch);
+    let dependent_root = block_root_at_slot(&state, proposer_dependent_slot(epoch))?;
+    let proposers = context.duties.proposers(epoch, dependent_root, || {
+        compute_epoch_proposers(&state.state)
+    })?;
+
+    let duties = proposers
+        .iter()
+        .zip(start_slot..)
+        .map(|(index, slot)| ProposerDuty {
+            pubkey: state.state.validators[*index as usize].pubkey,
+            validator_index: *index,
+            slot,
+        })
+        .collect();
+    Ok(Json(DutiesResponse {
+        dependent_root,
+        execution_optimistic: state.execution_optimistic,
+        data: duties,
+    }))
+}
+
+/// `POST /eth/v1/validator/duties/sync/{epoch}`, for the current and next sync committee
+/// period of the head.
+pub async fn post_sync_duties(
+    State(context): State<ApiContext>,
+    Path(epoch): Path<String>,
+    Json(ValidatorIndices(indices)): Json<ValidatorIndices>,
+) -> Result<Json<OptimisticResponse<Vec<SyncDuty>>>, ApiError> {
+    let epoch = parse_epoch(&epoch)?;
+    let state = resolve_state(&context, StateId::Head)?;
+    let head_period = compute_sync_committee_period(compute_epoch_at_slot(state.state.slot));
+    let period = compute_sync_committee_period(epoch);
+    let committee = if period == head_period {
+        &state.state.current_sync_committee
+    } else if period == head_period + 1 {
+        &state.state.next_sync_committee
+    } else {
+        return Err(ApiError::BadRequest(format!(
+            "sync committee of period {period} isn't known at period {head_period}"
+        )));
+    };
+
+    let mut duties = vec![];
+    for index in indices {
+        let validator = validator(&state, index)?;
+        let positions: Vec<u64> = committee
+            .pubkeys
+            .iter()
+            .enumerate()
+            .filter(|(_, pubkey)| **pubkey == validator.pubkey)
+            .map(|(position, _)| position as u64)
+            .collect();
+        if !positions.is_empty() {
+            duties.push(SyncDuty {
+                pubkey: validator.pubkey,
+                validator_index: index,
+                validator_sync_committee_indices: positions,
+            });
+        }
+    }
+    Ok(Json(OptimisticResponse {
+        execution_optimistic: state.execution_optimistic,
+        data: duties,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_block_header::BeaconBlockHeader,
+        constants::{FAR_FUTURE_EPOCH, MAX_EFFECTIVE_BALANCE, SLOTS_PER_EPOCH},
+        fork_schedule::ForkSchedule,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+    use crate::context::ChainInfo;
+
+    #[tokio::test]
+    async fn test_duties() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+
+        let slot = 3 * SLOTS_PER_EPOCH + 2;
+        let message = BeaconBlock {
+            slot,
+            state_root: B256::repeat_byte(1),
+            ..BeaconBlock::default()
+        };
+        let mut state = BeaconState {
+            slot,
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            ..BeaconState::default()
+        };
+        for index in 0..64 {
+            state
+                .validators
+                .push(Validator {
+                    pubkey: BLSPubkey::repeat_byte(index + 1),
+                    effective_balance: MAX_EFFECTIVE_BALANCE,
+                    exit_epoch: FAR_FUTURE_EPOCH,
+                    ..Validator::default()
+                })
+                .unwrap();
+        }
+        let epoch_1_root = B256::repeat_byte(2);
+        let epoch_2_root = B256::repeat_byte(3);
+        state.block_roots[(2 * SLOTS_PER_EPOCH - 1) as usize] = epoch_1_root;
+        state.block_roots[(3 * SLOTS_PER_EPOCH - 1) as usize] = epoch_2_root;
+        state.next_sync_committee.pubkeys[3] = BLSPubkey::repeat_byte(6);
+        state.next_sync_committee.pubkeys[9] = BLSPubkey::repeat_byte(6);
+        context
+            .store
+            .hot_states()
+            .put_snapshot(message.state_root, &state)
+            .unwrap();
+        let head_root = context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root,
+            head_slot: slot,
+            ..ChainInfo::default()
+        };
+
+        let indices = || Json(ValidatorIndices((0..64).collect()));
+        let Json(current) =
+            post_attester_duties(State(context.clone()), Path("3".into()), indices())
+                .await
+                .unwrap();
+        assert_eq!(current.dependent_root, epoch_1_root);
+        assert_eq!(current.data.len(), 64);
+        assert!(current
+            .data
+            .iter()
+            .all(|duty| compute_epoch_at_slot(duty.slot) == 3 && duty.committees_at_slot == 1));
+        let Json(next) = post_attester_duties(State(context.clone()), Path("4".into()), indices())
+            .await
+            .unwrap();
+        assert_eq!(next.dependent_root, epoch_2_root);
+        assert!(matches!(
+            post_attester_duties(State(context.clone()), Path("5".into()), indices()).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let Json(proposers) = get_proposer_duties(State(context.clone()), Path("3".into()))
+            .await
+            .unwrap();
+        assert_eq!(proposers.dependent_root, epoch_2_root);
+        assert_eq!(proposers.data.len(), SLOTS_PER_EPOCH as usize);
+        assert_eq!(proposers.data[0].slot, 3 * SLOTS_PER_EPOCH);
+        assert!(matches!(
+            get_proposer_duties(State(context.clone()), Path("4".into())).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let Json(sync) = post_sync_duties(State(context.clone()), Path("256".into()), indices())
+            .await
+            .unwrap();
+        assert_eq!(sync.data.len(), 1);
+        assert_eq!(sync.data[0].validator_index, 5);
+        assert_eq!(sync.data[0].validator_sync_committee_indices, vec![3, 9]);
+        assert!(matches!(
+            post_sync_duties(
+                State(context),
+                Path("3".into()),
+                Json(ValidatorIndices(vec![64]))
+            )
+            .await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
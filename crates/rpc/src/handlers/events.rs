@@ -0,0 +1,36 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{RawQuery, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{Stream, StreamExt};
+
+use crate::{context::ApiContext, error::ApiError, events::EventTopic, query::query_values};
+
+/// `GET /eth/v1/events?topics=`, a server-sent event stream of the requested topics.
+pub async fn get_events(
+    State(context): State<ApiContext>,
+    RawQuery(query): RawQuery,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let topics = query_values(query.as_deref(), "topics")
+        .iter()
+        .map(|topic| topic.parse())
+        .collect::<Result<Vec<EventTopic>, _>>()?;
+    if topics.is_empty() {
+        return Err(ApiError::BadRequest(
+            "at least one event topic is required".to_string(),
+        ));
+    }
+    let subscription = context
+        .events
+        .subscribe()
+        .ok_or_else(|| ApiError::Unavailable("too many event subscribers".to_string()))?;
+
+    let stream = subscription.into_stream(topics).map(|event| {
+        Ok(Event::default()
+            .event(event.topic().to_string())
+            .data(event.to_json()))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,18 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    context::{ApiContext, GenesisInfo},
+    error::ApiError,
+    response::DataResponse,
+};
+
+/// `GET /eth/v1/beacon/genesis`
+pub async fn get_genesis(
+    State(context): State<ApiContext>,
+) -> Result<Json<DataResponse<GenesisInfo>>, ApiError> {
+    context
+        .chain_info()
+        .genesis
+        .map(|genesis| Json(DataResponse::new(genesis)))
+        .ok_or_else(|| ApiError::NotFound("chain genesis info is not yet known".to_string()))
+}
@@ -0,0 +1,13 @@
+pub mod attestation;
+pub mod blob;
+pub mod block;
+pub mod debug;
+pub mod duties;
+pub mod events;
+pub mod genesis;
+pub mod node;
+pub mod pool;
+pub mod preparation;
+pub mod production;
+pub mod state;
+pub mod validator;
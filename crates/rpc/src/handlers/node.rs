@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{extract::State, http::StatusCode, Json};
+use ream_consensus::constants::SECONDS_PER_SLOT;
+use serde::{Deserialize, Serialize};
+
+use crate::{context::ApiContext, error::ApiError, response::DataResponse};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Version {
+    pub version: String,
+}
+
+pub fn version_string() -> String {
+    format!(
+        "ream/v{}/{}-{}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    )
+}
+
+/// `GET /eth/v1/node/version`
+pub async fn get_version() -> Json<DataResponse<Version>> {
+    Json(DataResponse::new(Version {
+        version: version_string(),
+    }))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncingStatus {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub head_slot: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub sync_distance: u64,
+    pub is_syncing: bool,
+    pub is_optimistic: bool,
+    pub el_offline: bool,
+}
+
+/// `GET /eth/v1/node/syncing`, how far the head is behind the wall clock slot.
+pub async fn get_syncing(
+    State(context): State<ApiContext>,
+) -> Result<Json<DataResponse<SyncingStatus>>, ApiError> {
+    let chain = context.chain_info();
+    let genesis = chain
+        .genesis
+        .ok_or_else(|| ApiError::Unavailable("node is not initialized".to_string()))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the unix epoch")
+        .as_secs();
+    let current_slot = now.saturating_sub(genesis.genesis_time) / SECONDS_PER_SLOT;
+    Ok(Json(DataResponse::new(SyncingStatus {
+        head_slot: chain.head_slot,
+        sync_distance: current_slot.saturating_sub(chain.head_slot),
+        is_syncing: chain.is_syncing,
+        is_optimistic: chain.head_optimistic,
+        el_offline: chain.el_offline,
+    })))
+}
+
+/// `GET /eth/v1/node/health`: 200 when synced, 206 while syncing and 503 before the node has
+/// a chain to serve.
+pub async fn get_health(State(context): State<ApiContext>) -> Result<StatusCode, ApiError> {
+    let chain = context.chain_info();
+    if chain.genesis.is_none() {
+        return Err(ApiError::Unavailable("node is not initialized".to_string()));
+    }
+    Ok(if chain.is_syncing {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use alloy_primitives::{aliases::B32, B256};
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+    use crate::context::{ChainInfo, GenesisInfo};
+
+    #[tokio::test]
+    async fn test_get_syncing() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        assert!(matches!(
+            get_syncing(State(context.clone())).await,
+            Err(ApiError::Unavailable(_))
+        ));
+
+        // A chain that started ten slots ago and has seen four of them.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        *context.chain.write().unwrap() = ChainInfo {
+            genesis: Some(GenesisInfo {
+                genesis_time: now - 10 * SECONDS_PER_SLOT,
+                genesis_validators_root: B256::ZERO,
+                genesis_fork_version: B32::ZERO,
+            }),
+            head_slot: 4,
+            is_syncing: true,
+            head_optimistic: true,
+            el_offline: true,
+            ..ChainInfo::default()
+        };
+        let Json(response) = get_syncing(State(context)).await.unwrap();
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["data"]["head_slot"], "4");
+        assert_eq!(json["data"]["sync_distance"], "6");
+        assert_eq!(json["data"]["is_optimistic"], true);
+        assert_eq!(json["data"]["el_offline"], true);
+    }
+}
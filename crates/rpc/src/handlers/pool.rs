@@ -0,0 +1,422 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ream_consensus::{
+    attestation::Attestation, attester_slashing::AttesterSlashing,
+    bls_to_execution_change::SignedBLSToExecutionChange, proposer_slashing::ProposerSlashing,
+    voluntary_exit::SignedVoluntaryExit,
+};
+use ream_operation_pool::operation_pool::OperationPool;
+use serde::Deserialize;
+use tracing::warn;
+
+use super::{
+    duties::epoch_committees,
+    state::{resolve_state, ResolvedState},
+};
+use crate::{
+    context::ApiContext,
+    error::{ApiError, IndexedError},
+    id::StateId,
+    publish::GossipOperation,
+    response::DataResponse,
+    validation::OperationValidator,
+};
+
+/// Validates submitted operations against the head state, pools the valid ones and broadcasts
+/// those the pool didn't have yet.
+///
+/// Returns the rejected items, the valid ones are pooled and broadcast regardless.
+pub async fn submit<T: Clone>(
+    context: &ApiContext,
+    items: Vec<T>,
+    validate: impl Fn(&OperationValidator, &ApiContext, &T) -> Result<(), String>,
+    insert: impl Fn(&mut OperationPool, T) -> bool,
+    to_gossip: impl Fn(T) -> GossipOperation,
+) -> Result<Vec<IndexedError>, ApiError> {
+    let ResolvedState { state, .. } = resolve_state(context, StateId::Head)?;
+    let validator = OperationValidator::new(&state, &context.fork_schedule);
+
+    let mut failures = vec![];
+    let mut valid = vec![];
+    for (index, item) in items.into_iter().enumerate() {
+        match validate(&validator, context, &item) {
+            Ok(()) => valid.push(item),
+            Err(message) => failures.push(IndexedError { index, message }),
+        }
+    }
+
+    let new: Vec<T> = {
+        let mut pool = context
+            .operation_pool
+            .write()
+            .expect("operation pool lock poisoned");
+        valid
+            .into_iter()
+            .filter(|item| insert(&mut pool, item.clone()))
+            .collect()
+    };
+    if let Some(publisher) = &context.operation_publisher {
+        for item in new {
+            if publisher.send(to_gossip(item)).await.is_err() {
+                warn!("Gossip has shut down, pooled operations are not broadcast");
+                break;
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+fn single_result(failures: Vec<IndexedError>) -> Result<(), ApiError> {
+    match failures.into_iter().next() {
+        Some(failure) => Err(ApiError::BadRequest(failure.message)),
+        None => Ok(()),
+    }
+}
+
+/// A list fails if any item was rejected, naming every rejected item.
+pub fn list_result(failures: Vec<IndexedError>) -> Result<(), ApiError> {
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::IndexedErrors {
+            message: "some submitted operations failed validation".to_string(),
+            failures,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct AttestationsQuery {
+    pub slot: Option<u64>,
+    pub committee_index: Option<u64>,
+}
+
+/// `GET /eth/v1/beacon/pool/attestations`
+pub async fn get_attestations(
+    State(context): State<ApiContext>,
+    Query(query): Query<AttestationsQuery>,
+) -> Json<DataResponse<Vec<Attestation>>> {
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    let attestations = pool
+        .attestations()
+        .filter(|attestation| {
+            query
+                .slot
+                .map_or(true, |slot| attestation.data.slot == slot)
+                && query
+                    .committee_index
+                    .map_or(true, |index| attestation.data.index == index)
+        })
+        .cloned()
+        .collect();
+    Json(DataResponse::new(attestations))
+}
+
+/// `POST /eth/v1/beacon/pool/attestations`
+///
+/// Unaggregated attestations also go to the naive aggregation pool, for aggregators to fetch
+/// through `GET /eth/v1/validator/aggregate_attestation`.
+pub async fn post_attestations(
+    State(context): State<ApiContext>,
+    Json(attestations): Json<Vec<Attestation>>,
+) -> Result<(), ApiError> {
+    list_result(
+        submit(
+            &context,
+            attestations,
+            |validator, context, attestation| {
+                validator
+                    .validate_attestation(attestation, |root| {
+                        context.store.get_block(root).ok().flatten().is_some()
+                    })
+                    .map_err(|err| err.to_string())?;
+                let data = &attestation.data;
+                let committees = epoch_committees(context, validator.state(), data.target.epoch)
+                    .map_err(|err| err.to_string())?;
+                let committee = committees
+                    .get_beacon_committee(data.slot, data.index)
+                    .ok_or_else(|| format!("no committee {} at slot {}", data.index, data.slot))?;
+                validator
+                    .validate_attestation_signature(attestation, committee)
+                    .map_err(|err| err.to_string())?;
+                if attestation.aggregation_bits.num_set_bits() == 1 {
+                    // Already aggregated is fine, the attestation is valid either way.
+                    let _ = context
+                        .naive_aggregation_pool
+                        .write()
+                        .expect("naive aggregation pool lock poisoned")
+                        .insert(attestation);
+                }
+                Ok(())
+            },
+            |pool, attestation| pool.insert_attestation(attestation),
+            GossipOperation::Attestation,
+        )
+        .await?,
+    )
+}
+
+/// `GET /eth/v1/beacon/pool/voluntary_exits`
+pub async fn get_voluntary_exits(
+    State(context): State<ApiContext>,
+) -> Json<DataResponse<Vec<SignedVoluntaryExit>>> {
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    Json(DataResponse::new(pool.voluntary_exits().cloned().collect()))
+}
+
+/// `POST /eth/v1/beacon/pool/voluntary_exits`
+pub async fn post_voluntary_exit(
+    State(context): State<ApiContext>,
+    Json(exit): Json<SignedVoluntaryExit>,
+) -> Result<(), ApiError> {
+    single_result(
+        submit(
+            &context,
+            vec![exit],
+            |validator, _, exit| {
+                validator
+                    .validate_voluntary_exit(exit)
+                    .map_err(|err| err.to_string())
+            },
+            |pool, exit| pool.insert_voluntary_exit(exit),
+            GossipOperation::VoluntaryExit,
+        )
+        .await?,
+    )
+}
+
+/// `GET /eth/v1/beacon/pool/proposer_slashings`
+pub async fn get_proposer_slashings(
+    State(context): State<ApiContext>,
+) -> Json<DataResponse<Vec<ProposerSlashing>>> {
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    Json(DataResponse::new(
+        pool.proposer_slashings().cloned().collect(),
+    ))
+}
+
+/// `POST /eth/v1/beacon/pool/proposer_slashings`
+pub async fn post_proposer_slashing(
+    State(context): State<ApiContext>,
+    Json(slashing): Json<ProposerSlashing>,
+) -> Result<(), ApiError> {
+    single_result(
+        submit(
+            &context,
+            vec![slashing],
+            |validator, _, slashing| {
+                validator
+                    .validate_proposer_slashing(slashing)
+                    .map_err(|err| err.to_string())
+            },
+            |pool, slashing| pool.insert_proposer_slashing(slashing),
+            GossipOperation::ProposerSlashing,
+        )
+        .await?,
+    )
+}
+
+/// `GET /eth/v1/beacon/pool/attester_slashings`
+pub async fn get_attester_slashings(
+    State(context): State<ApiContext>,
+) -> Json<DataResponse<Vec<AttesterSlashing>>> {
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    Json(DataResponse::new(pool.attester_slashings().to_vec()))
+}
+
+/// `POST /eth/v1/beacon/pool/attester_slashings`
+pub async fn post_attester_slashing(
+    State(context): State<ApiContext>,
+    Json(slashing): Json<AttesterSlashing>,
+) -> Result<(), ApiError> {
+    single_result(
+        submit(
+            &context,
+            vec![slashing],
+            |validator, _, slashing| {
+                validator
+                    .validate_attester_slashing(slashing)
+                    .map_err(|err| err.to_string())
+            },
+            |pool, slashing| pool.insert_attester_slashing(slashing),
+            GossipOperation::AttesterSlashing,
+        )
+        .await?,
+    )
+}
+
+/// `GET /eth/v1/beacon/pool/bls_to_execution_changes`
+pub async fn get_bls_to_execution_changes(
+    State(context): State<ApiContext>,
+) -> Json<DataResponse<Vec<SignedBLSToExecutionChange>>> {
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    Json(DataResponse::new(
+        pool.bls_to_execution_changes().cloned().collect(),
+    ))
+}
+
+/// `POST /eth/v1/beacon/pool/bls_to_execution_changes`
+pub async fn post_bls_to_execution_changes(
+    State(context): State<ApiContext>,
+    Json(changes): Json<Vec<SignedBLSToExecutionChange>>,
+) -> Result<(), ApiError> {
+    list_result(
+        submit(
+            &context,
+            changes,
+            |validator, _, change| {
+                validator
+                    .validate_bls_to_execution_change(change)
+                    .map_err(|err| err.to_string())
+            },
+            |pool, change| pool.insert_bls_to_execution_change(change),
+            GossipOperation::BlsToExecutionChange,
+        )
+        .await?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use alloy_primitives::B256;
+    use ream_bls::PrivateKey;
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_block_header::BeaconBlockHeader,
+        beacon_state::BeaconState,
+        bls_to_execution_change::BLSToExecutionChange,
+        constants::{BLS_WITHDRAWAL_PREFIX, DOMAIN_BLS_TO_EXECUTION_CHANGE},
+        fork_schedule::ForkSchedule,
+        misc::{compute_domain, compute_signing_root},
+        validator::Validator,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::context::ChainInfo;
+
+    #[tokio::test]
+    async fn test_submit_bls_to_execution_changes() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let mut context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        let (publisher, mut broadcast) = mpsc::channel(4);
+        context.operation_publisher = Some(publisher);
+
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let mut withdrawal_credentials =
+            B256::from_slice(&ethereum_hashing::hash(key.public_key().as_slice()));
+        withdrawal_credentials[0] = BLS_WITHDRAWAL_PREFIX;
+        let message = BeaconBlock {
+            state_root: B256::repeat_byte(1),
+            ..BeaconBlock::default()
+        };
+        let mut state = BeaconState {
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            ..BeaconState::default()
+        };
+        state
+            .validators
+            .push(Validator {
+                pubkey: key.public_key(),
+                withdrawal_credentials,
+                ..Validator::default()
+            })
+            .unwrap();
+        context
+            .store
+            .hot_states()
+            .put_snapshot(message.state_root, &state)
+            .unwrap();
+        let head_root = context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root,
+            ..ChainInfo::default()
+        };
+
+        let domain = compute_domain(
+            DOMAIN_BLS_TO_EXECUTION_CHANGE,
+            context.fork_schedule.genesis_fork().version,
+            state.genesis_validators_root,
+        );
+        let message = BLSToExecutionChange {
+            validator_index: 0,
+            from_bls_pubkey: key.public_key(),
+            to_execution_address: Default::default(),
+        };
+        let valid = SignedBLSToExecutionChange {
+            signature: key.sign(compute_signing_root(&message, domain).as_slice()),
+            message: message.clone(),
+        };
+        let invalid = SignedBLSToExecutionChange {
+            message,
+            ..SignedBLSToExecutionChange::default()
+        };
+
+        let result = post_bls_to_execution_changes(
+            State(context.clone()),
+            Json(vec![valid.clone(), invalid]),
+        )
+        .await;
+        match result {
+            Err(ApiError::IndexedErrors { failures, .. }) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].index, 1);
+            }
+            other => panic!("expected an indexed error, got {other:?}"),
+        }
+
+        let Json(pooled) = get_bls_to_execution_changes(State(context.clone())).await;
+        assert_eq!(pooled.data.len(), 1);
+        assert_eq!(pooled.data[0], valid);
+        assert_eq!(
+            broadcast.recv().await,
+            Some(GossipOperation::BlsToExecutionChange(valid.clone()))
+        );
+
+        // Already pooled, accepted without being broadcast again.
+        post_bls_to_execution_changes(State(context.clone()), Json(vec![valid]))
+            .await
+            .unwrap();
+        assert!(broadcast.try_recv().is_err());
+    }
+}
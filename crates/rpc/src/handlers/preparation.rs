@@ -0,0 +1,133 @@
+use alloy_primitives::Address;
+use axum::{extract::State, Json};
+use ream_builder::types::SignedValidatorRegistrationV1;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::ApiContext,
+    error::{ApiError, IndexedError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposerPreparation {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    pub fee_recipient: Address,
+}
+
+/// `POST /eth/v1/validator/prepare_beacon_proposer`
+///
+/// Sets the fee recipient of the blocks the node builds for each validator.
+pub async fn post_prepare_beacon_proposer(
+    State(context): State<ApiContext>,
+    Json(preparations): Json<Vec<ProposerPreparation>>,
+) {
+    for preparation in preparations {
+        context
+            .proposer_preparations
+            .prepare(preparation.validator_index, preparation.fee_recipient);
+    }
+}
+
+/// `POST /eth/v1/validator/register_validator`
+///
+/// Keeps the registrations signed by their validator for the builder, those with a bad
+/// signature are rejected.
+pub async fn post_register_validator(
+    State(context): State<ApiContext>,
+    Json(registrations): Json<Vec<SignedValidatorRegistrationV1>>,
+) -> Result<(), ApiError> {
+    let genesis_fork_version = context.fork_schedule.genesis_fork().version;
+    let mut failures = vec![];
+    for (index, registration) in registrations.into_iter().enumerate() {
+        if registration.verify_signature(genesis_fork_version) {
+            context.proposer_preparations.register(registration);
+        } else {
+            failures.push(IndexedError {
+                index,
+                message: "invalid signature".to_string(),
+            });
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::IndexedErrors {
+            message: "some validator registrations were rejected".to_string(),
+            failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_bls::PrivateKey;
+    use ream_builder::types::ValidatorRegistrationV1;
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_proposer_preparations() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+
+        post_prepare_beacon_proposer(
+            State(context.clone()),
+            Json(vec![ProposerPreparation {
+                validator_index: 3,
+                fee_recipient: Address::repeat_byte(1),
+            }]),
+        )
+        .await;
+        let preparations = &context.proposer_preparations;
+        assert_eq!(preparations.fee_recipient(3), Some(Address::repeat_byte(1)));
+        assert_eq!(preparations.fee_recipient(4), None);
+
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let genesis_fork_version = context.fork_schedule.genesis_fork().version;
+        let sign = |timestamp| {
+            let message = ValidatorRegistrationV1 {
+                fee_recipient: Address::repeat_byte(1),
+                gas_limit: 30_000_000,
+                timestamp,
+                pubkey: key.public_key(),
+            };
+            SignedValidatorRegistrationV1 {
+                signature: key.sign(message.signing_root(genesis_fork_version).as_slice()),
+                message,
+            }
+        };
+        let forged = SignedValidatorRegistrationV1 {
+            message: ValidatorRegistrationV1 {
+                gas_limit: 1,
+                ..sign(2).message
+            },
+            ..sign(2)
+        };
+        let err = post_register_validator(State(context.clone()), Json(vec![sign(2), forged]))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::IndexedErrors { failures, .. } if failures[0].index == 1
+        ));
+        // An older registration doesn't replace the newest one.
+        post_register_validator(State(context.clone()), Json(vec![sign(1)]))
+            .await
+            .unwrap();
+        assert_eq!(preparations.registration(&key.public_key()), Some(sign(2)));
+    }
+}
@@ -0,0 +1,578 @@
+use std::{collections::HashSet, sync::Arc};
+
+use alloy_primitives::{B256, U256};
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use ream_bls::verify;
+use ream_consensus::{
+    attestation::Attestation,
+    beacon_block::{BeaconBlock, SignedBeaconBlock},
+    beacon_block_body::BeaconBlockBody,
+    beacon_state::BeaconState,
+    blob_sidecar::{Blob, KZGProof},
+    bls::{BLSSignature, G2_POINT_AT_INFINITY},
+    committee::{compute_epoch_proposers, CommitteeCache},
+    constants::{DOMAIN_RANDAO, SECONDS_PER_SLOT, SLOTS_PER_EPOCH, SLOTS_PER_HISTORICAL_ROOT},
+    deposit::Deposit,
+    eth1_data::Eth1Data,
+    fork_schedule::ForkName,
+    misc::{compute_epoch_at_slot, compute_signing_root},
+    sync_aggregate::SyncAggregate,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tree_hash::TreeHash;
+
+use super::{
+    block::CONSENSUS_VERSION_HEADER,
+    duties::{epoch_committees, proposer_dependent_slot},
+    state::resolve_state,
+};
+use crate::{
+    context::ApiContext,
+    error::ApiError,
+    eth1::eth1_data_after_vote,
+    id::StateId,
+    payload::{BuiltPayload, PayloadRequest},
+    validation::{is_slashable_validator, OperationValidator},
+};
+
+pub const EXECUTION_PAYLOAD_BLINDED_HEADER: &str = "Eth-Execution-Payload-Blinded";
+pub const EXECUTION_PAYLOAD_VALUE_HEADER: &str = "Eth-Execution-Payload-Value";
+pub const CONSENSUS_BLOCK_VALUE_HEADER: &str = "Eth-Consensus-Block-Value";
+
+const GWEI_TO_WEI: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProduceBlockQuery {
+    pub randao_reveal: BLSSignature,
+    #[serde(default)]
+    pub graffiti: B256,
+    /// Set without a value to skip the check of `randao_reveal`, which must then be the point
+    /// at infinity.
+    pub skip_randao_verification: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HexBlob(#[serde(with = "ssz_types::serde_utils::hex_fixed_vec")] pub Blob);
+
+/// An unsigned Deneb block with the blobs its commitments refer to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockContents {
+    pub block: BeaconBlock,
+    pub kzg_proofs: Vec<KZGProof>,
+    pub blobs: Vec<HexBlob>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProduceBlockResponse<T> {
+    pub version: ForkName,
+    pub execution_payload_blinded: bool,
+    #[serde(with = "serde_utils::quoted_u256")]
+    pub execution_payload_value: U256,
+    #[serde(with = "serde_utils::quoted_u256")]
+    pub consensus_block_value: U256,
+    pub data: T,
+}
+
+/// Whether `attestation` passes the checks of `process_attestation` that the operation pool
+/// doesn't make, all but the signature.
+fn is_includable(
+    state: &BeaconState,
+    committees: &[Arc<CommitteeCache>],
+    attestation: &Attestation,
+) -> bool {
+    let data = &attestation.data;
+    let justified = if data.target.epoch == compute_epoch_at_slot(state.slot) {
+        state.current_justified_checkpoint
+    } else {
+        state.previous_justified_checkpoint
+    };
+    data.source == justified
+        && committees
+            .iter()
+            .find(|committees| committees.epoch() == data.target.epoch)
+            .and_then(|committees| committees.get_beacon_committee(data.slot, data.index))
+            .is_some_and(|committee| committee.len() == attestation.aggregation_bits.len())
+}
+
+/// Fills `body` with the operations of the pool that are valid in `state`.
+fn pack_operations(
+    context: &ApiContext,
+    state: &BeaconState,
+    body: &mut BeaconBlockBody,
+) -> Result<(), ApiError> {
+    let epoch = compute_epoch_at_slot(state.slot);
+    let mut committees = vec![epoch_committees(context, state, epoch)?];
+    if epoch > 0 {
+        committees.push(epoch_committees(context, state, epoch - 1)?);
+    }
+    let validator = OperationValidator::new(state, &context.fork_schedule);
+    let is_slashable = |index: u64| {
+        state
+            .validators
+            .get(index as usize)
+            .is_some_and(|validator| is_slashable_validator(validator, epoch))
+    };
+
+    let pool = context
+        .operation_pool
+        .read()
+        .expect("operation pool lock poisoned");
+    body.attestations = pool
+        .get_attestations(state.slot, |attestation| {
+            is_includable(state, &committees, attestation)
+        })
+        .into();
+    let (proposer_slashings, attester_slashings) = pool.get_slashings(is_slashable);
+    let mut slashed: HashSet<u64> = proposer_slashings
+        .iter()
+        .map(|slashing| slashing.signed_header_1.message.proposer_index)
+        .collect();
+    for slashing in &attester_slashings {
+        slashed.extend(slashing.slashable_indices());
+    }
+    body.proposer_slashings = proposer_slashings.into();
+    body.attester_slashings = attester_slashings.into();
+    body.voluntary_exits = pool
+        .get_voluntary_exits(&slashed, |exit| {
+            validator.validate_voluntary_exit(exit).is_ok()
+        })
+        .into();
+    body.bls_to_execution_changes = pool
+        .get_bls_to_execution_changes(|change| {
+            validator.validate_bls_to_execution_change(change).is_ok()
+        })
+        .into();
+    Ok(())
+}
+
+/// The eth1 data vote of a block proposed on `state` and the deposits it has to include.
+fn eth1_data_and_deposits(
+    context: &ApiContext,
+    state: &BeaconState,
+) -> Result<(Eth1Data, Vec<Deposit>), ApiError> {
+    let Some(eth1) = &context.eth1 else {
+        if state.eth1_deposit_index < state.eth1_data.deposit_count {
+            return Err(ApiError::Unavailable(
+                "pending deposits can't be included without an execution client".to_string(),
+            ));
+        }
+        return Ok((state.eth1_data.clone(), vec![]));
+    };
+    let vote = eth1.eth1_data_vote(state);
+    let eth1_data = eth1_data_after_vote(state, &vote);
+    if state.eth1_deposit_index >= eth1_data.deposit_count {
+        return Ok((vote, vec![]));
+    }
+    let deposits = eth1
+        .deposits(state.eth1_deposit_index, &eth1_data)
+        .map_err(|err| ApiError::Unavailable(format!("can't include pending deposits: {err}")))?;
+    Ok((vote, deposits))
+}
+
+async fn request_payload(
+    context: &ApiContext,
+    state: &BeaconState,
+    proposer_index: u64,
+    parent_root: B256,
+) -> Result<BuiltPayload, ApiError> {
+    let requester = context.payload_requester.as_ref().ok_or_else(|| {
+        ApiError::Unavailable("the node has no execution client to build payloads".to_string())
+    })?;
+    let (result, built) = oneshot::channel();
+    let unavailable = || ApiError::Unavailable("payload building has shut down".to_string());
+    requester
+        .send(PayloadRequest {
+            slot: state.slot,
+            proposer_index,
+            parent_hash: state.latest_execution_payload_header.block_hash,
+            timestamp: state.genesis_time + state.slot * SECONDS_PER_SLOT,
+            prev_randao: state.get_randao_mix(compute_epoch_at_slot(state.slot)),
+            withdrawals: state.get_expected_withdrawals(),
+            parent_beacon_block_root: parent_root,
+            result,
+        })
+        .await
+        .map_err(|_| unavailable())?;
+    built
+        .await
+        .map_err(|_| unavailable())?
+        .map_err(|err| ApiError::Unavailable(format!("failed to build a payload: {err}")))
+}
+
+/// `GET /eth/v3/validator/blocks/{slot}`
+///
+/// Builds an unsigned block on the head chosen by fork choice: the head state is advanced to
+/// `slot`, the execution client builds the payload and the operation pool supplies the
+/// operations. The block is applied to the advanced state for its state root and the
+/// proposer's reward is reported as the consensus block value.
+///
+/// Payloads always come from the local execution client, so they're never blinded. The eth1
+/// data vote and the pending deposits come from the node's view of the deposit contract, without
+/// an execution client production fails while deposits are pending.
+pub async fn get_block_v3(
+    State(context): State<ApiContext>,
+    Path(slot): Path<String>,
+    Query(query): Query<ProduceBlockQuery>,
+) -> Result<Response, ApiError> {
+    let slot: u64 = slot
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid slot: {slot}")))?;
+    let chain = context.chain_info();
+    if chain.is_syncing || chain.head_optimistic {
+        return Err(ApiError::Unavailable(
+            "the node is syncing, blocks can't be produced".to_string(),
+        ));
+    }
+    if chain.el_offline {
+        return Err(ApiError::Unavailable(
+            "the execution client is offline, blocks can't be produced".to_string(),
+        ));
+    }
+    if slot <= chain.head_slot {
+        return Err(ApiError::BadRequest(format!(
+            "slot {slot} is not after the head slot {}",
+            chain.head_slot
+        )));
+    }
+
+    let head = resolve_state(&context, StateId::Head)?;
+    let parent_root = head.state.latest_block_root(head.root);
+    let mut state = BeaconState::clone(&head.state);
+    context
+        .replayer
+        .process_slots(&mut state, slot)
+        .map_err(|err| {
+            ApiError::Unavailable(format!(
+                "can't advance the head state to slot {slot}: {err}"
+            ))
+        })?;
+    let (eth1_data, deposits) = eth1_data_and_deposits(&context, &state)?;
+
+    let epoch = compute_epoch_at_slot(slot);
+    let dependent_slot = proposer_dependent_slot(epoch);
+    let dependent_root = state.block_roots[(dependent_slot % SLOTS_PER_HISTORICAL_ROOT) as usize];
+    let proposers = context
+        .duties
+        .proposers(epoch, dependent_root, || compute_epoch_proposers(&state))?;
+    let proposer_index = proposers[(slot % SLOTS_PER_EPOCH) as usize];
+    if query.skip_randao_verification.is_some() {
+        if query.randao_reveal != G2_POINT_AT_INFINITY {
+            return Err(ApiError::BadRequest(
+                "randao_reveal must be the point at infinity when skipping its verification"
+                    .to_string(),
+            ));
+        }
+    } else {
+        let domain =
+            context
+                .fork_schedule
+                .get_domain(DOMAIN_RANDAO, epoch, state.genesis_validators_root);
+        let signing_root = compute_signing_root(&epoch, domain);
+        let pubkey = &state.validators[proposer_index as usize].pubkey;
+        if !matches!(
+            verify(pubkey, signing_root.as_slice(), &query.randao_reveal),
+            Ok(true)
+        ) {
+            return Err(ApiError::BadRequest(format!(
+                "invalid randao_reveal for proposer {proposer_index}"
+            )));
+        }
+    }
+
+    let mut body = BeaconBlockBody {
+        randao_reveal: query.randao_reveal,
+        eth1_data,
+        graffiti: query.graffiti,
+        deposits: deposits.into(),
+        sync_aggregate: SyncAggregate::empty(),
+        ..BeaconBlockBody::default()
+    };
+    pack_operations(&context, &state, &mut body)?;
+    let built = request_payload(&context, &state, proposer_index, parent_root).await?;
+    body.execution_payload = built.payload;
+    body.blob_kzg_commitments = built.blobs_bundle.commitments.into();
+
+    let mut block = BeaconBlock {
+        slot,
+        proposer_index,
+        parent_root,
+        state_root: B256::ZERO,
+        body,
+    };
+    let balance_before = state.balances[proposer_index as usize];
+    context
+        .replayer
+        .process_block(
+            &mut state,
+            &SignedBeaconBlock {
+                message: block.clone(),
+                signature: BLSSignature::default(),
+            },
+        )
+        .map_err(|err| ApiError::Internal(format!("the produced block is invalid: {err}")))?;
+    block.state_root = state.tree_hash_root();
+    let consensus_block_value =
+        U256::from(state.balances[proposer_index as usize].saturating_sub(balance_before))
+            * U256::from(GWEI_TO_WEI);
+
+    let version = ForkName::Deneb;
+    Ok((
+        [
+            (CONSENSUS_VERSION_HEADER, version.to_string()),
+            (EXECUTION_PAYLOAD_BLINDED_HEADER, false.to_string()),
+            (
+                EXECUTION_PAYLOAD_VALUE_HEADER,
+                built.block_value.to_string(),
+            ),
+            (
+                CONSENSUS_BLOCK_VALUE_HEADER,
+                consensus_block_value.to_string(),
+            ),
+        ],
+        Json(ProduceBlockResponse {
+            version,
+            execution_payload_blinded: false,
+            execution_payload_value: built.block_value,
+            consensus_block_value,
+            data: BlockContents {
+                block,
+                kzg_proofs: built.blobs_bundle.proofs,
+                blobs: built.blobs_bundle.blobs.into_iter().map(HexBlob).collect(),
+            },
+        }),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_bls::PrivateKey;
+    use ream_consensus::{
+        beacon_block_header::BeaconBlockHeader, constants::FAR_FUTURE_EPOCH,
+        fork_schedule::ForkSchedule, validator::Validator,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        state_store::StateReplayer,
+        store::Store,
+    };
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{context::ChainInfo, eth1::Eth1Provider};
+
+    /// Advances the slot and records the parent in `block_roots`, enough for block production.
+    struct SlotReplayer;
+
+    impl StateReplayer for SlotReplayer {
+        type Error = String;
+
+        fn process_slots(&self, state: &mut BeaconState, slot: u64) -> Result<(), String> {
+            while state.slot < slot {
+                let root = state.latest_block_root(state.tree_hash_root());
+                state.block_roots[(state.slot % SLOTS_PER_HISTORICAL_ROOT) as usize] = root;
+                state.slot += 1;
+            }
+            Ok(())
+        }
+
+        fn process_block(
+            &self,
+            state: &mut BeaconState,
+            block: &SignedBeaconBlock,
+        ) -> Result<(), String> {
+            state.balances[block.message.proposer_index as usize] += 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_block_v3() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let mut context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        context.replayer = Arc::new(SlotReplayer);
+
+        let keys: Vec<_> = (1..=4)
+            .map(|seed| PrivateKey::key_gen(&[seed; 32]).unwrap())
+            .collect();
+        let message = BeaconBlock {
+            slot: 1,
+            state_root: B256::repeat_byte(1),
+            ..BeaconBlock::default()
+        };
+        let mut state = BeaconState {
+            slot: 1,
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            ..BeaconState::default()
+        };
+        for key in &keys {
+            state
+                .validators
+                .push(Validator {
+                    pubkey: key.public_key(),
+                    effective_balance: 32_000_000_000,
+                    exit_epoch: FAR_FUTURE_EPOCH,
+                    ..Validator::default()
+                })
+                .unwrap();
+            state.balances.push(32_000_000_000).unwrap();
+        }
+        context
+            .store
+            .hot_states()
+            .put_snapshot(message.state_root, &state)
+            .unwrap();
+        let head_root = context
+            .store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root,
+            head_slot: 1,
+            ..ChainInfo::default()
+        };
+
+        let query = |randao_reveal, skip: bool| {
+            Query(ProduceBlockQuery {
+                randao_reveal,
+                graffiti: B256::repeat_byte(9),
+                skip_randao_verification: skip.then(String::new),
+            })
+        };
+        let produce = |context: &ApiContext, randao_reveal, skip| {
+            get_block_v3(
+                State(context.clone()),
+                Path("2".to_string()),
+                query(randao_reveal, skip),
+            )
+        };
+
+        assert!(matches!(
+            produce(&context, G2_POINT_AT_INFINITY, true).await,
+            Err(ApiError::Unavailable(_))
+        ));
+        assert!(matches!(
+            produce(&context, BLSSignature::default(), true).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let (requester, mut requests) = mpsc::channel::<PayloadRequest>(1);
+        context.payload_requester = Some(requester);
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                assert_eq!(request.parent_beacon_block_root, head_root);
+                let _ = request.result.send(Ok(BuiltPayload {
+                    block_value: U256::from(7),
+                    ..BuiltPayload::default()
+                }));
+            }
+        });
+
+        let mut advanced = state.clone();
+        SlotReplayer.process_slots(&mut advanced, 2).unwrap();
+        let proposer_index = compute_epoch_proposers(&advanced).unwrap()[2];
+        let domain =
+            context
+                .fork_schedule
+                .get_domain(DOMAIN_RANDAO, 0, advanced.genesis_validators_root);
+        let randao_reveal =
+            keys[proposer_index as usize].sign(compute_signing_root(&0u64, domain).as_slice());
+        assert!(matches!(
+            produce(&context, G2_POINT_AT_INFINITY, false).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let response = produce(&context, randao_reveal, false).await.unwrap();
+        let headers = response.headers();
+        assert_eq!(headers[CONSENSUS_VERSION_HEADER], "deneb");
+        assert_eq!(headers[EXECUTION_PAYLOAD_BLINDED_HEADER], "false");
+        assert_eq!(headers[EXECUTION_PAYLOAD_VALUE_HEADER], "7");
+        assert_eq!(headers[CONSENSUS_BLOCK_VALUE_HEADER], "1000000000");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let produced: ProduceBlockResponse<BlockContents> = serde_json::from_slice(&body).unwrap();
+        let block = produced.data.block;
+        assert_eq!(
+            (block.slot, block.proposer_index, block.parent_root),
+            (2, proposer_index, head_root)
+        );
+        assert_eq!(block.body.randao_reveal, randao_reveal);
+        assert_eq!(block.body.graffiti, B256::repeat_byte(9));
+        assert_ne!(block.state_root, B256::ZERO);
+    }
+
+    /// Votes for 3 deposits, proving each with an empty proof.
+    struct StubEth1;
+
+    impl Eth1Provider for StubEth1 {
+        fn eth1_data_vote(&self, _state: &BeaconState) -> Eth1Data {
+            Eth1Data {
+                deposit_count: 3,
+                block_hash: B256::repeat_byte(3),
+                ..Eth1Data::default()
+            }
+        }
+
+        fn deposits(
+            &self,
+            eth1_deposit_index: u64,
+            eth1_data: &Eth1Data,
+        ) -> Result<Vec<Deposit>, String> {
+            Ok((eth1_deposit_index..eth1_data.deposit_count)
+                .map(|_| Deposit::default())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_eth1_data_and_deposits() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let mut context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        let mut state = BeaconState {
+            eth1_data: Eth1Data {
+                deposit_count: 1,
+                ..Eth1Data::default()
+            },
+            ..BeaconState::default()
+        };
+        assert!(matches!(
+            eth1_data_and_deposits(&context, &state),
+            Err(ApiError::Unavailable(_))
+        ));
+
+        context.eth1 = Some(Arc::new(StubEth1));
+        let (vote, deposits) = eth1_data_and_deposits(&context, &state).unwrap();
+        assert_eq!(vote.deposit_count, 3);
+        // The vote doesn't reach a majority, only the deposit the state knows is pending.
+        assert_eq!(deposits.len(), 1);
+
+        state.eth1_deposit_index = 1;
+        let (_, deposits) = eth1_data_and_deposits(&context, &state).unwrap();
+        assert!(deposits.is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use alloy_primitives::B256;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use ream_consensus::{
+    beacon_state::BeaconState, checkpoint::Checkpoint, constants::SLOTS_PER_HISTORICAL_ROOT,
+    fork::Fork, misc::compute_start_slot_at_epoch,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::{ApiContext, ChainInfo},
+    error::ApiError,
+    id::StateId,
+    response::BeaconResponse,
+};
+
+/// A state looked up by [`StateId`].
+#[derive(Debug, Clone)]
+pub struct ResolvedState {
+    pub root: B256,
+    pub state: Arc<BeaconState>,
+    pub execution_optimistic: bool,
+    pub finalized: bool,
+}
+
+impl ResolvedState {
+    pub fn response<T>(&self, data: T) -> BeaconResponse<T> {
+        BeaconResponse {
+            execution_optimistic: self.execution_optimistic,
+            finalized: self.finalized,
+            data,
+        }
+    }
+}
+
+/// Finds the state `state_id` refers to on the canonical chain.
+///
+/// A state is finalized when it's canonical and not after the finalized checkpoint. Blocks are
+/// only imported optimistically at the head, so any other state inherits the head's flag unless
+/// it's finalized.
+pub fn resolve_state(context: &ApiContext, state_id: StateId) -> Result<ResolvedState, ApiError> {
+    let chain = context.chain_info();
+    let root = match state_id {
+        StateId::Head => block_state_root(context, chain.head_root)?,
+        StateId::Genesis => state_root_at_slot(context, &chain, 0)?,
+        StateId::Finalized => block_state_root(context, chain.finalized_checkpoint.root)?,
+        StateId::Justified => block_state_root(context, chain.justified_checkpoint.root)?,
+        StateId::Slot(slot) => state_root_at_slot(context, &chain, slot)?,
+        StateId::Root(root) => root,
+    };
+    let state = context
+        .store
+        .get_state(&root, context.replayer.as_ref())?
+        .ok_or_else(|| ApiError::NotFound(format!("state {state_id} not found")))?;
+
+    let block_slot = state.latest_block_header.slot;
+    let canonical =
+        context.store.get_block_root_at_slot(block_slot)? == Some(state.latest_block_root(root));
+    let finalized =
+        canonical && state.slot <= compute_start_slot_at_epoch(chain.finalized_checkpoint.epoch);
+    Ok(ResolvedState {
+        root,
+        execution_optimistic: chain.head_optimistic && !finalized,
+        finalized,
+        state,
+    })
+}
+
+fn block_state_root(context: &ApiContext, block_root: B256) -> Result<B256, ApiError> {
+    context
+        .store
+        .get_block(&block_root)?
+        .map(|block| block.message.state_root)
+        .ok_or_else(|| ApiError::NotFound(format!("block {block_root} not found")))
+}
+
+/// Recent slots are looked up in the `state_roots` of the head state, which covers skipped
+/// slots, older ones in the canonical block index.
+fn state_root_at_slot(
+    context: &ApiContext,
+    chain: &ChainInfo,
+    slot: u64,
+) -> Result<B256, ApiError> {
+    let not_found = || ApiError::NotFound(format!("no canonical state at slot {slot}"));
+    if slot > chain.head_slot {
+        return Err(not_found());
+    }
+    let head_state_root = block_state_root(context, chain.head_root)?;
+    if slot == chain.head_slot {
+        return Ok(head_state_root);
+    }
+    if slot + SLOTS_PER_HISTORICAL_ROOT >= chain.head_slot {
+        if let Some(head_state) = context
+            .store
+            .get_state(&head_state_root, context.replayer.as_ref())?
+        {
+            if slot < head_state.slot && slot + SLOTS_PER_HISTORICAL_ROOT >= head_state.slot {
+                return Ok(head_state.state_roots[(slot % SLOTS_PER_HISTORICAL_ROOT) as usize]);
+            }
+        }
+    }
+
+    let block_root = context
+        .store
+        .get_block_root_at_slot(slot)?
+        .ok_or_else(not_found)?;
+    let block = context
+        .store
+        .get_block(&block_root)?
+        .ok_or_else(not_found)?;
+    if block.message.slot == slot {
+        Ok(block.message.state_root)
+    } else {
+        Err(not_found())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootData {
+    pub root: B256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityCheckpoints {
+    pub previous_justified: Checkpoint,
+    pub current_justified: Checkpoint,
+    pub finalized: Checkpoint,
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/root`
+pub async fn get_state_root(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+) -> Result<Json<BeaconResponse<RootData>>, ApiError> {
+    let state = resolve_state(&context, state_id.parse()?)?;
+    Ok(Json(state.response(RootData { root: state.root })))
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/fork`
+pub async fn get_state_fork(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+) -> Result<Json<BeaconResponse<Fork>>, ApiError> {
+    let state = resolve_state(&context, state_id.parse()?)?;
+    Ok(Json(state.response(state.state.fork)))
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/finality_checkpoints`
+pub async fn get_finality_checkpoints(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+) -> Result<Json<BeaconResponse<FinalityCheckpoints>>, ApiError> {
+    let state = resolve_state(&context, state_id.parse()?)?;
+    Ok(Json(state.response(FinalityCheckpoints {
+        previous_justified: state.state.previous_justified_checkpoint,
+        current_justified: state.state.current_justified_checkpoint,
+        finalized: state.state.finalized_checkpoint,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_block_header::BeaconBlockHeader,
+        fork_schedule::ForkSchedule,
+    };
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+
+    use super::*;
+
+    /// Stores a block and a snapshot of its post-state at `state_slot`.
+    fn import(
+        store: &HotColdStore,
+        slot: u64,
+        parent_root: B256,
+        state_slot: u64,
+        state_roots: &[B256],
+    ) -> (B256, B256) {
+        let state_root = B256::with_last_byte(state_slot as u8 + 1);
+        let message = BeaconBlock {
+            slot,
+            parent_root,
+            state_root,
+            ..BeaconBlock::default()
+        };
+        let mut state = BeaconState {
+            slot: state_slot,
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            ..BeaconState::default()
+        };
+        for (slot, root) in state_roots.iter().enumerate() {
+            state.state_roots[slot] = *root;
+        }
+        let block_root = store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        store.hot_states().put_snapshot(state_root, &state).unwrap();
+        (block_root, state_root)
+    }
+
+    #[test]
+    fn test_resolve_state() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+
+        // 0 <- 1 <- 3, with slot 2 skipped.
+        let (genesis, genesis_state) = import(&store, 0, B256::ZERO, 0, &[]);
+        let (a, a_state) = import(&store, 1, genesis, 1, &[]);
+        let (_, skipped_state) = import(&store, 1, genesis, 2, &[]);
+        let (head, head_state) = import(&store, 3, a, 3, &[genesis_state, a_state, skipped_state]);
+        store.hot_blocks().set_canonical_head(head).unwrap();
+
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        *context.chain.write().unwrap() = ChainInfo {
+            head_root: head,
+            head_slot: 3,
+            finalized_checkpoint: Checkpoint {
+                epoch: 0,
+                root: genesis,
+            },
+            head_optimistic: true,
+            ..ChainInfo::default()
+        };
+
+        let state = resolve_state(&context, StateId::Head).unwrap();
+        assert_eq!(state.root, head_state);
+        assert!(state.execution_optimistic);
+        assert!(!state.finalized);
+
+        assert_eq!(
+            resolve_state(&context, StateId::Slot(2)).unwrap().root,
+            skipped_state
+        );
+
+        for state_id in [StateId::Genesis, StateId::Finalized, StateId::Slot(0)] {
+            let state = resolve_state(&context, state_id).unwrap();
+            assert_eq!(state.root, genesis_state);
+            assert!(!state.execution_optimistic);
+            assert!(state.finalized);
+        }
+
+        assert!(matches!(
+            resolve_state(&context, StateId::Slot(4)),
+            Err(ApiError::NotFound(_))
+        ));
+        assert!(matches!(
+            resolve_state(&context, StateId::Root(B256::repeat_byte(0xff))),
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
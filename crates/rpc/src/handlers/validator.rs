@@ -0,0 +1,369 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use axum::{
+    extract::{Path, RawQuery, State},
+    Json,
+};
+use ream_consensus::{
+    constants::FAR_FUTURE_EPOCH, misc::compute_epoch_at_slot, validator::Validator,
+};
+use serde::{Deserialize, Serialize};
+
+use super::state::{resolve_state, ResolvedState};
+use crate::{
+    context::ApiContext,
+    error::ApiError,
+    id::{StateId, ValidatorId},
+    query::query_values,
+    response::BeaconResponse,
+};
+
+/// Lifecycle status of a validator as defined by the beacon-APIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ActiveSlashed,
+    ExitedUnslashed,
+    ExitedSlashed,
+    WithdrawalPossible,
+    WithdrawalDone,
+}
+
+impl ValidatorStatus {
+    pub fn of(validator: &Validator, epoch: u64) -> Self {
+        if validator.is_active_validator(epoch) {
+            if validator.exit_epoch == FAR_FUTURE_EPOCH {
+                ValidatorStatus::ActiveOngoing
+            } else if validator.slashed {
+                ValidatorStatus::ActiveSlashed
+            } else {
+                ValidatorStatus::ActiveExiting
+            }
+        } else if validator.exit_epoch <= epoch {
+            if epoch < validator.withdrawable_epoch {
+                if validator.slashed {
+                    ValidatorStatus::ExitedSlashed
+                } else {
+                    ValidatorStatus::ExitedUnslashed
+                }
+            } else if validator.effective_balance == 0 {
+                ValidatorStatus::WithdrawalDone
+            } else {
+                ValidatorStatus::WithdrawalPossible
+            }
+        } else if validator.activation_eligibility_epoch == FAR_FUTURE_EPOCH {
+            ValidatorStatus::PendingInitialized
+        } else {
+            ValidatorStatus::PendingQueued
+        }
+    }
+
+    /// The general status, `pending`, `active`, `exited` or `withdrawal`.
+    pub fn general(&self) -> &'static str {
+        match self {
+            ValidatorStatus::PendingInitialized | ValidatorStatus::PendingQueued => "pending",
+            ValidatorStatus::ActiveOngoing
+            | ValidatorStatus::ActiveExiting
+            | ValidatorStatus::ActiveSlashed => "active",
+            ValidatorStatus::ExitedUnslashed | ValidatorStatus::ExitedSlashed => "exited",
+            ValidatorStatus::WithdrawalPossible | ValidatorStatus::WithdrawalDone => "withdrawal",
+        }
+    }
+
+    /// Whether the `status` filter of a request, a status or a general status, matches.
+    pub fn matches(&self, status: &str) -> bool {
+        self.to_string() == status || self.general() == status
+    }
+}
+
+impl fmt::Display for ValidatorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValidatorStatus::PendingInitialized => "pending_initialized",
+            ValidatorStatus::PendingQueued => "pending_queued",
+            ValidatorStatus::ActiveOngoing => "active_ongoing",
+            ValidatorStatus::ActiveExiting => "active_exiting",
+            ValidatorStatus::ActiveSlashed => "active_slashed",
+            ValidatorStatus::ExitedUnslashed => "exited_unslashed",
+            ValidatorStatus::ExitedSlashed => "exited_slashed",
+            ValidatorStatus::WithdrawalPossible => "withdrawal_possible",
+            ValidatorStatus::WithdrawalDone => "withdrawal_done",
+        })
+    }
+}
+
+const STATUS_FILTERS: [&str; 13] = [
+    "pending_initialized",
+    "pending_queued",
+    "active_ongoing",
+    "active_exiting",
+    "active_slashed",
+    "exited_unslashed",
+    "exited_slashed",
+    "withdrawal_possible",
+    "withdrawal_done",
+    "pending",
+    "active",
+    "exited",
+    "withdrawal",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorData {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub balance: u64,
+    pub status: ValidatorStatus,
+    pub validator: Validator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorBalance {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub balance: u64,
+}
+
+/// Body of `POST /eth/v1/beacon/states/{state_id}/validators`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ValidatorsRequest {
+    #[serde(default)]
+    pub ids: Vec<ValidatorId>,
+    #[serde(default)]
+    pub statuses: Vec<String>,
+}
+
+impl ValidatorsRequest {
+    fn from_query(query: Option<&str>) -> Result<Self, ApiError> {
+        Ok(Self {
+            ids: query_values(query, "id")
+                .iter()
+                .map(|id| id.parse())
+                .collect::<Result<_, _>>()?,
+            statuses: query_values(query, "status"),
+        })
+    }
+}
+
+/// Validators of `state` selected by `ids`, all of them if empty, with their index. Unknown ids
+/// are skipped.
+fn select_validators<'a>(
+    state: &'a ResolvedState,
+    ids: &[ValidatorId],
+) -> impl Iterator<Item = (u64, &'a Validator)> {
+    let indices: HashSet<u64> = ids
+        .iter()
+        .filter_map(|id| match id {
+            ValidatorId::Index(index) => Some(*index),
+            ValidatorId::Pubkey(_) => None,
+        })
+        .collect();
+    let pubkeys: HashSet<_> = ids
+        .iter()
+        .filter_map(|id| match id {
+            ValidatorId::Pubkey(pubkey) => Some(*pubkey),
+            ValidatorId::Index(_) => None,
+        })
+        .collect();
+    let select_all = ids.is_empty();
+    state
+        .state
+        .validators
+        .iter()
+        .enumerate()
+        .map(|(index, validator)| (index as u64, validator))
+        .filter(move |(index, validator)| {
+            select_all || indices.contains(index) || pubkeys.contains(&validator.pubkey)
+        })
+}
+
+fn validator_data(state: &ResolvedState, index: u64, validator: &Validator) -> ValidatorData {
+    ValidatorData {
+        index,
+        balance: state.state.balances[index as usize],
+        status: ValidatorStatus::of(validator, compute_epoch_at_slot(state.state.slot)),
+        validator: validator.clone(),
+    }
+}
+
+fn get_validators(
+    context: &ApiContext,
+    state_id: &str,
+    request: ValidatorsRequest,
+) -> Result<BeaconResponse<Vec<ValidatorData>>, ApiError> {
+    if let Some(status) = request
+        .statuses
+        .iter()
+        .find(|status| !STATUS_FILTERS.contains(&status.as_str()))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "invalid validator status: {status}"
+        )));
+    }
+    let state = resolve_state(context, StateId::from_str(state_id)?)?;
+    let validators = select_validators(&state, &request.ids)
+        .map(|(index, validator)| validator_data(&state, index, validator))
+        .filter(|data| {
+            request.statuses.is_empty()
+                || request
+                    .statuses
+                    .iter()
+                    .any(|status| data.status.matches(status))
+        })
+        .collect();
+    Ok(state.response(validators))
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/validators`
+pub async fn get_state_validators(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Result<Json<BeaconResponse<Vec<ValidatorData>>>, ApiError> {
+    let request = ValidatorsRequest::from_query(query.as_deref())?;
+    get_validators(&context, &state_id, request).map(Json)
+}
+
+/// `POST /eth/v1/beacon/states/{state_id}/validators`, for id lists too long for a URL.
+pub async fn post_state_validators(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+    request: Option<Json<ValidatorsRequest>>,
+) -> Result<Json<BeaconResponse<Vec<ValidatorData>>>, ApiError> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    get_validators(&context, &state_id, request).map(Json)
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/validators/{validator_id}`
+pub async fn get_state_validator(
+    State(context): State<ApiContext>,
+    Path((state_id, validator_id)): Path<(String, String)>,
+) -> Result<Json<BeaconResponse<ValidatorData>>, ApiError> {
+    let validator_id: ValidatorId = validator_id.parse()?;
+    let state = resolve_state(&context, state_id.parse()?)?;
+    let (index, validator) = select_validators(&state, &[validator_id])
+        .next()
+        .ok_or_else(|| ApiError::NotFound(format!("validator {validator_id:?} not found")))?;
+    Ok(Json(
+        state.response(validator_data(&state, index, validator)),
+    ))
+}
+
+fn get_balances(
+    context: &ApiContext,
+    state_id: &str,
+    ids: &[ValidatorId],
+) -> Result<BeaconResponse<Vec<ValidatorBalance>>, ApiError> {
+    let state = resolve_state(context, state_id.parse()?)?;
+    let balances = select_validators(&state, ids)
+        .map(|(index, _)| ValidatorBalance {
+            index,
+            balance: state.state.balances[index as usize],
+        })
+        .collect();
+    Ok(state.response(balances))
+}
+
+/// `GET /eth/v1/beacon/states/{state_id}/validator_balances`
+pub async fn get_validator_balances(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Result<Json<BeaconResponse<Vec<ValidatorBalance>>>, ApiError> {
+    let ids = ValidatorsRequest::from_query(query.as_deref())?.ids;
+    get_balances(&context, &state_id, &ids).map(Json)
+}
+
+/// `POST /eth/v1/beacon/states/{state_id}/validator_balances`
+pub async fn post_validator_balances(
+    State(context): State<ApiContext>,
+    Path(state_id): Path<String>,
+    ids: Option<Json<Vec<ValidatorId>>>,
+) -> Result<Json<BeaconResponse<Vec<ValidatorBalance>>>, ApiError> {
+    let ids = ids.map(|Json(ids)| ids).unwrap_or_default();
+    get_balances(&context, &state_id, &ids).map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_status() {
+        let validator =
+            |activation_eligibility_epoch, activation_epoch, exit_epoch, slashed| Validator {
+                effective_balance: 32_000_000_000,
+                slashed,
+                activation_eligibility_epoch,
+                activation_epoch,
+                exit_epoch,
+                withdrawable_epoch: exit_epoch.saturating_add(256),
+                ..Validator::default()
+            };
+        let far = FAR_FUTURE_EPOCH;
+
+        for (validator, epoch, status) in [
+            (
+                validator(far, far, far, false),
+                10,
+                ValidatorStatus::PendingInitialized,
+            ),
+            (
+                validator(5, far, far, false),
+                10,
+                ValidatorStatus::PendingQueued,
+            ),
+            (
+                validator(5, 12, far, false),
+                10,
+                ValidatorStatus::PendingQueued,
+            ),
+            (
+                validator(0, 0, far, false),
+                10,
+                ValidatorStatus::ActiveOngoing,
+            ),
+            (
+                validator(0, 0, 20, false),
+                10,
+                ValidatorStatus::ActiveExiting,
+            ),
+            (
+                validator(0, 0, 20, true),
+                10,
+                ValidatorStatus::ActiveSlashed,
+            ),
+            (
+                validator(0, 0, 5, false),
+                10,
+                ValidatorStatus::ExitedUnslashed,
+            ),
+            (validator(0, 0, 5, true), 10, ValidatorStatus::ExitedSlashed),
+            (
+                validator(0, 0, 5, false),
+                300,
+                ValidatorStatus::WithdrawalPossible,
+            ),
+            (
+                Validator {
+                    effective_balance: 0,
+                    ..validator(0, 0, 5, false)
+                },
+                300,
+                ValidatorStatus::WithdrawalDone,
+            ),
+        ] {
+            assert_eq!(ValidatorStatus::of(&validator, epoch), status);
+        }
+
+        assert!(ValidatorStatus::ActiveSlashed.matches("active"));
+        assert!(ValidatorStatus::ActiveSlashed.matches("active_slashed"));
+        assert!(!ValidatorStatus::ActiveSlashed.matches("exited"));
+    }
+}
@@ -0,0 +1,165 @@
+use std::{fmt, str::FromStr};
+
+use alloy_primitives::B256;
+use ream_consensus::bls::BLSPubkey;
+use serde::{Deserialize, Deserializer};
+
+use crate::error::ApiError;
+
+/// `state_id` path parameter of the `/eth/v1/beacon/states` endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateId {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(u64),
+    Root(B256),
+}
+
+impl FromStr for StateId {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(StateId::Head),
+            "genesis" => Ok(StateId::Genesis),
+            "finalized" => Ok(StateId::Finalized),
+            "justified" => Ok(StateId::Justified),
+            _ if s.starts_with("0x") => s
+                .parse()
+                .map(StateId::Root)
+                .map_err(|_| ApiError::BadRequest(format!("invalid state root: {s}"))),
+            _ => s
+                .parse()
+                .map(StateId::Slot)
+                .map_err(|_| ApiError::BadRequest(format!("invalid state ID: {s}"))),
+        }
+    }
+}
+
+impl fmt::Display for StateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateId::Head => f.write_str("head"),
+            StateId::Genesis => f.write_str("genesis"),
+            StateId::Finalized => f.write_str("finalized"),
+            StateId::Justified => f.write_str("justified"),
+            StateId::Slot(slot) => write!(f, "{slot}"),
+            StateId::Root(root) => write!(f, "{root}"),
+        }
+    }
+}
+
+/// `block_id` path parameter of the `/eth/*/beacon/blocks` and `headers` endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(u64),
+    Root(B256),
+}
+
+impl FromStr for BlockId {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(BlockId::Head),
+            "genesis" => Ok(BlockId::Genesis),
+            "finalized" => Ok(BlockId::Finalized),
+            "justified" => Ok(BlockId::Justified),
+            _ if s.starts_with("0x") => s
+                .parse()
+                .map(BlockId::Root)
+                .map_err(|_| ApiError::BadRequest(format!("invalid block root: {s}"))),
+            _ => s
+                .parse()
+                .map(BlockId::Slot)
+                .map_err(|_| ApiError::BadRequest(format!("invalid block ID: {s}"))),
+        }
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockId::Head => f.write_str("head"),
+            BlockId::Genesis => f.write_str("genesis"),
+            BlockId::Finalized => f.write_str("finalized"),
+            BlockId::Justified => f.write_str("justified"),
+            BlockId::Slot(slot) => write!(f, "{slot}"),
+            BlockId::Root(root) => write!(f, "{root}"),
+        }
+    }
+}
+
+/// `validator_id` of the validator endpoints, an index or a public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidatorId {
+    Index(u64),
+    Pubkey(BLSPubkey),
+}
+
+impl FromStr for ValidatorId {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            s.parse()
+                .map(ValidatorId::Pubkey)
+                .map_err(|_| ApiError::BadRequest(format!("invalid validator public key: {s}")))
+        } else {
+            s.parse()
+                .map(ValidatorId::Index)
+                .map_err(|_| ApiError::BadRequest(format!("invalid validator index: {s}")))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ValidatorId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_state_id() {
+        assert_eq!("head".parse(), Ok(StateId::Head));
+        assert_eq!("finalized".parse(), Ok(StateId::Finalized));
+        assert_eq!("12".parse(), Ok(StateId::Slot(12)));
+        assert_eq!(
+            format!("{}", B256::repeat_byte(1)).parse(),
+            Ok(StateId::Root(B256::repeat_byte(1)))
+        );
+        assert!(matches!(
+            "0x12".parse::<StateId>(),
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(matches!(
+            "latest".parse::<StateId>(),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_validator_id() {
+        assert_eq!("7".parse(), Ok(ValidatorId::Index(7)));
+        assert_eq!(
+            format!("{}", BLSPubkey::repeat_byte(2)).parse(),
+            Ok(ValidatorId::Pubkey(BLSPubkey::repeat_byte(2)))
+        );
+        assert!(matches!(
+            format!("{}", B256::ZERO).parse::<ValidatorId>(),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -1,14 +1,17 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod cache;
+pub mod config;
+pub mod context;
+pub mod encoding;
+pub mod error;
+pub mod eth1;
+pub mod events;
+pub mod handlers;
+pub mod id;
+pub mod payload;
+pub mod preparation;
+pub mod publish;
+pub mod query;
+pub mod response;
+pub mod router;
+pub mod server;
+pub mod validation;
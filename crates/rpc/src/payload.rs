@@ -0,0 +1,44 @@
+use alloy_primitives::{B256, U256};
+use ream_consensus::{
+    beacon_block_body::KZGCommitment,
+    blob_sidecar::{Blob, KZGProof},
+    execution_payload::ExecutionPayload,
+    withdrawal::Withdrawal,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// The blobs of a built payload with their commitments and proofs, in the same order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobsBundle {
+    pub commitments: Vec<KZGCommitment>,
+    pub proofs: Vec<KZGProof>,
+    pub blobs: Vec<Blob>,
+}
+
+/// A payload built by the execution layer for a block being produced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuiltPayload {
+    pub payload: ExecutionPayload,
+    /// Fees paid to the fee recipient, in wei.
+    pub block_value: U256,
+    pub blobs_bundle: BlobsBundle,
+}
+
+/// A payload needed to produce the block of `proposer_index` at `slot`, handed to the node to
+/// build on the execution layer. The node picks the fee recipient of the proposer.
+///
+/// The fields are the payload attributes of `engine_forkchoiceUpdatedV3` and the execution
+/// block to build on.
+#[derive(Debug)]
+pub struct PayloadRequest {
+    pub slot: u64,
+    pub proposer_index: u64,
+    pub parent_hash: B256,
+    pub timestamp: u64,
+    pub prev_randao: B256,
+    pub withdrawals: Vec<Withdrawal>,
+    pub parent_beacon_block_root: B256,
+    pub result: oneshot::Sender<Result<BuiltPayload, String>>,
+}
+
+pub type PayloadRequester = mpsc::Sender<PayloadRequest>;
@@ -0,0 +1,56 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use alloy_primitives::Address;
+use ream_builder::types::SignedValidatorRegistrationV1;
+use ream_consensus::bls::BLSPubkey;
+
+/// Fee recipients and builder registrations validator clients sent for their validators, kept
+/// until the node restarts. Block production pays the fees of a proposer to its fee recipient.
+#[derive(Debug, Default)]
+pub struct ProposerPreparations {
+    fee_recipients: RwLock<HashMap<u64, Address>>,
+    registrations: RwLock<HashMap<BLSPubkey, SignedValidatorRegistrationV1>>,
+}
+
+impl ProposerPreparations {
+    pub fn prepare(&self, validator_index: u64, fee_recipient: Address) {
+        self.fee_recipients
+            .write()
+            .expect("fee recipients lock poisoned")
+            .insert(validator_index, fee_recipient);
+    }
+
+    pub fn fee_recipient(&self, validator_index: u64) -> Option<Address> {
+        self.fee_recipients
+            .read()
+            .expect("fee recipients lock poisoned")
+            .get(&validator_index)
+            .copied()
+    }
+
+    /// Keeps the newest registration of each validator, returns whether `registration` was
+    /// kept.
+    pub fn register(&self, registration: SignedValidatorRegistrationV1) -> bool {
+        let mut registrations = self
+            .registrations
+            .write()
+            .expect("validator registrations lock poisoned");
+        let pubkey = registration.message.pubkey;
+        if registrations
+            .get(&pubkey)
+            .is_some_and(|current| current.message.timestamp >= registration.message.timestamp)
+        {
+            return false;
+        }
+        registrations.insert(pubkey, registration);
+        true
+    }
+
+    pub fn registration(&self, pubkey: &BLSPubkey) -> Option<SignedValidatorRegistrationV1> {
+        self.registrations
+            .read()
+            .expect("validator registrations lock poisoned")
+            .get(pubkey)
+            .cloned()
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use ream_consensus::{
+    aggregate_and_proof::SignedAggregateAndProof, attestation::Attestation,
+    attester_slashing::AttesterSlashing, beacon_block::SignedBeaconBlock,
+    bls_to_execution_change::SignedBLSToExecutionChange, proposer_slashing::ProposerSlashing,
+    voluntary_exit::SignedVoluntaryExit,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::Span;
+
+/// Validation a published block must pass before it's broadcast, the `broadcast_validation`
+/// query parameter of `POST /eth/v2/beacon/blocks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastValidation {
+    /// The gossip rules only, the block is broadcast before it's imported.
+    #[default]
+    Gossip,
+    /// The block is imported through the state transition and fork choice before it's
+    /// broadcast.
+    Consensus,
+    /// As [`BroadcastValidation::Consensus`], and the proposer must not have published another
+    /// block for the slot.
+    ConsensusAndEquivocation,
+}
+
+/// What became of a published block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishOutcome {
+    /// Broadcast and imported.
+    Imported,
+    /// Broadcast, but the import failed for the given reason.
+    BroadcastOnly(String),
+}
+
+/// A block published through the API, handed to the node to validate, broadcast on gossipsub
+/// and import through fork choice. A rejected block is neither broadcast nor imported.
+#[derive(Debug)]
+pub struct PublishBlockRequest {
+    pub block: Arc<SignedBeaconBlock>,
+    pub validation: BroadcastValidation,
+    pub result: oneshot::Sender<Result<PublishOutcome, String>>,
+    /// Span of the block since it was received, for the import stages to run in.
+    pub span: Span,
+}
+
+pub type BlockPublisher = mpsc::Sender<PublishBlockRequest>;
+
+/// An operation accepted into the pool through the API, for the node to broadcast on its
+/// gossip topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GossipOperation {
+    Attestation(Attestation),
+    AggregateAndProof(SignedAggregateAndProof),
+    VoluntaryExit(SignedVoluntaryExit),
+    ProposerSlashing(ProposerSlashing),
+    AttesterSlashing(AttesterSlashing),
+    BlsToExecutionChange(SignedBLSToExecutionChange),
+}
+
+pub type OperationPublisher = mpsc::Sender<GossipOperation>;
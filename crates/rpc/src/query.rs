@@ -0,0 +1,37 @@
+/// Values of the query parameter `key`, which the beacon-APIs allow to be repeated and to hold
+/// comma separated lists, e.g. `?id=1,2&id=3`.
+pub fn query_values(query: Option<&str>, key: &str) -> Vec<String> {
+    let mut values = vec![];
+    for (name, value) in query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+    {
+        if name == key {
+            values.extend(
+                value
+                    .replace("%2C", ",")
+                    .replace("%2c", ",")
+                    .split(',')
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_values() {
+        assert_eq!(
+            query_values(Some("id=1,2&status=active&id=3%2C4"), "id"),
+            ["1", "2", "3", "4"]
+        );
+        assert_eq!(query_values(Some("id="), "id"), Vec::<String>::new());
+        assert_eq!(query_values(None, "id"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,48 @@
+use alloy_primitives::B256;
+use ream_consensus::fork_schedule::ForkName;
+use serde::{Deserialize, Serialize};
+
+/// `{"data": ...}`, the envelope of most beacon-APIs responses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataResponse<T> {
+    pub data: T,
+}
+
+impl<T> DataResponse<T> {
+    pub fn new(data: T) -> Self {
+        Self { data }
+    }
+}
+
+/// Envelope of responses read from the chain, flagging whether the data can still change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconResponse<T> {
+    pub execution_optimistic: bool,
+    pub finalized: bool,
+    pub data: T,
+}
+
+/// [`BeaconResponse`] of fork dependent data, naming the fork it's encoded for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedResponse<T> {
+    pub version: ForkName,
+    pub execution_optimistic: bool,
+    pub finalized: bool,
+    pub data: T,
+}
+
+/// Envelope of validator duties, which stay valid as long as the chain still contains
+/// `dependent_root`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DutiesResponse<T> {
+    pub dependent_root: B256,
+    pub execution_optimistic: bool,
+    pub data: T,
+}
+
+/// Envelope of data derived from the head that is never finalized.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptimisticResponse<T> {
+    pub execution_optimistic: bool,
+    pub data: T,
+}
@@ -0,0 +1,138 @@
+use axum::{
+    http::Uri,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    context::ApiContext,
+    error::ApiError,
+    handlers::{
+        attestation, blob, block, debug, duties, events, genesis, node, pool, preparation,
+        production, state, validator,
+    },
+};
+
+pub fn router(context: ApiContext) -> Router {
+    Router::new()
+        .route("/eth/v1/beacon/genesis", get(genesis::get_genesis))
+        .route(
+            "/eth/v1/beacon/pool/attestations",
+            get(pool::get_attestations).post(pool::post_attestations),
+        )
+        .route(
+            "/eth/v1/beacon/pool/voluntary_exits",
+            get(pool::get_voluntary_exits).post(pool::post_voluntary_exit),
+        )
+        .route(
+            "/eth/v1/beacon/pool/proposer_slashings",
+            get(pool::get_proposer_slashings).post(pool::post_proposer_slashing),
+        )
+        .route(
+            "/eth/v1/beacon/pool/attester_slashings",
+            get(pool::get_attester_slashings).post(pool::post_attester_slashing),
+        )
+        .route(
+            "/eth/v1/beacon/pool/bls_to_execution_changes",
+            get(pool::get_bls_to_execution_changes).post(pool::post_bls_to_execution_changes),
+        )
+        .route("/eth/v1/beacon/headers", get(block::get_block_headers))
+        .route(
+            "/eth/v1/beacon/headers/{block_id}",
+            get(block::get_block_header),
+        )
+        .route("/eth/v1/beacon/blocks", post(block::post_block))
+        .route("/eth/v2/beacon/blocks", post(block::post_block_v2))
+        .route("/eth/v2/beacon/blocks/{block_id}", get(block::get_block))
+        .route(
+            "/eth/v1/beacon/blocks/{block_id}/root",
+            get(block::get_block_root),
+        )
+        .route(
+            "/eth/v1/beacon/blob_sidecars/{block_id}",
+            get(blob::get_blob_sidecars),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/root",
+            get(state::get_state_root),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/fork",
+            get(state::get_state_fork),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/finality_checkpoints",
+            get(state::get_finality_checkpoints),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/validators",
+            get(validator::get_state_validators).post(validator::post_state_validators),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/validators/{validator_id}",
+            get(validator::get_state_validator),
+        )
+        .route(
+            "/eth/v1/beacon/states/{state_id}/validator_balances",
+            get(validator::get_validator_balances).post(validator::post_validator_balances),
+        )
+        .route(
+            "/eth/v2/debug/beacon/states/{state_id}",
+            get(debug::get_debug_state),
+        )
+        .route("/eth/v2/debug/beacon/heads", get(debug::get_debug_heads))
+        .route(
+            "/eth/v1/debug/fork_choice",
+            get(debug::get_debug_fork_choice),
+        )
+        .route(
+            "/eth/v1/validator/duties/attester/{epoch}",
+            post(duties::post_attester_duties),
+        )
+        .route(
+            "/eth/v1/validator/duties/proposer/{epoch}",
+            get(duties::get_proposer_duties),
+        )
+        .route(
+            "/eth/v1/validator/duties/sync/{epoch}",
+            post(duties::post_sync_duties),
+        )
+        .route(
+            "/eth/v1/validator/attestation_data",
+            get(attestation::get_attestation_data),
+        )
+        .route(
+            "/eth/v1/validator/aggregate_attestation",
+            get(attestation::get_aggregate_attestation),
+        )
+        .route(
+            "/eth/v1/validator/aggregate_and_proofs",
+            post(attestation::post_aggregate_and_proofs),
+        )
+        .route(
+            "/eth/v1/validator/beacon_committee_subscriptions",
+            post(attestation::post_beacon_committee_subscriptions),
+        )
+        .route(
+            "/eth/v1/validator/prepare_beacon_proposer",
+            post(preparation::post_prepare_beacon_proposer),
+        )
+        .route(
+            "/eth/v1/validator/register_validator",
+            post(preparation::post_register_validator),
+        )
+        .route(
+            "/eth/v3/validator/blocks/{slot}",
+            get(production::get_block_v3),
+        )
+        .route("/eth/v1/events", get(events::get_events))
+        .route("/eth/v1/node/version", get(node::get_version))
+        .route("/eth/v1/node/syncing", get(node::get_syncing))
+        .route("/eth/v1/node/health", get(node::get_health))
+        .fallback(not_found)
+        .with_state(context)
+}
+
+async fn not_found(uri: Uri) -> ApiError {
+    ApiError::NotFound(format!("no route for {}", uri.path()))
+}
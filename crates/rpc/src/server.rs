@@ -0,0 +1,236 @@
+use std::{future::Future, io};
+
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{Method, StatusCode},
+    ServiceExt,
+};
+use tokio::net::TcpListener;
+use tower::{limit::ConcurrencyLimitLayer, ServiceBuilder};
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    timeout::TimeoutLayer,
+};
+use tracing::info;
+
+use crate::{
+    config::{CorsOrigin, HttpServerConfig},
+    context::ApiContext,
+    router::router,
+};
+
+/// Serves the beacon API on the configured address until `shutdown` completes.
+pub async fn start_http_server(
+    config: &HttpServerConfig,
+    context: ApiContext,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(config.socket_address()).await?;
+    info!("HTTP API listening on {}", listener.local_addr()?);
+    serve(listener, config, context, shutdown).await
+}
+
+pub async fn serve(
+    listener: TcpListener,
+    config: &HttpServerConfig,
+    context: ApiContext,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let router = router(context).layer(DefaultBodyLimit::max(config.max_body_size));
+    // Layered around the whole router rather than with `Router::layer`, which would give every
+    // route a concurrency limit of its own.
+    let service = ServiceBuilder::new()
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            config.request_timeout,
+        ))
+        .layer(cors_layer(&config.allow_origins))
+        .layer(ConcurrencyLimitLayer::new(config.max_concurrent_requests))
+        .service(router);
+    axum::serve(listener, ServiceExt::<Request>::into_make_service(service))
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// CORS headers for `allow_origins`, the `Eth-*` response headers included. Without them
+/// browsers refuse cross-origin responses.
+fn cors_layer(allow_origins: &[CorsOrigin]) -> CorsLayer {
+    let allow_origin = if allow_origins.contains(&CorsOrigin::Any) {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(allow_origins.iter().filter_map(|origin| match origin {
+            CorsOrigin::Any => None,
+            CorsOrigin::Origin(origin) => Some(origin.clone()),
+        }))
+    };
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers(Any)
+        .expose_headers(Any)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use alloy_primitives::B256;
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use ream_storage::{
+        hot_cold::{HotColdStore, StoreConfig},
+        memory_store::MemoryStore,
+        store::Store,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::oneshot,
+    };
+
+    use super::*;
+    use crate::{
+        events::{BeaconEvent, BlockEvent},
+        handlers::node::version_string,
+    };
+
+    async fn get(address: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                    .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    async fn request(address: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (stop, stopped) = oneshot::channel::<()>();
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        let server = tokio::spawn({
+            let context = context.clone();
+            async move {
+                let config = HttpServerConfig::default();
+                serve(listener, &config, context, async move {
+                    let _ = stopped.await;
+                })
+                .await
+            }
+        });
+
+        let response = get(address, "/eth/v1/node/version").await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with(&format!(
+            r#"{{"data":{{"version":"{}"}}}}"#,
+            version_string()
+        )));
+
+        let response = get(address, "/eth/v1/node/health").await;
+        assert!(response.starts_with("HTTP/1.1 503"));
+
+        let response = get(address, "/eth/v1/unknown").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+        assert!(response.ends_with(r#"{"code":404,"message":"no route for /eth/v1/unknown"}"#));
+
+        let response = get(address, "/eth/v1/events?topics=unknown").await;
+        assert!(response.starts_with("HTTP/1.1 400"));
+
+        let mut events = TcpStream::connect(address).await.unwrap();
+        events
+            .write_all(b"GET /eth/v1/events?topics=block HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        while context.events.connections() == 0 {
+            tokio::task::yield_now().await;
+        }
+        context.events.send(BeaconEvent::Block(BlockEvent {
+            slot: 1,
+            block: B256::ZERO,
+            execution_optimistic: false,
+        }));
+        let mut received = String::new();
+        let mut buffer = [0; 1024];
+        while !received.contains("event: block") {
+            let read = events.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "event stream closed");
+            received.push_str(&String::from_utf8_lossy(&buffer[..read]));
+        }
+        assert!(received.contains(r#"data: {"slot":"1","#));
+        drop(events);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_limits_and_cors() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (stop, stopped) = oneshot::channel::<()>();
+        let config = HttpServerConfig {
+            allow_origins: vec!["http://localhost:3000".parse().unwrap()],
+            max_body_size: 1024,
+            ..HttpServerConfig::default()
+        };
+        let context = ApiContext::new(Arc::new(store), ForkSchedule::mainnet());
+        let server = tokio::spawn(async move {
+            serve(listener, &config, context, async move {
+                let _ = stopped.await;
+            })
+            .await
+        });
+
+        let response = request(
+            address,
+            &format!(
+                "POST /eth/v1/beacon/blocks HTTP/1.1\r\nHost: localhost\r\n\
+                 Connection: close\r\nContent-Type: application/octet-stream\r\n\
+                 Content-Length: 2048\r\n\r\n{}",
+                "0".repeat(2048)
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 413"));
+
+        let preflight = |origin: &str| {
+            format!(
+                "OPTIONS /eth/v1/beacon/blocks HTTP/1.1\r\nHost: localhost\r\n\
+                 Connection: close\r\nOrigin: {origin}\r\n\
+                 Access-Control-Request-Method: POST\r\n\r\n"
+            )
+        };
+        let response = request(address, &preflight("http://localhost:3000")).await;
+        assert!(response.contains("access-control-allow-origin: http://localhost:3000"));
+        let response = request(address, &preflight("http://evil.example.org")).await;
+        assert!(!response.contains("access-control-allow-origin"));
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
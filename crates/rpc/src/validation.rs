@@ -0,0 +1,477 @@
+use alloy_primitives::B256;
+use ream_bls::{fast_aggregate_verify, verify};
+use ream_consensus::{
+    aggregate_and_proof::{is_aggregator, SignedAggregateAndProof},
+    attestation::{Attestation, IndexedAttestation},
+    attestation_data::is_slashable_attestation_data,
+    attester_slashing::AttesterSlashing,
+    beacon_block_header::SignedBeaconBlockHeader,
+    beacon_state::BeaconState,
+    bls::{BLSPubkey, BLSSignature},
+    bls_to_execution_change::SignedBLSToExecutionChange,
+    constants::{
+        BLS_WITHDRAWAL_PREFIX, DOMAIN_AGGREGATE_AND_PROOF, DOMAIN_BEACON_ATTESTER,
+        DOMAIN_BEACON_PROPOSER, DOMAIN_BLS_TO_EXECUTION_CHANGE, DOMAIN_SELECTION_PROOF,
+        DOMAIN_VOLUNTARY_EXIT, FAR_FUTURE_EPOCH, SHARD_COMMITTEE_PERIOD,
+    },
+    fork_schedule::{ForkName, ForkSchedule},
+    misc::{compute_domain, compute_epoch_at_slot, compute_signing_root},
+    proposer_slashing::ProposerSlashing,
+    validator::Validator,
+    voluntary_exit::SignedVoluntaryExit,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidOperation {
+    #[error("unknown validator {0}")]
+    UnknownValidator(u64),
+    #[error("validator {0} is not active")]
+    NotActive(u64),
+    #[error("validator {0} has already initiated an exit")]
+    AlreadyExiting(u64),
+    #[error("exit epoch {epoch} is after the current epoch {current_epoch}")]
+    FutureExit { epoch: u64, current_epoch: u64 },
+    #[error("validator {0} has not been active long enough to exit")]
+    TooYoungToExit(u64),
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("the headers are not a slashable pair")]
+    NotSlashableHeaders,
+    #[error("validator {0} is not slashable")]
+    NotSlashable(u64),
+    #[error("the attestations are not a slashable pair")]
+    NotSlashableAttestations,
+    #[error("attesting indices must be non-empty and strictly ascending")]
+    InvalidAttestingIndices,
+    #[error("the slashing doesn't slash any validator")]
+    NothingToSlash,
+    #[error("validator {0} doesn't have BLS withdrawal credentials")]
+    NotBlsCredentials(u64),
+    #[error("the public key doesn't match the withdrawal credentials of validator {0}")]
+    WrongBlsPubkey(u64),
+    #[error("target epoch {target_epoch} is not the epoch of slot {slot}")]
+    TargetEpochMismatch { target_epoch: u64, slot: u64 },
+    #[error("target epoch {0} is neither the current nor the previous epoch")]
+    NotRecent(u64),
+    #[error("the attestation has no aggregation bits set")]
+    NoAggregationBits,
+    #[error("block {0} is unknown")]
+    UnknownBlock(B256),
+    #[error("aggregation bits of length {got} for a committee of {expected}")]
+    CommitteeSizeMismatch { expected: usize, got: usize },
+    #[error("validator {0} is not a member of the committee")]
+    NotInCommittee(u64),
+    #[error("validator {0} is not selected to aggregate")]
+    NotAggregator(u64),
+}
+
+/// Check if ``validator`` is slashable.
+pub fn is_slashable_validator(validator: &Validator, epoch: u64) -> bool {
+    !validator.slashed
+        && validator.activation_epoch <= epoch
+        && epoch < validator.withdrawable_epoch
+}
+
+/// Checks operations submitted through the API against the head state, following the
+/// assertions of the state transition that processes them in a block.
+pub struct OperationValidator<'a> {
+    state: &'a BeaconState,
+    fork_schedule: &'a ForkSchedule,
+}
+
+impl<'a> OperationValidator<'a> {
+    pub fn new(state: &'a BeaconState, fork_schedule: &'a ForkSchedule) -> Self {
+        Self {
+            state,
+            fork_schedule,
+        }
+    }
+
+    pub fn state(&self) -> &'a BeaconState {
+        self.state
+    }
+
+    fn current_epoch(&self) -> u64 {
+        compute_epoch_at_slot(self.state.slot)
+    }
+
+    fn validator(&self, index: u64) -> Result<&'a Validator, InvalidOperation> {
+        self.state
+            .validators
+            .get(index as usize)
+            .ok_or(InvalidOperation::UnknownValidator(index))
+    }
+
+    fn verify_signature(
+        &self,
+        pubkey: &BLSPubkey,
+        signing_root: B256,
+        signature: &BLSSignature,
+    ) -> Result<(), InvalidOperation> {
+        match verify(pubkey, signing_root.as_slice(), signature) {
+            Ok(true) => Ok(()),
+            _ => Err(InvalidOperation::InvalidSignature),
+        }
+    }
+
+    pub fn validate_voluntary_exit(
+        &self,
+        exit: &SignedVoluntaryExit,
+    ) -> Result<(), InvalidOperation> {
+        let message = &exit.message;
+        let index = message.validator_index;
+        let validator = self.validator(index)?;
+        let current_epoch = self.current_epoch();
+        if !validator.is_active_validator(current_epoch) {
+            return Err(InvalidOperation::NotActive(index));
+        }
+        if validator.exit_epoch != FAR_FUTURE_EPOCH {
+            return Err(InvalidOperation::AlreadyExiting(index));
+        }
+        if current_epoch < message.epoch {
+            return Err(InvalidOperation::FutureExit {
+                epoch: message.epoch,
+                current_epoch,
+            });
+        }
+        if current_epoch < validator.activation_epoch + SHARD_COMMITTEE_PERIOD {
+            return Err(InvalidOperation::TooYoungToExit(index));
+        }
+
+        // Exits are signed with the Capella fork version from Deneb on (EIP-7044).
+        let capella_version = self
+            .fork_schedule
+            .fork_by_name(ForkName::Capella)
+            .unwrap_or_else(|| self.fork_schedule.genesis_fork())
+            .version;
+        let domain = compute_domain(
+            DOMAIN_VOLUNTARY_EXIT,
+            capella_version,
+            self.state.genesis_validators_root,
+        );
+        self.verify_signature(
+            &validator.pubkey,
+            compute_signing_root(message, domain),
+            &exit.signature,
+        )
+    }
+
+    fn verify_header(
+        &self,
+        pubkey: &BLSPubkey,
+        header: &SignedBeaconBlockHeader,
+    ) -> Result<(), InvalidOperation> {
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_BEACON_PROPOSER,
+            compute_epoch_at_slot(header.message.slot),
+            self.state.genesis_validators_root,
+        );
+        self.verify_signature(
+            pubkey,
+            compute_signing_root(&header.message, domain),
+            &header.signature,
+        )
+    }
+
+    pub fn validate_proposer_slashing(
+        &self,
+        slashing: &ProposerSlashing,
+    ) -> Result<(), InvalidOperation> {
+        let header_1 = &slashing.signed_header_1.message;
+        let header_2 = &slashing.signed_header_2.message;
+        if header_1.slot != header_2.slot
+            || header_1.proposer_index != header_2.proposer_index
+            || header_1 == header_2
+        {
+            return Err(InvalidOperation::NotSlashableHeaders);
+        }
+        let index = header_1.proposer_index;
+        let proposer = self.validator(index)?;
+        if !is_slashable_validator(proposer, self.current_epoch()) {
+            return Err(InvalidOperation::NotSlashable(index));
+        }
+        self.verify_header(&proposer.pubkey, &slashing.signed_header_1)?;
+        self.verify_header(&proposer.pubkey, &slashing.signed_header_2)
+    }
+
+    fn validate_indexed_attestation(
+        &self,
+        attestation: &IndexedAttestation,
+    ) -> Result<(), InvalidOperation> {
+        let indices = &attestation.attesting_indices;
+        if indices.is_empty() || indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(InvalidOperation::InvalidAttestingIndices);
+        }
+        let pubkeys = indices
+            .iter()
+            .map(|index| self.validator(*index).map(|validator| validator.pubkey))
+            .collect::<Result<Vec<_>, _>>()?;
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_BEACON_ATTESTER,
+            attestation.data.target.epoch,
+            self.state.genesis_validators_root,
+        );
+        let signing_root = compute_signing_root(&attestation.data, domain);
+        match fast_aggregate_verify(&pubkeys, signing_root.as_slice(), &attestation.signature) {
+            Ok(true) => Ok(()),
+            _ => Err(InvalidOperation::InvalidSignature),
+        }
+    }
+
+    pub fn validate_attester_slashing(
+        &self,
+        slashing: &AttesterSlashing,
+    ) -> Result<(), InvalidOperation> {
+        if !is_slashable_attestation_data(
+            &slashing.attestation_1.data,
+            &slashing.attestation_2.data,
+        ) {
+            return Err(InvalidOperation::NotSlashableAttestations);
+        }
+        self.validate_indexed_attestation(&slashing.attestation_1)?;
+        self.validate_indexed_attestation(&slashing.attestation_2)?;
+
+        let current_epoch = self.current_epoch();
+        let mut slashable = false;
+        for index in slashing.slashable_indices() {
+            slashable |= is_slashable_validator(self.validator(index)?, current_epoch);
+        }
+        if slashable {
+            Ok(())
+        } else {
+            Err(InvalidOperation::NothingToSlash)
+        }
+    }
+
+    pub fn validate_bls_to_execution_change(
+        &self,
+        change: &SignedBLSToExecutionChange,
+    ) -> Result<(), InvalidOperation> {
+        let message = &change.message;
+        let index = message.validator_index;
+        let credentials = self.validator(index)?.withdrawal_credentials;
+        if credentials[0] != BLS_WITHDRAWAL_PREFIX {
+            return Err(InvalidOperation::NotBlsCredentials(index));
+        }
+        if credentials[1..] != ethereum_hashing::hash(message.from_bls_pubkey.as_slice())[1..] {
+            return Err(InvalidOperation::WrongBlsPubkey(index));
+        }
+
+        // Signed with the genesis fork version so that changes stay valid across forks.
+        let domain = compute_domain(
+            DOMAIN_BLS_TO_EXECUTION_CHANGE,
+            self.fork_schedule.genesis_fork().version,
+            self.state.genesis_validators_root,
+        );
+        self.verify_signature(
+            &message.from_bls_pubkey,
+            compute_signing_root(message, domain),
+            &change.signature,
+        )
+    }
+
+    /// Checks the parts of an attestation that don't depend on its committee, see
+    /// [`Self::validate_attestation_signature`] for the rest.
+    pub fn validate_attestation(
+        &self,
+        attestation: &Attestation,
+        is_known_block: impl Fn(&B256) -> bool,
+    ) -> Result<(), InvalidOperation> {
+        let data = &attestation.data;
+        if data.target.epoch != compute_epoch_at_slot(data.slot) {
+            return Err(InvalidOperation::TargetEpochMismatch {
+                target_epoch: data.target.epoch,
+                slot: data.slot,
+            });
+        }
+        let current_epoch = self.current_epoch();
+        if data.target.epoch > current_epoch || data.target.epoch + 1 < current_epoch {
+            return Err(InvalidOperation::NotRecent(data.target.epoch));
+        }
+        if attestation.aggregation_bits.num_set_bits() == 0 {
+            return Err(InvalidOperation::NoAggregationBits);
+        }
+        for root in [data.beacon_block_root, data.target.root] {
+            if !is_known_block(&root) {
+                return Err(InvalidOperation::UnknownBlock(root));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the aggregation bits fit `committee`, the committee of the attestation, and
+    /// the aggregate signature of the members they select.
+    pub fn validate_attestation_signature(
+        &self,
+        attestation: &Attestation,
+        committee: &[u64],
+    ) -> Result<(), InvalidOperation> {
+        let bits = &attestation.aggregation_bits;
+        if bits.len() != committee.len() {
+            return Err(InvalidOperation::CommitteeSizeMismatch {
+                expected: committee.len(),
+                got: bits.len(),
+            });
+        }
+        let pubkeys = committee
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| bits.get(*position).unwrap_or(false))
+            .map(|(_, index)| self.validator(*index).map(|validator| validator.pubkey))
+            .collect::<Result<Vec<_>, _>>()?;
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_BEACON_ATTESTER,
+            attestation.data.target.epoch,
+            self.state.genesis_validators_root,
+        );
+        let signing_root = compute_signing_root(&attestation.data, domain);
+        match fast_aggregate_verify(&pubkeys, signing_root.as_slice(), &attestation.signature) {
+            Ok(true) => Ok(()),
+            _ => Err(InvalidOperation::InvalidSignature),
+        }
+    }
+
+    /// Checks that the aggregator is a member of `committee`, the committee of the aggregate,
+    /// and selected by its proof, and all three signatures.
+    pub fn validate_aggregate_and_proof(
+        &self,
+        signed: &SignedAggregateAndProof,
+        committee: &[u64],
+    ) -> Result<(), InvalidOperation> {
+        let message = &signed.message;
+        let index = message.aggregator_index;
+        if !committee.contains(&index) {
+            return Err(InvalidOperation::NotInCommittee(index));
+        }
+        if !is_aggregator(committee.len() as u64, &message.selection_proof) {
+            return Err(InvalidOperation::NotAggregator(index));
+        }
+
+        let pubkey = self.validator(index)?.pubkey;
+        let slot = message.aggregate.data.slot;
+        let epoch = compute_epoch_at_slot(slot);
+        let genesis_validators_root = self.state.genesis_validators_root;
+        let domain =
+            self.fork_schedule
+                .get_domain(DOMAIN_SELECTION_PROOF, epoch, genesis_validators_root);
+        self.verify_signature(
+            &pubkey,
+            compute_signing_root(&slot, domain),
+            &message.selection_proof,
+        )?;
+        let domain = self.fork_schedule.get_domain(
+            DOMAIN_AGGREGATE_AND_PROOF,
+            epoch,
+            genesis_validators_root,
+        );
+        self.verify_signature(
+            &pubkey,
+            compute_signing_root(message, domain),
+            &signed.signature,
+        )?;
+        self.validate_attestation_signature(&message.aggregate, committee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_bls::PrivateKey;
+    use ream_consensus::{
+        bls_to_execution_change::BLSToExecutionChange, voluntary_exit::VoluntaryExit,
+    };
+
+    use super::*;
+
+    fn bls_credentials(pubkey: &BLSPubkey) -> B256 {
+        let mut credentials = B256::from_slice(&ethereum_hashing::hash(pubkey.as_slice()));
+        credentials[0] = BLS_WITHDRAWAL_PREFIX;
+        credentials
+    }
+
+    #[test]
+    fn test_validate_exit_and_bls_change() {
+        let fork_schedule = ForkSchedule::mainnet();
+        let keys: Vec<_> = (1..=2)
+            .map(|seed| PrivateKey::key_gen(&[seed; 32]).unwrap())
+            .collect();
+        let mut state = BeaconState {
+            slot: 300 * 32,
+            genesis_validators_root: B256::repeat_byte(7),
+            ..BeaconState::default()
+        };
+        for (key, activation_epoch) in keys.iter().zip([0, 100]) {
+            state
+                .validators
+                .push(Validator {
+                    pubkey: key.public_key(),
+                    withdrawal_credentials: bls_credentials(&key.public_key()),
+                    activation_epoch,
+                    exit_epoch: FAR_FUTURE_EPOCH,
+                    withdrawable_epoch: FAR_FUTURE_EPOCH,
+                    ..Validator::default()
+                })
+                .unwrap();
+        }
+        let validator = OperationValidator::new(&state, &fork_schedule);
+
+        let exit = |validator_index, key: &PrivateKey| {
+            let message = VoluntaryExit {
+                epoch: 300,
+                validator_index,
+            };
+            let domain = compute_domain(
+                DOMAIN_VOLUNTARY_EXIT,
+                fork_schedule
+                    .fork_by_name(ForkName::Capella)
+                    .unwrap()
+                    .version,
+                state.genesis_validators_root,
+            );
+            SignedVoluntaryExit {
+                signature: key.sign(compute_signing_root(&message, domain).as_slice()),
+                message,
+            }
+        };
+        assert_eq!(
+            validator.validate_voluntary_exit(&exit(0, &keys[0])),
+            Ok(())
+        );
+        assert_eq!(
+            validator.validate_voluntary_exit(&exit(0, &keys[1])),
+            Err(InvalidOperation::InvalidSignature)
+        );
+        assert_eq!(
+            validator.validate_voluntary_exit(&exit(1, &keys[1])),
+            Err(InvalidOperation::TooYoungToExit(1))
+        );
+        assert_eq!(
+            validator.validate_voluntary_exit(&exit(2, &keys[1])),
+            Err(InvalidOperation::UnknownValidator(2))
+        );
+
+        let change = |from: &PrivateKey| {
+            let message = BLSToExecutionChange {
+                validator_index: 0,
+                from_bls_pubkey: from.public_key(),
+                to_execution_address: Default::default(),
+            };
+            let domain = compute_domain(
+                DOMAIN_BLS_TO_EXECUTION_CHANGE,
+                fork_schedule.genesis_fork().version,
+                state.genesis_validators_root,
+            );
+            SignedBLSToExecutionChange {
+                signature: from.sign(compute_signing_root(&message, domain).as_slice()),
+                message,
+            }
+        };
+        assert_eq!(
+            validator.validate_bls_to_execution_change(&change(&keys[0])),
+            Ok(())
+        );
+        assert_eq!(
+            validator.validate_bls_to_execution_change(&change(&keys[1])),
+            Err(InvalidOperation::WrongBlsPubkey(0))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use alloy_primitives::B256;
+use ream_consensus::{
+    blob_sidecar::BlobSidecar,
+    constants::{MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS, SLOTS_PER_EPOCH},
+};
+use tree_hash::TreeHash;
+
+use crate::{
+    error::StoreError,
+    store::{Store, TypedBatch},
+    tables::{BlobRootsBySlot, BlobSidecars},
+};
+
+/// Verified blob sidecars, kept for the data availability window peers can request them in.
+#[derive(Clone)]
+pub struct BlobStore {
+    store: Store,
+    prune: bool,
+}
+
+impl BlobStore {
+    /// With `prune` false blobs are kept forever.
+    pub fn new(store: Store, prune: bool) -> Self {
+        Self { store, prune }
+    }
+
+    pub fn put_blob_sidecar(&self, sidecar: &BlobSidecar) -> Result<(), StoreError> {
+        let header = &sidecar.signed_block_header.message;
+        let block_root = header.tree_hash_root();
+        let mut batch = TypedBatch::new();
+        batch.put::<BlobSidecars>(&(block_root, sidecar.index), sidecar);
+        batch.put::<BlobRootsBySlot>(&(header.slot, block_root), &block_root);
+        self.store.write(batch)
+    }
+
+    pub fn get_blob_sidecar(
+        &self,
+        block_root: B256,
+        index: u64,
+    ) -> Result<Option<BlobSidecar>, StoreError> {
+        self.store.get::<BlobSidecars>(&(block_root, index))
+    }
+
+    /// Sidecars of the block with root `block_root`, ordered by index.
+    pub fn get_blob_sidecars(&self, block_root: B256) -> Result<Vec<BlobSidecar>, StoreError> {
+        let mut sidecars = vec![];
+        self.store
+            .iterate::<BlobSidecars>(&(block_root, 0), |(root, _), sidecar| {
+                if root != block_root {
+                    return false;
+                }
+                sidecars.push(sidecar);
+                true
+            })?;
+        Ok(sidecars)
+    }
+
+    /// First slot whose blobs must still be served at `current_epoch`.
+    pub fn data_availability_boundary(current_epoch: u64) -> u64 {
+        current_epoch.saturating_sub(MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS) * SLOTS_PER_EPOCH
+    }
+
+    /// Deletes blobs of blocks before the data availability window and returns how many block
+    /// roots had blobs removed.
+    pub fn prune(&self, current_epoch: u64) -> Result<usize, StoreError> {
+        if !self.prune {
+            return Ok(0);
+        }
+
+        let boundary = Self::data_availability_boundary(current_epoch);
+        let mut expired = vec![];
+        self.store
+            .iterate::<BlobRootsBySlot>(&(0, B256::ZERO), |(slot, root), _| {
+                if slot < boundary {
+                    expired.push((slot, root));
+                }
+                slot < boundary
+            })?;
+
+        let mut batch = TypedBatch::new();
+        for &(slot, root) in &expired {
+            batch.delete::<BlobRootsBySlot>(&(slot, root));
+            self.store
+                .iterate::<BlobSidecars>(&(root, 0), |(sidecar_root, index), _| {
+                    if sidecar_root != root {
+                        return false;
+                    }
+                    batch.delete::<BlobSidecars>(&(root, index));
+                    true
+                })?;
+        }
+        if !batch.is_empty() {
+            self.store.write(batch)?;
+        }
+        Ok(expired.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::beacon_block_header::{BeaconBlockHeader, SignedBeaconBlockHeader};
+
+    use super::*;
+    use crate::memory_store::MemoryStore;
+
+    fn sidecar(slot: u64, index: u64) -> BlobSidecar {
+        BlobSidecar {
+            index,
+            signed_block_header: SignedBeaconBlockHeader {
+                message: BeaconBlockHeader {
+                    slot,
+                    ..BeaconBlockHeader::default()
+                },
+                ..SignedBeaconBlockHeader::default()
+            },
+            ..BlobSidecar::default()
+        }
+    }
+
+    #[test]
+    fn test_blob_store() {
+        let store = Store::new(Arc::new(MemoryStore::new()));
+        let blobs = BlobStore::new(store.clone(), true);
+        let window_start = MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS * SLOTS_PER_EPOCH;
+
+        for sidecar in [sidecar(1, 1), sidecar(1, 0), sidecar(window_start, 0)] {
+            blobs.put_blob_sidecar(&sidecar).unwrap();
+        }
+        let old_root = sidecar(1, 0).signed_block_header.message.tree_hash_root();
+        let new_root = sidecar(window_start, 0)
+            .signed_block_header
+            .message
+            .tree_hash_root();
+
+        let indices = blobs
+            .get_blob_sidecars(old_root)
+            .unwrap()
+            .iter()
+            .map(|sidecar| sidecar.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![0, 1]);
+
+        // Nothing is pruned when blobs are archived.
+        assert_eq!(BlobStore::new(store, false).prune(u64::MAX).unwrap(), 0);
+
+        assert_eq!(
+            blobs.prune(MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS).unwrap(),
+            0
+        );
+        assert_eq!(
+            blobs
+                .prune(MIN_EPOCHS_FOR_BLOB_SIDECARS_REQUESTS + 1)
+                .unwrap(),
+            1
+        );
+        assert!(blobs.get_blob_sidecars(old_root).unwrap().is_empty());
+        assert!(blobs.get_blob_sidecar(new_root, 0).unwrap().is_some());
+    }
+}
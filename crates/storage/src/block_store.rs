@@ -0,0 +1,201 @@
+use alloy_primitives::B256;
+use ream_consensus::beacon_block::SignedBeaconBlock;
+use tree_hash::TreeHash;
+
+use crate::{
+    error::StoreError,
+    store::{Store, TypedBatch},
+    tables::{BlockRootsBySlot, BlockSummaries, BlockSummary, BlocksByRoot},
+};
+
+/// Blocks with a parent index for walking ancestors and a slot index of the canonical chain.
+///
+/// Blocks of every fork are stored, only [`BlockStore::set_canonical_head`] changes which of
+/// them the slot index points to.
+#[derive(Clone)]
+pub struct BlockStore {
+    store: Store,
+}
+
+impl BlockStore {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+
+    /// Stores `block` and returns its root.
+    pub fn put_block(&self, block: &SignedBeaconBlock) -> Result<B256, StoreError> {
+        let mut batch = TypedBatch::new();
+        let root = self.stage_block(&mut batch, block);
+        self.store.write(batch)?;
+        Ok(root)
+    }
+
+    /// Adds the writes of [`Self::put_block`] to `batch` and returns the root of `block`.
+    pub fn stage_block(&self, batch: &mut TypedBatch, block: &SignedBeaconBlock) -> B256 {
+        let root = block.message.tree_hash_root();
+        batch.put::<BlocksByRoot>(&root, block);
+        batch.put::<BlockSummaries>(
+            &root,
+            &BlockSummary {
+                slot: block.message.slot,
+                parent_root: block.message.parent_root,
+            },
+        );
+        root
+    }
+
+    pub fn get_block(&self, root: &B256) -> Result<Option<SignedBeaconBlock>, StoreError> {
+        self.store.get::<BlocksByRoot>(root)
+    }
+
+    pub fn get_block_summary(&self, root: &B256) -> Result<Option<BlockSummary>, StoreError> {
+        self.store.get::<BlockSummaries>(root)
+    }
+
+    pub fn contains_block(&self, root: &B256) -> Result<bool, StoreError> {
+        Ok(self.get_block_summary(root)?.is_some())
+    }
+
+    /// Root of the canonical block at `slot`, `None` if the slot is empty or past the head.
+    pub fn get_block_root_at_slot(&self, slot: u64) -> Result<Option<B256>, StoreError> {
+        self.store.get::<BlockRootsBySlot>(&slot)
+    }
+
+    /// Iterates `(root, slot)` of `root` and its ancestors, newest first, until a block that
+    /// isn't stored is reached.
+    pub fn ancestors(&self, root: B256) -> Ancestors<'_> {
+        Ancestors {
+            store: &self.store,
+            next: Some(root),
+        }
+    }
+
+    /// Points the slot index at the chain ending in `head_root`.
+    ///
+    /// Only the part of the index after the latest ancestor it already contains is rewritten,
+    /// so following the head block by block is cheap.
+    pub fn set_canonical_head(&self, head_root: B256) -> Result<(), StoreError> {
+        let mut batch = TypedBatch::new();
+        self.stage_canonical_head(&mut batch, head_root)?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.store.write(batch)
+    }
+
+    /// Adds the writes of [`Self::set_canonical_head`] to `batch`. The head and its ancestors
+    /// must already be stored.
+    pub fn stage_canonical_head(
+        &self,
+        batch: &mut TypedBatch,
+        head_root: B256,
+    ) -> Result<(), StoreError> {
+        if !self.contains_block(&head_root)? {
+            return Err(StoreError::UnknownBlock(head_root));
+        }
+
+        let mut chain = vec![];
+        let mut first_changed_slot = 0;
+        for ancestor in self.ancestors(head_root) {
+            let (root, slot) = ancestor?;
+            if self.get_block_root_at_slot(slot)? == Some(root) {
+                first_changed_slot = slot + 1;
+                break;
+            }
+            chain.push((slot, root));
+        }
+
+        // Entries of the previous chain after the fork point are removed first, the batch is
+        // applied in order.
+        self.store
+            .iterate::<BlockRootsBySlot>(&first_changed_slot, |slot, _| {
+                batch.delete::<BlockRootsBySlot>(&slot);
+                true
+            })?;
+        for (slot, root) in chain {
+            batch.put::<BlockRootsBySlot>(&slot, &root);
+        }
+        Ok(())
+    }
+}
+
+/// Iterator returned by [`BlockStore::ancestors`].
+pub struct Ancestors<'a> {
+    store: &'a Store,
+    next: Option<B256>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = Result<(B256, u64), StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let root = self.next.take()?;
+        match self.store.get::<BlockSummaries>(&root) {
+            Ok(Some(summary)) => {
+                self.next = Some(summary.parent_root);
+                Some(Ok((root, summary.slot)))
+            }
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::beacon_block::BeaconBlock;
+
+    use super::*;
+    use crate::memory_store::MemoryStore;
+
+    fn put_block(store: &BlockStore, slot: u64, parent_root: B256) -> B256 {
+        store
+            .put_block(&SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot,
+                    parent_root,
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap()
+    }
+
+    fn canonical_slots(store: &BlockStore) -> Vec<Option<B256>> {
+        (0..5)
+            .map(|slot| store.get_block_root_at_slot(slot).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_ancestors_and_reorg() {
+        let store = BlockStore::new(Store::new(Arc::new(MemoryStore::new())));
+        let genesis = put_block(&store, 0, B256::ZERO);
+        let a = put_block(&store, 1, genesis);
+        let b = put_block(&store, 2, a);
+        let c = put_block(&store, 4, a);
+
+        assert_eq!(store.get_block(&b).unwrap().unwrap().message.parent_root, a);
+        let ancestors = store.ancestors(b).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(ancestors, vec![(b, 2), (a, 1), (genesis, 0)]);
+
+        store.set_canonical_head(b).unwrap();
+        assert_eq!(
+            canonical_slots(&store),
+            vec![Some(genesis), Some(a), Some(b), None, None]
+        );
+
+        store.set_canonical_head(c).unwrap();
+        assert_eq!(
+            canonical_slots(&store),
+            vec![Some(genesis), Some(a), None, None, Some(c)]
+        );
+
+        assert!(matches!(
+            store.set_canonical_head(B256::repeat_byte(9)),
+            Err(StoreError::UnknownBlock(_))
+        ));
+    }
+}
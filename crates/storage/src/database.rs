@@ -0,0 +1,133 @@
+use crate::error::StoreError;
+
+/// Key-value namespaces of the database, one per table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Column {
+    BlocksByRoot,
+    BlockSummaries,
+    BlockRootsBySlot,
+    StatesByRoot,
+    StateSummaries,
+    BlobSidecars,
+    BlobRootsBySlot,
+    ForkChoice,
+    OperationPool,
+    Split,
+}
+
+impl Column {
+    pub const ALL: [Column; 10] = [
+        Column::BlocksByRoot,
+        Column::BlockSummaries,
+        Column::BlockRootsBySlot,
+        Column::StatesByRoot,
+        Column::StateSummaries,
+        Column::BlobSidecars,
+        Column::BlobRootsBySlot,
+        Column::ForkChoice,
+        Column::OperationPool,
+        Column::Split,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::BlocksByRoot => "blocks_by_root",
+            Column::BlockSummaries => "block_summaries",
+            Column::BlockRootsBySlot => "block_roots_by_slot",
+            Column::StatesByRoot => "states_by_root",
+            Column::StateSummaries => "state_summaries",
+            Column::BlobSidecars => "blob_sidecars",
+            Column::BlobRootsBySlot => "blob_roots_by_slot",
+            Column::ForkChoice => "fork_choice",
+            Column::OperationPool => "operation_pool",
+            Column::Split => "split",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    Put {
+        column: Column,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Delete {
+        column: Column,
+        key: Vec<u8>,
+    },
+}
+
+/// Writes applied together by [`KeyValueStore::write`], either all of them or none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteBatch {
+    operations: Vec<BatchOperation>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(&mut self, column: Column, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.operations.push(BatchOperation::Put {
+            column,
+            key: key.into(),
+            value: value.into(),
+        });
+    }
+
+    pub fn delete(&mut self, column: Column, key: impl Into<Vec<u8>>) {
+        self.operations.push(BatchOperation::Delete {
+            column,
+            key: key.into(),
+        });
+    }
+
+    pub fn operations(&self) -> &[BatchOperation] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+/// Raw byte storage behind the typed [`Store`](crate::store::Store). Keys are ordered
+/// bytewise within a column.
+pub trait KeyValueStore: Send + Sync {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError>;
+
+    /// Atomically applies every operation of `batch`.
+    fn write(&self, batch: WriteBatch) -> Result<(), StoreError>;
+
+    /// Calls `f` with every entry of `column` whose key is at least `from`, in key order, until
+    /// it returns `false`.
+    fn iterate(
+        &self,
+        column: Column,
+        from: &[u8],
+        f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> Result<(), StoreError>;
+
+    /// Reclaims space freed by deletes. Does nothing for stores that don't need it.
+    fn compact(&self) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn put(&self, column: Column, key: &[u8], value: &[u8]) -> Result<(), StoreError> {
+        let mut batch = WriteBatch::new();
+        batch.put(column, key, value);
+        self.write(batch)
+    }
+
+    fn delete(&self, column: Column, key: &[u8]) -> Result<(), StoreError> {
+        let mut batch = WriteBatch::new();
+        batch.delete(column, key);
+        self.write(batch)
+    }
+}
@@ -0,0 +1,75 @@
+//! The e2store container format era files are built on: a sequence of entries, each an 8 byte
+//! header (type, little endian length, two zero bytes) followed by the data.
+
+use std::io::{self, Read, Write};
+
+use super::EraError;
+
+pub const HEADER_LEN: usize = 8;
+
+pub const VERSION: [u8; 2] = [0x65, 0x32];
+pub const COMPRESSED_SIGNED_BEACON_BLOCK: [u8; 2] = [0x01, 0x00];
+pub const COMPRESSED_BEACON_STATE: [u8; 2] = [0x02, 0x00];
+pub const SLOT_INDEX: [u8; 2] = [0x69, 0x32];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub entry_type: [u8; 2],
+    pub data: Vec<u8>,
+}
+
+/// Writes an entry and returns its total length.
+pub fn write_entry(
+    writer: &mut impl Write,
+    entry_type: [u8; 2],
+    data: &[u8],
+) -> Result<u64, EraError> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| EraError::Format(format!("entry of {} bytes is too large", data.len())))?;
+    writer.write_all(&entry_type)?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(&[0, 0])?;
+    writer.write_all(data)?;
+    Ok((HEADER_LEN + data.len()) as u64)
+}
+
+/// Splits `bytes` into entries.
+pub fn read_entries(mut bytes: &[u8]) -> Result<Vec<Entry>, EraError> {
+    let mut entries = vec![];
+    while !bytes.is_empty() {
+        if bytes.len() < HEADER_LEN {
+            return Err(EraError::Format("truncated entry header".to_string()));
+        }
+        let (header, rest) = bytes.split_at(HEADER_LEN);
+        let length = u32::from_le_bytes(header[2..6].try_into().expect("4 bytes")) as usize;
+        if header[6..] != [0, 0] {
+            return Err(EraError::Format(
+                "reserved header bytes are not zero".to_string(),
+            ));
+        }
+        if rest.len() < length {
+            return Err(EraError::Format("truncated entry data".to_string()));
+        }
+        let (data, rest) = rest.split_at(length);
+        entries.push(Entry {
+            entry_type: [header[0], header[1]],
+            data: data.to_vec(),
+        });
+        bytes = rest;
+    }
+    Ok(entries)
+}
+
+pub fn compress(data: &[u8]) -> Result<Vec<u8>, EraError> {
+    let mut encoder = snap::write::FrameEncoder::new(vec![]);
+    encoder.write_all(data)?;
+    encoder
+        .into_inner()
+        .map_err(|err| EraError::Io(io::Error::other(err.to_string())))
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, EraError> {
+    let mut decompressed = vec![];
+    snap::read::FrameDecoder::new(data).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
@@ -0,0 +1,420 @@
+//! Era files: the blocks of one `SLOTS_PER_HISTORICAL_ROOT` period and the state right after it,
+//! snappy compressed in an e2store container. They let finalized history be distributed and
+//! imported without fetching it from peers.
+//!
+//! Layout: `Version | block* | era-state | slot-index(block)? | slot-index(state)`.
+
+pub mod e2store;
+
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock, beacon_state::BeaconState,
+    constants::SLOTS_PER_HISTORICAL_ROOT,
+};
+use ssz::{Decode, Encode};
+use tree_hash::TreeHash;
+
+use crate::{
+    error::StoreError,
+    hot_cold::HotColdStore,
+    state_store::StateReplayer,
+    store::TypedBatch,
+    tables::{
+        BlockRootsBySlot, BlockSummaries, BlockSummary, BlocksByRoot, StateSummaries, StateSummary,
+        StatesByRoot,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EraError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid era file: {0}")]
+    Format(String),
+    #[error("invalid SSZ in era file: {0}")]
+    Ssz(String),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error("block at slot {slot} has root {computed}, the era state expects {expected}")]
+    BlockRootMismatch {
+        slot: u64,
+        expected: B256,
+        computed: B256,
+    },
+    #[error("era {0} isn't finalized yet")]
+    NotFinalized(u64),
+    #[error("no block at or before slot {0} to build the era state from")]
+    MissingHistory(u64),
+}
+
+/// Contents of one era file: the blocks of era `number - 1` and the state at the first slot
+/// of era `number`, before that slot's block. Era 0 only holds the genesis state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Era {
+    pub number: u64,
+    pub blocks: Vec<SignedBeaconBlock>,
+    pub state: BeaconState,
+}
+
+impl Era {
+    /// First slot blocks of this era can be at.
+    pub fn start_slot(&self) -> u64 {
+        self.number.saturating_sub(1) * SLOTS_PER_HISTORICAL_ROOT
+    }
+
+    /// `<config name>-<era number>-<short historical root>.era`.
+    pub fn file_name(&self, config_name: &str) -> String {
+        let root = if self.number == 0 {
+            self.state.genesis_validators_root
+        } else {
+            let index = self.number as usize - 1;
+            let historical_roots = &self.state.historical_roots;
+            match historical_roots.get(index) {
+                Some(root) => *root,
+                None => self
+                    .state
+                    .historical_summaries
+                    .get(index - historical_roots.len())
+                    .map(|summary| summary.tree_hash_root())
+                    .unwrap_or_default(),
+            }
+        };
+        format!(
+            "{config_name}-{:05}-{}.era",
+            self.number,
+            alloy_primitives::hex::encode(&root[..4])
+        )
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), EraError> {
+        let mut position = e2store::write_entry(writer, e2store::VERSION, &[])?;
+
+        let mut block_offsets = vec![0i64; SLOTS_PER_HISTORICAL_ROOT as usize];
+        for block in &self.blocks {
+            let index = block
+                .message
+                .slot
+                .checked_sub(self.start_slot())
+                .filter(|index| *index < SLOTS_PER_HISTORICAL_ROOT)
+                .ok_or_else(|| {
+                    EraError::Format(format!(
+                        "block at slot {} is outside era {}",
+                        block.message.slot, self.number
+                    ))
+                })?;
+            block_offsets[index as usize] = position as i64;
+            position += e2store::write_entry(
+                writer,
+                e2store::COMPRESSED_SIGNED_BEACON_BLOCK,
+                &e2store::compress(&block.as_ssz_bytes())?,
+            )?;
+        }
+
+        let state_position = position;
+        position += e2store::write_entry(
+            writer,
+            e2store::COMPRESSED_BEACON_STATE,
+            &e2store::compress(&self.state.as_ssz_bytes())?,
+        )?;
+
+        // Offsets are relative to the start of the index entry, zero for empty slots.
+        if self.number > 0 {
+            let index_position = position as i64;
+            let offsets = block_offsets
+                .iter()
+                .map(|offset| match offset {
+                    0 => 0,
+                    offset => offset - index_position,
+                })
+                .collect::<Vec<_>>();
+            position += e2store::write_entry(
+                writer,
+                e2store::SLOT_INDEX,
+                &slot_index(self.start_slot(), &offsets),
+            )?;
+        }
+        e2store::write_entry(
+            writer,
+            e2store::SLOT_INDEX,
+            &slot_index(self.state.slot, &[state_position as i64 - position as i64]),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn read(bytes: &[u8]) -> Result<Self, EraError> {
+        let entries = e2store::read_entries(bytes)?;
+        if entries.first().map(|entry| entry.entry_type) != Some(e2store::VERSION) {
+            return Err(EraError::Format("missing version entry".to_string()));
+        }
+
+        let mut blocks = vec![];
+        let mut state = None;
+        for entry in &entries[1..] {
+            match entry.entry_type {
+                e2store::COMPRESSED_SIGNED_BEACON_BLOCK => blocks.push(
+                    SignedBeaconBlock::from_ssz_bytes(&e2store::decompress(&entry.data)?)
+                        .map_err(|err| EraError::Ssz(format!("{err:?}")))?,
+                ),
+                e2store::COMPRESSED_BEACON_STATE if state.is_none() => {
+                    state = Some(
+                        BeaconState::from_ssz_bytes(&e2store::decompress(&entry.data)?)
+                            .map_err(|err| EraError::Ssz(format!("{err:?}")))?,
+                    )
+                }
+                e2store::COMPRESSED_BEACON_STATE => {
+                    return Err(EraError::Format("more than one state".to_string()))
+                }
+                // Slot indices only speed up random access, other entry types may be skipped.
+                _ => {}
+            }
+        }
+
+        let state = state.ok_or_else(|| EraError::Format("missing state".to_string()))?;
+        if state.slot % SLOTS_PER_HISTORICAL_ROOT != 0 {
+            return Err(EraError::Format(format!(
+                "state slot {} is not at an era boundary",
+                state.slot
+            )));
+        }
+        Ok(Self {
+            number: state.slot / SLOTS_PER_HISTORICAL_ROOT,
+            blocks,
+            state,
+        })
+    }
+
+    /// Checks every block against the block roots of the era state and returns the roots.
+    pub fn verify(&self) -> Result<Vec<B256>, EraError> {
+        let mut roots = Vec::with_capacity(self.blocks.len());
+        let mut previous_slot = None;
+        for block in &self.blocks {
+            let slot = block.message.slot;
+            if slot < self.start_slot()
+                || slot >= self.state.slot
+                || previous_slot.is_some_and(|previous| slot <= previous)
+            {
+                return Err(EraError::Format(format!(
+                    "block at slot {slot} is out of order or outside era {}",
+                    self.number
+                )));
+            }
+            previous_slot = Some(slot);
+
+            let computed = block.message.tree_hash_root();
+            let expected = self.state.block_roots[(slot % SLOTS_PER_HISTORICAL_ROOT) as usize];
+            if computed != expected {
+                return Err(EraError::BlockRootMismatch {
+                    slot,
+                    expected,
+                    computed,
+                });
+            }
+            roots.push(computed);
+        }
+        Ok(roots)
+    }
+}
+
+fn slot_index(start_slot: u64, offsets: &[i64]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(16 + offsets.len() * 8);
+    data.extend((start_slot as i64).to_le_bytes());
+    for offset in offsets {
+        data.extend(offset.to_le_bytes());
+    }
+    data.extend((offsets.len() as i64).to_le_bytes());
+    data
+}
+
+/// Collects era `number` from the finalized chain. The era state is rebuilt from the post-state
+/// of the last block before it.
+pub fn export_era<R: StateReplayer>(
+    store: &HotColdStore,
+    number: u64,
+    replayer: &R,
+) -> Result<Era, EraError> {
+    let boundary = number * SLOTS_PER_HISTORICAL_ROOT;
+    if boundary > store.split().slot {
+        return Err(EraError::NotFinalized(number));
+    }
+
+    let start_slot = number.saturating_sub(1) * SLOTS_PER_HISTORICAL_ROOT;
+    let mut blocks = vec![];
+    for slot in start_slot..boundary {
+        if let Some(root) = store.get_block_root_at_slot(slot)? {
+            blocks.push(
+                store
+                    .get_block(&root)?
+                    .ok_or(StoreError::UnknownBlock(root))?,
+            );
+        }
+    }
+
+    // Genesis has no block before it, its state is the post-state of the genesis block.
+    let latest_block = match blocks.last() {
+        Some(block) => block.clone(),
+        None => {
+            let mut slot = boundary.saturating_sub(1);
+            loop {
+                if let Some(root) = store.get_block_root_at_slot(slot)? {
+                    break store
+                        .get_block(&root)?
+                        .ok_or(StoreError::UnknownBlock(root))?;
+                }
+                if slot == 0 {
+                    return Err(EraError::MissingHistory(boundary));
+                }
+                slot -= 1;
+            }
+        }
+    };
+    let mut state = store
+        .get_state(&latest_block.message.state_root, replayer)?
+        .map(Arc::unwrap_or_clone)
+        .ok_or(StoreError::MissingSnapshot(latest_block.message.state_root))?;
+    if state.slot < boundary {
+        replayer
+            .process_slots(&mut state, boundary)
+            .map_err(|err| StoreError::Replay(err.to_string()))?;
+    }
+
+    Ok(Era {
+        number,
+        blocks,
+        state,
+    })
+}
+
+/// Verifies `era` and writes its blocks and state to the cold database, as finalized history
+/// to serve and sync from.
+pub fn import_era(store: &HotColdStore, era: &Era) -> Result<(), EraError> {
+    let roots = era.verify()?;
+
+    let mut batch = TypedBatch::new();
+    for (block, root) in era.blocks.iter().zip(roots) {
+        batch.put::<BlocksByRoot>(&root, block);
+        batch.put::<BlockSummaries>(
+            &root,
+            &BlockSummary {
+                slot: block.message.slot,
+                parent_root: block.message.parent_root,
+            },
+        );
+        batch.put::<BlockRootsBySlot>(&block.message.slot, &root);
+    }
+
+    let state_root = era.state.tree_hash_root();
+    batch.put::<StateSummaries>(
+        &state_root,
+        &StateSummary {
+            slot: era.state.slot,
+            latest_block_root: era.state.latest_block_root(state_root),
+            snapshot: true,
+        },
+    );
+    batch.put::<StatesByRoot>(&state_root, &era.state);
+
+    store.cold().write(batch)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::beacon_block::BeaconBlock;
+
+    use super::*;
+    use crate::{hot_cold::StoreConfig, memory_store::MemoryStore, store::Store};
+
+    fn era() -> Era {
+        let mut state = BeaconState {
+            slot: SLOTS_PER_HISTORICAL_ROOT,
+            ..BeaconState::default()
+        };
+        let mut parent_root = B256::ZERO;
+        let mut blocks = vec![];
+        for slot in [3, 10] {
+            let block = SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot,
+                    parent_root,
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            };
+            parent_root = block.message.tree_hash_root();
+            state.block_roots[slot as usize] = parent_root;
+            blocks.push(block);
+        }
+        Era {
+            number: 1,
+            blocks,
+            state,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let era = era();
+        let mut bytes = vec![];
+        era.write(&mut bytes).unwrap();
+
+        let entries = e2store::read_entries(&bytes).unwrap();
+        let types = entries
+            .iter()
+            .map(|entry| entry.entry_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                e2store::VERSION,
+                e2store::COMPRESSED_SIGNED_BEACON_BLOCK,
+                e2store::COMPRESSED_SIGNED_BEACON_BLOCK,
+                e2store::COMPRESSED_BEACON_STATE,
+                e2store::SLOT_INDEX,
+                e2store::SLOT_INDEX,
+            ]
+        );
+        // The state index points back at the state entry.
+        let state_index = &entries[5].data;
+        let index_position = bytes.len() - e2store::HEADER_LEN - state_index.len();
+        let state_position = entries[..3]
+            .iter()
+            .map(|entry| e2store::HEADER_LEN + entry.data.len())
+            .sum::<usize>();
+        let offset = i64::from_le_bytes(state_index[8..16].try_into().unwrap());
+        assert_eq!(index_position as i64 + offset, state_position as i64);
+
+        assert_eq!(Era::read(&bytes).unwrap(), era);
+        assert!(era.file_name("mainnet").starts_with("mainnet-00001-"));
+    }
+
+    #[test]
+    fn test_verify_and_import() {
+        let mut era = era();
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+
+        import_era(&store, &era).unwrap();
+        let root = era.blocks[1].message.tree_hash_root();
+        assert_eq!(
+            store.cold().get::<BlockRootsBySlot>(&10).unwrap(),
+            Some(root)
+        );
+        assert!(store.get_block(&root).unwrap().is_some());
+
+        era.blocks[0].message.proposer_index = 1;
+        assert!(matches!(
+            era.verify(),
+            Err(EraError::BlockRootMismatch { slot: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("block {0} is not in the database")]
+    UnknownBlock(alloy_primitives::B256),
+    #[error("no snapshot to rebuild state {0} from")]
+    MissingSnapshot(alloy_primitives::B256),
+    #[error("replaying blocks to rebuild a state failed: {0}")]
+    Replay(String),
+    #[error("database error: {0}")]
+    Database(Box<redb::Error>),
+    #[error("failed to decode {kind} in {column}: {message}")]
+    Decode {
+        column: &'static str,
+        kind: &'static str,
+        message: String,
+    },
+}
+
+macro_rules! impl_from_redb_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for StoreError {
+                fn from(err: $error) -> Self {
+                    StoreError::Database(Box::new(err.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_redb_error!(
+    redb::Error,
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError,
+    redb::CompactionError
+);
@@ -0,0 +1,673 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+
+use alloy_primitives::B256;
+use ream_consensus::{beacon_block::SignedBeaconBlock, beacon_state::BeaconState};
+use ream_fork_choice::persisted_fork_choice::PersistedForkChoice;
+use ream_operation_pool::persisted_operation_pool::PersistedOperationPool;
+
+use crate::{
+    block_store::BlockStore,
+    error::StoreError,
+    pruning::PruningMode,
+    state_cache::{StateCache, DEFAULT_STATE_CACHE_BYTES},
+    state_store::{StateReplayer, StateStore, DEFAULT_SNAPSHOT_INTERVAL_EPOCHS},
+    store::{Store, TypedBatch},
+    tables::{
+        BlockRootsBySlot, BlockSummaries, BlockSummary, BlocksByRoot, ForkChoiceSnapshot,
+        OperationPoolSnapshot, Split, SplitPoint, StateSummaries, StateSummary, StatesByRoot,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreConfig {
+    pub snapshot_interval_epochs: u64,
+    pub pruning: PruningMode,
+    /// Memory budget of the state cache.
+    pub state_cache_bytes: usize,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_interval_epochs: DEFAULT_SNAPSHOT_INTERVAL_EPOCHS,
+            pruning: PruningMode::Archive,
+            state_cache_bytes: DEFAULT_STATE_CACHE_BYTES,
+        }
+    }
+}
+
+/// Everything importing a block persists, see [`HotColdStore::import_block`].
+pub struct BlockImport<'a> {
+    pub block: &'a SignedBeaconBlock,
+    /// State after applying `block`, its root is the block's state root.
+    pub post_state: &'a BeaconState,
+    /// Fork choice after the block was added, when it is due to be persisted.
+    pub fork_choice: Option<&'a PersistedForkChoice>,
+    /// Whether the block became the head.
+    pub is_head: bool,
+}
+
+/// Blocks and states split between a hot database holding everything after the latest
+/// finalized block and a cold database holding the finalized chain.
+///
+/// Keeping abandoned forks and old states out of the hot database bounds its size, which keeps
+/// the writes done on every block cheap.
+pub struct HotColdStore {
+    hot: Store,
+    cold: Store,
+    hot_blocks: BlockStore,
+    cold_blocks: BlockStore,
+    hot_states: StateStore,
+    cold_states: StateStore,
+    pruning: PruningMode,
+    state_cache: StateCache,
+    split: RwLock<Split>,
+}
+
+impl HotColdStore {
+    pub fn open(hot: Store, cold: Store, config: StoreConfig) -> Result<Self, StoreError> {
+        let split = hot.get::<SplitPoint>(&())?.unwrap_or_default();
+        Ok(Self {
+            hot_blocks: BlockStore::new(hot.clone()),
+            cold_blocks: BlockStore::new(cold.clone()),
+            hot_states: StateStore::new(hot.clone(), config.snapshot_interval_epochs),
+            cold_states: StateStore::new(cold.clone(), config.snapshot_interval_epochs),
+            hot,
+            cold,
+            pruning: config.pruning,
+            state_cache: StateCache::new(config.state_cache_bytes),
+            split: RwLock::new(split),
+        })
+    }
+
+    pub fn split(&self) -> Split {
+        *self.split.read().expect("split lock poisoned")
+    }
+
+    pub fn hot(&self) -> &Store {
+        &self.hot
+    }
+
+    pub fn cold(&self) -> &Store {
+        &self.cold
+    }
+
+    /// Slot of the oldest block in either database.
+    pub fn anchor_slot(&self) -> Result<Option<u64>, StoreError> {
+        let mut anchor = None;
+        for store in [&self.cold, &self.hot] {
+            store.iterate::<BlockRootsBySlot>(&0, |slot, _| {
+                anchor = Some(slot);
+                false
+            })?;
+            if anchor.is_some() {
+                break;
+            }
+        }
+        Ok(anchor)
+    }
+
+    pub fn compact(&self) -> Result<(), StoreError> {
+        self.hot.compact()?;
+        self.cold.compact()
+    }
+
+    /// New blocks and states always go to the hot database.
+    pub fn hot_blocks(&self) -> &BlockStore {
+        &self.hot_blocks
+    }
+
+    pub fn hot_states(&self) -> &StateStore {
+        &self.hot_states
+    }
+
+    /// Writes the block, its post-state, fork choice and the slot index in a single batch, so
+    /// that a crash during import can't leave the database referring to data never written.
+    /// Returns the block root.
+    pub fn import_block(&self, import: BlockImport<'_>) -> Result<B256, StoreError> {
+        let block = &import.block.message;
+        let mut batch = TypedBatch::new();
+        let root = self.hot_blocks.stage_block(&mut batch, import.block);
+        self.hot_states
+            .stage_state(&mut batch, block.state_root, import.post_state)?;
+        if let Some(fork_choice) = import.fork_choice {
+            batch.put::<ForkChoiceSnapshot>(&(), fork_choice);
+        }
+        if import.is_head {
+            // The block isn't stored yet, so the index is moved to its parent and then extended.
+            if self.hot_blocks.contains_block(&block.parent_root)? {
+                self.hot_blocks
+                    .stage_canonical_head(&mut batch, block.parent_root)?;
+            }
+            batch.put::<BlockRootsBySlot>(&block.slot, &root);
+        }
+        self.hot.write(batch)?;
+        Ok(root)
+    }
+
+    /// Writes the operation pool and, when there's one, fork choice in a single batch, for them
+    /// to be restored by the next start.
+    pub fn persist_on_shutdown(
+        &self,
+        operation_pool: &PersistedOperationPool,
+        fork_choice: Option<&PersistedForkChoice>,
+    ) -> Result<(), StoreError> {
+        let mut batch = TypedBatch::new();
+        batch.put::<OperationPoolSnapshot>(&(), operation_pool);
+        if let Some(fork_choice) = fork_choice {
+            batch.put::<ForkChoiceSnapshot>(&(), fork_choice);
+        }
+        self.hot.write(batch)
+    }
+
+    /// Operations persisted by the last shutdown.
+    pub fn get_operation_pool(&self) -> Result<Option<PersistedOperationPool>, StoreError> {
+        self.hot.get::<OperationPoolSnapshot>(&())
+    }
+
+    /// Fork choice persisted by the last shutdown or block import.
+    pub fn get_fork_choice(&self) -> Result<Option<PersistedForkChoice>, StoreError> {
+        self.hot.get::<ForkChoiceSnapshot>(&())
+    }
+
+    pub fn get_block(&self, root: &B256) -> Result<Option<SignedBeaconBlock>, StoreError> {
+        match self.hot_blocks.get_block(root)? {
+            Some(block) => Ok(Some(block)),
+            None => self.cold_blocks.get_block(root),
+        }
+    }
+
+    pub fn get_block_root_at_slot(&self, slot: u64) -> Result<Option<B256>, StoreError> {
+        if slot < self.split().slot {
+            self.cold_blocks.get_block_root_at_slot(slot)
+        } else {
+            self.hot_blocks.get_block_root_at_slot(slot)
+        }
+    }
+
+    /// Recently used states are served from memory, others are loaded or rebuilt and then
+    /// cached.
+    pub fn get_state<R: StateReplayer + ?Sized>(
+        &self,
+        state_root: &B256,
+        replayer: &R,
+    ) -> Result<Option<Arc<BeaconState>>, StoreError> {
+        if let Some(state) = self.state_cache.get(state_root) {
+            return Ok(Some(state));
+        }
+        let state = match self.hot_states.get_state(state_root, replayer)? {
+            Some(state) => state,
+            None => match self.cold_states.get_state(state_root, replayer)? {
+                Some(state) => state,
+                None => return Ok(None),
+            },
+        };
+        let state = Arc::new(state);
+        self.state_cache.insert(*state_root, state.clone());
+        Ok(Some(state))
+    }
+
+    pub fn state_cache(&self) -> &StateCache {
+        &self.state_cache
+    }
+
+    /// Moves the chain up to `finalized_root` into the cold database, drops blocks and states
+    /// that don't descend from it, compacts the hot database and advances the split to the
+    /// finalized block. The cold database is then pruned according to the [`PruningMode`].
+    ///
+    /// Meant to run off the block import path every time the finalized checkpoint changes. The
+    /// cold database is written before the hot one, so an interrupted migration leaves both
+    /// readable and is redone on the next finalization. If compaction fails the data has
+    /// already been migrated.
+    pub fn migrate<R: StateReplayer>(
+        &self,
+        finalized_root: B256,
+        replayer: &R,
+    ) -> Result<(), StoreError> {
+        let split = self.split();
+        let finalized_block = self
+            .hot_blocks
+            .get_block(&finalized_root)?
+            .ok_or(StoreError::UnknownBlock(finalized_root))?;
+        let finalized_slot = finalized_block.message.slot;
+        if finalized_slot <= split.slot && split.block_root != B256::ZERO {
+            return Ok(());
+        }
+
+        // The hot database always has a snapshot at the split to replay newer states from.
+        let finalized_state_root = finalized_block.message.state_root;
+        let finalized_state = self
+            .hot_states
+            .get_state(&finalized_state_root, replayer)?
+            .ok_or(StoreError::MissingSnapshot(finalized_state_root))?;
+
+        let mut finalized_chain = HashSet::new();
+        let mut cold_batch = TypedBatch::new();
+        for ancestor in self.hot_blocks.ancestors(finalized_root) {
+            let (root, slot) = ancestor?;
+            if root == split.block_root {
+                break;
+            }
+            let block = self
+                .hot_blocks
+                .get_block(&root)?
+                .ok_or(StoreError::UnknownBlock(root))?;
+            cold_batch.put::<BlocksByRoot>(&root, &block);
+            cold_batch.put::<BlockSummaries>(
+                &root,
+                &BlockSummary {
+                    slot,
+                    parent_root: block.message.parent_root,
+                },
+            );
+            cold_batch.put::<BlockRootsBySlot>(&slot, &root);
+            finalized_chain.insert(root);
+        }
+
+        // Only descendants of the finalized block stay hot, blocks are visited by slot so that
+        // parents are seen before their children.
+        let mut blocks = vec![];
+        self.hot
+            .iterate::<BlockSummaries>(&B256::ZERO, |root, summary| {
+                blocks.push((root, summary));
+                true
+            })?;
+        blocks.sort_by_key(|(_, summary)| summary.slot);
+        let mut descendants = HashSet::from([finalized_root]);
+        let mut hot_batch = TypedBatch::new();
+        for (root, summary) in blocks {
+            if summary.slot > finalized_slot && descendants.contains(&summary.parent_root) {
+                descendants.insert(root);
+            } else if root != finalized_root {
+                hot_batch.delete::<BlocksByRoot>(&root);
+                hot_batch.delete::<BlockSummaries>(&root);
+            }
+        }
+        self.hot.iterate::<BlockRootsBySlot>(&0, |slot, root| {
+            if slot < finalized_slot || !descendants.contains(&root) {
+                hot_batch.delete::<BlockRootsBySlot>(&slot);
+            }
+            true
+        })?;
+
+        let mut stale_states = vec![];
+        self.hot
+            .iterate::<StateSummaries>(&B256::ZERO, |root, summary| {
+                if summary.slot < finalized_slot
+                    || (summary.slot == finalized_slot && root != finalized_state_root)
+                    || !descendants.contains(&summary.latest_block_root)
+                {
+                    stale_states.push((root, summary));
+                }
+                true
+            })?;
+        for (root, summary) in stale_states {
+            if finalized_chain.contains(&summary.latest_block_root)
+                || summary.latest_block_root == split.block_root
+            {
+                cold_batch.put::<StateSummaries>(&root, &summary);
+                if summary.snapshot {
+                    if let Some(state) = self.hot.get::<StatesByRoot>(&root)? {
+                        cold_batch.put::<StatesByRoot>(&root, &state);
+                    }
+                }
+            }
+            hot_batch.delete::<StateSummaries>(&root);
+            hot_batch.delete::<StatesByRoot>(&root);
+        }
+
+        let finalized_summary = StateSummary {
+            slot: finalized_state.slot,
+            latest_block_root: finalized_root,
+            snapshot: true,
+        };
+        cold_batch.put::<StateSummaries>(&finalized_state_root, &finalized_summary);
+        cold_batch.put::<StatesByRoot>(&finalized_state_root, &finalized_state);
+        hot_batch.put::<StateSummaries>(&finalized_state_root, &finalized_summary);
+        hot_batch.put::<StatesByRoot>(&finalized_state_root, &finalized_state);
+
+        let new_split = Split {
+            slot: finalized_slot,
+            block_root: finalized_root,
+            state_root: finalized_state_root,
+        };
+        hot_batch.put::<SplitPoint>(&(), &new_split);
+
+        self.cold.write(cold_batch)?;
+        self.hot.write(hot_batch)?;
+        *self.split.write().expect("split lock poisoned") = new_split;
+
+        self.prune_cold()?;
+        self.hot.compact()
+    }
+
+    /// Deletes finalized blocks and states older than the [`PruningMode`] retains.
+    ///
+    /// The cut is moved back to the latest snapshot at or before the first retained slot, so
+    /// every retained state can still be rebuilt.
+    pub fn prune_cold(&self) -> Result<(), StoreError> {
+        let Some(retain_from) = self.pruning.retain_from(self.split().slot) else {
+            return Ok(());
+        };
+
+        let mut states = vec![];
+        self.cold
+            .iterate::<StateSummaries>(&B256::ZERO, |root, summary| {
+                states.push((root, summary));
+                true
+            })?;
+        let Some(cutoff) = states
+            .iter()
+            .filter(|(_, summary)| summary.snapshot && summary.slot <= retain_from)
+            .map(|(_, summary)| summary.slot)
+            .max()
+        else {
+            return Ok(());
+        };
+
+        let mut batch = TypedBatch::new();
+        for (root, summary) in states {
+            if summary.slot < cutoff {
+                batch.delete::<StateSummaries>(&root);
+                batch.delete::<StatesByRoot>(&root);
+            }
+        }
+        let mut blocks = vec![];
+        self.cold.iterate::<BlockRootsBySlot>(&0, |slot, root| {
+            if slot < cutoff {
+                blocks.push((slot, root));
+            }
+            slot < cutoff
+        })?;
+        for (slot, root) in blocks {
+            batch.delete::<BlockRootsBySlot>(&slot);
+            batch.delete::<BlocksByRoot>(&root);
+            batch.delete::<BlockSummaries>(&root);
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.cold.write(batch)?;
+        self.cold.compact()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::{
+        beacon_block::BeaconBlock, beacon_block_header::BeaconBlockHeader, checkpoint::Checkpoint,
+        proposer_slashing::ProposerSlashing,
+    };
+    use ream_fork_choice::{
+        proto_array::{Block, ExecutionStatus},
+        proto_array_fork_choice::ProtoArrayForkChoice,
+    };
+
+    use super::*;
+    use crate::memory_store::MemoryStore;
+
+    struct NoReplay;
+
+    impl StateReplayer for NoReplay {
+        type Error = &'static str;
+
+        fn process_slots(&self, _: &mut BeaconState, _: u64) -> Result<(), Self::Error> {
+            Err("unexpected replay")
+        }
+
+        fn process_block(
+            &self,
+            _: &mut BeaconState,
+            _: &SignedBeaconBlock,
+        ) -> Result<(), Self::Error> {
+            Err("unexpected replay")
+        }
+    }
+
+    /// Stores a block at `slot` and a snapshot of its post-state, whose root is derived from
+    /// `tag` so that forks get distinct roots.
+    fn import(store: &HotColdStore, slot: u64, parent_root: B256, tag: u8) -> (B256, B256) {
+        let state_root = B256::repeat_byte(tag);
+        let message = BeaconBlock {
+            slot,
+            parent_root,
+            state_root,
+            proposer_index: tag as u64,
+            ..BeaconBlock::default()
+        };
+        let state = BeaconState {
+            slot,
+            latest_block_header: BeaconBlockHeader {
+                state_root: B256::ZERO,
+                ..message.block_header()
+            },
+            ..BeaconState::default()
+        };
+        let block_root = store
+            .hot_blocks()
+            .put_block(&SignedBeaconBlock {
+                message,
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap();
+        store.hot_states().put_snapshot(state_root, &state).unwrap();
+        (block_root, state_root)
+    }
+
+    #[test]
+    fn test_migrate() {
+        let hot = Store::new(Arc::new(MemoryStore::new()));
+        let cold = Store::new(Arc::new(MemoryStore::new()));
+        let store = HotColdStore::open(
+            hot.clone(),
+            cold.clone(),
+            StoreConfig {
+                snapshot_interval_epochs: 1,
+                pruning: PruningMode::Archive,
+                ..StoreConfig::default()
+            },
+        )
+        .unwrap();
+
+        // 0 <- 1 <- 2 <- 3, with forks 1 <- 2' and 1 <- 3' <- 4' that get abandoned.
+        let (genesis, _) = import(&store, 0, B256::ZERO, 1);
+        let (a, a_state) = import(&store, 1, genesis, 2);
+        let (b, _) = import(&store, 2, a, 3);
+        let (fork, fork_state) = import(&store, 2, a, 4);
+        let (c, _) = import(&store, 3, b, 5);
+        let (late_fork, late_fork_state) = import(&store, 3, a, 6);
+        let (late_fork_child, _) = import(&store, 4, late_fork, 7);
+        store.hot_blocks().set_canonical_head(c).unwrap();
+
+        store.migrate(b, &NoReplay).unwrap();
+
+        assert_eq!(
+            store.split(),
+            Split {
+                slot: 2,
+                block_root: b,
+                state_root: B256::repeat_byte(3),
+            }
+        );
+        assert_eq!(store.get_block_root_at_slot(1).unwrap(), Some(a));
+        assert_eq!(store.get_block_root_at_slot(3).unwrap(), Some(c));
+        assert!(store.get_block(&a).unwrap().is_some());
+        assert!(!store.hot_blocks().contains_block(&a).unwrap());
+        assert!(store.get_block(&fork).unwrap().is_none());
+        assert!(store.get_state(&a_state, &NoReplay).unwrap().is_some());
+        assert!(store.get_state(&fork_state, &NoReplay).unwrap().is_none());
+        assert!(store.get_block(&late_fork).unwrap().is_none());
+        assert!(store.get_block(&late_fork_child).unwrap().is_none());
+        assert!(store
+            .get_state(&late_fork_state, &NoReplay)
+            .unwrap()
+            .is_none());
+
+        // The split survives a restart and migrating to it again does nothing.
+        let reopened = HotColdStore::open(
+            hot,
+            cold,
+            StoreConfig {
+                snapshot_interval_epochs: 1,
+                pruning: PruningMode::Archive,
+                ..StoreConfig::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(reopened.split(), store.split());
+        reopened.migrate(b, &NoReplay).unwrap();
+        reopened.migrate(c, &NoReplay).unwrap();
+        assert_eq!(reopened.get_block_root_at_slot(2).unwrap(), Some(b));
+        assert_eq!(reopened.split().slot, 3);
+    }
+
+    #[test]
+    fn test_prune_cold() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig {
+                snapshot_interval_epochs: 1,
+                pruning: PruningMode::Prune {
+                    retention_epochs: 1,
+                },
+                ..StoreConfig::default()
+            },
+        )
+        .unwrap();
+
+        // A block every 8 slots up to slot 96, finalized at 96 so that slots before 64 can go.
+        // Only the post-states at 40 and 72 are snapshots apart from the anchors.
+        let mut parent_root = B256::ZERO;
+        let mut roots = vec![];
+        for (tag, slot) in (0..=96).step_by(8).enumerate() {
+            let (root, state_root) = import(&store, slot, parent_root, tag as u8 + 1);
+            if ![0, 40, 72, 96].contains(&slot) {
+                let mut summary = store
+                    .hot_states()
+                    .get_state_summary(&state_root)
+                    .unwrap()
+                    .unwrap();
+                summary.snapshot = false;
+                store
+                    .hot
+                    .put::<StateSummaries>(&state_root, &summary)
+                    .unwrap();
+            }
+            roots.push(root);
+            parent_root = root;
+        }
+
+        store.migrate(parent_root, &NoReplay).unwrap();
+
+        // Slot 64 is the first one to keep, pruning stops at the snapshot at 40.
+        assert_eq!(store.get_block_root_at_slot(32).unwrap(), None);
+        assert!(store.get_block(&roots[4]).unwrap().is_none());
+        assert_eq!(store.get_block_root_at_slot(40).unwrap(), Some(roots[5]));
+        assert!(store
+            .get_state(&B256::repeat_byte(6), &NoReplay)
+            .unwrap()
+            .is_some());
+        assert!(store
+            .get_state(&B256::repeat_byte(5), &NoReplay)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_import_block() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        let import = |slot, parent_root, tag: u8, is_head, fork_choice| {
+            let message = BeaconBlock {
+                slot,
+                parent_root,
+                state_root: B256::repeat_byte(tag),
+                ..BeaconBlock::default()
+            };
+            let post_state = BeaconState {
+                slot,
+                latest_block_header: message.block_header(),
+                ..BeaconState::default()
+            };
+            store
+                .import_block(BlockImport {
+                    block: &SignedBeaconBlock {
+                        message,
+                        ..SignedBeaconBlock::default()
+                    },
+                    post_state: &post_state,
+                    fork_choice,
+                    is_head,
+                })
+                .unwrap()
+        };
+
+        let genesis = import(0, B256::ZERO, 1, true, None);
+        let fork_choice = PersistedForkChoice::from(
+            &ProtoArrayForkChoice::new(
+                Block {
+                    slot: 0,
+                    root: genesis,
+                    parent_root: B256::ZERO,
+                    justified_checkpoint: Checkpoint::default(),
+                    finalized_checkpoint: Checkpoint::default(),
+                    execution_status: ExecutionStatus::Irrelevant(false),
+                },
+                Checkpoint::default(),
+                Checkpoint::default(),
+            )
+            .unwrap(),
+        );
+        let a = import(1, genesis, 2, true, Some(&fork_choice));
+        let b = import(2, a, 3, true, None);
+        let fork = import(2, genesis, 4, false, None);
+        assert_eq!(store.get_block_root_at_slot(2).unwrap(), Some(b));
+
+        // Reorg onto the fork with a block that skips slot 2.
+        let c = import(3, fork, 5, true, None);
+        let index = (0..4)
+            .map(|slot| store.get_block_root_at_slot(slot).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(index, vec![Some(genesis), None, Some(fork), Some(c)]);
+
+        assert!(store
+            .hot_states()
+            .get_state_summary(&B256::repeat_byte(3))
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            store.hot().get::<ForkChoiceSnapshot>(&()).unwrap(),
+            Some(fork_choice)
+        );
+    }
+
+    #[test]
+    fn test_persist_on_shutdown() {
+        let store = HotColdStore::open(
+            Store::new(Arc::new(MemoryStore::new())),
+            Store::new(Arc::new(MemoryStore::new())),
+            StoreConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(store.get_operation_pool().unwrap(), None);
+
+        let operation_pool = PersistedOperationPool {
+            proposer_slashings: vec![ProposerSlashing::default()],
+            ..PersistedOperationPool::default()
+        };
+        store.persist_on_shutdown(&operation_pool, None).unwrap();
+        assert_eq!(store.get_operation_pool().unwrap(), Some(operation_pool));
+        assert_eq!(store.hot().get::<ForkChoiceSnapshot>(&()).unwrap(), None);
+    }
+}
@@ -1,14 +1,15 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod blob_store;
+pub mod block_store;
+pub mod database;
+pub mod era;
+pub mod error;
+pub mod hot_cold;
+pub mod memory_store;
+pub mod metrics;
+pub mod pruning;
+pub mod redb_store;
+pub mod state_cache;
+pub mod state_store;
+pub mod store;
+pub mod tables;
+pub mod verify;
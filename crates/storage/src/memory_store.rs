@@ -0,0 +1,70 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::RwLock,
+};
+
+use crate::{
+    database::{BatchOperation, Column, KeyValueStore, WriteBatch},
+    error::StoreError,
+};
+
+type Columns = HashMap<Column, BTreeMap<Vec<u8>, Vec<u8>>>;
+
+/// In-memory [`KeyValueStore`], for tests and ephemeral nodes.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    columns: RwLock<Columns>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyValueStore for MemoryStore {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let columns = self.columns.read().expect("memory store lock poisoned");
+        Ok(columns
+            .get(&column)
+            .and_then(|entries| entries.get(key))
+            .cloned())
+    }
+
+    fn write(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        let mut columns = self.columns.write().expect("memory store lock poisoned");
+        for operation in batch.operations() {
+            match operation {
+                BatchOperation::Put { column, key, value } => {
+                    columns
+                        .entry(*column)
+                        .or_default()
+                        .insert(key.clone(), value.clone());
+                }
+                BatchOperation::Delete { column, key } => {
+                    if let Some(entries) = columns.get_mut(column) {
+                        entries.remove(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn iterate(
+        &self,
+        column: Column,
+        from: &[u8],
+        f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> Result<(), StoreError> {
+        let columns = self.columns.read().expect("memory store lock poisoned");
+        if let Some(entries) = columns.get(&column) {
+            for (key, value) in entries.range(from.to_vec()..) {
+                if !f(key, value) {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
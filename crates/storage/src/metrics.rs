@@ -0,0 +1,19 @@
+//! Cost of rebuilding states that weren't stored in full, by stage of the state transition.
+
+use ream_metrics::{histogram, histogram_vec, Histogram, HistogramVec, Metric};
+
+pub const LOAD_SNAPSHOT: &str = "load_snapshot";
+pub const PROCESS_SLOTS: &str = "process_slots";
+pub const PROCESS_BLOCK: &str = "process_block";
+
+pub static STATE_REPLAY_STAGE_TIME: Metric<HistogramVec> = histogram_vec!(
+    "ream_state_replay_stage_seconds",
+    "Time spent in each stage of replaying blocks onto a snapshot",
+    [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0],
+    ["stage"]
+);
+pub static STATE_REPLAY_BLOCKS: Metric<Histogram> = histogram!(
+    "ream_state_replay_blocks",
+    "Blocks replayed to rebuild a state",
+    [0.0, 1.0, 4.0, 16.0, 64.0, 256.0, 1024.0, 2048.0]
+);
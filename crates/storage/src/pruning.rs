@@ -0,0 +1,79 @@
+use std::{fmt, str::FromStr};
+
+use ream_consensus::constants::SLOTS_PER_EPOCH;
+
+/// Which finalized blocks and states the cold database keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PruningMode {
+    /// Keep the whole finalized history.
+    #[default]
+    Archive,
+    /// Keep roughly the last `retention_epochs` epochs before the split. History is cut at a
+    /// snapshot, so a little more than that can be kept.
+    Prune { retention_epochs: u64 },
+}
+
+impl PruningMode {
+    /// First slot to keep when the split is at `split_slot`, `None` to keep everything.
+    pub fn retain_from(&self, split_slot: u64) -> Option<u64> {
+        match self {
+            PruningMode::Archive => None,
+            PruningMode::Prune { retention_epochs } => {
+                Some(split_slot.saturating_sub(retention_epochs.saturating_mul(SLOTS_PER_EPOCH)))
+            }
+        }
+    }
+}
+
+/// Parses `archive` or `prune:<retention epochs>`.
+impl FromStr for PruningMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "archive" => Ok(PruningMode::Archive),
+            Some(("prune", epochs)) => epochs
+                .parse()
+                .map(|retention_epochs| PruningMode::Prune { retention_epochs })
+                .map_err(|err| format!("invalid retention epochs {epochs:?}: {err}")),
+            _ => Err(format!(
+                "unknown pruning mode {s:?}, expected archive or prune:<epochs>"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PruningMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruningMode::Archive => f.write_str("archive"),
+            PruningMode::Prune { retention_epochs } => write!(f, "prune:{retention_epochs}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pruning_mode() {
+        for mode in [
+            PruningMode::Archive,
+            PruningMode::Prune {
+                retention_epochs: 256,
+            },
+        ] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        assert!("prune".parse::<PruningMode>().is_err());
+        assert!("prune:many".parse::<PruningMode>().is_err());
+
+        let mode = PruningMode::Prune {
+            retention_epochs: 2,
+        };
+        assert_eq!(mode.retain_from(100), Some(36));
+        assert_eq!(mode.retain_from(10), Some(0));
+        assert_eq!(PruningMode::Archive.retain_from(100), None);
+    }
+}
@@ -0,0 +1,95 @@
+use std::{path::Path, sync::RwLock};
+
+use redb::{Database, TableDefinition};
+
+use crate::{
+    database::{BatchOperation, Column, KeyValueStore, WriteBatch},
+    error::StoreError,
+};
+
+fn table(column: Column) -> TableDefinition<'static, &'static [u8], &'static [u8]> {
+    TableDefinition::new(column.name())
+}
+
+/// [`KeyValueStore`] backed by a redb database file.
+///
+/// Transactions only need shared access to the database, the lock is taken exclusively for
+/// compaction.
+#[derive(Debug)]
+pub struct RedbStore {
+    db: RwLock<Database>,
+}
+
+impl RedbStore {
+    /// Opens the database at `path`, creating it and any missing table.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let db = Database::create(path)?;
+
+        let write_txn = db.begin_write()?;
+        for column in Column::ALL {
+            write_txn.open_table(table(column))?;
+        }
+        write_txn.commit()?;
+
+        Ok(Self {
+            db: RwLock::new(db),
+        })
+    }
+
+    fn database(&self) -> std::sync::RwLockReadGuard<'_, Database> {
+        self.db.read().expect("redb store lock poisoned")
+    }
+}
+
+impl KeyValueStore for RedbStore {
+    fn get(&self, column: Column, key: &[u8]) -> Result<Option<Vec<u8>>, StoreError> {
+        let read_txn = self.database().begin_read()?;
+        let table = read_txn.open_table(table(column))?;
+        Ok(table.get(key)?.map(|value| value.value().to_vec()))
+    }
+
+    fn write(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        let write_txn = self.database().begin_write()?;
+        for operation in batch.operations() {
+            match operation {
+                BatchOperation::Put { column, key, value } => {
+                    write_txn
+                        .open_table(table(*column))?
+                        .insert(key.as_slice(), value.as_slice())?;
+                }
+                BatchOperation::Delete { column, key } => {
+                    write_txn
+                        .open_table(table(*column))?
+                        .remove(key.as_slice())?;
+                }
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    fn iterate(
+        &self,
+        column: Column,
+        from: &[u8],
+        f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> Result<(), StoreError> {
+        let read_txn = self.database().begin_read()?;
+        let table = read_txn.open_table(table(column))?;
+        for entry in table.range(from..)? {
+            let (key, value) = entry?;
+            if !f(key.value(), value.value()) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn compact(&self) -> Result<(), StoreError> {
+        self.db
+            .write()
+            .expect("redb store lock poisoned")
+            .compact()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::size_of,
+    sync::{Arc, Mutex},
+};
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    beacon_state::BeaconState, eth1_data::Eth1Data, historical_summary::HistoricalSummary,
+    validator::Validator,
+};
+
+pub const DEFAULT_STATE_CACHE_BYTES: usize = 1 << 30;
+
+/// Fields whose size doesn't depend on the validator count: block and state roots, randao
+/// mixes, slashings and the sync committees dominate.
+const FIXED_STATE_BYTES: usize = 2 * 8192 * 32 + 65536 * 32 + 8192 * 8 + 2 * 513 * 48 + 4096;
+
+/// Validator record, balance, two participation flags and inactivity score.
+const PER_VALIDATOR_BYTES: usize = size_of::<Validator>() + 8 + 2 + 8;
+
+/// Rough in-memory size of `state`, good enough to budget the cache by.
+pub fn estimate_state_size(state: &BeaconState) -> usize {
+    FIXED_STATE_BYTES
+        + state.validators.len() * PER_VALIDATOR_BYTES
+        + state.eth1_data_votes.len() * size_of::<Eth1Data>()
+        + state.historical_roots.len() * size_of::<B256>()
+        + state.historical_summaries.len() * size_of::<HistoricalSummary>()
+}
+
+#[derive(Default)]
+struct Inner {
+    states: HashMap<B256, CachedState>,
+    /// State roots by the tick they were last used at, oldest first.
+    lru: BTreeMap<u64, B256>,
+    tick: u64,
+    used_bytes: usize,
+}
+
+struct CachedState {
+    state: Arc<BeaconState>,
+    size: usize,
+    last_used: u64,
+}
+
+impl Inner {
+    fn touch(&mut self, root: B256) -> Option<Arc<BeaconState>> {
+        self.tick += 1;
+        let entry = self.states.get_mut(&root)?;
+        self.lru.remove(&entry.last_used);
+        entry.last_used = self.tick;
+        self.lru.insert(self.tick, root);
+        Some(entry.state.clone())
+    }
+
+    fn remove(&mut self, root: &B256) {
+        if let Some(entry) = self.states.remove(root) {
+            self.lru.remove(&entry.last_used);
+            self.used_bytes -= entry.size;
+        }
+    }
+}
+
+/// Least recently used cache of states by state root, bounded by the estimated memory the
+/// states take rather than their number.
+pub struct StateCache {
+    inner: Mutex<Inner>,
+    budget_bytes: usize,
+}
+
+impl StateCache {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            inner: Mutex::default(),
+            budget_bytes,
+        }
+    }
+
+    pub fn get(&self, state_root: &B256) -> Option<Arc<BeaconState>> {
+        self.lock().touch(*state_root)
+    }
+
+    /// Caches `state`, evicting the least recently used states until it fits. States larger
+    /// than the whole budget aren't cached.
+    pub fn insert(&self, state_root: B256, state: Arc<BeaconState>) {
+        let size = estimate_state_size(&state);
+        if size > self.budget_bytes {
+            return;
+        }
+
+        let mut inner = self.lock();
+        inner.remove(&state_root);
+        while inner.used_bytes + size > self.budget_bytes {
+            let Some((_, oldest)) = inner.lru.pop_first() else {
+                break;
+            };
+            inner.remove(&oldest);
+        }
+
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.lru.insert(tick, state_root);
+        inner.used_bytes += size;
+        inner.states.insert(
+            state_root,
+            CachedState {
+                state,
+                size,
+                last_used: tick,
+            },
+        );
+    }
+
+    pub fn remove(&self, state_root: &B256) {
+        self.lock().remove(state_root);
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.lock().used_bytes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().expect("state cache lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let state = Arc::new(BeaconState::default());
+        let size = estimate_state_size(&state);
+        let cache = StateCache::new(2 * size);
+
+        cache.insert(B256::repeat_byte(1), state.clone());
+        cache.insert(B256::repeat_byte(2), state.clone());
+        assert!(cache.get(&B256::repeat_byte(1)).is_some());
+        cache.insert(B256::repeat_byte(3), state.clone());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.used_bytes(), 2 * size);
+        assert!(cache.get(&B256::repeat_byte(2)).is_none());
+        assert!(cache.get(&B256::repeat_byte(1)).is_some());
+
+        // A state with many validators is bigger than the whole budget.
+        let large = BeaconState {
+            validators: vec![Validator::default(); 2 * size / PER_VALIDATOR_BYTES]
+                .try_into()
+                .unwrap(),
+            ..BeaconState::default()
+        };
+        cache.insert(B256::repeat_byte(4), Arc::new(large));
+        assert!(cache.get(&B256::repeat_byte(4)).is_none());
+        assert_eq!(cache.len(), 2);
+    }
+}
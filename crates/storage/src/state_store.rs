@@ -0,0 +1,288 @@
+use std::fmt::Display;
+
+use alloy_primitives::B256;
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock, beacon_state::BeaconState, constants::SLOTS_PER_EPOCH,
+};
+use ream_metrics::{observe, start_timer_vec};
+use tracing::{debug_span, trace_span};
+
+use crate::{
+    block_store::BlockStore,
+    error::StoreError,
+    metrics::{self, LOAD_SNAPSHOT, PROCESS_BLOCK, PROCESS_SLOTS},
+    store::{Store, TypedBatch},
+    tables::{StateSummaries, StateSummary, StatesByRoot},
+};
+
+pub const DEFAULT_SNAPSHOT_INTERVAL_EPOCHS: u64 = 64;
+
+/// The state transition, used to rebuild states that weren't stored in full.
+pub trait StateReplayer {
+    type Error: Display;
+
+    /// Advances `state` through empty slots up to `slot`.
+    fn process_slots(&self, state: &mut BeaconState, slot: u64) -> Result<(), Self::Error>;
+
+    /// Applies `block` to `state`, which is already at the slot of the block. Replayed blocks
+    /// were valid when they were imported, so signatures don't need to be checked again.
+    fn process_block(
+        &self,
+        state: &mut BeaconState,
+        block: &SignedBeaconBlock,
+    ) -> Result<(), Self::Error>;
+}
+
+/// States stored as full snapshots every `snapshot_interval_epochs` and as summaries otherwise.
+///
+/// A snapshot is taken of the post-state of the first block of every interval on each chain, so
+/// rebuilding a state replays at most one interval of blocks. A larger interval saves disk at
+/// the cost of slower reconstruction.
+#[derive(Clone)]
+pub struct StateStore {
+    store: Store,
+    blocks: BlockStore,
+    snapshot_interval_slots: u64,
+}
+
+impl StateStore {
+    pub fn new(store: Store, snapshot_interval_epochs: u64) -> Self {
+        Self {
+            blocks: BlockStore::new(store.clone()),
+            store,
+            snapshot_interval_slots: snapshot_interval_epochs.max(1) * SLOTS_PER_EPOCH,
+        }
+    }
+
+    pub fn put_state(&self, state_root: B256, state: &BeaconState) -> Result<(), StoreError> {
+        let mut batch = TypedBatch::new();
+        self.stage_state(&mut batch, state_root, state)?;
+        self.store.write(batch)
+    }
+
+    /// Adds the writes of [`Self::put_state`] to `batch`.
+    pub fn stage_state(
+        &self,
+        batch: &mut TypedBatch,
+        state_root: B256,
+        state: &BeaconState,
+    ) -> Result<(), StoreError> {
+        let snapshot = self.should_snapshot(state)?;
+        batch.put::<StateSummaries>(
+            &state_root,
+            &StateSummary {
+                slot: state.slot,
+                latest_block_root: state.latest_block_root(state_root),
+                snapshot,
+            },
+        );
+        if snapshot {
+            batch.put::<StatesByRoot>(&state_root, state);
+        }
+        Ok(())
+    }
+
+    /// Always stores `state` in full, for anchors such as genesis or a checkpoint sync state.
+    pub fn put_snapshot(&self, state_root: B256, state: &BeaconState) -> Result<(), StoreError> {
+        let mut batch = TypedBatch::new();
+        batch.put::<StateSummaries>(
+            &state_root,
+            &StateSummary {
+                slot: state.slot,
+                latest_block_root: state.latest_block_root(state_root),
+                snapshot: true,
+            },
+        );
+        batch.put::<StatesByRoot>(&state_root, state);
+        self.store.write(batch)
+    }
+
+    pub fn get_state_summary(&self, state_root: &B256) -> Result<Option<StateSummary>, StoreError> {
+        self.store.get::<StateSummaries>(state_root)
+    }
+
+    /// Loads the state with root `state_root`, replaying blocks from the nearest snapshot if it
+    /// isn't stored in full.
+    pub fn get_state<R: StateReplayer + ?Sized>(
+        &self,
+        state_root: &B256,
+        replayer: &R,
+    ) -> Result<Option<BeaconState>, StoreError> {
+        let Some(summary) = self.get_state_summary(state_root)? else {
+            return Ok(None);
+        };
+        if summary.snapshot {
+            return self.store.get::<StatesByRoot>(state_root);
+        }
+
+        // Blocks after the snapshot, newest first.
+        let mut blocks = vec![];
+        let mut base = None;
+        for ancestor in self.blocks.ancestors(summary.latest_block_root) {
+            let (root, _) = ancestor?;
+            let block = self
+                .blocks
+                .get_block(&root)?
+                .ok_or(StoreError::UnknownBlock(root))?;
+            let block_state_root = block.message.state_root;
+            if self
+                .get_state_summary(&block_state_root)?
+                .is_some_and(|summary| summary.snapshot)
+            {
+                base = Some(block_state_root);
+                break;
+            }
+            blocks.push(block);
+        }
+
+        let _span = debug_span!("state_replay", %state_root, blocks = blocks.len()).entered();
+        observe(&metrics::STATE_REPLAY_BLOCKS, blocks.len() as f64);
+        let timer = start_timer_vec(&metrics::STATE_REPLAY_STAGE_TIME, &[LOAD_SNAPSHOT]);
+        let mut state = base
+            .map(|root| self.store.get::<StatesByRoot>(&root))
+            .transpose()?
+            .flatten()
+            .ok_or(StoreError::MissingSnapshot(*state_root))?;
+        drop(timer);
+        let replay_error = |err: R::Error| StoreError::Replay(err.to_string());
+        for block in blocks.iter().rev() {
+            let _span = trace_span!("replay_block", slot = block.message.slot).entered();
+            replay_slots(replayer, &mut state, block.message.slot).map_err(replay_error)?;
+            let _timer = start_timer_vec(&metrics::STATE_REPLAY_STAGE_TIME, &[PROCESS_BLOCK]);
+            replayer
+                .process_block(&mut state, block)
+                .map_err(replay_error)?;
+        }
+        if state.slot < summary.slot {
+            replay_slots(replayer, &mut state, summary.slot).map_err(replay_error)?;
+        }
+
+        Ok(Some(state))
+    }
+
+    /// Snapshots the post-state of a block whose parent is in an earlier interval, or unknown.
+    fn should_snapshot(&self, state: &BeaconState) -> Result<bool, StoreError> {
+        if state.slot != state.latest_block_header.slot {
+            return Ok(false);
+        }
+        let parent = self
+            .blocks
+            .get_block_summary(&state.latest_block_header.parent_root)?;
+        Ok(parent.map_or(true, |parent| {
+            parent.slot / self.snapshot_interval_slots != state.slot / self.snapshot_interval_slots
+        }))
+    }
+}
+
+/// [`StateReplayer::process_slots`], timed.
+fn replay_slots<R: StateReplayer + ?Sized>(
+    replayer: &R,
+    state: &mut BeaconState,
+    slot: u64,
+) -> Result<(), R::Error> {
+    let _timer = start_timer_vec(&metrics::STATE_REPLAY_STAGE_TIME, &[PROCESS_SLOTS]);
+    replayer.process_slots(state, slot)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, sync::Arc};
+
+    use ream_consensus::beacon_block::BeaconBlock;
+
+    use super::*;
+    use crate::memory_store::MemoryStore;
+
+    /// Moves the state forward without doing any real processing, counting replayed blocks.
+    #[derive(Default)]
+    struct FakeTransition {
+        replayed: Cell<usize>,
+    }
+
+    impl StateReplayer for FakeTransition {
+        type Error = String;
+
+        fn process_slots(&self, state: &mut BeaconState, slot: u64) -> Result<(), String> {
+            if slot > state.slot && state.latest_block_header.state_root == B256::ZERO {
+                state.latest_block_header.state_root = state_root(state.slot);
+            }
+            state.slot = slot;
+            Ok(())
+        }
+
+        fn process_block(
+            &self,
+            state: &mut BeaconState,
+            block: &SignedBeaconBlock,
+        ) -> Result<(), String> {
+            let mut header = block.message.block_header();
+            header.state_root = B256::ZERO;
+            state.latest_block_header = header;
+            state.eth1_deposit_index += 1;
+            self.replayed.set(self.replayed.get() + 1);
+            Ok(())
+        }
+    }
+
+    fn state_root(slot: u64) -> B256 {
+        B256::left_padding_from(&(slot + 1).to_be_bytes())
+    }
+
+    #[test]
+    fn test_replay_from_snapshot() {
+        let store = Store::new(Arc::new(MemoryStore::new()));
+        let blocks = BlockStore::new(store.clone());
+        let states = StateStore::new(store, 1);
+        let transition = FakeTransition::default();
+
+        // A block at every slot from 0 to 40, the post-states at slots 0 and 32 get snapshotted.
+        let mut state = BeaconState::default();
+        let mut parent_root = B256::ZERO;
+        for slot in 0..=40 {
+            let block = SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot,
+                    parent_root,
+                    state_root: state_root(slot),
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            };
+            parent_root = blocks.put_block(&block).unwrap();
+            transition.process_slots(&mut state, slot).unwrap();
+            transition.process_block(&mut state, &block).unwrap();
+            states.put_state(state_root(slot), &state).unwrap();
+        }
+        let snapshots = (0..=40)
+            .filter(|slot| {
+                states
+                    .get_state_summary(&state_root(*slot))
+                    .unwrap()
+                    .unwrap()
+                    .snapshot
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(snapshots, vec![0, 32]);
+
+        let replayer = FakeTransition::default();
+        assert_eq!(
+            states.get_state(&state_root(40), &replayer).unwrap(),
+            Some(state.clone())
+        );
+        assert_eq!(replayer.replayed.get(), 8);
+
+        // A state after empty slots is rebuilt from its latest block.
+        transition.process_slots(&mut state, 45).unwrap();
+        let skipped_root = B256::repeat_byte(0xff);
+        states.put_state(skipped_root, &state).unwrap();
+        assert_eq!(
+            states.get_state(&skipped_root, &replayer).unwrap(),
+            Some(state)
+        );
+
+        assert_eq!(
+            states.get_state(&B256::repeat_byte(1), &replayer).unwrap(),
+            None
+        );
+    }
+}
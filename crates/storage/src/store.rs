@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use ssz::{Decode, Encode};
+
+use crate::{
+    database::{Column, KeyValueStore, WriteBatch},
+    error::StoreError,
+    tables::{Table, TableKey},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnStats {
+    pub entries: u64,
+    /// Size of keys and values, not counting database overhead.
+    pub bytes: u64,
+}
+
+/// Typed access to the tables of a [`KeyValueStore`].
+#[derive(Clone)]
+pub struct Store {
+    db: Arc<dyn KeyValueStore>,
+}
+
+impl Store {
+    pub fn new(db: Arc<dyn KeyValueStore>) -> Self {
+        Self { db }
+    }
+
+    pub fn get<T: Table>(&self, key: &T::Key) -> Result<Option<T::Value>, StoreError> {
+        self.db
+            .get(T::COLUMN, &key.encode_key())?
+            .map(|bytes| decode_value::<T>(&bytes))
+            .transpose()
+    }
+
+    pub fn put<T: Table>(&self, key: &T::Key, value: &T::Value) -> Result<(), StoreError> {
+        self.db
+            .put(T::COLUMN, &key.encode_key(), &value.as_ssz_bytes())
+    }
+
+    pub fn delete<T: Table>(&self, key: &T::Key) -> Result<(), StoreError> {
+        self.db.delete(T::COLUMN, &key.encode_key())
+    }
+
+    /// Atomically applies `batch`.
+    pub fn write(&self, batch: TypedBatch) -> Result<(), StoreError> {
+        self.db.write(batch.0)
+    }
+
+    /// Counts the entries of `column` by reading all of them.
+    pub fn column_stats(&self, column: Column) -> Result<ColumnStats, StoreError> {
+        let mut stats = ColumnStats::default();
+        self.db.iterate(column, &[], &mut |key, value| {
+            stats.entries += 1;
+            stats.bytes += (key.len() + value.len()) as u64;
+            true
+        })?;
+        Ok(stats)
+    }
+
+    pub fn compact(&self) -> Result<(), StoreError> {
+        self.db.compact()
+    }
+
+    /// Calls `f` with every entry of `T` whose key is at least `from`, in key order, until it
+    /// returns `false`.
+    pub fn iterate<T: Table>(
+        &self,
+        from: &T::Key,
+        mut f: impl FnMut(T::Key, T::Value) -> bool,
+    ) -> Result<(), StoreError> {
+        let mut result = Ok(());
+        self.db
+            .iterate(T::COLUMN, &from.encode_key(), &mut |key, value| {
+                let entry = T::Key::decode_key(key)
+                    .ok_or(StoreError::Decode {
+                        column: T::COLUMN.name(),
+                        kind: "key",
+                        message: format!("unexpected key length {}", key.len()),
+                    })
+                    .and_then(|key| Ok((key, decode_value::<T>(value)?)));
+                match entry {
+                    Ok((key, value)) => f(key, value),
+                    Err(err) => {
+                        result = Err(err);
+                        false
+                    }
+                }
+            })?;
+        result
+    }
+}
+
+fn decode_value<T: Table>(bytes: &[u8]) -> Result<T::Value, StoreError> {
+    T::Value::from_ssz_bytes(bytes).map_err(|err| StoreError::Decode {
+        column: T::COLUMN.name(),
+        kind: "value",
+        message: format!("{err:?}"),
+    })
+}
+
+/// A [`WriteBatch`] built from typed table operations.
+#[derive(Debug, Clone, Default)]
+pub struct TypedBatch(WriteBatch);
+
+impl TypedBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put<T: Table>(&mut self, key: &T::Key, value: &T::Value) {
+        self.0
+            .put(T::COLUMN, key.encode_key(), value.as_ssz_bytes());
+    }
+
+    pub fn delete<T: Table>(&mut self, key: &T::Key) {
+        self.0.delete(T::COLUMN, key.encode_key());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+
+    use super::*;
+    use crate::{
+        database::Column,
+        memory_store::MemoryStore,
+        redb_store::RedbStore,
+        tables::{BlockRootsBySlot, ForkChoiceSnapshot},
+    };
+
+    fn check_store(store: Store) {
+        let mut batch = TypedBatch::new();
+        for slot in [3u64, 1, 256, 2] {
+            batch.put::<BlockRootsBySlot>(&slot, &B256::repeat_byte(slot as u8));
+        }
+        store.write(batch).unwrap();
+
+        assert_eq!(
+            store.get::<BlockRootsBySlot>(&256).unwrap(),
+            Some(B256::repeat_byte(0))
+        );
+        assert_eq!(store.get::<BlockRootsBySlot>(&4).unwrap(), None);
+
+        let mut slots = vec![];
+        store
+            .iterate::<BlockRootsBySlot>(&2, |slot, _| {
+                slots.push(slot);
+                slot < 3
+            })
+            .unwrap();
+        assert_eq!(slots, vec![2, 3]);
+
+        store.delete::<BlockRootsBySlot>(&3).unwrap();
+        assert_eq!(store.get::<BlockRootsBySlot>(&3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        check_store(Store::new(Arc::new(MemoryStore::new())));
+    }
+
+    #[test]
+    fn test_redb_store_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.redb");
+        check_store(Store::new(Arc::new(RedbStore::open(&path).unwrap())));
+
+        let reopened = Store::new(Arc::new(RedbStore::open(&path).unwrap()));
+        assert_eq!(
+            reopened.get::<BlockRootsBySlot>(&1).unwrap(),
+            Some(B256::repeat_byte(1))
+        );
+    }
+
+    #[test]
+    fn test_corrupt_value() {
+        let db = Arc::new(MemoryStore::new());
+        db.put(Column::ForkChoice, &[], &[1, 2, 3]).unwrap();
+
+        assert!(matches!(
+            Store::new(db).get::<ForkChoiceSnapshot>(&()),
+            Err(StoreError::Decode {
+                column: "fork_choice",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use alloy_primitives::B256;
+use ream_consensus::{
+    beacon_block::SignedBeaconBlock, beacon_state::BeaconState, blob_sidecar::BlobSidecar,
+};
+use ream_fork_choice::persisted_fork_choice::PersistedForkChoice;
+use ream_operation_pool::persisted_operation_pool::PersistedOperationPool;
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+
+use crate::database::Column;
+
+/// Encoding of a table key. Encodings must sort in the same order as the keys, so that ranges
+/// of keys can be iterated.
+pub trait TableKey: Sized {
+    fn encode_key(&self) -> Vec<u8>;
+
+    fn decode_key(bytes: &[u8]) -> Option<Self>;
+}
+
+impl TableKey for B256 {
+    fn encode_key(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn decode_key(bytes: &[u8]) -> Option<Self> {
+        (bytes.len() == 32).then(|| B256::from_slice(bytes))
+    }
+}
+
+/// Big endian so that slots iterate in ascending order.
+impl TableKey for u64 {
+    fn encode_key(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode_key(bytes: &[u8]) -> Option<Self> {
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// Key of tables holding a single value.
+impl TableKey for () {
+    fn encode_key(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn decode_key(bytes: &[u8]) -> Option<Self> {
+        bytes.is_empty().then_some(())
+    }
+}
+
+/// Concatenation of both encodings, the first element must have a fixed length.
+impl<A: TableKey, B: TableKey> TableKey for (A, B) {
+    fn encode_key(&self) -> Vec<u8> {
+        let mut key = self.0.encode_key();
+        key.extend(self.1.encode_key());
+        key
+    }
+
+    fn decode_key(bytes: &[u8]) -> Option<Self> {
+        (1..=bytes.len()).find_map(|split| {
+            let (a, b) = bytes.split_at(split);
+            Some((A::decode_key(a)?, B::decode_key(b)?))
+        })
+    }
+}
+
+/// A typed view of a [`Column`], values are stored SSZ encoded.
+pub trait Table {
+    const COLUMN: Column;
+    type Key: TableKey;
+    type Value: Encode + Decode;
+}
+
+pub struct BlocksByRoot;
+
+impl Table for BlocksByRoot {
+    const COLUMN: Column = Column::BlocksByRoot;
+    type Key = B256;
+    type Value = SignedBeaconBlock;
+}
+
+/// Slot and parent of a stored block, so that ancestors can be walked without decoding whole
+/// blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct BlockSummary {
+    pub slot: u64,
+    pub parent_root: B256,
+}
+
+pub struct BlockSummaries;
+
+impl Table for BlockSummaries {
+    const COLUMN: Column = Column::BlockSummaries;
+    type Key = B256;
+    type Value = BlockSummary;
+}
+
+/// Canonical block root of every slot that has a block.
+pub struct BlockRootsBySlot;
+
+impl Table for BlockRootsBySlot {
+    const COLUMN: Column = Column::BlockRootsBySlot;
+    type Key = u64;
+    type Value = B256;
+}
+
+/// Full states, only written for snapshots.
+pub struct StatesByRoot;
+
+impl Table for StatesByRoot {
+    const COLUMN: Column = Column::StatesByRoot;
+    type Key = B256;
+    type Value = BeaconState;
+}
+
+/// Enough about a state to rebuild it from the nearest snapshot before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct StateSummary {
+    pub slot: u64,
+    pub latest_block_root: B256,
+    /// Whether the full state is in [`StatesByRoot`].
+    pub snapshot: bool,
+}
+
+pub struct StateSummaries;
+
+impl Table for StateSummaries {
+    const COLUMN: Column = Column::StateSummaries;
+    type Key = B256;
+    type Value = StateSummary;
+}
+
+/// Blob sidecars by block root and index.
+pub struct BlobSidecars;
+
+impl Table for BlobSidecars {
+    const COLUMN: Column = Column::BlobSidecars;
+    type Key = (B256, u64);
+    type Value = BlobSidecar;
+}
+
+/// Roots of blocks with stored blobs by slot, to find the blobs that left the availability
+/// window.
+pub struct BlobRootsBySlot;
+
+impl Table for BlobRootsBySlot {
+    const COLUMN: Column = Column::BlobRootsBySlot;
+    type Key = (u64, B256);
+    type Value = B256;
+}
+
+/// Latest fork choice snapshot, written periodically and on shutdown.
+pub struct ForkChoiceSnapshot;
+
+impl Table for ForkChoiceSnapshot {
+    const COLUMN: Column = Column::ForkChoice;
+    type Key = ();
+    type Value = PersistedForkChoice;
+}
+
+/// Operations pooled when the node last shut down.
+pub struct OperationPoolSnapshot;
+
+impl Table for OperationPoolSnapshot {
+    const COLUMN: Column = Column::OperationPool;
+    type Key = ();
+    type Value = PersistedOperationPool;
+}
+
+/// First slot of the hot database. Everything before it is finalized and lives in the cold
+/// database, the block and state at the split are in both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub struct Split {
+    pub slot: u64,
+    pub block_root: B256,
+    pub state_root: B256,
+}
+
+pub struct SplitPoint;
+
+impl Table for SplitPoint {
+    const COLUMN: Column = Column::Split;
+    type Key = ();
+    type Value = Split;
+}
@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+
+use alloy_primitives::B256;
+use tree_hash::TreeHash;
+
+use crate::{
+    block_store::BlockStore,
+    error::StoreError,
+    store::{Store, TypedBatch},
+    tables::{
+        BlockRootsBySlot, BlockSummaries, BlockSummary, BlocksByRoot, StateSummaries, StateSummary,
+        StatesByRoot,
+    },
+};
+
+/// Something wrong with the chain stored in a database, found by [`verify_chain`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Inconsistency {
+    #[error("slot {slot} of the canonical chain points at block {root} which is not stored")]
+    MissingBlock { slot: u64, root: B256 },
+    #[error("slot {slot} of the canonical chain points at block {root} of slot {block_slot}")]
+    WrongSlot {
+        slot: u64,
+        root: B256,
+        block_slot: u64,
+    },
+    #[error(
+        "parent {parent_root} of canonical block {root} at slot {slot} is not the previous \
+         canonical block {previous}"
+    )]
+    BrokenParentLink {
+        slot: u64,
+        root: B256,
+        parent_root: B256,
+        previous: B256,
+    },
+    #[error("summary of block {0} doesn't match the block")]
+    WrongBlockSummary(B256),
+    #[error("state stored as {state_root} has root {computed}")]
+    WrongStateRoot { state_root: B256, computed: B256 },
+    #[error("state {0} is stored in full but its summary doesn't say so")]
+    SnapshotWithoutSummary(B256),
+    #[error("summary of state {0} claims a snapshot that isn't stored")]
+    MissingSnapshot(B256),
+}
+
+impl Inconsistency {
+    /// Whether repairing it means rebuilding the slot index.
+    fn breaks_index(&self) -> bool {
+        matches!(
+            self,
+            Inconsistency::MissingBlock { .. }
+                | Inconsistency::WrongSlot { .. }
+                | Inconsistency::BrokenParentLink { .. }
+        )
+    }
+}
+
+/// Walks the canonical chain of `store` by slot, checking that every indexed block is stored at
+/// that slot with a matching summary and is the parent of the next one, then checks that every
+/// stored snapshot hashes to its root and agrees with the state summaries.
+pub fn verify_chain(store: &Store) -> Result<Vec<Inconsistency>, StoreError> {
+    let mut inconsistencies = vec![];
+
+    let mut chain = vec![];
+    store.iterate::<BlockRootsBySlot>(&0, |slot, root| {
+        chain.push((slot, root));
+        true
+    })?;
+    let mut previous = None;
+    for (slot, root) in chain {
+        let Some(block) = store.get::<BlocksByRoot>(&root)? else {
+            inconsistencies.push(Inconsistency::MissingBlock { slot, root });
+            previous = None;
+            continue;
+        };
+        let block = block.message;
+        if block.slot != slot {
+            inconsistencies.push(Inconsistency::WrongSlot {
+                slot,
+                root,
+                block_slot: block.slot,
+            });
+        }
+        let summary = BlockSummary {
+            slot: block.slot,
+            parent_root: block.parent_root,
+        };
+        if store.get::<BlockSummaries>(&root)? != Some(summary) {
+            inconsistencies.push(Inconsistency::WrongBlockSummary(root));
+        }
+        if let Some(previous) = previous {
+            if block.parent_root != previous {
+                inconsistencies.push(Inconsistency::BrokenParentLink {
+                    slot,
+                    root,
+                    parent_root: block.parent_root,
+                    previous,
+                });
+            }
+        }
+        previous = Some(root);
+    }
+
+    let mut snapshots = HashSet::new();
+    let mut valid_snapshots = vec![];
+    store.iterate::<StatesByRoot>(&B256::ZERO, |state_root, state| {
+        let computed = state.tree_hash_root();
+        if computed == state_root {
+            valid_snapshots.push(state_root);
+        } else {
+            inconsistencies.push(Inconsistency::WrongStateRoot {
+                state_root,
+                computed,
+            });
+        }
+        snapshots.insert(state_root);
+        true
+    })?;
+    for state_root in &valid_snapshots {
+        if !store
+            .get::<StateSummaries>(state_root)?
+            .is_some_and(|summary| summary.snapshot)
+        {
+            inconsistencies.push(Inconsistency::SnapshotWithoutSummary(*state_root));
+        }
+    }
+    store.iterate::<StateSummaries>(&B256::ZERO, |state_root, summary| {
+        if summary.snapshot && !snapshots.contains(&state_root) {
+            inconsistencies.push(Inconsistency::MissingSnapshot(state_root));
+        }
+        true
+    })?;
+
+    Ok(inconsistencies)
+}
+
+/// Fixes what [`verify_chain`] found:
+///
+/// - block summaries are rewritten from their blocks,
+/// - snapshots with a wrong root are deleted with their summary, missing ones are unmarked so
+///   that their states are rebuilt from an older snapshot,
+/// - a broken slot index is rebuilt from the ancestors of its newest stored block, blocks no
+///   longer connected to it stay stored but leave the canonical chain.
+pub fn repair_chain(store: &Store, inconsistencies: &[Inconsistency]) -> Result<(), StoreError> {
+    let blocks = BlockStore::new(store.clone());
+
+    // Summaries first, rebuilding the index walks them.
+    let mut batch = TypedBatch::new();
+    for inconsistency in inconsistencies {
+        match inconsistency {
+            Inconsistency::WrongBlockSummary(root) => {
+                if let Some(block) = blocks.get_block(root)? {
+                    blocks.stage_block(&mut batch, &block);
+                }
+            }
+            Inconsistency::WrongStateRoot { state_root, .. } => {
+                batch.delete::<StatesByRoot>(state_root);
+                batch.delete::<StateSummaries>(state_root);
+            }
+            Inconsistency::SnapshotWithoutSummary(state_root) => {
+                if let Some(state) = store.get::<StatesByRoot>(state_root)? {
+                    batch.put::<StateSummaries>(
+                        state_root,
+                        &StateSummary {
+                            slot: state.slot,
+                            latest_block_root: state.latest_block_root(*state_root),
+                            snapshot: true,
+                        },
+                    );
+                }
+            }
+            Inconsistency::MissingSnapshot(state_root) => {
+                if let Some(summary) = store.get::<StateSummaries>(state_root)? {
+                    batch.put::<StateSummaries>(
+                        state_root,
+                        &StateSummary {
+                            snapshot: false,
+                            ..summary
+                        },
+                    );
+                }
+            }
+            Inconsistency::MissingBlock { .. }
+            | Inconsistency::WrongSlot { .. }
+            | Inconsistency::BrokenParentLink { .. } => {}
+        }
+    }
+    if !batch.is_empty() {
+        store.write(batch)?;
+    }
+
+    if inconsistencies.iter().any(Inconsistency::breaks_index) {
+        rebuild_index(store, &blocks)?;
+    }
+    Ok(())
+}
+
+/// Points the slot index at the chain ending in the newest indexed block that is stored at its
+/// slot.
+fn rebuild_index(store: &Store, blocks: &BlockStore) -> Result<(), StoreError> {
+    let mut chain = vec![];
+    store.iterate::<BlockRootsBySlot>(&0, |slot, root| {
+        chain.push((slot, root));
+        true
+    })?;
+
+    let mut head = None;
+    for (slot, root) in chain.iter().rev() {
+        if blocks
+            .get_block_summary(root)?
+            .is_some_and(|summary| summary.slot == *slot)
+        {
+            head = Some(*root);
+            break;
+        }
+    }
+
+    let mut batch = TypedBatch::new();
+    for (slot, _) in &chain {
+        batch.delete::<BlockRootsBySlot>(slot);
+    }
+    if let Some(head) = head {
+        for ancestor in blocks.ancestors(head) {
+            let (root, slot) = ancestor?;
+            batch.put::<BlockRootsBySlot>(&slot, &root);
+        }
+    }
+    if batch.is_empty() {
+        return Ok(());
+    }
+    store.write(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        beacon_state::BeaconState,
+    };
+
+    use super::*;
+    use crate::{memory_store::MemoryStore, state_store::StateStore};
+
+    fn put_block(blocks: &BlockStore, slot: u64, parent_root: B256) -> B256 {
+        blocks
+            .put_block(&SignedBeaconBlock {
+                message: BeaconBlock {
+                    slot,
+                    parent_root,
+                    ..BeaconBlock::default()
+                },
+                ..SignedBeaconBlock::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_and_repair() {
+        let store = Store::new(Arc::new(MemoryStore::new()));
+        let blocks = BlockStore::new(store.clone());
+        let genesis = put_block(&blocks, 0, B256::ZERO);
+        let a = put_block(&blocks, 1, genesis);
+        let b = put_block(&blocks, 2, a);
+        let fork = put_block(&blocks, 2, genesis);
+        blocks.set_canonical_head(b).unwrap();
+
+        let state = BeaconState::default();
+        let state_root = state.tree_hash_root();
+        StateStore::new(store.clone(), 1)
+            .put_snapshot(state_root, &state)
+            .unwrap();
+        assert_eq!(verify_chain(&store).unwrap(), vec![]);
+
+        // A crash left the index pointing at a fork block and a snapshot under a wrong root.
+        store.put::<BlockRootsBySlot>(&2, &fork).unwrap();
+        store.put::<BlockRootsBySlot>(&3, &B256::ZERO).unwrap();
+        store
+            .put::<BlockSummaries>(
+                &a,
+                &BlockSummary {
+                    slot: 1,
+                    parent_root: B256::ZERO,
+                },
+            )
+            .unwrap();
+        let wrong_root = B256::repeat_byte(9);
+        store.put::<StatesByRoot>(&wrong_root, &state).unwrap();
+
+        let inconsistencies = verify_chain(&store).unwrap();
+        assert_eq!(
+            inconsistencies,
+            vec![
+                Inconsistency::WrongBlockSummary(a),
+                Inconsistency::BrokenParentLink {
+                    slot: 2,
+                    root: fork,
+                    parent_root: genesis,
+                    previous: a,
+                },
+                Inconsistency::MissingBlock {
+                    slot: 3,
+                    root: B256::ZERO,
+                },
+                Inconsistency::WrongStateRoot {
+                    state_root: wrong_root,
+                    computed: state_root,
+                },
+            ]
+        );
+
+        repair_chain(&store, &inconsistencies).unwrap();
+        assert_eq!(verify_chain(&store).unwrap(), vec![]);
+        // The fork block is the newest stored one, the index follows its chain.
+        assert_eq!(blocks.get_block_root_at_slot(1).unwrap(), None);
+        assert_eq!(blocks.get_block_root_at_slot(2).unwrap(), Some(fork));
+        assert_eq!(blocks.get_block_root_at_slot(3).unwrap(), None);
+        assert_eq!(store.get::<StatesByRoot>(&wrong_root).unwrap(), None);
+    }
+}
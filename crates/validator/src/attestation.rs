@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use ream_consensus::{
+    aggregate_and_proof::is_aggregator, attestation::Attestation,
+    attestation_data::AttestationData, subnet::SubnetId,
+};
+use ream_fork_choice::attestation_data_check::verify_attestation_epochs;
+use ream_rpc::handlers::{attestation::BeaconCommitteeSubscription, duties::AttesterDuty};
+use ssz_types::BitList;
+use tracing::{debug, info, warn};
+
+use crate::{
+    beacon_node::BeaconNodeClient, error::ValidatorError, validator_store::ValidatorStore,
+};
+
+/// Asks the beacon node to join the subnets of the committees of `duties`, telling it which
+/// of them the validators aggregate.
+pub async fn subscribe_to_committees(
+    beacon_node: &BeaconNodeClient,
+    store: &ValidatorStore,
+    duties: &[AttesterDuty],
+) -> Result<(), ValidatorError> {
+    let mut subscriptions = vec![];
+    for duty in duties {
+        let selection_proof = store.sign_selection_proof(&duty.pubkey, duty.slot).await?;
+        subscriptions.push(BeaconCommitteeSubscription {
+            validator_index: duty.validator_index,
+            committee_index: duty.committee_index,
+            committees_at_slot: duty.committees_at_slot,
+            slot: duty.slot,
+            is_aggregator: is_aggregator(duty.committee_length, &selection_proof),
+        });
+    }
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+    beacon_node
+        .subscribe_to_beacon_committees(&subscriptions)
+        .await
+}
+
+/// Signs and submits the attestations of `duties`, all for the same slot, returning the duties
+/// attested with the data of each. Attestation data is fetched once per committee, a
+/// committee whose data can't be fetched or is refused is skipped like a failed signature.
+pub async fn attest(
+    beacon_node: &BeaconNodeClient,
+    store: &ValidatorStore,
+    slot: u64,
+    duties: &[AttesterDuty],
+) -> Result<Vec<(AttesterDuty, AttestationData)>, ValidatorError> {
+    let mut data_by_committee: HashMap<u64, Option<AttestationData>> = HashMap::new();
+    let mut attestations = vec![];
+    let mut attested = vec![];
+    for duty in duties {
+        let data = match data_by_committee.get(&duty.committee_index) {
+            Some(data) => *data,
+            None => {
+                let data =
+                    match fetch_attestation_data(beacon_node, slot, duty.committee_index).await {
+                        Ok(data) => Some(data),
+                        Err(err) => {
+                            warn!(
+                                "Failed to get the attestation data of committee {}: {err}",
+                                duty.committee_index
+                            );
+                            None
+                        }
+                    };
+                data_by_committee.insert(duty.committee_index, data);
+                data
+            }
+        };
+        let Some(data) = data else {
+            continue;
+        };
+        match sign_attestation(store, duty, data).await {
+            Ok(attestation) => {
+                let subnet = SubnetId::compute_for_attestation(
+                    duty.committees_at_slot,
+                    slot,
+                    duty.committee_index,
+                );
+                debug!(
+                    "Attestation of validator {} goes to subnet {subnet}",
+                    duty.validator_index
+                );
+                attestations.push(attestation);
+                attested.push((duty.clone(), data));
+            }
+            Err(err) => warn!(
+                "Failed to sign the attestation of validator {}: {err}",
+                duty.validator_index
+            ),
+        }
+    }
+    if attestations.is_empty() {
+        return Ok(attested);
+    }
+    beacon_node.submit_attestations(&attestations).await?;
+    info!(
+        "Published {} attestations for slot {slot}",
+        attestations.len()
+    );
+    Ok(attested)
+}
+
+/// The attestation data of `committee_index` at `slot`, checked against what was asked for.
+async fn fetch_attestation_data(
+    beacon_node: &BeaconNodeClient,
+    slot: u64,
+    committee_index: u64,
+) -> Result<AttestationData, ValidatorError> {
+    let data = beacon_node.attestation_data(slot, committee_index).await?;
+    check_attestation_data(&data, slot, committee_index)?;
+    Ok(data)
+}
+
+/// Refuses attestation data the beacon node returned for another slot or committee, or with
+/// checkpoints that can't be right. The roots are left to the beacon node, the validator
+/// client has no fork choice to check them against.
+fn check_attestation_data(
+    data: &AttestationData,
+    slot: u64,
+    committee_index: u64,
+) -> Result<(), ValidatorError> {
+    if data.slot != slot || data.index != committee_index {
+        return Err(ValidatorError::InvalidResponse(format!(
+            "attestation data for slot {} and committee {}, asked for slot {slot} and \
+             committee {committee_index}",
+            data.slot, data.index
+        )));
+    }
+    verify_attestation_epochs(data)
+        .map_err(|err| ValidatorError::InvalidResponse(format!("attestation data: {err}")))
+}
+
+/// An attestation to `data` by the validator of `duty` alone.
+pub async fn sign_attestation(
+    store: &ValidatorStore,
+    duty: &AttesterDuty,
+    data: AttestationData,
+) -> Result<Attestation, ValidatorError> {
+    let mut aggregation_bits = BitList::with_capacity(duty.committee_length as usize)
+        .map_err(|err| ValidatorError::InvalidResponse(format!("committee length: {err:?}")))?;
+    aggregation_bits
+        .set(duty.validator_committee_index as usize, true)
+        .map_err(|err| ValidatorError::InvalidResponse(format!("committee position: {err:?}")))?;
+    let signature = store.sign_attestation_data(&duty.pubkey, &data).await?;
+    Ok(Attestation {
+        aggregation_bits,
+        data,
+        signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use alloy_primitives::B256;
+    use axum::{
+        extract::{Query, State},
+        routing::{get, post},
+        Json, Router,
+    };
+    use ream_bls::PrivateKey;
+    use ream_consensus::{checkpoint::Checkpoint, fork_schedule::ForkSchedule};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+
+    /// A beacon node that returns the data of committee 1 for the wrong slot, keeping the
+    /// attestations submitted to it in `submitted`.
+    async fn mock_beacon_node(submitted: Arc<Mutex<Vec<Value>>>) -> Url {
+        let attestation_data = |Query(query): Query<HashMap<String, u64>>| async move {
+            let committee_index = query["committee_index"];
+            let slot = match committee_index {
+                1 => query["slot"] + 1,
+                _ => query["slot"],
+            };
+            Json(json!({
+                "data": AttestationData {
+                    slot,
+                    index: committee_index,
+                    ..AttestationData::default()
+                },
+            }))
+        };
+        let submit = |State(submitted): State<Arc<Mutex<Vec<Value>>>>,
+                      Json(attestations): Json<Vec<Value>>| async move {
+            submitted.lock().unwrap().extend(attestations);
+        };
+        let router = Router::new()
+            .route("/eth/v1/validator/attestation_data", get(attestation_data))
+            .route("/eth/v1/beacon/pool/attestations", post(submit))
+            .with_state(submitted);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_attest_skips_refused_committee() {
+        let keys = [
+            PrivateKey::key_gen(&[1; 32]).unwrap(),
+            PrivateKey::key_gen(&[2; 32]).unwrap(),
+        ];
+        let duties = keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| AttesterDuty {
+                pubkey: key.public_key(),
+                validator_index: index as u64,
+                committee_index: index as u64,
+                committee_length: 1,
+                committees_at_slot: 2,
+                validator_committee_index: 0,
+                slot: 0,
+            })
+            .collect::<Vec<_>>();
+        let store = ValidatorStore::new(keys, ForkSchedule::mainnet(), B256::ZERO);
+        let submitted = Arc::new(Mutex::new(vec![]));
+        let beacon_node = BeaconNodeClient::new(mock_beacon_node(submitted.clone()).await);
+
+        let attested = attest(&beacon_node, &store, 0, &duties).await.unwrap();
+        assert_eq!(attested.len(), 1);
+        assert_eq!(attested[0].0.committee_index, 0);
+        let submitted = submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0]["data"]["index"], "0");
+    }
+
+    #[tokio::test]
+    async fn test_sign_attestation() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let duty = AttesterDuty {
+            pubkey: key.public_key(),
+            validator_index: 0,
+            committee_index: 0,
+            committee_length: 4,
+            committees_at_slot: 1,
+            validator_committee_index: 2,
+            slot: 1,
+        };
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::ZERO);
+        let attestation = sign_attestation(&store, &duty, AttestationData::default())
+            .await
+            .unwrap();
+        assert_eq!(attestation.aggregation_bits.len(), 4);
+        assert_eq!(
+            attestation.aggregation_bits.iter().collect::<Vec<_>>(),
+            [false, false, true, false]
+        );
+
+        let outside = AttesterDuty {
+            validator_committee_index: 4,
+            ..duty
+        };
+        assert!(
+            sign_attestation(&store, &outside, AttestationData::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_check_attestation_data() {
+        let data = AttestationData {
+            slot: 40,
+            index: 1,
+            target: Checkpoint {
+                epoch: 1,
+                root: B256::repeat_byte(1),
+            },
+            ..AttestationData::default()
+        };
+        check_attestation_data(&data, 40, 1).unwrap();
+        assert!(check_attestation_data(&data, 41, 1).is_err());
+        assert!(check_attestation_data(&data, 40, 0).is_err());
+
+        let wrong_target = AttestationData {
+            target: Checkpoint::default(),
+            ..data
+        };
+        assert!(check_attestation_data(&wrong_target, 40, 1).is_err());
+    }
+}
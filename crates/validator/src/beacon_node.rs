@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use alloy_primitives::B256;
+use ream_builder::types::SignedValidatorRegistrationV1;
+use ream_consensus::{
+    attestation::Attestation,
+    attestation_data::AttestationData,
+    beacon_block::SignedBeaconBlock,
+    bls::{BLSPubkey, BLSSignature},
+    constants::SECONDS_PER_SLOT,
+    fork_schedule::ForkName,
+};
+use ream_rpc::{
+    context::GenesisInfo,
+    handlers::{
+        attestation::BeaconCommitteeSubscription,
+        block::CONSENSUS_VERSION_HEADER,
+        duties::{AttesterDuty, ProposerDuty, ValidatorIndices},
+        node::SyncingStatus,
+        preparation::ProposerPreparation,
+        production::{BlockContents, ProduceBlockResponse},
+        validator::ValidatorData,
+    },
+    response::{DataResponse, DutiesResponse, VersionedResponse},
+};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use url::Url;
+
+use crate::error::ValidatorError;
+
+/// Timeout of every beacon node call, a duty is worthless once its slot is over.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(SECONDS_PER_SLOT);
+
+#[derive(Debug, Deserialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+/// Client of the beacon-APIs of the beacon node the validators follow.
+#[derive(Debug, Clone)]
+pub struct BeaconNodeClient {
+    http: reqwest::Client,
+    endpoint: Url,
+}
+
+impl BeaconNodeClient {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint,
+        }
+    }
+
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// `GET /eth/v1/beacon/genesis`
+    pub async fn genesis(&self) -> Result<GenesisInfo, ValidatorError> {
+        self.get_data("/eth/v1/beacon/genesis").await
+    }
+
+    /// `GET /eth/v1/node/syncing`
+    pub async fn syncing(&self) -> Result<SyncingStatus, ValidatorError> {
+        self.get_data("/eth/v1/node/syncing").await
+    }
+
+    /// `POST /eth/v1/beacon/states/head/validators`, the validators of `pubkeys` the head state
+    /// knows about.
+    pub async fn validators(
+        &self,
+        pubkeys: &[BLSPubkey],
+    ) -> Result<Vec<ValidatorData>, ValidatorError> {
+        let response = self
+            .send(
+                self.http
+                    .post(self.url("/eth/v1/beacon/states/head/validators"))
+                    .json(&json!({ "ids": pubkeys })),
+            )
+            .await?;
+        Ok(Self::decode::<DataResponse<_>>(response).await?.data)
+    }
+
+    /// `GET /eth/v1/validator/duties/proposer/{epoch}`
+    pub async fn proposer_duties(
+        &self,
+        epoch: u64,
+    ) -> Result<DutiesResponse<Vec<ProposerDuty>>, ValidatorError> {
+        let response = self
+            .send(
+                self.http
+                    .get(self.url(&format!("/eth/v1/validator/duties/proposer/{epoch}"))),
+            )
+            .await?;
+        Self::decode(response).await
+    }
+
+    /// `POST /eth/v1/validator/duties/attester/{epoch}`
+    pub async fn attester_duties(
+        &self,
+        epoch: u64,
+        indices: &[u64],
+    ) -> Result<DutiesResponse<Vec<AttesterDuty>>, ValidatorError> {
+        let response = self
+            .send(
+                self.http
+                    .post(self.url(&format!("/eth/v1/validator/duties/attester/{epoch}")))
+                    .json(&ValidatorIndices(indices.to_vec())),
+            )
+            .await?;
+        Self::decode(response).await
+    }
+
+    /// `GET /eth/v1/validator/attestation_data`
+    pub async fn attestation_data(
+        &self,
+        slot: u64,
+        committee_index: u64,
+    ) -> Result<AttestationData, ValidatorError> {
+        let response = self
+            .send(
+                self.http
+                    .get(self.url("/eth/v1/validator/attestation_data"))
+                    .query(&[("slot", slot), ("committee_index", committee_index)]),
+            )
+            .await?;
+        Ok(Self::decode::<DataResponse<_>>(response).await?.data)
+    }
+
+    /// `POST /eth/v1/beacon/pool/attestations`
+    pub async fn submit_attestations(
+        &self,
+        attestations: &[Attestation],
+    ) -> Result<(), ValidatorError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v1/beacon/pool/attestations"))
+                .json(attestations),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `POST /eth/v1/validator/beacon_committee_subscriptions`, asks the beacon node to join
+    /// the subnets of upcoming committees.
+    pub async fn subscribe_to_beacon_committees(
+        &self,
+        subscriptions: &[BeaconCommitteeSubscription],
+    ) -> Result<(), ValidatorError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v1/validator/beacon_committee_subscriptions"))
+                .json(subscriptions),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `POST /eth/v1/validator/prepare_beacon_proposer`, where the beacon node pays the fees
+    /// of the blocks it builds for the validators.
+    pub async fn prepare_beacon_proposer(
+        &self,
+        preparations: &[ProposerPreparation],
+    ) -> Result<(), ValidatorError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v1/validator/prepare_beacon_proposer"))
+                .json(preparations),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `POST /eth/v1/validator/register_validator`, the registrations the beacon node hands to
+    /// its builder.
+    pub async fn register_validators(
+        &self,
+        registrations: &[SignedValidatorRegistrationV1],
+    ) -> Result<(), ValidatorError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v1/validator/register_validator"))
+                .json(registrations),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// `GET /eth/v2/beacon/blocks/{slot}`, `None` if the slot is empty.
+    pub async fn block_at_slot(
+        &self,
+        slot: u64,
+    ) -> Result<Option<SignedBeaconBlock>, ValidatorError> {
+        let path = format!("/eth/v2/beacon/blocks/{slot}");
+        match self.send(self.http.get(self.url(&path))).await {
+            Ok(response) => Ok(Some(
+                Self::decode::<VersionedResponse<_>>(response).await?.data,
+            )),
+            Err(ValidatorError::BeaconNode { status, .. })
+                if status == StatusCode::NOT_FOUND.as_u16() =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// `GET /eth/v3/validator/blocks/{slot}`, an unsigned block for the proposer of `slot`.
+    pub async fn produce_block(
+        &self,
+        slot: u64,
+        randao_reveal: BLSSignature,
+        graffiti: B256,
+    ) -> Result<ProduceBlockResponse<BlockContents>, ValidatorError> {
+        let response = self
+            .send(
+                self.http
+                    .get(self.url(&format!("/eth/v3/validator/blocks/{slot}")))
+                    .query(&[
+                        ("randao_reveal", randao_reveal.to_string()),
+                        ("graffiti", graffiti.to_string()),
+                    ]),
+            )
+            .await?;
+        Self::decode(response).await
+    }
+
+    /// `POST /eth/v2/beacon/blocks`
+    pub async fn publish_block(&self, block: &SignedBeaconBlock) -> Result<(), ValidatorError> {
+        self.send(
+            self.http
+                .post(self.url("/eth/v2/beacon/blocks"))
+                .header(CONSENSUS_VERSION_HEADER, ForkName::Deneb.to_string())
+                .json(block),
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        url.set_path(path);
+        url
+    }
+
+    async fn get_data<T: DeserializeOwned>(&self, path: &str) -> Result<T, ValidatorError> {
+        let response = self.send(self.http.get(self.url(path))).await?;
+        Ok(Self::decode::<DataResponse<T>>(response).await?.data)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ValidatorError> {
+        let response = request.timeout(REQUEST_TIMEOUT).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ErrorMessage>(&body)
+            .map(|error| error.message)
+            .unwrap_or(body);
+        Err(ValidatorError::BeaconNode {
+            status: status.as_u16(),
+            message,
+        })
+    }
+
+    async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, ValidatorError> {
+        let body = response.bytes().await?;
+        serde_json::from_slice(&body)
+            .map_err(|err| ValidatorError::InvalidResponse(err.to_string()))
+    }
+}
@@ -0,0 +1,148 @@
+use alloy_primitives::B256;
+use ream_consensus::misc::compute_epoch_at_slot;
+use ream_rpc::handlers::duties::ProposerDuty;
+use tracing::info;
+
+use crate::{
+    beacon_node::BeaconNodeClient, error::ValidatorError, validator_store::ValidatorStore,
+};
+
+/// Proposes the block of `duty`: reveals the randao contribution, has the beacon node produce
+/// the block with `graffiti`, signs it and publishes it. A block that isn't the one asked for
+/// is refused unsigned.
+pub async fn propose(
+    beacon_node: &BeaconNodeClient,
+    store: &ValidatorStore,
+    duty: &ProposerDuty,
+    graffiti: B256,
+) -> Result<(), ValidatorError> {
+    let randao_reveal = store
+        .sign_randao_reveal(&duty.pubkey, compute_epoch_at_slot(duty.slot))
+        .await?;
+    let produced = beacon_node
+        .produce_block(duty.slot, randao_reveal, graffiti)
+        .await?;
+    let block = produced.data.block;
+    if block.slot != duty.slot || block.proposer_index != duty.validator_index {
+        return Err(ValidatorError::InvalidResponse(format!(
+            "asked for the block of validator {} at slot {}, got that of validator {} at slot {}",
+            duty.validator_index, duty.slot, block.proposer_index, block.slot
+        )));
+    }
+    if block.body.randao_reveal != randao_reveal || block.body.graffiti != graffiti {
+        return Err(ValidatorError::InvalidResponse(format!(
+            "the block of slot {} doesn't carry the randao reveal and graffiti asked for",
+            duty.slot
+        )));
+    }
+    let signed = store.sign_block(&duty.pubkey, block).await?;
+    beacon_node.publish_block(&signed).await?;
+    info!(
+        "Published the block of validator {} at slot {}",
+        duty.validator_index, duty.slot
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use alloy_primitives::U256;
+    use axum::{
+        extract::{Path, Query},
+        http::HeaderMap,
+        routing::{get, post},
+        Json, Router,
+    };
+    use ream_bls::PrivateKey;
+    use ream_consensus::{
+        beacon_block::{BeaconBlock, SignedBeaconBlock},
+        fork_schedule::{ForkName, ForkSchedule},
+    };
+    use ream_rpc::handlers::{
+        block::CONSENSUS_VERSION_HEADER,
+        production::{BlockContents, ProduceBlockResponse},
+    };
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::graffiti::Graffiti;
+
+    type Params = HashMap<String, String>;
+
+    /// A beacon node producing blocks for validator 3 with the requested randao reveal and
+    /// graffiti, keeping the blocks published to it.
+    async fn mock_beacon_node(published: Arc<Mutex<Vec<SignedBeaconBlock>>>) -> Url {
+        let produce = |Path(slot): Path<u64>, Query(query): Query<Params>| async move {
+            let mut block = BeaconBlock {
+                slot,
+                proposer_index: 3,
+                ..BeaconBlock::default()
+            };
+            block.body.randao_reveal = query["randao_reveal"].parse().unwrap();
+            block.body.graffiti = query["graffiti"].parse().unwrap();
+            Json(ProduceBlockResponse {
+                version: ForkName::Deneb,
+                execution_payload_blinded: false,
+                execution_payload_value: U256::ZERO,
+                consensus_block_value: U256::ZERO,
+                data: BlockContents {
+                    block,
+                    kzg_proofs: vec![],
+                    blobs: vec![],
+                },
+            })
+        };
+        let publish = move |headers: HeaderMap, Json(block): Json<SignedBeaconBlock>| async move {
+            assert_eq!(headers[CONSENSUS_VERSION_HEADER], "deneb");
+            published.lock().unwrap().push(block);
+        };
+        let router = Router::new()
+            .route("/eth/v3/validator/blocks/{slot}", get(produce))
+            .route("/eth/v2/beacon/blocks", post(publish));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_propose() {
+        let published = Arc::new(Mutex::new(vec![]));
+        let beacon_node = BeaconNodeClient::new(mock_beacon_node(published.clone()).await);
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let duty = ProposerDuty {
+            pubkey: key.public_key(),
+            validator_index: 3,
+            slot: 40,
+        };
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::ZERO);
+        let graffiti: B256 = "ream".parse::<Graffiti>().unwrap().into();
+
+        propose(&beacon_node, &store, &duty, graffiti)
+            .await
+            .unwrap();
+        let block = published.lock().unwrap().pop().unwrap();
+        assert_eq!(block.message.slot, 40);
+        assert_eq!(block.message.body.graffiti, graffiti);
+        assert_eq!(
+            block.message.body.randao_reveal,
+            store.sign_randao_reveal(&duty.pubkey, 1).await.unwrap()
+        );
+
+        // The beacon node produces blocks for validator 3 only.
+        let other = ProposerDuty {
+            validator_index: 4,
+            ..duty
+        };
+        assert!(matches!(
+            propose(&beacon_node, &store, &other, graffiti).await,
+            Err(ValidatorError::InvalidResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+//! Deposits of new validators, in the `deposit_data-*.json` format of the staking launchpad.
+
+use alloy_primitives::{aliases::B32, hex, Address, B256};
+use ream_bls::PrivateKey;
+use ream_consensus::{
+    bls::BLSPubkey,
+    constants::{
+        BLS_WITHDRAWAL_PREFIX, DOMAIN_DEPOSIT, ETH1_ADDRESS_WITHDRAWAL_PREFIX, MIN_DEPOSIT_AMOUNT,
+    },
+    deposit::{DepositData, DepositMessage},
+    misc::{compute_domain, compute_signing_root},
+};
+use serde::{Deserialize, Serialize};
+use tree_hash::TreeHash;
+
+use crate::error::ValidatorError;
+
+/// Withdrawal credentials committing to the BLS key `pubkey`, to be changed to an address later.
+pub fn bls_withdrawal_credentials(pubkey: &BLSPubkey) -> B256 {
+    let mut credentials = B256::from_slice(&ethereum_hashing::hash(pubkey.as_slice()));
+    credentials[0] = BLS_WITHDRAWAL_PREFIX;
+    credentials
+}
+
+/// Withdrawal credentials paying out to `address`.
+pub fn eth1_withdrawal_credentials(address: Address) -> B256 {
+    let mut credentials = B256::ZERO;
+    credentials[0] = ETH1_ADDRESS_WITHDRAWAL_PREFIX;
+    credentials[12..].copy_from_slice(address.as_slice());
+    credentials
+}
+
+/// Refuses a deposit of `amount` gwei the deposit contract would revert, the contract takes at
+/// least [`MIN_DEPOSIT_AMOUNT`] in whole gwei, which an amount counted in gwei always is.
+pub fn check_deposit_amount(amount: u64) -> Result<(), ValidatorError> {
+    if amount < MIN_DEPOSIT_AMOUNT {
+        return Err(ValidatorError::DepositAmount(amount));
+    }
+    Ok(())
+}
+
+/// The deposit of `amount` gwei for `key`, signed over the genesis fork version of the network
+/// as deposits are valid whatever the fork.
+pub fn sign_deposit(
+    key: &PrivateKey,
+    withdrawal_credentials: B256,
+    amount: u64,
+    genesis_fork_version: B32,
+) -> DepositData {
+    let message = DepositMessage {
+        pubkey: key.public_key(),
+        withdrawal_credentials,
+        amount,
+    };
+    let domain = compute_domain(DOMAIN_DEPOSIT, genesis_fork_version, B256::ZERO);
+    let signature = key.sign(compute_signing_root(&message, domain).as_slice());
+    DepositData {
+        pubkey: message.pubkey,
+        withdrawal_credentials,
+        amount,
+        signature,
+    }
+}
+
+/// A deposit as the launchpad takes it, bytes in hex without the `0x` prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchpadDeposit {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub amount: u64,
+    pub signature: String,
+    pub deposit_message_root: String,
+    pub deposit_data_root: String,
+    pub fork_version: String,
+    pub network_name: String,
+    pub deposit_cli_version: String,
+}
+
+impl LaunchpadDeposit {
+    pub fn new(deposit: &DepositData, genesis_fork_version: B32, network_name: &str) -> Self {
+        let message = DepositMessage {
+            pubkey: deposit.pubkey,
+            withdrawal_credentials: deposit.withdrawal_credentials,
+            amount: deposit.amount,
+        };
+        Self {
+            pubkey: hex::encode(deposit.pubkey),
+            withdrawal_credentials: hex::encode(deposit.withdrawal_credentials),
+            amount: deposit.amount,
+            signature: hex::encode(deposit.signature),
+            deposit_message_root: hex::encode(message.tree_hash_root()),
+            deposit_data_root: hex::encode(deposit.tree_hash_root()),
+            fork_version: hex::encode(genesis_fork_version),
+            network_name: network_name.to_string(),
+            deposit_cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_consensus::{bls::BLSSignature, constants::MAX_EFFECTIVE_BALANCE};
+
+    use super::*;
+
+    #[test]
+    fn test_sign_deposit() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let withdrawal_key = PrivateKey::key_gen(&[2; 32]).unwrap();
+        let credentials = bls_withdrawal_credentials(&withdrawal_key.public_key());
+        assert_eq!(credentials[0], 0);
+        assert_eq!(
+            credentials[1..],
+            ethereum_hashing::hash(withdrawal_key.public_key().as_slice())[1..]
+        );
+
+        let fork_version = B32::new([0x01, 0x01, 0x70, 0x00]);
+        let deposit = sign_deposit(&key, credentials, MAX_EFFECTIVE_BALANCE, fork_version);
+        let message = DepositMessage {
+            pubkey: key.public_key(),
+            withdrawal_credentials: credentials,
+            amount: MAX_EFFECTIVE_BALANCE,
+        };
+        let domain = compute_domain(DOMAIN_DEPOSIT, fork_version, B256::ZERO);
+        let signing_root = compute_signing_root(&message, domain);
+        assert!(
+            ream_bls::verify(&deposit.pubkey, signing_root.as_slice(), &deposit.signature).unwrap()
+        );
+
+        let launchpad = LaunchpadDeposit::new(&deposit, fork_version, "holesky");
+        assert_eq!(launchpad.pubkey, hex::encode(key.public_key()));
+        assert_eq!(launchpad.amount, 32_000_000_000);
+        assert_eq!(launchpad.fork_version, "01017000");
+        assert_eq!(
+            launchpad.deposit_data_root,
+            hex::encode(deposit.tree_hash_root())
+        );
+        assert_ne!(launchpad.deposit_message_root, launchpad.deposit_data_root);
+    }
+
+    #[test]
+    fn test_deposit_roots() {
+        let deposit = DepositData {
+            pubkey: BLSPubkey::repeat_byte(0xaa),
+            withdrawal_credentials: eth1_withdrawal_credentials(Address::repeat_byte(0xbb)),
+            amount: MAX_EFFECTIVE_BALANCE,
+            signature: BLSSignature::repeat_byte(0xcc),
+        };
+        let launchpad = LaunchpadDeposit::new(&deposit, B32::ZERO, "mainnet");
+        assert_eq!(
+            launchpad.deposit_message_root,
+            "5272212ac83ce372758180a43163cb6bc5e9e9889debb48ab750b551070ff78b"
+        );
+        assert_eq!(
+            launchpad.deposit_data_root,
+            "ce00baa30f0bf55a9608f2ffd362b81feb31a51341e11e40c19dfb72b9b8e58a"
+        );
+    }
+
+    #[test]
+    fn test_check_deposit_amount() {
+        check_deposit_amount(MIN_DEPOSIT_AMOUNT).unwrap();
+        check_deposit_amount(MAX_EFFECTIVE_BALANCE).unwrap();
+        assert!(matches!(
+            check_deposit_amount(MIN_DEPOSIT_AMOUNT - 1),
+            Err(ValidatorError::DepositAmount(_))
+        ));
+    }
+
+    #[test]
+    fn test_eth1_withdrawal_credentials() {
+        let credentials = eth1_withdrawal_credentials(Address::repeat_byte(0xaa));
+        assert_eq!(
+            hex::encode(credentials),
+            "010000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        );
+    }
+}
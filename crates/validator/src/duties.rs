@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ream_consensus::bls::BLSPubkey;
+use ream_rpc::handlers::duties::{AttesterDuty, ProposerDuty};
+use tracing::{debug, warn};
+
+use crate::{
+    attestation::subscribe_to_committees, beacon_node::BeaconNodeClient, error::ValidatorError,
+    validator_store::ValidatorStore,
+};
+
+/// Epochs of duties kept, those of the previous epoch are still needed early in an epoch.
+const RETAINED_EPOCHS: u64 = 2;
+
+/// Duties of the client's validators, fetched from the beacon node one epoch ahead.
+#[derive(Debug, Default)]
+pub struct DutiesService {
+    /// Validator indices, known once the chain has seen the deposits.
+    indices: HashMap<BLSPubkey, u64>,
+    attesters: BTreeMap<u64, Vec<AttesterDuty>>,
+    proposers: BTreeMap<u64, Vec<ProposerDuty>>,
+}
+
+impl DutiesService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks up the indices of validators new to the chain, then fetches the proposers of
+    /// `epoch` and the attesters of `epoch` and the next one, subscribing to the committees of
+    /// the attesters so the beacon node is on their subnets in time. Duties are fetched anew
+    /// every call as a reorg may have changed them.
+    pub async fn update(
+        &mut self,
+        beacon_node: &BeaconNodeClient,
+        store: &ValidatorStore,
+        epoch: u64,
+    ) -> Result<(), ValidatorError> {
+        let unknown: Vec<BLSPubkey> = store
+            .pubkeys()
+            .into_iter()
+            .filter(|pubkey| !self.indices.contains_key(pubkey))
+            .collect();
+        if !unknown.is_empty() {
+            for validator in beacon_node.validators(&unknown).await? {
+                if store.has_validator(&validator.validator.pubkey) {
+                    self.indices
+                        .insert(validator.validator.pubkey, validator.index);
+                }
+            }
+        }
+        let indices: Vec<u64> = self.indices.values().copied().collect();
+        if indices.is_empty() {
+            debug!(
+                "None of the {} validators is known to the chain",
+                store.len()
+            );
+            return Ok(());
+        }
+
+        let proposers = beacon_node.proposer_duties(epoch).await?.data;
+        self.proposers.insert(
+            epoch,
+            proposers
+                .into_iter()
+                .filter(|duty| store.has_validator(&duty.pubkey))
+                .collect(),
+        );
+        for epoch in [epoch, epoch + 1] {
+            let attesters = beacon_node.attester_duties(epoch, &indices).await?.data;
+            if let Err(err) = subscribe_to_committees(beacon_node, store, &attesters).await {
+                warn!("Failed to subscribe to the committees of epoch {epoch}: {err}");
+            }
+            self.attesters.insert(epoch, attesters);
+        }
+
+        let oldest = epoch.saturating_sub(RETAINED_EPOCHS - 1);
+        self.proposers.retain(|kept, _| *kept >= oldest);
+        self.attesters.retain(|kept, _| *kept >= oldest);
+        Ok(())
+    }
+
+    pub fn index_of(&self, pubkey: &BLSPubkey) -> Option<u64> {
+        self.indices.get(pubkey).copied()
+    }
+
+    pub fn proposers_at(&self, slot: u64) -> Vec<ProposerDuty> {
+        self.proposers
+            .values()
+            .flatten()
+            .filter(|duty| duty.slot == slot)
+            .cloned()
+            .collect()
+    }
+
+    pub fn attesters_at(&self, slot: u64) -> Vec<AttesterDuty> {
+        self.attesters
+            .values()
+            .flatten()
+            .filter(|duty| duty.slot == slot)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether duties of `epoch` were fetched.
+    pub fn has_epoch(&self, epoch: u64) -> bool {
+        self.attesters.contains_key(&epoch) && self.proposers.contains_key(&epoch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use axum::{
+        extract::Path,
+        routing::{get, post},
+        Json, Router,
+    };
+    use ream_bls::PrivateKey;
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+
+    /// A beacon node where the validator of `pubkey` has index 5, proposes at the first slot
+    /// of every epoch and attests at the second one.
+    async fn mock_beacon_node(pubkey: BLSPubkey) -> Url {
+        let validators = move |Json(_): Json<Value>| async move {
+            Json(json!({
+                "execution_optimistic": false,
+                "finalized": false,
+                "data": [{
+                    "index": "5",
+                    "balance": "32000000000",
+                    "status": "active_ongoing",
+                    "validator": {
+                        "pubkey": pubkey,
+                        "withdrawal_credentials": B256::ZERO,
+                        "effective_balance": "32000000000",
+                        "slashed": false,
+                        "activation_eligibility_epoch": "0",
+                        "activation_epoch": "0",
+                        "exit_epoch": "18446744073709551615",
+                        "withdrawable_epoch": "18446744073709551615",
+                    },
+                }],
+            }))
+        };
+        let proposers = move |Path(epoch): Path<u64>| async move {
+            Json(json!({
+                "dependent_root": B256::ZERO,
+                "execution_optimistic": false,
+                "data": [
+                    { "pubkey": pubkey, "validator_index": "5", "slot": (epoch * 32).to_string() },
+                    { "pubkey": BLSPubkey::ZERO, "validator_index": "6", "slot": (epoch * 32 + 1).to_string() },
+                ],
+            }))
+        };
+        let attesters = move |Path(epoch): Path<u64>, Json(indices): Json<Value>| async move {
+            assert_eq!(indices, json!(["5"]));
+            Json(json!({
+                "dependent_root": B256::ZERO,
+                "execution_optimistic": false,
+                "data": [{
+                    "pubkey": pubkey,
+                    "validator_index": "5",
+                    "committee_index": "0",
+                    "committee_length": "1",
+                    "committees_at_slot": "1",
+                    "validator_committee_index": "0",
+                    "slot": (epoch * 32 + 1).to_string(),
+                }],
+            }))
+        };
+        let subscriptions = |Json(subscriptions): Json<Value>| async move {
+            assert_eq!(subscriptions[0]["validator_index"], "5");
+            // A committee of one always aggregates.
+            assert_eq!(subscriptions[0]["is_aggregator"], true);
+        };
+        let router = Router::new()
+            .route("/eth/v1/beacon/states/head/validators", post(validators))
+            .route(
+                "/eth/v1/validator/beacon_committee_subscriptions",
+                post(subscriptions),
+            )
+            .route("/eth/v1/validator/duties/proposer/{epoch}", get(proposers))
+            .route("/eth/v1/validator/duties/attester/{epoch}", post(attesters));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_duties() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        let beacon_node = BeaconNodeClient::new(mock_beacon_node(pubkey).await);
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::ZERO);
+
+        let mut duties = DutiesService::new();
+        assert!(!duties.has_epoch(2));
+        duties.update(&beacon_node, &store, 2).await.unwrap();
+        assert!(duties.has_epoch(2));
+        assert!(!duties.has_epoch(3));
+        assert_eq!(duties.index_of(&pubkey), Some(5));
+
+        // Proposals of other validators are dropped.
+        assert_eq!(duties.proposers_at(64).len(), 1);
+        assert!(duties.proposers_at(65).is_empty());
+        assert_eq!(duties.attesters_at(65).len(), 1);
+        assert_eq!(duties.attesters_at(97).len(), 1);
+
+        // Duties of epochs long gone are dropped.
+        duties.update(&beacon_node, &store, 4).await.unwrap();
+        assert!(duties.proposers_at(64).is_empty());
+        assert!(duties.attesters_at(65).is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+use std::{io, path::PathBuf};
+
+use ream_consensus::{bls::BLSPubkey, constants::MIN_DEPOSIT_AMOUNT};
+
+use crate::slashing_protection::SlashingProtectionError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ValidatorError {
+    #[error("beacon node unreachable: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("beacon node returned error {status}: {message}")]
+    BeaconNode { status: u16, message: String },
+    #[error("invalid response from the beacon node: {0}")]
+    InvalidResponse(String),
+    #[error("no key for validator {0}")]
+    UnknownValidator(BLSPubkey),
+    #[error("invalid secret key: {0}")]
+    InvalidKey(String),
+    #[error("{0}")]
+    Keystore(String),
+    #[error("invalid mnemonic: {0}")]
+    Mnemonic(String),
+    #[error("deposit of {0} gwei is below the minimum of {min} gwei", min = MIN_DEPOSIT_AMOUNT)]
+    DepositAmount(u64),
+    #[error("invalid proposer config {0}")]
+    ProposerConfig(String),
+    #[error("remote signer failed: {0}")]
+    RemoteSigner(String),
+    #[error("refusing to sign: {0}")]
+    SlashingProtection(#[from] SlashingProtectionError),
+    #[error("{path}: {error}")]
+    File { path: PathBuf, error: io::Error },
+}
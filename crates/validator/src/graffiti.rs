@@ -0,0 +1,58 @@
+use std::{fmt, str::FromStr};
+
+use alloy_primitives::B256;
+
+/// Text a proposer puts in its blocks, up to 32 bytes of UTF-8 padded with zeros.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Graffiti(pub B256);
+
+impl FromStr for Graffiti {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > B256::len_bytes() {
+            return Err(format!(
+                "graffiti is {} bytes, at most {} fit in a block",
+                s.len(),
+                B256::len_bytes()
+            ));
+        }
+        let mut graffiti = B256::ZERO;
+        graffiti[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Self(graffiti))
+    }
+}
+
+impl fmt::Display for Graffiti {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.0.as_slice();
+        let end = text
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+        f.write_str(&String::from_utf8_lossy(&text[..end]))
+    }
+}
+
+impl From<Graffiti> for B256 {
+    fn from(graffiti: Graffiti) -> Self {
+        graffiti.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graffiti() {
+        let graffiti: Graffiti = "ream 🦀".parse().unwrap();
+        assert_eq!(&graffiti.0[..9], "ream 🦀".as_bytes());
+        assert_eq!(graffiti.0[9..], [0; 23]);
+        assert_eq!(graffiti.to_string(), "ream 🦀");
+
+        assert_eq!("".parse::<Graffiti>().unwrap(), Graffiti::default());
+        assert!("a".repeat(32).parse::<Graffiti>().is_ok());
+        assert!("a".repeat(33).parse::<Graffiti>().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use ream_consensus::{
+    attestation_data::AttestationData, beacon_block::BeaconBlock, misc::compute_epoch_at_slot,
+};
+use ream_rpc::handlers::duties::AttesterDuty;
+
+/// An attestation the client published, waiting to be seen in a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingAttestation {
+    validator_index: u64,
+    validator_committee_index: usize,
+    data: AttestationData,
+}
+
+/// When an attestation made it into a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inclusion {
+    pub validator_index: u64,
+    pub attestation_slot: u64,
+    pub block_slot: u64,
+}
+
+impl Inclusion {
+    /// Slots between the attestation and its block, 1 at best.
+    pub fn delay(&self) -> u64 {
+        self.block_slot - self.attestation_slot
+    }
+}
+
+/// Attestations the client published, checked against the blocks that follow to report which
+/// were included and how late.
+#[derive(Debug, Default)]
+pub struct InclusionTracker {
+    pending: Vec<PendingAttestation>,
+}
+
+impl InclusionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&mut self, duty: &AttesterDuty, data: AttestationData) {
+        self.pending.push(PendingAttestation {
+            validator_index: duty.validator_index,
+            validator_committee_index: duty.validator_committee_index as usize,
+            data,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Stops tracking the attestations `block` includes and returns them.
+    pub fn process_block(&mut self, block: &BeaconBlock) -> Vec<Inclusion> {
+        let mut included = vec![];
+        self.pending.retain(|pending| {
+            let found = block.body.attestations.iter().any(|attestation| {
+                attestation.data == pending.data
+                    && attestation
+                        .aggregation_bits
+                        .get(pending.validator_committee_index)
+                        .unwrap_or(false)
+            });
+            if found {
+                included.push(Inclusion {
+                    validator_index: pending.validator_index,
+                    attestation_slot: pending.data.slot,
+                    block_slot: block.slot,
+                });
+            }
+            !found
+        });
+        included
+    }
+
+    /// Stops tracking the attestations that can't be included in blocks from `slot` on, an
+    /// attestation is only valid until the end of the epoch after its own. Returns the
+    /// validator index and slot of each.
+    pub fn prune(&mut self, slot: u64) -> Vec<(u64, u64)> {
+        let epoch = compute_epoch_at_slot(slot);
+        let mut missed = vec![];
+        self.pending.retain(|pending| {
+            let expired = compute_epoch_at_slot(pending.data.slot) + 1 < epoch;
+            if expired {
+                missed.push((pending.validator_index, pending.data.slot));
+            }
+            !expired
+        });
+        missed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use ream_consensus::{attestation::Attestation, constants::SLOTS_PER_EPOCH};
+    use ssz_types::BitList;
+
+    use super::*;
+
+    fn duty(validator_index: u64, validator_committee_index: u64) -> AttesterDuty {
+        AttesterDuty {
+            pubkey: Default::default(),
+            validator_index,
+            committee_index: 0,
+            committee_length: 4,
+            committees_at_slot: 1,
+            validator_committee_index,
+            slot: 3,
+        }
+    }
+
+    #[test]
+    fn test_inclusion_tracking() {
+        let data = AttestationData {
+            slot: 3,
+            beacon_block_root: B256::repeat_byte(1),
+            ..AttestationData::default()
+        };
+        let mut tracker = InclusionTracker::new();
+        tracker.track(&duty(10, 1), data);
+        tracker.track(&duty(11, 2), data);
+
+        let mut aggregation_bits = BitList::with_capacity(4).unwrap();
+        aggregation_bits.set(1, true).unwrap();
+        let mut block = BeaconBlock {
+            slot: 5,
+            ..BeaconBlock::default()
+        };
+        block
+            .body
+            .attestations
+            .push(Attestation {
+                aggregation_bits,
+                data,
+                signature: Default::default(),
+            })
+            .unwrap();
+        let included = tracker.process_block(&block);
+        assert_eq!(included.len(), 1);
+        assert_eq!(included[0].validator_index, 10);
+        assert_eq!(included[0].delay(), 2);
+        assert_eq!(tracker.len(), 1);
+
+        assert!(tracker.prune(2 * SLOTS_PER_EPOCH - 1).is_empty());
+        assert_eq!(tracker.prune(2 * SLOTS_PER_EPOCH), [(11, 3)]);
+        assert!(tracker.is_empty());
+    }
+}
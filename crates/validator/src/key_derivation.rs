@@ -0,0 +1,135 @@
+//! Validator keys derived from a BIP-39 mnemonic, with the tree of EIP-2333 and the paths of
+//! EIP-2334.
+
+use bip39::{Language, Mnemonic};
+use hkdf::Hkdf;
+use ream_bls::PrivateKey;
+use sha2::{Digest, Sha256};
+
+use crate::error::ValidatorError;
+
+/// Number of 32 byte chunks of a Lamport secret key.
+const LAMPORT_CHUNKS: usize = 255;
+
+/// `m/12381/3600/{index}/0/0`, the key signing the duties of the validator.
+pub fn signing_key_path(index: u32) -> String {
+    format!("m/12381/3600/{index}/0/0")
+}
+
+/// `m/12381/3600/{index}/0`, the key the validator's BLS withdrawal credentials commit to.
+pub fn withdrawal_key_path(index: u32) -> String {
+    format!("m/12381/3600/{index}/0")
+}
+
+/// The BIP-39 seed of `mnemonic`, which must be made of words of the English list and end
+/// with a valid checksum.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<[u8; 64], ValidatorError> {
+    let mnemonic = Mnemonic::parse_in(Language::English, mnemonic)
+        .map_err(|err| ValidatorError::Mnemonic(err.to_string()))?;
+    Ok(mnemonic.to_seed(passphrase))
+}
+
+/// The key at `path` of the tree rooted at `seed`, such as `m/12381/3600/0/0/0`.
+pub fn derive_path(seed: &[u8], path: &str) -> Result<PrivateKey, ValidatorError> {
+    let invalid = || ValidatorError::Mnemonic(format!("invalid derivation path {path}"));
+    let mut nodes = path.split('/');
+    if nodes.next() != Some("m") {
+        return Err(invalid());
+    }
+    let mut key = derive_master_sk(seed)?;
+    for node in nodes {
+        key = derive_child_sk(&key, node.parse().map_err(|_| invalid())?)?;
+    }
+    Ok(key)
+}
+
+pub fn derive_master_sk(seed: &[u8]) -> Result<PrivateKey, ValidatorError> {
+    if seed.len() < 32 {
+        return Err(ValidatorError::Mnemonic(
+            "seeds must be at least 32 bytes".to_string(),
+        ));
+    }
+    hkdf_mod_r(seed)
+}
+
+pub fn derive_child_sk(parent: &PrivateKey, index: u32) -> Result<PrivateKey, ValidatorError> {
+    hkdf_mod_r(&parent_sk_to_lamport_pk(parent, index))
+}
+
+/// The `KeyGen` of the BLS signature draft, which EIP-2333 calls `HKDF_mod_r`.
+fn hkdf_mod_r(ikm: &[u8]) -> Result<PrivateKey, ValidatorError> {
+    PrivateKey::key_gen(ikm).map_err(|err| ValidatorError::InvalidKey(err.to_string()))
+}
+
+/// The compressed Lamport public key of the child `index` of `parent`.
+fn parent_sk_to_lamport_pk(parent: &PrivateKey, index: u32) -> [u8; 32] {
+    let salt = index.to_be_bytes();
+    let ikm = parent.to_bytes();
+    let not_ikm = ikm.map(|byte| !byte);
+    let mut lamport_pk = Sha256::new();
+    for ikm in [ikm, not_ikm] {
+        for chunk in ikm_to_lamport_sk(&ikm, &salt).chunks(32) {
+            lamport_pk.update(Sha256::digest(chunk));
+        }
+    }
+    lamport_pk.finalize().into()
+}
+
+fn ikm_to_lamport_sk(ikm: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut okm = vec![0; 32 * LAMPORT_CHUNKS];
+    Hkdf::<Sha256>::new(Some(salt), ikm)
+        .expand(&[], &mut okm)
+        .expect("8160 bytes is a valid HKDF-SHA256 output length");
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+
+    use super::*;
+
+    /// Test case 0 of EIP-2333.
+    const SEED: &str = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+
+    #[test]
+    fn test_mnemonic_to_seed() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        let seed = mnemonic_to_seed(mnemonic, "TREZOR").unwrap();
+        assert_eq!(hex::encode(seed), SEED);
+        assert!(mnemonic_to_seed("abandon about", "").is_err());
+
+        // The last word carries the checksum, another word of the list breaks it.
+        let flipped = mnemonic.replace("about", "above");
+        assert!(matches!(
+            mnemonic_to_seed(&flipped, "TREZOR"),
+            Err(ValidatorError::Mnemonic(_))
+        ));
+        let unknown = mnemonic.replace("about", "abuot");
+        assert!(mnemonic_to_seed(&unknown, "TREZOR").is_err());
+    }
+
+    #[test]
+    fn test_derive_keys() {
+        let seed = hex::decode(SEED).unwrap();
+        let master = derive_master_sk(&seed).unwrap();
+        assert_eq!(
+            hex::encode(master.to_bytes()),
+            "0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070"
+        );
+        let child = derive_child_sk(&master, 0).unwrap();
+        assert_eq!(
+            hex::encode(child.to_bytes()),
+            "2d18bd6c14e6d15bf8b5085c9b74f3daae3b03cc2014770a599d8c1539e50f8e"
+        );
+        assert_eq!(
+            derive_path(&seed, "m/0").unwrap().to_bytes(),
+            child.to_bytes()
+        );
+
+        assert_eq!(signing_key_path(2), "m/12381/3600/2/0/0");
+        assert!(derive_path(&seed, "12381/3600").is_err());
+        assert!(derive_path(&seed, "m/-1").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use ream_bls::PrivateKey;
+use ream_consensus::{bls::BLSPubkey, constants::SECONDS_PER_SLOT};
+use tracing::{info, warn};
+
+use crate::{error::ValidatorError, keystore::Keystore, validator_store::ValidatorStore};
+
+/// How often the validators directory is checked for new keystores.
+pub const RELOAD_INTERVAL: Duration = Duration::from_secs(SECONDS_PER_SLOT);
+
+/// Where the keystores of the validators are and how to unlock them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreLocations {
+    /// Holds the keystores as `.json` files, directly or in a directory per validator.
+    pub validators_dir: PathBuf,
+    /// Holds a password file per keystore, named after its `0x` prefixed public key.
+    pub secrets_dir: Option<PathBuf>,
+    /// Password of the keystores without one in `secrets_dir`.
+    pub password_file: Option<PathBuf>,
+}
+
+impl KeystoreLocations {
+    /// Keystore files of the validators directory, sorted.
+    pub fn keystore_paths(&self) -> Result<Vec<PathBuf>, ValidatorError> {
+        let mut paths = vec![];
+        for entry in read_dir(&self.validators_dir)? {
+            if entry.is_dir() {
+                paths.extend(read_dir(&entry)?.into_iter().filter(|path| is_json(path)));
+            } else if is_json(&entry) {
+                paths.push(entry);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Keystore file of each validator of the validators directory, unreadable ones left out.
+    pub fn keystore_files(&self) -> Result<HashMap<BLSPubkey, PathBuf>, ValidatorError> {
+        Ok(self
+            .keystore_paths()?
+            .into_iter()
+            .filter_map(|path| {
+                let keystore = Keystore::from_json(&read_to_string(&path).ok()?).ok()?;
+                Some((keystore.pubkey().ok()?, path))
+            })
+            .collect())
+    }
+
+    /// Writes the keystore of `pubkey` to a directory of its own in the validators directory
+    /// and its password to the secrets directory, where they are loaded from at startup. The
+    /// password is written first so the keystore is never seen without it.
+    pub fn write_keystore(
+        &self,
+        pubkey: &BLSPubkey,
+        keystore_json: &str,
+        password: &str,
+    ) -> Result<PathBuf, ValidatorError> {
+        let secrets_dir = self.secrets_dir.as_ref().ok_or_else(|| {
+            ValidatorError::Keystore("no secrets directory to write the password to".to_string())
+        })?;
+        let dir = self.validators_dir.join(pubkey.to_string());
+        let path = dir.join("voting-keystore.json");
+        write_file(&secrets_dir.join(pubkey.to_string()), password)?;
+        fs::create_dir_all(&dir).map_err(|error| ValidatorError::File {
+            path: dir.clone(),
+            error,
+        })?;
+        write_file(&path, keystore_json)?;
+        Ok(path)
+    }
+
+    /// Deletes the keystore at `path` and the password file of `pubkey`. The directory of the
+    /// keystore goes too once empty, unless it is the validators directory.
+    pub fn remove_keystore(&self, pubkey: &BLSPubkey, path: &Path) -> Result<(), ValidatorError> {
+        remove_file(path)?;
+        if let Some(dir) = path.parent().filter(|dir| *dir != self.validators_dir) {
+            // Fails if the directory holds other files, which are kept.
+            let _ = fs::remove_dir(dir);
+        }
+        if let Some(secrets_dir) = &self.secrets_dir {
+            let password = secrets_dir.join(pubkey.to_string());
+            if password.exists() {
+                remove_file(&password)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the keystore at `path` and decrypts it with its password.
+    pub fn load(&self, path: &Path) -> Result<PrivateKey, ValidatorError> {
+        let keystore = Keystore::from_json(&read_to_string(path)?)?;
+        keystore.decrypt(&self.password(&keystore)?)
+    }
+
+    fn password(&self, keystore: &Keystore) -> Result<String, ValidatorError> {
+        let pubkey = keystore.pubkey()?;
+        if let Some(secrets_dir) = &self.secrets_dir {
+            let path = secrets_dir.join(pubkey.to_string());
+            if path.exists() {
+                return read_password(&path);
+            }
+        }
+        match &self.password_file {
+            Some(path) => read_password(path),
+            None => Err(ValidatorError::Keystore(format!(
+                "no password for validator {pubkey}"
+            ))),
+        }
+    }
+}
+
+/// Decrypts the keystores of `paths` on every core, key derivation being slow on purpose.
+/// Results come in the order of `paths`.
+pub fn decrypt_keystores(
+    locations: &KeystoreLocations,
+    paths: &[PathBuf],
+) -> Vec<Result<PrivateKey, ValidatorError>> {
+    let workers = std::thread::available_parallelism().map_or(1, |workers| workers.get());
+    let chunk_size = paths.len().div_ceil(workers).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|path| locations.load(path))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("keystore decryption panicked"))
+            .collect()
+    })
+}
+
+/// Adds the keystores appearing in the validators directory to `store` every
+/// [`RELOAD_INTERVAL`], `known` being the keystores already loaded. Keystores failing to load
+/// are reported once. Meant to be spawned.
+pub async fn watch_keystores(
+    locations: KeystoreLocations,
+    store: Arc<ValidatorStore>,
+    mut known: HashSet<PathBuf>,
+) {
+    loop {
+        tokio::time::sleep(RELOAD_INTERVAL).await;
+        let paths = match locations.keystore_paths() {
+            Ok(paths) => paths,
+            Err(err) => {
+                warn!("Failed to list the keystores: {err}");
+                continue;
+            }
+        };
+        let new_paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| !known.contains(path))
+            .collect();
+        if new_paths.is_empty() {
+            continue;
+        }
+
+        let decrypting = (locations.clone(), new_paths.clone());
+        let results = match tokio::task::spawn_blocking(move || {
+            decrypt_keystores(&decrypting.0, &decrypting.1)
+        })
+        .await
+        {
+            Ok(results) => results,
+            Err(err) => {
+                warn!("Keystore decryption failed: {err}");
+                continue;
+            }
+        };
+        for (path, result) in new_paths.into_iter().zip(results) {
+            match result {
+                Ok(key) => {
+                    let pubkey = key.public_key();
+                    if store.add_key(key) {
+                        info!("Loaded validator {pubkey} from {}", path.display());
+                    }
+                }
+                Err(err) => warn!("Failed to load {}: {err}", path.display()),
+            }
+            known.insert(path);
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, ValidatorError> {
+    let file_error = |error| ValidatorError::File {
+        path: dir.to_path_buf(),
+        error,
+    };
+    fs::read_dir(dir)
+        .map_err(file_error)?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(file_error))
+        .collect()
+}
+
+fn read_to_string(path: &Path) -> Result<String, ValidatorError> {
+    fs::read_to_string(path).map_err(|error| ValidatorError::File {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn remove_file(path: &Path) -> Result<(), ValidatorError> {
+    fs::remove_file(path).map_err(|error| ValidatorError::File {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), ValidatorError> {
+    fs::write(path, contents).map_err(|error| ValidatorError::File {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// The password in the file at `path`, without the line break editors add.
+fn read_password(path: &Path) -> Result<String, ValidatorError> {
+    Ok(read_to_string(path)?
+        .trim_end_matches(['\r', '\n'])
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::hex;
+
+    use super::*;
+    use crate::keystore::tests::{keystore_json, PASSWORD, PUBKEY, SECRET};
+
+    #[test]
+    fn test_decrypt_keystores() {
+        let dir = tempfile::tempdir().unwrap();
+        let validators_dir = dir.path().join("validators");
+        let secrets_dir = dir.path().join("secrets");
+        fs::create_dir_all(validators_dir.join(format!("0x{PUBKEY}"))).unwrap();
+        fs::create_dir_all(&secrets_dir).unwrap();
+        fs::write(
+            validators_dir.join("keystore-a.json"),
+            keystore_json("scrypt"),
+        )
+        .unwrap();
+        fs::write(
+            validators_dir
+                .join(format!("0x{PUBKEY}"))
+                .join("voting-keystore.json"),
+            keystore_json("pbkdf2"),
+        )
+        .unwrap();
+        fs::write(validators_dir.join("notes.txt"), "not a keystore").unwrap();
+
+        let mut locations = KeystoreLocations {
+            validators_dir: validators_dir.clone(),
+            secrets_dir: Some(secrets_dir.clone()),
+            password_file: None,
+        };
+        let paths = locations.keystore_paths().unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(decrypt_keystores(&locations, &paths)
+            .iter()
+            .all(Result::is_err));
+
+        // Per keystore passwords, and the shared one for those without.
+        fs::write(
+            secrets_dir.join(format!("0x{PUBKEY}")),
+            format!("{PASSWORD}\n"),
+        )
+        .unwrap();
+        for result in decrypt_keystores(&locations, &paths) {
+            assert_eq!(hex::encode(result.unwrap().to_bytes()), SECRET);
+        }
+        fs::remove_file(secrets_dir.join(format!("0x{PUBKEY}"))).unwrap();
+        fs::write(dir.path().join("password.txt"), PASSWORD).unwrap();
+        locations.password_file = Some(dir.path().join("password.txt"));
+        assert!(decrypt_keystores(&locations, &paths)
+            .iter()
+            .all(Result::is_ok));
+    }
+}
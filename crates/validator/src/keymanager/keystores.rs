@@ -0,0 +1,282 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use axum::{extract::State, Json};
+use ream_consensus::bls::BLSPubkey;
+use ream_rpc::response::DataResponse;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{
+    DeleteKeysRequest, DeleteStatus, ImportStatus, KeyStatus, KeymanagerContext, KeymanagerError,
+};
+use crate::{
+    error::ValidatorError,
+    keystore::Keystore,
+    slashing_protection::{interchange::Interchange, SlashingDatabase},
+    validator_store::Signer,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreInfo {
+    pub validating_pubkey: BLSPubkey,
+    /// Whether the key can't be deleted, those loaded from a secret keys file can't.
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportKeystoresRequest {
+    /// JSON encoded keystores.
+    pub keystores: Vec<String>,
+    /// Password of each keystore, in the same order.
+    pub passwords: Vec<String>,
+    /// JSON encoded EIP-3076 interchange, imported before the keys.
+    #[serde(default)]
+    pub slashing_protection: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteKeystoresResponse {
+    pub data: Vec<KeyStatus<DeleteStatus>>,
+    /// JSON encoded EIP-3076 interchange of the history of the requested keys.
+    pub slashing_protection: String,
+}
+
+/// `GET /eth/v1/keystores`, the keys held by the client.
+pub async fn get_keystores(
+    State(context): State<KeymanagerContext>,
+) -> Result<Json<DataResponse<Vec<KeystoreInfo>>>, KeymanagerError> {
+    let files = keystore_files(&context)?;
+    let mut keys: Vec<KeystoreInfo> = context
+        .store
+        .pubkeys()
+        .into_iter()
+        .filter(|pubkey| matches!(context.store.signer(pubkey), Some(Signer::Local(_))))
+        .map(|pubkey| KeystoreInfo {
+            validating_pubkey: pubkey,
+            readonly: !files.contains_key(&pubkey),
+        })
+        .collect();
+    keys.sort_by_key(|key| key.validating_pubkey);
+    Ok(Json(DataResponse::new(keys)))
+}
+
+/// `POST /eth/v1/keystores`
+///
+/// Imports the slashing protection history, then writes the keystores to the validators
+/// directory and starts signing with them. The request fails as a whole only if the history
+/// can't be imported.
+pub async fn post_keystores(
+    State(context): State<KeymanagerContext>,
+    Json(request): Json<ImportKeystoresRequest>,
+) -> Result<Json<DataResponse<Vec<KeyStatus<ImportStatus>>>>, KeymanagerError> {
+    if request.keystores.len() != request.passwords.len() {
+        return Err(KeymanagerError::BadRequest(format!(
+            "{} keystores but {} passwords",
+            request.keystores.len(),
+            request.passwords.len()
+        )));
+    }
+    if let Some(slashing_protection) = &request.slashing_protection {
+        let interchange: Interchange = serde_json::from_str(slashing_protection)
+            .map_err(|err| KeymanagerError::BadRequest(format!("invalid interchange: {err}")))?;
+        slashing_database(&context)?
+            .import_interchange(&interchange)
+            .map_err(|err| KeymanagerError::BadRequest(err.to_string()))?;
+    }
+
+    let mut statuses = vec![];
+    for (keystore, password) in request.keystores.into_iter().zip(request.passwords) {
+        statuses.push(match import_keystore(&context, keystore, password).await {
+            Ok(true) => KeyStatus::new(ImportStatus::Imported),
+            Ok(false) => KeyStatus::new(ImportStatus::Duplicate),
+            Err(err) => KeyStatus::with_message(ImportStatus::Error, err),
+        });
+    }
+    Ok(Json(DataResponse::new(statuses)))
+}
+
+/// Returns whether the key is new.
+async fn import_keystore(
+    context: &KeymanagerContext,
+    json: String,
+    password: String,
+) -> Result<bool, ValidatorError> {
+    let locations = context.locations.as_ref().ok_or_else(|| {
+        ValidatorError::Keystore("no validators directory to import into".to_string())
+    })?;
+    let keystore = Keystore::from_json(&json)?;
+    let pubkey = keystore.pubkey()?;
+    if context.store.has_validator(&pubkey) {
+        return Ok(false);
+    }
+    // Key derivation takes a while on purpose.
+    let (key, password) =
+        tokio::task::spawn_blocking(move || (keystore.decrypt(&password), password))
+            .await
+            .map_err(|err| ValidatorError::Keystore(err.to_string()))?;
+    let key = key?;
+    let path = locations.write_keystore(&pubkey, &json, &password)?;
+    if context.store.add_key(key) {
+        info!("Imported validator {pubkey} to {}", path.display());
+    }
+    Ok(true)
+}
+
+/// `DELETE /eth/v1/keystores`
+///
+/// Stops signing with the keys and deletes their keystores, then exports their slashing
+/// protection history for them to be used elsewhere. Keys unknown but with a history are
+/// reported as not active.
+pub async fn delete_keystores(
+    State(context): State<KeymanagerContext>,
+    Json(request): Json<DeleteKeysRequest>,
+) -> Result<Json<DeleteKeystoresResponse>, KeymanagerError> {
+    let db = slashing_database(&context)?;
+    let files = keystore_files(&context)?;
+    let mut data = vec![];
+    for pubkey in &request.pubkeys {
+        let status = match (context.store.signer(pubkey), files.get(pubkey)) {
+            (Some(Signer::Local(_)), Some(path)) => {
+                context.store.remove_signer(pubkey);
+                info!("Deleted validator {pubkey}");
+                let locations = context
+                    .locations
+                    .as_ref()
+                    .expect("keystore files come from the validators directory");
+                match locations.remove_keystore(pubkey, path) {
+                    Ok(()) => KeyStatus::new(DeleteStatus::Deleted),
+                    Err(err) => KeyStatus::with_message(DeleteStatus::Error, err),
+                }
+            }
+            (Some(Signer::Local(_)), None) => KeyStatus::with_message(
+                DeleteStatus::Error,
+                "the key is read only, it doesn't come from a keystore",
+            ),
+            _ => KeyStatus::new(DeleteStatus::NotFound),
+        };
+        data.push(status);
+    }
+
+    let interchange = db
+        .export_interchange_of(&request.pubkeys)
+        .map_err(|err| KeymanagerError::Internal(err.to_string()))?;
+    for (pubkey, status) in request.pubkeys.iter().zip(&mut data) {
+        if status.status == DeleteStatus::NotFound
+            && interchange.data.iter().any(|data| data.pubkey == *pubkey)
+        {
+            status.status = DeleteStatus::NotActive;
+        }
+    }
+    let slashing_protection = serde_json::to_string(&interchange)
+        .map_err(|err| KeymanagerError::Internal(err.to_string()))?;
+    Ok(Json(DeleteKeystoresResponse {
+        data,
+        slashing_protection,
+    }))
+}
+
+fn keystore_files(
+    context: &KeymanagerContext,
+) -> Result<HashMap<BLSPubkey, PathBuf>, KeymanagerError> {
+    match &context.locations {
+        Some(locations) => locations
+            .keystore_files()
+            .map_err(|err| KeymanagerError::Internal(err.to_string())),
+        None => Ok(HashMap::new()),
+    }
+}
+
+fn slashing_database(context: &KeymanagerContext) -> Result<&SlashingDatabase, KeymanagerError> {
+    context.store.slashing_protection().ok_or_else(|| {
+        KeymanagerError::Internal("the client has no slashing protection database".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        keymanager::tests::{serve, TOKEN},
+        keystore::tests::{keystore_json, PASSWORD, PUBKEY},
+    };
+
+    #[tokio::test]
+    async fn test_import_and_delete_keystores() {
+        let (url, context, _dir) = serve().await;
+        let url = url.join("/eth/v1/keystores").unwrap();
+        let http = reqwest::Client::new();
+        let pubkey: BLSPubkey = format!("0x{PUBKEY}").parse().unwrap();
+
+        let request = json!({
+            "keystores": [keystore_json("pbkdf2"), keystore_json("pbkdf2")],
+            "passwords": [PASSWORD, "wrong"],
+        });
+        let response: Value = http
+            .post(url.clone())
+            .bearer_auth(TOKEN)
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["data"][0]["status"], "imported");
+        // The second copy is already known, whatever its password.
+        assert_eq!(response["data"][1]["status"], "duplicate");
+        assert!(context.store.has_validator(&pubkey));
+        let locations = context.locations.clone().unwrap();
+        assert_eq!(locations.keystore_files().unwrap().len(), 1);
+
+        let response: Value = http
+            .get(url.clone())
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            response["data"],
+            json!([{ "validating_pubkey": pubkey, "readonly": false }])
+        );
+
+        let db = context.store.slashing_protection().unwrap();
+        db.check_and_insert_block(&pubkey, 5, B256::ZERO).unwrap();
+        let request = json!({ "pubkeys": [pubkey, BLSPubkey::repeat_byte(1)] });
+        let response: DeleteKeystoresResponse = http
+            .delete(url.clone())
+            .bearer_auth(TOKEN)
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response.data[0].status, DeleteStatus::Deleted);
+        assert_eq!(response.data[1].status, DeleteStatus::NotFound);
+        let interchange: Interchange = serde_json::from_str(&response.slashing_protection).unwrap();
+        assert_eq!(interchange.data[0].signed_blocks[0].slot, 5);
+        assert!(!context.store.has_validator(&pubkey));
+        assert!(locations.keystore_files().unwrap().is_empty());
+
+        // Once deleted the key is only known to the slashing protection database.
+        let request = json!({ "pubkeys": [pubkey] });
+        let response: DeleteKeystoresResponse = http
+            .delete(url)
+            .bearer_auth(TOKEN)
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response.data[0].status, DeleteStatus::NotActive);
+    }
+}
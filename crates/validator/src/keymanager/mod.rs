@@ -0,0 +1,313 @@
+//! The keymanager API of the validator client, letting tools such as eth-docker and Wagyu
+//! manage its keys and proposer settings over HTTP.
+
+pub mod keystores;
+pub mod proposer;
+pub mod remote_keys;
+
+use std::{
+    fs, io,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use alloy_primitives::hex;
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use ream_consensus::bls::BLSPubkey;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::info;
+
+use crate::{
+    error::ValidatorError, key_loader::KeystoreLocations, proposer_config::ProposerConfig,
+    validator_store::ValidatorStore,
+};
+
+pub const DEFAULT_KEYMANAGER_PORT: u16 = 5062;
+
+/// Handles to the validator client shared by all handlers.
+#[derive(Clone)]
+pub struct KeymanagerContext {
+    pub store: Arc<ValidatorStore>,
+    /// Where imported keystores are written, `None` unless the keys come from keystores.
+    pub locations: Option<KeystoreLocations>,
+    pub proposer_config: Arc<RwLock<ProposerConfig>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeymanagerError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("missing bearer token")]
+    Unauthorized,
+    #[error("invalid bearer token")]
+    Forbidden,
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl KeymanagerError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            KeymanagerError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            KeymanagerError::NotFound(_) => StatusCode::NOT_FOUND,
+            KeymanagerError::Unauthorized => StatusCode::UNAUTHORIZED,
+            KeymanagerError::Forbidden => StatusCode::FORBIDDEN,
+            KeymanagerError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorMessage {
+    pub message: String,
+}
+
+impl IntoResponse for KeymanagerError {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        (self.status_code(), Json(ErrorMessage { message })).into_response()
+    }
+}
+
+/// Bearer token every request must carry.
+#[derive(Clone)]
+pub struct ApiToken(Arc<str>);
+
+impl ApiToken {
+    /// Reads the token of the file at `path`, writing a random one first if there's none.
+    pub fn load_or_create(path: &Path) -> Result<Self, ValidatorError> {
+        let file_error = |error| ValidatorError::File {
+            path: path.to_path_buf(),
+            error,
+        };
+        if !path.exists() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(file_error)?;
+            }
+            write_secret(path, &hex::encode(rand::random::<[u8; 32]>())).map_err(file_error)?;
+        }
+        let token = fs::read_to_string(path).map_err(file_error)?;
+        Ok(Self(token.trim().into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for ApiToken {
+    fn from(token: &str) -> Self {
+        Self(token.into())
+    }
+}
+
+/// Only the user running the client may read the token.
+fn write_secret(path: &Path, contents: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::{fs::OpenOptions, io::Write, os::unix::fs::OpenOptionsExt};
+
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?
+            .write_all(contents.as_bytes())
+    }
+    #[cfg(not(unix))]
+    fs::write(path, contents)
+}
+
+pub fn router(context: KeymanagerContext, token: ApiToken) -> Router {
+    Router::new()
+        .route(
+            "/eth/v1/keystores",
+            get(keystores::get_keystores)
+                .post(keystores::post_keystores)
+                .delete(keystores::delete_keystores),
+        )
+        .route(
+            "/eth/v1/remotekeys",
+            get(remote_keys::get_remote_keys)
+                .post(remote_keys::post_remote_keys)
+                .delete(remote_keys::delete_remote_keys),
+        )
+        .route(
+            "/eth/v1/validator/{pubkey}/feerecipient",
+            get(proposer::get_fee_recipient)
+                .post(proposer::post_fee_recipient)
+                .delete(proposer::delete_fee_recipient),
+        )
+        .route(
+            "/eth/v1/validator/{pubkey}/gas_limit",
+            get(proposer::get_gas_limit)
+                .post(proposer::post_gas_limit)
+                .delete(proposer::delete_gas_limit),
+        )
+        .layer(middleware::from_fn_with_state(token, authorize))
+        .with_state(context)
+}
+
+/// Serves the keymanager API on `address` until the process stops.
+pub async fn start_keymanager_server(
+    address: SocketAddr,
+    context: KeymanagerContext,
+    token: ApiToken,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    info!("Keymanager API listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(context, token)).await
+}
+
+async fn authorize(
+    State(token): State<ApiToken>,
+    request: Request,
+    next: Next,
+) -> Result<Response, KeymanagerError> {
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(KeymanagerError::Unauthorized)?;
+    if !constant_time_eq(provided.as_bytes(), token.as_str().as_bytes()) {
+        return Err(KeymanagerError::Forbidden);
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compares without returning early, so response times don't leak how much of the token was
+/// guessed right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Result of an operation on one key of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyStatus<S> {
+    pub status: S,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+}
+
+impl<S> KeyStatus<S> {
+    pub fn new(status: S) -> Self {
+        Self {
+            status,
+            message: String::new(),
+        }
+    }
+
+    pub fn with_message(status: S, message: impl ToString) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,
+    Duplicate,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteStatus {
+    Deleted,
+    /// The key is gone but its slashing protection history is kept and exported.
+    NotActive,
+    NotFound,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteKeysRequest {
+    pub pubkeys: Vec<BLSPubkey>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloy_primitives::B256;
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use tempfile::TempDir;
+    use url::Url;
+
+    use super::*;
+    use crate::slashing_protection::SlashingDatabase;
+
+    pub(crate) const TOKEN: &str = "secret";
+
+    /// A keymanager API without keys, importing keystores into a temporary directory that is
+    /// deleted when the returned [`TempDir`] is dropped.
+    pub(crate) async fn serve() -> (Url, KeymanagerContext, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let locations = KeystoreLocations {
+            validators_dir: dir.path().join("validators"),
+            secrets_dir: Some(dir.path().join("secrets")),
+            password_file: None,
+        };
+        fs::create_dir_all(&locations.validators_dir).unwrap();
+        fs::create_dir_all(dir.path().join("secrets")).unwrap();
+        let db = SlashingDatabase::open(dir.path().join("slashing_protection.redb")).unwrap();
+        let store = ValidatorStore::new([], ForkSchedule::mainnet(), B256::ZERO)
+            .with_slashing_protection(db)
+            .unwrap();
+        let context = KeymanagerContext {
+            store: Arc::new(store),
+            locations: Some(locations),
+            proposer_config: Arc::default(),
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = router(context.clone(), ApiToken::from(TOKEN));
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (format!("http://{address}").parse().unwrap(), context, dir)
+    }
+
+    #[tokio::test]
+    async fn test_authorization() {
+        let (url, _, _dir) = serve().await;
+        let url = url.join("/eth/v1/keystores").unwrap();
+        let http = reqwest::Client::new();
+
+        let response = http.get(url.clone()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = http
+            .get(url.clone())
+            .bearer_auth("guess")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = http.get(url).bearer_auth(TOKEN).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_api_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token.txt");
+        let token = ApiToken::load_or_create(&path).unwrap();
+        assert_eq!(token.as_str().len(), 64);
+        assert_eq!(
+            ApiToken::load_or_create(&path).unwrap().as_str(),
+            token.as_str()
+        );
+    }
+}
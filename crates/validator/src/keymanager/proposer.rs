@@ -0,0 +1,247 @@
+use std::sync::RwLockReadGuard;
+
+use alloy_primitives::Address;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use ream_consensus::bls::BLSPubkey;
+use ream_rpc::response::DataResponse;
+use serde::{Deserialize, Serialize};
+
+use super::{KeymanagerContext, KeymanagerError};
+use crate::proposer_config::{BuilderSettings, ProposerConfig, ProposerSettings};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeRecipientData {
+    pub pubkey: BLSPubkey,
+    pub ethaddress: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetFeeRecipientRequest {
+    pub ethaddress: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasLimitData {
+    pub pubkey: BLSPubkey,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetGasLimitRequest {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+}
+
+/// `GET /eth/v1/validator/{pubkey}/feerecipient`
+pub async fn get_fee_recipient(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+) -> Result<Json<DataResponse<FeeRecipientData>>, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    let ethaddress = read_config(&context)
+        .fee_recipient(&pubkey)
+        .ok_or_else(|| {
+            KeymanagerError::NotFound(format!("no fee recipient set for validator {pubkey}"))
+        })?;
+    Ok(Json(DataResponse::new(FeeRecipientData {
+        pubkey,
+        ethaddress,
+    })))
+}
+
+/// `POST /eth/v1/validator/{pubkey}/feerecipient`, kept until the proposer config file
+/// changes or the client restarts.
+pub async fn post_fee_recipient(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+    Json(request): Json<SetFeeRecipientRequest>,
+) -> Result<StatusCode, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    write_config(&context, pubkey, |settings| {
+        settings.fee_recipient = Some(request.ethaddress)
+    });
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// `DELETE /eth/v1/validator/{pubkey}/feerecipient`, back to the default fee recipient.
+pub async fn delete_fee_recipient(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+) -> Result<StatusCode, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    write_config(&context, pubkey, |settings| settings.fee_recipient = None);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /eth/v1/validator/{pubkey}/gas_limit`
+pub async fn get_gas_limit(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+) -> Result<Json<DataResponse<GasLimitData>>, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    let gas_limit = read_config(&context).gas_limit(&pubkey);
+    Ok(Json(DataResponse::new(GasLimitData { pubkey, gas_limit })))
+}
+
+/// `POST /eth/v1/validator/{pubkey}/gas_limit`, kept until the proposer config file changes or
+/// the client restarts.
+pub async fn post_gas_limit(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+    Json(request): Json<SetGasLimitRequest>,
+) -> Result<StatusCode, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    write_config(&context, pubkey, |settings| {
+        settings.builder = Some(BuilderSettings {
+            gas_limit: request.gas_limit,
+        })
+    });
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// `DELETE /eth/v1/validator/{pubkey}/gas_limit`, back to the default gas limit.
+pub async fn delete_gas_limit(
+    State(context): State<KeymanagerContext>,
+    Path(pubkey): Path<BLSPubkey>,
+) -> Result<StatusCode, KeymanagerError> {
+    check_validator(&context, &pubkey)?;
+    write_config(&context, pubkey, |settings| settings.builder = None);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn check_validator(context: &KeymanagerContext, pubkey: &BLSPubkey) -> Result<(), KeymanagerError> {
+    if !context.store.has_validator(pubkey) {
+        return Err(KeymanagerError::NotFound(format!(
+            "validator {pubkey} not found"
+        )));
+    }
+    Ok(())
+}
+
+fn read_config(context: &KeymanagerContext) -> RwLockReadGuard<'_, ProposerConfig> {
+    context
+        .proposer_config
+        .read()
+        .expect("proposer config lock poisoned")
+}
+
+fn write_config(
+    context: &KeymanagerContext,
+    pubkey: BLSPubkey,
+    update: impl FnOnce(&mut ProposerSettings),
+) {
+    context
+        .proposer_config
+        .write()
+        .expect("proposer config lock poisoned")
+        .update(pubkey, update);
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_bls::PrivateKey;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        keymanager::tests::{serve, TOKEN},
+        proposer_config::DEFAULT_GAS_LIMIT,
+    };
+
+    #[tokio::test]
+    async fn test_proposer_settings() {
+        let (url, context, _dir) = serve().await;
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        context.store.add_key(key);
+        let http = reqwest::Client::new();
+        let fee_recipient = url
+            .join(&format!("/eth/v1/validator/{pubkey}/feerecipient"))
+            .unwrap();
+        let gas_limit = url
+            .join(&format!("/eth/v1/validator/{pubkey}/gas_limit"))
+            .unwrap();
+
+        let response = http
+            .get(fee_recipient.clone())
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = http
+            .post(fee_recipient.clone())
+            .bearer_auth(TOKEN)
+            .json(&json!({ "ethaddress": Address::repeat_byte(1) }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let response: Value = http
+            .get(fee_recipient.clone())
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            response["data"],
+            json!({ "pubkey": pubkey, "ethaddress": Address::repeat_byte(1) })
+        );
+
+        let response = http
+            .post(gas_limit.clone())
+            .bearer_auth(TOKEN)
+            .json(&json!({ "gas_limit": "36000000" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(read_config(&context).gas_limit(&pubkey), 36_000_000);
+        let response = http
+            .delete(gas_limit.clone())
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response: Value = http
+            .get(gas_limit)
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["data"]["gas_limit"], DEFAULT_GAS_LIMIT.to_string());
+
+        let response = http
+            .delete(fee_recipient)
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(read_config(&context).proposer_config.is_empty());
+
+        let other = BLSPubkey::repeat_byte(1);
+        let response = http
+            .get(
+                url.join(&format!("/eth/v1/validator/{other}/gas_limit"))
+                    .unwrap(),
+            )
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
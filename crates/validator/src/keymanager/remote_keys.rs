@@ -0,0 +1,160 @@
+use axum::{extract::State, Json};
+use ream_consensus::bls::BLSPubkey;
+use ream_rpc::response::DataResponse;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use url::Url;
+
+use super::{
+    DeleteKeysRequest, DeleteStatus, ImportStatus, KeyStatus, KeymanagerContext, KeymanagerError,
+};
+use crate::{validator_store::Signer, web3signer::Web3SignerClient};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteKey {
+    pub pubkey: BLSPubkey,
+    pub url: Url,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteKeyInfo {
+    pub pubkey: BLSPubkey,
+    pub url: Url,
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportRemoteKeysRequest {
+    pub remote_keys: Vec<RemoteKey>,
+}
+
+/// `GET /eth/v1/remotekeys`, the keys signing through a Web3Signer.
+pub async fn get_remote_keys(
+    State(context): State<KeymanagerContext>,
+) -> Json<DataResponse<Vec<RemoteKeyInfo>>> {
+    let mut keys: Vec<RemoteKeyInfo> = context
+        .store
+        .pubkeys()
+        .into_iter()
+        .filter_map(|pubkey| match context.store.signer(&pubkey)? {
+            Signer::Web3Signer(client) => Some(RemoteKeyInfo {
+                pubkey,
+                url: client.endpoint().clone(),
+                readonly: false,
+            }),
+            Signer::Local(_) => None,
+        })
+        .collect();
+    keys.sort_by_key(|key| key.pubkey);
+    Json(DataResponse::new(keys))
+}
+
+/// `POST /eth/v1/remotekeys`
+///
+/// Starts signing with the keys through their Web3Signer, until the client restarts.
+pub async fn post_remote_keys(
+    State(context): State<KeymanagerContext>,
+    Json(request): Json<ImportRemoteKeysRequest>,
+) -> Result<Json<DataResponse<Vec<KeyStatus<ImportStatus>>>>, KeymanagerError> {
+    let statuses = request
+        .remote_keys
+        .into_iter()
+        .map(|RemoteKey { pubkey, url }| {
+            if context.store.has_validator(&pubkey) {
+                return KeyStatus::new(ImportStatus::Duplicate);
+            }
+            match Web3SignerClient::new(url, None) {
+                Ok(client) => {
+                    info!("Signing for validator {pubkey} with {}", client.endpoint());
+                    context.store.add_signer(pubkey, Signer::Web3Signer(client));
+                    KeyStatus::new(ImportStatus::Imported)
+                }
+                Err(err) => KeyStatus::with_message(ImportStatus::Error, err),
+            }
+        })
+        .collect();
+    Ok(Json(DataResponse::new(statuses)))
+}
+
+/// `DELETE /eth/v1/remotekeys`, stops signing with the keys.
+pub async fn delete_remote_keys(
+    State(context): State<KeymanagerContext>,
+    Json(request): Json<DeleteKeysRequest>,
+) -> Result<Json<DataResponse<Vec<KeyStatus<DeleteStatus>>>>, KeymanagerError> {
+    let statuses = request
+        .pubkeys
+        .iter()
+        .map(|pubkey| match context.store.signer(pubkey) {
+            Some(Signer::Web3Signer(_)) => {
+                context.store.remove_signer(pubkey);
+                info!("Deleted validator {pubkey}");
+                KeyStatus::new(DeleteStatus::Deleted)
+            }
+            _ => KeyStatus::new(DeleteStatus::NotFound),
+        })
+        .collect();
+    Ok(Json(DataResponse::new(statuses)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::keymanager::tests::{serve, TOKEN};
+
+    #[tokio::test]
+    async fn test_remote_keys() {
+        let (url, context, _dir) = serve().await;
+        let url = url.join("/eth/v1/remotekeys").unwrap();
+        let http = reqwest::Client::new();
+        let pubkey = BLSPubkey::repeat_byte(1);
+
+        let request = json!({
+            "remote_keys": [
+                { "pubkey": pubkey, "url": "http://localhost:9000" },
+                { "pubkey": pubkey, "url": "http://localhost:9000" },
+            ],
+        });
+        let response: Value = http
+            .post(url.clone())
+            .bearer_auth(TOKEN)
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["data"][0]["status"], "imported");
+        assert_eq!(response["data"][1]["status"], "duplicate");
+
+        let response: Value = http
+            .get(url.clone())
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            response["data"],
+            json!([{ "pubkey": pubkey, "url": "http://localhost:9000/", "readonly": false }])
+        );
+
+        let response: Value = http
+            .delete(url)
+            .bearer_auth(TOKEN)
+            .json(&json!({ "pubkeys": [pubkey, BLSPubkey::repeat_byte(2)] }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["data"][0]["status"], "deleted");
+        assert_eq!(response["data"][1]["status"], "not_found");
+        assert!(context.store.is_empty());
+    }
+}
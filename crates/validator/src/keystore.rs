@@ -0,0 +1,394 @@
+//! EIP-2335 keystores, validator secret keys encrypted with a password.
+
+use aes::Aes128;
+use alloy_primitives::hex;
+use ctr::{
+    cipher::{KeyIvInit, StreamCipher},
+    Ctr128BE,
+};
+use ream_bls::PrivateKey;
+use ream_consensus::bls::BLSPubkey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::ValidatorError;
+
+pub const KEYSTORE_VERSION: u32 = 4;
+const DERIVED_KEY_LENGTH: u32 = 32;
+/// scrypt cost of new keystores, that of EIP-2335.
+const SCRYPT_N: u32 = 1 << 18;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub dklen: u32,
+    pub n: u32,
+    pub p: u32,
+    pub r: u32,
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pbkdf2Params {
+    pub dklen: u32,
+    pub c: u32,
+    pub prf: String,
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt(ScryptParams),
+    Pbkdf2(Pbkdf2Params),
+}
+
+impl KdfParams {
+    /// scrypt as EIP-2335 recommends, with a random salt.
+    pub fn scrypt() -> Self {
+        KdfParams::Scrypt(ScryptParams {
+            dklen: DERIVED_KEY_LENGTH,
+            n: SCRYPT_N,
+            p: 1,
+            r: 8,
+            salt: rand::random::<[u8; 32]>().to_vec(),
+        })
+    }
+
+    fn function(&self) -> &'static str {
+        match self {
+            KdfParams::Scrypt(_) => "scrypt",
+            KdfParams::Pbkdf2(_) => "pbkdf2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmptyParams {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherParams {
+    #[serde(with = "hex_bytes")]
+    pub iv: Vec<u8>,
+}
+
+/// A step of the decryption, naming its function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module<P> {
+    pub function: String,
+    pub params: P,
+    #[serde(with = "hex_bytes")]
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Crypto {
+    pub kdf: Module<KdfParams>,
+    pub checksum: Module<EmptyParams>,
+    pub cipher: Module<CipherParams>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub crypto: Crypto,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(with = "hex_bytes")]
+    pub pubkey: Vec<u8>,
+    /// EIP-2334 derivation path of the key, empty when not derived.
+    #[serde(default)]
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+impl Keystore {
+    pub fn from_json(json: &str) -> Result<Self, ValidatorError> {
+        let keystore: Self = serde_json::from_str(json)
+            .map_err(|err| ValidatorError::Keystore(format!("invalid keystore: {err}")))?;
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(ValidatorError::Keystore(format!(
+                "unsupported keystore version {}",
+                keystore.version
+            )));
+        }
+        Ok(keystore)
+    }
+
+    /// Encrypts `key` with `password`, `path` being the EIP-2334 path it was derived at if any.
+    pub fn encrypt(
+        key: &PrivateKey,
+        password: &str,
+        path: &str,
+        kdf: KdfParams,
+    ) -> Result<Self, ValidatorError> {
+        let kdf = Module {
+            function: kdf.function().to_string(),
+            params: kdf,
+            message: vec![],
+        };
+        let encryption_key = derive_key(&kdf, &normalize_password(password))?;
+        let iv = rand::random::<[u8; 16]>();
+        let mut secret = key.to_bytes().to_vec();
+        Ctr128BE::<Aes128>::new_from_slices(&encryption_key[..16], &iv)
+            .map_err(|_| ValidatorError::Keystore("invalid cipher IV".to_string()))?
+            .apply_keystream(&mut secret);
+        let mut preimage = encryption_key[16..32].to_vec();
+        preimage.extend_from_slice(&secret);
+
+        Ok(Self {
+            crypto: Crypto {
+                kdf,
+                checksum: Module {
+                    function: "sha256".to_string(),
+                    params: EmptyParams {},
+                    message: ethereum_hashing::hash(&preimage),
+                },
+                cipher: Module {
+                    function: "aes-128-ctr".to_string(),
+                    params: CipherParams { iv: iv.to_vec() },
+                    message: secret,
+                },
+            },
+            description: None,
+            pubkey: key.public_key().to_vec(),
+            path: path.to_string(),
+            uuid: random_uuid(),
+            version: KEYSTORE_VERSION,
+        })
+    }
+
+    /// The public key the keystore claims to hold, checked against the secret on decryption.
+    pub fn pubkey(&self) -> Result<BLSPubkey, ValidatorError> {
+        BLSPubkey::try_from(self.pubkey.as_slice())
+            .map_err(|_| ValidatorError::Keystore("invalid public key".to_string()))
+    }
+
+    /// Decrypts the secret key, failing on a wrong password or when the secret isn't that of
+    /// the keystore's public key.
+    pub fn decrypt(&self, password: &str) -> Result<PrivateKey, ValidatorError> {
+        let crypto = &self.crypto;
+        let decryption_key = derive_key(&crypto.kdf, &normalize_password(password))?;
+
+        if crypto.checksum.function != "sha256" {
+            return Err(ValidatorError::Keystore(format!(
+                "unsupported checksum function {}",
+                crypto.checksum.function
+            )));
+        }
+        let mut preimage = decryption_key[16..32].to_vec();
+        preimage.extend_from_slice(&crypto.cipher.message);
+        if ethereum_hashing::hash(&preimage) != crypto.checksum.message {
+            return Err(ValidatorError::Keystore("wrong password".to_string()));
+        }
+
+        if crypto.cipher.function != "aes-128-ctr" {
+            return Err(ValidatorError::Keystore(format!(
+                "unsupported cipher function {}",
+                crypto.cipher.function
+            )));
+        }
+        let mut secret = crypto.cipher.message.clone();
+        Ctr128BE::<Aes128>::new_from_slices(&decryption_key[..16], &crypto.cipher.params.iv)
+            .map_err(|_| ValidatorError::Keystore("invalid cipher IV".to_string()))?
+            .apply_keystream(&mut secret);
+
+        let key = PrivateKey::from_bytes(&secret)
+            .map_err(|err| ValidatorError::Keystore(format!("invalid secret key: {err}")))?;
+        if key.public_key() != self.pubkey()? {
+            return Err(ValidatorError::Keystore(
+                "secret key doesn't match the public key".to_string(),
+            ));
+        }
+        Ok(key)
+    }
+}
+
+/// The password as UTF-8 bytes of its NFKD form, without control codes.
+pub fn normalize_password(password: &str) -> Vec<u8> {
+    password
+        .nfkd()
+        .filter(|c| !matches!(*c as u32, 0x00..=0x1f | 0x7f..=0x9f))
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn derive_key(kdf: &Module<KdfParams>, password: &[u8]) -> Result<Vec<u8>, ValidatorError> {
+    let mut key = vec![0; DERIVED_KEY_LENGTH as usize];
+    match (kdf.function.as_str(), &kdf.params) {
+        ("scrypt", KdfParams::Scrypt(params)) => {
+            if params.dklen != DERIVED_KEY_LENGTH || !params.n.is_power_of_two() {
+                return Err(ValidatorError::Keystore(
+                    "invalid scrypt parameters".to_string(),
+                ));
+            }
+            let scrypt_params = scrypt::Params::new(
+                params.n.trailing_zeros() as u8,
+                params.r,
+                params.p,
+                DERIVED_KEY_LENGTH as usize,
+            )
+            .map_err(|err| ValidatorError::Keystore(format!("invalid scrypt parameters: {err}")))?;
+            scrypt::scrypt(password, &params.salt, &scrypt_params, &mut key)
+                .map_err(|err| ValidatorError::Keystore(format!("scrypt failed: {err}")))?;
+        }
+        ("pbkdf2", KdfParams::Pbkdf2(params)) => {
+            if params.dklen != DERIVED_KEY_LENGTH || params.prf != "hmac-sha256" {
+                return Err(ValidatorError::Keystore(
+                    "invalid pbkdf2 parameters".to_string(),
+                ));
+            }
+            pbkdf2::pbkdf2_hmac::<Sha256>(password, &params.salt, params.c, &mut key);
+        }
+        (function, _) => {
+            return Err(ValidatorError::Keystore(format!(
+                "unsupported or misconfigured key derivation function {function}"
+            )))
+        }
+    }
+    Ok(key)
+}
+
+/// A version 4 UUID.
+fn random_uuid() -> String {
+    let mut bytes = rand::random::<[u8; 16]>();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Hex without the `0x` prefix, as keystores encode bytes.
+mod hex_bytes {
+    use alloy_primitives::hex;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloy_primitives::hex;
+    use serde_json::json;
+
+    use super::*;
+
+    pub(crate) const PASSWORD: &str = "𝔱𝔢𝔰𝔱𝔭𝔞𝔰𝔰𝔴𝔬𝔯𝔡🔑";
+    pub(crate) const SECRET: &str =
+        "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+    pub(crate) const PUBKEY: &str = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+    /// The keystores of EIP-2335 with cheaper key derivation, 16 rounds instead of 262144.
+    pub(crate) fn keystore_json(kdf: &str) -> String {
+        let salt = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3";
+        let (kdf, checksum, message) = match kdf {
+            "scrypt" => (
+                json!({
+                    "function": "scrypt",
+                    "params": { "dklen": 32, "n": 16, "p": 1, "r": 8, "salt": salt },
+                    "message": "",
+                }),
+                "01fdeadd92f9333bcb830fb2c6de4c9f906c91cb84b77435d886722fa4c418f2",
+                "32da0474576fbb8f22eb7935f0b32e48d142e94431f0e93bf56f55961ce99520",
+            ),
+            _ => (
+                json!({
+                    "function": "pbkdf2",
+                    "params": { "dklen": 32, "c": 16, "prf": "hmac-sha256", "salt": salt },
+                    "message": "",
+                }),
+                "28751e1411954ff1e93a3dcf128c0a44b20a99159ac8aad39ca34cb9a3cfd13a",
+                "88885f5836629ae359f7954334cfa3a1cdd6cbdf549825f18c05d027c2874cc9",
+            ),
+        };
+        json!({
+            "crypto": {
+                "kdf": kdf,
+                "checksum": { "function": "sha256", "params": {}, "message": checksum },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": { "iv": "264daa3f303d7259501c93d997d84fe6" },
+                    "message": message,
+                },
+            },
+            "description": "This is a test keystore.",
+            "pubkey": PUBKEY,
+            "path": "m/12381/60/3141592653/589793238",
+            "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+            "version": 4,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_normalize_password() {
+        assert_eq!(normalize_password(PASSWORD), "testpassword🔑".as_bytes());
+        assert_eq!(normalize_password("pass\u{7f}word\n"), b"password");
+    }
+
+    #[test]
+    fn test_decrypt() {
+        for kdf in ["scrypt", "pbkdf2"] {
+            let keystore = Keystore::from_json(&keystore_json(kdf)).unwrap();
+            let key = keystore.decrypt(PASSWORD).unwrap();
+            assert_eq!(hex::encode(key.to_bytes()), SECRET);
+            assert_eq!(hex::encode(keystore.pubkey().unwrap()), PUBKEY);
+
+            assert!(matches!(
+                keystore.decrypt("wrong password"),
+                Err(ValidatorError::Keystore(message)) if message == "wrong password"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let key = PrivateKey::from_bytes(&hex::decode(SECRET).unwrap()).unwrap();
+        let kdf = KdfParams::Pbkdf2(Pbkdf2Params {
+            dklen: 32,
+            c: 16,
+            prf: "hmac-sha256".to_string(),
+            salt: vec![1; 32],
+        });
+        let keystore = Keystore::encrypt(&key, PASSWORD, "m/12381/3600/0/0/0", kdf).unwrap();
+        assert_eq!(keystore.crypto.kdf.function, "pbkdf2");
+        assert_eq!(hex::encode(keystore.pubkey().unwrap()), PUBKEY);
+        assert_eq!(keystore.uuid.len(), 36);
+        assert_eq!(&keystore.uuid[14..15], "4");
+
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore = Keystore::from_json(&json).unwrap();
+        assert_eq!(
+            keystore.decrypt(PASSWORD).unwrap().to_bytes(),
+            key.to_bytes()
+        );
+        assert!(keystore.decrypt("wrong password").is_err());
+        assert!(matches!(KdfParams::scrypt(), KdfParams::Scrypt(params) if params.n == 262144));
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let keystore = Keystore::from_json(&keystore_json("scrypt")).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        assert_eq!(Keystore::from_json(&json).unwrap(), keystore);
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["version"] = json!(3);
+        assert!(Keystore::from_json(&value.to_string()).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+pub mod attestation;
+pub mod beacon_node;
+pub mod block;
+pub mod deposit;
+pub mod duties;
+pub mod error;
+pub mod graffiti;
+pub mod inclusion;
+pub mod key_derivation;
+pub mod key_loader;
+pub mod keymanager;
+pub mod keystore;
+pub mod metrics;
+pub mod preparation;
+pub mod proposer_config;
+pub mod service;
+pub mod slashing_protection;
+pub mod slot_clock;
+pub mod validator_store;
+pub mod web3signer;
@@ -0,0 +1,13 @@
+//! Metrics of the validator client's own attestations.
+
+use ream_metrics::{histogram, int_counter, Histogram, IntCounter, Metric};
+
+pub static INCLUSION_DISTANCE: Metric<Histogram> = histogram!(
+    "ream_validator_attestation_inclusion_distance",
+    "Slots between an attestation and the block that included it, 1 at best",
+    [1.0, 2.0, 3.0, 4.0, 8.0, 16.0, 32.0]
+);
+pub static MISSED_ATTESTATIONS: Metric<IntCounter> = int_counter!(
+    "ream_validator_attestations_missed_total",
+    "Attestations not included by any block in time"
+);
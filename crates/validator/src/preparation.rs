@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::Address;
+use ream_builder::types::{SignedValidatorRegistrationV1, ValidatorRegistrationV1};
+use ream_consensus::bls::BLSPubkey;
+use ream_rpc::handlers::preparation::ProposerPreparation;
+use tracing::warn;
+
+use crate::{
+    beacon_node::BeaconNodeClient, error::ValidatorError, proposer_config::ProposerConfig,
+    validator_store::ValidatorStore,
+};
+
+/// Tells the beacon node the fee recipient and gas limit of each validator, signing a new
+/// builder registration only when they change so builders see a stable timestamp.
+#[derive(Debug, Default)]
+pub struct PreparationService {
+    registrations: HashMap<BLSPubkey, SignedValidatorRegistrationV1>,
+}
+
+impl PreparationService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends the fee recipients and registrations of the validators of `indices`, pairs of
+    /// pubkey and validator index. Validators without a fee recipient are left out, as are the
+    /// registrations failing to sign.
+    pub async fn prepare(
+        &mut self,
+        beacon_node: &BeaconNodeClient,
+        store: &ValidatorStore,
+        indices: &[(BLSPubkey, u64)],
+        config: &ProposerConfig,
+    ) -> Result<(), ValidatorError> {
+        let mut preparations = vec![];
+        let mut registrations = vec![];
+        for &(pubkey, validator_index) in indices {
+            let Some(fee_recipient) = config.fee_recipient(&pubkey) else {
+                continue;
+            };
+            preparations.push(ProposerPreparation {
+                validator_index,
+                fee_recipient,
+            });
+            match self
+                .registration(store, pubkey, fee_recipient, config.gas_limit(&pubkey))
+                .await
+            {
+                Ok(registration) => registrations.push(registration),
+                Err(err) => warn!("Failed to sign the registration of validator {pubkey}: {err}"),
+            }
+        }
+        if preparations.is_empty() {
+            return Ok(());
+        }
+        beacon_node.prepare_beacon_proposer(&preparations).await?;
+        if !registrations.is_empty() {
+            beacon_node.register_validators(&registrations).await?;
+        }
+        Ok(())
+    }
+
+    async fn registration(
+        &mut self,
+        store: &ValidatorStore,
+        pubkey: BLSPubkey,
+        fee_recipient: Address,
+        gas_limit: u64,
+    ) -> Result<SignedValidatorRegistrationV1, ValidatorError> {
+        if let Some(registration) = self.registrations.get(&pubkey) {
+            let message = &registration.message;
+            if message.fee_recipient == fee_recipient && message.gas_limit == gas_limit {
+                return Ok(registration.clone());
+            }
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock before 1970")
+            .as_secs();
+        let registration = store
+            .sign_validator_registration(ValidatorRegistrationV1 {
+                fee_recipient,
+                gas_limit,
+                timestamp,
+                pubkey,
+            })
+            .await?;
+        self.registrations.insert(pubkey, registration.clone());
+        Ok(registration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use alloy_primitives::B256;
+    use axum::{extract::State, routing::post, Json, Router};
+    use ream_bls::PrivateKey;
+    use ream_consensus::fork_schedule::ForkSchedule;
+    use serde_json::Value;
+    use tokio::net::TcpListener;
+    use url::Url;
+
+    use super::*;
+    use crate::proposer_config::{BuilderSettings, ProposerSettings};
+
+    type Received = Arc<Mutex<Vec<Value>>>;
+
+    /// A beacon node recording the bodies of the preparations and registrations it receives.
+    async fn mock_beacon_node(received: Received) -> Url {
+        let record = |State(received): State<Received>, Json(body): Json<Value>| async move {
+            received.lock().unwrap().push(body);
+        };
+        let router = Router::new()
+            .route("/eth/v1/validator/prepare_beacon_proposer", post(record))
+            .route("/eth/v1/validator/register_validator", post(record))
+            .with_state(received);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prepare_proposers() {
+        let received = Received::default();
+        let beacon_node = BeaconNodeClient::new(mock_beacon_node(received.clone()).await);
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::ZERO);
+        let mut service = PreparationService::new();
+
+        // Nothing is sent without a fee recipient.
+        service
+            .prepare(
+                &beacon_node,
+                &store,
+                &[(pubkey, 5)],
+                &ProposerConfig::default(),
+            )
+            .await
+            .unwrap();
+        assert!(received.lock().unwrap().is_empty());
+
+        let mut config = ProposerConfig::from_defaults(ProposerSettings {
+            fee_recipient: Some(Address::repeat_byte(1)),
+            builder: None,
+        });
+        service
+            .prepare(&beacon_node, &store, &[(pubkey, 5)], &config)
+            .await
+            .unwrap();
+        let registration = {
+            let received = received.lock().unwrap();
+            assert_eq!(received[0][0]["validator_index"], "5");
+            assert_eq!(
+                received[0][0]["fee_recipient"],
+                Address::repeat_byte(1).to_string()
+            );
+            assert_eq!(received[1][0]["message"]["gas_limit"], "30000000");
+            received[1][0].clone()
+        };
+        let signed: SignedValidatorRegistrationV1 =
+            serde_json::from_value(registration.clone()).unwrap();
+        assert!(signed.verify_signature(ForkSchedule::mainnet().genesis_fork().version));
+
+        // Unchanged settings send the same registration, new ones sign another.
+        service
+            .prepare(&beacon_node, &store, &[(pubkey, 5)], &config)
+            .await
+            .unwrap();
+        assert_eq!(received.lock().unwrap()[3][0], registration);
+        config.default_config.builder = Some(BuilderSettings {
+            gas_limit: 36_000_000,
+        });
+        service
+            .prepare(&beacon_node, &store, &[(pubkey, 5)], &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            received.lock().unwrap()[5][0]["message"]["gas_limit"],
+            "36000000"
+        );
+    }
+}
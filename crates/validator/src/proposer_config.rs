@@ -0,0 +1,177 @@
+//! Fee recipients and gas limits of the proposers, in the proposer config format of Teku and
+//! Nimbus.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use alloy_primitives::Address;
+use ream_consensus::bls::BLSPubkey;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::{error::ValidatorError, key_loader::RELOAD_INTERVAL};
+
+/// Gas limit asked of builders when neither the config nor the command line sets one.
+pub const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct BuilderSettings {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gas_limit: u64,
+}
+
+/// Settings of one proposer, those left out fall back to the default ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct ProposerSettings {
+    pub fee_recipient: Option<Address>,
+    pub builder: Option<BuilderSettings>,
+}
+
+impl ProposerSettings {
+    /// Fills the settings left out with those of `fallback`.
+    fn or(self, fallback: &ProposerSettings) -> Self {
+        Self {
+            fee_recipient: self.fee_recipient.or(fallback.fee_recipient),
+            builder: self.builder.or(fallback.builder),
+        }
+    }
+}
+
+/// `{"proposer_config": {pubkey: settings}, "default_config": settings}`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProposerConfig {
+    #[serde(default)]
+    pub proposer_config: HashMap<BLSPubkey, ProposerSettings>,
+    #[serde(default)]
+    pub default_config: ProposerSettings,
+}
+
+impl ProposerConfig {
+    /// A config giving every proposer `settings`.
+    pub fn from_defaults(settings: ProposerSettings) -> Self {
+        Self {
+            proposer_config: HashMap::new(),
+            default_config: settings,
+        }
+    }
+
+    /// Reads the config file at `path`, its defaults completed with `defaults`.
+    pub fn load(path: &Path, defaults: &ProposerSettings) -> Result<Self, ValidatorError> {
+        let contents = fs::read(path).map_err(|error| ValidatorError::File {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let mut config: ProposerConfig = serde_json::from_slice(&contents)
+            .map_err(|err| ValidatorError::ProposerConfig(format!("{}: {err}", path.display())))?;
+        config.default_config = config.default_config.or(defaults);
+        Ok(config)
+    }
+
+    /// Where the fees of blocks proposed by `pubkey` go, `None` if the config sets none.
+    pub fn fee_recipient(&self, pubkey: &BLSPubkey) -> Option<Address> {
+        self.settings(pubkey).fee_recipient
+    }
+
+    pub fn gas_limit(&self, pubkey: &BLSPubkey) -> u64 {
+        self.settings(pubkey)
+            .builder
+            .map_or(DEFAULT_GAS_LIMIT, |builder| builder.gas_limit)
+    }
+
+    /// Changes the settings of `pubkey` alone, those left out falling back to the defaults.
+    pub fn update(&mut self, pubkey: BLSPubkey, update: impl FnOnce(&mut ProposerSettings)) {
+        let settings = self.proposer_config.entry(pubkey).or_default();
+        update(settings);
+        if *settings == ProposerSettings::default() {
+            self.proposer_config.remove(&pubkey);
+        }
+    }
+
+    fn settings(&self, pubkey: &BLSPubkey) -> ProposerSettings {
+        self.proposer_config
+            .get(pubkey)
+            .map_or(self.default_config, |settings| {
+                settings.or(&self.default_config)
+            })
+    }
+}
+
+/// Re-reads the proposer config file at `path` every [`RELOAD_INTERVAL`], replacing `config`
+/// when the file changed, `config` being loaded from it when spawned. Changes made to `config`
+/// meanwhile, through the keymanager API, are kept until the file changes. An invalid file is
+/// reported and the current config kept. Meant to be spawned.
+pub async fn watch_proposer_config(
+    path: PathBuf,
+    defaults: ProposerSettings,
+    config: Arc<RwLock<ProposerConfig>>,
+) {
+    let mut loaded = config
+        .read()
+        .expect("proposer config lock poisoned")
+        .clone();
+    loop {
+        tokio::time::sleep(RELOAD_INTERVAL).await;
+        match ProposerConfig::load(&path, &defaults) {
+            Ok(new) if new != loaded => {
+                *config.write().expect("proposer config lock poisoned") = new.clone();
+                loaded = new;
+                info!("Reloaded the proposer config {}", path.display());
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Keeping the current proposer config: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_proposer_config() {
+        let (first, second) = (BLSPubkey::repeat_byte(1), BLSPubkey::repeat_byte(2));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proposer_config.json");
+        fs::write(
+            &path,
+            json!({
+                "proposer_config": {
+                    first.to_string(): {
+                        "fee_recipient": Address::repeat_byte(1),
+                        "builder": { "enabled": true, "gas_limit": "36000000" }
+                    },
+                    second.to_string(): { "builder": { "gas_limit": "20000000" } }
+                },
+                "default_config": { "fee_recipient": Address::repeat_byte(9) }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let defaults = ProposerSettings {
+            fee_recipient: Some(Address::repeat_byte(3)),
+            builder: Some(BuilderSettings {
+                gas_limit: 25_000_000,
+            }),
+        };
+        let config = ProposerConfig::load(&path, &defaults).unwrap();
+
+        assert_eq!(config.fee_recipient(&first), Some(Address::repeat_byte(1)));
+        assert_eq!(config.gas_limit(&first), 36_000_000);
+        // The file's defaults come before those of the command line.
+        assert_eq!(config.fee_recipient(&second), Some(Address::repeat_byte(9)));
+        assert_eq!(config.gas_limit(&second), 20_000_000);
+        let other = BLSPubkey::repeat_byte(3);
+        assert_eq!(config.fee_recipient(&other), Some(Address::repeat_byte(9)));
+        assert_eq!(config.gas_limit(&other), 25_000_000);
+
+        let config = ProposerConfig::default();
+        assert_eq!(config.fee_recipient(&other), None);
+        assert_eq!(config.gas_limit(&other), DEFAULT_GAS_LIMIT);
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use alloy_primitives::B256;
+use ream_consensus::{constants::SECONDS_PER_SLOT, misc::compute_epoch_at_slot};
+use ream_metrics::{inc_counter, observe};
+use ream_rpc::context::GenesisInfo;
+use tracing::{debug, info, warn};
+
+use crate::{
+    attestation::attest, beacon_node::BeaconNodeClient, block::propose, duties::DutiesService,
+    error::ValidatorError, inclusion::InclusionTracker, metrics, preparation::PreparationService,
+    proposer_config::ProposerConfig, slot_clock::SlotClock, validator_store::ValidatorStore,
+};
+
+/// How far into its slot an attestation is made, leaving the block of the slot time to arrive.
+pub const ATTESTATION_DUE: Duration = Duration::from_secs(SECONDS_PER_SLOT / 3);
+/// Delay between attempts to reach a beacon node that doesn't answer.
+const RETRY_DELAY: Duration = Duration::from_secs(SECONDS_PER_SLOT);
+
+/// Performs the duties of the validators of a [`ValidatorStore`] through a beacon node.
+pub struct ValidatorClient {
+    beacon_node: BeaconNodeClient,
+    store: Arc<ValidatorStore>,
+    duties: DutiesService,
+    inclusion: Arc<Mutex<InclusionTracker>>,
+    clock: SlotClock,
+    graffiti: B256,
+    /// Reloaded while the client runs, read at every preparation.
+    proposer_config: Arc<RwLock<ProposerConfig>>,
+    preparation: PreparationService,
+    /// Epoch the proposers were last prepared for.
+    prepared_epoch: Option<u64>,
+}
+
+impl ValidatorClient {
+    pub fn new(
+        beacon_node: BeaconNodeClient,
+        store: Arc<ValidatorStore>,
+        genesis_time: u64,
+        graffiti: B256,
+        proposer_config: Arc<RwLock<ProposerConfig>>,
+    ) -> Self {
+        Self {
+            beacon_node,
+            store,
+            duties: DutiesService::new(),
+            inclusion: Arc::new(Mutex::new(InclusionTracker::new())),
+            clock: SlotClock::new(genesis_time),
+            graffiti,
+            proposer_config,
+            preparation: PreparationService::new(),
+            prepared_epoch: None,
+        }
+    }
+
+    /// Runs the duties of every slot forever: proposals at the start of the slot and
+    /// attestations at [`ATTESTATION_DUE`]. Duties are fetched at the first slot of each epoch
+    /// and retried every slot until the beacon node serves them, as are the fee recipients and
+    /// builder registrations of the proposers. The block of the previous slot is checked for
+    /// the attestations published.
+    pub async fn run(mut self) {
+        info!(
+            "Running {} validators through {}",
+            self.store.len(),
+            self.beacon_node.endpoint()
+        );
+        loop {
+            let Some(slot) = self.clock.now() else {
+                tokio::time::sleep(self.clock.duration_to(0, Duration::ZERO)).await;
+                continue;
+            };
+            let epoch = compute_epoch_at_slot(slot);
+            if !self.duties.has_epoch(epoch) {
+                if let Err(err) = self
+                    .duties
+                    .update(&self.beacon_node, &self.store, epoch)
+                    .await
+                {
+                    warn!("Failed to fetch the duties of epoch {epoch}: {err}");
+                }
+            }
+
+            for duty in self.duties.proposers_at(slot) {
+                let (beacon_node, store) = (self.beacon_node.clone(), self.store.clone());
+                let graffiti = self.graffiti;
+                tokio::spawn(async move {
+                    if let Err(err) = propose(&beacon_node, &store, &duty, graffiti).await {
+                        warn!(
+                            "Failed to propose the block of validator {} at slot {slot}: {err}",
+                            duty.validator_index
+                        );
+                    }
+                });
+            }
+
+            let attesters = self.duties.attesters_at(slot);
+            if !attesters.is_empty() {
+                let (beacon_node, store) = (self.beacon_node.clone(), self.store.clone());
+                let inclusion = self.inclusion.clone();
+                let until_due = self.clock.duration_to(slot, ATTESTATION_DUE);
+                tokio::spawn(async move {
+                    tokio::time::sleep(until_due).await;
+                    match attest(&beacon_node, &store, slot, &attesters).await {
+                        Ok(attested) => {
+                            let mut inclusion =
+                                inclusion.lock().expect("inclusion tracker lock poisoned");
+                            for (duty, data) in attested {
+                                inclusion.track(&duty, data);
+                            }
+                        }
+                        Err(err) => warn!("Failed to attest at slot {slot}: {err}"),
+                    }
+                });
+            }
+
+            if self.prepared_epoch != Some(epoch) {
+                match self.prepare_proposers().await {
+                    Ok(()) => self.prepared_epoch = Some(epoch),
+                    Err(err) => warn!("Failed to prepare the proposers of epoch {epoch}: {err}"),
+                }
+            }
+
+            if slot > 0 {
+                tokio::spawn(check_inclusion(
+                    self.beacon_node.clone(),
+                    self.inclusion.clone(),
+                    slot,
+                ));
+            }
+
+            tokio::time::sleep(self.clock.duration_to(slot + 1, Duration::ZERO)).await;
+        }
+    }
+
+    async fn prepare_proposers(&mut self) -> Result<(), ValidatorError> {
+        let indices: Vec<_> = self
+            .store
+            .pubkeys()
+            .into_iter()
+            .filter_map(|pubkey| Some((pubkey, self.duties.index_of(&pubkey)?)))
+            .collect();
+        let config = self
+            .proposer_config
+            .read()
+            .expect("proposer config lock poisoned")
+            .clone();
+        self.preparation
+            .prepare(&self.beacon_node, &self.store, &indices, &config)
+            .await
+    }
+}
+
+/// Reports the tracked attestations the block of the slot before `slot` included and those too
+/// old to be included from `slot` on.
+async fn check_inclusion(
+    beacon_node: BeaconNodeClient,
+    inclusion: Arc<Mutex<InclusionTracker>>,
+    slot: u64,
+) {
+    if inclusion
+        .lock()
+        .expect("inclusion tracker lock poisoned")
+        .is_empty()
+    {
+        return;
+    }
+    let block = match beacon_node.block_at_slot(slot - 1).await {
+        Ok(block) => block,
+        Err(err) => {
+            debug!("Failed to fetch the block of slot {}: {err}", slot - 1);
+            None
+        }
+    };
+
+    let mut inclusion = inclusion.lock().expect("inclusion tracker lock poisoned");
+    if let Some(block) = block {
+        for included in inclusion.process_block(&block.message) {
+            info!(
+                "Attestation of validator {} at slot {} included in slot {} with delay {}",
+                included.validator_index,
+                included.attestation_slot,
+                included.block_slot,
+                included.delay()
+            );
+            observe(&metrics::INCLUSION_DISTANCE, included.delay() as f64);
+        }
+    }
+    for (validator_index, attestation_slot) in inclusion.prune(slot) {
+        warn!(
+            "Attestation of validator {validator_index} at slot {attestation_slot} was not \
+             included"
+        );
+        inc_counter(&metrics::MISSED_ATTESTATIONS);
+    }
+}
+
+/// Fetches the genesis of the chain the beacon node follows, retrying until it has one.
+pub async fn wait_for_genesis(beacon_node: &BeaconNodeClient) -> GenesisInfo {
+    loop {
+        match beacon_node.genesis().await {
+            Ok(genesis) => return genesis,
+            Err(err) => {
+                warn!(
+                    "Waiting for the beacon node at {}: {err}",
+                    beacon_node.endpoint()
+                );
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! The EIP-3076 interchange format, moving the slashing protection history of validators
+//! between clients.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::B256;
+use ream_consensus::bls::BLSPubkey;
+use redb::ReadableTable;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    prune_attestations, SlashingDatabase, SlashingProtectionError, ATTESTATION_WATERMARKS,
+    GENESIS_VALIDATORS_ROOT_KEY, METADATA, SIGNED_ATTESTATIONS, SIGNED_BLOCKS,
+};
+
+pub const INTERCHANGE_FORMAT_VERSION: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchangeMetadata {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub interchange_format_version: u64,
+    pub genesis_validators_root: B256,
+}
+
+/// History of one validator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterchangeData {
+    pub pubkey: BLSPubkey,
+    pub signed_blocks: Vec<SignedBlock>,
+    pub signed_attestations: Vec<SignedAttestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlock {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<B256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedAttestation {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub source_epoch: u64,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub target_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<B256>,
+}
+
+impl SlashingDatabase {
+    /// Adds the history of `interchange` to the database, keeping what it already holds where
+    /// both have a message for the same slot or target. Nothing is imported if the interchange
+    /// is for another chain. Attestations far below the latest imported are pruned like those
+    /// signed.
+    pub fn import_interchange(
+        &self,
+        interchange: &Interchange,
+    ) -> Result<(), SlashingProtectionError> {
+        let metadata = &interchange.metadata;
+        if metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+            return Err(SlashingProtectionError::UnsupportedInterchangeVersion(
+                metadata.interchange_format_version,
+            ));
+        }
+        self.check_genesis_validators_root(metadata.genesis_validators_root)?;
+
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut blocks = write_txn.open_table(SIGNED_BLOCKS)?;
+            let mut attestations = write_txn.open_table(SIGNED_ATTESTATIONS)?;
+            let mut watermarks = write_txn.open_table(ATTESTATION_WATERMARKS)?;
+            for data in &interchange.data {
+                let pubkey = data.pubkey.as_slice();
+                for block in &data.signed_blocks {
+                    if blocks.get((pubkey, block.slot))?.is_none() {
+                        blocks.insert((pubkey, block.slot), root_bytes(&block.signing_root))?;
+                    }
+                }
+                for attestation in &data.signed_attestations {
+                    if attestation.source_epoch > attestation.target_epoch {
+                        return Err(SlashingProtectionError::SourceAfterTarget {
+                            source_epoch: attestation.source_epoch,
+                            target_epoch: attestation.target_epoch,
+                        });
+                    }
+                    let key = (pubkey, attestation.target_epoch);
+                    if attestations.get(key)?.is_none() {
+                        attestations.insert(
+                            key,
+                            (
+                                attestation.source_epoch,
+                                root_bytes(&attestation.signing_root),
+                            ),
+                        )?;
+                    }
+                }
+                if let Some(target_epoch) = data
+                    .signed_attestations
+                    .iter()
+                    .map(|attestation| attestation.target_epoch)
+                    .max()
+                {
+                    prune_attestations(&mut attestations, &mut watermarks, pubkey, target_epoch)?;
+                }
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// The whole history of the database, fails if it never protected a chain.
+    pub fn export_interchange(&self) -> Result<Interchange, SlashingProtectionError> {
+        self.export(None)
+    }
+
+    /// The history of the validators of `pubkeys`, those without one are left out.
+    pub fn export_interchange_of(
+        &self,
+        pubkeys: &[BLSPubkey],
+    ) -> Result<Interchange, SlashingProtectionError> {
+        self.export(Some(pubkeys))
+    }
+
+    fn export(
+        &self,
+        pubkeys: Option<&[BLSPubkey]>,
+    ) -> Result<Interchange, SlashingProtectionError> {
+        let exported = |pubkey: &[u8]| {
+            pubkeys.map_or(true, |pubkeys| {
+                pubkeys.iter().any(|wanted| wanted.as_slice() == pubkey)
+            })
+        };
+        let read_txn = self.db.begin_read()?;
+        let genesis_validators_root = read_txn
+            .open_table(METADATA)?
+            .get(GENESIS_VALIDATORS_ROOT_KEY)?
+            .map(|root| B256::from_slice(root.value()))
+            .ok_or(SlashingProtectionError::UnknownChain)?;
+
+        let mut data = BTreeMap::<BLSPubkey, InterchangeData>::new();
+        for entry in read_txn.open_table(SIGNED_BLOCKS)?.iter()? {
+            let (key, root) = entry?;
+            let (pubkey, slot) = key.value();
+            if !exported(pubkey) {
+                continue;
+            }
+            history(&mut data, pubkey).signed_blocks.push(SignedBlock {
+                slot,
+                signing_root: root_from_bytes(root.value()),
+            });
+        }
+        // The watermark stands for the pruned attestations, it is below every one kept.
+        for entry in read_txn.open_table(ATTESTATION_WATERMARKS)?.iter()? {
+            let (pubkey, watermark) = entry?;
+            let pubkey = pubkey.value();
+            if !exported(pubkey) {
+                continue;
+            }
+            let (source_epoch, target_epoch) = watermark.value();
+            history(&mut data, pubkey)
+                .signed_attestations
+                .push(SignedAttestation {
+                    source_epoch,
+                    target_epoch,
+                    signing_root: None,
+                });
+        }
+        for entry in read_txn.open_table(SIGNED_ATTESTATIONS)?.iter()? {
+            let (key, value) = entry?;
+            let (pubkey, target_epoch) = key.value();
+            if !exported(pubkey) {
+                continue;
+            }
+            let (source_epoch, root) = value.value();
+            history(&mut data, pubkey)
+                .signed_attestations
+                .push(SignedAttestation {
+                    source_epoch,
+                    target_epoch,
+                    signing_root: root_from_bytes(root),
+                });
+        }
+
+        Ok(Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root,
+            },
+            data: data.into_values().collect(),
+        })
+    }
+}
+
+fn history<'a>(
+    data: &'a mut BTreeMap<BLSPubkey, InterchangeData>,
+    pubkey: &[u8],
+) -> &'a mut InterchangeData {
+    let pubkey = BLSPubkey::from_slice(pubkey);
+    data.entry(pubkey).or_insert_with(|| InterchangeData {
+        pubkey,
+        signed_blocks: vec![],
+        signed_attestations: vec![],
+    })
+}
+
+/// Unknown roots are stored empty.
+fn root_bytes(root: &Option<B256>) -> &[u8] {
+    root.as_ref().map_or(&[][..], |root| root.as_slice())
+}
+
+fn root_from_bytes(bytes: &[u8]) -> Option<B256> {
+    (!bytes.is_empty()).then(|| B256::from_slice(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::slashing_protection::{tests::temp_database, Safe};
+
+    #[test]
+    fn test_interchange_round_trip() {
+        let pubkey = BLSPubkey::repeat_byte(1);
+        let json = json!({
+            "metadata": {
+                "interchange_format_version": "5",
+                "genesis_validators_root": B256::repeat_byte(9),
+            },
+            "data": [{
+                "pubkey": pubkey,
+                "signed_blocks": [
+                    { "slot": "81952", "signing_root": B256::repeat_byte(1) },
+                    { "slot": "81951" },
+                ],
+                "signed_attestations": [
+                    {
+                        "source_epoch": "2290",
+                        "target_epoch": "3007",
+                        "signing_root": B256::repeat_byte(2),
+                    },
+                    { "source_epoch": "2290", "target_epoch": "3008" },
+                ],
+            }],
+        });
+        let interchange: Interchange = serde_json::from_value(json.clone()).unwrap();
+
+        let (_dir, db) = temp_database();
+        db.import_interchange(&interchange).unwrap();
+        assert_eq!(
+            db.check_and_insert_block(&pubkey, 81952, B256::repeat_byte(1))
+                .unwrap(),
+            Safe::SameData
+        );
+        // Without a root the block can't be told apart from another one.
+        assert!(db
+            .check_and_insert_block(&pubkey, 81951, B256::repeat_byte(1))
+            .is_err());
+        assert!(db
+            .check_and_insert_attestation(&pubkey, 2290, 3008, B256::repeat_byte(2))
+            .is_err());
+
+        let mut exported = serde_json::to_value(db.export_interchange().unwrap()).unwrap();
+        exported["data"][0]["signed_blocks"]
+            .as_array_mut()
+            .unwrap()
+            .reverse();
+        assert_eq!(exported, json);
+
+        let other = db
+            .export_interchange_of(&[BLSPubkey::repeat_byte(2)])
+            .unwrap();
+        assert!(other.data.is_empty());
+        assert_eq!(
+            db.export_interchange_of(&[pubkey]).unwrap(),
+            db.export_interchange().unwrap()
+        );
+
+        let mut other_chain = interchange.clone();
+        other_chain.metadata.genesis_validators_root = B256::ZERO;
+        assert!(matches!(
+            db.import_interchange(&other_chain),
+            Err(SlashingProtectionError::WrongChain { .. })
+        ));
+        let mut other_version = interchange;
+        other_version.metadata.interchange_format_version = 4;
+        assert!(matches!(
+            db.import_interchange(&other_version),
+            Err(SlashingProtectionError::UnsupportedInterchangeVersion(4))
+        ));
+    }
+}
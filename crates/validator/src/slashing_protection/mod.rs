@@ -0,0 +1,458 @@
+//! Local record of what the validators signed, refusing to sign anything slashable or that
+//! EIP-3076 considers unsafe.
+
+pub mod interchange;
+
+use std::path::Path;
+
+use alloy_primitives::B256;
+use ream_consensus::bls::BLSPubkey;
+use redb::{Database, ReadableTable, Table, TableDefinition};
+
+/// `(pubkey, slot) -> signing root`, the root is empty when an interchange file didn't have it.
+const SIGNED_BLOCKS: TableDefinition<(&[u8], u64), &[u8]> = TableDefinition::new("signed_blocks");
+/// `(pubkey, target epoch) -> (source epoch, signing root)`.
+const SIGNED_ATTESTATIONS: AttestationsTable = TableDefinition::new("signed_attestations");
+/// `pubkey -> (source epoch, target epoch)`, the highest epochs of the attestations pruned from
+/// [`SIGNED_ATTESTATIONS`]. Like the low watermark of EIP-3076, nothing with a lower source or
+/// a target that isn't higher is signed, so a pruned attestation can't be surrounded.
+const ATTESTATION_WATERMARKS: TableDefinition<&[u8], (u64, u64)> =
+    TableDefinition::new("attestation_watermarks");
+const METADATA: TableDefinition<&str, &[u8]> = TableDefinition::new("metadata");
+
+const GENESIS_VALIDATORS_ROOT_KEY: &str = "genesis_validators_root";
+
+/// Epochs of attestations kept below the latest target signed, the surround check scans them
+/// all on each signing.
+pub const ATTESTATION_HISTORY_EPOCHS: u64 = 512;
+
+/// `(pubkey, target epoch)`.
+type AttestationKey = (&'static [u8], u64);
+/// `(source epoch, signing root)`.
+type AttestationValue = (u64, &'static [u8]);
+type AttestationsTable = TableDefinition<'static, AttestationKey, AttestationValue>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SlashingProtectionError {
+    #[error("slashing protection database error: {0}")]
+    Database(Box<redb::Error>),
+    #[error("already signed another block at slot {slot}")]
+    DoubleBlockProposal { slot: u64 },
+    #[error("slot {slot} is not after the earliest signed block at slot {min_slot}")]
+    BlockSlotTooLow { slot: u64, min_slot: u64 },
+    #[error("source epoch {source_epoch} is after target epoch {target_epoch}")]
+    SourceAfterTarget {
+        source_epoch: u64,
+        target_epoch: u64,
+    },
+    #[error("already signed another attestation with target epoch {target_epoch}")]
+    DoubleVote { target_epoch: u64 },
+    #[error(
+        "attestation from epoch {source_epoch} to {target_epoch} surrounds or is surrounded by \
+         the signed one from {previous_source_epoch} to {previous_target_epoch}"
+    )]
+    SurroundVote {
+        source_epoch: u64,
+        target_epoch: u64,
+        previous_source_epoch: u64,
+        previous_target_epoch: u64,
+    },
+    #[error(
+        "source epoch {source_epoch} is before the earliest signed source epoch {min_source_epoch}"
+    )]
+    SourceEpochTooLow {
+        source_epoch: u64,
+        min_source_epoch: u64,
+    },
+    #[error(
+        "target epoch {target_epoch} is not after the earliest signed target epoch \
+         {min_target_epoch}"
+    )]
+    TargetEpochTooLow {
+        target_epoch: u64,
+        min_target_epoch: u64,
+    },
+    #[error("the database protects the chain of genesis validators root {expected}, not {found}")]
+    WrongChain { expected: B256, found: B256 },
+    #[error("the database has not protected any chain yet")]
+    UnknownChain,
+    #[error("unsupported interchange format version {0}, only version 5 is supported")]
+    UnsupportedInterchangeVersion(u64),
+}
+
+macro_rules! impl_from_redb_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for SlashingProtectionError {
+                fn from(err: $error) -> Self {
+                    SlashingProtectionError::Database(Box::new(err.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_redb_error!(
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError
+);
+
+/// Whether a message is safe to sign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Safe {
+    /// Nothing signed conflicts with it, it is now recorded.
+    Valid,
+    /// The exact same message was signed before, signing it again is harmless.
+    SameData,
+}
+
+/// Signed blocks and attestations of every validator of the client, checked and recorded in
+/// the same transaction so concurrent signing can't race past a check.
+#[derive(Debug)]
+pub struct SlashingDatabase {
+    db: Database,
+}
+
+impl SlashingDatabase {
+    /// Opens the database at `path`, creating it and any missing table.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SlashingProtectionError> {
+        let db = Database::create(path)?;
+
+        let write_txn = db.begin_write()?;
+        write_txn.open_table(SIGNED_BLOCKS)?;
+        write_txn.open_table(SIGNED_ATTESTATIONS)?;
+        write_txn.open_table(ATTESTATION_WATERMARKS)?;
+        write_txn.open_table(METADATA)?;
+        write_txn.commit()?;
+
+        Ok(Self { db })
+    }
+
+    /// Genesis validators root of the chain the database protects, `None` until it is used.
+    pub fn genesis_validators_root(&self) -> Result<Option<B256>, SlashingProtectionError> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(METADATA)?;
+        Ok(table
+            .get(GENESIS_VALIDATORS_ROOT_KEY)?
+            .map(|root| B256::from_slice(root.value())))
+    }
+
+    /// Ties the database to the chain of `genesis_validators_root` on first use, fails if it
+    /// already protects another chain.
+    pub fn check_genesis_validators_root(
+        &self,
+        genesis_validators_root: B256,
+    ) -> Result<(), SlashingProtectionError> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(METADATA)?;
+            if let Some(expected) = table.get(GENESIS_VALIDATORS_ROOT_KEY)? {
+                let expected = B256::from_slice(expected.value());
+                if expected != genesis_validators_root {
+                    return Err(SlashingProtectionError::WrongChain {
+                        expected,
+                        found: genesis_validators_root,
+                    });
+                }
+                return Ok(());
+            }
+            table.insert(
+                GENESIS_VALIDATORS_ROOT_KEY,
+                genesis_validators_root.as_slice(),
+            )?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Records the block of `slot` signed by `pubkey` unless it conflicts with a signed one or
+    /// isn't after the earliest one signed.
+    pub fn check_and_insert_block(
+        &self,
+        pubkey: &BLSPubkey,
+        slot: u64,
+        signing_root: B256,
+    ) -> Result<Safe, SlashingProtectionError> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(SIGNED_BLOCKS)?;
+            let pubkey = pubkey.as_slice();
+            if let Some(previous) = table.get((pubkey, slot))? {
+                return if previous.value() == signing_root.as_slice() {
+                    Ok(Safe::SameData)
+                } else {
+                    Err(SlashingProtectionError::DoubleBlockProposal { slot })
+                };
+            }
+            if let Some(earliest) = table.range((pubkey, 0)..=(pubkey, u64::MAX))?.next() {
+                let min_slot = earliest?.0.value().1;
+                if slot <= min_slot {
+                    return Err(SlashingProtectionError::BlockSlotTooLow { slot, min_slot });
+                }
+            }
+            table.insert((pubkey, slot), signing_root.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(Safe::Valid)
+    }
+
+    /// Records the attestation from `source_epoch` to `target_epoch` signed by `pubkey` unless
+    /// it is a double or surround vote or older than the earliest one signed. Attestations more
+    /// than [`ATTESTATION_HISTORY_EPOCHS`] below the target are pruned into the watermark.
+    pub fn check_and_insert_attestation(
+        &self,
+        pubkey: &BLSPubkey,
+        source_epoch: u64,
+        target_epoch: u64,
+        signing_root: B256,
+    ) -> Result<Safe, SlashingProtectionError> {
+        if source_epoch > target_epoch {
+            return Err(SlashingProtectionError::SourceAfterTarget {
+                source_epoch,
+                target_epoch,
+            });
+        }
+
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(SIGNED_ATTESTATIONS)?;
+            let mut watermarks = write_txn.open_table(ATTESTATION_WATERMARKS)?;
+            let pubkey = pubkey.as_slice();
+            if let Some(previous) = table.get((pubkey, target_epoch))? {
+                return if previous.value().1 == signing_root.as_slice() {
+                    Ok(Safe::SameData)
+                } else {
+                    Err(SlashingProtectionError::DoubleVote { target_epoch })
+                };
+            }
+
+            let mut min_epochs: Option<(u64, u64)> = None;
+            for entry in table.range((pubkey, 0)..=(pubkey, u64::MAX))? {
+                let (key, value) = entry?;
+                let previous_target_epoch = key.value().1;
+                let previous_source_epoch = value.value().0;
+                let surrounds =
+                    source_epoch < previous_source_epoch && target_epoch > previous_target_epoch;
+                let surrounded =
+                    source_epoch > previous_source_epoch && target_epoch < previous_target_epoch;
+                if surrounds || surrounded {
+                    return Err(SlashingProtectionError::SurroundVote {
+                        source_epoch,
+                        target_epoch,
+                        previous_source_epoch,
+                        previous_target_epoch,
+                    });
+                }
+                // Entries are sorted by target, the first one has the lowest.
+                let (min_source_epoch, _) =
+                    min_epochs.get_or_insert((previous_source_epoch, previous_target_epoch));
+                *min_source_epoch = (*min_source_epoch).min(previous_source_epoch);
+            }
+            let watermark = watermarks.get(pubkey)?.map(|watermark| watermark.value());
+            for (min_source_epoch, min_target_epoch) in min_epochs.into_iter().chain(watermark) {
+                if source_epoch < min_source_epoch {
+                    return Err(SlashingProtectionError::SourceEpochTooLow {
+                        source_epoch,
+                        min_source_epoch,
+                    });
+                }
+                if target_epoch <= min_target_epoch {
+                    return Err(SlashingProtectionError::TargetEpochTooLow {
+                        target_epoch,
+                        min_target_epoch,
+                    });
+                }
+            }
+            table.insert(
+                (pubkey, target_epoch),
+                (source_epoch, signing_root.as_slice()),
+            )?;
+            prune_attestations(&mut table, &mut watermarks, pubkey, target_epoch)?;
+        }
+        write_txn.commit()?;
+        Ok(Safe::Valid)
+    }
+}
+
+/// Removes the attestations of `pubkey` more than [`ATTESTATION_HISTORY_EPOCHS`] below
+/// `target_epoch`, raising its watermark to the highest epochs removed.
+fn prune_attestations(
+    table: &mut Table<AttestationKey, AttestationValue>,
+    watermarks: &mut Table<&'static [u8], (u64, u64)>,
+    pubkey: &[u8],
+    target_epoch: u64,
+) -> Result<(), SlashingProtectionError> {
+    let Some(cutoff) = target_epoch.checked_sub(ATTESTATION_HISTORY_EPOCHS) else {
+        return Ok(());
+    };
+    let mut pruned = vec![];
+    for entry in table.range((pubkey, 0)..(pubkey, cutoff))? {
+        let (key, value) = entry?;
+        pruned.push((value.value().0, key.value().1));
+    }
+    if pruned.is_empty() {
+        return Ok(());
+    }
+    let (mut source_epoch, mut target_epoch) = watermarks
+        .get(pubkey)?
+        .map_or((0, 0), |watermark| watermark.value());
+    for (previous_source_epoch, previous_target_epoch) in pruned {
+        table.remove((pubkey, previous_target_epoch))?;
+        source_epoch = source_epoch.max(previous_source_epoch);
+        target_epoch = target_epoch.max(previous_target_epoch);
+    }
+    watermarks.insert(pubkey, (source_epoch, target_epoch))?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// An empty database in a temporary directory, which is deleted when dropped.
+    pub(crate) fn temp_database() -> (TempDir, SlashingDatabase) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SlashingDatabase::open(dir.path().join("slashing_protection.redb")).unwrap();
+        (dir, db)
+    }
+
+    #[test]
+    fn test_block_rules() {
+        let (_dir, db) = temp_database();
+        let pubkey = BLSPubkey::repeat_byte(1);
+
+        assert_eq!(
+            db.check_and_insert_block(&pubkey, 10, B256::repeat_byte(1))
+                .unwrap(),
+            Safe::Valid
+        );
+        assert_eq!(
+            db.check_and_insert_block(&pubkey, 10, B256::repeat_byte(1))
+                .unwrap(),
+            Safe::SameData
+        );
+        assert!(matches!(
+            db.check_and_insert_block(&pubkey, 10, B256::repeat_byte(2)),
+            Err(SlashingProtectionError::DoubleBlockProposal { slot: 10 })
+        ));
+        assert!(matches!(
+            db.check_and_insert_block(&pubkey, 9, B256::repeat_byte(2)),
+            Err(SlashingProtectionError::BlockSlotTooLow {
+                slot: 9,
+                min_slot: 10
+            })
+        ));
+        assert_eq!(
+            db.check_and_insert_block(&pubkey, 11, B256::repeat_byte(2))
+                .unwrap(),
+            Safe::Valid
+        );
+        // Other validators are unaffected.
+        assert_eq!(
+            db.check_and_insert_block(&BLSPubkey::repeat_byte(2), 9, B256::repeat_byte(3))
+                .unwrap(),
+            Safe::Valid
+        );
+    }
+
+    #[test]
+    fn test_attestation_rules() {
+        let (_dir, db) = temp_database();
+        let pubkey = BLSPubkey::repeat_byte(1);
+        let root = B256::repeat_byte(1);
+
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, 2, 4, root)
+                .unwrap(),
+            Safe::Valid
+        );
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, 2, 4, root)
+                .unwrap(),
+            Safe::SameData
+        );
+        assert!(matches!(
+            db.check_and_insert_attestation(&pubkey, 3, 4, B256::repeat_byte(2)),
+            Err(SlashingProtectionError::DoubleVote { target_epoch: 4 })
+        ));
+        assert!(matches!(
+            db.check_and_insert_attestation(&pubkey, 5, 3, root),
+            Err(SlashingProtectionError::SourceAfterTarget { .. })
+        ));
+        assert!(matches!(
+            db.check_and_insert_attestation(&pubkey, 1, 5, root),
+            Err(SlashingProtectionError::SurroundVote {
+                previous_source_epoch: 2,
+                previous_target_epoch: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            db.check_and_insert_attestation(&pubkey, 2, 3, root),
+            Err(SlashingProtectionError::TargetEpochTooLow {
+                min_target_epoch: 4,
+                ..
+            })
+        ));
+        assert_eq!(
+            db.check_and_insert_attestation(&pubkey, 5, 8, root)
+                .unwrap(),
+            Safe::Valid
+        );
+        assert!(matches!(
+            db.check_and_insert_attestation(&pubkey, 6, 7, root),
+            Err(SlashingProtectionError::SurroundVote { .. })
+        ));
+    }
+
+    #[test]
+    fn test_attestation_pruning() {
+        let (_dir, db) = temp_database();
+        let pubkey = BLSPubkey::repeat_byte(1);
+        let root = B256::repeat_byte(1);
+        db.check_and_insert_attestation(&pubkey, 0, 1, root)
+            .unwrap();
+        db.check_and_insert_attestation(&pubkey, 3, 4, root)
+            .unwrap();
+        db.check_and_insert_attestation(&pubkey, 600, 601, root)
+            .unwrap();
+
+        db.check_genesis_validators_root(B256::ZERO).unwrap();
+        let exported = db.export_interchange().unwrap();
+        let epochs: Vec<_> = exported.data[0]
+            .signed_attestations
+            .iter()
+            .map(|attestation| (attestation.source_epoch, attestation.target_epoch))
+            .collect();
+        // The watermark takes the place of both pruned attestations.
+        assert_eq!(epochs, [(3, 4), (600, 601)]);
+
+        // A double vote against a pruned attestation is still refused.
+        assert!(db
+            .check_and_insert_attestation(&pubkey, 3, 4, B256::repeat_byte(2))
+            .is_err());
+    }
+
+    #[test]
+    fn test_genesis_validators_root() {
+        let (_dir, db) = temp_database();
+        assert_eq!(db.genesis_validators_root().unwrap(), None);
+        db.check_genesis_validators_root(B256::repeat_byte(1))
+            .unwrap();
+        db.check_genesis_validators_root(B256::repeat_byte(1))
+            .unwrap();
+        assert!(matches!(
+            db.check_genesis_validators_root(B256::repeat_byte(2)),
+            Err(SlashingProtectionError::WrongChain { .. })
+        ));
+        assert_eq!(
+            db.genesis_validators_root().unwrap(),
+            Some(B256::repeat_byte(1))
+        );
+    }
+}
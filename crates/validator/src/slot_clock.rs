@@ -0,0 +1,58 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ream_consensus::constants::SECONDS_PER_SLOT;
+
+/// Maps wall clock time to the slots of a chain started at `genesis_time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotClock {
+    genesis_time: u64,
+}
+
+impl SlotClock {
+    pub fn new(genesis_time: u64) -> Self {
+        Self { genesis_time }
+    }
+
+    /// The current slot, `None` before genesis.
+    pub fn now(&self) -> Option<u64> {
+        self.slot_at(unix_time())
+    }
+
+    /// The slot at `time`, a duration since the unix epoch.
+    pub fn slot_at(&self, time: Duration) -> Option<u64> {
+        let genesis = Duration::from_secs(self.genesis_time);
+        time.checked_sub(genesis)
+            .map(|since_genesis| since_genesis.as_secs() / SECONDS_PER_SLOT)
+    }
+
+    /// When `slot` starts, as a duration since the unix epoch.
+    pub fn start_of(&self, slot: u64) -> Duration {
+        Duration::from_secs(self.genesis_time + slot * SECONDS_PER_SLOT)
+    }
+
+    /// Time left until `offset` into `slot`, zero once it has passed.
+    pub fn duration_to(&self, slot: u64, offset: Duration) -> Duration {
+        (self.start_of(slot) + offset).saturating_sub(unix_time())
+    }
+}
+
+fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the unix epoch")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_clock() {
+        let clock = SlotClock::new(1_000);
+        assert_eq!(clock.slot_at(Duration::from_secs(999)), None);
+        assert_eq!(clock.slot_at(Duration::from_secs(1_000)), Some(0));
+        assert_eq!(clock.slot_at(Duration::from_millis(1_023_999)), Some(1));
+        assert_eq!(clock.start_of(2), Duration::from_secs(1_024));
+        assert_eq!(clock.duration_to(0, Duration::ZERO), Duration::ZERO);
+    }
+}
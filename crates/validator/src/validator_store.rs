@@ -0,0 +1,417 @@
+use std::{
+    collections::HashMap,
+    sync::{RwLock, RwLockReadGuard},
+};
+
+use alloy_primitives::{aliases::B32, B256};
+use ream_bls::PrivateKey;
+use ream_builder::types::{
+    compute_builder_domain, SignedValidatorRegistrationV1, ValidatorRegistrationV1,
+};
+use ream_consensus::{
+    attestation_data::AttestationData,
+    beacon_block::{BeaconBlock, SignedBeaconBlock},
+    bls::{BLSPubkey, BLSSignature},
+    constants::{
+        DOMAIN_APPLICATION_BUILDER, DOMAIN_BEACON_ATTESTER, DOMAIN_BEACON_PROPOSER, DOMAIN_RANDAO,
+        DOMAIN_SELECTION_PROOF,
+    },
+    fork_schedule::ForkSchedule,
+    misc::{compute_epoch_at_slot, compute_signing_root},
+};
+
+use crate::{
+    error::ValidatorError,
+    slashing_protection::SlashingDatabase,
+    web3signer::{
+        AggregationSlot, BlockRequest, ForkInfo, RandaoReveal, SigningMessage, SigningRequest,
+        Web3SignerClient,
+    },
+};
+
+/// Where the key of a validator lives.
+#[derive(Clone)]
+pub enum Signer {
+    Local(PrivateKey),
+    Web3Signer(Web3SignerClient),
+}
+
+/// A message to sign, with its domain and what a remote signer is told about it.
+#[derive(Debug, Clone, Copy)]
+enum SignableMessage<'a> {
+    RandaoReveal(u64),
+    SelectionProof(u64),
+    Block(&'a BeaconBlock),
+    AttestationData(&'a AttestationData),
+    ValidatorRegistration(&'a ValidatorRegistrationV1),
+}
+
+impl SignableMessage<'_> {
+    fn domain_type(&self) -> B32 {
+        match self {
+            SignableMessage::RandaoReveal(_) => DOMAIN_RANDAO,
+            SignableMessage::SelectionProof(_) => DOMAIN_SELECTION_PROOF,
+            SignableMessage::Block(_) => DOMAIN_BEACON_PROPOSER,
+            SignableMessage::AttestationData(_) => DOMAIN_BEACON_ATTESTER,
+            SignableMessage::ValidatorRegistration(_) => DOMAIN_APPLICATION_BUILDER,
+        }
+    }
+
+    fn epoch(&self) -> u64 {
+        match self {
+            SignableMessage::RandaoReveal(epoch) => *epoch,
+            SignableMessage::SelectionProof(slot) => compute_epoch_at_slot(*slot),
+            SignableMessage::Block(block) => compute_epoch_at_slot(block.slot),
+            SignableMessage::AttestationData(data) => data.target.epoch,
+            SignableMessage::ValidatorRegistration(_) => 0,
+        }
+    }
+
+    fn signing_root(&self, domain: B256) -> B256 {
+        match self {
+            SignableMessage::RandaoReveal(epoch) => compute_signing_root(epoch, domain),
+            SignableMessage::SelectionProof(slot) => compute_signing_root(slot, domain),
+            SignableMessage::Block(block) => compute_signing_root(*block, domain),
+            SignableMessage::AttestationData(data) => compute_signing_root(*data, domain),
+            SignableMessage::ValidatorRegistration(registration) => {
+                compute_signing_root(*registration, domain)
+            }
+        }
+    }
+
+    fn web3signer_message(&self, fork_schedule: &ForkSchedule) -> SigningMessage {
+        match self {
+            SignableMessage::RandaoReveal(epoch) => {
+                SigningMessage::RandaoReveal(RandaoReveal { epoch: *epoch })
+            }
+            SignableMessage::SelectionProof(slot) => {
+                SigningMessage::AggregationSlot(AggregationSlot { slot: *slot })
+            }
+            SignableMessage::Block(block) => SigningMessage::BeaconBlock(BlockRequest {
+                version: fork_schedule
+                    .fork_at_epoch(self.epoch())
+                    .name
+                    .to_string()
+                    .to_uppercase(),
+                block_header: block.block_header(),
+            }),
+            SignableMessage::AttestationData(data) => SigningMessage::Attestation(**data),
+            SignableMessage::ValidatorRegistration(registration) => {
+                SigningMessage::ValidatorRegistration((*registration).clone())
+            }
+        }
+    }
+}
+
+/// Keys of the validators the client runs, signing their messages for the chain of
+/// `genesis_validators_root`. Keys are held locally or by a Web3Signer, and can be added while
+/// the client runs.
+///
+/// With a slashing protection database, blocks and attestations are only signed once it
+/// recorded them as safe.
+pub struct ValidatorStore {
+    signers: RwLock<HashMap<BLSPubkey, Signer>>,
+    fork_schedule: ForkSchedule,
+    genesis_validators_root: B256,
+    slashing_protection: Option<SlashingDatabase>,
+}
+
+impl ValidatorStore {
+    pub fn new(
+        keys: impl IntoIterator<Item = PrivateKey>,
+        fork_schedule: ForkSchedule,
+        genesis_validators_root: B256,
+    ) -> Self {
+        Self {
+            signers: RwLock::new(
+                keys.into_iter()
+                    .map(|key| (key.public_key(), Signer::Local(key)))
+                    .collect(),
+            ),
+            fork_schedule,
+            genesis_validators_root,
+            slashing_protection: None,
+        }
+    }
+
+    /// Checks blocks and attestations against `db` before signing them, fails if `db` protects
+    /// another chain.
+    pub fn with_slashing_protection(
+        mut self,
+        db: SlashingDatabase,
+    ) -> Result<Self, ValidatorError> {
+        db.check_genesis_validators_root(self.genesis_validators_root)?;
+        self.slashing_protection = Some(db);
+        Ok(self)
+    }
+
+    pub fn pubkeys(&self) -> Vec<BLSPubkey> {
+        self.signers().keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.signers().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers().is_empty()
+    }
+
+    pub fn has_validator(&self, pubkey: &BLSPubkey) -> bool {
+        self.signers().contains_key(pubkey)
+    }
+
+    /// Adds a key, returns whether it is new.
+    pub fn add_key(&self, key: PrivateKey) -> bool {
+        self.add_signer(key.public_key(), Signer::Local(key))
+    }
+
+    pub fn signer(&self, pubkey: &BLSPubkey) -> Option<Signer> {
+        self.signers().get(pubkey).cloned()
+    }
+
+    /// Stops signing for the validator of `pubkey`, returns its signer.
+    pub fn remove_signer(&self, pubkey: &BLSPubkey) -> Option<Signer> {
+        self.signers
+            .write()
+            .expect("validator signers lock poisoned")
+            .remove(pubkey)
+    }
+
+    pub fn slashing_protection(&self) -> Option<&SlashingDatabase> {
+        self.slashing_protection.as_ref()
+    }
+
+    /// Adds a validator signing with `signer`, returns whether it is new.
+    pub fn add_signer(&self, pubkey: BLSPubkey, signer: Signer) -> bool {
+        self.signers
+            .write()
+            .expect("validator signers lock poisoned")
+            .insert(pubkey, signer)
+            .is_none()
+    }
+
+    /// Signature of `epoch` revealing the proposer's randao contribution.
+    pub async fn sign_randao_reveal(
+        &self,
+        pubkey: &BLSPubkey,
+        epoch: u64,
+    ) -> Result<BLSSignature, ValidatorError> {
+        let message = SignableMessage::RandaoReveal(epoch);
+        self.sign(pubkey, message, self.signing_root(&message))
+            .await
+    }
+
+    /// Signature of `slot` deciding whether the validator aggregates its committee's
+    /// attestations.
+    pub async fn sign_selection_proof(
+        &self,
+        pubkey: &BLSPubkey,
+        slot: u64,
+    ) -> Result<BLSSignature, ValidatorError> {
+        let message = SignableMessage::SelectionProof(slot);
+        self.sign(pubkey, message, self.signing_root(&message))
+            .await
+    }
+
+    pub async fn sign_block(
+        &self,
+        pubkey: &BLSPubkey,
+        block: BeaconBlock,
+    ) -> Result<SignedBeaconBlock, ValidatorError> {
+        let message = SignableMessage::Block(&block);
+        let signing_root = self.signing_root(&message);
+        if let Some(db) = &self.slashing_protection {
+            db.check_and_insert_block(pubkey, block.slot, signing_root)?;
+        }
+        let signature = self.sign(pubkey, message, signing_root).await?;
+        Ok(SignedBeaconBlock {
+            message: block,
+            signature,
+        })
+    }
+
+    pub async fn sign_attestation_data(
+        &self,
+        pubkey: &BLSPubkey,
+        data: &AttestationData,
+    ) -> Result<BLSSignature, ValidatorError> {
+        let message = SignableMessage::AttestationData(data);
+        let signing_root = self.signing_root(&message);
+        if let Some(db) = &self.slashing_protection {
+            db.check_and_insert_attestation(
+                pubkey,
+                data.source.epoch,
+                data.target.epoch,
+                signing_root,
+            )?;
+        }
+        self.sign(pubkey, message, signing_root).await
+    }
+
+    /// Signature of the fee recipient and gas limit the validator asks builders for.
+    pub async fn sign_validator_registration(
+        &self,
+        registration: ValidatorRegistrationV1,
+    ) -> Result<SignedValidatorRegistrationV1, ValidatorError> {
+        let message = SignableMessage::ValidatorRegistration(&registration);
+        let signature = self
+            .sign(&registration.pubkey, message, self.signing_root(&message))
+            .await?;
+        Ok(SignedValidatorRegistrationV1 {
+            message: registration,
+            signature,
+        })
+    }
+
+    async fn sign(
+        &self,
+        pubkey: &BLSPubkey,
+        message: SignableMessage<'_>,
+        signing_root: B256,
+    ) -> Result<BLSSignature, ValidatorError> {
+        let signer = self
+            .signer(pubkey)
+            .ok_or(ValidatorError::UnknownValidator(*pubkey))?;
+        match signer {
+            Signer::Local(key) => Ok(key.sign(signing_root.as_slice())),
+            Signer::Web3Signer(client) => {
+                let fork_info = ForkInfo {
+                    fork: self.fork_schedule.fork(message.epoch()),
+                    genesis_validators_root: self.genesis_validators_root,
+                };
+                let request = SigningRequest::new(
+                    message.web3signer_message(&self.fork_schedule),
+                    fork_info,
+                    signing_root,
+                );
+                client.sign(pubkey, &request).await
+            }
+        }
+    }
+
+    fn signing_root(&self, message: &SignableMessage) -> B256 {
+        let domain = match message {
+            // Builders don't follow forks, registrations are valid on every fork and chain.
+            SignableMessage::ValidatorRegistration(_) => {
+                compute_builder_domain(self.fork_schedule.genesis_fork().version)
+            }
+            _ => self.fork_schedule.get_domain(
+                message.domain_type(),
+                message.epoch(),
+                self.genesis_validators_root,
+            ),
+        };
+        message.signing_root(domain)
+    }
+
+    fn signers(&self) -> RwLockReadGuard<'_, HashMap<BLSPubkey, Signer>> {
+        self.signers
+            .read()
+            .expect("validator signers lock poisoned")
+    }
+}
+
+/// Reads hex encoded secret keys, one per line, blank lines and `#` comments ignored.
+pub fn parse_secret_keys(contents: &str) -> Result<Vec<PrivateKey>, ValidatorError> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(number, line)| {
+            let bytes = line.parse::<B256>().map_err(|_| {
+                ValidatorError::InvalidKey(format!("key {} is not 32 hex bytes", number + 1))
+            })?;
+            PrivateKey::from_bytes(bytes.as_slice())
+                .map_err(|err| ValidatorError::InvalidKey(format!("key {}: {err}", number + 1)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ream_bls::verify;
+    use ream_consensus::checkpoint::Checkpoint;
+
+    use super::*;
+    use crate::slashing_protection::{tests::temp_database, SlashingProtectionError};
+
+    #[tokio::test]
+    async fn test_sign_randao_reveal() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::repeat_byte(2));
+        assert_eq!(store.pubkeys(), [pubkey]);
+        assert!(!store.add_key(PrivateKey::key_gen(&[1; 32]).unwrap()));
+        assert!(store.add_key(PrivateKey::key_gen(&[2; 32]).unwrap()));
+        assert_eq!(store.len(), 2);
+
+        let signature = store.sign_randao_reveal(&pubkey, 3).await.unwrap();
+        let domain = ForkSchedule::mainnet().get_domain(DOMAIN_RANDAO, 3, B256::repeat_byte(2));
+        let signing_root = compute_signing_root(&3u64, domain);
+        assert!(verify(&pubkey, signing_root.as_slice(), &signature).unwrap());
+
+        assert!(matches!(
+            store.sign_randao_reveal(&BLSPubkey::ZERO, 3).await,
+            Err(ValidatorError::UnknownValidator(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slashing_protection() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        let (_dir, db) = temp_database();
+        let store = ValidatorStore::new([key], ForkSchedule::mainnet(), B256::ZERO)
+            .with_slashing_protection(db)
+            .unwrap();
+
+        let block = BeaconBlock {
+            slot: 1,
+            ..BeaconBlock::default()
+        };
+        store.sign_block(&pubkey, block.clone()).await.unwrap();
+        store.sign_block(&pubkey, block).await.unwrap();
+        let other_block = BeaconBlock {
+            slot: 1,
+            proposer_index: 1,
+            ..BeaconBlock::default()
+        };
+        assert!(matches!(
+            store.sign_block(&pubkey, other_block).await,
+            Err(ValidatorError::SlashingProtection(
+                SlashingProtectionError::DoubleBlockProposal { slot: 1 }
+            ))
+        ));
+
+        let data = AttestationData {
+            target: Checkpoint {
+                epoch: 1,
+                root: B256::ZERO,
+            },
+            ..AttestationData::default()
+        };
+        store.sign_attestation_data(&pubkey, &data).await.unwrap();
+        let other_data = AttestationData {
+            beacon_block_root: B256::repeat_byte(1),
+            ..data
+        };
+        assert!(matches!(
+            store.sign_attestation_data(&pubkey, &other_data).await,
+            Err(ValidatorError::SlashingProtection(
+                SlashingProtectionError::DoubleVote { target_epoch: 1 }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_secret_keys() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let contents = format!("# devnet keys\n\n{}\n", B256::from(key.to_bytes()));
+        let keys = parse_secret_keys(&contents).unwrap();
+        assert_eq!(keys[0].public_key(), key.public_key());
+
+        assert!(parse_secret_keys("0x1234").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use alloy_primitives::B256;
+use ream_builder::types::ValidatorRegistrationV1;
+use ream_consensus::{
+    attestation_data::AttestationData,
+    beacon_block_header::BeaconBlockHeader,
+    bls::{BLSPubkey, BLSSignature},
+    fork::Fork,
+};
+use reqwest::{header::ACCEPT, Certificate, Response};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::error::ValidatorError;
+
+/// Timeout of signing requests, a signature is worthless once its duty is over.
+pub const SIGNING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The fork a message is signed for, letting the remote signer compute the domain itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ForkInfo {
+    pub fork: Fork,
+    pub genesis_validators_root: B256,
+}
+
+/// `{"epoch": ...}` of randao reveal requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RandaoReveal {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub epoch: u64,
+}
+
+/// `{"slot": ...}` of selection proof requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AggregationSlot {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub slot: u64,
+}
+
+/// Blocks are sent as their header, which is all the signer needs to protect from slashing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockRequest {
+    /// Upper case fork name, such as `DENEB`.
+    pub version: String,
+    pub block_header: BeaconBlockHeader,
+}
+
+/// The message of a signing request, keyed by the name of its field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningMessage {
+    RandaoReveal(RandaoReveal),
+    AggregationSlot(AggregationSlot),
+    BeaconBlock(BlockRequest),
+    Attestation(AttestationData),
+    ValidatorRegistration(ValidatorRegistrationV1),
+}
+
+impl SigningMessage {
+    /// The `type` of the request carrying the message.
+    pub fn request_type(&self) -> &'static str {
+        match self {
+            SigningMessage::RandaoReveal(_) => "RANDAO_REVEAL",
+            SigningMessage::AggregationSlot(_) => "AGGREGATION_SLOT",
+            SigningMessage::BeaconBlock(_) => "BLOCK_V2",
+            SigningMessage::Attestation(_) => "ATTESTATION",
+            SigningMessage::ValidatorRegistration(_) => "VALIDATOR_REGISTRATION",
+        }
+    }
+}
+
+/// Body of `POST /api/v1/eth2/sign/{identifier}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SigningRequest {
+    #[serde(rename = "type")]
+    pub request_type: &'static str,
+    pub fork_info: ForkInfo,
+    #[serde(rename = "signingRoot")]
+    pub signing_root: B256,
+    #[serde(flatten)]
+    pub message: SigningMessage,
+}
+
+impl SigningRequest {
+    pub fn new(message: SigningMessage, fork_info: ForkInfo, signing_root: B256) -> Self {
+        Self {
+            request_type: message.request_type(),
+            fork_info,
+            signing_root,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SignatureResponse {
+    signature: BLSSignature,
+}
+
+/// Client of a Web3Signer compatible remote signer, which keeps the keys off the validator
+/// client's host. The signer checks the messages against its own slashing protection too.
+#[derive(Debug, Clone)]
+pub struct Web3SignerClient {
+    http: reqwest::Client,
+    endpoint: Url,
+}
+
+impl Web3SignerClient {
+    /// A client trusting the PEM certificate `ca_certificate` on top of the system ones, for
+    /// signers serving HTTPS with a self-signed certificate.
+    pub fn new(endpoint: Url, ca_certificate: Option<&[u8]>) -> Result<Self, ValidatorError> {
+        let mut builder = reqwest::Client::builder().timeout(SIGNING_TIMEOUT);
+        if let Some(pem) = ca_certificate {
+            let certificate = Certificate::from_pem(pem).map_err(|err| {
+                ValidatorError::RemoteSigner(format!("invalid CA certificate: {err}"))
+            })?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        let http = builder
+            .build()
+            .map_err(|err| ValidatorError::RemoteSigner(err.to_string()))?;
+        Ok(Self { http, endpoint })
+    }
+
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// `GET /api/v1/eth2/publicKeys`, the keys the signer holds.
+    pub async fn public_keys(&self) -> Result<Vec<BLSPubkey>, ValidatorError> {
+        let response = self
+            .check(
+                self.http
+                    .get(self.url("/api/v1/eth2/publicKeys"))
+                    .send()
+                    .await,
+            )
+            .await?;
+        response
+            .json()
+            .await
+            .map_err(|err| ValidatorError::RemoteSigner(format!("invalid public keys: {err}")))
+    }
+
+    /// `POST /api/v1/eth2/sign/{pubkey}`, the signature of `request` by the key of `pubkey`.
+    pub async fn sign(
+        &self,
+        pubkey: &BLSPubkey,
+        request: &SigningRequest,
+    ) -> Result<BLSSignature, ValidatorError> {
+        let response = self
+            .check(
+                self.http
+                    .post(self.url(&format!("/api/v1/eth2/sign/{pubkey}")))
+                    .header(ACCEPT, "application/json")
+                    .json(request)
+                    .send()
+                    .await,
+            )
+            .await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|err| ValidatorError::RemoteSigner(err.to_string()))?;
+        // Older signers answer with the bare signature whatever the request accepts.
+        serde_json::from_str::<SignatureResponse>(&body)
+            .map(|response| response.signature)
+            .or_else(|_| body.trim().parse())
+            .map_err(|_| ValidatorError::RemoteSigner(format!("invalid signature: {body}")))
+    }
+
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        url.set_path(path);
+        url
+    }
+
+    async fn check(
+        &self,
+        response: Result<Response, reqwest::Error>,
+    ) -> Result<Response, ValidatorError> {
+        let response = response.map_err(|err| {
+            ValidatorError::RemoteSigner(format!("{} unreachable: {err}", self.endpoint))
+        })?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(ValidatorError::RemoteSigner(format!(
+            "{} returned error {status}: {body}",
+            self.endpoint
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::Path,
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use ream_bls::PrivateKey;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A signer holding `key`, signing the root of any request.
+    async fn mock_signer(key: PrivateKey) -> Url {
+        let pubkey = key.public_key();
+        let sign = move |Path(identifier): Path<String>, Json(request): Json<Value>| async move {
+            if identifier != pubkey.to_string() {
+                return Err(StatusCode::NOT_FOUND);
+            }
+            assert_eq!(request["type"], "RANDAO_REVEAL");
+            assert_eq!(request["randao_reveal"]["epoch"], "3");
+            assert_eq!(request["fork_info"]["fork"]["epoch"], "0");
+            let signing_root: B256 = request["signingRoot"].as_str().unwrap().parse().unwrap();
+            Ok(Json(
+                json!({ "signature": key.sign(signing_root.as_slice()) }),
+            ))
+        };
+        let router = Router::new()
+            .route(
+                "/api/v1/eth2/publicKeys",
+                get(move || async move { Json(json!([pubkey])) }),
+            )
+            .route("/api/v1/eth2/sign/{identifier}", post(sign));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{address}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_remote_signing() {
+        let key = PrivateKey::key_gen(&[1; 32]).unwrap();
+        let pubkey = key.public_key();
+        let client = Web3SignerClient::new(mock_signer(key).await, None).unwrap();
+        assert_eq!(client.public_keys().await.unwrap(), [pubkey]);
+
+        let request = SigningRequest::new(
+            SigningMessage::RandaoReveal(RandaoReveal { epoch: 3 }),
+            ForkInfo {
+                fork: Fork::default(),
+                genesis_validators_root: B256::ZERO,
+            },
+            B256::repeat_byte(7),
+        );
+        let signature = client.sign(&pubkey, &request).await.unwrap();
+        assert!(ream_bls::verify(&pubkey, B256::repeat_byte(7).as_slice(), &signature).unwrap());
+
+        assert!(matches!(
+            client.sign(&BLSPubkey::ZERO, &request).await,
+            Err(ValidatorError::RemoteSigner(_))
+        ));
+    }
+}